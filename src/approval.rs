@@ -0,0 +1,132 @@
+//! Scoped `--yes`.
+//!
+//! `--yes` on its own answers every prompt. `--yes=overwrite,orphans`
+//! answers only the named kinds of prompt; the others still ask, or in
+//! non-interactive runs fail or skip as they would without `--yes`. Prompts
+//! answered this way are counted so `aps sync` can report them.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Kinds of confirmation `--yes` can be scoped to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prompt {
+    /// Replacing existing or locally edited content at a destination
+    Overwrite,
+    /// Deleting old destinations left after a `dest` change
+    Orphans,
+    /// Removing entries from the manifest
+    Remove,
+}
+
+impl Prompt {
+    const ALL: [Prompt; 3] = [Prompt::Overwrite, Prompt::Orphans, Prompt::Remove];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Prompt::Overwrite => "overwrite",
+            Prompt::Orphans => "orphans",
+            Prompt::Remove => "remove",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+static AUTO_APPROVED: [AtomicUsize; 3] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+
+/// The prompts `--yes` answers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Approvals {
+    pub overwrite: bool,
+    pub orphans: bool,
+    pub remove: bool,
+}
+
+impl Approvals {
+    /// Plain `--yes`
+    pub const ALL: Approvals = Approvals {
+        overwrite: true,
+        orphans: true,
+        remove: true,
+    };
+
+    /// Parse a `--yes` value: `all`, or a comma-separated list of prompt
+    /// kinds. An empty value (no `--yes`) approves nothing.
+    pub fn parse(value: &str) -> std::result::Result<Self, String> {
+        let mut approvals = Approvals::default();
+        for kind in value.split(',').map(str::trim).filter(|k| !k.is_empty()) {
+            match kind {
+                "all" => approvals = Approvals::ALL,
+                "overwrite" => approvals.overwrite = true,
+                "orphans" | "orphan-cleanup" => approvals.orphans = true,
+                "remove" => approvals.remove = true,
+                other => {
+                    return Err(format!(
+                        "unknown prompt '{}'; expected all, overwrite, orphans, or remove",
+                        other
+                    ))
+                }
+            }
+        }
+        Ok(approvals)
+    }
+
+    /// Whether `--yes` was passed at all
+    pub fn any(&self) -> bool {
+        *self != Approvals::default()
+    }
+
+    pub fn covers(&self, prompt: Prompt) -> bool {
+        match prompt {
+            Prompt::Overwrite => self.overwrite,
+            Prompt::Orphans => self.orphans,
+            Prompt::Remove => self.remove,
+        }
+    }
+
+    /// Answer `prompt` with yes if it is in scope, counting the approval
+    pub fn approve(&self, prompt: Prompt) -> bool {
+        let covered = self.covers(prompt);
+        if covered {
+            AUTO_APPROVED[prompt.index()].fetch_add(1, Ordering::Relaxed);
+        }
+        covered
+    }
+}
+
+/// Prompts answered by `--yes` since the last call, with how many times
+pub fn take_auto_approved() -> Vec<(Prompt, usize)> {
+    Prompt::ALL
+        .into_iter()
+        .map(|prompt| {
+            (
+                prompt,
+                AUTO_APPROVED[prompt.index()].swap(0, Ordering::Relaxed),
+            )
+        })
+        .filter(|(_, count)| *count > 0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_scoped_yes() {
+        assert_eq!(Approvals::parse(""), Ok(Approvals::default()));
+        assert_eq!(Approvals::parse("all"), Ok(Approvals::ALL));
+        let scoped = Approvals::parse("overwrite,orphan-cleanup").unwrap();
+        assert!(scoped.covers(Prompt::Overwrite));
+        assert!(scoped.covers(Prompt::Orphans));
+        assert!(!scoped.covers(Prompt::Remove));
+        assert!(scoped.any());
+        assert!(Approvals::parse("everything").is_err());
+    }
+}
@@ -1,18 +1,156 @@
+//! Backups of content aps is about to overwrite or delete.
+//!
+//! The default `copy` strategy duplicates the file or directory under
+//! `.aps-backups/`. The `store` strategy instead writes each file once into
+//! `.aps-backups/objects/`, keyed by its SHA-256, and records a restore point
+//! listing the files under `.aps-backups/points/`. Identical files across
+//! backups share one object, and `aps backup restore` rebuilds a point from
+//! its listing.
+//!
+//! Backups can be turned off (`backup: false`) or sent to another directory
+//! (`backup_dir`) for the whole manifest or for a single entry.
+
+use crate::checksum::compute_bytes_checksum;
 use crate::error::{ApsError, Result};
-use chrono::Local;
+use crate::manifest::{BackupStrategy, Entry, Manifest};
+use chrono::{Local, Utc};
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use tracing::{debug, info};
+use walkdir::WalkDir;
 
 /// Directory for storing backups
 pub const BACKUP_DIR: &str = ".aps-backups";
 
-/// Create a backup of an existing file or directory
-pub fn create_backup(base_dir: &Path, dest_path: &Path) -> Result<PathBuf> {
-    let backup_root = base_dir.join(BACKUP_DIR);
+/// Content-addressed file objects within the backup directory
+const OBJECTS_DIR: &str = "objects";
+
+/// Restore point listings within the backup directory
+const POINTS_DIR: &str = "points";
+
+/// A backup recorded by the `store` strategy
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RestorePoint {
+    /// Identifier used by `aps backup restore`
+    pub id: String,
+    /// When the backup was taken (RFC 3339)
+    pub created_at: String,
+    /// Backed-up path, relative to the manifest directory when inside it
+    pub path: String,
+    /// Files, symlinks, and directories under `path`, parents first
+    pub items: Vec<StoredItem>,
+}
+
+/// One file, symlink, or directory in a restore point
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct StoredItem {
+    /// Path relative to the backed-up path (empty for the path itself)
+    pub path: String,
+    /// Object holding a file's content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub object: Option<String>,
+    /// Target of a symlink
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    /// Unix permission bits of a file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u32>,
+}
+
+/// Whether, where, and how content is backed up, from the manifest's
+/// settings and an entry's overrides
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupPolicy {
+    pub strategy: BackupStrategy,
+    /// `false` when backups are turned off with `backup: false`
+    pub enabled: bool,
+    /// Backup directory, relative to the manifest directory unless absolute
+    pub dir: PathBuf,
+}
+
+impl Default for BackupPolicy {
+    fn default() -> Self {
+        Self {
+            strategy: BackupStrategy::default(),
+            enabled: true,
+            dir: PathBuf::from(BACKUP_DIR),
+        }
+    }
+}
+
+impl BackupPolicy {
+    /// The manifest-wide policy
+    pub fn for_manifest(manifest: &Manifest) -> Self {
+        Self {
+            strategy: manifest.backup_strategy,
+            enabled: manifest.backup,
+            dir: manifest
+                .backup_dir
+                .as_deref()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| PathBuf::from(BACKUP_DIR)),
+        }
+    }
+
+    /// This policy with an entry's `backup` and `backup_dir` overrides applied
+    pub fn for_entry(&self, entry: &Entry) -> Self {
+        Self {
+            strategy: self.strategy,
+            enabled: entry.backup.unwrap_or(self.enabled),
+            dir: entry
+                .backup_dir
+                .as_deref()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| self.dir.clone()),
+        }
+    }
+
+    /// The backup directory under `base_dir`
+    pub fn root(&self, base_dir: &Path) -> PathBuf {
+        base_dir.join(&self.dir)
+    }
+}
+
+/// Every backup directory a manifest uses: its own and any entry's `backup_dir`
+pub fn backup_roots(manifest: &Manifest, base_dir: &Path) -> Vec<PathBuf> {
+    let policy = BackupPolicy::for_manifest(manifest);
+    let mut roots = vec![policy.root(base_dir)];
+    for entry in &manifest.entries {
+        let root = policy.for_entry(entry).root(base_dir);
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    roots
+}
 
+/// Back up an existing file or directory as `policy` says, returning the copy
+/// or the restore point listing, or `None` when backups are turned off
+pub fn create_backup(
+    base_dir: &Path,
+    dest_path: &Path,
+    policy: &BackupPolicy,
+) -> Result<Option<PathBuf>> {
+    if !policy.enabled {
+        debug!("Backups are turned off; not backing up {:?}", dest_path);
+        return Ok(None);
+    }
+    let backup_root = policy.root(base_dir);
+    let backup_path = match policy.strategy {
+        BackupStrategy::Copy => copy_backup(base_dir, &backup_root, dest_path)?,
+        BackupStrategy::Store => {
+            let point = store_backup(base_dir, &backup_root, dest_path)?;
+            point_path(&backup_root, &point.id)
+        }
+    };
+    Ok(Some(backup_path))
+}
+
+/// Copy an existing file or directory to a timestamped backup path
+fn copy_backup(base_dir: &Path, backup_root: &Path, dest_path: &Path) -> Result<PathBuf> {
     // Create backup directory if it doesn't exist
     if !backup_root.exists() {
-        std::fs::create_dir_all(&backup_root).map_err(|e| {
+        std::fs::create_dir_all(backup_root).map_err(|e| {
             ApsError::io(
                 e,
                 format!("Failed to create backup directory at {:?}", backup_root),
@@ -70,6 +208,247 @@ fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Record a restore point for an existing file or directory under
+/// `backup_root`, storing each file's content once
+pub fn store_backup(base_dir: &Path, backup_root: &Path, dest_path: &Path) -> Result<RestorePoint> {
+    let objects = backup_root.join(OBJECTS_DIR);
+    let points = backup_root.join(POINTS_DIR);
+    std::fs::create_dir_all(&points).map_err(|e| {
+        ApsError::io(
+            e,
+            format!("Failed to create backup directory at {:?}", points),
+        )
+    })?;
+
+    let mut items = Vec::new();
+    for entry in WalkDir::new(dest_path).sort_by_file_name() {
+        let entry =
+            entry.map_err(|e| ApsError::io(e.into(), format!("Failed to read {:?}", dest_path)))?;
+        let relative = entry
+            .path()
+            .strip_prefix(dest_path)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .replace('\\', "/");
+        let file_type = entry.file_type();
+        let item = if file_type.is_symlink() {
+            let target = std::fs::read_link(entry.path())
+                .map_err(|e| ApsError::io(e, format!("Failed to read link {:?}", entry.path())))?;
+            StoredItem {
+                path: relative,
+                object: None,
+                link: Some(target.to_string_lossy().to_string()),
+                mode: None,
+            }
+        } else if file_type.is_dir() {
+            StoredItem {
+                path: relative,
+                object: None,
+                link: None,
+                mode: None,
+            }
+        } else {
+            let content = std::fs::read(entry.path()).map_err(|e| {
+                ApsError::io(e, format!("Failed to backup file {:?}", entry.path()))
+            })?;
+            StoredItem {
+                path: relative,
+                object: Some(write_object(&objects, &content)?),
+                link: None,
+                mode: file_mode(entry.path()),
+            }
+        };
+        items.push(item);
+    }
+
+    let path = dest_path
+        .strip_prefix(base_dir)
+        .unwrap_or(dest_path)
+        .to_string_lossy()
+        .replace('\\', "/");
+    let now = Utc::now();
+    let stem = format!(
+        "{}-{}",
+        now.format("%Y%m%d-%H%M%S"),
+        path.replace(['/', '\\'], "-")
+            .trim_start_matches(['.', '-'])
+    );
+    let mut id = stem.clone();
+    let mut n = 1;
+    while point_path(backup_root, &id).exists() {
+        n += 1;
+        id = format!("{}-{}", stem, n);
+    }
+
+    let point = RestorePoint {
+        id,
+        created_at: now.to_rfc3339(),
+        path,
+        items,
+    };
+    let listing = serde_yaml::to_string(&point).map_err(|e| ApsError::InvalidInput {
+        message: format!("Failed to serialize restore point: {}", e),
+    })?;
+    let listing_path = point_path(backup_root, &point.id);
+    std::fs::write(&listing_path, listing)
+        .map_err(|e| ApsError::io(e, format!("Failed to write {:?}", listing_path)))?;
+    info!("Recorded restore point {} for {:?}", point.id, dest_path);
+    Ok(point)
+}
+
+/// Path of a restore point's listing
+fn point_path(backup_root: &Path, id: &str) -> PathBuf {
+    backup_root.join(POINTS_DIR).join(format!("{}.yaml", id))
+}
+
+/// Write content to the object store unless an identical object exists,
+/// returning its hash
+fn write_object(objects: &Path, content: &[u8]) -> Result<String> {
+    let checksum = compute_bytes_checksum(content);
+    let hash = checksum.trim_start_matches("sha256:").to_string();
+    let object = object_path(objects, &hash);
+    if object.exists() {
+        debug!("Object {} already stored", hash);
+        return Ok(hash);
+    }
+
+    let parent = object.parent().unwrap_or(objects);
+    std::fs::create_dir_all(parent)
+        .map_err(|e| ApsError::io(e, format!("Failed to create {:?}", parent)))?;
+    // Write beside the object and rename, so an interrupted write never
+    // leaves a truncated object behind
+    let partial = object.with_extension("part");
+    std::fs::write(&partial, content)
+        .map_err(|e| ApsError::io(e, format!("Failed to write {:?}", partial)))?;
+    std::fs::rename(&partial, &object)
+        .map_err(|e| ApsError::io(e, format!("Failed to write {:?}", object)))?;
+    Ok(hash)
+}
+
+/// Objects are sharded by the first two hex digits of their hash
+fn object_path(objects: &Path, hash: &str) -> PathBuf {
+    objects.join(&hash[..2]).join(&hash[2..])
+}
+
+#[cfg(unix)]
+fn file_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .ok()
+        .map(|m| m.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn file_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
+/// All restore points under `backup_root`, oldest first
+pub fn list_restore_points(backup_root: &Path) -> Result<Vec<RestorePoint>> {
+    let points_dir = backup_root.join(POINTS_DIR);
+    if !points_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut points = Vec::new();
+    for entry in std::fs::read_dir(&points_dir)
+        .map_err(|e| ApsError::io(e, format!("Failed to read {:?}", points_dir)))?
+    {
+        let path = entry
+            .map_err(|e| ApsError::io(e, "Failed to read directory entry"))?
+            .path();
+        if path.extension().is_some_and(|ext| ext == "yaml") {
+            points.push(read_restore_point(&path)?);
+        }
+    }
+    points.sort_by(|a, b| (&a.created_at, &a.id).cmp(&(&b.created_at, &b.id)));
+    Ok(points)
+}
+
+/// Find a restore point by ID in any of `backup_roots`, returning the root it
+/// was found in
+pub fn find_restore_point(backup_roots: &[PathBuf], id: &str) -> Result<(PathBuf, RestorePoint)> {
+    let root = backup_roots
+        .iter()
+        .find(|root| point_path(root, id).is_file())
+        .ok_or_else(|| ApsError::RestorePointNotFound { id: id.to_string() })?;
+    Ok((root.clone(), load_restore_point(root, id)?))
+}
+
+/// Load a restore point under `backup_root` by ID
+pub fn load_restore_point(backup_root: &Path, id: &str) -> Result<RestorePoint> {
+    let path = point_path(backup_root, id);
+    if !path.is_file() {
+        return Err(ApsError::RestorePointNotFound { id: id.to_string() });
+    }
+    read_restore_point(&path)
+}
+
+fn read_restore_point(path: &Path) -> Result<RestorePoint> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ApsError::io(e, format!("Failed to read {:?}", path)))?;
+    serde_yaml::from_str(&content).map_err(|e| ApsError::InvalidInput {
+        message: format!("Failed to parse restore point {:?}: {}", path, e),
+    })
+}
+
+/// Rebuild a restore point's content at `target`, which must not exist
+pub fn restore_point(backup_root: &Path, point: &RestorePoint, target: &Path) -> Result<()> {
+    let objects = backup_root.join(OBJECTS_DIR);
+    for item in &point.items {
+        let path = if item.path.is_empty() {
+            target.to_path_buf()
+        } else {
+            target.join(&item.path)
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| ApsError::io(e, format!("Failed to create {:?}", parent)))?;
+        }
+
+        if let Some(hash) = &item.object {
+            let object = object_path(&objects, hash);
+            std::fs::copy(&object, &path).map_err(|e| {
+                ApsError::io(e, format!("Failed to restore {:?} from {:?}", path, object))
+            })?;
+            #[cfg(unix)]
+            if let Some(mode) = item.mode {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).map_err(
+                    |e| ApsError::io(e, format!("Failed to set permissions on {:?}", path)),
+                )?;
+            }
+        } else if let Some(link) = &item.link {
+            restore_symlink(Path::new(link), &path)?;
+        } else {
+            std::fs::create_dir_all(&path)
+                .map_err(|e| ApsError::io(e, format!("Failed to create {:?}", path)))?;
+        }
+    }
+    info!("Restored {} to {:?}", point.id, target);
+    Ok(())
+}
+
+#[cfg(unix)]
+fn restore_symlink(link: &Path, path: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(link, path)
+        .map_err(|e| ApsError::io(e, format!("Failed to restore symlink {:?}", path)))
+}
+
+#[cfg(windows)]
+fn restore_symlink(link: &Path, path: &Path) -> Result<()> {
+    let target = path
+        .parent()
+        .map(|p| p.join(link))
+        .unwrap_or_else(|| link.to_path_buf());
+    let result = if target.is_dir() {
+        std::os::windows::fs::symlink_dir(link, path)
+    } else {
+        std::os::windows::fs::symlink_file(link, path)
+    };
+    result.map_err(|e| ApsError::io(e, format!("Failed to restore symlink {:?}", path)))
+}
+
 /// Check if a destination has a conflict
 pub fn has_conflict(dest_path: &Path) -> bool {
     // Check if path exists (including broken symlinks)
@@ -156,4 +535,71 @@ mod tests {
 
         assert!(is_aps_managed_dir(&dir));
     }
+
+    #[test]
+    fn test_entry_overrides_backup_policy() {
+        let manifest: Manifest = serde_yaml::from_str(
+            "backup_dir: .backups\nentries:\n  - id: big\n    kind: agents_md\n    backup: false\n  - id: moved\n    kind: agents_md\n    backup_dir: /tmp/aps-backups\n",
+        )
+        .unwrap();
+        let policy = BackupPolicy::for_manifest(&manifest);
+        assert!(policy.enabled);
+        assert_eq!(policy.dir, PathBuf::from(".backups"));
+
+        let big = policy.for_entry(&manifest.entries[0]);
+        assert!(!big.enabled);
+        assert_eq!(big.dir, PathBuf::from(".backups"));
+
+        let base = Path::new("/project");
+        assert_eq!(
+            backup_roots(&manifest, base),
+            vec![
+                PathBuf::from("/project/.backups"),
+                PathBuf::from("/tmp/aps-backups")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_store_backup_dedupes_and_restores() {
+        let temp = tempdir().unwrap();
+        let base = temp.path();
+        let dest = base.join(".claude/skills");
+        fs::create_dir_all(dest.join("one")).unwrap();
+        fs::create_dir_all(dest.join("two")).unwrap();
+        fs::create_dir_all(dest.join("empty")).unwrap();
+        fs::write(dest.join("one/SKILL.md"), "same").unwrap();
+        fs::write(dest.join("two/SKILL.md"), "same").unwrap();
+
+        let root = base.join(BACKUP_DIR);
+        let first = store_backup(base, &root, &dest).unwrap();
+        let second = store_backup(base, &root, &dest).unwrap();
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.path, ".claude/skills");
+
+        // Two identical files across two backups share one object
+        let objects: Vec<_> = WalkDir::new(root.join(OBJECTS_DIR))
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .collect();
+        assert_eq!(objects.len(), 1);
+
+        let listed = list_restore_points(&root).unwrap();
+        assert_eq!(listed.len(), 2);
+
+        let target = base.join("restored");
+        let point = load_restore_point(&root, &first.id).unwrap();
+        restore_point(&root, &point, &target).unwrap();
+        assert_eq!(
+            fs::read_to_string(target.join("two/SKILL.md")).unwrap(),
+            "same"
+        );
+        assert!(target.join("empty").is_dir());
+
+        assert!(matches!(
+            load_restore_point(&root, "missing"),
+            Err(ApsError::RestorePointNotFound { .. })
+        ));
+    }
 }
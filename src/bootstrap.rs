@@ -0,0 +1,365 @@
+//! Bootstrap script generation for machines without APS installed.
+//!
+//! Renders a standalone POSIX shell script from the manifest and lockfile that
+//! fetches each git source at its locked commit (or copies filesystem sources)
+//! and installs it to the locked destination.
+
+use crate::lockfile::Lockfile;
+use crate::manifest::{Manifest, Source};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Shell helpers shared by every generated script
+const SCRIPT_HELPERS: &str = r#"ROOT="${APS_ROOT:-$(pwd)}"
+WORK="$(mktemp -d)"
+trap 'rm -rf "$WORK"' EXIT
+
+# fetch_git <repo> <commit> <dir>
+fetch_git() {
+    if [ -d "$3" ]; then
+        return 0
+    fi
+    mkdir -p "$3"
+    git -C "$3" init -q
+    git -C "$3" remote add origin "$1"
+    git -C "$3" fetch -q --depth 1 origin "$2" 2>/dev/null || git -C "$3" fetch -q origin
+    git -C "$3" checkout -q "$2"
+}
+
+# install_copy <src> <dest>
+install_copy() {
+    rm -rf "$2"
+    if [ -d "$1" ]; then
+        mkdir -p "$2"
+        cp -R "$1"/. "$2"/
+    else
+        mkdir -p "$(dirname "$2")"
+        cp "$1" "$2"
+    fi
+}
+
+# install_merge <src> <dest>: copy into dest without clearing it
+install_merge() {
+    mkdir -p "$2"
+    cp -R "$1"/. "$2"/
+}
+
+# install_include <src> <dest> <prefix>...: copy top-level items matching prefixes
+install_include() {
+    src="$1"
+    dest="$2"
+    shift 2
+    rm -rf "$dest"
+    mkdir -p "$dest"
+    for prefix in "$@"; do
+        for item in "$src"/"$prefix"*; do
+            if [ -e "$item" ]; then
+                cp -R "$item" "$dest"/
+            fi
+        done
+    done
+}
+
+# install_symlink <src> <dest>
+install_symlink() {
+    if [ -d "$1" ]; then
+        mkdir -p "$2"
+        for item in "$1"/*; do
+            ln -sfn "$item" "$2"/
+        done
+    else
+        mkdir -p "$(dirname "$2")"
+        ln -sfn "$1" "$2"
+    fi
+}
+"#;
+
+/// Render a standalone shell script that materializes the locked assets.
+///
+/// Entries are emitted in manifest order. Entries missing from the lockfile
+/// are skipped with a comment, since there is no locked state to reproduce.
+/// Composite entries embed their installed content from `base_dir`.
+pub fn render_bootstrap_script(
+    manifest: &Manifest,
+    lockfile: &Lockfile,
+    base_dir: &Path,
+) -> String {
+    let mut script = String::new();
+    let _ = writeln!(script, "#!/bin/sh");
+    let _ = writeln!(
+        script,
+        "# Generated by aps {} from the lockfile. Do not edit by hand.",
+        env!("CARGO_PKG_VERSION")
+    );
+    let _ = writeln!(
+        script,
+        "# Run from the project root (or set APS_ROOT) to install assets without aps."
+    );
+    let _ = writeln!(script, "set -eu\n");
+    script.push_str(SCRIPT_HELPERS);
+
+    // One checkout per (repo, commit) pair
+    let mut checkouts: HashMap<(String, String), String> = HashMap::new();
+
+    for entry in &manifest.entries {
+        let _ = writeln!(script, "\n# {}", entry.id);
+
+        let Some(locked) = lockfile.entries.get(&entry.id) else {
+            let _ = writeln!(script, "# skipped: not in lockfile (run `aps sync` first)");
+            continue;
+        };
+        let dest = format!(
+            "\"$ROOT\"/{}",
+            shell_path(locked.dest.trim_start_matches("./"))
+        );
+
+        if entry.is_composite() {
+            match std::fs::read_to_string(base_dir.join(&locked.dest)) {
+                Ok(content) => render_embedded_file(&mut script, &dest, &content),
+                Err(_) => {
+                    let _ = writeln!(script, "# skipped: composite output not found on disk");
+                }
+            }
+            continue;
+        }
+
+        let Some(source) = &entry.source else {
+            let _ = writeln!(script, "# skipped: no source configured");
+            continue;
+        };
+
+        let (src, use_symlink) = match source {
+            Source::Git {
+                repo, r#ref, path, ..
+            } => {
+                let commit = locked.commit.clone().unwrap_or_else(|| r#ref.clone());
+                let next = checkouts.len() + 1;
+                let dir = checkouts
+                    .entry((repo.clone(), commit.clone()))
+                    .or_insert_with(|| format!("repo-{}", next))
+                    .clone();
+                let _ = writeln!(
+                    script,
+                    "fetch_git {} {} \"$WORK\"/{}",
+                    single_quote(repo),
+                    single_quote(&commit),
+                    dir
+                );
+                let src = match path {
+                    Some(p) if !p.is_empty() && p != "." => {
+                        format!("\"$WORK\"/{}/{}", dir, shell_path(p))
+                    }
+                    _ => format!("\"$WORK\"/{}", dir),
+                };
+                (src, false)
+            }
+            Source::Filesystem {
+                root,
+                symlink,
+                path,
+            } => {
+                let full = match path {
+                    Some(p) if !p.is_empty() && p != "." => {
+                        format!("{}/{}", root.trim_end_matches('/'), p)
+                    }
+                    _ => root.clone(),
+                };
+                // Relative roots are resolved against the manifest directory
+                let src = if full.starts_with(['/', '$', '~']) {
+                    shell_path(&full)
+                } else {
+                    format!("\"$ROOT\"/{}", shell_path(full.trim_start_matches("./")))
+                };
+                (src, *symlink)
+            }
+            Source::Http { .. } => {
+                let _ = writeln!(script, "# skipped: http sources need aps");
+                continue;
+            }
+            Source::Aps { .. } => {
+                let _ = writeln!(script, "# skipped: aps sources need aps");
+                continue;
+            }
+            Source::Generated { .. } => {
+                let _ = writeln!(script, "# skipped: generated sources need aps");
+                continue;
+            }
+        };
+
+        let line = if use_symlink {
+            format!("install_symlink {} {}", src, dest)
+        } else if !entry.include.is_empty() {
+            let prefixes: Vec<String> = entry.include.iter().map(|p| single_quote(p)).collect();
+            format!("install_include {} {} {}", src, dest, prefixes.join(" "))
+        } else if entry.kind.merges_into_dest() {
+            format!("install_merge {} {}", src, dest)
+        } else {
+            format!("install_copy {} {}", src, dest)
+        };
+        let _ = writeln!(script, "{}", line);
+    }
+
+    let _ = writeln!(script, "\necho \"Bootstrap complete.\"");
+    script
+}
+
+/// Write a file's content verbatim using a quoted heredoc.
+fn render_embedded_file(script: &mut String, dest: &str, content: &str) {
+    let mut delimiter = "APS_EOF".to_string();
+    while content.lines().any(|l| l == delimiter) {
+        delimiter.push('_');
+    }
+    let _ = writeln!(script, "mkdir -p \"$(dirname {})\"", dest);
+    let _ = writeln!(script, "cat > {} <<'{}'", dest, delimiter);
+    script.push_str(content);
+    if !content.ends_with('\n') {
+        script.push('\n');
+    }
+    let _ = writeln!(script, "{}", delimiter);
+}
+
+/// Quote a string so the shell treats it literally.
+fn single_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Double-quote a path, keeping `$VAR` references (and a leading `~`) expandable
+/// so paths like `$HOME/skills` resolve on the target machine.
+fn shell_path(path: &str) -> String {
+    let (prefix, rest) = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => ("$HOME", rest),
+        _ => ("", path),
+    };
+    let escaped: String = rest
+        .chars()
+        .flat_map(|c| match c {
+            '"' | '\\' | '`' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect();
+    format!("\"{}{}\"", prefix, escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lockfile::LockedEntry;
+    use crate::manifest::{AssetKind, ComposeLimits, Entry};
+
+    fn git_entry(id: &str, path: &str) -> Entry {
+        Entry {
+            id: id.to_string(),
+            kind: AssetKind::AgentSkill,
+            source: Some(Source::Git {
+                repo: "https://github.com/owner/repo.git".to_string(),
+                r#ref: "main".to_string(),
+                shallow: true,
+                path: Some(path.to_string()),
+            }),
+            sources: Vec::new(),
+            dest: None,
+            include: Vec::new(),
+            max_age: None,
+            preserve_permissions: true,
+            preserve_mtime: false,
+            hooks_merge: Default::default(),
+            default_dest_override: None,
+            checksum_ignore: Vec::new(),
+            description: None,
+            owner: None,
+            copy_license: false,
+            also_install_for: Vec::new(),
+            frozen: false,
+            targets: Vec::new(),
+            transform: Vec::new(),
+            suppress_warnings: Vec::new(),
+            backup: None,
+            backup_dir: None,
+            symlink_style: None,
+        }
+    }
+
+    #[test]
+    fn test_render_git_entries_share_checkout() {
+        let manifest = Manifest {
+            entries: vec![
+                git_entry("one", "skills/one"),
+                git_entry("two", "skills/two"),
+            ],
+            integrations: Default::default(),
+            max_age: None,
+            dest_roots: Default::default(),
+            backup_strategy: Default::default(),
+            targets: Vec::new(),
+            check_uncommitted: false,
+            suppress_warnings: Vec::new(),
+            ci: Default::default(),
+            backup: true,
+            backup_dir: None,
+            symlink_style: Default::default(),
+            lockfiles: Default::default(),
+            sort: false,
+            compose_limits: ComposeLimits::default(),
+            github_tarballs: false,
+        };
+        let mut lockfile = Lockfile::new();
+        for id in ["one", "two"] {
+            lockfile.upsert(
+                id.to_string(),
+                LockedEntry::new_git(
+                    "https://github.com/owner/repo.git",
+                    &format!("./.claude/skills/{}/", id),
+                    "main".to_string(),
+                    "abc123".to_string(),
+                    "sha256:x".to_string(),
+                ),
+            );
+        }
+
+        let script = render_bootstrap_script(&manifest, &lockfile, Path::new("."));
+        assert_eq!(
+            script
+                .matches("fetch_git 'https://github.com/owner/repo.git' 'abc123' \"$WORK\"/repo-1")
+                .count(),
+            2
+        );
+        assert!(script.contains(
+            "install_copy \"$WORK\"/repo-1/\"skills/one\" \"$ROOT\"/\".claude/skills/one/\""
+        ));
+        assert!(!script.contains("repo-2"));
+    }
+
+    #[test]
+    fn test_render_skips_unlocked_entries() {
+        let manifest = Manifest {
+            entries: vec![git_entry("one", "skills/one")],
+            integrations: Default::default(),
+            max_age: None,
+            dest_roots: Default::default(),
+            backup_strategy: Default::default(),
+            targets: Vec::new(),
+            check_uncommitted: false,
+            suppress_warnings: Vec::new(),
+            ci: Default::default(),
+            backup: true,
+            backup_dir: None,
+            symlink_style: Default::default(),
+            lockfiles: Default::default(),
+            sort: false,
+            compose_limits: ComposeLimits::default(),
+            github_tarballs: false,
+        };
+        let script = render_bootstrap_script(&manifest, &Lockfile::new(), Path::new("."));
+        assert!(script.contains("# one\n# skipped: not in lockfile"));
+        assert!(!script.contains("fetch_git '"));
+    }
+
+    #[test]
+    fn test_shell_path_keeps_variables_expandable() {
+        assert_eq!(shell_path("$HOME/skills"), "\"$HOME/skills\"");
+        assert_eq!(shell_path("~/skills"), "\"$HOME/skills\"");
+        assert_eq!(shell_path("a\"b"), "\"a\\\"b\"");
+        assert_eq!(single_quote("it's"), "'it'\\''s'");
+    }
+}
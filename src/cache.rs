@@ -0,0 +1,282 @@
+//! Clone cache export and import for offline syncs.
+//!
+//! Locked git commits are kept in bare clones under `.aps-cache/clones/`.
+//! `aps cache export` bundles the clones a lockfile needs into a tar archive
+//! with a SHA-256 per bundle; `aps cache import` verifies and unpacks it on
+//! another machine so `aps sync --offline` can install without the network.
+
+use crate::checksum::compute_bytes_checksum;
+use crate::error::{ApsError, Result};
+use crate::lockfile::{LockedEntry, Lockfile};
+use crate::manifest::Manifest;
+use crate::output::short_id;
+use crate::path_guard::safe_join;
+use crate::sources::{
+    cached_clone_dir, create_bundle, has_commit, import_bundle, update_mirror_clone,
+};
+use crate::staleness::CACHE_DIR;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use tracing::info;
+
+/// Newest cache archive format this build understands
+pub const CACHE_ARCHIVE_VERSION: u32 = 1;
+
+/// Index file stored at the root of a cache archive
+const INDEX_FILE: &str = "aps-cache.yaml";
+
+/// Clone cache directory within the cache directory
+const CLONES_DIR: &str = "clones";
+
+/// Contents of a cache archive
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheIndex {
+    pub version: u32,
+    pub aps_version: String,
+    pub repos: Vec<CachedRepo>,
+}
+
+/// One repository bundled in a cache archive
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CachedRepo {
+    pub url: String,
+    /// Bundle file name within the archive
+    pub bundle: String,
+    /// SHA-256 of the bundle file
+    pub checksum: String,
+    /// Locked commits the bundle must contain
+    pub commits: Vec<String>,
+}
+
+/// The clone cache directory for a project
+pub fn clones_dir(base_dir: &Path) -> PathBuf {
+    base_dir.join(CACHE_DIR).join(CLONES_DIR)
+}
+
+/// Locked commits of the manifest's git entries and composite git sources,
+/// grouped by repository
+fn locked_commits(manifest: &Manifest, lockfile: &Lockfile) -> BTreeMap<String, BTreeSet<String>> {
+    let mut repos: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for entry in &manifest.entries {
+        let Some(locked) = lockfile.entries.get(&entry.id) else {
+            continue;
+        };
+        for (repo, git_ref) in entry
+            .source
+            .iter()
+            .chain(&entry.sources)
+            .filter_map(|s| s.git_info())
+        {
+            let commit = if entry.is_composite() {
+                locked
+                    .source_commits
+                    .get(&LockedEntry::source_commit_key(repo, git_ref))
+            } else {
+                locked.commit.as_ref()
+            };
+            if let Some(commit) = commit {
+                repos
+                    .entry(repo.to_string())
+                    .or_default()
+                    .insert(commit.clone());
+            }
+        }
+    }
+    repos
+}
+
+/// Bundle the cached clones needed by the lockfile into a tar archive at
+/// `out`, fetching any repository whose locked commits are not cached yet
+pub fn export_cache(
+    manifest: &Manifest,
+    lockfile: &Lockfile,
+    base_dir: &Path,
+    out: &Path,
+) -> Result<CacheIndex> {
+    let repos = locked_commits(manifest, lockfile);
+    if repos.is_empty() {
+        return Err(ApsError::InvalidInput {
+            message: "No locked git entries to export; run `aps sync` first".to_string(),
+        });
+    }
+
+    let clones = clones_dir(base_dir);
+    let staging = TempDir::new()
+        .map_err(|e| ApsError::io(e, "Failed to create temp directory for cache export"))?;
+    let mut index = CacheIndex {
+        version: CACHE_ARCHIVE_VERSION,
+        aps_version: env!("CARGO_PKG_VERSION").to_string(),
+        repos: Vec::new(),
+    };
+
+    for (url, commits) in repos {
+        let cached = cached_clone_dir(&clones, &url);
+        let repo_path = if commits.iter().all(|c| has_commit(&cached, c)) {
+            cached
+        } else {
+            update_mirror_clone(&clones, &url)?
+        };
+        if let Some(missing) = commits.iter().find(|c| !has_commit(&repo_path, c)) {
+            return Err(ApsError::GitError {
+                message: format!("Locked commit {} not found in {}", missing, url),
+            });
+        }
+
+        let bundle = format!(
+            "{}.bundle",
+            repo_path.file_name().unwrap_or_default().to_string_lossy()
+        );
+        let bundle_path = staging.path().join(&bundle);
+        create_bundle(&repo_path, &bundle_path)?;
+        let content = std::fs::read(&bundle_path)
+            .map_err(|e| ApsError::io(e, format!("Failed to read {:?}", bundle_path)))?;
+
+        info!("Bundled {} ({} locked commits)", url, commits.len());
+        index.repos.push(CachedRepo {
+            url,
+            bundle,
+            checksum: compute_bytes_checksum(&content),
+            commits: commits.into_iter().collect(),
+        });
+    }
+
+    let index_yaml = serde_yaml::to_string(&index).map_err(|e| ApsError::InvalidInput {
+        message: format!("Failed to serialize cache index: {}", e),
+    })?;
+    std::fs::write(staging.path().join(INDEX_FILE), index_yaml)
+        .map_err(|e| ApsError::io(e, "Failed to write cache index"))?;
+
+    let file = std::fs::File::create(out)
+        .map_err(|e| ApsError::io(e, format!("Failed to create {:?}", out)))?;
+    let mut archive = tar::Builder::new(file);
+    let names = std::iter::once(INDEX_FILE).chain(index.repos.iter().map(|r| r.bundle.as_str()));
+    for name in names {
+        archive
+            .append_path_with_name(staging.path().join(name), name)
+            .map_err(|e| ApsError::io(e, format!("Failed to write {:?}", out)))?;
+    }
+    archive
+        .finish()
+        .map_err(|e| ApsError::io(e, format!("Failed to write {:?}", out)))?;
+
+    Ok(index)
+}
+
+/// Verify a cache archive and load its bundles into the project's clone cache
+pub fn import_cache(archive_path: &Path, base_dir: &Path) -> Result<CacheIndex> {
+    let invalid = |message: String| ApsError::InvalidCacheArchive {
+        path: archive_path.to_path_buf(),
+        message,
+    };
+
+    let file = std::fs::File::open(archive_path)
+        .map_err(|e| ApsError::io(e, format!("Failed to open {:?}", archive_path)))?;
+    let staging = TempDir::new()
+        .map_err(|e| ApsError::io(e, "Failed to create temp directory for cache import"))?;
+    tar::Archive::new(file)
+        .unpack(staging.path())
+        .map_err(|e| invalid(e.to_string()))?;
+
+    let index_content = std::fs::read_to_string(staging.path().join(INDEX_FILE))
+        .map_err(|_| invalid(format!("missing {}", INDEX_FILE)))?;
+    let index: CacheIndex =
+        serde_yaml::from_str(&index_content).map_err(|e| invalid(e.to_string()))?;
+    if index.version > CACHE_ARCHIVE_VERSION {
+        return Err(invalid(format!(
+            "format v{} (written by aps {}) is newer than supported v{}",
+            index.version, index.aps_version, CACHE_ARCHIVE_VERSION
+        )));
+    }
+
+    // Verify everything before touching the cache
+    let mut bundles = Vec::new();
+    for repo in &index.repos {
+        let bundle_path = safe_join(staging.path(), Path::new(&repo.bundle))?;
+        let content = std::fs::read(&bundle_path)
+            .map_err(|_| invalid(format!("missing bundle {}", repo.bundle)))?;
+        let actual = compute_bytes_checksum(&content);
+        if actual != repo.checksum {
+            return Err(ApsError::CacheIntegrity {
+                file: repo.bundle.clone(),
+                expected: repo.checksum.clone(),
+                actual,
+            });
+        }
+        bundles.push(bundle_path);
+    }
+
+    let clones = clones_dir(base_dir);
+    std::fs::create_dir_all(&clones)
+        .map_err(|e| ApsError::io(e, format!("Failed to create {:?}", clones)))?;
+    for (repo, bundle_path) in index.repos.iter().zip(bundles) {
+        let repo_path = cached_clone_dir(&clones, &repo.url);
+        import_bundle(&bundle_path, &repo_path, &repo.url)?;
+        if let Some(missing) = repo.commits.iter().find(|c| !has_commit(&repo_path, c)) {
+            return Err(invalid(format!(
+                "bundle {} lacks locked commit {}",
+                repo.bundle, missing
+            )));
+        }
+        info!("Imported {} into {:?}", repo.url, repo_path);
+    }
+
+    Ok(index)
+}
+
+/// The cached clone to install a locked commit from when syncing offline
+pub fn offline_clone(base_dir: &Path, id: &str, url: &str, commit: &str) -> Result<PathBuf> {
+    let repo_path = cached_clone_dir(&clones_dir(base_dir), url);
+    if !has_commit(&repo_path, commit) {
+        return Err(ApsError::OfflineUnavailable {
+            id: id.to_string(),
+            reason: format!(
+                "commit {} of {} is not in the clone cache",
+                short_id(commit),
+                url
+            ),
+        });
+    }
+    Ok(repo_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_rejects_bundle_with_wrong_checksum() {
+        let dir = TempDir::new().unwrap();
+        let index = CacheIndex {
+            version: CACHE_ARCHIVE_VERSION,
+            aps_version: "0.0.0".to_string(),
+            repos: vec![CachedRepo {
+                url: "https://example.com/skills.git".to_string(),
+                bundle: "skills.bundle".to_string(),
+                checksum: compute_bytes_checksum(b"original"),
+                commits: Vec::new(),
+            }],
+        };
+        std::fs::write(
+            dir.path().join(INDEX_FILE),
+            serde_yaml::to_string(&index).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("skills.bundle"), "tampered").unwrap();
+
+        let archive_path = dir.path().join("cache.tar");
+        let mut archive = tar::Builder::new(std::fs::File::create(&archive_path).unwrap());
+        for name in [INDEX_FILE, "skills.bundle"] {
+            archive
+                .append_path_with_name(dir.path().join(name), name)
+                .unwrap();
+        }
+        archive.finish().unwrap();
+
+        let project = TempDir::new().unwrap();
+        let err = import_cache(&archive_path, project.path()).unwrap_err();
+        assert!(matches!(err, ApsError::CacheIntegrity { .. }));
+        assert!(!clones_dir(project.path()).exists());
+    }
+}
@@ -7,18 +7,25 @@
 //! - cursor_hooks: One entry per hook script
 //! - cursor_skills_root: One entry per skill folder
 //! - agent_skill: One entry per skill folder
+//! - persona: One entry per persona file
 
+use crate::discover::{extract_frontmatter_field, find_skill_md};
 use crate::error::{ApsError, Result};
+use crate::license::detect_license;
 use crate::manifest::{AssetKind, Entry, Manifest};
+use crate::persona::{is_persona_file, PersonaInfo};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
+use walkdir::WalkDir;
 
 /// Default catalog filename
 pub const CATALOG_FILENAME: &str = "aps.catalog.yaml";
 
 /// The catalog structure containing all enumerated assets
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct Catalog {
     /// Version of the catalog format
     #[serde(default = "default_version")]
@@ -43,7 +50,7 @@ impl Default for Catalog {
 }
 
 /// A single entry in the catalog representing an individual asset
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct CatalogEntry {
     /// Unique identifier for this catalog entry (derived from manifest entry id + asset name)
     pub id: String,
@@ -57,9 +64,96 @@ pub struct CatalogEntry {
     /// Destination path where this asset will be installed
     pub destination: String,
 
-    /// Short description extracted from the asset file (if available)
+    /// Short description: the manifest entry's `description`, or else one
+    /// extracted from the asset file (if available)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub short_description: Option<String>,
+
+    /// Maintainer from the manifest entry's `owner`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+
+    /// SPDX identifier guessed from the upstream license file, or `unknown`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+
+    /// Quality metadata, present when generated with `--with-health`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<Health>,
+}
+
+/// Computed quality metadata for a catalog entry
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+pub struct Health {
+    /// Whether the entry has a short description
+    pub has_description: bool,
+
+    /// Whether SKILL.md exists with a `name` and `description` in its
+    /// frontmatter (skill kinds only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skill_md_valid: Option<bool>,
+
+    /// Days since the upstream commit (git sources only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream_commit_age_days: Option<i64>,
+
+    /// Installed size of the asset in bytes
+    pub install_size: u64,
+
+    /// Score from 0 to 100; each failed check deducts points
+    pub score: u8,
+}
+
+/// Scores below this are reported by `catalog generate --with-health`
+pub const HEALTH_WARN_SCORE: u8 = 70;
+
+/// Upstream commits older than this many days count against an entry's score
+const STALE_UPSTREAM_DAYS: i64 = 365;
+
+/// Assets larger than this count against an entry's score
+const LARGE_ASSET_BYTES: u64 = 1024 * 1024;
+
+impl Health {
+    /// Check an asset at `asset_path`, as described by its catalog entry
+    fn compute(
+        catalog_entry: &CatalogEntry,
+        asset_path: &Path,
+        commit_date: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let has_description = catalog_entry.short_description.is_some();
+        let skill_md_valid = matches!(
+            catalog_entry.kind,
+            AssetKind::AgentSkill | AssetKind::CursorSkillsRoot
+        )
+        .then(|| skill_md_is_valid(asset_path));
+        let upstream_commit_age_days = commit_date
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+            .map(|date| (now - date.with_timezone(&Utc)).num_days());
+        let install_size = asset_size(asset_path);
+
+        let mut score: u8 = 100;
+        if !has_description {
+            score -= 40;
+        }
+        if skill_md_valid == Some(false) {
+            score -= 30;
+        }
+        if upstream_commit_age_days.is_some_and(|days| days > STALE_UPSTREAM_DAYS) {
+            score -= 20;
+        }
+        if install_size > LARGE_ASSET_BYTES {
+            score -= 10;
+        }
+
+        Self {
+            has_description,
+            skill_md_valid,
+            upstream_commit_age_days,
+            install_size,
+            score,
+        }
+    }
 }
 
 impl Catalog {
@@ -108,12 +202,17 @@ impl Catalog {
         Ok(())
     }
 
-    /// Generate a catalog from a manifest by enumerating all individual assets
-    pub fn generate_from_manifest(manifest: &Manifest, manifest_dir: &Path) -> Result<Self> {
+    /// Generate a catalog from a manifest by enumerating all individual assets,
+    /// optionally computing each asset's health
+    pub fn generate_from_manifest(
+        manifest: &Manifest,
+        manifest_dir: &Path,
+        with_health: bool,
+    ) -> Result<Self> {
         let mut catalog = Catalog::new();
 
         for entry in &manifest.entries {
-            let entries = enumerate_entry_assets(entry, manifest_dir)?;
+            let entries = enumerate_entry_assets(entry, manifest_dir, with_health)?;
             catalog.entries.extend(entries);
         }
 
@@ -128,7 +227,11 @@ impl Catalog {
 }
 
 /// Enumerate all individual assets from a manifest entry
-fn enumerate_entry_assets(entry: &Entry, manifest_dir: &Path) -> Result<Vec<CatalogEntry>> {
+pub fn enumerate_entry_assets(
+    entry: &Entry,
+    manifest_dir: &Path,
+    with_health: bool,
+) -> Result<Vec<CatalogEntry>> {
     let base_dest = entry.destination();
     let mut catalog_entries = Vec::new();
 
@@ -141,6 +244,9 @@ fn enumerate_entry_assets(entry: &Entry, manifest_dir: &Path) -> Result<Vec<Cata
             kind: AssetKind::CompositeAgentsMd,
             destination: format!("./{}", base_dest.display()),
             short_description: Some(format!("Composed from {} sources", entry.sources.len())),
+            owner: None,
+            license: None,
+            health: None,
         });
         return Ok(catalog_entries);
     }
@@ -157,11 +263,11 @@ fn enumerate_entry_assets(entry: &Entry, manifest_dir: &Path) -> Result<Vec<Cata
     let resolved = adapter.resolve(manifest_dir)?;
 
     if !resolved.source_path.exists() {
-        return Err(ApsError::SourcePathNotFound {
-            path: resolved.source_path,
-        });
+        return Err(resolved.path_not_found());
     }
 
+    let license = detect_license(&resolved.source_path, resolved.root.as_deref()).map(|l| l.spdx);
+
     match entry.kind {
         AssetKind::AgentsMd => {
             // Single file - create one entry
@@ -179,6 +285,9 @@ fn enumerate_entry_assets(entry: &Entry, manifest_dir: &Path) -> Result<Vec<Cata
                 kind: AssetKind::AgentsMd,
                 destination: format!("./{}", base_dest.display()),
                 short_description,
+                owner: None,
+                license: None,
+                health: None,
             });
         }
         AssetKind::CompositeAgentsMd => {
@@ -189,6 +298,9 @@ fn enumerate_entry_assets(entry: &Entry, manifest_dir: &Path) -> Result<Vec<Cata
                 kind: AssetKind::CompositeAgentsMd,
                 destination: format!("./{}", base_dest.display()),
                 short_description: None,
+                owner: None,
+                license: None,
+                health: None,
             });
         }
         AssetKind::CursorRules => {
@@ -213,6 +325,9 @@ fn enumerate_entry_assets(entry: &Entry, manifest_dir: &Path) -> Result<Vec<Cata
                     kind: AssetKind::CursorRules,
                     destination: format!("./{}", dest_path.display()),
                     short_description,
+                    owner: None,
+                    license: None,
+                    health: None,
                 });
             }
         }
@@ -239,6 +354,9 @@ fn enumerate_entry_assets(entry: &Entry, manifest_dir: &Path) -> Result<Vec<Cata
                     kind: entry.kind.clone(),
                     destination: format!("./{}", dest_path.display()),
                     short_description: None,
+                    owner: None,
+                    license: None,
+                    health: None,
                 });
             }
         }
@@ -264,6 +382,33 @@ fn enumerate_entry_assets(entry: &Entry, manifest_dir: &Path) -> Result<Vec<Cata
                     kind: AssetKind::CursorSkillsRoot,
                     destination: format!("./{}", dest_path.display()),
                     short_description,
+                    owner: None,
+                    license: None,
+                    health: None,
+                });
+            }
+        }
+        AssetKind::Persona => {
+            // Enumerate each persona file, named by its frontmatter
+            let files = enumerate_files_recursive(&resolved.source_path, &entry.include)?;
+            for file_path in files.into_iter().filter(|p| is_persona_file(p)) {
+                let relative_path = file_path
+                    .strip_prefix(&resolved.source_path)
+                    .unwrap_or(&file_path)
+                    .to_path_buf();
+                let file_name = relative_path.to_string_lossy().replace('\\', "/");
+                let persona = PersonaInfo::read(&file_path);
+                let dest_path = base_dest.join(&relative_path);
+
+                catalog_entries.push(CatalogEntry {
+                    id: format!("{}:{}", entry.id, file_name),
+                    name: persona.name.unwrap_or(file_name),
+                    kind: AssetKind::Persona,
+                    destination: format!("./{}", dest_path.display()),
+                    short_description: persona.description,
+                    owner: None,
+                    license: None,
+                    health: None,
                 });
             }
         }
@@ -289,11 +434,51 @@ fn enumerate_entry_assets(entry: &Entry, manifest_dir: &Path) -> Result<Vec<Cata
                     kind: AssetKind::AgentSkill,
                     destination: format!("./{}", dest_path.display()),
                     short_description,
+                    owner: None,
+                    license: None,
+                    health: None,
                 });
             }
         }
     }
 
+    // Manifest metadata takes precedence over anything extracted from files
+    for catalog_entry in &mut catalog_entries {
+        if entry.description.is_some() {
+            catalog_entry.short_description = entry.description.clone();
+        }
+        catalog_entry.owner = entry.owner.clone();
+        catalog_entry.license = license.clone();
+    }
+
+    if with_health {
+        let commit_date = resolved
+            .git_info
+            .as_ref()
+            .and_then(|info| info.commit_date.as_deref());
+        let now = Utc::now();
+        let base = format!("./{}", base_dest.display());
+        for catalog_entry in &mut catalog_entries {
+            // Destinations mirror the source layout below the entry's dest
+            let relative = catalog_entry
+                .destination
+                .strip_prefix(&base)
+                .map(|rest| rest.trim_start_matches('/'))
+                .unwrap_or_default();
+            let asset_path = if relative.is_empty() {
+                resolved.source_path.clone()
+            } else {
+                resolved.source_path.join(relative)
+            };
+            catalog_entry.health = Some(Health::compute(
+                catalog_entry,
+                &asset_path,
+                commit_date,
+                now,
+            ));
+        }
+    }
+
     Ok(catalog_entries)
 }
 
@@ -338,19 +523,18 @@ fn extract_cursor_skill_description(folder_path: &Path) -> Option<String> {
         return Some(desc);
     }
 
-    extract_first_paragraph(&content)
+    extract_first_paragraph(&strip_frontmatter(&content))
 }
 
 /// Extract a short description from an agent skill folder (SKILL.md or README.md)
-fn extract_agent_skill_description(folder_path: &Path) -> Option<String> {
+pub fn extract_agent_skill_description(folder_path: &Path) -> Option<String> {
     // Try SKILL.md first
-    let skill_md = folder_path.join("SKILL.md");
-    if skill_md.exists() {
+    if let Some(skill_md) = find_skill_md(folder_path) {
         if let Ok(content) = std::fs::read_to_string(&skill_md) {
             if let Some(desc) = extract_frontmatter_description(&content) {
                 return Some(desc);
             }
-            if let Some(desc) = extract_first_paragraph(&content) {
+            if let Some(desc) = extract_first_paragraph(&strip_frontmatter(&content)) {
                 return Some(desc);
             }
         }
@@ -367,6 +551,44 @@ fn extract_agent_skill_description(folder_path: &Path) -> Option<String> {
     None
 }
 
+/// Whether a skill folder has a SKILL.md whose frontmatter names and describes it
+fn skill_md_is_valid(folder_path: &Path) -> bool {
+    find_skill_md(folder_path)
+        .and_then(|skill_md| std::fs::read_to_string(skill_md).ok())
+        .map(|content| {
+            extract_frontmatter_field(&content, "name").is_some()
+                && extract_frontmatter_field(&content, "description").is_some()
+        })
+        .unwrap_or(false)
+}
+
+/// Total size in bytes of a file, or of the files in a directory (excluding `.git`)
+pub fn asset_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// A byte count for display, such as `512 B` or `1.5 KiB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
 /// Extract description from YAML frontmatter
 fn extract_frontmatter_description(content: &str) -> Option<String> {
     // Check if content starts with frontmatter delimiter
@@ -760,4 +982,61 @@ This is the second paragraph.
         assert!(truncated.ends_with("..."));
         assert!(truncated.len() <= 30);
     }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024), "3.0 MiB");
+    }
+
+    #[test]
+    fn test_health_scores_skill_quality() {
+        let temp_dir = TempDir::new().unwrap();
+        let skill = temp_dir.path().join("refactor");
+        std::fs::create_dir(&skill).unwrap();
+        std::fs::write(skill.join("SKILL.md"), "# Refactor\n\nNo frontmatter.\n").unwrap();
+
+        let catalog_entry = CatalogEntry {
+            id: "skills:refactor".to_string(),
+            name: "refactor".to_string(),
+            kind: AssetKind::AgentSkill,
+            destination: "./.claude/skills/refactor".to_string(),
+            short_description: None,
+            owner: None,
+            license: None,
+            health: None,
+        };
+        let now = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let health = Health::compute(
+            &catalog_entry,
+            &skill,
+            Some("2024-06-01T00:00:00+00:00"),
+            now,
+        );
+        assert!(!health.has_description);
+        assert_eq!(health.skill_md_valid, Some(false));
+        assert_eq!(health.upstream_commit_age_days, Some(579));
+        assert_eq!(health.install_size, 28);
+        assert_eq!(health.score, 10);
+
+        // SKILL.md is found in any case
+        std::fs::remove_file(skill.join("SKILL.md")).unwrap();
+        std::fs::write(
+            skill.join("skill.md"),
+            "---\nname: refactor\ndescription: Refactors code\n---\n",
+        )
+        .unwrap();
+        let described = CatalogEntry {
+            short_description: Some("Refactors code".to_string()),
+            ..catalog_entry
+        };
+        let health = Health::compute(&described, &skill, None, now);
+        assert_eq!(health.skill_md_valid, Some(true));
+        assert_eq!(health.upstream_commit_age_days, None);
+        assert_eq!(health.score, 100);
+    }
 }
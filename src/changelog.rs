@@ -0,0 +1,190 @@
+//! Upstream changelog for git-sourced entries (`aps log`).
+//!
+//! History comes from a blobless clone cached in `.aps-cache/history/`, so
+//! repeated queries only fetch new commits.
+
+use crate::color::Style;
+use crate::error::{ApsError, Result};
+use crate::lockfile::LockedEntry;
+use crate::manifest::Entry;
+use crate::output::{say, short_id};
+use crate::sources::{log_commits, update_history_clone, CommitSummary};
+use crate::staleness::CACHE_DIR;
+use chrono::{DateTime, NaiveDate};
+use std::path::Path;
+
+/// History clone directory within the cache directory
+const HISTORY_DIR: &str = "history";
+
+/// Where an `aps log` range starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Since {
+    /// A date (`YYYY-MM-DD` or RFC 3339)
+    Date(String),
+    /// A commit, tag, or branch
+    Rev(String),
+}
+
+impl Since {
+    /// Interpret `--since`: dates are recognized, anything else is a git revision
+    pub fn parse(value: &str) -> Self {
+        let is_date = NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+            || DateTime::parse_from_rfc3339(value).is_ok();
+        if is_date {
+            Self::Date(value.to_string())
+        } else {
+            Self::Rev(value.to_string())
+        }
+    }
+}
+
+/// Upstream commits affecting an entry's source path
+#[derive(Debug)]
+pub struct Changelog {
+    pub id: String,
+    pub repo: String,
+    pub path: Option<String>,
+    /// Ref the range ends at
+    pub head: String,
+    /// Human-readable start of the range
+    pub since: String,
+    pub commits: Vec<CommitSummary>,
+}
+
+impl Changelog {
+    /// Collect commits since `since`, or since the locked commit when omitted
+    pub fn build(
+        entry: &Entry,
+        locked: Option<&LockedEntry>,
+        since: Option<Since>,
+        base_dir: &Path,
+    ) -> Result<Self> {
+        let not_git = || ApsError::InvalidInput {
+            message: format!(
+                "Entry '{}' is not git-sourced; aps log only supports git sources",
+                entry.id
+            ),
+        };
+        let source = entry.source.as_ref().ok_or_else(not_git)?;
+        let (repo, git_ref) = source.git_info().ok_or_else(not_git)?;
+        let path = source
+            .git_path()
+            .filter(|p| *p != ".")
+            .map(|p| p.to_string());
+
+        // `auto` follows the branch the entry was locked to, else the remote default
+        let head = if git_ref == "auto" {
+            locked
+                .and_then(|l| l.resolved_ref.clone())
+                .unwrap_or_else(|| "HEAD".to_string())
+        } else {
+            git_ref.to_string()
+        };
+
+        let since = match since {
+            Some(since) => since,
+            None => Since::Rev(locked.and_then(|l| l.commit.clone()).ok_or_else(|| {
+                ApsError::InvalidInput {
+                    message: format!(
+                        "Entry '{}' has no locked commit; pass --since <ref|date>",
+                        entry.id
+                    ),
+                }
+            })?),
+        };
+
+        let cache_dir = base_dir.join(CACHE_DIR).join(HISTORY_DIR);
+        let repo_path = update_history_clone(&cache_dir, repo)?;
+        let (from, date, label) = match &since {
+            Since::Rev(rev) => (Some(rev.as_str()), None, short_rev(rev)),
+            Since::Date(date) => (None, Some(date.as_str()), date.clone()),
+        };
+        let commits = log_commits(&repo_path, &head, from, date, path.as_deref())?;
+
+        Ok(Self {
+            id: entry.id.clone(),
+            repo: repo.to_string(),
+            path,
+            head,
+            since: label,
+            commits,
+        })
+    }
+}
+
+/// Abbreviate full commit SHAs, leaving branch and tag names alone
+fn short_rev(rev: &str) -> String {
+    if rev.len() == 40 && rev.chars().all(|c| c.is_ascii_hexdigit()) {
+        short_id(rev)
+    } else {
+        rev.to_string()
+    }
+}
+
+/// Print a changelog as one line per commit, newest first
+pub fn print_changelog(log: &Changelog) {
+    let dim = Style::new().dim();
+    let location = match &log.path {
+        Some(path) => format!("{} ({})", log.repo, path),
+        None => log.repo.clone(),
+    };
+    say!(
+        "{} since {}",
+        Style::new().bold().apply_to(&log.id),
+        log.since
+    );
+    say!("{}", dim.apply_to(format!("{} @ {}", location, log.head)));
+    say!();
+
+    if log.commits.is_empty() {
+        say!("No upstream changes.");
+        return;
+    }
+
+    for commit in &log.commits {
+        say!(
+            "  {} {} {} {}",
+            Style::new().yellow().apply_to(short_id(&commit.sha)),
+            dim.apply_to(commit.date.get(..10).unwrap_or(&commit.date)),
+            commit.subject,
+            dim.apply_to(format!("({})", commit.author)),
+        );
+    }
+
+    let noun = if log.commits.len() == 1 {
+        "commit"
+    } else {
+        "commits"
+    };
+    say!(
+        "\n{} {}. Run `aps sync --upgrade` to pull them in.",
+        log.commits.len(),
+        noun
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_since_parses_dates_and_revisions() {
+        assert_eq!(
+            Since::parse("2026-01-31"),
+            Since::Date("2026-01-31".to_string())
+        );
+        assert_eq!(
+            Since::parse("2026-01-31T12:00:00Z"),
+            Since::Date("2026-01-31T12:00:00Z".to_string())
+        );
+        assert_eq!(Since::parse("v1.2.0"), Since::Rev("v1.2.0".to_string()));
+        assert_eq!(Since::parse("abc1234"), Since::Rev("abc1234".to_string()));
+    }
+
+    #[test]
+    fn test_short_rev_only_abbreviates_full_shas() {
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        assert_eq!(short_rev(sha), "01234567");
+        assert_eq!(short_rev("main"), "main");
+    }
+}
@@ -1,13 +1,36 @@
 use crate::error::{ApsError, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use sha2::{Digest, Sha256};
 use std::path::Path;
 use walkdir::WalkDir;
 
-/// Compute a deterministic SHA256 checksum for a file or directory
-pub fn compute_checksum(path: &Path) -> Result<String> {
+/// Build a matcher for an entry's `checksum_ignore` globs
+pub fn build_ignore_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| ApsError::InvalidChecksumIgnore {
+            pattern: pattern.clone(),
+            message: e.kind().to_string(),
+        })?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| ApsError::InvalidChecksumIgnore {
+            pattern: patterns.join(", "),
+            message: e.to_string(),
+        })
+}
+
+/// Compute a deterministic SHA256 checksum for a file or directory.
+///
+/// Files whose path (relative to `path`, or the file name for a single file)
+/// matches `ignore` are left out.
+fn compute_checksum(path: &Path, ignore: &GlobSet) -> Result<String> {
     let mut hasher = Sha256::new();
 
-    if path.is_file() {
+    let ignored_file = path.file_name().is_some_and(|name| ignore.is_match(name));
+    if path.is_file() && !ignored_file {
         let content = std::fs::read(path).map_err(|e| {
             ApsError::io(e, format!("Failed to read file for checksum: {:?}", path))
         })?;
@@ -23,6 +46,7 @@ pub fn compute_checksum(path: &Path) -> Result<String> {
                 !e.path().components().any(|c| c.as_os_str() == ".git")
             })
             .filter(|e| e.file_type().is_file())
+            .filter(|e| !ignore.is_match(e.path().strip_prefix(path).unwrap_or(e.path())))
             .map(|e| e.path().to_path_buf())
             .collect();
 
@@ -52,15 +76,63 @@ pub fn compute_checksum(path: &Path) -> Result<String> {
     Ok(format!("sha256:{}", hex::encode(result)))
 }
 
-/// Compute checksum for source content (before copying)
-pub fn compute_source_checksum(source_path: &Path) -> Result<String> {
-    compute_checksum(source_path)
+/// Compute checksum for source content (before copying), skipping files
+/// matched by the entry's `checksum_ignore` globs
+pub fn compute_source_checksum(source_path: &Path, checksum_ignore: &[String]) -> Result<String> {
+    compute_checksum(source_path, &build_ignore_set(checksum_ignore)?)
 }
 
 /// Compute checksum for string content (for composed files)
 pub fn compute_string_checksum(content: &str) -> String {
+    compute_bytes_checksum(content.as_bytes())
+}
+
+/// Compute checksum for raw bytes (for archived cache bundles)
+pub fn compute_bytes_checksum(content: &[u8]) -> String {
     let mut hasher = Sha256::new();
-    hasher.update(content.as_bytes());
+    hasher.update(content);
     let result = hasher.finalize();
     format!("sha256:{}", hex::encode(result))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_checksum_ignore_skips_volatile_files() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("SKILL.md"), "# Skill").unwrap();
+        std::fs::create_dir(dir.path().join("build")).unwrap();
+        std::fs::write(dir.path().join("build/id.txt"), "1").unwrap();
+        std::fs::write(dir.path().join("stamp.timestamp"), "1").unwrap();
+
+        let ignore = vec!["build/**".to_string(), "*.timestamp".to_string()];
+        let before = compute_source_checksum(dir.path(), &ignore).unwrap();
+        let unfiltered = compute_source_checksum(dir.path(), &[]).unwrap();
+
+        std::fs::write(dir.path().join("build/id.txt"), "2").unwrap();
+        std::fs::write(dir.path().join("stamp.timestamp"), "2").unwrap();
+        assert_eq!(
+            compute_source_checksum(dir.path(), &ignore).unwrap(),
+            before
+        );
+        assert_ne!(
+            compute_source_checksum(dir.path(), &[]).unwrap(),
+            unfiltered
+        );
+
+        std::fs::write(dir.path().join("SKILL.md"), "# Changed").unwrap();
+        assert_ne!(
+            compute_source_checksum(dir.path(), &ignore).unwrap(),
+            before
+        );
+    }
+
+    #[test]
+    fn test_invalid_checksum_ignore_glob_is_rejected() {
+        let err = build_ignore_set(&["[unclosed".to_string()]).unwrap_err();
+        assert!(matches!(err, ApsError::InvalidChecksumIgnore { .. }));
+    }
+}
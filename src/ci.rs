@@ -0,0 +1,63 @@
+//! CI profile.
+//!
+//! When the `CI` environment variable is set, as GitHub Actions, GitLab CI,
+//! and most other CI services do, aps behaves as if `--yes` were passed,
+//! installs copies instead of symlinks (unless the manifest sets
+//! `ci.symlink: true`), never prompts, and prints output without colors.
+//! `APS_CI_PROFILE=0` turns the profile off in CI, and `APS_CI_PROFILE=1`
+//! turns it on anywhere else.
+
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Environment variable that forces the CI profile on or off
+const PROFILE_ENV: &str = "APS_CI_PROFILE";
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Detect the CI profile from the environment, returning whether it is active
+pub fn init() -> bool {
+    let ci = std::env::var("CI").ok();
+    let setting = std::env::var(PROFILE_ENV).ok();
+    let active = profile_enabled(ci.as_deref(), setting.as_deref());
+    ACTIVE.store(active, Ordering::Relaxed);
+    active
+}
+
+/// Whether the CI profile is active
+pub fn active() -> bool {
+    ACTIVE.load(Ordering::Relaxed)
+}
+
+/// Whether aps may prompt: stdin is a terminal and the CI profile is off
+pub fn interactive() -> bool {
+    !active() && std::io::stdin().is_terminal()
+}
+
+fn truthy(value: &str) -> bool {
+    !value.is_empty() && value != "0" && !value.eq_ignore_ascii_case("false")
+}
+
+/// `APS_CI_PROFILE` wins when set; otherwise a truthy `CI` enables the profile
+fn profile_enabled(ci: Option<&str>, setting: Option<&str>) -> bool {
+    match setting.filter(|v| !v.is_empty()) {
+        Some(setting) => truthy(setting),
+        None => ci.is_some_and(truthy),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_detection() {
+        assert!(!profile_enabled(None, None));
+        assert!(profile_enabled(Some("true"), None));
+        assert!(profile_enabled(Some("1"), None));
+        assert!(!profile_enabled(Some("false"), None));
+        assert!(!profile_enabled(Some(""), None));
+        assert!(!profile_enabled(Some("true"), Some("0")));
+        assert!(profile_enabled(None, Some("1")));
+    }
+}
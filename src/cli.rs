@@ -1,3 +1,4 @@
+use crate::approval::Approvals;
 use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
@@ -16,6 +17,23 @@ pub struct Cli {
     /// Enable verbose logging output
     #[arg(short, long, global = true)]
     pub verbose: bool,
+
+    /// Disable colored output (also honors NO_COLOR and CLICOLOR_FORCE)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Suppress all non-error output
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Print commit SHAs and checksums in full instead of their first 8 digits
+    #[arg(long, global = true)]
+    pub full_ids: bool,
+
+    /// Lockfile to read and write: a path, or a profile named in the
+    /// manifest's `lockfiles` (e.g. `ci`)
+    #[arg(long, global = true, value_name = "PATH|PROFILE")]
+    pub lockfile: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -26,20 +44,103 @@ pub enum Commands {
     /// Add a skill from a GitHub URL or local path to the manifest
     Add(AddArgs),
 
+    /// Remove entries from the manifest, lockfile, and disk
+    Remove(RemoveArgs),
+
     /// Sync and install assets from manifest sources
     Sync(SyncArgs),
 
+    /// Resolve sources and update the lockfile without installing anything
+    Lock(LockArgs),
+
+    /// Compare the locked commits of git entries with what their remotes offer
+    Outdated(OutdatedArgs),
+
+    /// Move the lockfile pins of entries to their latest upstream commits without installing
+    Update(UpdateArgs),
+
+    /// Show the changes `aps sync` would make, optionally saving them as a plan file
+    Plan(PlanArgs),
+
     /// Validate manifest and sources
     Validate(ValidateArgs),
 
+    /// Check the environment aps runs in, such as the installed git version
+    Doctor,
+
     /// Display status from lockfile
     Status(StatusArgs),
 
+    /// Show upstream commits affecting a git entry since its locked commit
+    Log(LogArgs),
+
+    /// Summarize when entries were added, upgraded, or removed, from the lockfile's git history
+    Changelog(ChangelogArgs),
+
     /// List manifest entries and their resources
     List(ListArgs),
 
+    /// Search the installed destinations of all entries for a pattern
+    Grep(GrepArgs),
+
+    /// List entries across the workspace's manifests that install from a repository or path
+    WhoUses(WhoUsesArgs),
+
+    /// List or search the machine-wide index of synced projects
+    Index(IndexArgs),
+
     /// Catalog operations for asset discovery
     Catalog(CatalogArgs),
+
+    /// Export or import the clone cache for offline syncs
+    Cache(CacheArgs),
+
+    /// List or restore backups recorded with `backup_strategy: store`
+    Backup(BackupArgs),
+
+    /// List or delete old destinations left behind when an entry's dest changed
+    Orphans(OrphansArgs),
+
+    /// Manifest maintenance, such as rebuilding a lost manifest from the lockfile
+    Manifest(ManifestArgs),
+
+    /// Rename an entry ID in the manifest, lockfile, and installed destination
+    Rename(RenameArgs),
+
+    /// Record an entry's installed content in the lockfile, accepting local edits
+    Pin(PinArgs),
+
+    /// Order manifest entries by kind, then ID
+    Sort(SortArgs),
+
+    /// Generate a standalone script that installs locked assets without aps
+    Bootstrap(BootstrapArgs),
+
+    /// Export an inventory of installed third-party content
+    Export(ExportArgs),
+
+    /// Print the JSON Schema of the manifest, lockfile, or catalog format
+    Schema(SchemaArgs),
+
+    /// Serve status, sync, validate, and explain over JSON-RPC for editor plugins
+    Serve(ServeArgs),
+}
+
+impl Commands {
+    /// Behave as if `--yes` was passed, for commands that take it
+    pub fn assume_yes(&mut self) {
+        match self {
+            Commands::Add(args) => args.yes = Approvals::ALL,
+            Commands::Sync(args) => args.yes = Approvals::ALL,
+            Commands::Orphans(OrphansArgs {
+                command: OrphansCommands::Clean(args),
+            }) => args.yes = true,
+            Commands::Manifest(ManifestArgs {
+                command: ManifestCommands::Rebuild(args),
+            }) => args.yes = true,
+            _ => {}
+        }
+    }
 }
 
 #[derive(Parser, Debug)]
@@ -51,6 +152,10 @@ pub struct InitArgs {
     /// Path for the manifest file
     #[arg(long)]
     pub manifest: Option<PathBuf>,
+
+    /// Set `sort: true`, keeping entries ordered by kind, then ID, as they are added
+    #[arg(long)]
+    pub sort: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -59,16 +164,37 @@ pub struct AddArgs {
     /// Supports: GitHub URLs (https://github.com/owner/repo/...) and local
     /// paths ($HOME/skills, ~/skills, ./skills). For repo-level URLs or
     /// directories without SKILL.md, discovers skills and prompts for selection.
-    #[arg(value_name = "URL_OR_PATH")]
-    pub url: String,
+    /// Also accepts `owner/repo[@ref][:path]` shorthand for GitHub repositories.
+    #[arg(value_name = "URL_OR_PATH", required_unless_present = "from_file")]
+    pub url: Option<String>,
+
+    /// Add every URL or path listed in a file, one per line (`-` reads
+    /// stdin). Blank lines and `#` comments are skipped; repo-level URLs add
+    /// all discovered skills. Entries are written and synced together.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["url", "id"])]
+    pub from_file: Option<PathBuf>,
+
+    /// GitHub host used to expand `owner/repo` shorthand and to read URLs
+    /// (e.g., a GitHub Enterprise instance)
+    #[arg(long, default_value = crate::github_url::DEFAULT_GITHUB_HOST)]
+    pub host: String,
+
+    /// Host of a self-hosted GitLab, whose URLs are read as GitLab URLs
+    #[arg(long, value_name = "HOST")]
+    pub gitlab_host: Option<String>,
+
+    /// Git ref to use, for branches whose names contain slashes
+    /// (e.g., `feature/foo`). Overrides the ref in the URL or shorthand.
+    #[arg(long = "ref", value_name = "REF")]
+    pub r#ref: Option<String>,
 
     /// Custom entry ID (defaults to skill folder name)
     #[arg(long)]
     pub id: Option<String>,
 
-    /// Asset kind (defaults to agent_skill)
-    #[arg(long, value_enum, default_value = "agent-skill")]
-    pub kind: AddAssetKind,
+    /// Asset kind (detected from the source when omitted, falling back to agent-skill)
+    #[arg(long, value_enum)]
+    pub kind: Option<AddAssetKind>,
 
     /// Path to the manifest file
     #[arg(long)]
@@ -78,13 +204,124 @@ pub struct AddArgs {
     #[arg(long)]
     pub no_sync: bool,
 
+    /// Add git URLs without checking that their branch or tag exists on the
+    /// remote
+    #[arg(long)]
+    pub no_verify: bool,
+
     /// Add all discovered skills without prompting (for repo-level URLs or directories)
     #[arg(long, conflicts_with = "id")]
     pub all: bool,
 
-    /// Skip confirmation prompts
-    #[arg(long, short = 'y')]
-    pub yes: bool,
+    /// Also discover directories without SKILL.md that look like skills: a
+    /// single markdown file with frontmatter, or a README.md beside
+    /// `scripts/`. Such matches are labeled heuristic, so `--kind` is required.
+    #[arg(long, requires = "kind")]
+    pub loose: bool,
+
+    /// Skip confirmation prompts.
+    /// `--yes=overwrite,orphans,remove` answers only those prompts.
+    #[arg(
+        long,
+        short = 'y',
+        value_name = "PROMPTS",
+        num_args = 0..=1,
+        require_equals = true,
+        default_value = "",
+        default_missing_value = "all",
+        hide_default_value = true,
+        value_parser = Approvals::parse
+    )]
+    pub yes: Approvals,
+
+    /// How to generate entry IDs for added skills. `plain` uses the skill
+    /// folder name and, during discovery, falls back to `repo-slug/skill-name` on collision;
+    /// `namespaced` always uses `repo-slug/skill-name`.
+    #[arg(long, value_enum, default_value = "plain")]
+    pub id_style: IdStyle,
+
+    /// Build the IDs of discovered skills from a template, e.g.
+    /// `{repo}-{name}`. Placeholders: `{repo}`, `{name}`, `{parent}` (the
+    /// folder holding the skill), and `{path}` (its path, dashed)
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "id")]
+    pub id_template: Option<String>,
+
+    /// Print the manifest changes as a diff before writing them (the
+    /// default in interactive sessions)
+    #[arg(long, overrides_with = "no_show_diff")]
+    pub show_diff: bool,
+
+    /// Write the manifest without printing a diff
+    #[arg(long, overrides_with = "show_diff")]
+    pub no_show_diff: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct RemoveArgs {
+    /// IDs of the entries to remove
+    #[arg(
+        value_name = "ID",
+        required_unless_present = "interactive",
+        conflicts_with = "interactive"
+    )]
+    pub ids: Vec<String>,
+
+    /// Pick the entries to remove from a list showing each one's sync
+    /// status and installed size
+    #[arg(long, short = 'i')]
+    pub interactive: bool,
+
+    /// Remove the entries from the manifest and lockfile but leave their
+    /// installed files in place
+    #[arg(long)]
+    pub keep_files: bool,
+
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+
+    /// Remove the picked entries without asking for confirmation.
+    /// `--yes=overwrite,orphans,remove` answers only those prompts.
+    #[arg(
+        long,
+        short = 'y',
+        value_name = "PROMPTS",
+        num_args = 0..=1,
+        require_equals = true,
+        default_value = "",
+        default_missing_value = "all",
+        hide_default_value = true,
+        value_parser = Approvals::parse
+    )]
+    pub yes: Approvals,
+
+    /// Print the manifest changes as a diff before writing them (the
+    /// default in interactive sessions)
+    #[arg(long, overrides_with = "no_show_diff")]
+    pub show_diff: bool,
+
+    /// Write the manifest without printing a diff
+    #[arg(long, overrides_with = "show_diff")]
+    pub no_show_diff: bool,
+}
+
+/// When `aps sync` asks remotes for their current commit
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RefreshPolicy {
+    /// Query every remote
+    #[default]
+    Always,
+    /// Reuse remote heads cached within the last hour
+    Auto,
+    /// Use cached remote heads only
+    Never,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdStyle {
+    #[default]
+    Plain,
+    Namespaced,
 }
 
 #[derive(ValueEnum, Clone, Debug, Default)]
@@ -94,6 +331,8 @@ pub enum AddAssetKind {
     AgentSkill,
     #[value(name = "cursor-rules")]
     CursorRules,
+    #[value(name = "cursor-hooks")]
+    CursorHooks,
     #[value(name = "cursor-skills-root")]
     CursorSkillsRoot,
     #[value(name = "agents-md")]
@@ -107,6 +346,36 @@ pub enum ManifestFormat {
     Toml,
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SchemaFormat {
+    /// aps.yaml
+    Manifest,
+    /// aps.lock.yaml
+    Lockfile,
+    /// aps.catalog.yaml
+    Catalog,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SbomFormat {
+    /// CycloneDX 1.5 JSON
+    #[default]
+    Cyclonedx,
+    /// Plain JSON list of entries
+    Json,
+}
+
+#[derive(Parser, Debug)]
+pub struct SchemaArgs {
+    /// File format to describe
+    #[arg(value_enum)]
+    pub format: SchemaFormat,
+
+    /// Write the schema to this file instead of stdout
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
 #[derive(Parser, Debug)]
 pub struct SyncArgs {
     /// Path to the manifest file
@@ -117,9 +386,24 @@ pub struct SyncArgs {
     #[arg(long = "only")]
     pub only: Vec<String>,
 
-    /// Skip confirmation prompts and allow overwrites
-    #[arg(long, short = 'y')]
-    pub yes: bool,
+    /// Only sync entries of this asset kind, e.g. agent_skill (can be repeated)
+    #[arg(long, value_name = "KIND")]
+    pub only_kind: Vec<String>,
+
+    /// Skip confirmation prompts and allow overwrites.
+    /// `--yes=overwrite,orphans,remove` answers only those prompts.
+    #[arg(
+        long,
+        short = 'y',
+        value_name = "PROMPTS",
+        num_args = 0..=1,
+        require_equals = true,
+        default_value = "",
+        default_missing_value = "all",
+        hide_default_value = true,
+        value_parser = Approvals::parse
+    )]
+    pub yes: Approvals,
 
     /// Ignore manifest (v0: not implemented)
     #[arg(long, hide = true)]
@@ -133,12 +417,151 @@ pub struct SyncArgs {
     #[arg(long)]
     pub strict: bool,
 
+    /// Review each entry's source, destination, and content before it
+    /// installs, approving, skipping, or disabling it. Disabled entries are
+    /// left out of later syncs until a review approves them
+    #[arg(long, conflicts_with = "plan")]
+    pub review: bool,
+
     /// Upgrade to latest versions from sources (ignore locked versions)
     ///
     /// By default, `aps sync` respects locked versions from aps.lock.yaml.
     /// Use --upgrade to fetch the latest versions and update the lockfile.
     #[arg(long, short = 'u')]
     pub upgrade: bool,
+
+    /// Read a lockfile written by a newer aps best-effort, dropping unknown fields on save.
+    /// With --upgrade, also upgrades frozen entries named in --only. Also skips
+    /// the `check_uncommitted` check
+    #[arg(long)]
+    pub force: bool,
+
+    /// Only sync entries whose last sync failed (or that were never synced)
+    #[arg(long, conflicts_with = "only")]
+    pub retry_failed: bool,
+
+    /// Keep syncing remaining entries when one fails, then exit with an error
+    #[arg(long)]
+    pub keep_going: bool,
+
+    /// Cancel an entry's git operations after this many seconds and move on
+    #[arg(long, value_name = "SECONDS")]
+    pub entry_timeout: Option<u64>,
+
+    /// Cancel remaining git operations once the whole sync has run this many seconds
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// Apply a plan saved by `aps plan --out`, failing if anything changed since
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["only", "only_kind", "retry_failed", "upgrade"]
+    )]
+    pub plan: Option<PathBuf>,
+
+    /// Install git sources only from the clone cache (see `aps cache import`), without network access
+    #[arg(long, conflicts_with = "upgrade")]
+    pub offline: bool,
+
+    /// When to check remotes for newer commits of locked git entries and
+    /// stale pins: `always`, `auto` (reuse results cached within the last
+    /// hour), or `never`. `--upgrade` still queries the remotes it upgrades from
+    #[arg(long, value_enum, value_name = "POLICY", default_value = "always")]
+    pub refresh: RefreshPolicy,
+
+    /// Show how long ago each entry was last updated, highlighting entries
+    /// not updated within this duration (e.g. `30d`, `12h`, `2w`)
+    #[arg(long, value_name = "DURATION", value_parser = crate::staleness::parse_age)]
+    pub stale_after: Option<chrono::Duration>,
+
+    /// Explain how to fix errors, with suggestions such as similar source paths
+    #[arg(long)]
+    pub explain: bool,
+
+    /// Run up to N network operations, such as upgrade checks, at once
+    /// (default: 4)
+    #[arg(long, short = 'j', value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub jobs: Option<u16>,
+
+    /// Use shallow clones that check out only each entry's path, and skip
+    /// checks for upgrades and stale pins
+    #[arg(long)]
+    pub low_bandwidth: bool,
+
+    /// Install an entry from another source for this run only, as
+    /// `<id>=git+<url>[@<ref>]` or `<id>=<local directory>`. The manifest is
+    /// not changed and the lockfile keeps the entry's locked source.
+    #[arg(long = "override", value_name = "ID=SOURCE", conflicts_with = "plan")]
+    pub overrides: Vec<String>,
+
+    /// Print one stable `<id>\t<status>\t<dest>` line per entry instead of styled output
+    #[arg(long)]
+    pub porcelain: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct LockArgs {
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+
+    /// Resolve git sources to their latest commits instead of keeping locked ones
+    #[arg(long, short = 'u')]
+    pub upgrade: bool,
+
+    /// Read a lockfile written by a newer aps best-effort, dropping unknown fields on save
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct OutdatedArgs {
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+
+    /// Use cached remote heads instead of querying remotes
+    #[arg(long)]
+    pub offline: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct UpdateArgs {
+    /// IDs of the entries to update (default: every entry with a git source)
+    #[arg(value_name = "ID")]
+    pub ids: Vec<String>,
+
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+
+    /// Show the pins that would move without writing the lockfile
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Also update frozen entries named on the command line
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct PlanArgs {
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+
+    /// Plan git sources at their latest commits instead of locked ones
+    #[arg(long, short = 'u')]
+    pub upgrade: bool,
+
+    /// Write the plan as JSON for `aps sync --plan`
+    #[arg(long, short = 'o', value_name = "FILE")]
+    pub out: Option<PathBuf>,
+
+    /// Read a lockfile written by a newer aps best-effort
+    #[arg(long)]
+    pub force: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -150,6 +573,28 @@ pub struct ValidateArgs {
     /// Treat warnings as errors
     #[arg(long)]
     pub strict: bool,
+
+    /// Rename entry IDs and destinations to match their SKILL.md `name`
+    #[arg(long)]
+    pub fix: bool,
+
+    /// Only validate entries of this asset kind (can be repeated)
+    #[arg(long, value_name = "KIND")]
+    pub only_kind: Vec<String>,
+
+    /// List each cursor_hooks event and command with the script paths they
+    /// run, flagging scripts outside the synced directory
+    #[arg(long)]
+    pub show_hooks: bool,
+
+    /// Explain how to fix errors and warnings, with suggestions such as
+    /// similar source paths
+    #[arg(long)]
+    pub explain: bool,
+
+    /// Clone up to N git repositories at once (default: 4)
+    #[arg(long, short = 'j', value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    pub jobs: Option<u16>,
 }
 
 #[derive(Parser, Debug)]
@@ -157,6 +602,24 @@ pub struct StatusArgs {
     /// Path to the manifest file
     #[arg(long)]
     pub manifest: Option<PathBuf>,
+
+    /// Read a lockfile written by a newer aps best-effort
+    #[arg(long)]
+    pub force: bool,
+
+    /// Only show entries matching an expression like `kind=agent_skill`,
+    /// `source~=github.com/org`, or `symlink!=true` (can be repeated)
+    #[arg(long = "filter", value_name = "EXPR")]
+    pub filter: Vec<String>,
+
+    /// Print one stable `<id>\t<status>\t<dest>` line per entry instead of styled output
+    #[arg(long)]
+    pub porcelain: bool,
+
+    /// Reinstall entries whose destination is missing, modified, or a dangling
+    /// symlink; modified destinations are backed up first
+    #[arg(long, conflicts_with = "porcelain")]
+    pub repair: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -168,6 +631,103 @@ pub struct ListArgs {
     /// Show on-disk asset tree for synced entries
     #[arg(long)]
     pub assets: bool,
+
+    /// Only show entries matching an expression like `kind=agent_skill`,
+    /// `source~=github.com/org`, or `symlink!=true` (can be repeated)
+    #[arg(long = "filter", value_name = "EXPR")]
+    pub filter: Vec<String>,
+
+    /// Print one stable `<id>\t<status>\t<dest>` line per entry instead of styled output
+    #[arg(long)]
+    pub porcelain: bool,
+
+    /// Only show git entries whose locked commit is behind the remote,
+    /// furthest behind first
+    #[arg(long, conflicts_with = "assets")]
+    pub outdated: bool,
+
+    /// With --outdated, use cached remote heads instead of querying remotes
+    #[arg(long, requires = "outdated")]
+    pub offline: bool,
+
+    /// Highlight entries not updated within this duration (e.g. `30d`, `12h`, `2w`)
+    #[arg(long, value_name = "DURATION", value_parser = crate::staleness::parse_age)]
+    pub stale_after: Option<chrono::Duration>,
+}
+
+#[derive(Parser, Debug)]
+pub struct GrepArgs {
+    /// Regular expression to search for
+    pub pattern: String,
+
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+
+    /// Only search entries matching an expression like `kind=agent_skill`
+    /// (can be repeated)
+    #[arg(long = "filter", value_name = "EXPR")]
+    pub filter: Vec<String>,
+
+    /// Match case-insensitively
+    #[arg(short = 'i', long)]
+    pub ignore_case: bool,
+
+    /// Treat the pattern as literal text instead of a regular expression
+    #[arg(short = 'F', long)]
+    pub fixed_strings: bool,
+
+    /// Only print the entries and files that match
+    #[arg(short = 'l', long)]
+    pub files_with_matches: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct WhoUsesArgs {
+    /// Repository (e.g. `github.com/org/repo`), local path, or glob such as
+    /// `github.com/org/*` to look for
+    pub source: String,
+
+    /// Manifests, or directories to search for them (default: the workspace
+    /// containing the current directory)
+    pub paths: Vec<PathBuf>,
+
+    /// Print one stable `<manifest>\t<id>\t<source>` line per entry instead of styled output
+    #[arg(long)]
+    pub porcelain: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct IndexArgs {
+    #[command(subcommand)]
+    pub command: IndexCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IndexCommands {
+    /// List indexed projects and the sources several of them share
+    List(IndexListArgs),
+
+    /// Find indexed projects with an entry ID, repository, or path
+    Search(IndexSearchArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct IndexListArgs {
+    /// Print one stable `<project>\t<id>\t<source>` line per entry instead of styled output
+    #[arg(long)]
+    pub porcelain: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct IndexSearchArgs {
+    /// Entry ID, repository (e.g. `github.com/org/repo`), local path, or glob
+    /// such as `github.com/org/*`
+    pub pattern: String,
+
+    /// Print one stable `<project>\t<id>\t<source>` line per entry instead of styled output
+    #[arg(long)]
+    pub porcelain: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -191,4 +751,283 @@ pub struct CatalogGenerateArgs {
     /// Output path for the catalog file (default: aps.catalog.yaml next to manifest)
     #[arg(long, short)]
     pub output: Option<PathBuf>,
+
+    /// Score each asset's health (description, SKILL.md, upstream age, size)
+    #[arg(long)]
+    pub with_health: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    pub command: CacheCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheCommands {
+    /// Bundle the cached clones needed by the lockfile into an archive
+    Export(CacheExportArgs),
+
+    /// Verify an archive from `aps cache export` and load it into the clone cache
+    Import(CacheImportArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct CacheExportArgs {
+    /// Archive file to write
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct CacheImportArgs {
+    /// Archive file to read
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct BackupArgs {
+    #[command(subcommand)]
+    pub command: BackupCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BackupCommands {
+    /// List restore points, oldest first
+    List(BackupListArgs),
+
+    /// Rebuild a restore point at the path it was backed up from
+    Restore(BackupRestoreArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct BackupListArgs {
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct BackupRestoreArgs {
+    /// Restore point ID, as shown by `aps backup list`
+    #[arg(value_name = "ID")]
+    pub id: String,
+
+    /// Restore to this path instead of the original one
+    #[arg(long, value_name = "PATH")]
+    pub to: Option<PathBuf>,
+
+    /// Replace existing content at the target, backing it up first
+    #[arg(long)]
+    pub force: bool,
+
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct OrphansArgs {
+    #[command(subcommand)]
+    pub command: OrphansCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum OrphansCommands {
+    /// List old destinations that are still on disk
+    List(OrphansListArgs),
+
+    /// Delete old destinations, backing up content aps did not install
+    Clean(OrphansCleanArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct OrphansListArgs {
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct OrphansCleanArgs {
+    /// Delete without prompting
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+
+    /// Show what would be deleted without deleting it
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct ManifestArgs {
+    #[command(subcommand)]
+    pub command: ManifestCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ManifestCommands {
+    /// Reconstruct a best-effort manifest from the lockfile's entries
+    Rebuild(ManifestRebuildArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct ManifestRebuildArgs {
+    /// Path of the manifest to write (default: aps.yaml in the current directory);
+    /// the lockfile is read from the same directory
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+
+    /// Print the rebuilt manifest instead of writing it
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Overwrite an existing manifest
+    #[arg(long)]
+    pub force: bool,
+
+    /// Write without asking for confirmation
+    #[arg(long, short = 'y')]
+    pub yes: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct LogArgs {
+    /// Entry ID to show upstream changes for
+    #[arg(value_name = "ENTRY_ID")]
+    pub id: String,
+
+    /// Start from this commit, tag, or branch, or a date (YYYY-MM-DD or RFC 3339)
+    /// instead of the locked commit
+    #[arg(long, value_name = "REF|DATE")]
+    pub since: Option<String>,
+
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct ChangelogArgs {
+    /// Write the changelog to a Markdown file (relative to the manifest
+    /// directory) instead of printing it
+    #[arg(
+        long,
+        value_name = "FILE",
+        num_args = 0..=1,
+        default_missing_value = crate::history::CHANGELOG_FILE
+    )]
+    pub write: Option<PathBuf>,
+
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct ServeArgs {
+    /// Speak newline-delimited JSON-RPC 2.0 on stdin/stdout
+    #[arg(long, required = true)]
+    pub stdio: bool,
+
+    /// Path to the manifest file (requests may override it per call)
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct RenameArgs {
+    /// Current entry ID
+    #[arg(value_name = "OLD_ID")]
+    pub old_id: String,
+
+    /// New entry ID
+    #[arg(value_name = "NEW_ID")]
+    pub new_id: String,
+
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct PinArgs {
+    /// Entry IDs to pin
+    #[arg(value_name = "ID", required = true)]
+    pub ids: Vec<String>,
+
+    /// Take the checksum from the content installed at each destination
+    #[arg(long, required = true)]
+    pub from_dest: bool,
+
+    /// Also store the installed content as a restore point in the backup directory
+    #[arg(long)]
+    pub backup: bool,
+
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct SortArgs {
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+
+    /// Fail instead of rewriting the manifest when entries are out of order
+    #[arg(long)]
+    pub check: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct BootstrapArgs {
+    /// Print a POSIX shell script reproducing the lockfile state to stdout
+    #[arg(long, required = true)]
+    pub emit_script: bool,
+
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
+}
+
+#[derive(Parser, Debug)]
+pub struct ExportArgs {
+    #[command(subcommand)]
+    pub command: ExportCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ExportCommands {
+    /// List each locked entry's source, commit, checksum, license, and
+    /// install path as a software bill of materials
+    Sbom(ExportSbomArgs),
+}
+
+#[derive(Parser, Debug)]
+pub struct ExportSbomArgs {
+    /// Document format
+    #[arg(long, value_enum, default_value = "cyclonedx")]
+    pub format: SbomFormat,
+
+    /// Write the SBOM to this file instead of stdout
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+
+    /// Path to the manifest file
+    #[arg(long)]
+    pub manifest: Option<PathBuf>,
 }
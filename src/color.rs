@@ -0,0 +1,74 @@
+//! Terminal color handling.
+//!
+//! All styled output goes through the `console` re-exports below, so a single
+//! decision made at startup (from `--no-color`, `NO_COLOR`, and
+//! `CLICOLOR_FORCE`) applies to every command, prompts, and error reports.
+
+pub use console::{style, Style};
+
+use miette::MietteHandlerOpts;
+
+/// Apply the color preference for this process. `plain` (set by the CI
+/// profile) turns colors off unless `CLICOLOR_FORCE` asks for them.
+///
+/// Without an explicit preference, `console` and `miette` keep their own
+/// terminal detection.
+pub fn init(no_color_flag: bool, plain: bool) {
+    let no_color = std::env::var("NO_COLOR").ok();
+    let force = std::env::var("CLICOLOR_FORCE").ok();
+    let Some(enabled) = color_override(no_color_flag, no_color.as_deref(), force.as_deref())
+        .or(plain.then_some(false))
+    else {
+        return;
+    };
+
+    console::set_colors_enabled(enabled);
+    console::set_colors_enabled_stderr(enabled);
+    // Only fails if a hook was already installed, which never happens before startup
+    let _ = miette::set_hook(Box::new(move |_| {
+        Box::new(MietteHandlerOpts::new().color(enabled).build())
+    }));
+}
+
+/// Whether colors are enabled for stdout after `init`
+pub fn enabled() -> bool {
+    console::colors_enabled()
+}
+
+/// Resolve an explicit color preference, if any.
+///
+/// `--no-color` wins, then a non-empty `NO_COLOR` (see no-color.org), then a
+/// `CLICOLOR_FORCE` other than `0`. `None` means auto-detect.
+fn color_override(
+    no_color_flag: bool,
+    no_color: Option<&str>,
+    clicolor_force: Option<&str>,
+) -> Option<bool> {
+    if no_color_flag || no_color.is_some_and(|v| !v.is_empty()) {
+        return Some(false);
+    }
+    if clicolor_force.is_some_and(|v| !v.is_empty() && v != "0") {
+        return Some(true);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_override_precedence() {
+        assert_eq!(color_override(false, None, None), None);
+        assert_eq!(color_override(true, None, Some("1")), Some(false));
+        assert_eq!(color_override(false, Some("1"), Some("1")), Some(false));
+        assert_eq!(color_override(false, None, Some("1")), Some(true));
+    }
+
+    #[test]
+    fn test_color_override_ignores_empty_and_zero_values() {
+        assert_eq!(color_override(false, Some(""), None), None);
+        assert_eq!(color_override(false, None, Some("0")), None);
+        assert_eq!(color_override(false, Some(""), Some("")), None);
+    }
+}
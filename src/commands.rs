@@ -1,27 +1,90 @@
-use crate::catalog::Catalog;
+use crate::approval::{take_auto_approved, Approvals, Prompt};
+use crate::backup::{
+    backup_roots, create_backup, find_restore_point, list_restore_points, restore_point,
+    store_backup, BackupPolicy,
+};
+use crate::bootstrap::render_bootstrap_script;
+use crate::cache::{export_cache, import_cache};
+use crate::catalog::{asset_size, format_size, Catalog, HEALTH_WARN_SCORE};
+use crate::changelog::{print_changelog, Changelog, Since};
+use crate::ci::{self, interactive};
 use crate::cli::{
-    AddArgs, AddAssetKind, CatalogGenerateArgs, InitArgs, ListArgs, ManifestFormat, StatusArgs,
-    SyncArgs, ValidateArgs,
+    AddArgs, AddAssetKind, BackupListArgs, BackupRestoreArgs, BootstrapArgs, CacheExportArgs,
+    CacheImportArgs, CatalogGenerateArgs, ChangelogArgs, ExportSbomArgs, GrepArgs, IdStyle,
+    IndexListArgs, IndexSearchArgs, InitArgs, ListArgs, LockArgs, LogArgs, ManifestFormat,
+    ManifestRebuildArgs, OrphansCleanArgs, OrphansListArgs, OutdatedArgs, PinArgs, PlanArgs,
+    RefreshPolicy, RemoveArgs, RenameArgs, SchemaArgs, ServeArgs, SortArgs, StatusArgs, SyncArgs,
+    UpdateArgs, ValidateArgs, WhoUsesArgs,
 };
+use crate::color::{style, Style};
+use crate::consumers::{find_manifests, who_uses, SourcePattern};
+use crate::dest_lock;
 use crate::discover::{
-    discover_skills_in_local_dir, discover_skills_in_repo, prompt_skill_selection,
+    detect_asset_kind, detect_asset_kind_from_name, discover_skills_in_local_dir,
+    discover_skills_in_repo, find_rule_files, find_skill_md, prompt_rule_selection,
+    prompt_skill_selection, read_skill_name,
 };
+use crate::entry_id::{self, dest_folder, normalize_id, IdParts};
 use crate::error::{ApsError, Result};
-use crate::github_url::parse_github_url;
-use crate::hooks::validate_cursor_hooks;
-use crate::install::{install_composite_entry, install_entry, InstallOptions, InstallResult};
-use crate::lockfile::{display_status, Lockfile};
+use crate::explain::{self, similar_refs};
+use crate::filter::{matches_all, Filter};
+use crate::github_url::{parse_github_shorthand, parse_repo_url, ParsedGitHubUrl};
+use crate::grep::{build_pattern, search};
+use crate::history;
+use crate::hooks::{hooks_root_dir, preview_hooks, validate_cursor_hooks, HooksPreview};
+use crate::install::{
+    check_case_collisions, compose_entry_sources, dangling_symlinks, dest_state,
+    install_composite_entry, install_entry, installed_checksum, lock_entry, sync_mirrors,
+    DestState, InstallOptions, InstallResult,
+};
+use crate::integrations::{apply_editor_integrations, EDITOR_SETTINGS_PATH};
+use crate::lockfile::{
+    display_manifest_changes, display_status, display_status_porcelain, LastSyncStatus,
+    LockedEntry, Lockfile, LOCKFILE_NAME,
+};
 use crate::manifest::{
-    detect_overlapping_destinations, discover_manifest, load_manifest, manifest_dir,
-    validate_manifest, AssetKind, Entry, Manifest, Source, DEFAULT_MANIFEST_NAME,
+    detect_overlapping_destinations, discover_manifest, insert_sorted, lint_manifest,
+    load_manifest, manifest_checksum, manifest_dir, parse_manifest, save_manifest, sort_entries,
+    validate_manifest, AssetKind, BackupStrategy, Entry, Manifest, Source, DEFAULT_MANIFEST_NAME,
+};
+use crate::manifest_diff::{print_diff, write_manifest_edit};
+use crate::orphan::{
+    detect_orphaned_paths, exclude_foreign_owned, foreign_owners, manifest_ref, paths_overlap,
+    prompt_and_cleanup_orphans, prune_and_report, remaining_orphans, workspace_root,
 };
-use crate::orphan::{detect_orphaned_paths, prompt_and_cleanup_orphans};
-use crate::sync_output::{print_sync_results, print_sync_summary, SyncDisplayItem, SyncStatus};
-use console::{style, Style};
+use crate::output::{display_dest, porcelain_line, say, say_inline, short_id};
+use crate::persona::validate_personas;
+use crate::plan::{print_plan, Plan};
+use crate::post_install::run_post_install;
+use crate::preflight::check_destinations;
+use crate::project_index::{self, index_path, ProjectIndex};
+use crate::rebuild::RebuiltManifest;
+use crate::review::{review_entries, ReviewState};
+use crate::sbom;
+use crate::schema::schema_for_format;
+use crate::serve::Server;
+use crate::sources::{
+    clone_and_resolve, git_version, list_remote_refs, release_constraint, require_git,
+    resolve_release, set_github_tarballs, set_low_bandwidth, supports, uncommitted_changes,
+    with_git_deadline, ClonePool, GitFeature, ResolvedSource, MIN_GIT_VERSION,
+};
+use crate::staleness::{
+    check_outdated, check_stale_pins, format_age, last_updated_age, remote_ahead, RemoteRefCache,
+};
+use crate::summary;
+use crate::sync_output::{
+    print_auto_approved, print_stale_pins, print_sync_porcelain, print_sync_results,
+    print_sync_summary, SyncDisplayItem, SyncStatus,
+};
+use crate::user_config::UserConfig;
+use crate::warning::{Warning, WarningCode, WarningSet};
+use chrono::Utc;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io::Write;
-use std::path::Path;
-use tracing::info;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
 
 /// Parsed add target — the adapter pattern for distinguishing GitHub vs. filesystem sources.
 enum ParsedAddTarget {
@@ -80,21 +143,21 @@ fn is_local_path(input: &str) -> bool {
 }
 
 /// Parse the add target into a typed enum for routing.
-fn parse_add_target(url_or_path: &str, all_flag: bool) -> Result<ParsedAddTarget> {
+fn parse_add_target(
+    url_or_path: &str,
+    all_flag: bool,
+    host: &str,
+    gitlab_host: Option<&str>,
+    git_ref: Option<&str>,
+) -> Result<ParsedAddTarget> {
     if is_local_path(url_or_path) {
+        if git_ref.is_some() {
+            return Err(ApsError::InvalidInput {
+                message: "--ref only applies to git URLs and owner/repo shorthand".to_string(),
+            });
+        }
         // Check if it contains a SKILL.md (single-skill) or not (discovery)
-        let expanded = shellexpand::full(url_or_path)
-            .map(|s| s.into_owned())
-            .unwrap_or_else(|_| url_or_path.to_string());
-
-        let expanded_path = std::path::Path::new(&expanded);
-        let expanded_path = if expanded_path.is_relative() {
-            std::env::current_dir()
-                .map_err(|e| ApsError::io(e, "Failed to get current directory"))?
-                .join(expanded_path)
-        } else {
-            expanded_path.to_path_buf()
-        };
+        let expanded_path = expand_local_path(url_or_path)?;
 
         let has_skill_md =
             expanded_path.join("SKILL.md").exists() || expanded_path.join("skill.md").exists();
@@ -116,6 +179,12 @@ fn parse_add_target(url_or_path: &str, all_flag: bool) -> Result<ParsedAddTarget
                 original_path: url_or_path.to_string(),
             })
         }
+    } else if let Some(mut parsed) = parse_github_shorthand(url_or_path, host) {
+        // owner/repo[@ref][:path] shorthand that isn't an existing local path
+        if let Some(git_ref) = git_ref {
+            parsed.git_ref = git_ref.to_string();
+        }
+        Ok(github_add_target(parsed, all_flag))
     } else if !url_or_path.contains("://") {
         // No URL scheme and is_local_path returned false — the path doesn't exist
         let expanded = shellexpand::full(url_or_path)
@@ -128,24 +197,164 @@ fn parse_add_target(url_or_path: &str, all_flag: bool) -> Result<ParsedAddTarget
             ),
         })
     } else {
-        // Parse as GitHub URL
-        let parsed = parse_github_url(url_or_path)?;
-        if parsed.is_repo_level || all_flag {
-            Ok(ParsedAddTarget::GitHubDiscovery {
-                repo_url: parsed.repo_url,
-                git_ref: parsed.git_ref,
-                search_path: parsed.path,
-            })
-        } else {
-            // Compute derived values before moving fields
-            let skill_path = parsed.skill_path().to_string();
-            let skill_name = parsed.skill_name().map(|s| s.to_string());
-            Ok(ParsedAddTarget::GitHubSkill {
-                repo_url: parsed.repo_url,
-                git_ref: parsed.git_ref,
+        // Parse as a GitHub or GitLab URL
+        let parsed = parse_repo_url(url_or_path, host, gitlab_host)?;
+        let parsed = resolve_url_ref(parsed, url_or_path, git_ref)?;
+        Ok(github_add_target(parsed, all_flag))
+    }
+}
+
+/// Find where the ref ends in a GitHub URL, since refs may contain slashes.
+///
+/// An explicit `--ref` must be a prefix of the URL's ref and path. Otherwise
+/// the longest prefix the remote advertises as a branch or tag wins; if the
+/// remote cannot be listed, the first segment is kept as the ref.
+fn resolve_url_ref(
+    parsed: ParsedGitHubUrl,
+    url: &str,
+    git_ref: Option<&str>,
+) -> Result<ParsedGitHubUrl> {
+    if let Some(git_ref) = git_ref {
+        return parsed
+            .with_ref(git_ref)
+            .ok_or_else(|| ApsError::InvalidGitHubUrl {
+                url: url.to_string(),
+                reason: format!(
+                    "--ref '{}' does not match the start of the ref and path in the URL",
+                    git_ref
+                ),
+            });
+    }
+
+    let candidates = parsed.ref_candidates();
+    if candidates.len() < 2 {
+        return Ok(parsed);
+    }
+    let remote_refs = match list_remote_refs(&parsed.repo_url) {
+        Ok(refs) => refs,
+        Err(e) => {
+            debug!("Could not list refs of {}: {}", parsed.repo_url, e);
+            return Ok(parsed);
+        }
+    };
+    let resolved = candidates
+        .iter()
+        .rev()
+        .find(|candidate| remote_refs.contains(candidate))
+        .and_then(|git_ref| parsed.with_ref(git_ref));
+    Ok(resolved.unwrap_or(parsed))
+}
+
+/// Check that the ref of a git add target exists on the remote, unless
+/// `--no-verify`. A missing ref fails with the closest branches and tags, or,
+/// in an interactive session, offers them to pick from.
+///
+/// Commit SHAs, release refs, and `auto` are not listed by `ls-remote` and
+/// pass unchecked, as does a remote that cannot be listed.
+fn verify_add_ref(target: ParsedAddTarget, args: &AddArgs) -> Result<ParsedAddTarget> {
+    let (repo_url, git_ref) = match &target {
+        ParsedAddTarget::GitHubSkill {
+            repo_url, git_ref, ..
+        }
+        | ParsedAddTarget::GitHubDiscovery {
+            repo_url, git_ref, ..
+        } => (repo_url.clone(), git_ref.clone()),
+        _ => return Ok(target),
+    };
+    let looks_like_commit =
+        (7..=40).contains(&git_ref.len()) && git_ref.chars().all(|c| c.is_ascii_hexdigit());
+    if args.no_verify
+        || git_ref == "auto"
+        || looks_like_commit
+        || release_constraint(&git_ref).is_some()
+    {
+        return Ok(target);
+    }
+    let remote_refs = match list_remote_refs(&repo_url) {
+        Ok(refs) => refs,
+        Err(e) => {
+            debug!("Could not verify ref {} of {}: {}", git_ref, repo_url, e);
+            return Ok(target);
+        }
+    };
+    if remote_refs.contains(&git_ref) {
+        return Ok(target);
+    }
+
+    let suggestions = similar_refs(&git_ref, &remote_refs);
+    if !suggestions.is_empty() && interactive() && !args.yes.any() {
+        let selection = dialoguer::Select::new()
+            .with_prompt(format!(
+                "Ref '{}' not found in {}. Use instead",
+                git_ref, repo_url
+            ))
+            .items(&suggestions)
+            .default(0)
+            .interact_opt()
+            .map_err(|_| ApsError::Cancelled)?
+            .ok_or(ApsError::Cancelled)?;
+        let picked = suggestions[selection].clone();
+        return Ok(match target {
+            ParsedAddTarget::GitHubSkill {
+                repo_url,
                 skill_path,
                 skill_name,
-            })
+                ..
+            } => ParsedAddTarget::GitHubSkill {
+                repo_url,
+                git_ref: picked,
+                skill_path,
+                skill_name,
+            },
+            ParsedAddTarget::GitHubDiscovery {
+                repo_url,
+                search_path,
+                ..
+            } => ParsedAddTarget::GitHubDiscovery {
+                repo_url,
+                git_ref: picked,
+                search_path,
+            },
+            other => other,
+        });
+    }
+
+    let hint = if suggestions.is_empty() {
+        "Check the branch or tag name, or pass `--no-verify` to add it anyway".to_string()
+    } else {
+        format!(
+            "Did you mean {}? Pass the intended ref with `--ref`, or `--no-verify` to add it anyway",
+            suggestions
+                .iter()
+                .map(|s| format!("'{}'", s))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    };
+    Err(ApsError::RefNotFound {
+        repo: repo_url,
+        git_ref,
+        hint,
+    })
+}
+
+/// Route a parsed GitHub location to single-skill add or discovery.
+fn github_add_target(parsed: ParsedGitHubUrl, all_flag: bool) -> ParsedAddTarget {
+    if parsed.is_repo_level || all_flag {
+        ParsedAddTarget::GitHubDiscovery {
+            repo_url: parsed.repo_url,
+            git_ref: parsed.git_ref,
+            search_path: parsed.path,
+        }
+    } else {
+        // Compute derived values before moving fields
+        let skill_path = parsed.skill_path().to_string();
+        let skill_name = parsed.skill_name().map(|s| s.to_string());
+        ParsedAddTarget::GitHubSkill {
+            repo_url: parsed.repo_url,
+            git_ref: parsed.git_ref,
+            skill_path,
+            skill_name,
         }
     }
 }
@@ -166,14 +375,33 @@ pub fn cmd_init(args: InitArgs) -> Result<()> {
         });
     }
 
-    // Create default manifest
-    let manifest = Manifest::default();
+    // Suggest entries for assets already in the project instead of the example
+    let project_dir = manifest_path.parent().unwrap_or_else(|| Path::new("."));
+    let suggested = detect_existing_assets(project_dir);
+    let mut manifest = if suggested.is_empty() {
+        Manifest::default()
+    } else {
+        Manifest {
+            entries: suggested.clone(),
+            ..Manifest::default()
+        }
+    };
+    if args.sort {
+        manifest.sort = true;
+        sort_entries(&mut manifest.entries);
+    }
 
     let content = match args.format {
         ManifestFormat::Yaml => {
-            serde_yaml::to_string(&manifest).map_err(|e| ApsError::ManifestParseError {
-                message: format!("Failed to serialize manifest: {}", e),
-            })?
+            let yaml =
+                serde_yaml::to_string(&manifest).map_err(|e| ApsError::ManifestParseError {
+                    message: format!("Failed to serialize manifest: {}", e),
+                })?;
+            if suggested.is_empty() {
+                yaml
+            } else {
+                comment_out_suggestions(&yaml)
+            }
         }
         ManifestFormat::Toml => {
             // For TOML, we'd need a different serializer, but YAML is default
@@ -192,8 +420,13 @@ pub fn cmd_init(args: InitArgs) -> Result<()> {
         )
     })?;
 
-    println!("Created manifest at {:?}", manifest_path);
+    say!("Created manifest at {:?}", manifest_path);
     info!("Created manifest at {:?}", manifest_path);
+    if !suggested.is_empty() {
+        let found: Vec<String> = suggested.iter().filter_map(|e| e.dest.clone()).collect();
+        say!("Found existing assets: {}", found.join(", "));
+        say!("Suggested entries for them are commented out in the manifest.");
+    }
 
     // Update .gitignore
     update_gitignore(&manifest_path)?;
@@ -201,19 +434,117 @@ pub fn cmd_init(args: InitArgs) -> Result<()> {
     Ok(())
 }
 
-/// Update .gitignore to include the backup directory
+/// Agent asset locations `aps init` looks for, with the entry ID it suggests
+const DETECTED_ASSETS: [(&str, AssetKind); 5] = [
+    ("agents-md", AssetKind::AgentsMd),
+    ("cursor-rules", AssetKind::CursorRules),
+    ("cursor-hooks", AssetKind::CursorHooks),
+    ("cursor-skills", AssetKind::CursorSkillsRoot),
+    ("personas", AssetKind::Persona),
+];
+
+/// Suggest an entry for each agent asset already present in `dir`: the
+/// known files and directories above, plus each skill under `.claude/skills`
+fn detect_existing_assets(dir: &Path) -> Vec<Entry> {
+    let mut found: Vec<(String, AssetKind, PathBuf)> = DETECTED_ASSETS
+        .iter()
+        .map(|(id, kind)| (id.to_string(), kind.clone(), kind.default_dest()))
+        .filter(|(_, _, dest)| dir.join(dest).exists())
+        .collect();
+
+    let skills_dir = AssetKind::AgentSkill.default_dest();
+    let mut skills: Vec<String> = fs::read_dir(dir.join(&skills_dir))
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| find_skill_md(&e.path()).is_some())
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    skills.sort();
+    found.extend(
+        skills
+            .into_iter()
+            .map(|name| (name.clone(), AssetKind::AgentSkill, skills_dir.join(name))),
+    );
+
+    found
+        .into_iter()
+        .map(|(id, kind, dest)| {
+            let dest = dest.to_string_lossy().to_string();
+            Entry {
+                id,
+                kind,
+                source: Some(Source::Filesystem {
+                    root: "../shared-assets".to_string(),
+                    symlink: true,
+                    path: Some(dest.clone()),
+                }),
+                sources: Vec::new(),
+                dest: Some(format!("./{}", dest)),
+                include: Vec::new(),
+                max_age: None,
+                preserve_permissions: true,
+                preserve_mtime: false,
+                hooks_merge: Default::default(),
+                default_dest_override: None,
+                checksum_ignore: Vec::new(),
+                description: None,
+                owner: None,
+                copy_license: false,
+                also_install_for: Vec::new(),
+                frozen: false,
+                targets: Vec::new(),
+                transform: Vec::new(),
+                suppress_warnings: Vec::new(),
+                backup: None,
+                backup_dir: None,
+                symlink_style: None,
+            }
+        })
+        .collect()
+}
+
+/// Turn a serialized manifest's entries into comments under an empty
+/// `entries` list, so the suggestions parse but install nothing
+fn comment_out_suggestions(yaml: &str) -> String {
+    let mut content = String::new();
+    let mut in_entries = false;
+    for line in yaml.lines() {
+        if line == "entries:" {
+            in_entries = true;
+            content.push_str(
+                "# Suggested entries for assets already in this project. Point each\n\
+                 # `source` at where the assets come from, then uncomment them in\n\
+                 # place of `entries: []`.\n\
+                 entries: []\n\
+                 # entries:\n",
+            );
+        } else if in_entries && (line.starts_with('-') || line.starts_with(' ')) {
+            content.push_str(&format!("# {}\n", line));
+        } else {
+            in_entries = false;
+            content.push_str(&format!("{}\n", line));
+        }
+    }
+    content
+}
+
+/// Update .gitignore to include the backup and cache directories
 fn update_gitignore(manifest_path: &Path) -> Result<()> {
     let manifest_dir = manifest_path.parent().unwrap_or_else(|| Path::new("."));
 
     let gitignore_path = manifest_dir.join(".gitignore");
-    let backup_entry = ".aps-backups/";
+    let required_entries = [".aps-backups/", ".aps-cache/"];
 
     // Read existing .gitignore or start with empty
     let existing = fs::read_to_string(&gitignore_path).unwrap_or_default();
 
-    let needs_backup = !existing.lines().any(|line| line.trim() == backup_entry);
+    let missing: Vec<&str> = required_entries
+        .into_iter()
+        .filter(|entry| !existing.lines().any(|line| line.trim() == *entry))
+        .collect();
 
-    if !needs_backup {
+    if missing.is_empty() {
         info!(".gitignore already contains required entries");
         return Ok(());
     }
@@ -230,20 +561,41 @@ fn update_gitignore(manifest_path: &Path) -> Result<()> {
         writeln!(file).map_err(|e| ApsError::io(e, "Failed to write to .gitignore"))?;
     }
 
-    // Add comment and entry
+    // Add comment and entries
     writeln!(file, "\n# APS (Agentic Prompt Sync)")
         .map_err(|e| ApsError::io(e, "Failed to write to .gitignore"))?;
 
-    writeln!(file, "{}", backup_entry)
-        .map_err(|e| ApsError::io(e, "Failed to write to .gitignore"))?;
-    println!("Added {} to .gitignore", backup_entry);
+    for entry in missing {
+        writeln!(file, "{}", entry)
+            .map_err(|e| ApsError::io(e, "Failed to write to .gitignore"))?;
+        say!("Added {} to .gitignore", entry);
+    }
 
     Ok(())
 }
 
 /// Execute the `aps add` command
 pub fn cmd_add(args: AddArgs) -> Result<()> {
-    let target = parse_add_target(&args.url, args.all)?;
+    // Discovery clones follow the target manifest's `github_tarballs`
+    let target_manifest = match args.manifest.as_deref() {
+        Some(p) => load_manifest(p).ok(),
+        None => discover_manifest(None).ok().map(|(m, _)| m),
+    };
+    set_github_tarballs(target_manifest.is_some_and(|m| m.github_tarballs));
+
+    if let Some(file) = args.from_file.clone() {
+        return cmd_add_from_file(args, &file);
+    }
+    let url = args.url.clone().unwrap_or_default();
+    let target = parse_add_target(
+        &url,
+        args.all,
+        &args.host,
+        args.gitlab_host.as_deref(),
+        args.r#ref.as_deref(),
+    )?;
+    let target = verify_add_ref(target, &args)?;
+    let kind = add_asset_kind(&args, &target)?;
 
     match target {
         ParsedAddTarget::GitHubSkill {
@@ -251,20 +603,235 @@ pub fn cmd_add(args: AddArgs) -> Result<()> {
             git_ref,
             skill_path,
             skill_name,
-        } => cmd_add_single_git(args, &repo_url, &git_ref, &skill_path, skill_name),
+        } if kind == AssetKind::CursorRules && !skill_path.ends_with(".mdc") => {
+            cmd_add_git_rules(args, kind, &repo_url, &git_ref, &skill_path, skill_name)
+        }
+        ParsedAddTarget::GitHubSkill {
+            repo_url,
+            git_ref,
+            skill_path,
+            skill_name,
+        } => cmd_add_single_git(args, kind, &repo_url, &git_ref, &skill_path, skill_name),
         ParsedAddTarget::GitHubDiscovery {
             repo_url,
             git_ref,
             search_path,
-        } => cmd_add_discover_git(args, &repo_url, &git_ref, &search_path),
+        } if kind == AssetKind::CursorRules => {
+            cmd_add_git_rules(args, kind, &repo_url, &git_ref, &search_path, None)
+        }
+        ParsedAddTarget::GitHubDiscovery {
+            repo_url,
+            git_ref,
+            search_path,
+        } => cmd_add_discover_git(args, kind, &repo_url, &git_ref, &search_path),
+        ParsedAddTarget::FilesystemSkill {
+            original_path,
+            skill_name,
+        } => cmd_add_single_filesystem(args, kind, &original_path, &skill_name),
+        ParsedAddTarget::FilesystemDiscovery { original_path } => {
+            if installs_whole_source(&kind) {
+                cmd_add_filesystem_asset(args, kind, &original_path)
+            } else {
+                cmd_add_discover_filesystem(args, kind, &original_path)
+            }
+        }
+    }
+}
+
+/// Add every URL or path listed in a file (or stdin for `-`), then write the
+/// manifest and sync once for all of them
+fn cmd_add_from_file(args: AddArgs, file: &Path) -> Result<()> {
+    let content = if file == Path::new("-") {
+        let mut content = String::new();
+        std::io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|e| ApsError::io(e, "Failed to read stdin"))?;
+        content
+    } else {
+        fs::read_to_string(file)
+            .map_err(|e| ApsError::io(e, format!("Failed to read {:?}", file)))?
+    };
+    // Lines are added unattended, so nothing prompts
+    let args = AddArgs {
+        yes: Approvals::ALL,
+        ..args
+    };
+
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut failed_lines = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let input = line.trim();
+        if input.is_empty() || input.starts_with('#') {
+            continue;
+        }
+        let line_no = index + 1;
+        match batch_entries(&args, input) {
+            Ok(found) => {
+                let new: Vec<Entry> = found
+                    .into_iter()
+                    .filter(|e| !entries.iter().any(|added| added.id == e.id))
+                    .collect();
+                let ids: Vec<&str> = new.iter().map(|e| e.id.as_str()).collect();
+                if ids.is_empty() {
+                    say!(
+                        "  {} {}",
+                        Style::new().dim().apply_to("·"),
+                        Style::new()
+                            .dim()
+                            .apply_to(format!("line {}: {} (nothing new)", line_no, input))
+                    );
+                } else {
+                    say!(
+                        "  {} line {}: {} -> {}",
+                        style("✓").green(),
+                        line_no,
+                        input,
+                        ids.join(", ")
+                    );
+                }
+                entries.extend(new);
+            }
+            Err(e) => {
+                say!(
+                    "  {} line {}: {}: {}",
+                    style("✗").red(),
+                    line_no,
+                    input,
+                    style(e).red()
+                );
+                failed_lines.push(line_no.to_string());
+            }
+        }
+    }
+    say!();
+
+    if !entries.is_empty() {
+        let (manifest_path, added_ids) = write_entries_to_manifest(
+            entries,
+            args.manifest.clone(),
+            show_diff(args.show_diff, args.no_show_diff),
+        )?;
+        if !added_ids.is_empty() {
+            info!("Added {} entries to {:?}", added_ids.len(), manifest_path);
+            say!(
+                "  {} {}\n",
+                style("✓").green(),
+                style(format!(
+                    "Added {} entries: {}",
+                    added_ids.len(),
+                    added_ids.join(", ")
+                ))
+                .green()
+            );
+        }
+        maybe_sync(&added_ids, args.no_sync, args.manifest.clone())?;
+    }
+
+    if !failed_lines.is_empty() {
+        return Err(ApsError::AddIncomplete {
+            count: failed_lines.len(),
+            lines: failed_lines.join(", "),
+        });
+    }
+    Ok(())
+}
+
+/// Builds the source for a discovered skill
+type SourceBuilder = Box<dyn Fn(&DiscoveredSkill) -> Source>;
+
+/// Entries for one line of a batch add, routed like a single `aps add`.
+/// Discovered skills are all added, except those already in the manifest.
+fn batch_entries(args: &AddArgs, input: &str) -> Result<Vec<Entry>> {
+    let target = parse_add_target(
+        input,
+        args.all,
+        &args.host,
+        args.gitlab_host.as_deref(),
+        args.r#ref.as_deref(),
+    )?;
+    let target = verify_add_ref(target, args)?;
+    let asset_kind = add_asset_kind(args, &target)?;
+
+    let (skills, source_builder, location): (_, SourceBuilder, _) = match target {
+        ParsedAddTarget::GitHubSkill {
+            repo_url,
+            git_ref,
+            skill_path,
+            skill_name,
+        } => {
+            let entry = single_git_entry(
+                args,
+                &asset_kind,
+                &repo_url,
+                &git_ref,
+                &skill_path,
+                skill_name,
+            );
+            check_duplicate_id(&entry.id, args.manifest.as_deref())?;
+            return Ok(vec![entry]);
+        }
         ParsedAddTarget::FilesystemSkill {
             original_path,
             skill_name,
-        } => cmd_add_single_filesystem(args, &original_path, &skill_name),
+        } => {
+            let entry = single_filesystem_entry(args, &asset_kind, &original_path, &skill_name);
+            check_duplicate_id(&entry.id, args.manifest.as_deref())?;
+            return Ok(vec![entry]);
+        }
+        ParsedAddTarget::FilesystemDiscovery { original_path }
+            if installs_whole_source(&asset_kind) =>
+        {
+            let entry = filesystem_asset_entry(args, &asset_kind, &original_path)?;
+            check_duplicate_id(&entry.id, args.manifest.as_deref())?;
+            return Ok(vec![entry]);
+        }
+        ParsedAddTarget::GitHubDiscovery {
+            repo_url,
+            git_ref,
+            search_path,
+        } => {
+            let skills = discover_skills_in_repo(&repo_url, &git_ref, &search_path, args.loose)?;
+            let repo = repo_url.clone();
+            let builder = move |skill: &DiscoveredSkill| Source::Git {
+                repo: repo.clone(),
+                r#ref: git_ref.clone(),
+                shallow: true,
+                path: Some(skill.repo_path.clone()),
+            };
+            (skills, Box::new(builder), repo_url)
+        }
         ParsedAddTarget::FilesystemDiscovery { original_path } => {
-            cmd_add_discover_filesystem(args, &original_path)
+            let skills = discover_skills_in_local_dir(&original_path, args.loose)?;
+            let root = original_path.clone();
+            let builder = move |skill: &DiscoveredSkill| Source::Filesystem {
+                root: root.clone(),
+                symlink: true,
+                path: Some(skill.repo_path.clone()),
+            };
+            (skills, Box::new(builder), original_path)
         }
+    };
+
+    if skills.is_empty() {
+        return Err(ApsError::NoSkillsFound { location });
     }
+    let existing = get_existing_entry_sources(args.manifest.as_deref());
+    let ids = resolve_discovered_ids(&skills, &source_builder, &existing, args, &location)?;
+    let default_dest = configured_default_dest(&asset_kind, args.manifest.as_deref());
+    let new: Vec<(&DiscoveredSkill, String)> = skills
+        .iter()
+        .zip(ids)
+        .filter(|(_, id)| !existing.contains_key(id))
+        .collect();
+    let new_ids: Vec<&str> = new.iter().map(|(_, id)| id.as_str()).collect();
+    let dests = discovered_dests(&new_ids, &default_dest, args.manifest.as_deref());
+    Ok(new
+        .iter()
+        .zip(dests)
+        .map(|((skill, id), dest)| {
+            discovered_entry(id.clone(), &asset_kind, source_builder(skill), dest)
+        })
+        .collect())
 }
 
 /// Convert CLI asset kind to manifest asset kind.
@@ -272,28 +839,219 @@ fn resolve_asset_kind(kind: &AddAssetKind) -> AssetKind {
     match kind {
         AddAssetKind::AgentSkill => AssetKind::AgentSkill,
         AddAssetKind::CursorRules => AssetKind::CursorRules,
+        AddAssetKind::CursorHooks => AssetKind::CursorHooks,
         AddAssetKind::CursorSkillsRoot => AssetKind::CursorSkillsRoot,
         AddAssetKind::AgentsMd => AssetKind::AgentsMd,
     }
 }
 
+/// Kinds `aps add` installs as one entry for the whole source, instead of
+/// discovering skills inside it
+fn installs_whole_source(kind: &AssetKind) -> bool {
+    matches!(
+        kind,
+        AssetKind::CursorRules | AssetKind::CursorHooks | AssetKind::AgentsMd
+    )
+}
+
+/// The kind to add: `--kind` if given, otherwise detected from the source
+/// and falling back to agent_skill. A detected kind other than agent_skill is
+/// confirmed interactively.
+fn add_asset_kind(args: &AddArgs, target: &ParsedAddTarget) -> Result<AssetKind> {
+    if let Some(kind) = &args.kind {
+        return Ok(resolve_asset_kind(kind));
+    }
+    let detected = match target {
+        ParsedAddTarget::GitHubSkill { skill_path, .. } => detect_asset_kind_from_name(skill_path),
+        ParsedAddTarget::GitHubDiscovery { .. } => None,
+        ParsedAddTarget::FilesystemSkill { original_path, .. }
+        | ParsedAddTarget::FilesystemDiscovery { original_path } => {
+            detect_asset_kind(&expand_local_path(original_path)?)
+        }
+    };
+    let Some((kind, reason)) = detected else {
+        return Ok(AssetKind::AgentSkill);
+    };
+    if kind == AssetKind::AgentSkill {
+        return Ok(kind);
+    }
+    if args.yes.any() || !interactive() {
+        say!(
+            "Detected {} ({}); pass --kind to override.\n",
+            kind.as_str(),
+            reason
+        );
+        return Ok(kind);
+    }
+
+    let choices = [
+        AssetKind::AgentSkill,
+        AssetKind::CursorRules,
+        AssetKind::CursorHooks,
+        AssetKind::CursorSkillsRoot,
+        AssetKind::AgentsMd,
+    ];
+    let names: Vec<&str> = choices.iter().map(AssetKind::as_str).collect();
+    let selection = dialoguer::Select::new()
+        .with_prompt(format!("Detected {} ({}). Add as", kind.as_str(), reason))
+        .items(&names)
+        .default(choices.iter().position(|k| *k == kind).unwrap_or(0))
+        .interact()
+        .map_err(|_| ApsError::Cancelled)?;
+    Ok(choices[selection].clone())
+}
+
+/// Expand shell variables in a local path and resolve it against the
+/// current directory
+fn expand_local_path(path: &str) -> Result<PathBuf> {
+    let expanded = shellexpand::full(path)
+        .map(|s| s.into_owned())
+        .unwrap_or_else(|_| path.to_string());
+    let expanded = Path::new(&expanded);
+    if expanded.is_relative() {
+        Ok(std::env::current_dir()
+            .map_err(|e| ApsError::io(e, "Failed to get current directory"))?
+            .join(expanded))
+    } else {
+        Ok(expanded.to_path_buf())
+    }
+}
+
 /// Compute the destination path for a skill entry.
-fn skill_dest(asset_kind: &AssetKind, entry_id: &str) -> String {
+///
+/// Namespaced IDs (`repo-slug/skill-name`) install into a folder named after
+/// the skill only, so the directory keeps matching the SKILL.md name.
+fn skill_dest(default_dest: &Path, entry_id: &str) -> String {
     format!(
         "{}/{}/",
-        asset_kind
-            .default_dest()
-            .to_string_lossy()
-            .trim_end_matches('/'),
-        entry_id
+        default_dest.to_string_lossy().trim_end_matches('/'),
+        dest_folder(entry_id)
     )
 }
 
+/// Destinations for new skill entries, in the order of `ids`.
+/// Each installs by its skill name unless another entry, existing or new,
+/// already uses that destination; then the whole ID names the folder, with a
+/// counter if that is taken too.
+fn discovered_dests(
+    ids: &[&str],
+    default_dest: &Path,
+    manifest_override: Option<&Path>,
+) -> Vec<String> {
+    let key = |dest: &str| {
+        dest.trim_start_matches("./")
+            .trim_end_matches('/')
+            .to_string()
+    };
+    let manifest = match manifest_override {
+        Some(p) => load_manifest(p).ok(),
+        None => discover_manifest(None).ok().map(|(m, _)| m),
+    };
+    let mut taken: HashSet<String> = manifest
+        .iter()
+        .flat_map(|m| &m.entries)
+        .map(|e| key(&e.destination().to_string_lossy()))
+        .collect();
+
+    ids.iter()
+        .map(|id| {
+            let mut dest = key(&skill_dest(default_dest, id));
+            if taken.contains(&dest) && id.contains('/') {
+                dest = key(&skill_dest(default_dest, &id.replace('/', "-")));
+            }
+            let dest = entry_id::unique(&dest, &taken);
+            taken.insert(dest.clone());
+            format!("{}/", dest)
+        })
+        .collect()
+}
+
+/// Destination for a single added skill, kept clear of existing entries'
+/// destinations like those of discovered skills
+fn single_skill_dest(
+    entry_id: &str,
+    default_dest: &Path,
+    manifest_override: Option<&Path>,
+) -> String {
+    discovered_dests(&[entry_id], default_dest, manifest_override)
+        .pop()
+        .unwrap_or_else(|| skill_dest(default_dest, entry_id))
+}
+
+/// Default destination for a kind in the target manifest, honoring its
+/// `dest_roots` when the manifest already exists.
+fn configured_default_dest(asset_kind: &AssetKind, manifest_override: Option<&Path>) -> PathBuf {
+    let manifest = match manifest_override {
+        Some(p) => load_manifest(p).ok(),
+        None => discover_manifest(None).ok().map(|(m, _)| m),
+    };
+    match manifest {
+        Some(manifest) => manifest.default_dest(asset_kind),
+        None => asset_kind.default_dest(),
+    }
+}
+
+/// Derive a short slug for a repository URL or local path
+/// (e.g., `https://github.com/owner/skills.git` -> `skills`).
+fn location_slug(location: &str) -> String {
+    let trimmed = location.trim_end_matches('/').trim_end_matches(".git");
+    let slug = trimmed
+        .rsplit(['/', ':', '\\'])
+        .next()
+        .filter(|s| !s.is_empty() && !s.starts_with('$') && *s != "~")
+        .unwrap_or("local");
+    slug.to_string()
+}
+
+/// Build a namespaced entry ID of the form `repo-slug/skill-name`.
+fn namespaced_id(location: &str, skill_name: &str) -> String {
+    format!("{}/{}", location_slug(location), skill_name)
+}
+
+/// Default ID for a single explicitly-added asset. Plain IDs that collide
+/// are reported as duplicates rather than silently namespaced.
+fn default_entry_id(style: IdStyle, location: &str, skill_name: &str) -> String {
+    match style {
+        IdStyle::Namespaced => namespaced_id(location, skill_name),
+        IdStyle::Plain => skill_name.to_string(),
+    }
+}
+
+/// Pick the entry ID for an auto-generated skill entry.
+///
+/// With `IdStyle::Plain` the skill name is used unless an existing entry with
+/// that ID points at a different source, in which case the namespaced form is
+/// used instead so that skills from different repos never collide.
+fn generate_entry_id(
+    style: IdStyle,
+    location: &str,
+    skill_name: &str,
+    source: &Source,
+    existing: &std::collections::HashMap<String, String>,
+) -> String {
+    match style {
+        IdStyle::Namespaced => namespaced_id(location, skill_name),
+        IdStyle::Plain => match existing.get(skill_name) {
+            Some(existing_source) if *existing_source != source.display_path() => {
+                namespaced_id(location, skill_name)
+            }
+            _ => skill_name.to_string(),
+        },
+    }
+}
+
+/// Whether `aps add`/`aps remove` print the manifest diff: when asked, or by
+/// default in interactive sessions
+fn show_diff(show_diff: bool, no_show_diff: bool) -> bool {
+    show_diff || (!no_show_diff && interactive())
+}
+
 /// Write entries to manifest, handling new manifest creation and deduplication.
 /// Returns the list of entry IDs that were actually added.
 fn write_entries_to_manifest(
     entries: Vec<Entry>,
     manifest_override: Option<std::path::PathBuf>,
+    show_diff: bool,
 ) -> Result<(std::path::PathBuf, Vec<String>)> {
     let manifest_path = match manifest_override {
         Some(p) => p,
@@ -303,16 +1061,38 @@ fn write_entries_to_manifest(
                 let path = std::env::current_dir()
                     .map_err(|e| ApsError::io(e, "Failed to get current directory"))?
                     .join(DEFAULT_MANIFEST_NAME);
-                println!("Creating new manifest at {:?}", path);
+                say!("Creating new manifest at {:?}", path);
 
                 let entry_ids: Vec<String> = entries.iter().map(|e| e.id.clone()).collect();
-                let manifest = Manifest { entries };
+                let mut entries = entries;
+                sort_entries(&mut entries);
+                let manifest = Manifest {
+                    entries,
+                    integrations: Default::default(),
+                    max_age: None,
+                    dest_roots: Default::default(),
+                    backup_strategy: Default::default(),
+                    targets: Vec::new(),
+                    check_uncommitted: false,
+                    suppress_warnings: Vec::new(),
+                    ci: Default::default(),
+                    backup: true,
+                    backup_dir: None,
+                    symlink_style: Default::default(),
+                    lockfiles: Default::default(),
+                    sort: false,
+                    compose_limits: Default::default(),
+                    github_tarballs: false,
+                };
 
                 let content =
                     serde_yaml::to_string(&manifest).map_err(|e| ApsError::ManifestParseError {
                         message: format!("Failed to serialize manifest: {}", e),
                     })?;
 
+                if show_diff {
+                    print_diff(&path, "", &content);
+                }
                 fs::write(&path, &content).map_err(|e| {
                     ApsError::io(e, format!("Failed to write manifest to {:?}", path))
                 })?;
@@ -323,8 +1103,10 @@ fn write_entries_to_manifest(
         },
     };
 
-    // Load existing manifest
-    let mut manifest = load_manifest(&manifest_path)?;
+    // Load existing manifest, keeping what was read to detect concurrent edits
+    let original = fs::read_to_string(&manifest_path)
+        .map_err(|e| ApsError::io(e, format!("Failed to read manifest at {:?}", manifest_path)))?;
+    let mut manifest = parse_manifest(&original)?;
 
     // Deduplicate
     let mut added_ids = Vec::new();
@@ -335,13 +1117,16 @@ fn write_entries_to_manifest(
             skipped_ids.push(entry.id.clone());
         } else {
             added_ids.push(entry.id.clone());
-            manifest.entries.push(entry.clone());
+            insert_sorted(&mut manifest.entries, entry.clone());
         }
     }
+    if manifest.sort {
+        sort_entries(&mut manifest.entries);
+    }
 
     if !skipped_ids.is_empty() {
         let dim = Style::new().dim();
-        println!(
+        say!(
             "  {} {}\n",
             dim.apply_to("·"),
             dim.apply_to(format!(
@@ -353,7 +1138,7 @@ fn write_entries_to_manifest(
     }
 
     if added_ids.is_empty() {
-        println!(
+        say!(
             "{}",
             Style::new()
                 .dim()
@@ -362,17 +1147,7 @@ fn write_entries_to_manifest(
         return Ok((manifest_path, added_ids));
     }
 
-    // Write back
-    let content = serde_yaml::to_string(&manifest).map_err(|e| ApsError::ManifestParseError {
-        message: format!("Failed to serialize manifest: {}", e),
-    })?;
-
-    fs::write(&manifest_path, &content).map_err(|e| {
-        ApsError::io(
-            e,
-            format!("Failed to write manifest to {:?}", manifest_path),
-        )
-    })?;
+    write_manifest_edit(&manifest, &manifest_path, &original, show_diff)?;
 
     Ok((manifest_path, added_ids))
 }
@@ -388,20 +1163,36 @@ fn maybe_sync(
     }
 
     if !no_sync {
-        println!("Syncing...\n");
+        say!("Syncing...\n");
         cmd_sync(SyncArgs {
             manifest: manifest_override,
             only: entry_ids.to_vec(),
-            yes: true,
+            yes: Approvals::ALL,
             ignore_manifest: false,
             dry_run: false,
             strict: false,
             upgrade: false,
+            force: false,
+            retry_failed: false,
+            keep_going: false,
+            entry_timeout: None,
+            timeout: None,
+            plan: None,
+            only_kind: Vec::new(),
+            offline: false,
+            refresh: RefreshPolicy::Always,
+            stale_after: None,
+            explain: false,
+            jobs: None,
+            low_bandwidth: false,
+            overrides: Vec::new(),
+            porcelain: false,
+            review: false,
         })?;
     } else {
-        println!(
-            "Run `aps sync` to install the skill{}.",
-            if entry_ids.len() > 1 { "s" } else { "" }
+        say!(
+            "Run `aps sync` to install {}.",
+            if entry_ids.len() > 1 { "them" } else { "it" }
         );
     }
 
@@ -412,42 +1203,99 @@ fn maybe_sync(
 // Git / GitHub add adapters
 // ============================================================================
 
-/// Add a single skill from a GitHub URL.
-fn cmd_add_single_git(
-    args: AddArgs,
+/// Build the entry for a single asset in a git repository.
+fn single_git_entry(
+    args: &AddArgs,
+    asset_kind: &AssetKind,
     repo_url: &str,
     git_ref: &str,
     skill_path: &str,
     skill_name: Option<String>,
-) -> Result<()> {
-    let entry_id = args
-        .id
-        .unwrap_or_else(|| skill_name.unwrap_or_else(|| "unnamed-skill".to_string()));
-
-    // For single-skill adds, check for duplicate ID upfront
-    check_duplicate_id(&entry_id, args.manifest.as_deref())?;
+) -> Entry {
+    let (source_path, include) = split_rule_file(asset_kind, skill_path);
+    let source = Source::Git {
+        repo: repo_url.to_string(),
+        r#ref: git_ref.to_string(),
+        shallow: true,
+        path: (!source_path.is_empty()).then(|| source_path.to_string()),
+    };
+    let entry_id = match args.id.clone() {
+        Some(id) => id,
+        None => {
+            let skill_name = skill_name.unwrap_or_else(|| "unnamed-skill".to_string());
+            let skill_name = match skill_path.rsplit_once('/') {
+                Some((parent, _)) => whole_source_name(parent, &skill_name),
+                None => whole_source_name(repo_url, &skill_name),
+            };
+            default_entry_id(args.id_style, repo_url, &skill_name)
+        }
+    };
 
-    let asset_kind = resolve_asset_kind(&args.kind);
+    let default_dest = configured_default_dest(asset_kind, args.manifest.as_deref());
+    let dest = if installs_whole_source(asset_kind) {
+        default_dest.to_string_lossy().to_string()
+    } else {
+        single_skill_dest(&entry_id, &default_dest, args.manifest.as_deref())
+    };
 
-    let entry = Entry {
-        id: entry_id.clone(),
+    Entry {
+        id: entry_id,
         kind: asset_kind.clone(),
-        source: Some(Source::Git {
-            repo: repo_url.to_string(),
-            r#ref: git_ref.to_string(),
-            shallow: true,
-            path: Some(skill_path.to_string()),
-        }),
+        source: Some(source),
         sources: Vec::new(),
-        dest: Some(skill_dest(&asset_kind, &entry_id)),
-        include: Vec::new(),
-    };
+        dest: Some(dest),
+        include,
+        max_age: None,
+        preserve_permissions: true,
+        preserve_mtime: false,
+        hooks_merge: Default::default(),
+        default_dest_override: None,
+        checksum_ignore: Vec::new(),
+        description: None,
+        owner: None,
+        copy_license: false,
+        also_install_for: Vec::new(),
+        frozen: false,
+        targets: Vec::new(),
+        transform: Vec::new(),
+        suppress_warnings: Vec::new(),
+        backup: None,
+        backup_dir: None,
+        symlink_style: None,
+    }
+}
+
+/// Add a single skill from a GitHub URL.
+fn cmd_add_single_git(
+    args: AddArgs,
+    asset_kind: AssetKind,
+    repo_url: &str,
+    git_ref: &str,
+    skill_path: &str,
+    skill_name: Option<String>,
+) -> Result<()> {
+    let entry = single_git_entry(
+        &args,
+        &asset_kind,
+        repo_url,
+        git_ref,
+        skill_path,
+        skill_name,
+    );
+    let entry_id = entry.id.clone();
+
+    // For single-skill adds, check for duplicate ID upfront
+    check_duplicate_id(&entry_id, args.manifest.as_deref())?;
 
-    let (manifest_path, added_ids) = write_entries_to_manifest(vec![entry], args.manifest.clone())?;
+    let (manifest_path, added_ids) = write_entries_to_manifest(
+        vec![entry],
+        args.manifest.clone(),
+        show_diff(args.show_diff, args.no_show_diff),
+    )?;
 
     if !added_ids.is_empty() {
         info!("Added entry '{}' to {:?}", entry_id, manifest_path);
-        println!(
+        say!(
             "  {} {}\n",
             style("✓").green(),
             style(format!("Added entry '{}'", entry_id)).green()
@@ -460,51 +1308,103 @@ fn cmd_add_single_git(
 /// Discover and add skills from a GitHub repository.
 fn cmd_add_discover_git(
     args: AddArgs,
+    asset_kind: AssetKind,
     repo_url: &str,
     git_ref: &str,
     search_path: &str,
 ) -> Result<()> {
-    println!("Searching for skills in {}...\n", repo_url);
-    let skills = discover_skills_in_repo(repo_url, git_ref, search_path)?;
+    say!("Searching for skills in {}...\n", repo_url);
+    let skills = discover_skills_in_repo(repo_url, git_ref, search_path, args.loose)?;
     let source_builder = |skill: &DiscoveredSkill| Source::Git {
         repo: repo_url.to_string(),
         r#ref: git_ref.to_string(),
         shallow: true,
         path: Some(skill.repo_path.clone()),
     };
-    cmd_add_discovered(args, skills, source_builder, repo_url)
+    cmd_add_discovered(args, asset_kind, skills, source_builder, repo_url)
 }
 
 // ============================================================================
 // Filesystem add adapters
 // ============================================================================
 
-/// Add a single skill from a local filesystem path.
-fn cmd_add_single_filesystem(args: AddArgs, original_path: &str, skill_name: &str) -> Result<()> {
-    let entry_id = args.id.unwrap_or_else(|| skill_name.to_string());
-
-    check_duplicate_id(&entry_id, args.manifest.as_deref())?;
+/// Build the entry for a single skill in a local directory.
+fn single_filesystem_entry(
+    args: &AddArgs,
+    asset_kind: &AssetKind,
+    original_path: &str,
+    skill_name: &str,
+) -> Entry {
+    let source = Source::Filesystem {
+        root: original_path.to_string(),
+        symlink: true,
+        path: None,
+    };
+    // A single local skill is namespaced by its parent directory
+    let parent = original_path
+        .trim_end_matches('/')
+        .rsplit_once('/')
+        .map(|(parent, _)| parent)
+        .unwrap_or(".");
+    let entry_id = match args.id.clone() {
+        Some(id) => id,
+        None => default_entry_id(args.id_style, parent, skill_name),
+    };
 
-    let asset_kind = resolve_asset_kind(&args.kind);
+    let default_dest = configured_default_dest(asset_kind, args.manifest.as_deref());
 
-    let entry = Entry {
+    Entry {
         id: entry_id.clone(),
         kind: asset_kind.clone(),
-        source: Some(Source::Filesystem {
-            root: original_path.to_string(),
-            symlink: true,
-            path: None,
-        }),
+        source: Some(source),
         sources: Vec::new(),
-        dest: Some(skill_dest(&asset_kind, &entry_id)),
+        dest: Some(single_skill_dest(
+            &entry_id,
+            &default_dest,
+            args.manifest.as_deref(),
+        )),
         include: Vec::new(),
-    };
+        max_age: None,
+        preserve_permissions: true,
+        preserve_mtime: false,
+        hooks_merge: Default::default(),
+        default_dest_override: None,
+        checksum_ignore: Vec::new(),
+        description: None,
+        owner: None,
+        copy_license: false,
+        also_install_for: Vec::new(),
+        frozen: false,
+        targets: Vec::new(),
+        transform: Vec::new(),
+        suppress_warnings: Vec::new(),
+        backup: None,
+        backup_dir: None,
+        symlink_style: None,
+    }
+}
+
+/// Add a single skill from a local filesystem path.
+fn cmd_add_single_filesystem(
+    args: AddArgs,
+    asset_kind: AssetKind,
+    original_path: &str,
+    skill_name: &str,
+) -> Result<()> {
+    let entry = single_filesystem_entry(&args, &asset_kind, original_path, skill_name);
+    let entry_id = entry.id.clone();
+
+    check_duplicate_id(&entry_id, args.manifest.as_deref())?;
 
-    let (manifest_path, added_ids) = write_entries_to_manifest(vec![entry], args.manifest.clone())?;
+    let (manifest_path, added_ids) = write_entries_to_manifest(
+        vec![entry],
+        args.manifest.clone(),
+        show_diff(args.show_diff, args.no_show_diff),
+    )?;
 
     if !added_ids.is_empty() {
         info!("Added entry '{}' to {:?}", entry_id, manifest_path);
-        println!(
+        say!(
             "  {} {}\n",
             style("✓").green(),
             style(format!("Added entry '{}'", entry_id)).green()
@@ -515,15 +1415,212 @@ fn cmd_add_single_filesystem(args: AddArgs, original_path: &str, skill_name: &st
 }
 
 /// Discover and add skills from a local filesystem directory.
-fn cmd_add_discover_filesystem(args: AddArgs, original_path: &str) -> Result<()> {
-    println!("Searching for skills in {}...\n", original_path);
-    let skills = discover_skills_in_local_dir(original_path)?;
+fn cmd_add_discover_filesystem(
+    args: AddArgs,
+    asset_kind: AssetKind,
+    original_path: &str,
+) -> Result<()> {
+    say!("Searching for skills in {}...\n", original_path);
+    let skills = discover_skills_in_local_dir(original_path, args.loose)?;
     let source_builder = |skill: &DiscoveredSkill| Source::Filesystem {
         root: original_path.to_string(),
         symlink: true,
         path: Some(skill.repo_path.clone()),
     };
-    cmd_add_discovered(args, skills, source_builder, original_path)
+    cmd_add_discovered(args, asset_kind, skills, source_builder, original_path)
+}
+
+/// Add a local file or directory as one entry of a kind that installs the
+/// whole source, such as a folder of Cursor rules or an AGENTS.md file.
+fn cmd_add_filesystem_asset(
+    args: AddArgs,
+    asset_kind: AssetKind,
+    original_path: &str,
+) -> Result<()> {
+    let mut entry = filesystem_asset_entry(&args, &asset_kind, original_path)?;
+    if picks_rule_files(&args, &entry) {
+        entry.include = select_rule_files(&expand_local_path(original_path)?)?;
+    }
+    add_whole_source_entry(args, asset_kind, entry)
+}
+
+/// Add a folder of Cursor rules from a git repository as one entry, after
+/// picking which rule files to keep
+fn cmd_add_git_rules(
+    args: AddArgs,
+    asset_kind: AssetKind,
+    repo_url: &str,
+    git_ref: &str,
+    rules_path: &str,
+    name: Option<String>,
+) -> Result<()> {
+    let name = name
+        .or_else(|| {
+            rules_path
+                .rsplit('/')
+                .next()
+                .filter(|n| !n.is_empty())
+                .map(String::from)
+        })
+        .unwrap_or_else(|| location_slug(repo_url));
+    let mut entry = single_git_entry(
+        &args,
+        &asset_kind,
+        repo_url,
+        git_ref,
+        rules_path,
+        Some(name),
+    );
+    if picks_rule_files(&args, &entry) {
+        let resolved = clone_and_resolve(repo_url, git_ref, true, Some(rules_path))?;
+        entry.include = select_rule_files(&resolved.repo_path.join(rules_path))?;
+    }
+    add_whole_source_entry(args, asset_kind, entry)
+}
+
+/// Whether to offer a picker over a Cursor rules folder: not for a single
+/// rule file, with `--all`, or without a terminal
+fn picks_rule_files(args: &AddArgs, entry: &Entry) -> bool {
+    entry.kind == AssetKind::CursorRules && entry.include.is_empty() && !args.all && interactive()
+}
+
+/// Pick which files of a Cursor rules folder to install. Returns the
+/// `include` list, empty when every rule is kept.
+fn select_rule_files(dir: &Path) -> Result<Vec<String>> {
+    let rules = find_rule_files(dir);
+    if rules.len() < 2 {
+        return Ok(Vec::new());
+    }
+    say!("Found {} rule(s):\n", style(rules.len()).bold());
+    let kept = prompt_rule_selection(&rules)?;
+    if kept.is_empty() {
+        return Err(ApsError::NoRulesSelected);
+    }
+    if kept.len() == rules.len() {
+        return Ok(Vec::new());
+    }
+    Ok(kept.iter().map(|&i| rules[i].repo_path.clone()).collect())
+}
+
+/// Write an entry that installs a whole source and sync it
+fn add_whole_source_entry(args: AddArgs, asset_kind: AssetKind, entry: Entry) -> Result<()> {
+    let entry_id = entry.id.clone();
+
+    check_duplicate_id(&entry_id, args.manifest.as_deref())?;
+
+    let (manifest_path, added_ids) = write_entries_to_manifest(
+        vec![entry],
+        args.manifest.clone(),
+        show_diff(args.show_diff, args.no_show_diff),
+    )?;
+
+    if !added_ids.is_empty() {
+        info!("Added entry '{}' to {:?}", entry_id, manifest_path);
+        say!(
+            "  {} {}\n",
+            style("✓").green(),
+            style(format!(
+                "Added {} entry '{}'",
+                asset_kind.as_str(),
+                entry_id
+            ))
+            .green()
+        );
+    }
+
+    maybe_sync(&added_ids, args.no_sync, args.manifest)
+}
+
+/// Build the entry for a local file or directory installed as a whole.
+fn filesystem_asset_entry(
+    args: &AddArgs,
+    asset_kind: &AssetKind,
+    original_path: &str,
+) -> Result<Entry> {
+    let path = expand_local_path(original_path)?;
+    let trimmed = original_path.trim_end_matches('/');
+    let (parent, name) = trimmed.rsplit_once('/').unwrap_or((".", trimmed));
+
+    // Hooks sit in a `hooks/` folder next to their hooks.json
+    let path_within = if *asset_kind == AssetKind::AgentsMd && path.is_dir() {
+        Some("AGENTS.md".to_string())
+    } else if *asset_kind == AssetKind::CursorHooks && path.join("hooks").is_dir() {
+        Some("hooks".to_string())
+    } else {
+        None
+    };
+    let (root, include) = split_rule_file(asset_kind, trimmed);
+    let entry_id = match args.id.clone() {
+        Some(id) => id,
+        None => {
+            let folder = path
+                .parent()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            default_entry_id(args.id_style, parent, &whole_source_name(&folder, name))
+        }
+    };
+
+    let default_dest = configured_default_dest(asset_kind, args.manifest.as_deref());
+    Ok(Entry {
+        id: entry_id,
+        kind: asset_kind.clone(),
+        source: Some(Source::Filesystem {
+            root: if root.is_empty() { "." } else { root }.to_string(),
+            symlink: true,
+            path: path_within,
+        }),
+        sources: Vec::new(),
+        dest: Some(default_dest.to_string_lossy().to_string()),
+        include,
+        max_age: None,
+        preserve_permissions: true,
+        preserve_mtime: false,
+        hooks_merge: Default::default(),
+        default_dest_override: None,
+        checksum_ignore: Vec::new(),
+        description: None,
+        owner: None,
+        copy_license: false,
+        also_install_for: Vec::new(),
+        frozen: false,
+        targets: Vec::new(),
+        transform: Vec::new(),
+        suppress_warnings: Vec::new(),
+        backup: None,
+        backup_dir: None,
+        symlink_style: None,
+    })
+}
+
+/// Entry name for a source file or folder: an AGENTS.md is named after the
+/// folder it sits in, and Markdown files lose their extension
+fn whole_source_name(parent: &str, name: &str) -> String {
+    if name.eq_ignore_ascii_case("AGENTS.md") {
+        match location_slug(parent).as_str() {
+            "." | "local" => "agents".to_string(),
+            slug => slug.to_string(),
+        }
+    } else {
+        name.strip_suffix(".mdc")
+            .or_else(|| name.strip_suffix(".md"))
+            .unwrap_or(name)
+            .to_string()
+    }
+}
+
+/// Split a single rule file into its folder and an `include` for just that
+/// file, since rules install as a folder. Other sources are returned as is.
+fn split_rule_file<'a>(kind: &AssetKind, path: &'a str) -> (&'a str, Vec<String>) {
+    let is_rule_file = Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("mdc"));
+    if *kind != AssetKind::CursorRules || !is_rule_file {
+        return (path, Vec::new());
+    }
+    let (folder, file) = path.rsplit_once('/').unwrap_or(("", path));
+    (folder, vec![file.to_string()])
 }
 
 // ============================================================================
@@ -537,6 +1634,7 @@ use crate::discover::DiscoveredSkill;
 /// Shows ALL skills with installed ones pre-checked; unchecking removes them.
 fn cmd_add_discovered(
     args: AddArgs,
+    asset_kind: AssetKind,
     skills: Vec<DiscoveredSkill>,
     source_builder: impl Fn(&DiscoveredSkill) -> Source,
     location: &str,
@@ -547,60 +1645,65 @@ fn cmd_add_discovered(
         });
     }
 
-    let existing_ids = get_existing_entry_ids(args.manifest.as_deref());
+    let existing = get_existing_entry_sources(args.manifest.as_deref());
+    let skill_ids = resolve_discovered_ids(&skills, &source_builder, &existing, &args, location)?;
 
     // Build defaults: true for already-installed, false for new
-    let defaults: Vec<bool> = skills
+    let defaults: Vec<bool> = skill_ids
         .iter()
-        .map(|s| existing_ids.contains(&s.name))
+        .map(|id| existing.contains_key(id))
         .collect();
 
     let installed_count = defaults.iter().filter(|&&d| d).count();
     let new_count = skills.len() - installed_count;
-    println!(
+    say!(
         "Found {} skill(s) ({}, {}):\n",
         style(skills.len()).bold(),
         style(format!("{} installed", installed_count)).green(),
         style(format!("{} new", new_count)).cyan()
     );
+    let heuristic_count = skills.iter().filter(|s| s.heuristic.is_some()).count();
+    if heuristic_count > 0 {
+        say!(
+            "{}\n",
+            style(format!(
+                "{} found by --loose heuristics, without a SKILL.md",
+                heuristic_count
+            ))
+            .yellow()
+        );
+    }
 
     let selected_indices = select_skills(&skills, &defaults, args.all)?;
-    let selected_names: std::collections::HashSet<&str> = selected_indices
-        .iter()
-        .map(|&i| skills[i].name.as_str())
-        .collect();
+    let is_selected = |i: usize| selected_indices.contains(&i);
 
     // Compute delta
-    let to_add: Vec<&DiscoveredSkill> = selected_indices
+    let to_add: Vec<usize> = selected_indices
         .iter()
-        .map(|&i| &skills[i])
-        .filter(|s| !existing_ids.contains(&s.name))
+        .copied()
+        .filter(|&i| !defaults[i])
         .collect();
-    let to_remove: Vec<&str> = existing_ids
-        .iter()
-        .filter(|id| {
-            // Only remove if the skill was discovered (so it appeared in the picker)
-            // and was unchecked
-            skills.iter().any(|s| &s.name == *id) && !selected_names.contains(id.as_str())
-        })
-        .map(|s| s.as_str())
+    // Only remove skills that appeared in the picker and were unchecked
+    let to_remove: Vec<&str> = (0..skills.len())
+        .filter(|&i| defaults[i] && !is_selected(i))
+        .map(|i| skill_ids[i].as_str())
         .collect();
     let unchanged: Vec<&str> = selected_indices
         .iter()
-        .map(|&i| skills[i].name.as_str())
-        .filter(|name| existing_ids.contains(*name))
+        .filter(|&&i| defaults[i])
+        .map(|&i| skill_ids[i].as_str())
         .collect();
 
     // Show confirmation summary
     let dim = Style::new().dim();
 
-    println!();
+    say!();
     if !to_add.is_empty() {
         let names: Vec<String> = to_add
             .iter()
-            .map(|s| style(&s.name).bold().to_string())
+            .map(|&i| style(&skill_ids[i]).bold().to_string())
             .collect();
-        println!(
+        say!(
             "  {} {} {}",
             style("✓").green().bold(),
             style("Will add:").green(),
@@ -612,7 +1715,7 @@ fn cmd_add_discovered(
             .iter()
             .map(|s| style(s).bold().to_string())
             .collect();
-        println!(
+        say!(
             "  {} {} {}",
             style("✗").red().bold(),
             style("Will remove:").red(),
@@ -620,7 +1723,7 @@ fn cmd_add_discovered(
         );
     }
     if !unchanged.is_empty() {
-        println!(
+        say!(
             "  {} {} {}",
             dim.apply_to("·"),
             dim.apply_to("Unchanged:"),
@@ -629,13 +1732,18 @@ fn cmd_add_discovered(
     }
 
     if to_add.is_empty() && to_remove.is_empty() {
-        println!("\n{}", dim.apply_to("No changes to make."));
+        say!("\n{}", dim.apply_to("No changes to make."));
         return Ok(());
     }
 
-    // Prompt for confirmation unless --yes or --all
-    if !args.yes && !args.all {
-        println!();
+    // Prompt for confirmation unless --yes (covering removals, if any) or --all
+    let approved = if to_remove.is_empty() {
+        args.yes.any()
+    } else {
+        args.yes.approve(Prompt::Remove)
+    };
+    if !approved && !args.all {
+        say!();
         let confirm = dialoguer::Confirm::new()
             .with_prompt("Proceed?")
             .default(true)
@@ -647,18 +1755,23 @@ fn cmd_add_discovered(
                 )
             })?;
         if !confirm {
-            println!("Cancelled.");
+            say!("Cancelled.");
             return Ok(());
         }
     }
 
-    println!();
+    say!();
 
     // Execute removes
     if !to_remove.is_empty() {
         let remove_ids: Vec<String> = to_remove.iter().map(|s| s.to_string()).collect();
-        remove_entries_from_manifest(&remove_ids, args.manifest.as_deref())?;
-        println!(
+        remove_entries_from_manifest(
+            &remove_ids,
+            args.manifest.as_deref(),
+            show_diff(args.show_diff, args.no_show_diff),
+            false,
+        )?;
+        say!(
             "  {} {}\n",
             style("✗").red(),
             style(format!(
@@ -672,41 +1785,32 @@ fn cmd_add_discovered(
 
     // Execute adds
     if !to_add.is_empty() {
-        // Detect duplicate names among selected skills
-        let mut name_counts = std::collections::HashMap::new();
-        for skill in &to_add {
-            *name_counts.entry(skill.name.as_str()).or_insert(0usize) += 1;
-        }
-        let make_id = |skill: &DiscoveredSkill| -> String {
-            if name_counts.get(skill.name.as_str()).copied().unwrap_or(0) > 1 {
-                skill.repo_path.replace('/', "-")
-            } else {
-                skill.name.clone()
-            }
-        };
-
-        let asset_kind = resolve_asset_kind(&args.kind);
+        let default_dest = configured_default_dest(&asset_kind, args.manifest.as_deref());
 
+        let ids: Vec<&str> = to_add.iter().map(|&i| skill_ids[i].as_str()).collect();
+        let dests = discovered_dests(&ids, &default_dest, args.manifest.as_deref());
         let entries: Vec<Entry> = to_add
             .iter()
-            .map(|skill| {
-                let id = make_id(skill);
-                Entry {
-                    id: id.clone(),
-                    kind: asset_kind.clone(),
-                    source: Some(source_builder(skill)),
-                    sources: Vec::new(),
-                    dest: Some(skill_dest(&asset_kind, &id)),
-                    include: Vec::new(),
-                }
+            .zip(dests)
+            .map(|(&i, dest)| {
+                discovered_entry(
+                    skill_ids[i].clone(),
+                    &asset_kind,
+                    source_builder(&skills[i]),
+                    dest,
+                )
             })
             .collect();
 
-        let (manifest_path, added_ids) = write_entries_to_manifest(entries, args.manifest.clone())?;
+        let (manifest_path, added_ids) = write_entries_to_manifest(
+            entries,
+            args.manifest.clone(),
+            show_diff(args.show_diff, args.no_show_diff),
+        )?;
 
         if !added_ids.is_empty() {
             info!("Added {} entries to {:?}", added_ids.len(), manifest_path);
-            println!(
+            say!(
                 "  {} {}\n",
                 style("✓").green(),
                 style(format!(
@@ -724,20 +1828,195 @@ fn cmd_add_discovered(
     Ok(())
 }
 
-/// Get the set of entry IDs already present in the manifest.
-fn get_existing_entry_ids(manifest_override: Option<&Path>) -> std::collections::HashSet<String> {
+/// Build the entry for a discovered skill.
+fn discovered_entry(id: String, asset_kind: &AssetKind, source: Source, dest: String) -> Entry {
+    Entry {
+        dest: Some(dest),
+        id,
+        kind: asset_kind.clone(),
+        source: Some(source),
+        sources: Vec::new(),
+        include: Vec::new(),
+        max_age: None,
+        preserve_permissions: true,
+        preserve_mtime: false,
+        hooks_merge: Default::default(),
+        default_dest_override: None,
+        checksum_ignore: Vec::new(),
+        description: None,
+        owner: None,
+        copy_license: false,
+        also_install_for: Vec::new(),
+        frozen: false,
+        targets: Vec::new(),
+        transform: Vec::new(),
+        suppress_warnings: Vec::new(),
+        backup: None,
+        backup_dir: None,
+        symlink_style: None,
+    }
+}
+
+/// Resolve the entry ID for every discovered skill.
+///
+/// IDs come from `--id-template` when given, otherwise from `--id-style`.
+/// Skills that share a name within the same location fall back to an ID
+/// derived from their repo path. IDs are normalized, and any still shared
+/// get a counter so that every ID in the picker is unique.
+fn resolve_discovered_ids(
+    skills: &[DiscoveredSkill],
+    source_builder: &impl Fn(&DiscoveredSkill) -> Source,
+    existing: &std::collections::HashMap<String, String>,
+    args: &AddArgs,
+    location: &str,
+) -> Result<Vec<String>> {
+    let mut name_counts = std::collections::HashMap::new();
+    for skill in skills {
+        *name_counts.entry(skill.name.as_str()).or_insert(0usize) += 1;
+    }
+
+    let repo = location_slug(location);
+    let mut taken = HashSet::new();
+    let mut ids = Vec::new();
+    for skill in skills {
+        let id = match &args.id_template {
+            Some(template) => {
+                let parts = IdParts {
+                    repo: &repo,
+                    name: &skill.name,
+                    path: &skill.repo_path,
+                };
+                entry_id::render_template(template, &parts)?
+            }
+            None => {
+                let name = if name_counts.get(skill.name.as_str()).copied().unwrap_or(0) > 1 {
+                    skill.repo_path.replace('/', "-")
+                } else {
+                    skill.name.clone()
+                };
+                normalize_id(&generate_entry_id(
+                    args.id_style,
+                    location,
+                    &name,
+                    &source_builder(skill),
+                    existing,
+                ))
+            }
+        };
+        let id = entry_id::unique(&id, &taken);
+        taken.insert(id.clone());
+        ids.push(id);
+    }
+    Ok(ids)
+}
+
+/// Get the entry IDs already present in the manifest, mapped to their source display path.
+fn get_existing_entry_sources(
+    manifest_override: Option<&Path>,
+) -> std::collections::HashMap<String, String> {
     let manifest_result = match manifest_override {
         Some(p) => load_manifest(p).ok(),
         None => discover_manifest(None).ok().map(|(m, _)| m),
     };
     match manifest_result {
-        Some(manifest) => manifest.entries.iter().map(|e| e.id.clone()).collect(),
-        None => std::collections::HashSet::new(),
+        Some(manifest) => manifest
+            .entries
+            .iter()
+            .map(|e| {
+                let source = e.source.as_ref().map(|s| s.display_path());
+                (e.id.clone(), source.unwrap_or_default())
+            })
+            .collect(),
+        None => std::collections::HashMap::new(),
+    }
+}
+
+/// Installed paths that removing `ids` deletes: each entry's locked
+/// destination (or its manifest `dest` when it was never synced) and mirrors.
+///
+/// A destination that is a kind's default root, or that another entry's
+/// destination equals, contains, or sits inside, is shared: only the
+/// symlinks this entry installed there are deleted.
+fn removal_paths(
+    manifest: &Manifest,
+    lockfile: Option<&Lockfile>,
+    base_dir: &Path,
+    ids: &[String],
+) -> Vec<PathBuf> {
+    let shared: Vec<PathBuf> = AssetKind::ALL
+        .iter()
+        .flat_map(|kind| [kind.default_dest(), manifest.default_dest(kind)])
+        .map(|root| base_dir.join(root))
+        .collect();
+    let other_dests = manifest
+        .entries
+        .iter()
+        .filter(|e| !ids.contains(&e.id))
+        .flat_map(|entry| {
+            let locked = lockfile
+                .and_then(|l| l.entries.get(&entry.id))
+                .map(|locked| PathBuf::from(&locked.dest));
+            locked
+                .into_iter()
+                .chain([entry.destination()])
+                .chain(entry.mirror_destinations().unwrap_or_default())
+        })
+        .map(|dest| base_dir.join(dest))
+        .collect::<Vec<_>>();
+
+    let mut paths = Vec::new();
+    for entry in manifest.entries.iter().filter(|e| ids.contains(&e.id)) {
+        let locked = lockfile.and_then(|l| l.entries.get(&entry.id));
+        let dest = locked
+            .map(|locked| PathBuf::from(&locked.dest))
+            .or_else(|| entry.dest.as_ref().map(|_| entry.destination()));
+        let installed = dest
+            .into_iter()
+            .chain(entry.mirror_destinations().unwrap_or_default())
+            .map(|path| base_dir.join(path));
+        for path in installed {
+            let is_shared = shared.contains(&path)
+                || other_dests.iter().any(|other| paths_overlap(other, &path));
+            if !is_shared {
+                paths.push(path);
+            } else if let Some(locked) = locked {
+                paths.extend(owned_links(&path, &locked.symlinked_items));
+            }
+        }
+    }
+    paths.retain(|path| path.symlink_metadata().is_ok());
+    paths
+}
+
+/// Symlinks under `dest` (or `dest` itself) that point at one of `items`
+fn owned_links(dest: &Path, items: &[String]) -> Vec<PathBuf> {
+    let targets: Vec<PathBuf> = items
+        .iter()
+        .filter_map(|item| fs::canonicalize(item).ok())
+        .collect();
+    if targets.is_empty() {
+        return Vec::new();
     }
+    walkdir::WalkDir::new(dest)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path_is_symlink())
+        .filter(|entry| {
+            fs::canonicalize(entry.path())
+                .map(|target| targets.contains(&target))
+                .unwrap_or(false)
+        })
+        .map(|entry| entry.into_path())
+        .collect()
 }
 
 /// Remove entries from the manifest, lockfile, and installed files.
-fn remove_entries_from_manifest(ids: &[String], manifest_override: Option<&Path>) -> Result<()> {
+fn remove_entries_from_manifest(
+    ids: &[String],
+    manifest_override: Option<&Path>,
+    show_diff: bool,
+    keep_files: bool,
+) -> Result<()> {
     let manifest_path = match manifest_override {
         Some(p) => p.to_path_buf(),
         None => {
@@ -746,59 +2025,211 @@ fn remove_entries_from_manifest(ids: &[String], manifest_override: Option<&Path>
         }
     };
 
-    let mut manifest = load_manifest(&manifest_path)?;
+    let original = fs::read_to_string(&manifest_path)
+        .map_err(|e| ApsError::io(e, format!("Failed to read manifest at {:?}", manifest_path)))?;
+    let mut manifest = parse_manifest(&original)?;
     let base_dir = manifest_dir(&manifest_path);
+    let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &manifest)?;
+    let lockfile = Lockfile::load(&lockfile_path).ok();
 
-    // Collect dest paths before removing entries
-    let dest_paths: Vec<(String, Option<String>)> = manifest
-        .entries
-        .iter()
-        .filter(|e| ids.contains(&e.id))
-        .map(|e| (e.id.clone(), e.dest.clone()))
-        .collect();
+    // Collect installed paths before removing entries
+    let installed = if keep_files {
+        Vec::new()
+    } else {
+        removal_paths(&manifest, lockfile.as_ref(), &base_dir, ids)
+    };
 
     // Remove entries from manifest
     manifest.entries.retain(|e| !ids.contains(&e.id));
-
-    let content = serde_yaml::to_string(&manifest).map_err(|e| ApsError::ManifestParseError {
-        message: format!("Failed to serialize manifest: {}", e),
-    })?;
-    fs::write(&manifest_path, &content).map_err(|e| {
-        ApsError::io(
-            e,
-            format!("Failed to write manifest to {:?}", manifest_path),
-        )
-    })?;
+    write_manifest_edit(&manifest, &manifest_path, &original, show_diff)?;
 
     // Remove from lockfile
-    let lockfile_path = Lockfile::path_for_manifest(&manifest_path);
-    if let Ok(mut lockfile) = Lockfile::load(&lockfile_path) {
+    if let Some(mut lockfile) = lockfile {
         let keep_ids: Vec<&str> = manifest.entries.iter().map(|e| e.id.as_str()).collect();
         lockfile.retain_entries(&keep_ids);
+        // The lockfile already reflects this edit
+        if lockfile.manifest_checksum.is_some() {
+            lockfile.manifest_checksum = Some(manifest_checksum(&manifest_path)?);
+        }
         lockfile.save(&lockfile_path)?;
     }
 
-    // Delete installed files/directories
-    for (_id, dest) in &dest_paths {
-        if let Some(dest) = dest {
-            let dest_path = base_dir.join(dest);
-            if dest_path.exists() {
-                if dest_path.is_dir() {
-                    fs::remove_dir_all(&dest_path).map_err(|e| {
-                        ApsError::io(e, format!("Failed to remove directory {:?}", dest_path))
-                    })?;
-                } else {
-                    fs::remove_file(&dest_path).map_err(|e| {
-                        ApsError::io(e, format!("Failed to remove file {:?}", dest_path))
-                    })?;
-                }
+    // Delete installed files/directories. Mirrors are symlinks (or copies)
+    // and may dangle once the dest is gone.
+    for path in &installed {
+        match path.symlink_metadata() {
+            Ok(meta) if meta.is_dir() => fs::remove_dir_all(path)
+                .map_err(|e| ApsError::io(e, format!("Failed to remove directory {:?}", path)))?,
+            Ok(_) => fs::remove_file(path)
+                .map_err(|e| ApsError::io(e, format!("Failed to remove file {:?}", path)))?,
+            Err(_) => {}
+        }
+    }
+    prune_and_report(&installed, &base_dir);
+
+    Ok(())
+}
+
+/// Execute the `aps remove` command
+pub fn cmd_remove(args: RemoveArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
+    let dim = Style::new().dim();
+
+    let lockfile = Lockfile::path_for_manifest(&manifest_path, &manifest)
+        .ok()
+        .and_then(|path| Lockfile::load(&path).ok());
+    let ids = if args.interactive {
+        if manifest.entries.is_empty() {
+            say!("No entries to remove.");
+            return Ok(());
+        }
+        let ids = pick_entries_to_remove(&manifest, lockfile.as_ref(), &base_dir)?;
+        if ids.is_empty() {
+            say!("{}", dim.apply_to("No entries selected."));
+            return Ok(());
+        }
+        ids
+    } else {
+        let mut ids: Vec<String> = Vec::new();
+        for id in &args.ids {
+            if !manifest.entries.iter().any(|e| &e.id == id) {
+                return Err(ApsError::EntryNotFound { id: id.clone() });
+            }
+            if !ids.contains(id) {
+                ids.push(id.clone());
             }
         }
+        ids
+    };
+    let installed = if args.keep_files {
+        Vec::new()
+    } else {
+        removal_paths(&manifest, lockfile.as_ref(), &base_dir, &ids)
+    };
+
+    say!();
+    say!(
+        "  {} {} {}",
+        style("✗").red().bold(),
+        style("Will remove:").red(),
+        style(ids.join(", ")).red()
+    );
+    if args.keep_files {
+        say!(
+            "  {}",
+            dim.apply_to("Keeping installed files (--keep-files).")
+        );
+    } else if installed.is_empty() {
+        say!("  {}", dim.apply_to("No installed files to delete."));
+    } else {
+        say!("  {}", style("Will delete:").red());
+        for path in &installed {
+            let relative = path.strip_prefix(&base_dir).unwrap_or(path);
+            say!("    {}", relative.display());
+        }
+    }
+
+    if !args.yes.approve(Prompt::Remove) {
+        if !interactive() {
+            return Err(ApsError::NotInteractive {
+                command: "aps remove".to_string(),
+            });
+        }
+        say!();
+        let confirm = dialoguer::Confirm::new()
+            .with_prompt("Proceed?")
+            .default(false)
+            .interact()
+            .map_err(|_| ApsError::Cancelled)?;
+        if !confirm {
+            say!("Cancelled.");
+            return Ok(());
+        }
     }
 
+    remove_entries_from_manifest(
+        &ids,
+        Some(&manifest_path),
+        show_diff(args.show_diff, args.no_show_diff),
+        args.keep_files,
+    )?;
+    say!(
+        "  {} {}",
+        style("✗").red(),
+        style(format!("Removed {} entries: {}", ids.len(), ids.join(", "))).red()
+    );
     Ok(())
 }
 
+/// Let the user pick entries to remove from a list showing each one's kind,
+/// sync status, and installed size
+fn pick_entries_to_remove(
+    manifest: &Manifest,
+    lockfile: Option<&Lockfile>,
+    base_dir: &Path,
+) -> Result<Vec<String>> {
+    use console::Term;
+    use dialoguer::MultiSelect;
+
+    if !interactive() {
+        return Err(ApsError::NotInteractive {
+            command: "aps remove --interactive".to_string(),
+        });
+    }
+
+    let id_width = manifest
+        .entries
+        .iter()
+        .map(|e| e.id.len())
+        .max()
+        .unwrap_or(0);
+    let items: Vec<String> = manifest
+        .entries
+        .iter()
+        .map(|entry| {
+            let locked = lockfile.and_then(|l| l.entries.get(&entry.id));
+            let (status, size) = match locked {
+                Some(locked) => {
+                    let state = dest_state(Some(entry), base_dir, locked);
+                    let status = if state.is_broken() {
+                        state.as_str()
+                    } else {
+                        "synced"
+                    };
+                    (
+                        status,
+                        format_size(asset_size(&base_dir.join(&locked.dest))),
+                    )
+                }
+                None => ("not synced", "-".to_string()),
+            };
+            format!(
+                "{:<id_width$}  {:<19}  {:<16}  {:>9}",
+                entry.id,
+                entry.kind.as_str(),
+                status,
+                size,
+            )
+        })
+        .collect();
+
+    let selected = MultiSelect::with_theme(&dialoguer::theme::ColorfulTheme::default())
+        .with_prompt("Select entries to remove (space to toggle, enter to confirm)")
+        .items(&items)
+        .interact_on(&Term::stderr())
+        .map_err(|e| {
+            ApsError::io(
+                std::io::Error::other(e.to_string()),
+                "Failed to display entry selection prompt",
+            )
+        })?;
+    Ok(selected
+        .iter()
+        .map(|&i| manifest.entries[i].id.clone())
+        .collect())
+}
+
 /// Check if an entry ID already exists in the manifest. Returns error if duplicate.
 fn check_duplicate_id(entry_id: &str, manifest_override: Option<&Path>) -> Result<()> {
     let manifest_result = match manifest_override {
@@ -828,113 +2259,446 @@ fn select_skills(skills: &[DiscoveredSkill], defaults: &[bool], all: bool) -> Re
     }
 }
 
+/// Parse `--only-kind` values into asset kinds
+fn parse_kinds(names: &[String]) -> Result<Vec<AssetKind>> {
+    names.iter().map(|n| AssetKind::from_str(n)).collect()
+}
+
+/// Network operations `aps sync` runs at once unless `--jobs` says otherwise
+const DEFAULT_JOBS: usize = 4;
+
 /// Execute the `aps sync` command
-pub fn cmd_sync(args: SyncArgs) -> Result<()> {
+pub fn cmd_sync(mut args: SyncArgs) -> Result<()> {
+    // User settings fill in what the command line leaves out
+    let config = UserConfig::load()?;
+    args.low_bandwidth |= config.low_bandwidth;
+    args.jobs = args.jobs.or(config.jobs);
+    set_low_bandwidth(args.low_bandwidth);
+
     // Discover and load manifest
-    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let (mut manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
     let base_dir = manifest_dir(&manifest_path);
 
+    // Substitute `--override` sources for this run only
+    let overridden = manifest.apply_overrides(&args.overrides)?;
+    if !overridden.is_empty() {
+        say!(
+            "{} {} {}",
+            style("Overriding sources for this run:").yellow(),
+            overridden.join(", "),
+            style("(not recorded in the lockfile)").dim()
+        );
+    }
+
     // Validate manifest
     validate_manifest(&manifest)?;
+    check_case_collisions(&manifest, &base_dir)?;
 
-    // Detect overlapping destinations (printed after header in sync output)
-    let overlap_warnings = detect_overlapping_destinations(&manifest);
-
-    // Filter entries if --only is specified
-    let entries_to_install: Vec<_> = if args.only.is_empty() {
-        manifest.entries.iter().collect()
-    } else {
-        let filtered: Vec<_> = manifest
-            .entries
-            .iter()
-            .filter(|e| args.only.contains(&e.id))
-            .collect();
-
-        // Check for invalid IDs
-        for id in &args.only {
-            if !manifest.entries.iter().any(|e| &e.id == id) {
-                return Err(ApsError::EntryNotFound { id: id.clone() });
-            }
+    // Check for invalid IDs
+    for id in &args.only {
+        if !manifest.entries.iter().any(|e| &e.id == id) {
+            return Err(ApsError::EntryNotFound { id: id.clone() });
         }
+    }
 
-        filtered
-    };
+    if !manifest.has_targets() {
+        let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &manifest)?;
+        return sync_target(
+            &manifest,
+            &manifest_path,
+            &base_dir,
+            &lockfile_path,
+            &mut args,
+        );
+    }
 
-    // Load existing lockfile (or create new)
-    let lockfile_path = Lockfile::path_for_manifest(&manifest_path);
-    let mut lockfile = Lockfile::load(&lockfile_path).unwrap_or_else(|_| {
-        info!("No existing lockfile, creating new one");
-        Lockfile::new()
-    });
+    if args.plan.is_some() {
+        return Err(ApsError::InvalidInput {
+            message: "--plan cannot be applied to a manifest with `targets`".to_string(),
+        });
+    }
+
+    // Fan out: sync each target directory against its own lockfile
+    let mut outcomes = Vec::new();
+    for (target, target_manifest) in manifest.fan_out(&base_dir) {
+        let expanded = shellexpand::full(&target)
+            .map(|s| s.into_owned())
+            .unwrap_or_else(|_| target.clone());
+        let target_dir = base_dir.join(expanded);
+        say!();
+        say!("{} {}", style("==>").cyan().bold(), style(&target).bold());
+        let outcome = if target_dir.is_dir() {
+            let lockfile_path = Lockfile::path_for_target(&target_dir, &manifest)?;
+            sync_target(
+                &target_manifest,
+                &manifest_path,
+                &target_dir,
+                &lockfile_path,
+                &mut args,
+            )
+        } else {
+            Err(ApsError::InvalidInput {
+                message: format!("Target directory {} does not exist", target_dir.display()),
+            })
+        };
+        if let Err(ref e) = outcome {
+            say!("{} {}", style("Error:").red(), e);
+        }
+        outcomes.push((target, target_manifest.entries.len(), outcome));
+    }
+
+    say!();
+    say!("{}", style("Targets:").bold());
+    for (target, entries, outcome) in &outcomes {
+        match outcome {
+            Ok(()) => say!("  {} {} ({} entries)", style("✓").green(), target, entries),
+            Err(_) => say!("  {} {} ({} entries)", style("✗").red(), target, entries),
+        }
+    }
+
+    let failed: Vec<&str> = outcomes
+        .iter()
+        .filter(|(_, _, outcome)| outcome.is_err())
+        .map(|(target, _, _)| target.as_str())
+        .collect();
+    if !failed.is_empty() {
+        return Err(ApsError::SyncTargetsIncomplete {
+            count: failed.len(),
+            targets: failed.join(", "),
+        });
+    }
+    Ok(())
+}
+
+/// Kinds with entries this sync installed or updated, in manifest order,
+/// with those entries' IDs
+fn changed_kinds(entries: &[&Entry], results: &[InstallResult]) -> Vec<(AssetKind, Vec<String>)> {
+    let mut changed: Vec<(AssetKind, Vec<String>)> = Vec::new();
+    for entry in entries {
+        let updated = results
+            .iter()
+            .any(|r| r.id == entry.id && !r.skipped_no_change);
+        if !updated {
+            continue;
+        }
+        match changed.iter_mut().find(|(kind, _)| *kind == entry.kind) {
+            Some((_, ids)) => ids.push(entry.id.clone()),
+            None => changed.push((entry.kind.clone(), vec![entry.id.clone()])),
+        }
+    }
+    changed
+}
+
+/// Sync a manifest's entries into `base_dir`, recording them in `lockfile_path`
+fn sync_target(
+    manifest: &Manifest,
+    manifest_path: &Path,
+    base_dir: &Path,
+    lockfile_path: &Path,
+    args: &mut SyncArgs,
+) -> Result<()> {
+    // Detect overlapping destinations (printed after header in sync output)
+    let overlap_warnings = detect_overlapping_destinations(manifest);
+
+    // Load existing lockfile (or create new), recovering a malformed one
+    let mut lockfile =
+        load_lockfile_for_update(lockfile_path, args.force, manifest, base_dir, !args.dry_run)?;
+
+    // Apply a saved plan only if it still describes exactly what would happen
+    if let Some(plan_path) = &args.plan {
+        let saved = Plan::load(plan_path)?;
+        let current = Plan::build(manifest, manifest_path, base_dir, &lockfile, saved.upgrade)?;
+        if let Some(reason) = saved.drift_from(&current) {
+            return Err(ApsError::PlanDrift { reason });
+        }
+        if current.changes.is_empty() {
+            say!("Plan has no changes. Nothing to apply.");
+            return Ok(());
+        }
+        args.upgrade = saved.upgrade;
+    }
+
+    let only_kinds = parse_kinds(&args.only_kind)?;
+
+    // Filter entries if --only or --retry-failed is specified
+    let entries_to_install: Vec<_> = if args.retry_failed {
+        let failed: Vec<_> = manifest
+            .entries
+            .iter()
+            .filter(|e| lockfile.needs_retry(&e.id))
+            .collect();
+        if failed.is_empty() {
+            say!("No failed entries to retry.");
+            return Ok(());
+        }
+        failed
+    } else if args.only.is_empty() {
+        manifest.entries.iter().collect()
+    } else {
+        manifest
+            .entries
+            .iter()
+            .filter(|e| args.only.contains(&e.id))
+            .collect()
+    };
+
+    // Narrow to --only-kind, which combines with the selection above
+    let mut entries_to_install: Vec<_> = entries_to_install
+        .into_iter()
+        .filter(|e| only_kinds.is_empty() || only_kinds.contains(&e.kind))
+        .collect();
+
+    // Review entries one by one, or leave out those a review disabled
+    let mut review = ReviewState::load(base_dir);
+    entries_to_install = if args.review {
+        let approved = review_entries(entries_to_install, base_dir, &mut review)?;
+        if !args.dry_run {
+            review.save(base_dir)?;
+        }
+        if approved.is_empty() {
+            say!("No entries approved.");
+            return Ok(());
+        }
+        approved
+    } else {
+        review.without_disabled(entries_to_install, &args.only)
+    };
+
+    // Generated sources index other entries, so render them after those install
+    entries_to_install.sort_by_key(|e| e.has_generated_sources());
+    if entries_to_install.is_empty() && !only_kinds.is_empty() {
+        say!("No entries match the requested kinds.");
+        return Ok(());
+    }
 
     // Set up install options
-    let options = InstallOptions {
+    let mut options = InstallOptions {
         dry_run: args.dry_run,
         yes: args.yes,
         strict: args.strict,
         upgrade: args.upgrade,
+        offline: args.offline,
+        backup: BackupPolicy::for_manifest(manifest),
+        suppress_warnings: WarningSet::of(&manifest.suppress_warnings),
+        force_copy: ci::active() && !manifest.ci.symlink,
+        // Low-bandwidth syncs make no upgrade or stale-pin checks either
+        refresh: if args.offline || args.low_bandwidth {
+            RefreshPolicy::Never
+        } else {
+            args.refresh
+        },
+        symlink_style: manifest.symlink_style,
     };
 
-    // Detect orphaned paths (destinations that changed)
-    let orphans = detect_orphaned_paths(&entries_to_install, &lockfile, &base_dir);
+    if manifest.check_uncommitted && !args.force {
+        check_uncommitted_dests(&entries_to_install, base_dir, args.strict)?;
+    }
+
+    // Report unwritable destinations and full disks before any clone or copy
+    if !args.dry_run {
+        check_destinations(&entries_to_install, base_dir)?;
+    }
+
+    // Overridden entries install as if never locked, so they resolve the
+    // override instead of the locked commit; their lock is put back afterwards
+    let held: Vec<(String, Option<LockedEntry>)> = entries_to_install
+        .iter()
+        .filter(|e| override_ids(&args.overrides).contains(&e.id.as_str()))
+        .map(|e| (e.id.clone(), lockfile.entries.remove(&e.id)))
+        .collect();
+
+    // Detect orphaned paths (destinations that changed), leaving alone those
+    // another manifest in the workspace has since claimed
+    let orphans = exclude_foreign_owned(
+        detect_orphaned_paths(&entries_to_install, &lockfile, base_dir),
+        &foreign_owners(manifest_path, lockfile_path),
+        base_dir,
+    );
+
+    // Install selected entries, cancelling git operations that exceed the timeouts
+    let sync_deadline = args
+        .timeout
+        .map(|secs| Instant::now() + Duration::from_secs(secs));
+
+    // Ask the remotes of locked git entries for their heads up front, several
+    // at once; the installs below then find the answers in the cache
+    if !args.upgrade && options.refresh != RefreshPolicy::Never {
+        let remotes: Vec<(&str, &str)> = entries_to_install
+            .iter()
+            .filter(|e| {
+                lockfile
+                    .entries
+                    .get(&e.id)
+                    .is_some_and(|l| l.commit.is_some())
+            })
+            .filter_map(|e| e.source.as_ref()?.git_info())
+            .collect();
+        let jobs = args.jobs.map_or(DEFAULT_JOBS, usize::from);
+        let mut remote_cache = RemoteRefCache::load(base_dir);
+        remote_cache.refresh_all(&remotes, options.refresh, jobs, sync_deadline, Utc::now());
+        if let Err(e) = remote_cache.save(base_dir) {
+            debug!("Failed to save remote ref cache: {}", e);
+        }
+        options.refresh = RefreshPolicy::Auto;
+    }
 
-    // Install selected entries
     let mut results: Vec<InstallResult> = Vec::new();
+    let mut failures: Vec<SyncFailure> = Vec::new();
+    let mut frozen_ids: Vec<&str> = Vec::new();
     for entry in &entries_to_install {
-        // Use composite install for composite entries, regular install otherwise
-        let result = if entry.is_composite() {
-            install_composite_entry(entry, &base_dir, &lockfile, &options)?
+        let started = Instant::now();
+        if sync_deadline.is_some_and(|deadline| started >= deadline) {
+            failures.push(SyncFailure {
+                entry,
+                status: LastSyncStatus::TimedOut,
+                message: "Not started: sync timeout reached".to_string(),
+            });
+            continue;
+        }
+        let entry_deadline = args
+            .entry_timeout
+            .map(|secs| started + Duration::from_secs(secs))
+            .into_iter()
+            .chain(sync_deadline)
+            .min();
+
+        // Frozen entries keep their locked commit through --upgrade
+        let mut entry_options = InstallOptions {
+            backup: options.backup.for_entry(entry),
+            symlink_style: entry.symlink_style.unwrap_or(options.symlink_style),
+            ..options.clone()
+        };
+        if holds_frozen(entry, args) {
+            frozen_ids.push(entry.id.as_str());
+            entry_options.upgrade = false;
+        }
+
+        // Another process writing the same or a nested destination goes first
+        let _dest_lock = if args.dry_run {
+            None
         } else {
-            install_entry(entry, &base_dir, &lockfile, &options)?
+            dest_lock::lock_destination(&base_dir.join(entry.destination()))
         };
-        results.push(result);
+
+        // Use composite install for composite entries, regular install otherwise
+        let outcome = with_git_deadline(entry_deadline, || {
+            if entry.is_composite() {
+                install_composite_entry(entry, manifest, base_dir, &lockfile, &entry_options)
+            } else {
+                install_entry(entry, base_dir, &lockfile, &entry_options)
+            }
+        });
+        match outcome {
+            Ok(result) => results.push(result),
+            Err(_) if entry_deadline.is_some_and(|deadline| Instant::now() >= deadline) => {
+                failures.push(SyncFailure {
+                    entry,
+                    status: LastSyncStatus::TimedOut,
+                    message: format!("Timed out after {}s", started.elapsed().as_secs()),
+                });
+            }
+            Err(e) if args.keep_going => {
+                failures.push(SyncFailure {
+                    entry,
+                    status: LastSyncStatus::Failed,
+                    message: e.to_string(),
+                });
+            }
+            Err(e) => {
+                // Keep what was installed so far and remember the failure for --retry-failed
+                if !args.dry_run {
+                    record_sync_results(&mut lockfile, &results);
+                    record_failures(&mut lockfile, &failures);
+                    restore_overridden(&mut lockfile, held, &results);
+                    lockfile.record_outcome(&entry.id, LastSyncStatus::Failed, Some(e.to_string()));
+                    lockfile.save(lockfile_path)?;
+                }
+                return Err(e);
+            }
+        }
     }
 
+    // Leave the previous destination of failed entries in place
+    let orphans: Vec<_> = orphans
+        .into_iter()
+        .filter(|o| !failures.iter().any(|f| f.entry.id == o.entry_id))
+        .collect();
+
     // Cleanup orphaned paths after successful install
     let orphan_count = if !orphans.is_empty() {
-        prompt_and_cleanup_orphans(&orphans, &options, &base_dir)?
+        prompt_and_cleanup_orphans(&orphans, &options, base_dir)?
     } else {
         0
     };
 
+    // Mirror installed entries into other tools' directories
+    let mut mirrors = Vec::new();
+    for result in &results {
+        if let Some(entry) = entries_to_install.iter().find(|e| e.id == result.id) {
+            mirrors.push((entry.id.as_str(), sync_mirrors(entry, base_dir, &options)?));
+        }
+    }
+
     // Update lockfile with results
     if !args.dry_run {
+        record_sync_results(&mut lockfile, &results);
+        record_failures(&mut lockfile, &failures);
+        for (id, paths) in mirrors {
+            if let Some(locked) = lockfile.entries.get_mut(id) {
+                locked.mirrors = paths;
+            }
+        }
         for result in &results {
-            if let Some(ref locked_entry) = result.locked_entry {
-                lockfile.upsert(result.id.clone(), locked_entry.clone());
+            let entry = entries_to_install.iter().find(|e| e.id == result.id);
+            if let (Some(entry), Some(locked)) = (entry, lockfile.entries.get_mut(&result.id)) {
+                locked.entry_checksum = Some(entry.checksum());
+                locked.manifest = Some(manifest_ref(manifest_path, lockfile_path));
+            }
+        }
+        // Remember old destinations that were kept, so they are offered again
+        let remaining = remaining_orphans(&orphans, base_dir);
+        for entry in &entries_to_install {
+            if failures.iter().any(|f| f.entry.id == entry.id) {
+                continue;
+            }
+            if let Some(locked) = lockfile.entries.get_mut(&entry.id) {
+                locked.previous_dests = remaining.get(&entry.id).cloned().unwrap_or_default();
             }
         }
 
-        // Clean up stale entries (only during full sync, not with --only)
-        let removed_count = if args.only.is_empty() {
-            let manifest_ids: Vec<&str> = manifest.entries.iter().map(|e| e.id.as_str()).collect();
-            let removed = lockfile.retain_entries(&manifest_ids);
-            removed.len()
-        } else {
-            0
-        };
+        restore_overridden(&mut lockfile, held, &results);
+
+        // Clean up stale entries (only during full sync, not with --only or --retry-failed)
+        let removed_count =
+            if args.only.is_empty() && args.only_kind.is_empty() && !args.retry_failed {
+                let manifest_ids: Vec<&str> =
+                    manifest.entries.iter().map(|e| e.id.as_str()).collect();
+                let removed = lockfile.retain_entries(&manifest_ids);
+                lockfile.manifest_checksum = Some(manifest_checksum(manifest_path)?);
+                removed.len()
+            } else {
+                0
+            };
         if removed_count > 0 {
             info!("Removed {} stale entries from lockfile", removed_count);
         }
 
         // Save lockfile
-        lockfile.save(&lockfile_path)?;
+        lockfile.save(lockfile_path)?;
     }
 
     // Convert results to display items
-    let display_items: Vec<SyncDisplayItem> = results
+    let now = Utc::now();
+    let mut display_items: Vec<SyncDisplayItem> = results
         .iter()
         .map(|r| {
-            let status = if !r.warnings.is_empty() {
-                SyncStatus::Warning
-            } else if r.skipped_no_change && r.upgrade_available.is_some() {
-                SyncStatus::Upgradable
-            } else if r.skipped_no_change {
-                SyncStatus::Current
-            } else if r.was_symlink {
-                SyncStatus::Synced
-            } else {
-                SyncStatus::Copied
+            let status = match sync_status(r) {
+                SyncStatus::Current | SyncStatus::Upgradable
+                    if frozen_ids.contains(&r.id.as_str()) =>
+                {
+                    SyncStatus::Frozen
+                }
+                status => status,
             };
 
             let mut item = SyncDisplayItem::new(
@@ -945,155 +2709,925 @@ pub fn cmd_sync(args: SyncArgs) -> Result<()> {
 
             // Add warning message if present
             if !r.warnings.is_empty() {
-                item = item.with_message(r.warnings.join(", "));
+                let warnings: Vec<String> = r.warnings.iter().map(ToString::to_string).collect();
+                item = item.with_message(warnings.join(", "));
+            }
+
+            // Name the composite partials that changed
+            if item.message.is_none() && !r.changed_sources.is_empty() {
+                item = item.with_message(format!("changed: {}", r.changed_sources.join(", ")));
             }
 
             // Add upgrade info message if available
             if let Some(ref upgrade_info) = r.upgrade_available {
-                let current_short =
-                    &upgrade_info.current_commit[..8.min(upgrade_info.current_commit.len())];
-                let available_short =
-                    &upgrade_info.available_commit[..8.min(upgrade_info.available_commit.len())];
+                let current_short = short_id(&upgrade_info.current_commit);
+                let available_short = short_id(&upgrade_info.available_commit);
                 item = item.with_message(format!("{} → {}", current_short, available_short));
             }
 
+            if override_ids(&args.overrides).contains(&r.id.as_str()) {
+                item.message = Some(match item.message.take() {
+                    Some(message) => format!("overridden, {}", message),
+                    None => "overridden".to_string(),
+                });
+            }
+
+            // How long ago the entry last changed, with --stale-after
+            if let Some(stale_after) = args.stale_after {
+                let age = lockfile
+                    .entries
+                    .get(&r.id)
+                    .and_then(|locked| last_updated_age(locked, now));
+                if let Some(age) = age {
+                    item = item.with_last_updated(format_age(age), age > stale_after);
+                }
+            }
+
             item
         })
         .collect();
+    display_items.extend(failures.iter().map(|f| {
+        SyncDisplayItem::new(
+            f.entry.id.clone(),
+            base_dir
+                .join(f.entry.destination())
+                .to_string_lossy()
+                .to_string(),
+            SyncStatus::Error,
+        )
+        .with_message(f.message.clone())
+    }));
 
     // Print styled results
+    if args.porcelain {
+        print_sync_porcelain(&display_items, manifest_path);
+    }
     print_sync_results(
         &display_items,
-        &manifest_path,
+        manifest_path,
         args.dry_run,
         &overlap_warnings,
     );
 
-    // Calculate counts for summary
-    let synced_count = display_items
-        .iter()
-        .filter(|i| i.status == SyncStatus::Synced)
-        .count();
-    let copied_count = display_items
-        .iter()
-        .filter(|i| i.status == SyncStatus::Copied)
-        .count();
-    let current_count = display_items
-        .iter()
-        .filter(|i| i.status == SyncStatus::Current)
-        .count();
-    let upgradable_count = display_items
-        .iter()
-        .filter(|i| i.status == SyncStatus::Upgradable)
-        .count();
-    let warning_count = display_items
-        .iter()
-        .filter(|i| i.status == SyncStatus::Warning)
-        .count();
-
     // Print summary
-    print_sync_summary(
-        synced_count,
-        copied_count,
-        current_count,
-        upgradable_count,
-        warning_count,
-        orphan_count,
-        args.dry_run,
-    );
+    print_sync_summary(&display_items, orphan_count, args.dry_run);
+    for item in &display_items {
+        summary::add_count(item.status.as_str(), 1);
+        if item.status == SyncStatus::Error {
+            summary::add_failure(&item.id, item.message.as_deref().unwrap_or_default());
+        }
+    }
+    summary::add_count("orphans_deleted", orphan_count);
+    print_auto_approved(&take_auto_approved());
+
+    // Warn about pins older than max_age, refreshing cached remote heads
+    let synced_ids: Vec<&str> = entries_to_install.iter().map(|e| e.id.as_str()).collect();
+    let mut remote_cache = RemoteRefCache::load(base_dir);
+    let stale = with_git_deadline(sync_deadline, || {
+        check_stale_pins(
+            manifest,
+            &synced_ids,
+            &lockfile,
+            &mut remote_cache,
+            options.refresh,
+            Utc::now(),
+        )
+    });
+    if !args.dry_run && !remote_cache.refs.is_empty() {
+        remote_cache.save(base_dir)?;
+    }
+    print_stale_pins(&stale);
+
+    if !args.dry_run {
+        project_index::record_sync(base_dir, manifest_path, manifest);
+        let changed = changed_kinds(&entries_to_install, &results);
+        if !changed.is_empty() {
+            if let Ok(config) = UserConfig::load() {
+                run_post_install(&config.post_install, &changed, base_dir);
+            }
+        }
+    }
+
+    if !args.dry_run && apply_editor_integrations(manifest, base_dir)?.is_some() {
+        say!(
+            "Registered synced directories in {}",
+            style(EDITOR_SETTINGS_PATH).cyan()
+        );
+    }
+
+    if !failures.is_empty() {
+        let ids: Vec<&str> = failures.iter().map(|f| f.entry.id.as_str()).collect();
+        return Err(ApsError::SyncIncomplete {
+            count: failures.len(),
+            ids: ids.join(", "),
+        });
+    }
 
     Ok(())
 }
 
-/// Execute the `aps validate` command
-pub fn cmd_validate(args: ValidateArgs) -> Result<()> {
-    // Discover and load manifest
-    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
-    println!("Validating manifest at {:?}", manifest_path);
+/// Warn, or with `strict` fail, when destinations about to be synced have
+/// uncommitted changes in git
+fn check_uncommitted_dests(entries: &[&Entry], base_dir: &Path, strict: bool) -> Result<()> {
+    let dests: Vec<PathBuf> = entries
+        .iter()
+        .map(|e| e.destination())
+        .filter(|dest| base_dir.join(dest).exists())
+        .collect();
+    let dirty = uncommitted_changes(base_dir, &dests);
+    if dirty.is_empty() {
+        return Ok(());
+    }
+    if strict {
+        return Err(ApsError::UncommittedChanges {
+            paths: dirty.join(", "),
+        });
+    }
+    say!(
+        "{} sync may overwrite uncommitted changes in:",
+        style("Warning:").yellow()
+    );
+    for path in &dirty {
+        say!("  {}", path);
+    }
+    say!("Commit or stash them first, or pass --force to skip this check.\n");
+    Ok(())
+}
 
-    // Validate schema
+/// Whether `--upgrade` must leave a frozen entry at its locked commit. Only
+/// `--force` together with the entry's ID in `--only` unfreezes it.
+fn holds_frozen(entry: &Entry, args: &SyncArgs) -> bool {
+    entry.frozen && args.upgrade && !(args.force && args.only.contains(&entry.id))
+}
+
+/// Load the lockfile, starting a new one if it is missing or unreadable.
+/// A lockfile from a newer, incompatible aps is never silently replaced.
+pub fn load_or_create_lockfile(path: &Path, force: bool) -> Result<Lockfile> {
+    match Lockfile::load_recovering(path, force) {
+        Ok((lockfile, recovery)) => {
+            if let Some(recovery) = recovery {
+                info!(
+                    "Read the readable entries of a malformed lockfile: {}",
+                    recovery.error
+                );
+            }
+            Ok(lockfile)
+        }
+        Err(e @ ApsError::LockfileVersionUnsupported { .. }) => Err(e),
+        Err(_) => {
+            info!("No existing lockfile, creating new one");
+            Ok(Lockfile::new())
+        }
+    }
+}
+
+/// Load the lockfile for a command that rewrites it. The readable entries of
+/// a malformed lockfile are kept and a copy of the file is quarantined under
+/// the backup directory; entries left without a pin are reported, since they
+/// are resolved fresh.
+fn load_lockfile_for_update(
+    path: &Path,
+    force: bool,
+    manifest: &Manifest,
+    base_dir: &Path,
+    quarantine: bool,
+) -> Result<Lockfile> {
+    let (lockfile, recovery) = match Lockfile::load_recovering(path, force) {
+        Ok(loaded) => loaded,
+        Err(e @ ApsError::LockfileVersionUnsupported { .. }) => return Err(e),
+        Err(_) => {
+            info!("No existing lockfile, creating new one");
+            return Ok(Lockfile::new());
+        }
+    };
+    let Some(recovery) = recovery else {
+        return Ok(lockfile);
+    };
+
+    say!(
+        "Warning: Lockfile {:?} is malformed ({}); recovered {} readable entries",
+        path,
+        recovery.error,
+        lockfile.entries.len()
+    );
+    if quarantine {
+        let policy = BackupPolicy {
+            strategy: BackupStrategy::Copy,
+            enabled: true,
+            ..BackupPolicy::for_manifest(manifest)
+        };
+        if let Some(copy) = create_backup(base_dir, path, &policy)? {
+            say!("Kept a copy of the malformed lockfile at {:?}", copy);
+        }
+    }
+    let mut unpinned: Vec<&str> = manifest
+        .entries
+        .iter()
+        .map(|e| e.id.as_str())
+        .filter(|id| !lockfile.entries.contains_key(*id))
+        .chain(recovery.unreadable.iter().map(String::as_str))
+        .collect();
+    unpinned.sort_unstable();
+    unpinned.dedup();
+    if !unpinned.is_empty() {
+        say!(
+            "These entries lost their pins and will be resolved fresh: {}",
+            unpinned.join(", ")
+        );
+    }
+    Ok(lockfile)
+}
+
+/// Execute the `aps plan` command
+pub fn cmd_plan(args: PlanArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
     validate_manifest(&manifest)?;
-    println!("  Schema validation passed");
 
-    // Check for overlapping destinations
-    let overlap_warnings = detect_overlapping_destinations(&manifest);
-    for warning in &overlap_warnings {
-        println!(
-            "  {} {}",
-            console::style("[WARN]").yellow(),
-            console::style(warning).yellow()
+    let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &manifest)?;
+    let lockfile = load_or_create_lockfile(&lockfile_path, args.force)?;
+
+    let plan = Plan::build(
+        &manifest,
+        &manifest_path,
+        &base_dir,
+        &lockfile,
+        args.upgrade,
+    )?;
+    print_plan(&plan);
+
+    if let Some(out) = &args.out {
+        plan.save(out)?;
+        say!(
+            "\nSaved plan to {}. Apply it with `aps sync --plan {}`.",
+            style(out.display()).cyan(),
+            out.display()
         );
     }
 
-    // Check sources are reachable
+    Ok(())
+}
+
+/// Execute the `aps lock` command
+pub fn cmd_lock(args: LockArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
     let base_dir = manifest_dir(&manifest_path);
-    let mut warnings = Vec::new();
+    validate_manifest(&manifest)?;
+
+    let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &manifest)?;
+    let mut lockfile =
+        load_lockfile_for_update(&lockfile_path, args.force, &manifest, &base_dir, true)?;
 
-    println!("\nValidating entries:");
+    say!(
+        "Locking {} entries (no files will be installed)\n",
+        manifest.entries.len()
+    );
+
+    let mut updated = 0;
+    let mut results = Vec::new();
     for entry in &manifest.entries {
-        // Handle composite entries differently
-        if entry.is_composite() {
-            print!(
-                "  [..] {} (composite) - checking {} sources...",
-                entry.id,
-                entry.sources.len()
+        let held = entry.frozen && args.upgrade;
+        let result = lock_entry(
+            entry,
+            &manifest,
+            &base_dir,
+            &lockfile,
+            args.upgrade && !held,
+        )?;
+        let detail = match &result.locked_entry.commit {
+            Some(commit) if held => format!("@ {} (frozen)", short_id(commit)),
+            Some(commit) => format!("@ {}", short_id(commit)),
+            None => short_id(&result.locked_entry.checksum),
+        };
+        if result.changed {
+            updated += 1;
+            say!(
+                "  {} {} {}",
+                style("✓").green(),
+                result.id,
+                style(detail).cyan()
             );
-            std::io::stdout().flush().ok();
+        } else {
+            say!(
+                "  {} {} {}",
+                style("·").dim(),
+                result.id,
+                style(detail).dim()
+            );
+        }
+        results.push(result);
+    }
 
-            let mut all_valid = true;
-            for source in &entry.sources {
-                let adapter = source.to_adapter();
-                match adapter.resolve(&base_dir) {
-                    Ok(resolved) => {
-                        if !resolved.source_path.exists() {
-                            let warning =
-                                format!("Source path not found: {:?}", resolved.source_path);
-                            if args.strict {
-                                println!(" FAILED");
-                                return Err(ApsError::SourcePathNotFound {
-                                    path: resolved.source_path,
-                                });
-                            }
-                            warnings.push(warning);
-                            all_valid = false;
-                        }
-                    }
-                    Err(e) => {
-                        if args.strict {
-                            println!(" FAILED");
-                            return Err(e);
-                        }
-                        let warning = format!("Source validation failed: {}", e);
-                        warnings.push(warning);
-                        all_valid = false;
-                    }
-                }
-            }
+    for result in results {
+        lockfile.upsert(result.id, result.locked_entry);
+    }
+    let manifest_ids: Vec<&str> = manifest.entries.iter().map(|e| e.id.as_str()).collect();
+    let removed = lockfile.retain_entries(&manifest_ids);
+    lockfile.save(&lockfile_path)?;
+
+    say!(
+        "\nLocked {} entries ({} updated, {} removed) in {}",
+        manifest.entries.len(),
+        updated,
+        removed.len(),
+        style(lockfile_path.display()).cyan()
+    );
 
-            if all_valid {
-                println!(
-                    "\r  [OK] {} (composite, {} sources)",
-                    entry.id,
-                    entry.sources.len()
-                );
-            } else {
-                println!(" WARN");
-            }
+    Ok(())
+}
+
+/// Whether an entry installs from a git source, directly or as one of a
+/// composite's sources
+fn has_git_source(entry: &Entry) -> bool {
+    entry
+        .source
+        .iter()
+        .chain(&entry.sources)
+        .any(|source| source.git_info().is_some())
+}
+
+/// Each git source of an entry with its locked commit. Composite entries
+/// list every repository and ref they read, pinned in `source_commits`.
+fn git_pins<'a>(
+    entry: &'a Entry,
+    locked: Option<&'a LockedEntry>,
+) -> Vec<(&'a str, &'a str, Option<&'a str>)> {
+    if !entry.is_composite() {
+        return entry
+            .source
+            .as_ref()
+            .and_then(|s| s.git_info())
+            .map(|(repo, git_ref)| (repo, git_ref, locked.and_then(|l| l.commit.as_deref())))
+            .into_iter()
+            .collect();
+    }
+    let mut pins: Vec<(&str, &str, Option<&str>)> = Vec::new();
+    for (repo, git_ref) in entry.sources.iter().filter_map(|s| s.git_info()) {
+        if pins.iter().any(|(r, g, _)| *r == repo && *g == git_ref) {
             continue;
         }
-
+        let key = LockedEntry::source_commit_key(repo, git_ref);
+        let commit = locked
+            .and_then(|l| l.source_commits.get(&key))
+            .map(String::as_str);
+        pins.push((repo, git_ref, commit));
+    }
+    pins
+}
+
+/// Execute the `aps outdated` command
+pub fn cmd_outdated(args: OutdatedArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
+    let lockfile = Lockfile::load(&Lockfile::path_for_manifest(&manifest_path, &manifest)?)?;
+
+    let entries: Vec<&Entry> = manifest
+        .entries
+        .iter()
+        .filter(|e| has_git_source(e))
+        .collect();
+    if entries.is_empty() {
+        say!("No git entries to check.");
+        return Ok(());
+    }
+    say!(
+        "Checking {} git entries against their remotes{}\n",
+        entries.len(),
+        if args.offline { " (cached)" } else { "" }
+    );
+
+    let mut remote_cache = RemoteRefCache::load(&base_dir);
+    let policy = if args.offline {
+        RefreshPolicy::Never
+    } else {
+        RefreshPolicy::Always
+    };
+    let now = Utc::now();
+
+    // ID, ref, locked, available, and status of each git source
+    let mut rows: Vec<[String; 5]> = Vec::new();
+    let mut behind_count = 0;
+    for entry in &entries {
+        let locked = lockfile.entries.get(&entry.id);
+        let mut entry_behind = false;
+        for (repo, git_ref, locked_commit) in git_pins(entry, locked) {
+            let ahead = locked_commit.and_then(|commit| {
+                remote_ahead(&mut remote_cache, repo, git_ref, commit, policy, now)
+            });
+            let head = remote_cache.head(repo, git_ref).map(str::to_string);
+            // Release refs show tags: the locked one, and the newest matching release
+            let release = release_constraint(git_ref)
+                .filter(|_| !args.offline)
+                .and_then(|constraint| resolve_release(repo, constraint).ok());
+            let locked_label = match locked_commit {
+                Some(commit) => locked
+                    .and_then(|l| l.resolved_ref.clone())
+                    .filter(|_| release_constraint(git_ref).is_some() && !entry.is_composite())
+                    .unwrap_or_else(|| short_id(commit)),
+                None => "-".to_string(),
+            };
+            let available = match (&release, &head) {
+                (Some(release), _) => release.tag.clone(),
+                (None, Some(commit)) => short_id(commit),
+                (None, None) => "?".to_string(),
+            };
+            let status = match ahead {
+                _ if locked_commit.is_none() => "not locked".to_string(),
+                Some((_, behind)) => {
+                    entry_behind = true;
+                    match behind {
+                        Some(1) => "1 commit behind".to_string(),
+                        Some(n) => format!("{} commits behind", n),
+                        None => "behind".to_string(),
+                    }
+                }
+                None if head.is_none() => "unknown".to_string(),
+                None => "up to date".to_string(),
+            };
+            let status = if locked.is_some_and(|l| l.dest_stale) {
+                format!("{}, sync pending", status)
+            } else {
+                status
+            };
+            let status = if entry.frozen {
+                format!("{} (frozen)", status)
+            } else {
+                status
+            };
+            // A composite reads several repositories, so name each one
+            let id = if entry.is_composite() {
+                format!("{} ({})", entry.id, location_slug(repo))
+            } else {
+                entry.id.clone()
+            };
+            rows.push([id, git_ref.to_string(), locked_label, available, status]);
+        }
+        if entry_behind {
+            behind_count += 1;
+        }
+    }
+    if !args.offline && !remote_cache.refs.is_empty() {
+        remote_cache.save(&base_dir)?;
+    }
+
+    let header = ["ID", "REF", "LOCKED", "AVAILABLE", "STATUS"];
+    let widths: Vec<usize> = (0..4)
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].len())
+                .chain([header[column].len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let line = |cells: &[String; 5]| {
+        (0..4)
+            .map(|column| format!("{:<width$}", cells[column], width = widths[column]))
+            .collect::<Vec<_>>()
+            .join("  ")
+    };
+    let header = header.map(str::to_string);
+    say!(
+        "  {}  {}",
+        style(line(&header)).dim(),
+        style(&header[4]).dim()
+    );
+    for row in &rows {
+        let status = if row[4].starts_with("up to date") {
+            style(&row[4]).green()
+        } else if row[4].contains("behind") {
+            style(&row[4]).yellow()
+        } else {
+            style(&row[4]).dim()
+        };
+        say!("  {}  {}", line(row), status);
+    }
+
+    say!();
+    if behind_count == 0 {
+        say!("{} All git entries are up to date.", style("✓").green());
+    } else {
+        say!(
+            "{} {} behind upstream. Run `aps update` to move the lockfile pins, then `aps sync` to install them.",
+            behind_count,
+            if behind_count == 1 {
+                "entry is"
+            } else {
+                "entries are"
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Execute the `aps update` command
+pub fn cmd_update(args: UpdateArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
+    validate_manifest(&manifest)?;
+    let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &manifest)?;
+    let mut lockfile = Lockfile::load(&lockfile_path)?;
+
+    let entries: Vec<&Entry> = if args.ids.is_empty() {
+        manifest
+            .entries
+            .iter()
+            .filter(|e| has_git_source(e))
+            .collect()
+    } else {
+        let mut entries: Vec<&Entry> = Vec::new();
+        for id in &args.ids {
+            let entry = manifest
+                .entries
+                .iter()
+                .find(|e| &e.id == id)
+                .ok_or_else(|| ApsError::EntryNotFound { id: id.clone() })?;
+            if !entries.iter().any(|e| e.id == entry.id) {
+                entries.push(entry);
+            }
+        }
+        entries
+    };
+    if entries.is_empty() {
+        say!("No git entries to update.");
+        return Ok(());
+    }
+
+    say!(
+        "Updating {} entries to their latest upstream commits{}\n",
+        entries.len(),
+        if args.dry_run { " (dry run)" } else { "" }
+    );
+
+    let mut results = Vec::new();
+    for entry in entries {
+        let previous = lockfile
+            .entries
+            .get(&entry.id)
+            .and_then(|l| l.commit.clone());
+        let commit_label = |commit: &Option<String>| {
+            commit
+                .as_deref()
+                .map(short_id)
+                .unwrap_or_else(|| "-".to_string())
+        };
+        if !has_git_source(entry) {
+            say!(
+                "  {} {} {}",
+                style("·").dim(),
+                entry.id,
+                style("(no git source)").dim()
+            );
+            continue;
+        }
+        if entry.frozen && !(args.force && args.ids.contains(&entry.id)) {
+            say!(
+                "  {} {} {}",
+                style("·").dim(),
+                entry.id,
+                style(format!("@ {} (frozen)", commit_label(&previous))).dim()
+            );
+            continue;
+        }
+
+        let mut result = lock_entry(entry, &manifest, &base_dir, &lockfile, true)?;
+        let current = result.locked_entry.commit.clone();
+        if result.changed {
+            // The next sync installs the new pin over what is there now
+            result.locked_entry.last_status = Some(LastSyncStatus::Pending);
+            result.locked_entry.last_error = None;
+            result.locked_entry.dest_stale = true;
+            say!(
+                "  {} {} {}",
+                style("✓").green(),
+                result.id,
+                style(format!(
+                    "{} -> {}",
+                    commit_label(&previous),
+                    commit_label(&current)
+                ))
+                .cyan()
+            );
+            results.push(result);
+        } else {
+            say!(
+                "  {} {} {}",
+                style("·").dim(),
+                result.id,
+                style(format!("@ {} (up to date)", commit_label(&current))).dim()
+            );
+        }
+    }
+
+    say!();
+    if results.is_empty() {
+        say!("{} All pins are up to date.", style("✓").green());
+        return Ok(());
+    }
+    let updated = results.len();
+    summary::add_count("updated", updated);
+    if args.dry_run {
+        say!(
+            "Would update {} {}; the lockfile was not changed.",
+            updated,
+            if updated == 1 { "pin" } else { "pins" }
+        );
+        return Ok(());
+    }
+    for result in results {
+        lockfile.upsert(result.id, result.locked_entry);
+    }
+    lockfile.save(&lockfile_path)?;
+    say!(
+        "Updated {} {} in {}. Run `aps sync` to install {}.",
+        updated,
+        if updated == 1 { "pin" } else { "pins" },
+        style(lockfile_path.display()).cyan(),
+        if updated == 1 { "it" } else { "them" }
+    );
+    Ok(())
+}
+
+/// Classify an install result for display
+fn sync_status(result: &InstallResult) -> SyncStatus {
+    if !result.warnings.is_empty() {
+        SyncStatus::Warning
+    } else if result.skipped_no_change && result.upgrade_available.is_some() {
+        SyncStatus::Upgradable
+    } else if result.skipped_no_change {
+        SyncStatus::Current
+    } else if result.dest_existed {
+        SyncStatus::Updated
+    } else {
+        SyncStatus::Created
+    }
+}
+
+/// Write install results into the lockfile, including each entry's last sync outcome
+fn record_sync_results(lockfile: &mut Lockfile, results: &[InstallResult]) {
+    for result in results {
+        if let Some(ref locked_entry) = result.locked_entry {
+            let mut locked_entry = locked_entry.clone();
+            let changed = lockfile
+                .entries
+                .get(&result.id)
+                .is_none_or(|previous| previous.checksum != locked_entry.checksum);
+            if changed {
+                locked_entry.last_updated_at = Some(Utc::now().to_rfc3339());
+            }
+            lockfile.upsert(result.id.clone(), locked_entry);
+        }
+
+        let status = match sync_status(result) {
+            SyncStatus::Warning => LastSyncStatus::Warning,
+            SyncStatus::Created | SyncStatus::Updated if result.was_symlink => {
+                LastSyncStatus::Synced
+            }
+            SyncStatus::Created | SyncStatus::Updated => LastSyncStatus::Copied,
+            SyncStatus::Error => LastSyncStatus::Failed,
+            // Unchanged entries keep their previous outcome unless it was a failure
+            SyncStatus::Current | SyncStatus::Upgradable | SyncStatus::Frozen => {
+                match lockfile.entries.get(&result.id) {
+                    Some(locked)
+                        if locked.last_status.is_some_and(|s| {
+                            !matches!(
+                                s,
+                                LastSyncStatus::Failed
+                                    | LastSyncStatus::TimedOut
+                                    | LastSyncStatus::Overridden
+                                    | LastSyncStatus::Pending
+                            )
+                        }) =>
+                    {
+                        continue
+                    }
+                    Some(locked) if locked.is_symlink => LastSyncStatus::Synced,
+                    _ => LastSyncStatus::Copied,
+                }
+            }
+        };
+        lockfile.record_outcome(&result.id, status, None);
+    }
+}
+
+/// Entry IDs named by `--override <id>=<source>` values
+fn override_ids(overrides: &[String]) -> Vec<&str> {
+    overrides
+        .iter()
+        .filter_map(|spec| spec.split_once('=').map(|(id, _)| id))
+        .collect()
+}
+
+/// Put back the locks of overridden entries, dropping what the override
+/// installed. Entries the override changed on disk are marked so the next
+/// sync reinstalls the locked source.
+fn restore_overridden(
+    lockfile: &mut Lockfile,
+    held: Vec<(String, Option<LockedEntry>)>,
+    results: &[InstallResult],
+) {
+    for (id, locked) in held {
+        match locked {
+            Some(mut locked) => {
+                if results.iter().any(|r| r.id == id && r.installed) {
+                    locked.last_status = Some(LastSyncStatus::Overridden);
+                    locked.dest_stale = true;
+                }
+                lockfile.entries.insert(id, locked);
+            }
+            None => {
+                lockfile.entries.remove(&id);
+            }
+        }
+    }
+}
+
+/// An entry that failed without aborting the rest of the sync
+struct SyncFailure<'a> {
+    entry: &'a Entry,
+    status: LastSyncStatus,
+    message: String,
+}
+
+/// Record failed entries so `--retry-failed` picks them up.
+fn record_failures(lockfile: &mut Lockfile, failures: &[SyncFailure]) {
+    for failure in failures {
+        lockfile.record_outcome(
+            &failure.entry.id,
+            failure.status,
+            Some(failure.message.clone()),
+        );
+    }
+}
+
+/// Execute the `aps validate` command
+pub fn cmd_validate(args: ValidateArgs) -> Result<()> {
+    // Discover and load manifest
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    say!("Validating manifest at {:?}", manifest_path);
+
+    // Validate schema
+    validate_manifest(&manifest)?;
+    say!("  Schema validation passed");
+
+    // Settings that parse but do nothing, like `include` on a single file
+    let raw = std::fs::read_to_string(&manifest_path)
+        .map_err(|e| ApsError::io(e, format!("Failed to read manifest at {:?}", manifest_path)))?;
+    let lints: Vec<_> = lint_manifest(&manifest, &raw)
+        .into_iter()
+        .filter(|lint| {
+            !manifest
+                .suppressed_warnings(&lint.id)
+                .contains(WarningCode::IneffectiveSetting)
+        })
+        .collect();
+    if args.strict {
+        if let Some(lint) = lints.first() {
+            return Err(lint.clone().into_error());
+        }
+    }
+    let mut warnings: Vec<Warning> = lints
+        .iter()
+        .map(|lint| Warning::new(WarningCode::IneffectiveSetting, lint.to_string()))
+        .collect();
+    for warning in &warnings {
+        say!("  {} {}", style("[WARN]").yellow(), style(warning).yellow());
+    }
+
+    // Check for overlapping destinations
+    check_case_collisions(&manifest, &manifest_dir(&manifest_path))?;
+    let overlap_warnings = detect_overlapping_destinations(&manifest);
+    for warning in &overlap_warnings {
+        say!("  {} {}", style("[WARN]").yellow(), style(warning).yellow());
+    }
+
+    // Check sources are reachable
+    let base_dir = manifest_dir(&manifest_path);
+
+    let mut name_mismatches = Vec::new();
+
+    let only_kinds = parse_kinds(&args.only_kind)?;
+
+    let selected: Vec<&Entry> = manifest
+        .entries
+        .iter()
+        .filter(|e| only_kinds.is_empty() || only_kinds.contains(&e.kind))
+        .collect();
+
+    // Clone each git repository once, several at a time, reporting clones as
+    // they finish; entries are then checked in manifest order
+    let sources: Vec<&Source> = selected
+        .iter()
+        .flat_map(|e| e.source.iter().chain(&e.sources))
+        .filter(|s| s.git_info().is_some())
+        .collect();
+    let jobs = args
+        .jobs
+        .or(UserConfig::load()?.jobs)
+        .map_or(DEFAULT_JOBS, usize::from);
+    if !sources.is_empty() {
+        say!("\nCloning git sources ({} at a time):", jobs);
+    }
+    let mut clones = ClonePool::prefetch(sources, jobs, |repo, git_ref, clone| match clone {
+        Ok(clone) => say!("  [done] {} @ {}", repo, clone.resolved_ref),
+        Err(_) => say!("  [fail] {} @ {}", repo, git_ref),
+    });
+
+    say!("\nValidating entries:");
+    for entry in selected {
+        // Suppressed problems are neither reported nor escalated by --strict
+        let suppressed = manifest.suppressed_warnings(&entry.id);
+        let strict = |code| args.strict && !suppressed.contains(code);
+        let unavailable =
+            |message: String| suppressed.coded(WarningCode::SourceUnavailable, vec![message]);
+
+        // Handle composite entries differently
+        if entry.is_composite() {
+            say_inline!(
+                "  [..] {} (composite) - checking {} sources...",
+                entry.id,
+                entry.sources.len()
+            );
+            std::io::stdout().flush().ok();
+
+            let mut all_valid = true;
+            for source in &entry.sources {
+                if matches!(source, Source::Generated { .. }) {
+                    continue;
+                }
+                match clones.resolve(source, &base_dir) {
+                    Ok(resolved) => {
+                        if !resolved.source_path.exists() {
+                            let warning = missing_path_warning(&resolved);
+                            if strict(WarningCode::SourceUnavailable) {
+                                say!(" FAILED");
+                                return Err(resolved.path_not_found());
+                            }
+                            let found = unavailable(warning);
+                            all_valid &= found.is_empty();
+                            warnings.extend(found);
+                        }
+                    }
+                    Err(e) => {
+                        if strict(WarningCode::SourceUnavailable) {
+                            say!(" FAILED");
+                            return Err(e);
+                        }
+                        let found = unavailable(format!("Source validation failed: {}", e));
+                        all_valid &= found.is_empty();
+                        warnings.extend(found);
+                    }
+                }
+            }
+
+            // Compose the sources to check the output against compose_limits
+            let mut composed_warnings = Vec::new();
+            if all_valid {
+                let code = WarningCode::ComposeLimitExceeded;
+                let problems = compose_entry_sources(entry, &manifest, &base_dir, None, false)
+                    .map(|composition| composition.problems)
+                    .unwrap_or_default();
+                if strict(code) && !problems.is_empty() {
+                    say!(" FAILED");
+                    return Err(ApsError::ComposeLimitExceeded {
+                        id: entry.id.clone(),
+                        problems: problems.join("; "),
+                    });
+                }
+                composed_warnings = suppressed.coded(code, problems);
+                all_valid &= composed_warnings.is_empty();
+            }
+
+            if all_valid {
+                say!(
+                    "\r  [OK] {} (composite, {} sources)",
+                    entry.id,
+                    entry.sources.len()
+                );
+            } else {
+                say!(" WARN");
+            }
+            for warning in &composed_warnings {
+                say!("       Warning: {}", warning);
+            }
+            warnings.extend(composed_warnings);
+            continue;
+        }
+
         // Handle regular (single-source) entries
         let source = match &entry.source {
             Some(s) => s,
             None => {
                 let warning = format!("Entry '{}' has no source configured", entry.id);
-                if args.strict {
+                if strict(WarningCode::SourceUnavailable) {
                     return Err(ApsError::EntryRequiresSource {
                         id: entry.id.clone(),
                     });
                 }
-                println!("  [WARN] {} - {}", entry.id, warning);
-                warnings.push(warning);
+                for warning in unavailable(warning) {
+                    say!("  [WARN] {} - {}", entry.id, warning);
+                    warnings.push(warning);
+                }
                 continue;
             }
         };
@@ -1104,141 +3638,1240 @@ pub fn cmd_validate(args: ValidateArgs) -> Result<()> {
 
         // For git sources, show progress indicator
         if source_type == "git" {
-            print!("  [..] {} ({}) - checking...", entry.id, display_name);
+            say_inline!("  [..] {} ({}) - checking...", entry.id, display_name);
             std::io::stdout().flush().ok();
         }
 
-        match adapter.resolve(&base_dir) {
+        match clones.resolve(source, &base_dir) {
             Ok(resolved) => {
                 if !resolved.source_path.exists() {
-                    let warning = format!("Source path not found: {:?}", resolved.source_path);
-                    if args.strict {
+                    let warning = missing_path_warning(&resolved);
+                    if strict(WarningCode::SourceUnavailable) {
                         if source_type == "git" {
-                            println!(" FAILED");
+                            say!(" FAILED");
                         }
-                        return Err(ApsError::SourcePathNotFound {
-                            path: resolved.source_path,
-                        });
+                        return Err(resolved.path_not_found());
                     }
-                    if source_type == "git" {
-                        println!(" WARN");
-                        println!("       Warning: {}", warning);
-                    } else {
-                        println!("  [WARN] {} - {}", entry.id, warning);
+                    let found = unavailable(warning);
+                    if source_type == "git" && found.is_empty() {
+                        say!("\r  [OK] {} ({})", entry.id, display_name);
+                    } else if source_type == "git" {
+                        say!(" WARN");
+                    }
+                    for warning in found {
+                        if source_type == "git" {
+                            say!("       Warning: {}", warning);
+                        } else {
+                            say!("  [WARN] {} - {}", entry.id, warning);
+                        }
+                        warnings.push(warning);
                     }
-                    warnings.push(warning);
                 } else {
                     // Validate skills if applicable
+                    let mut asset_warnings = Vec::new();
                     if entry.kind == AssetKind::CursorSkillsRoot {
-                        let skill_warnings = validate_skills_for_validate(
+                        let code = WarningCode::MissingSkillMd;
+                        let found = validate_skills_for_validate(
                             &resolved.source_path,
                             &entry.id,
-                            args.strict,
+                            strict(code),
                         )?;
-                        warnings.extend(skill_warnings);
+                        asset_warnings.extend(suppressed.coded(code, found));
                     }
+                    let mut hooks_preview = None;
                     if entry.kind == AssetKind::CursorHooks {
-                        let hook_warnings =
-                            validate_cursor_hooks(&resolved.source_path, args.strict)?;
-                        for warning in &hook_warnings {
-                            println!("       Warning: {}", warning);
+                        let code = WarningCode::InvalidHooks;
+                        let found = validate_cursor_hooks(&resolved.source_path, strict(code))?;
+                        asset_warnings.extend(suppressed.coded(code, found));
+                        if args.show_hooks {
+                            let preview = preview_hooks(&resolved.source_path, strict(code))?;
+                            asset_warnings.extend(suppressed.coded(code, preview.warnings.clone()));
+                            hooks_preview = Some(preview);
                         }
-                        warnings.extend(hook_warnings);
                     }
+                    if entry.kind == AssetKind::Persona {
+                        let code = WarningCode::InvalidPersona;
+                        let found = validate_personas(&resolved.source_path, strict(code))?;
+                        asset_warnings.extend(suppressed.coded(code, found));
+                    }
+                    for warning in &asset_warnings {
+                        say!("       Warning: {}", warning);
+                    }
+                    warnings.extend(asset_warnings);
+                    let name_mismatch = if entry.kind == AssetKind::AgentSkill {
+                        check_skill_name_sync(entry, &resolved.source_path)
+                    } else {
+                        None
+                    };
                     // Format output based on source type
                     if let Some(git_info) = &resolved.git_info {
-                        println!(
+                        say!(
                             "\r  [OK] {} ({} @ {})",
-                            entry.id, display_name, git_info.resolved_ref
+                            entry.id,
+                            display_name,
+                            git_info.resolved_ref
                         );
                     } else {
-                        println!("  [OK] {} ({})", entry.id, display_name);
+                        say!("  [OK] {} ({})", entry.id, display_name);
+                    }
+                    if let Some(preview) = hooks_preview {
+                        print_hooks_preview(&preview, &entry.destination());
+                    }
+                    if let Some(mismatch) = name_mismatch {
+                        let found = suppressed
+                            .coded(WarningCode::SkillNameMismatch, vec![mismatch.message()]);
+                        for warning in found {
+                            say!("       Warning: {}", warning);
+                            warnings.push(warning);
+                        }
+                        name_mismatches.push(mismatch);
                     }
                 }
             }
             Err(e) => {
-                if args.strict {
+                if strict(WarningCode::SourceUnavailable) {
                     if source_type == "git" {
-                        println!(" FAILED");
+                        say!(" FAILED");
                     }
                     return Err(e);
                 }
-                if source_type == "git" {
-                    println!(" WARN");
+                let found = unavailable(format!("Source validation failed: {}", e));
+                if source_type == "git" && found.is_empty() {
+                    say!("\r  [OK] {} ({})", entry.id, display_name);
+                } else if source_type == "git" {
+                    say!(" WARN");
+                }
+                for warning in found {
+                    say!("       Warning: {}", warning);
+                    warnings.push(warning);
                 }
-                let warning = format!("Source validation failed: {}", e);
-                println!("       Warning: {}", warning);
-                warnings.push(warning);
             }
         }
     }
 
-    // Print summary
-    println!();
-    if warnings.is_empty() {
-        println!(
-            "Manifest is valid. All {} entries validated successfully.",
-            manifest.entries.len()
+    if args.fix && !name_mismatches.is_empty() {
+        say!();
+        fix_skill_names(&manifest, &manifest_path, &name_mismatches)?;
+    } else if !name_mismatches.is_empty() {
+        say!("\nRun with --fix to rename entries to match their SKILL.md names.");
+    }
+
+    // Print summary
+    summary::add_count("entries", manifest.entries.len());
+    summary::add_count("warnings", warnings.len());
+    say!();
+    if warnings.is_empty() {
+        say!(
+            "Manifest is valid. All {} entries validated successfully.",
+            manifest.entries.len()
+        );
+    } else {
+        say!("Manifest is valid with {} warning(s).", warnings.len());
+        if !args.strict {
+            say!("Run with --strict to treat warnings as errors.");
+        }
+    }
+
+    Ok(())
+}
+
+/// Print what the editor will run for a cursor_hooks entry: each event, its
+/// commands, and where their scripts land once synced to `dest`
+fn print_hooks_preview(preview: &HooksPreview, dest: &Path) {
+    if preview.hooks.is_empty() {
+        say!("       No hook commands");
+        return;
+    }
+    let root = hooks_root_dir(dest);
+    let mut event = "";
+    for hook in &preview.hooks {
+        if hook.event != event {
+            event = &hook.event;
+            say!("       {}", style(event).cyan());
+        }
+        say!("         {}", hook.command);
+        for script in &hook.scripts {
+            say!(
+                "           {} {}",
+                style("→").dim(),
+                display_dest(&root.join(script).to_string_lossy())
+            );
+        }
+    }
+}
+
+/// An agent skill whose SKILL.md `name` disagrees with its entry ID or
+/// installed directory name.
+struct SkillNameMismatch {
+    entry_id: String,
+    skill_name: String,
+    dir_name: String,
+    /// ID that matches the skill name (keeps any namespace prefix)
+    new_id: String,
+    /// Destination that matches the skill name, if the current one doesn't
+    new_dest: Option<String>,
+}
+
+impl SkillNameMismatch {
+    fn message(&self) -> String {
+        let mut parts = Vec::new();
+        if self.new_id != self.entry_id {
+            parts.push(format!("entry ID '{}'", self.entry_id));
+        }
+        if self.new_dest.is_some() {
+            parts.push(format!("installed directory '{}'", self.dir_name));
+        }
+        format!(
+            "SKILL.md name '{}' does not match {}",
+            self.skill_name,
+            parts.join(" or ")
+        )
+    }
+}
+
+/// Compare a skill's SKILL.md `name` with its entry ID and installed directory name.
+///
+/// The directory is only checked when the entry has its own `dest`; without one
+/// the skill installs straight into the kind's shared root, which is not ours to rename.
+fn check_skill_name_sync(entry: &Entry, source_path: &Path) -> Option<SkillNameMismatch> {
+    let skill_md = find_skill_md(source_path)?;
+    let skill_name = read_skill_name(&skill_md)?;
+
+    // Only names that can be used as a directory are actionable
+    if skill_name.contains(['/', '\\']) || skill_name.starts_with('.') {
+        return None;
+    }
+
+    let (prefix, id_name) = match entry.id.rsplit_once('/') {
+        Some((prefix, name)) => (Some(prefix), name),
+        None => (None, entry.id.as_str()),
+    };
+    let dir_name = entry.dest.as_ref().and_then(|_| {
+        entry
+            .destination()
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+    });
+    let dir_matches = dir_name.as_deref().is_none_or(|d| d == skill_name);
+
+    if id_name == skill_name && dir_matches {
+        return None;
+    }
+
+    let new_id = match prefix {
+        Some(prefix) => format!("{}/{}", prefix, skill_name),
+        None => skill_name.clone(),
+    };
+    let new_dest = entry.dest.as_deref().filter(|_| !dir_matches).map(|dest| {
+        let trimmed_dest = dest.trim_end_matches('/');
+        match trimmed_dest.rsplit_once('/') {
+            Some((dest_parent, _)) => format!("{}/{}/", dest_parent, skill_name),
+            None => format!("{}/", skill_name),
+        }
+    });
+
+    Some(SkillNameMismatch {
+        entry_id: entry.id.clone(),
+        skill_name,
+        dir_name: dir_name.unwrap_or_default(),
+        new_id,
+        new_dest,
+    })
+}
+
+/// Rename entries (and their installed directories) to match SKILL.md names.
+fn fix_skill_names(
+    manifest: &Manifest,
+    manifest_path: &Path,
+    mismatches: &[SkillNameMismatch],
+) -> Result<()> {
+    let base_dir = manifest_dir(manifest_path);
+    let mut manifest = manifest.clone();
+    let lockfile_path = Lockfile::path_for_manifest(manifest_path, &manifest)?;
+    let mut lockfile = Lockfile::load(&lockfile_path).ok();
+
+    say!("Fixing skill names:");
+    for mismatch in mismatches {
+        if mismatch.new_id != mismatch.entry_id
+            && manifest.entries.iter().any(|e| e.id == mismatch.new_id)
+        {
+            say!(
+                "  {} {} - cannot rename to '{}': ID already in use",
+                style("[SKIP]").yellow(),
+                mismatch.entry_id,
+                mismatch.new_id
+            );
+            continue;
+        }
+
+        relocate_entry(
+            &mut manifest,
+            &base_dir,
+            &mismatch.entry_id,
+            &mismatch.new_id,
+            mismatch.new_dest.as_deref(),
+        )?;
+        if let Some(lockfile) = lockfile.as_mut() {
+            lockfile.rename_entry(
+                &mismatch.entry_id,
+                &mismatch.new_id,
+                mismatch.new_dest.as_deref(),
+            );
+        }
+        say!(
+            "  {} {} → {}",
+            style("[FIXED]").green(),
+            mismatch.entry_id,
+            mismatch.new_id
+        );
+    }
+
+    save_manifest(&manifest, manifest_path)?;
+    if let Some(mut lockfile) = lockfile {
+        lockfile.save(&lockfile_path)?;
+    }
+
+    Ok(())
+}
+
+/// Validate's warning for a missing source path, with the remediation steps
+/// of its error under `--explain`
+fn missing_path_warning(resolved: &ResolvedSource) -> String {
+    let warning = format!("Source path not found: {:?}", resolved.source_path);
+    match resolved.path_not_found() {
+        ApsError::SourcePathNotFound { help, .. } if explain::enabled() => {
+            format!("{}. {}", warning, help)
+        }
+        _ => warning,
+    }
+}
+
+/// Validate skills directory for the validate command
+fn validate_skills_for_validate(
+    source: &Path,
+    entry_id: &str,
+    strict: bool,
+) -> Result<Vec<String>> {
+    let mut warnings = Vec::new();
+
+    for dir_entry in std::fs::read_dir(source)
+        .map_err(|e| ApsError::io(e, format!("Failed to read skills directory {:?}", source)))?
+    {
+        let dir_entry = dir_entry.map_err(|e| ApsError::io(e, "Failed to read directory entry"))?;
+        let skill_path = dir_entry.path();
+
+        if !skill_path.is_dir() {
+            continue;
+        }
+
+        let skill_name = dir_entry.file_name().to_string_lossy().to_string();
+        let skill_md_path = skill_path.join("SKILL.md");
+
+        if !skill_md_path.exists() {
+            let warning = format!(
+                "Skill '{}' in entry '{}' is missing SKILL.md",
+                skill_name, entry_id
+            );
+            if strict {
+                return Err(ApsError::MissingSkillMd { skill_name });
+            }
+            warnings.push(warning);
+        }
+    }
+
+    Ok(warnings)
+}
+
+/// Execute the `aps status` command
+pub fn cmd_status(args: StatusArgs) -> Result<()> {
+    // Discover manifest to find lockfile location
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &manifest)?;
+
+    // Load lockfile
+    let mut lockfile = Lockfile::load_with_options(&lockfile_path, args.force)?;
+
+    // Keep only entries matching --filter
+    let filters = Filter::parse_all(&args.filter)?;
+    if !filters.is_empty() {
+        let matching: Vec<String> = lockfile
+            .entries
+            .iter()
+            .filter(|(id, locked)| {
+                let entry = manifest.entries.iter().find(|e| &e.id == *id);
+                matches_all(&filters, id, entry, Some(locked))
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        if matching.is_empty() {
+            say!("No lockfile entries match the filter.");
+            return Ok(());
+        }
+        let keep: Vec<&str> = matching.iter().map(String::as_str).collect();
+        lockfile.retain_entries(&keep);
+    }
+
+    // Check each destination on disk
+    let base_dir = manifest_dir(&manifest_path);
+    let states: HashMap<String, DestState> = lockfile
+        .entries
+        .iter()
+        .map(|(id, locked)| {
+            let entry = manifest.entries.iter().find(|e| &e.id == id);
+            (id.clone(), dest_state(entry, &base_dir, locked))
+        })
+        .collect();
+
+    if args.repair {
+        return repair_entries(&manifest, &args, &base_dir, &lockfile, &states);
+    }
+
+    // Display status
+    if args.porcelain {
+        display_status_porcelain(&lockfile, &states);
+    }
+    display_status(&lockfile, &manifest, &states);
+    let broken = states.values().filter(|s| s.is_broken()).count();
+    if broken > 0 {
+        say!(
+            "\n{} {} {} a broken destination. Run `aps status --repair` to reinstall {}.",
+            style("!").yellow(),
+            broken,
+            if broken == 1 {
+                "entry has"
+            } else {
+                "entries have"
+            },
+            if broken == 1 { "it" } else { "them" }
+        );
+    }
+    if let Some(changes) = lockfile.manifest_changes(&manifest, &manifest_checksum(&manifest_path)?)
+    {
+        display_manifest_changes(&changes);
+    }
+
+    // Report stale pins from cached remote data only (no network access)
+    let ids: Vec<&str> = manifest
+        .entries
+        .iter()
+        .map(|e| e.id.as_str())
+        .filter(|id| lockfile.entries.contains_key(*id))
+        .collect();
+    let mut remote_cache = RemoteRefCache::load(&manifest_dir(&manifest_path));
+    let stale = check_stale_pins(
+        &manifest,
+        &ids,
+        &lockfile,
+        &mut remote_cache,
+        RefreshPolicy::Never,
+        Utc::now(),
+    );
+    print_stale_pins(&stale);
+
+    Ok(())
+}
+
+/// Reinstall the entries of `aps status --repair` whose destination is
+/// broken. Modified destinations are backed up and removed and dangling
+/// symlinks are removed, so the sync below takes its repair path.
+fn repair_entries(
+    manifest: &Manifest,
+    args: &StatusArgs,
+    base_dir: &Path,
+    lockfile: &Lockfile,
+    states: &HashMap<String, DestState>,
+) -> Result<()> {
+    let mut ids: Vec<String> = Vec::new();
+    for entry in &manifest.entries {
+        let (Some(state), Some(locked)) = (states.get(&entry.id), lockfile.entries.get(&entry.id))
+        else {
+            continue;
+        };
+        let dest_path = base_dir.join(&locked.dest);
+        match state {
+            DestState::Ok => continue,
+            DestState::Missing => {}
+            DestState::Modified => {
+                let policy = BackupPolicy::for_manifest(manifest).for_entry(entry);
+                match create_backup(base_dir, &dest_path, &policy)? {
+                    Some(backup) => say!("Backed up {} to {:?}", locked.dest, backup),
+                    None => say!("Discarding changes to {} (backup: false)", locked.dest),
+                }
+                let removed = if dest_path.is_dir() {
+                    fs::remove_dir_all(&dest_path)
+                } else {
+                    fs::remove_file(&dest_path)
+                };
+                removed
+                    .map_err(|e| ApsError::io(e, format!("Failed to remove {:?}", dest_path)))?;
+            }
+            DestState::DanglingSymlink => {
+                for link in dangling_symlinks(&dest_path) {
+                    fs::remove_file(&link).map_err(|e| {
+                        ApsError::io(e, format!("Failed to remove dangling symlink {:?}", link))
+                    })?;
+                }
+            }
+        }
+        say!("Repairing {} ({})", entry.id, state);
+        ids.push(entry.id.clone());
+    }
+
+    if ids.is_empty() {
+        say!("{} All destinations are intact.", style("✓").green());
+        return Ok(());
+    }
+    say!();
+    cmd_sync(SyncArgs {
+        manifest: args.manifest.clone(),
+        only: ids,
+        yes: Approvals::ALL,
+        ignore_manifest: false,
+        dry_run: false,
+        strict: false,
+        upgrade: false,
+        force: args.force,
+        retry_failed: false,
+        keep_going: false,
+        entry_timeout: None,
+        timeout: None,
+        plan: None,
+        only_kind: Vec::new(),
+        offline: false,
+        refresh: RefreshPolicy::Always,
+        stale_after: None,
+        explain: false,
+        jobs: None,
+        low_bandwidth: false,
+        overrides: Vec::new(),
+        porcelain: false,
+        review: false,
+    })
+}
+
+/// Print the entries of `aps list --outdated`: git entries whose locked
+/// commit is behind the remote head, furthest behind first
+fn list_outdated(
+    manifest: &Manifest,
+    entries: &[&Entry],
+    lockfile: Option<&Lockfile>,
+    base_dir: &Path,
+    args: &ListArgs,
+) -> Result<()> {
+    let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+    let mut remote_cache = RemoteRefCache::load(base_dir);
+    let outdated = match lockfile {
+        Some(lockfile) => check_outdated(
+            manifest,
+            &ids,
+            lockfile,
+            &mut remote_cache,
+            if args.offline {
+                RefreshPolicy::Never
+            } else {
+                RefreshPolicy::Always
+            },
+            Utc::now(),
+        ),
+        None => Vec::new(),
+    };
+    if !args.offline && !remote_cache.refs.is_empty() {
+        remote_cache.save(base_dir)?;
+    }
+
+    if args.porcelain {
+        for item in &outdated {
+            let dest = entries
+                .iter()
+                .find(|e| e.id == item.entry_id)
+                .map(|e| format_entry_dest(e))
+                .unwrap_or_default();
+            porcelain_line(&item.entry_id, "outdated", &dest);
+        }
+        return Ok(());
+    }
+
+    if outdated.is_empty() {
+        say!("{} All git entries are up to date.", style("✓").green());
+        return Ok(());
+    }
+    say!(
+        "{} {} behind upstream:",
+        outdated.len(),
+        if outdated.len() == 1 {
+            "entry is"
+        } else {
+            "entries are"
+        }
+    );
+    say!();
+    for item in &outdated {
+        let behind = match item.behind {
+            Some(1) => "1 commit behind".to_string(),
+            Some(n) => format!("{} commits behind", n),
+            None => "behind".to_string(),
+        };
+        let age = item
+            .age_days
+            .map(|days| format!(", pinned commit is {} days old", days))
+            .unwrap_or_default();
+        say!(
+            "  {} {}{}",
+            style(&item.entry_id).white().bold(),
+            style(behind).yellow(),
+            style(age).dim()
+        );
+        say!(
+            "    {} {} -> {}",
+            style("Commit:").dim(),
+            short_id(&item.locked_commit),
+            short_id(&item.remote_commit)
+        );
+    }
+    say!();
+    say!("Run `aps sync --upgrade` to install the latest commits.");
+    Ok(())
+}
+
+/// Execute the `aps manifest rebuild` command
+pub fn cmd_manifest_rebuild(args: ManifestRebuildArgs) -> Result<()> {
+    let manifest_path = match args.manifest {
+        Some(p) => p,
+        None => std::env::current_dir()
+            .map_err(|e| ApsError::io(e, "Failed to get current directory"))?
+            .join(DEFAULT_MANIFEST_NAME),
+    };
+    let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &Manifest::default())?;
+    let lockfile = Lockfile::load(&lockfile_path)?;
+    if lockfile.entries.is_empty() {
+        return Err(ApsError::InvalidInput {
+            message: format!(
+                "Lockfile {:?} has no entries to rebuild from",
+                lockfile_path
+            ),
+        });
+    }
+
+    let mut rebuilt = RebuiltManifest::from_lockfile(&lockfile);
+    rebuilt.locate_git_paths(&lockfile);
+    let lockfile_name = lockfile_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| LOCKFILE_NAME.to_string());
+    let content = rebuilt.to_yaml(&lockfile_name)?;
+
+    if args.dry_run {
+        say_inline!("{}", content);
+        return Ok(());
+    }
+
+    if manifest_path.exists() && !args.force {
+        return Err(ApsError::ManifestAlreadyExists {
+            path: manifest_path,
+        });
+    }
+
+    say!(
+        "Rebuilt {} entries from {}",
+        rebuilt.manifest.entries.len(),
+        lockfile_name
+    );
+    if !rebuilt.unconfirmed.is_empty() {
+        say!("Fields to confirm:");
+        for u in &rebuilt.unconfirmed {
+            say!(
+                "  {} {}.{}: {}",
+                style("?").yellow(),
+                u.id,
+                u.field,
+                u.reason
+            );
+        }
+    }
+
+    if !args.yes {
+        if !interactive() {
+            return Err(ApsError::RequiresYesFlag);
+        }
+        let confirm = dialoguer::Confirm::new()
+            .with_prompt(format!("Write manifest to {:?}?", manifest_path))
+            .default(true)
+            .interact()
+            .map_err(|_| ApsError::Cancelled)?;
+        if !confirm {
+            say!("Cancelled.");
+            return Ok(());
+        }
+    }
+
+    fs::write(&manifest_path, &content).map_err(|e| {
+        ApsError::io(
+            e,
+            format!("Failed to write manifest to {:?}", manifest_path),
+        )
+    })?;
+    say!(
+        "Wrote {}. Review the flagged fields, then run `aps sync`.",
+        style(manifest_path.display()).cyan()
+    );
+    Ok(())
+}
+
+/// Execute the `aps rename` command
+pub fn cmd_rename(args: RenameArgs) -> Result<()> {
+    let (mut manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
+
+    if args.old_id == args.new_id {
+        return Err(ApsError::InvalidInput {
+            message: format!("Entry is already named '{}'", args.new_id),
+        });
+    }
+    if manifest.entries.iter().any(|e| e.id == args.new_id) {
+        return Err(ApsError::DuplicateId {
+            id: args.new_id.clone(),
+        });
+    }
+
+    let entry = manifest
+        .entries
+        .iter()
+        .find(|e| e.id == args.old_id)
+        .ok_or_else(|| ApsError::EntryNotFound {
+            id: args.old_id.clone(),
+        })?;
+
+    // Only follow the rename on disk when the dest was derived from the old ID
+    let default_dest = manifest.default_dest(&entry.kind);
+    let old_derived_dest = skill_dest(&default_dest, &args.old_id);
+    let new_derived_dest = skill_dest(&default_dest, &args.new_id);
+    let dest_follows_id = entry
+        .dest
+        .as_deref()
+        .map(|d| same_relative_path(d, &old_derived_dest))
+        .unwrap_or(false)
+        && old_derived_dest != new_derived_dest;
+    let new_dest = dest_follows_id.then_some(new_derived_dest.as_str());
+
+    relocate_entry(
+        &mut manifest,
+        &base_dir,
+        &args.old_id,
+        &args.new_id,
+        new_dest,
+    )?;
+    save_manifest(&manifest, &manifest_path)?;
+
+    let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &manifest)?;
+    if let Ok(mut lockfile) = Lockfile::load(&lockfile_path) {
+        if lockfile.rename_entry(&args.old_id, &args.new_id, new_dest) {
+            lockfile.save(&lockfile_path)?;
+        }
+    }
+
+    say!(
+        "  {} {}",
+        style("✓").green(),
+        style(format!(
+            "Renamed entry '{}' to '{}'",
+            args.old_id, args.new_id
+        ))
+        .green()
+    );
+
+    Ok(())
+}
+
+/// Execute the `aps pin` command
+pub fn cmd_pin(args: PinArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
+    let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &manifest)?;
+    let mut lockfile = Lockfile::load(&lockfile_path)?;
+    let policy = BackupPolicy::for_manifest(&manifest);
+
+    // Check every entry before changing anything, so one bad ID pins nothing
+    let mut pins = Vec::new();
+    for id in &args.ids {
+        let entry = manifest
+            .entries
+            .iter()
+            .find(|e| &e.id == id)
+            .ok_or_else(|| ApsError::EntryNotFound { id: id.clone() })?;
+        let locked = lockfile
+            .entries
+            .get(id)
+            .ok_or_else(|| ApsError::PinUnsupported {
+                id: id.clone(),
+                reason: "it has not been synced yet".to_string(),
+            })?;
+        let checksum = installed_checksum(entry, &base_dir, locked)?;
+        pins.push((entry, checksum));
+    }
+
+    let mut changed = false;
+    for (entry, checksum) in pins {
+        let Some(locked) = lockfile.entries.get_mut(&entry.id) else {
+            continue;
+        };
+        if locked.checksum == checksum {
+            say!(
+                "  {} {} already matches its installed content",
+                style("·").dim(),
+                entry.id
+            );
+            continue;
+        }
+
+        if args.backup {
+            let backup_root = policy.for_entry(entry).root(&base_dir);
+            let point = store_backup(&base_dir, &backup_root, &base_dir.join(&locked.dest))?;
+            say!(
+                "  Stored installed content of {} as {}",
+                entry.id,
+                style(&point.id).cyan()
+            );
+        }
+        locked.checksum = checksum;
+        locked.last_updated_at = Some(Utc::now().to_rfc3339());
+        changed = true;
+        say!(
+            "  {} {}",
+            style("✓").green(),
+            style(format!("Pinned {} to its installed content", entry.id)).green()
+        );
+    }
+
+    if changed {
+        lockfile.save(&lockfile_path)?;
+    }
+    Ok(())
+}
+
+/// Execute the `aps bootstrap` command
+pub fn cmd_bootstrap(args: BootstrapArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
+    let lockfile = Lockfile::load(&Lockfile::path_for_manifest(&manifest_path, &manifest)?)?;
+
+    if args.emit_script {
+        say_inline!(
+            "{}",
+            render_bootstrap_script(&manifest, &lockfile, &base_dir)
+        );
+    }
+
+    Ok(())
+}
+
+/// Execute the `aps export sbom` command
+pub fn cmd_export_sbom(args: ExportSbomArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &manifest)?;
+    let lockfile = Lockfile::load(&lockfile_path)?;
+
+    let sbom = sbom::render(&lockfile, args.format);
+    let json = serde_json::to_string_pretty(&sbom).map_err(|e| ApsError::InvalidInput {
+        message: format!("Failed to serialize SBOM: {}", e),
+    })?;
+
+    match args.output {
+        Some(path) => {
+            std::fs::write(&path, format!("{}\n", json))
+                .map_err(|e| ApsError::io(e, format!("Failed to write SBOM to {:?}", path)))?;
+            say!(
+                "Wrote SBOM of {} entries to {}",
+                lockfile.entries.len(),
+                path.display()
+            );
+        }
+        None => say!("{}", json),
+    }
+    Ok(())
+}
+
+/// Execute the `aps schema` command
+pub fn cmd_schema(args: SchemaArgs) -> Result<()> {
+    let schema = schema_for_format(args.format);
+    let json = serde_json::to_string_pretty(&schema).map_err(|e| ApsError::InvalidInput {
+        message: format!("Failed to serialize schema: {}", e),
+    })?;
+
+    match args.output {
+        Some(path) => {
+            std::fs::write(&path, format!("{}\n", json))
+                .map_err(|e| ApsError::io(e, format!("Failed to write schema to {:?}", path)))?;
+            say!("Wrote schema to {}", path.display());
+        }
+        None => say!("{}", json),
+    }
+    Ok(())
+}
+
+/// Execute the `aps doctor` command
+pub fn cmd_doctor() -> Result<()> {
+    say!("aps {}", env!("CARGO_PKG_VERSION"));
+
+    let Some(version) = git_version() else {
+        say!("{} git not found", style("✗").red());
+        return Err(ApsError::GitNotFound);
+    };
+    if version < MIN_GIT_VERSION {
+        say!(
+            "{} git {} (aps needs git >= {})",
+            style("✗").red(),
+            version,
+            MIN_GIT_VERSION
+        );
+        return require_git().map(|_| ());
+    }
+    say!("{} git {}", style("✓").green(), version);
+
+    for feature in GitFeature::ALL {
+        if supports(feature) {
+            say!(
+                "  {} {} (git >= {})",
+                style("✓").green(),
+                feature.name(),
+                feature.min_version()
+            );
+        } else {
+            say!(
+                "  {} {} needs git >= {}: {}",
+                style("!").yellow(),
+                feature.name(),
+                feature.min_version(),
+                feature.fallback()
+            );
+        }
+    }
+
+    let locks = dest_lock::diagnose();
+    match locks.problem {
+        Some(problem) => say!(
+            "{} destination locks unavailable in {}: {}",
+            style("!").yellow(),
+            locks.dir.display(),
+            problem
+        ),
+        None => say!(
+            "{} destination locks in {} ({} lock file(s))",
+            style("✓").green(),
+            locks.dir.display(),
+            locks.files
+        ),
+    }
+    for dest in &locks.held {
+        say!(
+            "  {} {} is being written by another aps process",
+            style("!").yellow(),
+            dest
+        );
+    }
+    Ok(())
+}
+
+/// Execute the `aps serve` command
+pub fn cmd_serve(args: ServeArgs) -> Result<()> {
+    let stdin = std::io::stdin();
+    Server::new(args.manifest).run(stdin.lock(), std::io::stdout().lock())
+}
+
+/// Execute the `aps cache export` command
+pub fn cmd_cache_export(args: CacheExportArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
+    let lockfile = Lockfile::load(&Lockfile::path_for_manifest(&manifest_path, &manifest)?)?;
+
+    let index = export_cache(&manifest, &lockfile, &base_dir, &args.file)?;
+    let commits: usize = index.repos.iter().map(|r| r.commits.len()).sum();
+    say!(
+        "Exported {} repositories ({} locked commits) to {}",
+        index.repos.len(),
+        commits,
+        style(args.file.display()).cyan()
+    );
+    Ok(())
+}
+
+/// Execute the `aps cache import` command
+pub fn cmd_cache_import(args: CacheImportArgs) -> Result<()> {
+    let (_, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
+
+    let index = import_cache(&args.file, &base_dir)?;
+    for repo in &index.repos {
+        say!(
+            "  {} {} {}",
+            style("✓").green(),
+            repo.url,
+            style(format!("({} locked commits)", repo.commits.len())).dim()
+        );
+    }
+    say!(
+        "Imported {} repositories. Run `aps sync --offline` to install from the cache.",
+        index.repos.len()
+    );
+    Ok(())
+}
+
+/// Execute the `aps backup list` command
+pub fn cmd_backup_list(args: BackupListArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
+
+    let mut points = Vec::new();
+    for root in backup_roots(&manifest, &base_dir) {
+        points.extend(list_restore_points(&root)?);
+    }
+    points.sort_by(|a, b| (&a.created_at, &a.id).cmp(&(&b.created_at, &b.id)));
+    if points.is_empty() {
+        say!("No restore points. Set `backup_strategy: store` in the manifest to record them.");
+        return Ok(());
+    }
+    for point in &points {
+        let files = point.items.iter().filter(|i| i.object.is_some()).count();
+        say!(
+            "{}  {} {}",
+            style(&point.id).cyan(),
+            point.path,
+            style(format!("({} files, {})", files, point.created_at)).dim()
+        );
+    }
+    Ok(())
+}
+
+/// Execute the `aps orphans list` command
+pub fn cmd_orphans_list(args: OrphansListArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
+    let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &manifest)?;
+    let lockfile = load_or_create_lockfile(&lockfile_path, false)?;
+
+    let entries: Vec<&Entry> = manifest.entries.iter().collect();
+    let orphans = exclude_foreign_owned(
+        detect_orphaned_paths(&entries, &lockfile, &base_dir),
+        &foreign_owners(&manifest_path, &lockfile_path),
+        &base_dir,
+    );
+    if orphans.is_empty() {
+        say!("No orphaned paths.");
+        return Ok(());
+    }
+    let relative = |path: &Path| {
+        display_dest(
+            &path
+                .strip_prefix(&base_dir)
+                .unwrap_or(path)
+                .to_string_lossy(),
+        )
+    };
+    for orphan in &orphans {
+        say!(
+            "{}  {} {}",
+            style(&orphan.entry_id).cyan(),
+            relative(&orphan.old_dest),
+            style(format!("(now {})", relative(&orphan.new_dest))).dim()
         );
-    } else {
-        println!("Manifest is valid with {} warning(s).", warnings.len());
-        if !args.strict {
-            println!("Run with --strict to treat warnings as errors.");
-        }
+    }
+    say!("\nRun `aps orphans clean` to delete them.");
+    Ok(())
+}
+
+/// Execute the `aps orphans clean` command
+pub fn cmd_orphans_clean(args: OrphansCleanArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
+    let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &manifest)?;
+    let mut lockfile = load_lockfile_for_update(&lockfile_path, false, &manifest, &base_dir, true)?;
+
+    let entries: Vec<&Entry> = manifest.entries.iter().collect();
+    let orphans = exclude_foreign_owned(
+        detect_orphaned_paths(&entries, &lockfile, &base_dir),
+        &foreign_owners(&manifest_path, &lockfile_path),
+        &base_dir,
+    );
+    if orphans.is_empty() {
+        say!("No orphaned paths.");
+        return Ok(());
+    }
+    let options = InstallOptions {
+        dry_run: args.dry_run,
+        yes: if args.yes {
+            Approvals::ALL
+        } else {
+            Approvals::default()
+        },
+        strict: false,
+        upgrade: false,
+        offline: false,
+        backup: BackupPolicy::for_manifest(&manifest),
+        suppress_warnings: WarningSet::of(&manifest.suppress_warnings),
+        force_copy: ci::active() && !manifest.ci.symlink,
+        refresh: RefreshPolicy::Never,
+        symlink_style: manifest.symlink_style,
+    };
+    prompt_and_cleanup_orphans(&orphans, &options, &base_dir)?;
+    if args.dry_run {
+        return Ok(());
     }
 
+    // Keep what was not deleted so `aps sync` still offers it
+    let remaining = remaining_orphans(&orphans, &base_dir);
+    for orphan in &orphans {
+        if let Some(locked) = lockfile.entries.get_mut(&orphan.entry_id) {
+            let kept = remaining.get(&orphan.entry_id);
+            let locked_dest = locked.dest.clone();
+            locked.previous_dests = kept
+                .into_iter()
+                .flatten()
+                .filter(|dest| **dest != locked_dest)
+                .cloned()
+                .collect();
+        }
+    }
+    lockfile.save(&lockfile_path)?;
     Ok(())
 }
 
-/// Validate skills directory for the validate command
-fn validate_skills_for_validate(
-    source: &Path,
-    entry_id: &str,
-    strict: bool,
-) -> Result<Vec<String>> {
-    let mut warnings = Vec::new();
+/// Execute the `aps backup restore` command
+pub fn cmd_backup_restore(args: BackupRestoreArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
 
-    for dir_entry in std::fs::read_dir(source)
-        .map_err(|e| ApsError::io(e, format!("Failed to read skills directory {:?}", source)))?
-    {
-        let dir_entry = dir_entry.map_err(|e| ApsError::io(e, "Failed to read directory entry"))?;
-        let skill_path = dir_entry.path();
+    let (backup_root, point) = find_restore_point(&backup_roots(&manifest, &base_dir), &args.id)?;
+    let target = args.to.unwrap_or_else(|| base_dir.join(&point.path));
 
-        if !skill_path.is_dir() {
-            continue;
+    if let Ok(meta) = target.symlink_metadata() {
+        if !args.force {
+            return Err(ApsError::RestoreTargetExists { path: target });
         }
+        let current = store_backup(&base_dir, &backup_root, &target)?;
+        say!("Backed up current content as {}", style(&current.id).cyan());
+        let removed = if meta.is_dir() {
+            std::fs::remove_dir_all(&target)
+        } else {
+            std::fs::remove_file(&target)
+        };
+        removed.map_err(|e| ApsError::io(e, format!("Failed to remove {:?}", target)))?;
+    }
 
-        let skill_name = dir_entry.file_name().to_string_lossy().to_string();
-        let skill_md_path = skill_path.join("SKILL.md");
+    restore_point(&backup_root, &point, &target)?;
+    say!(
+        "{} Restored {} to {}",
+        style("✓").green(),
+        point.id,
+        target.display()
+    );
+    Ok(())
+}
 
-        if !skill_md_path.exists() {
-            let warning = format!(
-                "Skill '{}' in entry '{}' is missing SKILL.md",
-                skill_name, entry_id
-            );
-            if strict {
-                return Err(ApsError::MissingSkillMd { skill_name });
+/// Execute the `aps log` command
+pub fn cmd_log(args: LogArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
+    let lockfile = Lockfile::load(&Lockfile::path_for_manifest(&manifest_path, &manifest)?).ok();
+
+    let entry = manifest
+        .entries
+        .iter()
+        .find(|e| e.id == args.id)
+        .ok_or_else(|| ApsError::EntryNotFound {
+            id: args.id.clone(),
+        })?;
+    let locked = lockfile.as_ref().and_then(|l| l.entries.get(&entry.id));
+
+    let since = args.since.as_deref().map(Since::parse);
+    let log = Changelog::build(entry, locked, since, &base_dir)?;
+    print_changelog(&log);
+
+    Ok(())
+}
+
+/// Give a manifest entry a new ID and, optionally, a new destination, moving any
+/// installed files along with it. The caller is responsible for saving.
+fn relocate_entry(
+    manifest: &mut Manifest,
+    base_dir: &Path,
+    old_id: &str,
+    new_id: &str,
+    new_dest: Option<&str>,
+) -> Result<()> {
+    let entry = manifest
+        .entries
+        .iter_mut()
+        .find(|e| e.id == old_id)
+        .ok_or_else(|| ApsError::EntryNotFound {
+            id: old_id.to_string(),
+        })?;
+
+    // Entries without their own dest share the kind's root with other entries,
+    // so there is no directory of theirs to move
+    if let (Some(new_dest), Some(old_dest)) = (new_dest, entry.dest.clone()) {
+        if !same_relative_path(&old_dest, new_dest) {
+            let expanded = shellexpand::full(new_dest)
+                .map(|s| s.into_owned())
+                .unwrap_or_else(|_| new_dest.to_string());
+            let old_path = base_dir.join(entry.destination());
+            let new_path = base_dir.join(expanded);
+            if new_path.exists() {
+                return Err(ApsError::Conflict { path: new_path });
+            }
+            if old_path.exists() {
+                if let Some(parent) = new_path.parent() {
+                    fs::create_dir_all(parent).map_err(|e| {
+                        ApsError::io(e, format!("Failed to create directory {:?}", parent))
+                    })?;
+                }
+                fs::rename(&old_path, &new_path).map_err(|e| {
+                    ApsError::io(
+                        e,
+                        format!("Failed to move {:?} to {:?}", old_path, new_path),
+                    )
+                })?;
+                say!(
+                    "  {} {} → {}",
+                    style("✓").green(),
+                    style(&old_dest).dim(),
+                    style(new_dest).cyan()
+                );
             }
-            println!("       Warning: {}", warning);
-            warnings.push(warning);
         }
+        entry.dest = Some(new_dest.to_string());
     }
 
-    Ok(warnings)
+    entry.id = new_id.to_string();
+    Ok(())
 }
 
-/// Execute the `aps status` command
-pub fn cmd_status(args: StatusArgs) -> Result<()> {
-    // Discover manifest to find lockfile location
-    let (_, manifest_path) = discover_manifest(args.manifest.as_deref())?;
-    let lockfile_path = Lockfile::path_for_manifest(&manifest_path);
+/// Compare two manifest-relative paths, ignoring `./` prefixes and trailing slashes.
+fn same_relative_path(a: &str, b: &str) -> bool {
+    let normalize = |p: &str| p.trim_start_matches("./").trim_end_matches('/').to_string();
+    normalize(a) == normalize(b)
+}
 
-    // Load lockfile
-    let lockfile = Lockfile::load(&lockfile_path)?;
+/// Execute the `aps changelog` command
+pub fn cmd_changelog(args: ChangelogArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
+    let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &manifest)?;
 
-    // Display status
-    display_status(&lockfile);
+    let releases = history::build(&lockfile_path)?;
+    let markdown = history::render_markdown(&releases);
+    let Some(out) = args.write else {
+        say_inline!("{}", markdown);
+        return Ok(());
+    };
 
+    let out = base_dir.join(out);
+    fs::write(&out, &markdown)
+        .map_err(|e| ApsError::io(e, format!("Failed to write changelog to {:?}", out)))?;
+    say!(
+        "{} Wrote {} lockfile changes to {}",
+        style("✓").green(),
+        releases.len(),
+        out.display()
+    );
     Ok(())
 }
 
@@ -1246,6 +4879,7 @@ pub fn cmd_status(args: StatusArgs) -> Result<()> {
 pub fn cmd_list(args: ListArgs) -> Result<()> {
     let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
     let base_dir = manifest_dir(&manifest_path);
+    let filters = Filter::parse_all(&args.filter)?;
 
     let manifest_display = manifest_path
         .file_name()
@@ -1258,30 +4892,81 @@ pub fn cmd_list(args: ListArgs) -> Result<()> {
     let yellow = Style::new().yellow();
     let white_bold = Style::new().white().bold();
 
-    println!(
+    // Load lockfile once for status checks
+    let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &manifest)?;
+    let lockfile = Lockfile::load(&lockfile_path).ok();
+    let now = Utc::now();
+
+    let entries: Vec<&Entry> = manifest
+        .entries
+        .iter()
+        .filter(|e| {
+            let locked = lockfile.as_ref().and_then(|lf| lf.entries.get(&e.id));
+            matches_all(&filters, &e.id, Some(e), locked)
+        })
+        .collect();
+    let count_label = if filters.is_empty() {
+        format!("({} entries)", manifest.entries.len())
+    } else {
+        format!(
+            "({} of {} entries match)",
+            entries.len(),
+            manifest.entries.len()
+        )
+    };
+
+    if args.outdated {
+        return list_outdated(&manifest, &entries, lockfile.as_ref(), &base_dir, &args);
+    }
+
+    if args.porcelain {
+        for entry in &entries {
+            let synced = lockfile
+                .as_ref()
+                .is_some_and(|lf| lf.entries.contains_key(&entry.id));
+            porcelain_line(
+                &entry.id,
+                if synced { "synced" } else { "pending" },
+                &format_entry_dest(entry),
+            );
+        }
+    }
+
+    say!(
         "{} {} {}",
         style("Manifest:").dim(),
         cyan.apply_to(&manifest_display),
-        dim.apply_to(format!("({} entries)", manifest.entries.len()))
+        dim.apply_to(count_label)
     );
-    println!();
+    say!();
 
-    // Load lockfile once for status checks
-    let lockfile_path = Lockfile::path_for_manifest(&manifest_path);
-    let lockfile = Lockfile::load(&lockfile_path).ok();
+    if entries.is_empty() && !filters.is_empty() {
+        say!("No entries match the filter.");
+        return Ok(());
+    }
 
-    for (i, entry) in manifest.entries.iter().enumerate() {
-        // Entry header: ID and kind
+    for (i, entry) in entries.iter().enumerate() {
+        // Entry header: ID, kind, and whether upgrades skip it
         let kind_label = format_kind_label(&entry.kind);
-        println!(
-            "  {} {}",
+        let frozen_label = if entry.frozen { " [frozen]" } else { "" };
+        say!(
+            "  {} {}{}",
             white_bold.apply_to(&entry.id),
             dim.apply_to(&kind_label),
+            cyan.apply_to(frozen_label),
         );
 
+        // Manifest annotations
+        if let Some(ref description) = entry.description {
+            say!("  {} {}", dim.apply_to("About: "), description);
+        }
+        if let Some(ref owner) = entry.owner {
+            say!("  {} {}", dim.apply_to("Owner: "), owner);
+        }
+
         // Source info
         if entry.is_composite() {
-            println!(
+            say!(
                 "  {} composite ({} sources)",
                 dim.apply_to("Source:"),
                 entry.sources.len()
@@ -1292,7 +4977,7 @@ pub fn cmd_list(args: ListArgs) -> Result<()> {
                 } else {
                     "├──"
                 };
-                println!(
+                say!(
                     "  {}  {} {}",
                     dim.apply_to("       "),
                     dim.apply_to(connector),
@@ -1300,7 +4985,7 @@ pub fn cmd_list(args: ListArgs) -> Result<()> {
                 );
             }
         } else if let Some(ref source) = entry.source {
-            println!(
+            say!(
                 "  {} {}",
                 dim.apply_to("Source:"),
                 dim.apply_to(format_source_short(source)),
@@ -1309,15 +4994,8 @@ pub fn cmd_list(args: ListArgs) -> Result<()> {
 
         // Destination
         let dest = entry.destination();
-        let dest_display = {
-            let s = dest.to_string_lossy();
-            if s.starts_with("./") || s.starts_with('/') {
-                s.to_string()
-            } else {
-                format!("./{}", s)
-            }
-        };
-        println!(
+        let dest_display = format_entry_dest(entry);
+        say!(
             "  {} {}",
             dim.apply_to("Dest:  "),
             cyan.apply_to(&dest_display),
@@ -1325,13 +5003,19 @@ pub fn cmd_list(args: ListArgs) -> Result<()> {
 
         // Include filter
         if !entry.include.is_empty() {
-            println!(
+            say!(
                 "  {} {}",
                 dim.apply_to("Filter:"),
                 yellow.apply_to(entry.include.join(", ")),
             );
         }
 
+        // Upstream license recorded at the last sync
+        let locked = lockfile.as_ref().and_then(|lf| lf.entries.get(&entry.id));
+        if let Some(license) = locked.and_then(|l| l.license.as_ref()) {
+            say!("  {} {}", dim.apply_to("License:"), license);
+        }
+
         // On-disk asset tree (when --assets is passed and destination exists)
         if args.assets {
             let abs_dest = if dest.is_relative() {
@@ -1341,10 +5025,10 @@ pub fn cmd_list(args: ListArgs) -> Result<()> {
             };
 
             if abs_dest.is_dir() {
-                println!("  {}", dim.apply_to("Assets:"));
+                say!("  {}", dim.apply_to("Assets:"));
                 print_asset_tree(&abs_dest, &entry.kind, "  ");
             } else if abs_dest.is_file() {
-                println!(
+                say!(
                     "  {} {}",
                     dim.apply_to("Assets:"),
                     green.apply_to(
@@ -1355,7 +5039,7 @@ pub fn cmd_list(args: ListArgs) -> Result<()> {
                     ),
                 );
             } else {
-                println!(
+                say!(
                     "  {} {}",
                     dim.apply_to("Assets:"),
                     dim.apply_to("(not synced)"),
@@ -1363,38 +5047,49 @@ pub fn cmd_list(args: ListArgs) -> Result<()> {
             }
         }
 
-        // Sync status indicator
-        if let Some(ref lf) = lockfile {
-            if lf.entries.contains_key(&entry.id) {
-                println!("  {} {}", green.apply_to("●"), green.apply_to("synced"));
+        // Sync status indicator, with how long ago the entry last changed
+        if let Some(locked) = locked {
+            match last_updated_age(locked, now) {
+                Some(age) if args.stale_after.is_some_and(|limit| age > limit) => say!(
+                    "  {} {} {}",
+                    yellow.apply_to("●"),
+                    yellow.apply_to("synced"),
+                    yellow.apply_to(format!("· updated {} (stale)", format_age(age))),
+                ),
+                Some(age) => say!(
+                    "  {} {} {}",
+                    green.apply_to("●"),
+                    green.apply_to("synced"),
+                    dim.apply_to(format!("· updated {}", format_age(age))),
+                ),
+                None => say!("  {} {}", green.apply_to("●"), green.apply_to("synced")),
             }
         }
 
         // Separator between entries (but not after the last)
-        if i < manifest.entries.len() - 1 {
-            println!();
+        if i < entries.len() - 1 {
+            say!();
         }
     }
 
-    println!();
+    say!();
 
     // Summary
     let synced_count = match lockfile {
-        Some(ref lf) => manifest
-            .entries
+        Some(ref lf) => entries
             .iter()
             .filter(|e| lf.entries.contains_key(&e.id))
             .count(),
         None => 0,
     };
-    let total = manifest.entries.len();
+    let total = entries.len();
     if synced_count == total {
-        println!(
+        say!(
             "{}",
             green.apply_to(format!("All {} entries synced", total))
         );
     } else {
-        println!(
+        say!(
             "{} synced, {} pending",
             green.apply_to(synced_count),
             yellow.apply_to(total - synced_count),
@@ -1404,6 +5099,318 @@ pub fn cmd_list(args: ListArgs) -> Result<()> {
     Ok(())
 }
 
+/// Execute the `aps sort` command
+pub fn cmd_sort(args: SortArgs) -> Result<()> {
+    let (mut manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+
+    if !sort_entries(&mut manifest.entries) {
+        say!("Entries in {:?} are already sorted.", manifest_path);
+        return Ok(());
+    }
+    if args.check {
+        return Err(ApsError::ManifestUnsorted {
+            path: manifest_path,
+        });
+    }
+
+    save_manifest(&manifest, &manifest_path)?;
+    say!(
+        "{} Sorted {} entries in {:?} by kind, then ID",
+        style("✓").green(),
+        manifest.entries.len(),
+        manifest_path
+    );
+    Ok(())
+}
+
+/// Execute the `aps grep` command
+pub fn cmd_grep(args: GrepArgs) -> Result<()> {
+    let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
+    let base_dir = manifest_dir(&manifest_path);
+    let filters = Filter::parse_all(&args.filter)?;
+    let pattern = build_pattern(&args.pattern, args.ignore_case, args.fixed_strings)?;
+
+    // Only synced entries have something installed to search
+    let lockfile_path = Lockfile::path_for_manifest(&manifest_path, &manifest)?;
+    let lockfile = Lockfile::load(&lockfile_path).unwrap_or_default();
+
+    let relative = |path: &Path| {
+        display_dest(
+            &path
+                .strip_prefix(&base_dir)
+                .unwrap_or(path)
+                .to_string_lossy(),
+        )
+    };
+    let highlight = Style::new().red().bold();
+
+    let (mut entry_count, mut file_count, mut line_count) = (0, 0, 0);
+    for entry in &manifest.entries {
+        let Some(locked) = lockfile.entries.get(&entry.id) else {
+            continue;
+        };
+        if !matches_all(&filters, &entry.id, Some(entry), Some(locked)) {
+            continue;
+        }
+        let found = search(&base_dir.join(&locked.dest), &pattern);
+        if found.is_empty() {
+            continue;
+        }
+
+        if entry_count > 0 {
+            say!();
+        }
+        say!("{}", style(&entry.id).cyan().bold());
+        for file in &found {
+            say!("  {}", relative(&file.path));
+            if args.files_with_matches {
+                continue;
+            }
+            for line in &file.lines {
+                let text = pattern.replace_all(&line.text, |caps: &regex::Captures| {
+                    highlight.apply_to(&caps[0]).to_string()
+                });
+                say!(
+                    "    {} {}",
+                    style(format!("{}:", line.number)).green(),
+                    text
+                );
+            }
+        }
+        entry_count += 1;
+        file_count += found.len();
+        line_count += found.iter().map(|f| f.lines.len()).sum::<usize>();
+    }
+
+    if entry_count == 0 {
+        say!("No matches.");
+    } else if !args.files_with_matches {
+        say!(
+            "\n{}",
+            style(format!(
+                "{} matching line(s) in {} file(s) across {} entr{}",
+                line_count,
+                file_count,
+                entry_count,
+                if entry_count == 1 { "y" } else { "ies" }
+            ))
+            .dim()
+        );
+    }
+    Ok(())
+}
+
+/// Execute the `aps who-uses` command
+pub fn cmd_who_uses(args: WhoUsesArgs) -> Result<()> {
+    let pattern = SourcePattern::parse(&args.source)?;
+    let cwd =
+        std::env::current_dir().map_err(|e| ApsError::io(e, "Failed to get current directory"))?;
+    // Manifests are shown relative to the workspace root
+    let workspace = workspace_root(&cwd);
+    let roots = if args.paths.is_empty() {
+        vec![workspace.clone()]
+    } else {
+        args.paths.iter().map(|p| cwd.join(p)).collect()
+    };
+    let manifests = find_manifests(&roots);
+    let consumers = who_uses(&pattern, &manifests);
+
+    let relative = |path: &Path| {
+        path.strip_prefix(&workspace)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string()
+    };
+
+    if args.porcelain {
+        for consumer in &consumers {
+            porcelain_line(
+                &relative(&consumer.manifest),
+                &consumer.entry_id,
+                &consumer.source,
+            );
+        }
+        return Ok(());
+    }
+
+    if consumers.is_empty() {
+        say!(
+            "No entries use {} ({} manifest(s) searched).",
+            style(&args.source).cyan(),
+            manifests.len()
+        );
+        return Ok(());
+    }
+
+    let mut current: Option<&Path> = None;
+    for consumer in &consumers {
+        if current != Some(consumer.manifest.as_path()) {
+            if current.is_some() {
+                say!();
+            }
+            say!("{}", style(relative(&consumer.manifest)).bold());
+            current = Some(&consumer.manifest);
+        }
+        say!(
+            "  {}  {}",
+            style(&consumer.entry_id).cyan(),
+            style(&consumer.source).dim()
+        );
+    }
+    let manifest_count = consumers
+        .iter()
+        .map(|c| &c.manifest)
+        .collect::<HashSet<_>>()
+        .len();
+    say!(
+        "\n{}",
+        style(format!(
+            "{} entr{} in {} of {} manifest(s) use {}",
+            consumers.len(),
+            if consumers.len() == 1 { "y" } else { "ies" },
+            manifest_count,
+            manifests.len(),
+            args.source
+        ))
+        .dim()
+    );
+    Ok(())
+}
+
+/// Load the project index, noting when syncs do not update it
+fn load_project_index() -> Result<ProjectIndex> {
+    let Some(path) = index_path() else {
+        return Ok(ProjectIndex::default());
+    };
+    if !UserConfig::load()?.index {
+        say!(
+            "{}",
+            style(
+                "The project index is off; set `index: true` in the user config to record syncs."
+            )
+            .dim()
+        );
+    }
+    ProjectIndex::load(&path)
+}
+
+/// Execute the `aps index list` command
+pub fn cmd_index_list(args: IndexListArgs) -> Result<()> {
+    let index = load_project_index()?;
+
+    if args.porcelain {
+        for (project, indexed) in &index.projects {
+            for entry in &indexed.entries {
+                porcelain_line(
+                    &project.to_string_lossy(),
+                    &entry.id,
+                    &entry.sources.join(","),
+                );
+            }
+        }
+        return Ok(());
+    }
+
+    if index.projects.is_empty() {
+        say!("No projects indexed yet.");
+        return Ok(());
+    }
+
+    for (project, indexed) in &index.projects {
+        let missing = if indexed.manifest.exists() {
+            String::new()
+        } else {
+            format!(" {}", style("(manifest missing)").yellow())
+        };
+        say!(
+            "{}{}  {}",
+            style(project.display()).bold(),
+            missing,
+            style(format!(
+                "{} entries, synced {}",
+                indexed.entries.len(),
+                indexed.synced_at
+            ))
+            .dim()
+        );
+    }
+
+    let shared = index.shared_sources();
+    if !shared.is_empty() {
+        say!("\n{}", style("Shared across projects:").bold());
+        for (source, projects) in shared {
+            say!(
+                "  {}  {}",
+                style(source).cyan(),
+                style(format!("{} projects", projects.len())).dim()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Execute the `aps index search` command
+pub fn cmd_index_search(args: IndexSearchArgs) -> Result<()> {
+    let pattern = SourcePattern::parse(&args.pattern)?;
+    let index = load_project_index()?;
+    let found = index.search(&pattern, &args.pattern);
+
+    if args.porcelain {
+        for (project, entry) in &found {
+            porcelain_line(
+                &project.to_string_lossy(),
+                &entry.id,
+                &entry.sources.join(","),
+            );
+        }
+        return Ok(());
+    }
+
+    if found.is_empty() {
+        say!(
+            "No indexed projects use {} ({} project(s) indexed).",
+            style(&args.pattern).cyan(),
+            index.projects.len()
+        );
+        return Ok(());
+    }
+
+    let mut current: Option<&Path> = None;
+    for (project, entry) in &found {
+        if current != Some(*project) {
+            if current.is_some() {
+                say!();
+            }
+            say!("{}", style(project.display()).bold());
+            current = Some(project);
+        }
+        say!(
+            "  {}  {}",
+            style(&entry.id).cyan(),
+            style(entry.sources.join(", ")).dim()
+        );
+    }
+    let project_count = found.iter().map(|(p, _)| p).collect::<HashSet<_>>().len();
+    say!(
+        "\n{}",
+        style(format!(
+            "{} entr{} in {} of {} project(s) use {}",
+            found.len(),
+            if found.len() == 1 { "y" } else { "ies" },
+            project_count,
+            index.projects.len(),
+            args.pattern
+        ))
+        .dim()
+    );
+    Ok(())
+}
+
+/// An entry's destination as shown by `aps list`
+fn format_entry_dest(entry: &Entry) -> String {
+    display_dest(&entry.destination().to_string_lossy())
+}
+
 /// Format the AssetKind as a human-readable label
 fn format_kind_label(kind: &AssetKind) -> String {
     match kind {
@@ -1413,6 +5420,7 @@ fn format_kind_label(kind: &AssetKind) -> String {
         AssetKind::CursorRules => "cursor_rules".to_string(),
         AssetKind::CursorHooks => "cursor_hooks".to_string(),
         AssetKind::CursorSkillsRoot => "cursor_skills_root".to_string(),
+        AssetKind::Persona => "persona".to_string(),
     }
 }
 
@@ -1452,6 +5460,9 @@ fn format_source_short(source: &Source) -> String {
                 format!("fs: {}{}", root, sym_tag)
             }
         }
+        Source::Http { url, .. } => format!("http: {}", url),
+        Source::Aps { project, entry, .. } => format!("aps: {}#{}", project, entry),
+        Source::Generated { generator } => format!("generated: {}", generator.as_str()),
     }
 }
 
@@ -1503,7 +5514,7 @@ fn print_skill_tree(path: &Path, indent: &str) {
             let name = name.to_string_lossy();
 
             if item.path().is_dir() {
-                println!(
+                say!(
                     "{}{}{}{}",
                     indent,
                     dim.apply_to(connector),
@@ -1529,7 +5540,7 @@ fn print_skill_tree(path: &Path, indent: &str) {
 
                 print_single_skill_contents(&sub_entries, &sub_indent);
             } else {
-                println!(
+                say!(
                     "{}{}{}",
                     indent,
                     dim.apply_to(connector),
@@ -1569,7 +5580,7 @@ fn print_single_skill_contents(items: &[std::fs::DirEntry], indent: &str) {
                 .map(|rd| rd.filter_map(|e| e.ok()).count())
                 .unwrap_or(0);
 
-            println!(
+            say!(
                 "{}{}{}{}  {}",
                 indent,
                 dim.apply_to(connector),
@@ -1584,7 +5595,7 @@ fn print_single_skill_contents(items: &[std::fs::DirEntry], indent: &str) {
             } else {
                 &dim
             };
-            println!(
+            say!(
                 "{}{}{}",
                 indent,
                 dim.apply_to(connector),
@@ -1621,7 +5632,7 @@ fn print_flat_tree(path: &Path, indent: &str) {
             let child_count = std::fs::read_dir(item.path())
                 .map(|rd| rd.filter_map(|e| e.ok()).count())
                 .unwrap_or(0);
-            println!(
+            say!(
                 "{}{}{}{}  {}",
                 indent,
                 dim.apply_to(connector),
@@ -1630,7 +5641,7 @@ fn print_flat_tree(path: &Path, indent: &str) {
                 dim.apply_to(format!("({} items)", child_count)),
             );
         } else {
-            println!(
+            say!(
                 "{}{}{}",
                 indent,
                 dim.apply_to(connector),
@@ -1646,13 +5657,13 @@ pub fn cmd_catalog_generate(args: CatalogGenerateArgs) -> Result<()> {
     let (manifest, manifest_path) = discover_manifest(args.manifest.as_deref())?;
     let base_dir = manifest_dir(&manifest_path);
 
-    println!("Using manifest: {:?}", manifest_path);
+    say!("Using manifest: {:?}", manifest_path);
 
     // Validate manifest
     validate_manifest(&manifest)?;
 
     // Generate catalog
-    let catalog = Catalog::generate_from_manifest(&manifest, &base_dir)?;
+    let catalog = Catalog::generate_from_manifest(&manifest, &base_dir, args.with_health)?;
 
     // Determine output path
     let output_path = args
@@ -1662,7 +5673,7 @@ pub fn cmd_catalog_generate(args: CatalogGenerateArgs) -> Result<()> {
     // Save catalog
     catalog.save(&output_path)?;
 
-    println!(
+    say!(
         "Generated catalog with {} entries at {:?}",
         catalog.entries.len(),
         output_path
@@ -1676,7 +5687,31 @@ pub fn cmd_catalog_generate(args: CatalogGenerateArgs) -> Result<()> {
         .count();
 
     if with_desc > 0 {
-        println!("  {} entries have descriptions", with_desc);
+        say!("  {} entries have descriptions", with_desc);
+    }
+
+    if args.with_health {
+        let unhealthy: Vec<_> = catalog
+            .entries
+            .iter()
+            .filter_map(|e| e.health.as_ref().map(|h| (e, h)))
+            .filter(|(_, h)| h.score < HEALTH_WARN_SCORE)
+            .collect();
+        if unhealthy.is_empty() {
+            say!(
+                "  All scored entries have a health score of at least {}",
+                HEALTH_WARN_SCORE
+            );
+        } else {
+            say!(
+                "  {} entries score below {}:",
+                unhealthy.len(),
+                HEALTH_WARN_SCORE
+            );
+            for (entry, health) in unhealthy {
+                say!("    {} {}", style(health.score).yellow(), entry.id);
+            }
+        }
     }
 
     Ok(())
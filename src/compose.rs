@@ -4,6 +4,8 @@
 //! a single composite AGENTS.md file.
 
 use crate::error::{ApsError, Result};
+use crate::manifest::ComposeLimits;
+use std::collections::BTreeMap;
 use std::path::Path;
 use tracing::{debug, info};
 
@@ -98,6 +100,83 @@ pub fn compose_markdown(sources: &[ComposedSource], options: &ComposeOptions) ->
     Ok(result)
 }
 
+/// Problems with a composed file that break `limits`. `partials` pairs each
+/// source's display name with its content, to find section titles that more
+/// than one partial uses.
+pub fn check_composition(
+    partials: &[(String, &str)],
+    composed: &str,
+    limits: &ComposeLimits,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Some(max) = limits.max_bytes.filter(|max| composed.len() > *max) {
+        problems.push(format!(
+            "composed file is {} bytes, over the limit of {} (compose_limits.max_bytes)",
+            composed.len(),
+            max
+        ));
+    }
+    let lines = composed.lines().count();
+    if let Some(max) = limits.max_lines.filter(|max| lines > *max) {
+        problems.push(format!(
+            "composed file has {} lines, over the limit of {} (compose_limits.max_lines)",
+            lines, max
+        ));
+    }
+    if let Some(max) = limits.max_heading_depth {
+        for (name, content) in partials {
+            let deepest = headings(content).map(|(level, _)| level).max();
+            if let Some(deepest) = deepest.filter(|d| *d > max) {
+                problems.push(format!(
+                    "{} has level-{} headings, deeper than the limit of {} (compose_limits.max_heading_depth)",
+                    name, deepest, max
+                ));
+            }
+        }
+    }
+    if limits.duplicate_sections {
+        let mut seen: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+        for (name, content) in partials {
+            for (_, title) in headings(content) {
+                let owners = seen.entry(title.to_lowercase()).or_default();
+                if !owners.contains(&name.as_str()) {
+                    owners.push(name);
+                }
+            }
+        }
+        for (title, owners) in seen.iter().filter(|(_, owners)| owners.len() > 1) {
+            problems.push(format!(
+                "section '{}' appears in {}",
+                title,
+                owners.join(" and ")
+            ));
+        }
+    }
+    problems
+}
+
+/// Markdown headings as (level, title), skipping fenced code blocks
+fn headings(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut in_fence = false;
+    content.lines().filter_map(move |line| {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            return None;
+        }
+        if in_fence {
+            return None;
+        }
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        let title = trimmed[level..]
+            .strip_prefix(' ')?
+            .trim()
+            .trim_end_matches('#')
+            .trim();
+        ((1..=6).contains(&level) && !title.is_empty()).then_some((level, title))
+    })
+}
+
 /// Write the composed markdown to a destination file
 pub fn write_composed_file(content: &str, dest: &Path) -> Result<()> {
     // Ensure parent directory exists
@@ -122,6 +201,44 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_check_composition_limits() {
+        let partials = vec![
+            (
+                "python.md".to_string(),
+                "# Testing\n\n```sh\n# not a heading\n```\n#### Deep\n",
+            ),
+            ("docker.md".to_string(), "# Docker\n\n## testing\n"),
+        ];
+        let composed = "x\n".repeat(20);
+
+        assert!(check_composition(
+            &partials,
+            &composed,
+            &ComposeLimits {
+                duplicate_sections: false,
+                ..ComposeLimits::default()
+            }
+        )
+        .is_empty());
+
+        let limits = ComposeLimits {
+            max_bytes: Some(10),
+            max_lines: Some(5),
+            max_heading_depth: Some(3),
+            duplicate_sections: true,
+        };
+        assert_eq!(
+            check_composition(&partials, &composed, &limits),
+            vec![
+                "composed file is 40 bytes, over the limit of 10 (compose_limits.max_bytes)",
+                "composed file has 20 lines, over the limit of 5 (compose_limits.max_lines)",
+                "python.md has level-4 headings, deeper than the limit of 3 (compose_limits.max_heading_depth)",
+                "section 'testing' appears in python.md and docker.md",
+            ]
+        );
+    }
+
     #[test]
     fn test_compose_markdown_single_source() {
         let sources = vec![ComposedSource {
@@ -0,0 +1,275 @@
+//! Reverse lookup of entries by source (`aps who-uses`).
+//!
+//! Every manifest in the workspace (or under the paths given) is read, and
+//! entries whose sources match a repository or path pattern are listed, so
+//! the people consuming an upstream can be found before it changes.
+
+use crate::error::{ApsError, Result};
+use crate::manifest::{load_manifest, manifest_dir, Manifest, Source, DEFAULT_MANIFEST_NAME};
+use globset::{Glob, GlobMatcher};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+use walkdir::WalkDir;
+
+/// What `aps who-uses` looks for
+#[derive(Debug)]
+pub enum SourcePattern {
+    /// An existing local path: matches filesystem sources at, inside, or
+    /// containing it
+    Path(PathBuf),
+    /// A glob (`*`, `?`, `[...]`) over normalized source labels
+    Glob(GlobMatcher),
+    /// Text contained in a normalized source label
+    Text(String),
+}
+
+impl SourcePattern {
+    pub fn parse(pattern: &str) -> Result<Self> {
+        if Path::new(pattern).exists() {
+            return Ok(SourcePattern::Path(canonical(Path::new(pattern))));
+        }
+        if pattern.contains(['*', '?', '[']) {
+            let glob = Glob::new(&normalize_label(pattern)).map_err(|e| {
+                ApsError::InvalidSourcePattern {
+                    pattern: pattern.to_string(),
+                    message: e.kind().to_string(),
+                }
+            })?;
+            return Ok(SourcePattern::Glob(glob.compile_matcher()));
+        }
+        Ok(SourcePattern::Text(normalize_label(pattern)))
+    }
+
+    /// Whether a label recorded by [`source_labels`] matches
+    pub fn matches_label(&self, label: &str) -> bool {
+        let candidate = if Path::new(label).is_absolute() {
+            Candidate::Path(PathBuf::from(label))
+        } else {
+            Candidate::Label(label.to_string())
+        };
+        self.matches(&candidate)
+    }
+
+    fn matches(&self, candidate: &Candidate) -> bool {
+        match (self, candidate) {
+            (SourcePattern::Path(wanted), Candidate::Path(path)) => {
+                path.starts_with(wanted) || wanted.starts_with(path)
+            }
+            (SourcePattern::Path(_), Candidate::Label(_)) => false,
+            (SourcePattern::Glob(glob), candidate) => glob.is_match(candidate.label()),
+            (SourcePattern::Text(text), candidate) => candidate.label().contains(text.as_str()),
+        }
+    }
+}
+
+/// One way of naming a source for matching
+enum Candidate {
+    /// A local directory or file, resolved against the manifest
+    Path(PathBuf),
+    /// A normalized repository or URL label
+    Label(String),
+}
+
+impl Candidate {
+    fn label(&self) -> String {
+        match self {
+            Candidate::Path(path) => path.to_string_lossy().replace('\\', "/"),
+            Candidate::Label(label) => label.clone(),
+        }
+    }
+}
+
+/// An entry whose source matched
+#[derive(Debug)]
+pub struct Consumer {
+    pub manifest: PathBuf,
+    pub entry_id: String,
+    /// The matching source, as the lockfile would record it
+    pub source: String,
+}
+
+/// Manifests to search: each of `paths` that is a manifest file, and every
+/// `aps.yaml` under those that are directories
+pub fn find_manifests(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut manifests = Vec::new();
+    for path in paths {
+        if path.is_file() {
+            manifests.push(path.clone());
+            continue;
+        }
+        manifests.extend(
+            WalkDir::new(path)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_entry(|e| e.file_name() != ".git" && e.file_name() != "node_modules")
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file() && e.file_name() == DEFAULT_MANIFEST_NAME)
+                .map(|e| e.into_path()),
+        );
+    }
+    manifests.sort();
+    manifests.dedup();
+    manifests
+}
+
+/// Entries of the manifests at `manifest_paths` with a source matching
+/// `pattern`. Manifests that cannot be read are skipped.
+pub fn who_uses(pattern: &SourcePattern, manifest_paths: &[PathBuf]) -> Vec<Consumer> {
+    let mut consumers = Vec::new();
+    for manifest_path in manifest_paths {
+        let manifest = match load_manifest(manifest_path) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                debug!("Skipping unreadable manifest {:?}: {}", manifest_path, e);
+                continue;
+            }
+        };
+        consumers.extend(consumers_in(pattern, manifest_path, &manifest));
+    }
+    consumers
+}
+
+fn consumers_in(
+    pattern: &SourcePattern,
+    manifest_path: &Path,
+    manifest: &Manifest,
+) -> Vec<Consumer> {
+    let base_dir = manifest_dir(manifest_path);
+    let mut consumers = Vec::new();
+    for entry in &manifest.entries {
+        let sources = entry.source.iter().chain(&entry.sources);
+        for source in sources {
+            if candidates(source, &base_dir)
+                .iter()
+                .any(|candidate| pattern.matches(candidate))
+            {
+                consumers.push(Consumer {
+                    manifest: manifest_path.to_path_buf(),
+                    entry_id: entry.id.clone(),
+                    source: source.display_path(),
+                });
+            }
+        }
+    }
+    consumers
+}
+
+/// The names `source` can be matched by, as text to store outside the
+/// manifest (the project index)
+pub fn source_labels(source: &Source, base_dir: &Path) -> Vec<String> {
+    candidates(source, base_dir)
+        .iter()
+        .map(Candidate::label)
+        .collect()
+}
+
+/// The names `source` can be matched by
+fn candidates(source: &Source, base_dir: &Path) -> Vec<Candidate> {
+    let local = |root: &str, path: Option<&str>| {
+        let root = shellexpand::full(root)
+            .map(|s| s.into_owned())
+            .unwrap_or_else(|_| root.to_string());
+        let dir = base_dir.join(root);
+        canonical(&path.map_or(dir.clone(), |p| dir.join(p)))
+    };
+    match source {
+        Source::Git { repo, path, .. } => {
+            let repo = normalize_label(repo);
+            let mut labels = vec![Candidate::Label(repo.clone())];
+            if let Some(path) = path {
+                labels.push(Candidate::Label(format!(
+                    "{}/{}",
+                    repo,
+                    path.trim_matches('/')
+                )));
+            }
+            labels
+        }
+        Source::Filesystem { root, path, .. } => {
+            vec![Candidate::Path(local(root, path.as_deref()))]
+        }
+        Source::Http { url, .. } => vec![Candidate::Label(normalize_label(url))],
+        Source::Aps { project, .. } if looks_remote(project) => {
+            vec![Candidate::Label(normalize_label(project))]
+        }
+        Source::Aps { project, .. } => vec![Candidate::Path(local(project, None))],
+        Source::Generated { .. } => Vec::new(),
+    }
+}
+
+fn looks_remote(project: &str) -> bool {
+    project.contains("://") || project.starts_with("git@")
+}
+
+/// A repository or URL reduced to `host/path`, so `https://host/org/repo.git`,
+/// `git@host:org/repo`, and `host/org/repo` compare equal
+fn normalize_label(label: &str) -> String {
+    let label = label.split_once("://").map_or(label, |(_, rest)| rest);
+    let label = match label.split_once('@') {
+        Some((user, rest)) if !user.contains('/') => rest,
+        _ => label,
+    };
+    // scp-style `host:org/repo`
+    let label = match label.split_once(':') {
+        Some((host, rest)) if !host.contains('/') && !rest.starts_with("//") => {
+            format!("{}/{}", host, rest)
+        }
+        _ => label.to_string(),
+    };
+    let label = label.trim_end_matches('/');
+    label.strip_suffix(".git").unwrap_or(label).to_string()
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_label() {
+        for repo in [
+            "https://github.com/org/skills.git",
+            "git@github.com:org/skills.git",
+            "ssh://git@github.com/org/skills",
+            "github.com/org/skills/",
+        ] {
+            assert_eq!(normalize_label(repo), "github.com/org/skills");
+        }
+    }
+
+    #[test]
+    fn test_pattern_matches_git_sources() {
+        let manifest: Manifest = serde_yaml::from_str(
+            r#"
+entries:
+  - id: review
+    kind: agent_skill
+    source:
+      type: git
+      repo: git@github.com:org/skills.git
+      path: skills/review
+  - id: other
+    kind: agent_skill
+    source:
+      type: git
+      repo: https://github.com/org/other.git
+"#,
+        )
+        .unwrap();
+        let ids = |pattern: &str| {
+            let pattern = SourcePattern::parse(pattern).unwrap();
+            consumers_in(&pattern, Path::new("aps.yaml"), &manifest)
+                .into_iter()
+                .map(|c| c.entry_id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids("https://github.com/org/skills"), vec!["review"]);
+        assert_eq!(ids("github.com/org/skills/skills/review"), vec!["review"]);
+        assert_eq!(ids("github.com/org/*"), vec!["review", "other"]);
+        assert!(ids("gitlab.com/org").is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+//! Advisory locks around writes to install destinations.
+//!
+//! `aps sync` holds an exclusive lock on each destination while it installs
+//! it, and shared locks on every directory above it, so two processes (from
+//! the same or different manifests) writing the same or nested destinations
+//! take turns instead of interleaving partial writes, while unrelated
+//! destinations install in parallel. Lock files live in `APS_LOCK_DIR`, or
+//! `aps-locks` under the system temp directory, one per path, named by a
+//! hash of it. The operating system releases the locks when a process exits,
+//! so leftover files are harmless.
+
+use crate::color::style;
+use crate::output::say;
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Environment variable naming the lock directory
+const LOCK_DIR_ENV: &str = "APS_LOCK_DIR";
+
+/// Directory holding destination lock files
+pub fn lock_dir() -> PathBuf {
+    match std::env::var_os(LOCK_DIR_ENV) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => std::env::temp_dir().join("aps-locks"),
+    }
+}
+
+/// Locks held on a destination and its ancestors, released on drop
+#[derive(Debug)]
+pub struct DestLock {
+    _files: Vec<File>,
+}
+
+/// Lock `dest` for writing, waiting (with a notice) while another process
+/// holds it or a destination nested with it. Returns `None` where locks are
+/// unavailable, in which case the install goes ahead unlocked.
+pub fn lock_destination(dest: &Path) -> Option<DestLock> {
+    let dir = lock_dir();
+    let attempt = acquire(&dir, dest, false).and_then(|lock| match lock {
+        Some(lock) => Ok(lock),
+        None => {
+            say!(
+                "{} Waiting for another aps process writing {}",
+                style("…").dim(),
+                dest.display()
+            );
+            acquire(&dir, dest, true).map(|lock| lock.expect("blocking lock"))
+        }
+    });
+    match attempt {
+        Ok(lock) => Some(lock),
+        Err(e) => {
+            debug!("Installing {:?} without a destination lock: {}", dest, e);
+            None
+        }
+    }
+}
+
+/// Take shared locks on the ancestors of `dest`, outermost first, then an
+/// exclusive lock on `dest`. Without `wait`, returns `None` as soon as one
+/// is held elsewhere.
+fn acquire(dir: &Path, dest: &Path, wait: bool) -> std::io::Result<Option<DestLock>> {
+    std::fs::create_dir_all(dir)?;
+    let dest = lock_key(dest);
+    let mut ancestors: Vec<&Path> = dest.ancestors().skip(1).collect();
+    ancestors.reverse();
+
+    let mut files = Vec::new();
+    for (path, exclusive) in ancestors
+        .into_iter()
+        .map(|p| (p, false))
+        .chain([(dest.as_path(), true)])
+    {
+        let file = open_lock_file(dir, path)?;
+        let attempt = match (exclusive, wait) {
+            (true, true) => file.lock().map_err(TryLockError::Error),
+            (false, true) => file.lock_shared().map_err(TryLockError::Error),
+            (true, false) => file.try_lock(),
+            (false, false) => file.try_lock_shared(),
+        };
+        match attempt {
+            Ok(()) => files.push(file),
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(e)) => return Err(e),
+        }
+    }
+    Ok(Some(DestLock { _files: files }))
+}
+
+/// The absolute path a destination is locked under. The directory it is in
+/// is resolved through symlinks, but not the destination itself, which may
+/// be a symlink to its source.
+fn lock_key(dest: &Path) -> PathBuf {
+    let dest = std::path::absolute(dest).unwrap_or_else(|_| dest.to_path_buf());
+    let (Some(parent), Some(name)) = (dest.parent(), dest.file_name()) else {
+        return dest;
+    };
+    // Resolve the part of the parent that exists
+    let mut existing = parent;
+    let mut missing = Vec::new();
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(up), Some(part)) => {
+                missing.push(part);
+                existing = up;
+            }
+            _ => break,
+        }
+    }
+    let mut key = existing
+        .canonicalize()
+        .unwrap_or_else(|_| existing.to_path_buf());
+    key.extend(missing.into_iter().rev());
+    key.join(name)
+}
+
+/// Open (creating if needed) the lock file for `path`. A new file records
+/// the path it locks, for `aps doctor`.
+fn open_lock_file(dir: &Path, path: &Path) -> std::io::Result<File> {
+    let digest = hex::encode(Sha256::digest(path.to_string_lossy().as_bytes()));
+    let file_path = dir.join(format!("{}.lock", &digest[..32]));
+    match OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&file_path)
+    {
+        Ok(mut file) => {
+            writeln!(file, "{}", path.display())?;
+            Ok(file)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+            OpenOptions::new().write(true).open(&file_path)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// What `aps doctor` reports about destination locks
+pub struct LockReport {
+    pub dir: PathBuf,
+    /// Lock files in the directory
+    pub files: usize,
+    /// Destinations another process is writing right now
+    pub held: Vec<String>,
+    /// Why locks cannot be taken here, if they cannot
+    pub problem: Option<String>,
+}
+
+/// Check that destination locks work, and list the ones currently held
+pub fn diagnose() -> LockReport {
+    let dir = lock_dir();
+    let mut report = LockReport {
+        dir: dir.clone(),
+        files: 0,
+        held: Vec::new(),
+        problem: None,
+    };
+    if let Err(e) = std::fs::create_dir_all(&dir)
+        .and_then(|_| open_lock_file(&dir, Path::new("/")))
+        .and_then(|probe| probe.try_lock_shared().map_err(std::io::Error::from))
+    {
+        report.problem = Some(e.to_string());
+        return report;
+    }
+
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return report;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "lock") {
+            continue;
+        }
+        report.files += 1;
+        let Ok(file) = File::open(&path) else {
+            continue;
+        };
+        // Only a destination being written is locked exclusively
+        if let Err(TryLockError::WouldBlock) = file.try_lock_shared() {
+            let locked = std::fs::read_to_string(&path).unwrap_or_default();
+            report.held.push(locked.trim().to_string());
+        }
+    }
+    report.held.sort();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_nested_destinations_serialize() {
+        let locks = tempdir().unwrap();
+        let project = tempdir().unwrap();
+        let skills = project.path().join(".claude/skills");
+
+        let held = acquire(locks.path(), &skills, false).unwrap().unwrap();
+        // The same, an enclosing, or a nested destination must wait
+        assert!(acquire(locks.path(), &skills, false).unwrap().is_none());
+        assert!(
+            acquire(locks.path(), &project.path().join(".claude"), false)
+                .unwrap()
+                .is_none()
+        );
+        assert!(acquire(locks.path(), &skills.join("review"), false)
+            .unwrap()
+            .is_none());
+        // Unrelated destinations do not
+        assert!(
+            acquire(locks.path(), &project.path().join("AGENTS.md"), false)
+                .unwrap()
+                .is_some()
+        );
+
+        drop(held);
+        assert!(acquire(locks.path(), &skills.join("review"), false)
+            .unwrap()
+            .is_some());
+    }
+}
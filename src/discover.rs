@@ -2,9 +2,12 @@
 //!
 //! Discovers skills by recursively searching for directories containing
 //! a SKILL.md file. Supports both git repositories (via clone) and local
-//! filesystem paths.
+//! filesystem paths. Loose discovery (`aps add --loose`) also offers
+//! directories that look like skills without a SKILL.md, labeled as
+//! heuristic matches.
 
 use crate::error::{ApsError, Result};
+use crate::manifest::AssetKind;
 use crate::sources::clone_and_resolve;
 use std::path::{Path, PathBuf};
 use tracing::{debug, info};
@@ -19,6 +22,8 @@ pub struct DiscoveredSkill {
     pub repo_path: String,
     /// Short description extracted from SKILL.md (first paragraph)
     pub description: Option<String>,
+    /// Why a directory without SKILL.md was offered, for loose discovery
+    pub heuristic: Option<&'static str>,
 }
 
 /// Discover skills in a git repository by cloning it and searching for SKILL.md files.
@@ -26,10 +31,12 @@ pub struct DiscoveredSkill {
 /// - `repo_url`: The git repository URL
 /// - `git_ref`: The git ref to clone (branch/tag/commit, or "auto")
 /// - `search_path`: Optional path within the repo to search (empty string = root)
+/// - `loose`: Also offer directories that only look like skills
 pub fn discover_skills_in_repo(
     repo_url: &str,
     git_ref: &str,
     search_path: &str,
+    loose: bool,
 ) -> Result<Vec<DiscoveredSkill>> {
     info!(
         "Discovering skills in {} (ref: {}, path: {})",
@@ -43,7 +50,7 @@ pub fn discover_skills_in_repo(
     );
 
     // Clone the repository
-    let resolved = clone_and_resolve(repo_url, git_ref, true)?;
+    let resolved = clone_and_resolve(repo_url, git_ref, true, Some(search_path))?;
 
     // Determine the search root
     let search_root = if search_path.is_empty() {
@@ -53,11 +60,22 @@ pub fn discover_skills_in_repo(
     };
 
     if !search_root.exists() {
-        return Err(ApsError::SourcePathNotFound { path: search_root });
+        return Err(ApsError::source_path_not_found(
+            search_root,
+            Some(&resolved.repo_path),
+            Some(&resolved.resolved_ref),
+        ));
     }
 
     // Find all SKILL.md files
-    let skills = find_skills_in_directory(&search_root, &resolved.repo_path)?;
+    let mut skills = find_skills_in_directory(&search_root, &resolved.repo_path)?;
+    if loose {
+        skills.extend(find_loose_candidates(
+            &search_root,
+            &resolved.repo_path,
+            &skills,
+        ));
+    }
 
     info!("Discovered {} skills", skills.len());
     Ok(skills)
@@ -66,7 +84,8 @@ pub fn discover_skills_in_repo(
 /// Discover skills in a local filesystem directory.
 ///
 /// - `local_path`: Path to search (supports shell variables like $HOME, ~)
-pub fn discover_skills_in_local_dir(local_path: &str) -> Result<Vec<DiscoveredSkill>> {
+/// - `loose`: Also offer directories that only look like skills
+pub fn discover_skills_in_local_dir(local_path: &str, loose: bool) -> Result<Vec<DiscoveredSkill>> {
     let expanded = shellexpand::full(local_path)
         .map(|s| s.into_owned())
         .unwrap_or_else(|_| local_path.to_string());
@@ -85,19 +104,81 @@ pub fn discover_skills_in_local_dir(local_path: &str) -> Result<Vec<DiscoveredSk
     info!("Discovering skills in local directory: {:?}", path);
 
     if !path.exists() {
-        return Err(ApsError::SourcePathNotFound { path });
+        return Err(ApsError::source_path_not_found(path, None, None));
     }
 
     if !path.is_dir() {
-        return Err(ApsError::SourcePathNotFound { path });
+        return Err(ApsError::source_path_not_found(path, None, None));
     }
 
-    let skills = find_skills_in_directory(&path, &path)?;
+    let mut skills = find_skills_in_directory(&path, &path)?;
+    if loose {
+        skills.extend(find_loose_candidates(&path, &path, &skills));
+    }
 
     info!("Discovered {} skills", skills.len());
     Ok(skills)
 }
 
+/// Guess the asset kind of a local file or directory, with the reason shown
+/// to the user. Returns `None` when nothing identifies it.
+///
+/// A SKILL.md means an agent skill, a `hooks.json` means Cursor hooks, `.mdc`
+/// files mean Cursor rules, and a lone AGENTS.md means an agents_md entry.
+pub fn detect_asset_kind(path: &Path) -> Option<(AssetKind, &'static str)> {
+    if path.is_file() {
+        let name = path.file_name()?.to_str()?;
+        return detect_asset_kind_from_name(name);
+    }
+    if find_skill_md(path).is_some() {
+        return Some((AssetKind::AgentSkill, "found SKILL.md"));
+    }
+    if path.join("hooks.json").is_file() {
+        return Some((AssetKind::CursorHooks, "found hooks.json"));
+    }
+
+    let files: Vec<PathBuf> = WalkDir::new(path)
+        .max_depth(2)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect();
+    if files.iter().any(|f| {
+        f.extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("mdc"))
+    }) {
+        return Some((AssetKind::CursorRules, "found .mdc files"));
+    }
+    match files.as_slice() {
+        [only]
+            if only
+                .file_name()
+                .is_some_and(|n| n.eq_ignore_ascii_case("AGENTS.md")) =>
+        {
+            Some((AssetKind::AgentsMd, "found a single AGENTS.md file"))
+        }
+        _ => None,
+    }
+}
+
+/// Guess the asset kind from a file name alone, for sources that have not
+/// been fetched (such as a path in a GitHub URL)
+pub fn detect_asset_kind_from_name(name: &str) -> Option<(AssetKind, &'static str)> {
+    let name = name.rsplit('/').next().unwrap_or(name);
+    if name.eq_ignore_ascii_case("AGENTS.md") {
+        Some((AssetKind::AgentsMd, "the source is an AGENTS.md file"))
+    } else if Path::new(name)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("mdc"))
+    {
+        Some((AssetKind::CursorRules, "the source is an .mdc file"))
+    } else {
+        None
+    }
+}
+
 /// Walk a directory tree and find all directories containing a SKILL.md file.
 fn find_skills_in_directory(search_root: &Path, repo_root: &Path) -> Result<Vec<DiscoveredSkill>> {
     let mut skills = Vec::new();
@@ -162,6 +243,7 @@ fn find_skills_in_directory(search_root: &Path, repo_root: &Path) -> Result<Vec<
                     name: skill_name,
                     repo_path,
                     description,
+                    heuristic: None,
                 });
             }
         }
@@ -172,6 +254,137 @@ fn find_skills_in_directory(search_root: &Path, repo_root: &Path) -> Result<Vec<
     Ok(skills)
 }
 
+/// Directories under `search_root` that look like skills without a SKILL.md:
+/// a single top-level markdown file with frontmatter, or a README.md next to
+/// a `scripts/` directory. Directories inside or above a found skill, and
+/// inside another candidate, are left out.
+fn find_loose_candidates(
+    search_root: &Path,
+    repo_root: &Path,
+    skills: &[DiscoveredSkill],
+) -> Vec<DiscoveredSkill> {
+    let relative = |dir: &Path| {
+        dir.strip_prefix(repo_root)
+            .map(|p| {
+                p.components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/")
+            })
+            .unwrap_or_default()
+    };
+    let nested = |path: &str, other: &str| {
+        path == other
+            || path.starts_with(&format!("{}/", other))
+            || other.starts_with(&format!("{}/", path))
+    };
+
+    let mut candidates: Vec<DiscoveredSkill> = Vec::new();
+    for entry in WalkDir::new(search_root)
+        .min_depth(1)
+        .follow_links(false)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+    {
+        let dir = entry.path();
+        let repo_path = relative(dir);
+        if repo_path.is_empty()
+            || skills.iter().any(|s| nested(&repo_path, &s.repo_path))
+            || candidates
+                .iter()
+                .any(|c| repo_path.starts_with(&format!("{}/", c.repo_path)))
+        {
+            continue;
+        }
+        let Some((doc, reason)) = loose_skill_doc(dir) else {
+            continue;
+        };
+        debug!("Found heuristic skill candidate at {}", repo_path);
+        candidates.push(DiscoveredSkill {
+            name: entry.file_name().to_string_lossy().to_string(),
+            repo_path,
+            description: extract_skill_description(&doc),
+            heuristic: Some(reason),
+        });
+    }
+    candidates
+}
+
+/// The markdown file that makes `dir` look like a skill, and why
+fn loose_skill_doc(dir: &Path) -> Option<(PathBuf, &'static str)> {
+    let markdown: Vec<PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+        })
+        .collect();
+
+    let readme = markdown.iter().find(|p| {
+        p.file_name()
+            .is_some_and(|n| n.eq_ignore_ascii_case("README.md"))
+    });
+    if let Some(readme) = readme.filter(|_| dir.join("scripts").is_dir()) {
+        return Some((readme.clone(), "README.md with scripts/"));
+    }
+    match markdown.as_slice() {
+        [only]
+            if std::fs::read_to_string(only)
+                .is_ok_and(|content| extract_frontmatter(&content).is_some()) =>
+        {
+            Some((only.clone(), "single markdown file with frontmatter"))
+        }
+        _ => None,
+    }
+}
+
+/// Top-level items of a Cursor rules folder that `include` can select: each
+/// `.mdc` file, and each subfolder holding rules (named with a trailing `/`).
+/// `repo_path` is the name to list under `include`.
+pub fn find_rule_files(dir: &Path) -> Vec<DiscoveredSkill> {
+    let is_rule = |path: &Path| path.extension().is_some_and(|ext| ext == "mdc");
+    let mut rules: Vec<DiscoveredSkill> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| {
+            let path = e.path();
+            let name = e.file_name().to_string_lossy().to_string();
+            if path.is_file() && is_rule(&path) {
+                Some(DiscoveredSkill {
+                    description: extract_skill_description(&path),
+                    name: name.clone(),
+                    repo_path: name,
+                    heuristic: None,
+                })
+            } else if path.is_dir()
+                && WalkDir::new(&path)
+                    .into_iter()
+                    .filter_map(|e| e.ok())
+                    .any(|e| e.file_type().is_file() && is_rule(e.path()))
+            {
+                Some(DiscoveredSkill {
+                    name: format!("{}/", name),
+                    repo_path: name,
+                    description: None,
+                    heuristic: None,
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+    rules.sort_by(|a, b| a.name.cmp(&b.name));
+    rules
+}
+
 /// Extract a short description from a SKILL.md file.
 /// Tries YAML frontmatter `description` field first, then falls back to first paragraph.
 fn extract_skill_description(skill_md_path: &Path) -> Option<String> {
@@ -226,14 +439,36 @@ fn extract_skill_description(skill_md_path: &Path) -> Option<String> {
     }
 }
 
-/// Extract a field value from YAML frontmatter.
-fn extract_frontmatter_field(content: &str, field: &str) -> Option<String> {
-    if !content.starts_with("---") {
-        return None;
-    }
-    let rest = &content[3..];
+/// Find a directory's SKILL.md, matching the file name case-insensitively.
+pub fn find_skill_md(dir: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .find(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .eq_ignore_ascii_case("skill.md")
+                && e.path().is_file()
+        })
+        .map(|e| e.path())
+}
+
+/// Read the `name` field from a SKILL.md file's YAML frontmatter.
+pub fn read_skill_name(skill_md_path: &Path) -> Option<String> {
+    let content = std::fs::read_to_string(skill_md_path).ok()?;
+    extract_frontmatter_field(&content, "name")
+}
+
+/// The YAML frontmatter block of a markdown file, without its delimiters
+fn extract_frontmatter(content: &str) -> Option<&str> {
+    let rest = content.strip_prefix("---")?;
     let end_pos = rest.find("\n---")?;
-    let frontmatter = &rest[..end_pos];
+    Some(&rest[..end_pos])
+}
+
+/// Extract a field value from YAML frontmatter.
+pub(crate) fn extract_frontmatter_field(content: &str, field: &str) -> Option<String> {
+    let frontmatter = extract_frontmatter(content)?;
 
     for line in frontmatter.lines() {
         let trimmed = line.trim();
@@ -292,7 +527,7 @@ struct SkillSelectTheme {
 
 impl SkillSelectTheme {
     fn new() -> Self {
-        use console::{style, Style};
+        use crate::color::{style, Style};
         Self {
             inner: dialoguer::theme::ColorfulTheme {
                 active_item_style: Style::new().for_stderr().bold().white().bright(),
@@ -329,7 +564,7 @@ impl dialoguer::theme::Theme for SkillSelectTheme {
         checked: bool,
         active: bool,
     ) -> std::fmt::Result {
-        use console::{style, Style};
+        use crate::color::{style, Style};
 
         let arrow = if active {
             format!("{}", style(">").green().bold())
@@ -389,6 +624,33 @@ impl dialoguer::theme::Theme for SkillSelectTheme {
 /// Present a multi-select TUI for choosing which skills to add.
 /// Returns the indices of selected skills.
 pub fn prompt_skill_selection(skills: &[DiscoveredSkill], defaults: &[bool]) -> Result<Vec<usize>> {
+    prompt_selection(
+        "Toggle skills (space to toggle, enter to confirm)",
+        skills,
+        defaults,
+        defaults,
+    )
+}
+
+/// Show an interactive multi-select of the files in a Cursor rules folder,
+/// all checked to start with. Returns the indices of the kept rules.
+pub fn prompt_rule_selection(rules: &[DiscoveredSkill]) -> Result<Vec<usize>> {
+    prompt_selection(
+        "Toggle rules (space to toggle, enter to confirm)",
+        rules,
+        &vec![false; rules.len()],
+        &vec![true; rules.len()],
+    )
+}
+
+/// Multi-select over discovered items. `installed` colors items already in
+/// the manifest; `checked` says which start selected.
+fn prompt_selection(
+    prompt: &str,
+    skills: &[DiscoveredSkill],
+    installed: &[bool],
+    checked: &[bool],
+) -> Result<Vec<usize>> {
     use console::Term;
     use dialoguer::MultiSelect;
 
@@ -396,23 +658,27 @@ pub fn prompt_skill_selection(skills: &[DiscoveredSkill], defaults: &[bool]) ->
 
     let items: Vec<String> = skills
         .iter()
-        .zip(defaults.iter())
+        .zip(installed.iter())
         .map(|(s, &installed)| {
-            SkillSelectTheme::encode_item(
-                &s.name,
-                s.description.as_deref(),
-                installed,
-                max_name_len,
-            )
+            // Heuristic matches say so, since nothing marks them as skills
+            let description = match s.heuristic {
+                Some(reason) => Some(format!(
+                    "[heuristic: {}] {}",
+                    reason,
+                    s.description.as_deref().unwrap_or("")
+                )),
+                None => s.description.clone(),
+            };
+            SkillSelectTheme::encode_item(&s.name, description.as_deref(), installed, max_name_len)
         })
         .collect();
 
     let theme = SkillSelectTheme::new();
 
     let selections = MultiSelect::with_theme(&theme)
-        .with_prompt("Toggle skills (space to toggle, enter to confirm)")
+        .with_prompt(prompt)
         .items(&items)
-        .defaults(defaults)
+        .defaults(checked)
         .interact_on(&Term::stderr())
         .map_err(|e| {
             ApsError::io(
@@ -429,6 +695,41 @@ mod tests {
     use super::*;
     use tempfile::TempDir;
 
+    #[test]
+    fn test_detect_asset_kind() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let write = |path: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "# x\n").unwrap();
+        };
+        write("skill/SKILL.md");
+        write("rules/rust/style.mdc");
+        write("cursor/hooks.json");
+        write("guide/AGENTS.md");
+        write("docs/README.md");
+        write("docs/AGENTS.md");
+        write("lower/Skill.md");
+        write("loud/STYLE.MDC");
+        write("notes/agents.md");
+
+        let kind = |path: &str| detect_asset_kind(&root.join(path)).map(|(kind, _)| kind);
+        assert_eq!(kind("skill"), Some(AssetKind::AgentSkill));
+        assert_eq!(kind("rules"), Some(AssetKind::CursorRules));
+        assert_eq!(kind("rules/rust/style.mdc"), Some(AssetKind::CursorRules));
+        assert_eq!(kind("cursor"), Some(AssetKind::CursorHooks));
+        assert_eq!(kind("guide"), Some(AssetKind::AgentsMd));
+        assert_eq!(kind("guide/AGENTS.md"), Some(AssetKind::AgentsMd));
+        // An AGENTS.md among other files does not decide the kind
+        assert_eq!(kind("docs"), None);
+        // Well-known names match in any case
+        assert_eq!(kind("lower"), Some(AssetKind::AgentSkill));
+        assert_eq!(kind("loud"), Some(AssetKind::CursorRules));
+        assert_eq!(kind("loud/STYLE.MDC"), Some(AssetKind::CursorRules));
+        assert_eq!(kind("notes"), Some(AssetKind::AgentsMd));
+    }
+
     #[test]
     fn test_find_skills_in_directory() {
         let temp = TempDir::new().unwrap();
@@ -472,6 +773,82 @@ mod tests {
         assert_eq!(skills[1].repo_path, "skills/test-gen");
     }
 
+    #[test]
+    fn test_find_rule_files() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write(
+            "iterators.mdc",
+            "---\ndescription: Prefer iterators\n---\n# Iterators\n",
+        );
+        write("api/errors.mdc", "# Errors\n");
+        write("notes/README.md", "# Notes\n");
+        write("README.md", "# Rules\n");
+        write(".hidden.mdc", "# Hidden\n");
+
+        let rules = find_rule_files(root);
+        let found: Vec<(&str, &str)> = rules
+            .iter()
+            .map(|r| (r.name.as_str(), r.repo_path.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![("api/", "api"), ("iterators.mdc", "iterators.mdc")]
+        );
+        assert_eq!(rules[1].description.as_deref(), Some("Prefer iterators"));
+    }
+
+    #[test]
+    fn test_find_loose_candidates() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let write = |path: &str, content: &str| {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, content).unwrap();
+        };
+        write("skills/refactor/SKILL.md", "# Refactor\n");
+        write("skills/refactor/notes/extra.md", "---\nname: x\n---\n");
+        write(
+            "prompts/review/review.md",
+            "---\ndescription: Reviews diffs\n---\n# Review\n",
+        );
+        write("prompts/review/examples/one.md", "---\nname: x\n---\n");
+        write("tools/deploy/README.md", "# Deploy\n\nShips builds.\n");
+        write("tools/deploy/scripts/run.sh", "#!/bin/sh\n");
+        write("tools/build/readme.md", "# Build\n\nBuilds things.\n");
+        write("tools/build/scripts/make.sh", "#!/bin/sh\n");
+        write("docs/guide.md", "# Guide without frontmatter\n");
+        write("mixed/a.md", "---\nname: a\n---\n");
+        write("mixed/b.md", "---\nname: b\n---\n");
+
+        let skills = find_skills_in_directory(root, root).unwrap();
+        let candidates = find_loose_candidates(root, root, &skills);
+        let found: Vec<(&str, Option<&str>)> = candidates
+            .iter()
+            .map(|c| (c.repo_path.as_str(), c.heuristic))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "prompts/review",
+                    Some("single markdown file with frontmatter")
+                ),
+                ("tools/build", Some("README.md with scripts/")),
+                ("tools/deploy", Some("README.md with scripts/")),
+            ]
+        );
+        assert_eq!(candidates[0].description.as_deref(), Some("Reviews diffs"));
+        assert_eq!(candidates[1].description.as_deref(), Some("Builds things."));
+        assert_eq!(candidates[2].description.as_deref(), Some("Ships builds."));
+    }
+
     #[test]
     fn test_find_skills_with_search_path() {
         let temp = TempDir::new().unwrap();
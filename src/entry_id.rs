@@ -0,0 +1,171 @@
+//! Entry IDs and destination folders generated for discovered skills.
+//!
+//! Skills found deep inside a repository can have names that make awkward
+//! IDs: spaces, punctuation, or long dashed repo paths when names repeat.
+//! Generated IDs are normalized here so manifests written by `aps add` stay
+//! clean and portable: each `/`-separated segment keeps only letters,
+//! digits, `-`, `_`, and `.`, and an ID longer than [`MAX_ID_LEN`] is cut
+//! short with a hash of the full ID appended so it stays unique. `aps add
+//! --id-template` builds IDs from the repository, skill name, and path.
+
+use crate::error::{ApsError, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+
+/// Longest generated entry ID
+pub const MAX_ID_LEN: usize = 64;
+
+/// Hex digits of the hash appended to a shortened ID
+const HASH_LEN: usize = 6;
+
+/// What an `--id-template` can refer to for a discovered skill
+pub struct IdParts<'a> {
+    /// Short name of the repository or directory searched
+    pub repo: &'a str,
+    /// The skill's name
+    pub name: &'a str,
+    /// Path of the skill within the repository
+    pub path: &'a str,
+}
+
+impl IdParts<'_> {
+    /// Folder containing the skill, or the repository at its top level
+    fn parent(&self) -> &str {
+        self.path
+            .trim_end_matches('/')
+            .rsplit_once('/')
+            .map(|(parent, _)| parent.rsplit('/').next().unwrap_or(parent))
+            .unwrap_or(self.repo)
+    }
+}
+
+/// Fill in the placeholders of `template` (`{repo}`, `{name}`, `{parent}`,
+/// `{path}`) and normalize the result
+pub fn render_template(template: &str, parts: &IdParts) -> Result<String> {
+    let invalid = |reason: String| ApsError::InvalidIdTemplate {
+        template: template.to_string(),
+        reason,
+    };
+    let mut id = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        id.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| invalid("unclosed `{`".to_string()))?;
+        match &rest[start + 1..start + end] {
+            "repo" => id.push_str(parts.repo),
+            "name" => id.push_str(parts.name),
+            "parent" => id.push_str(parts.parent()),
+            "path" => id.push_str(&parts.path.replace('/', "-")),
+            other => return Err(invalid(format!("unknown placeholder `{{{}}}`", other))),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    id.push_str(rest);
+
+    let id = normalize_id(&id);
+    if id.is_empty() {
+        return Err(invalid("it produces an empty ID".to_string()));
+    }
+    Ok(id)
+}
+
+/// Keep letters, digits, `-`, `_`, and `.`, turning anything else into a
+/// single `-`, and trim separators from the ends
+fn sanitize_segment(segment: &str) -> String {
+    let mut clean = String::with_capacity(segment.len());
+    for c in segment.chars() {
+        if c.is_ascii_alphanumeric() || matches!(c, '_' | '.') {
+            clean.push(c);
+        } else if !clean.ends_with('-') {
+            clean.push('-');
+        }
+    }
+    clean.trim_matches(['-', '_', '.']).to_string()
+}
+
+/// Normalize a generated ID: sanitize each `/`-separated segment, drop empty
+/// ones, and shorten IDs over [`MAX_ID_LEN`], keeping them unique with a
+/// hash of the full ID
+pub fn normalize_id(id: &str) -> String {
+    let segments: Vec<String> = id
+        .split('/')
+        .map(sanitize_segment)
+        .filter(|s| !s.is_empty())
+        .collect();
+    let id = segments.join("/");
+    if id.len() <= MAX_ID_LEN {
+        return id;
+    }
+    let hash = hex::encode(Sha256::digest(id.as_bytes()));
+    let keep = MAX_ID_LEN - HASH_LEN - 1;
+    let prefix = id[..keep].trim_end_matches(['-', '_', '.', '/']);
+    format!("{}-{}", prefix, &hash[..HASH_LEN])
+}
+
+/// Folder a skill entry installs into: the last segment of its ID, so
+/// namespaced IDs still install under the skill's name
+pub fn dest_folder(id: &str) -> String {
+    let folder = id.rsplit('/').next().unwrap_or(id);
+    match sanitize_segment(folder) {
+        clean if clean.is_empty() => folder.to_string(),
+        clean => clean,
+    }
+}
+
+/// `candidate`, or the first of `candidate-2`, `candidate-3`, … not in `taken`
+pub fn unique(candidate: &str, taken: &HashSet<String>) -> String {
+    if !taken.contains(candidate) {
+        return candidate.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", candidate, n))
+        .find(|c| !taken.contains(c))
+        .expect("an unused suffix exists")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_id_sanitizes_and_shortens() {
+        assert_eq!(normalize_id("My Skill (v2)!"), "My-Skill-v2");
+        assert_eq!(normalize_id("skills/--review--/"), "skills/review");
+        let long = format!("team/{}", "deep-path-".repeat(10));
+        let shortened = normalize_id(&long);
+        assert!(shortened.len() <= MAX_ID_LEN);
+        assert_ne!(shortened, normalize_id(&format!("{}x", long)));
+    }
+
+    #[test]
+    fn test_render_template() {
+        let parts = IdParts {
+            repo: "skills",
+            name: "review",
+            path: "packs/python/review",
+        };
+        assert_eq!(
+            render_template("{repo}-{name}", &parts).unwrap(),
+            "skills-review"
+        );
+        assert_eq!(
+            render_template("{parent}/{name}", &parts).unwrap(),
+            "python/review"
+        );
+        assert_eq!(
+            render_template("{path}", &parts).unwrap(),
+            "packs-python-review"
+        );
+        assert!(render_template("{owner}-{name}", &parts).is_err());
+        assert!(render_template("{name", &parts).is_err());
+    }
+
+    #[test]
+    fn test_unique_appends_a_counter() {
+        let taken: HashSet<String> = ["review".to_string(), "review-2".to_string()].into();
+        assert_eq!(unique("review", &taken), "review-3");
+        assert_eq!(unique("lint", &taken), "lint");
+    }
+}
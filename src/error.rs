@@ -5,7 +5,7 @@
 #![allow(unused_assignments)]
 
 use miette::Diagnostic;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, ApsError>;
@@ -28,13 +28,48 @@ pub enum ApsError {
     #[diagnostic(code(aps::manifest::parse_error))]
     ManifestParseError { message: String },
 
+    #[error("Manifest at {path:?} changed on disk while aps was editing it")]
+    #[diagnostic(
+        code(aps::manifest::changed_on_disk),
+        help("Nothing was written. Run the command again to apply it to the current manifest")
+    )]
+    ManifestChangedOnDisk { path: PathBuf },
+
     #[error("Invalid asset kind: {kind}")]
     #[diagnostic(
         code(aps::manifest::invalid_kind),
-        help("Valid kinds are: cursor_rules, cursor_hooks, cursor_skills_root, agents_md, composite_agents_md, agent_skill")
+        help("Valid kinds are: cursor_rules, cursor_hooks, cursor_skills_root, agents_md, composite_agents_md, agent_skill, persona")
     )]
     InvalidAssetKind { kind: String },
 
+    #[error("Invalid dest_roots key: {key}")]
+    #[diagnostic(
+        code(aps::manifest::invalid_dest_root),
+        help("Keys are asset kinds (e.g., agent_skill, cursor_rules) or tools (cursor, claude)")
+    )]
+    InvalidDestRoot { key: String },
+
+    #[error("Invalid transform '{transform}': {reason}")]
+    #[diagnostic(
+        code(aps::manifest::invalid_transform),
+        help("Transforms are add_mdc_frontmatter, strip_frontmatter, and rename_extension: <from>-><to>")
+    )]
+    InvalidTransform { transform: String, reason: String },
+
+    #[error("Invalid checksum_ignore pattern '{pattern}': {message}")]
+    #[diagnostic(
+        code(aps::manifest::invalid_checksum_ignore),
+        help("Use glob patterns relative to the source, e.g. `build/**` or `*.timestamp`")
+    )]
+    InvalidChecksumIgnore { pattern: String, message: String },
+
+    #[error("Invalid also_install_for on entry '{id}': {message}")]
+    #[diagnostic(
+        code(aps::manifest::invalid_mirror),
+        help("also_install_for accepts `claude` and `cursor`, for entries installed under .claude/ or .cursor/")
+    )]
+    InvalidMirror { id: String, message: String },
+
     #[error("Invalid source type: {source_type}")]
     #[diagnostic(
         code(aps::manifest::invalid_source),
@@ -42,13 +77,60 @@ pub enum ApsError {
     )]
     InvalidSourceType { source_type: String },
 
+    #[error("Entry '{id}': `{field}` {message}")]
+    #[diagnostic(
+        code(aps::manifest::ineffective_setting),
+        help("Remove the setting, or move it to an entry where it applies")
+    )]
+    IneffectiveSetting {
+        id: String,
+        field: String,
+        message: String,
+    },
+
     #[error("Duplicate entry ID: {id}")]
     #[diagnostic(code(aps::manifest::duplicate_id))]
     DuplicateId { id: String },
 
+    #[error("Entries [{ids}] write to destinations that differ only in case: {dests}")]
+    #[diagnostic(
+        code(aps::manifest::case_collision),
+        help("This filesystem ignores case, so these entries would overwrite each other. Rename one destination so the paths differ by more than case")
+    )]
+    CaseInsensitiveCollision { ids: String, dests: String },
+
     #[error("Source path not found: {path}")]
     #[diagnostic(code(aps::source::path_not_found))]
-    SourcePathNotFound { path: PathBuf },
+    SourcePathNotFound {
+        path: PathBuf,
+        #[help]
+        help: String,
+    },
+
+    #[error("Source symlink {link} points outside the source ({target})")]
+    #[diagnostic(
+        code(aps::source::unsafe_link),
+        help("Remove the symlink from the source, or run without --strict to skip it")
+    )]
+    UnsafeSourceLink { link: PathBuf, target: PathBuf },
+
+    #[error("Entry '{entry}' not found in {manifest}")]
+    #[diagnostic(
+        code(aps::source::aps_entry_not_found),
+        help("Check the `entry` ID against the referenced project's manifest")
+    )]
+    ApsSourceEntryNotFound { entry: String, manifest: PathBuf },
+
+    #[error("aps sources form a cycle: {chain}")]
+    #[diagnostic(
+        code(aps::source::aps_cycle),
+        help("Point one of these entries at a git or filesystem source instead")
+    )]
+    ApsSourceCycle { chain: String },
+
+    #[error("Path {path} would escape {root}")]
+    #[diagnostic(code(aps::install::unsafe_path))]
+    UnsafePath { path: PathBuf, root: PathBuf },
 
     #[error("Conflict detected at {path}")]
     #[diagnostic(
@@ -57,6 +139,20 @@ pub enum ApsError {
     )]
     Conflict { path: PathBuf },
 
+    #[error("Entry '{id}' has local changes at {path} and upstream changes to install")]
+    #[diagnostic(
+        code(aps::install::locally_modified),
+        help("Run interactively to review the diff, or use --yes (or --yes=overwrite) to back up the local changes and overwrite")
+    )]
+    LocallyModified { id: String, path: PathBuf },
+
+    #[error("Sync would overwrite uncommitted changes: {paths}")]
+    #[diagnostic(
+        code(aps::install::uncommitted_changes),
+        help("Commit or stash the changes first, or pass --force to sync anyway")
+    )]
+    UncommittedChanges { paths: String },
+
     #[error("Operation cancelled by user")]
     #[diagnostic(code(aps::cancelled))]
     Cancelled,
@@ -77,9 +173,85 @@ pub enum ApsError {
     },
 
     #[error("Failed to read lockfile: {message}")]
-    #[diagnostic(code(aps::lockfile::read_error))]
+    #[diagnostic(
+        code(aps::lockfile::read_error),
+        help("Run `aps sync` to recover the readable entries; the malformed lockfile is kept under .aps-backups/")
+    )]
     LockfileReadError { message: String },
 
+    #[error("Lockfile format v{version} (written by aps {aps_version}) is newer than supported v{supported}")]
+    #[diagnostic(
+        code(aps::lockfile::unsupported_version),
+        help("Upgrade aps to read this lockfile, or pass --force to read it best-effort (unknown fields are dropped on save)")
+    )]
+    LockfileVersionUnsupported {
+        version: u64,
+        supported: u32,
+        aps_version: String,
+    },
+
+    #[error("{count} entries failed to sync: {ids}")]
+    #[diagnostic(
+        code(aps::sync::incomplete),
+        help(
+            "Other entries were synced. Fix the errors above, then run `aps sync --retry-failed`"
+        )
+    )]
+    SyncIncomplete { count: usize, ids: String },
+
+    #[error("{count} lines could not be added: {lines}")]
+    #[diagnostic(
+        code(aps::add::incomplete),
+        help("The other lines were added. Fix the lines above, then add them again")
+    )]
+    AddIncomplete { count: usize, lines: String },
+
+    #[error("{count} sync targets failed: {targets}")]
+    #[diagnostic(
+        code(aps::sync::targets_incomplete),
+        help("Other targets were synced. Fix the errors above, then run `aps sync` again")
+    )]
+    SyncTargetsIncomplete { count: usize, targets: String },
+
+    #[error("Plan is out of date: {reason}")]
+    #[diagnostic(
+        code(aps::plan::drift),
+        help("Run `aps plan` again and review the new plan before applying it")
+    )]
+    PlanDrift { reason: String },
+
+    #[error("Invalid cache archive {path}: {message}")]
+    #[diagnostic(
+        code(aps::cache::invalid_archive),
+        help("Create the archive with `aps cache export`")
+    )]
+    InvalidCacheArchive { path: PathBuf, message: String },
+
+    #[error("Cache archive entry {file} failed verification (expected {expected}, got {actual})")]
+    #[diagnostic(
+        code(aps::cache::integrity),
+        help("The archive was modified or corrupted in transit; export it again")
+    )]
+    CacheIntegrity {
+        file: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error("Cannot sync '{id}' offline: {reason}")]
+    #[diagnostic(
+        code(aps::cache::offline_unavailable),
+        help("Import a cache exported for this lockfile with `aps cache import`, or sync online")
+    )]
+    OfflineUnavailable { id: String, reason: String },
+
+    #[error("Unknown lockfile profile '{name}'")]
+    #[diagnostic(
+        code(aps::lockfile::unknown_profile),
+        help("Profiles are the keys of `lockfiles` in the manifest ({available}); pass a path like ./aps.lock.ci.yaml to use a file directly")
+    )]
+    UnknownLockfileProfile { name: String, available: String },
+
     #[error("No lockfile found")]
     #[diagnostic(
         code(aps::lockfile::not_found),
@@ -98,6 +270,24 @@ pub enum ApsError {
     #[diagnostic(code(aps::git::error))]
     GitError { message: String },
 
+    #[error("git is not installed or could not be run")]
+    #[diagnostic(
+        code(aps::git::not_found),
+        help("Install git and make sure it is on your PATH")
+    )]
+    GitNotFound,
+
+    #[error("{feature} needs git >= {required}, found {found}")]
+    #[diagnostic(
+        code(aps::git::version_unsupported),
+        help("Upgrade git; `aps doctor` shows which features your version supports")
+    )]
+    GitVersionUnsupported {
+        feature: String,
+        required: String,
+        found: String,
+    },
+
     #[error("Git ref not found: tried {refs:?}")]
     #[diagnostic(
         code(aps::git::ref_not_found),
@@ -112,6 +302,20 @@ pub enum ApsError {
     )]
     EntryNotFound { id: String },
 
+    #[error("Restore point not found: {id}")]
+    #[diagnostic(
+        code(aps::backup::not_found),
+        help("Run `aps backup list` to see the available restore points")
+    )]
+    RestorePointNotFound { id: String },
+
+    #[error("Cannot restore over existing content at {path}")]
+    #[diagnostic(
+        code(aps::backup::target_exists),
+        help("Pass --force to back up the current content and replace it, or --to to restore elsewhere")
+    )]
+    RestoreTargetExists { path: PathBuf },
+
     #[error("Catalog not found")]
     #[diagnostic(
         code(aps::catalog::not_found),
@@ -130,6 +334,51 @@ pub enum ApsError {
     )]
     CompositeRequiresSources { id: String },
 
+    #[error("Generated source '{generator}' can only be used in a composite entry's sources")]
+    #[diagnostic(
+        code(aps::manifest::generated_source),
+        help("Move it into the `sources` list of a composite_agents_md entry")
+    )]
+    GeneratedSourceNotComposite { generator: String },
+
+    #[error("HTTP source '{url}' fetches a single file and can't install a {kind} entry")]
+    #[diagnostic(
+        code(aps::manifest::http_source),
+        help(
+            "Use it for an agents_md entry or in the `sources` list of a composite_agents_md entry"
+        )
+    )]
+    HttpSourceNotSingleFile { url: String, kind: String },
+
+    #[error("Failed to fetch {url}: {message}")]
+    #[diagnostic(
+        code(aps::source::http),
+        help("Check that the URL is reachable and that curl is installed")
+    )]
+    HttpError { url: String, message: String },
+
+    #[error("Header '{header}' for {url} refers to unset environment variable {var}")]
+    #[diagnostic(
+        code(aps::source::http_header_env),
+        help("Set the variable before running aps, e.g. from your CI secrets")
+    )]
+    HttpHeaderEnv {
+        url: String,
+        header: String,
+        var: String,
+    },
+
+    #[error("Content of {url} changed since it was locked ({locked} -> {actual})")]
+    #[diagnostic(
+        code(aps::source::http_checksum_mismatch),
+        help("Review the new content, then run `aps sync --upgrade` to accept it and update the lockfile")
+    )]
+    HttpChecksumMismatch {
+        url: String,
+        locked: String,
+        actual: String,
+    },
+
     #[error("Entry '{id}' requires a 'source' field")]
     #[diagnostic(
         code(aps::manifest::entry_requires_source),
@@ -161,10 +410,144 @@ pub enum ApsError {
     #[diagnostic(code(aps::hooks::script_not_found))]
     HookScriptNotFound { path: PathBuf },
 
+    #[error("Hook '{event}' runs {path}, outside the synced hooks directory")]
+    #[diagnostic(
+        code(aps::hooks::script_outside),
+        help("Ship the script with the hooks source and reference it relative to .cursor/")
+    )]
+    HookScriptOutside { event: String, path: String },
+
+    #[error("No persona markdown files found in {path}")]
+    #[diagnostic(
+        code(aps::persona::none_found),
+        help("A persona entry's source should be a directory of .md files")
+    )]
+    NoPersonasFound { path: PathBuf },
+
+    #[error("Persona {path} is missing frontmatter: {fields}")]
+    #[diagnostic(
+        code(aps::persona::missing_frontmatter),
+        help("Start the file with YAML frontmatter defining `name` and `description`")
+    )]
+    InvalidPersona { path: PathBuf, fields: String },
+
+    #[error("Invalid filter '{expr}': {message}")]
+    #[diagnostic(
+        code(aps::filter::invalid),
+        help("Use <field>=<value>, <field>!=<value>, or <field>~=<value> (contains). Fields: id, kind, owner, type, source, dest, ref, commit, symlink, status")
+    )]
+    InvalidFilter { expr: String, message: String },
+
+    #[error("Invalid user config {path}: {message}")]
+    #[diagnostic(
+        code(aps::config::invalid),
+        help(
+            "Supported settings are `jobs`, `low_bandwidth`, `index`, and `post_install`; set APS_CONFIG= to ignore the file"
+        )
+    )]
+    UserConfigInvalid { path: PathBuf, message: String },
+
+    #[error("Invalid project index {path}: {message}")]
+    #[diagnostic(
+        code(aps::index::invalid),
+        help("Delete the file; the next syncs of each project record it again")
+    )]
+    ProjectIndexInvalid { path: PathBuf, message: String },
+
+    #[error("Entries in {path} are not sorted by kind, then ID")]
+    #[diagnostic(code(aps::manifest::unsorted), help("Run `aps sort` to reorder them"))]
+    ManifestUnsorted { path: PathBuf },
+
+    #[error("No git history found for {path}")]
+    #[diagnostic(
+        code(aps::changelog::no_history),
+        help("aps changelog reads the lockfile's git history; commit the lockfile to start one")
+    )]
+    NoLockfileHistory { path: PathBuf },
+
+    #[error("Invalid --override '{value}': {reason}")]
+    #[diagnostic(
+        code(aps::sync::invalid_override),
+        help("Use `--override <id>=git+<url>[@<ref>]` or `--override <id>=<local directory>`")
+    )]
+    InvalidOverride { value: String, reason: String },
+
+    #[error("Sync cannot write {count} destination(s):{problems}")]
+    #[diagnostic(
+        code(aps::sync::preflight),
+        help("Fix the permissions or free up space, then sync again. Nothing was installed.")
+    )]
+    DestinationPreflightFailed { count: usize, problems: String },
+
+    #[error("Cannot pin '{id}' from its destination: {reason}")]
+    #[diagnostic(
+        code(aps::pin::unsupported),
+        help("Only copied destinations that aps writes in full can be pinned; change the source instead")
+    )]
+    PinUnsupported { id: String, reason: String },
+
+    #[error("Invalid search pattern '{pattern}': {message}")]
+    #[diagnostic(
+        code(aps::grep::invalid_pattern),
+        help("Patterns are regular expressions; pass --fixed-strings to search for the text as written")
+    )]
+    InvalidPattern { pattern: String, message: String },
+
+    #[error("Invalid source pattern '{pattern}': {message}")]
+    #[diagnostic(
+        code(aps::who_uses::invalid_pattern),
+        help("Pass a repository (github.com/org/repo), a local path, or a glob such as 'github.com/org/*'")
+    )]
+    InvalidSourcePattern { pattern: String, message: String },
+
+    #[error("Invalid release ref '{value}'")]
+    #[diagnostic(
+        code(aps::git::invalid_release_ref),
+        help("Use release:latest, or a version constraint such as release:^1, release:~1.2, or release:1.4.0")
+    )]
+    InvalidReleaseRef { value: String },
+
+    #[error("No release of {repo} matches '{constraint}'")]
+    #[diagnostic(
+        code(aps::git::no_matching_release),
+        help("Releases are read from the GitHub releases API, or from version tags like v1.2.0 elsewhere; loosen the constraint or publish a matching release")
+    )]
+    NoMatchingRelease { repo: String, constraint: String },
+
+    #[error("Source path '{pattern}' of entry '{id}' cannot be expanded: {message}")]
+    #[diagnostic(
+        code(aps::source::invalid_glob),
+        help("Glob paths such as `skills/*` install each match under `dest` by its name; matches must exist and have distinct names")
+    )]
+    InvalidSourceGlob {
+        id: String,
+        pattern: String,
+        message: String,
+    },
+
     #[error("Invalid GitHub URL: {url}")]
     #[diagnostic(code(aps::add::invalid_github_url), help("{reason}"))]
     InvalidGitHubUrl { url: String, reason: String },
 
+    #[error("Invalid GitLab URL: {url}")]
+    #[diagnostic(code(aps::add::invalid_gitlab_url), help("{reason}"))]
+    InvalidGitLabUrl { url: String, reason: String },
+
+    #[error("Ref '{git_ref}' not found in {repo}")]
+    #[diagnostic(code(aps::add::ref_not_found), help("{hint}"))]
+    RefNotFound {
+        repo: String,
+        git_ref: String,
+        hint: String,
+    },
+
+    #[error("Invalid --id-template '{template}': {reason}")]
+    #[diagnostic(
+        code(aps::add::invalid_id_template),
+        help("Use the placeholders {{repo}}, {{name}}, {{parent}}, and {{path}}, e.g. `{{repo}}-{{name}}`")
+    )]
+    InvalidIdTemplate { template: String, reason: String },
+
     #[error("No skills found in {location}")]
     #[diagnostic(
         code(aps::discover::no_skills),
@@ -172,10 +555,33 @@ pub enum ApsError {
     )]
     NoSkillsFound { location: String },
 
+    #[error("`{command}` needs an interactive terminal")]
+    #[diagnostic(
+        code(aps::cli::not_interactive),
+        help("Run it from a terminal, outside CI")
+    )]
+    NotInteractive { command: String },
+
     #[error("No skills selected")]
     #[diagnostic(code(aps::discover::none_selected))]
     NoSkillsSelected,
 
+    #[error("Composed file for '{id}' breaks compose_limits: {problems}")]
+    #[diagnostic(
+        code(aps::compose::limit_exceeded),
+        help(
+            "Trim or split the partials, raise `compose_limits` in the manifest, or suppress W012"
+        )
+    )]
+    ComposeLimitExceeded { id: String, problems: String },
+
+    #[error("No rule files selected")]
+    #[diagnostic(
+        code(aps::discover::no_rules_selected),
+        help("Keep at least one rule, or pass --all to add the whole folder")
+    )]
+    NoRulesSelected,
+
     #[error("{message}")]
     #[diagnostic(code(aps::invalid_input))]
     InvalidInput { message: String },
@@ -188,4 +594,14 @@ impl ApsError {
             source: err,
         }
     }
+
+    /// A missing source path, relative to `root` when known; see [`crate::explain`]
+    pub fn source_path_not_found(
+        path: PathBuf,
+        root: Option<&Path>,
+        git_ref: Option<&str>,
+    ) -> Self {
+        let help = crate::explain::missing_source_path(&path, root, git_ref);
+        ApsError::SourcePathNotFound { path, help }
+    }
 }
@@ -0,0 +1,178 @@
+//! Remediation hints for `--explain`.
+//!
+//! Errors carry a short help line by default. With `--explain` on `aps sync`
+//! or `aps validate`, the help spells out how to fix the problem, including
+//! suggestions computed by listing what the source actually contains.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use walkdir::WalkDir;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Most similar paths offered for a missing source path
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Source entries considered when looking for similar paths
+const MAX_CANDIDATES: usize = 5000;
+
+/// Enable detailed remediation hints for this process
+pub fn init(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether `--explain` was passed
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Help for a source `path` that does not exist. `root` is the clone or
+/// directory the entry's `path:` is relative to, and `git_ref` the ref it
+/// was resolved at.
+pub fn missing_source_path(path: &Path, root: Option<&Path>, git_ref: Option<&str>) -> String {
+    if !enabled() {
+        return "Check the entry's `path:`, or rerun with --explain for suggestions".to_string();
+    }
+    let Some((root, wanted)) =
+        root.and_then(|root| Some((root, path.strip_prefix(root).ok()?.to_string_lossy())))
+    else {
+        return format!(
+            "Check that {} exists and is readable, or fix the entry's `root:`",
+            path.display()
+        );
+    };
+
+    let at = git_ref
+        .map(|git_ref| format!(" at ref {}", git_ref))
+        .unwrap_or_default();
+    let similar = similar_paths(root, &wanted);
+    if similar.is_empty() {
+        format!(
+            "Check that `path: {}` exists{}; no similar paths were found in the source",
+            wanted, at
+        )
+    } else {
+        format!(
+            "Check that `path: {}` exists{}. Similar paths in the source: {}",
+            wanted,
+            at,
+            similar.join(", ")
+        )
+    }
+}
+
+/// Paths under `root` closest to `wanted`, best first. A path counts as
+/// similar when it is a few edits away or has the same file name.
+fn similar_paths(root: &Path, wanted: &str) -> Vec<String> {
+    let wanted = wanted.replace('\\', "/").to_lowercase();
+    let wanted_name = wanted.rsplit('/').next().unwrap_or(&wanted).to_string();
+    let threshold = (wanted.chars().count() / 3).max(2);
+
+    let mut scored: Vec<(usize, String)> = WalkDir::new(root)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+        .take(MAX_CANDIDATES)
+        .filter_map(|e| {
+            let relative = e
+                .path()
+                .strip_prefix(root)
+                .ok()?
+                .to_string_lossy()
+                .replace('\\', "/");
+            let lower = relative.to_lowercase();
+            let name = lower.rsplit('/').next().unwrap_or(&lower);
+            let score = if name == wanted_name {
+                1
+            } else {
+                edit_distance(&wanted, &lower)
+            };
+            (score <= threshold).then_some((score, relative))
+        })
+        .collect();
+    scored.sort();
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, path)| path)
+        .collect()
+}
+
+/// Branches and tags closest to a `wanted` ref that the remote lacks, best
+/// first. A ref counts as similar when it is a few edits away, or contains
+/// (or is contained in) the wanted one, ignoring case.
+pub fn similar_refs(wanted: &str, refs: &[String]) -> Vec<String> {
+    let wanted = wanted.to_lowercase();
+    let threshold = (wanted.chars().count() / 3).max(2);
+    let mut scored: Vec<(usize, &String)> = refs
+        .iter()
+        .filter_map(|candidate| {
+            let lower = candidate.to_lowercase();
+            let distance = edit_distance(&wanted, &lower);
+            let contains = lower.contains(&wanted) || wanted.contains(&lower);
+            (distance <= threshold || contains).then_some((distance, candidate))
+        })
+        .collect();
+    scored.sort();
+    scored.dedup_by(|a, b| a.1 == b.1);
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.clone())
+        .collect()
+}
+
+/// Levenshtein distance between two strings, by character
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ca != *cb);
+            current.push(substitute.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_similar_paths_suggests_near_misses() {
+        let temp = tempdir().unwrap();
+        fs::create_dir_all(temp.path().join("skills/refactor")).unwrap();
+        fs::create_dir_all(temp.path().join("docs")).unwrap();
+        fs::write(temp.path().join("docs/AGENTS.md"), "# Agents\n").unwrap();
+
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(
+            similar_paths(temp.path(), "skills/refactr"),
+            vec!["skills/refactor"]
+        );
+        // Same file name in another directory
+        assert_eq!(
+            similar_paths(temp.path(), "AGENTS.md"),
+            vec!["docs/AGENTS.md"]
+        );
+        assert!(similar_paths(temp.path(), "completely/unrelated").is_empty());
+    }
+
+    #[test]
+    fn test_similar_refs_suggests_branches_and_tags() {
+        let refs: Vec<String> = ["main", "develop", "feature/login", "v1.2.0", "v2.0.0"]
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        assert_eq!(similar_refs("mian", &refs), vec!["main"]);
+        assert_eq!(similar_refs("Develpo", &refs), vec!["develop"]);
+        assert_eq!(similar_refs("v1.2", &refs)[0], "v1.2.0");
+        assert!(similar_refs("release-candidate", &refs).is_empty());
+    }
+}
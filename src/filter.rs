@@ -0,0 +1,240 @@
+//! Filter expressions for slicing `aps status` and `aps list` output.
+//!
+//! An expression is `<field><op><value>` where `op` is `=` (equals), `!=`
+//! (differs), or `~=` (contains). Repeated `--filter` flags must all match.
+
+use crate::error::{ApsError, Result};
+use crate::lockfile::LockedEntry;
+use crate::manifest::{Entry, Source};
+
+/// Fields that filters can test
+pub const FILTER_FIELDS: [&str; 10] = [
+    "id", "kind", "owner", "type", "source", "dest", "ref", "commit", "symlink", "status",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FilterOp {
+    Equals,
+    NotEquals,
+    Contains,
+}
+
+/// A parsed `--filter` expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    field: &'static str,
+    op: FilterOp,
+    value: String,
+}
+
+impl Filter {
+    /// Parse an expression such as `kind=agent_skill` or `source~=github.com/org`
+    pub fn parse(expr: &str) -> Result<Self> {
+        let invalid = |message: String| ApsError::InvalidFilter {
+            expr: expr.to_string(),
+            message,
+        };
+
+        // Check two-character operators before the bare `=` they end with
+        let (field, op, value) = if let Some((field, value)) = expr.split_once("~=") {
+            (field, FilterOp::Contains, value)
+        } else if let Some((field, value)) = expr.split_once("!=") {
+            (field, FilterOp::NotEquals, value)
+        } else if let Some((field, value)) = expr.split_once('=') {
+            (field, FilterOp::Equals, value)
+        } else {
+            return Err(invalid(
+                "expected <field>=<value>, <field>!=<value>, or <field>~=<value>".to_string(),
+            ));
+        };
+
+        let field = field.trim();
+        let known = FILTER_FIELDS
+            .into_iter()
+            .find(|name| *name == field)
+            .ok_or_else(|| invalid(format!("unknown field '{}'", field)))?;
+
+        Ok(Self {
+            field: known,
+            op,
+            value: value.trim().to_string(),
+        })
+    }
+
+    /// Parse every `--filter` flag
+    pub fn parse_all(exprs: &[String]) -> Result<Vec<Self>> {
+        exprs.iter().map(|e| Self::parse(e)).collect()
+    }
+
+    fn matches(&self, facts: &EntryFacts) -> bool {
+        let actual = facts.value(self.field);
+        match self.op {
+            FilterOp::Equals => actual.as_deref() == Some(self.value.as_str()),
+            FilterOp::NotEquals => actual.as_deref() != Some(self.value.as_str()),
+            FilterOp::Contains => actual.is_some_and(|a| a.contains(&self.value)),
+        }
+    }
+}
+
+/// Whether an entry passes all filters.
+///
+/// Either side may be missing: manifest entries that were never synced have no
+/// locked entry, and stale lockfile entries have no manifest entry.
+pub fn matches_all(
+    filters: &[Filter],
+    id: &str,
+    entry: Option<&Entry>,
+    locked: Option<&LockedEntry>,
+) -> bool {
+    let facts = EntryFacts { id, entry, locked };
+    filters.iter().all(|f| f.matches(&facts))
+}
+
+struct EntryFacts<'a> {
+    id: &'a str,
+    entry: Option<&'a Entry>,
+    locked: Option<&'a LockedEntry>,
+}
+
+impl EntryFacts<'_> {
+    fn value(&self, field: &str) -> Option<String> {
+        let source = self.entry.and_then(|e| e.source.as_ref());
+        match field {
+            "id" => Some(self.id.to_string()),
+            "kind" => self.entry.map(|e| e.kind.as_str().to_string()),
+            "owner" => self.entry.and_then(|e| e.owner.clone()),
+            "type" => match (self.entry, source) {
+                (Some(e), _) if e.is_composite() => Some("composite".to_string()),
+                (_, Some(Source::Git { .. })) => Some("git".to_string()),
+                (_, Some(Source::Filesystem { .. })) => Some("filesystem".to_string()),
+                (_, Some(Source::Http { .. })) => Some("http".to_string()),
+                (_, Some(Source::Aps { .. })) => Some("aps".to_string()),
+                (_, Some(Source::Generated { .. })) => Some("generated".to_string()),
+                _ => None,
+            },
+            "source" => match self.entry {
+                Some(e) if e.is_composite() => Some(
+                    e.sources
+                        .iter()
+                        .map(source_label)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+                _ => source
+                    .map(source_label)
+                    .or_else(|| self.locked.map(|l| l.source.to_string())),
+            },
+            "dest" => self
+                .entry
+                .map(|e| e.destination().to_string_lossy().to_string())
+                .or_else(|| self.locked.map(|l| l.dest.clone())),
+            "ref" => source
+                .and_then(|s| s.git_info())
+                .map(|(_, r)| r.to_string()),
+            "commit" => self.locked.and_then(|l| l.commit.clone()),
+            "symlink" => match source {
+                Some(Source::Filesystem { symlink, .. }) => Some(symlink.to_string()),
+                Some(Source::Git { .. } | Source::Http { .. } | Source::Generated { .. }) => {
+                    Some("false".to_string())
+                }
+                Some(Source::Aps { .. }) => self.locked.map(|l| l.is_symlink.to_string()),
+                None => self.locked.map(|l| l.is_symlink.to_string()),
+            },
+            "status" => match self.locked {
+                Some(l) => l
+                    .last_status
+                    .map(|s| s.to_string().replace(' ', "_"))
+                    .or_else(|| Some("synced".to_string())),
+                None => Some("never".to_string()),
+            },
+            _ => None,
+        }
+    }
+}
+
+fn source_label(source: &Source) -> String {
+    match source {
+        Source::Git { repo, path, .. } => match path {
+            Some(p) => format!("{}/{}", repo.trim_end_matches(".git"), p),
+            None => repo.clone(),
+        },
+        Source::Filesystem { root, path, .. } => match path {
+            Some(p) => format!("{}/{}", root, p),
+            None => root.clone(),
+        },
+        Source::Http { .. } | Source::Aps { .. } | Source::Generated { .. } => {
+            source.display_path()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::AssetKind;
+
+    fn git_skill(id: &str, repo: &str) -> Entry {
+        Entry {
+            id: id.to_string(),
+            kind: AssetKind::AgentSkill,
+            source: Some(Source::Git {
+                repo: repo.to_string(),
+                r#ref: "main".to_string(),
+                shallow: true,
+                path: Some("skills/review".to_string()),
+            }),
+            sources: Vec::new(),
+            dest: None,
+            include: Vec::new(),
+            checksum_ignore: Vec::new(),
+            max_age: None,
+            preserve_permissions: true,
+            preserve_mtime: false,
+            hooks_merge: Default::default(),
+            default_dest_override: None,
+            description: None,
+            owner: None,
+            copy_license: false,
+            also_install_for: Vec::new(),
+            frozen: false,
+            targets: Vec::new(),
+            transform: Vec::new(),
+            suppress_warnings: Vec::new(),
+            backup: None,
+            backup_dir: None,
+            symlink_style: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_operators_and_unknown_fields() {
+        let filter = Filter::parse("source~=github.com/org").unwrap();
+        assert_eq!(filter.op, FilterOp::Contains);
+        assert_eq!(filter.value, "github.com/org");
+        assert_eq!(
+            Filter::parse("kind!=persona").unwrap().op,
+            FilterOp::NotEquals
+        );
+        assert_eq!(Filter::parse("symlink=true").unwrap().op, FilterOp::Equals);
+
+        let err = Filter::parse("team=me").unwrap_err();
+        assert!(err.to_string().contains("unknown field 'team'"));
+        assert!(Filter::parse("kind").is_err());
+    }
+
+    #[test]
+    fn test_matches_manifest_and_lockfile_facts() {
+        let entry = git_skill("review", "https://github.com/org/skills.git");
+        let filters = Filter::parse_all(&[
+            "kind=agent_skill".to_string(),
+            "source~=github.com/org".to_string(),
+            "symlink=false".to_string(),
+            "status=never".to_string(),
+        ])
+        .unwrap();
+        assert!(matches_all(&filters, "review", Some(&entry), None));
+
+        let other = git_skill("other", "https://gitlab.com/team/skills.git");
+        assert!(!matches_all(&filters, "other", Some(&other), None));
+    }
+}
@@ -1,11 +1,19 @@
-//! GitHub URL parsing for the `aps add` command.
+//! GitHub and GitLab URL parsing for the `aps add` command.
 //!
-//! Parses GitHub URLs to extract repository, branch/ref, and path information.
+//! Parses repository URLs to extract repository, branch/ref, and path information.
 //!
 //! Supported URL formats:
 //! - `https://github.com/{owner}/{repo}/blob/{ref}/{path}` - file URLs
 //! - `https://github.com/{owner}/{repo}/tree/{ref}/{path}` - directory URLs
 //! - `https://github.com/{owner}/{repo}/blob/{ref}/{path}/SKILL.md` - direct skill file
+//! - `https://gitlab.com/{group}[/{subgroup}...]/{repo}[/-/{blob|tree}/{ref}/{path}]` -
+//!   GitLab URLs, also on a self-hosted instance named with `--gitlab-host`
+//! - The GitHub formats on a GitHub Enterprise instance named with `--host`
+//! - `{owner}/{repo}[@{ref}][:{path}]` - shorthand, expanded against a host
+//!
+//! URLs assume a single-segment ref. For refs containing slashes
+//! (`feature/foo`), `aps add` re-splits the URL with [`ParsedGitHubUrl::with_ref`]
+//! using `--ref` or the refs the remote advertises.
 
 use crate::error::{ApsError, Result};
 
@@ -47,21 +55,69 @@ impl ParsedGitHubUrl {
         let skill_path = self.skill_path();
         skill_path.rsplit('/').next().filter(|s| !s.is_empty())
     }
+
+    /// Refs the URL could name, shortest first.
+    ///
+    /// A URL does not mark where a ref containing slashes ends, so
+    /// `blob/feature/foo/skills/x` may name the ref `feature`, `feature/foo`,
+    /// `feature/foo/skills`, or `feature/foo/skills/x`.
+    pub fn ref_candidates(&self) -> Vec<String> {
+        let mut candidates = vec![self.git_ref.clone()];
+        for segment in self.path.split('/').filter(|s| !s.is_empty()) {
+            let longer = format!("{}/{}", candidates[candidates.len() - 1], segment);
+            candidates.push(longer);
+        }
+        candidates
+    }
+
+    /// Re-split the URL so that `git_ref` is the ref and the rest is the path.
+    /// Returns `None` if the URL does not start with `git_ref`.
+    pub fn with_ref(&self, git_ref: &str) -> Option<Self> {
+        let git_ref = git_ref.trim_matches('/');
+        let combined = if self.path.is_empty() {
+            self.git_ref.clone()
+        } else {
+            format!("{}/{}", self.git_ref, self.path)
+        };
+        let path = if combined == git_ref {
+            ""
+        } else {
+            combined.strip_prefix(git_ref)?.strip_prefix('/')?
+        };
+
+        Some(Self {
+            repo_url: self.repo_url.clone(),
+            git_ref: git_ref.to_string(),
+            path: path.to_string(),
+            is_skill_file: is_skill_file_path(path),
+            is_repo_level: path.is_empty(),
+        })
+    }
 }
 
-/// Parse a GitHub URL into its components.
+/// Whether a repository path points to a SKILL.md file
+fn is_skill_file_path(path: &str) -> bool {
+    path.ends_with("/SKILL.md")
+        || path.ends_with("/skill.md")
+        || path == "SKILL.md"
+        || path == "skill.md"
+}
+
+/// Parse a GitHub URL into its components. URLs on `host` (a GitHub
+/// Enterprise instance) are accepted alongside github.com.
 ///
 /// # Examples
 ///
 /// ```ignore
 /// let parsed = parse_github_url(
-///     "https://github.com/hashicorp/agent-skills/blob/main/terraform/skills/refactor"
+///     "https://github.com/hashicorp/agent-skills/blob/main/terraform/skills/refactor",
+///     DEFAULT_GITHUB_HOST,
 /// )?;
 /// assert_eq!(parsed.repo_url, "https://github.com/hashicorp/agent-skills.git");
 /// assert_eq!(parsed.git_ref, "main");
 /// assert_eq!(parsed.path, "terraform/skills/refactor");
 /// ```
-pub fn parse_github_url(url: &str) -> Result<ParsedGitHubUrl> {
+pub fn parse_github_url(url: &str, host: &str) -> Result<ParsedGitHubUrl> {
     // Normalize the URL: trim whitespace
     let url = url.trim();
 
@@ -72,19 +128,28 @@ pub fn parse_github_url(url: &str) -> Result<ParsedGitHubUrl> {
     })?;
 
     // Verify it's a GitHub URL
-    let host = parsed
+    let url_host = parsed
         .host_str()
         .ok_or_else(|| ApsError::InvalidGitHubUrl {
             url: url.to_string(),
             reason: "Missing host".to_string(),
         })?;
 
-    if host != "github.com" && host != "www.github.com" {
+    let repo_host = if url_host == DEFAULT_GITHUB_HOST || url_host == "www.github.com" {
+        DEFAULT_GITHUB_HOST
+    } else if url_host == bare_host(host) {
+        bare_host(host)
+    } else {
         return Err(ApsError::InvalidGitHubUrl {
             url: url.to_string(),
-            reason: format!("Expected github.com host, got: {}", host),
+            reason: format!(
+                "Expected a github.com or gitlab.com host, got: {}. \
+                 For GitHub Enterprise, pass its host with --host; \
+                 for a self-hosted GitLab, with --gitlab-host",
+                url_host
+            ),
         });
-    }
+    };
 
     // Parse the path: /{owner}/{repo}[/{blob|tree}/{ref}[/{path...}]]
     let path_segments: Vec<&str> = parsed
@@ -106,7 +171,7 @@ pub fn parse_github_url(url: &str) -> Result<ParsedGitHubUrl> {
     let repo = path_segments[1].trim_end_matches(".git");
 
     // Construct the repo URL
-    let repo_url = format!("https://github.com/{}/{}.git", owner, repo);
+    let repo_url = format!("https://{}/{}/{}.git", repo_host, owner, repo);
 
     // Handle repo-level URLs: https://github.com/owner/repo
     if path_segments.len() == 2 {
@@ -164,10 +229,7 @@ pub fn parse_github_url(url: &str) -> Result<ParsedGitHubUrl> {
     };
 
     // Check if path points to SKILL.md
-    let is_skill_file = path.ends_with("/SKILL.md")
-        || path.ends_with("/skill.md")
-        || path == "SKILL.md"
-        || path == "skill.md";
+    let is_skill_file = is_skill_file_path(&path);
 
     Ok(ParsedGitHubUrl {
         repo_url,
@@ -178,6 +240,187 @@ pub fn parse_github_url(url: &str) -> Result<ParsedGitHubUrl> {
     })
 }
 
+/// Default host used when expanding `owner/repo` shorthand
+pub const DEFAULT_GITHUB_HOST: &str = "github.com";
+
+/// Host of GitLab's hosted service
+const GITLAB_HOST: &str = "gitlab.com";
+
+/// Strip the scheme and trailing slash from a `--host` or `--gitlab-host` value
+fn bare_host(host: &str) -> &str {
+    let host = host.trim().trim_end_matches('/');
+    host.strip_prefix("https://")
+        .or_else(|| host.strip_prefix("http://"))
+        .unwrap_or(host)
+}
+
+/// Parse a repository URL on GitHub or GitLab.
+///
+/// `gitlab.com` URLs, and URLs on `gitlab_host` (a self-hosted GitLab passed
+/// with `--gitlab-host`), are read as GitLab URLs; everything else goes to
+/// [`parse_github_url`] with `github_host`.
+pub fn parse_repo_url(
+    url: &str,
+    github_host: &str,
+    gitlab_host: Option<&str>,
+) -> Result<ParsedGitHubUrl> {
+    let url_host = url::Url::parse(url.trim())
+        .ok()
+        .and_then(|parsed| parsed.host_str().map(str::to_string));
+    let is_gitlab = match url_host.as_deref() {
+        Some(GITLAB_HOST) | Some("www.gitlab.com") => true,
+        Some(url_host) => gitlab_host.is_some_and(|host| url_host == bare_host(host)),
+        None => false,
+    };
+    if is_gitlab {
+        parse_gitlab_url(url)
+    } else {
+        parse_github_url(url, github_host)
+    }
+}
+
+/// Parse a GitLab URL into its components.
+///
+/// GitLab projects can sit in nested groups, so everything before the `/-/`
+/// separator is the project path; a URL without one names a whole project.
+///
+/// # Examples
+///
+/// ```ignore
+/// let parsed = parse_gitlab_url("https://gitlab.com/team/ai/skills/-/tree/main/skills/foo")?;
+/// assert_eq!(parsed.repo_url, "https://gitlab.com/team/ai/skills.git");
+/// assert_eq!(parsed.git_ref, "main");
+/// assert_eq!(parsed.path, "skills/foo");
+/// ```
+pub fn parse_gitlab_url(url: &str) -> Result<ParsedGitHubUrl> {
+    let url = url.trim();
+    let invalid = |reason: String| ApsError::InvalidGitLabUrl {
+        url: url.to_string(),
+        reason,
+    };
+
+    let parsed = url::Url::parse(url).map_err(|e| invalid(format!("Invalid URL format: {}", e)))?;
+    let host = parsed
+        .host_str()
+        .ok_or_else(|| invalid("Missing host".to_string()))?;
+    let origin = match parsed.port() {
+        Some(port) => format!("{}://{}:{}", parsed.scheme(), host, port),
+        None => format!("{}://{}", parsed.scheme(), host),
+    };
+
+    // Parse the path: /{group}/.../{repo}[/-/{blob|tree}/{ref}[/{path...}]]
+    let path_segments: Vec<&str> = parsed.path().split('/').filter(|s| !s.is_empty()).collect();
+    let separator = path_segments.iter().position(|s| *s == "-");
+    let project = &path_segments[..separator.unwrap_or(path_segments.len())];
+
+    // Need at least: group, repo
+    if project.len() < 2 {
+        return Err(invalid(
+            "URL must include at least a group and project".to_string(),
+        ));
+    }
+    let project_path = project.join("/");
+    let repo_url = format!("{}/{}.git", origin, project_path.trim_end_matches(".git"));
+
+    let Some(separator) = separator else {
+        return Ok(ParsedGitHubUrl {
+            repo_url,
+            git_ref: "auto".to_string(),
+            path: String::new(),
+            is_skill_file: false,
+            is_repo_level: true,
+        });
+    };
+
+    let rest = &path_segments[separator + 1..];
+    let url_type = rest.first().copied().unwrap_or_default();
+    if !matches!(url_type, "blob" | "tree" | "raw") {
+        return Err(invalid(format!(
+            "Expected 'blob' or 'tree' after '/-/', got: '{}'. \
+             URL should be like: https://gitlab.com/group/project/-/tree/main/path/to/skill",
+            url_type
+        )));
+    }
+    let Some(git_ref) = rest.get(1) else {
+        return Err(invalid(format!(
+            "URL must include a ref after {}",
+            url_type
+        )));
+    };
+
+    let path = rest[2..].join("/");
+    if path.is_empty() && url_type != "tree" {
+        return Err(invalid(format!(
+            "{} URL must include a file path after the ref",
+            url_type
+        )));
+    }
+
+    let is_skill_file = is_skill_file_path(&path);
+    Ok(ParsedGitHubUrl {
+        repo_url,
+        git_ref: git_ref.to_string(),
+        is_repo_level: path.is_empty(),
+        path,
+        is_skill_file,
+    })
+}
+
+/// Parse `owner/repo[@ref][:path]` shorthand, expanding it to a repository on `host`.
+///
+/// Returns `None` if the input does not look like shorthand, so callers can
+/// fall back to other interpretations.
+///
+/// # Examples
+///
+/// ```ignore
+/// let parsed = parse_github_shorthand("hashicorp/agent-skills@v1:terraform/refactor", "github.com")
+///     .unwrap();
+/// assert_eq!(parsed.repo_url, "https://github.com/hashicorp/agent-skills.git");
+/// assert_eq!(parsed.git_ref, "v1");
+/// assert_eq!(parsed.path, "terraform/refactor");
+/// ```
+pub fn parse_github_shorthand(input: &str, host: &str) -> Option<ParsedGitHubUrl> {
+    let input = input.trim();
+    if input.contains("://") {
+        return None;
+    }
+
+    let (repo_part, path) = match input.split_once(':') {
+        Some((repo_part, path)) => (repo_part, path.trim_matches('/')),
+        None => (input, ""),
+    };
+    let (owner_repo, git_ref) = match repo_part.split_once('@') {
+        Some((owner_repo, git_ref)) if !git_ref.is_empty() => (owner_repo, git_ref),
+        Some(_) => return None,
+        None => (repo_part, "auto"),
+    };
+
+    let (owner, repo) = owner_repo.split_once('/')?;
+    let repo = repo.trim_end_matches(".git");
+    let valid_segment = |s: &str| {
+        !s.is_empty()
+            && !s.starts_with('.')
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    if !valid_segment(owner) || !valid_segment(repo) {
+        return None;
+    }
+
+    let host = bare_host(host);
+
+    let is_skill_file = is_skill_file_path(path);
+
+    Some(ParsedGitHubUrl {
+        repo_url: format!("https://{}/{}/{}.git", host, owner, repo),
+        git_ref: git_ref.to_string(),
+        path: path.to_string(),
+        is_skill_file,
+        is_repo_level: path.is_empty(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,7 +428,7 @@ mod tests {
     #[test]
     fn test_parse_skill_folder_url() {
         let url = "https://github.com/hashicorp/agent-skills/blob/main/terraform/module-generation/skills/refactor-module";
-        let parsed = parse_github_url(url).unwrap();
+        let parsed = parse_github_url(url, DEFAULT_GITHUB_HOST).unwrap();
 
         assert_eq!(
             parsed.repo_url,
@@ -204,7 +447,7 @@ mod tests {
     #[test]
     fn test_parse_skill_md_url() {
         let url = "https://github.com/hashicorp/agent-skills/blob/main/terraform/module-generation/skills/refactor-module/SKILL.md";
-        let parsed = parse_github_url(url).unwrap();
+        let parsed = parse_github_url(url, DEFAULT_GITHUB_HOST).unwrap();
 
         assert_eq!(
             parsed.repo_url,
@@ -227,7 +470,7 @@ mod tests {
     #[test]
     fn test_parse_tree_url() {
         let url = "https://github.com/anthropics/skills/tree/main/skills/skill-creation";
-        let parsed = parse_github_url(url).unwrap();
+        let parsed = parse_github_url(url, DEFAULT_GITHUB_HOST).unwrap();
 
         assert_eq!(parsed.repo_url, "https://github.com/anthropics/skills.git");
         assert_eq!(parsed.git_ref, "main");
@@ -240,7 +483,7 @@ mod tests {
     #[test]
     fn test_parse_with_different_ref() {
         let url = "https://github.com/owner/repo/blob/v1.2.3/path/to/skill";
-        let parsed = parse_github_url(url).unwrap();
+        let parsed = parse_github_url(url, DEFAULT_GITHUB_HOST).unwrap();
 
         assert_eq!(parsed.git_ref, "v1.2.3");
         assert_eq!(parsed.path, "path/to/skill");
@@ -249,7 +492,7 @@ mod tests {
     #[test]
     fn test_parse_with_commit_sha() {
         let url = "https://github.com/owner/repo/blob/abc123def/path/to/skill";
-        let parsed = parse_github_url(url).unwrap();
+        let parsed = parse_github_url(url, DEFAULT_GITHUB_HOST).unwrap();
 
         assert_eq!(parsed.git_ref, "abc123def");
     }
@@ -257,14 +500,14 @@ mod tests {
     #[test]
     fn test_invalid_host() {
         let url = "https://gitlab.com/owner/repo/blob/main/path";
-        let result = parse_github_url(url);
+        let result = parse_github_url(url, DEFAULT_GITHUB_HOST);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_repo_level_url_with_tree_ref_no_path() {
         let url = "https://github.com/owner/repo/tree/main";
-        let parsed = parse_github_url(url).unwrap();
+        let parsed = parse_github_url(url, DEFAULT_GITHUB_HOST).unwrap();
 
         assert_eq!(parsed.repo_url, "https://github.com/owner/repo.git");
         assert_eq!(parsed.git_ref, "main");
@@ -275,21 +518,21 @@ mod tests {
     #[test]
     fn test_blob_url_without_path_is_invalid() {
         let url = "https://github.com/owner/repo/blob/main";
-        let result = parse_github_url(url);
+        let result = parse_github_url(url, DEFAULT_GITHUB_HOST);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_invalid_url_type() {
         let url = "https://github.com/owner/repo/commits/main/path";
-        let result = parse_github_url(url);
+        let result = parse_github_url(url, DEFAULT_GITHUB_HOST);
         assert!(result.is_err());
     }
 
     #[test]
     fn test_lowercase_skill_md() {
         let url = "https://github.com/owner/repo/blob/main/path/skill.md";
-        let parsed = parse_github_url(url).unwrap();
+        let parsed = parse_github_url(url, DEFAULT_GITHUB_HOST).unwrap();
         assert!(parsed.is_skill_file);
     }
 
@@ -297,7 +540,7 @@ mod tests {
     fn test_root_level_skill_md() {
         // Test uppercase SKILL.md at root
         let url = "https://github.com/owner/repo/blob/main/SKILL.md";
-        let parsed = parse_github_url(url).unwrap();
+        let parsed = parse_github_url(url, DEFAULT_GITHUB_HOST).unwrap();
 
         assert_eq!(parsed.repo_url, "https://github.com/owner/repo.git");
         assert_eq!(parsed.git_ref, "main");
@@ -308,7 +551,7 @@ mod tests {
 
         // Test lowercase skill.md at root
         let url = "https://github.com/owner/repo/blob/main/skill.md";
-        let parsed = parse_github_url(url).unwrap();
+        let parsed = parse_github_url(url, DEFAULT_GITHUB_HOST).unwrap();
 
         assert_eq!(parsed.path, "skill.md");
         assert!(parsed.is_skill_file);
@@ -319,7 +562,7 @@ mod tests {
     #[test]
     fn test_bare_repo_url() {
         let url = "https://github.com/hashicorp/agent-skills";
-        let parsed = parse_github_url(url).unwrap();
+        let parsed = parse_github_url(url, DEFAULT_GITHUB_HOST).unwrap();
 
         assert_eq!(
             parsed.repo_url,
@@ -334,7 +577,7 @@ mod tests {
     #[test]
     fn test_repo_url_with_trailing_slash() {
         let url = "https://github.com/hashicorp/agent-skills/";
-        let parsed = parse_github_url(url).unwrap();
+        let parsed = parse_github_url(url, DEFAULT_GITHUB_HOST).unwrap();
 
         assert_eq!(
             parsed.repo_url,
@@ -346,7 +589,7 @@ mod tests {
     #[test]
     fn test_tree_url_with_subpath_not_repo_level() {
         let url = "https://github.com/owner/repo/tree/main/skills";
-        let parsed = parse_github_url(url).unwrap();
+        let parsed = parse_github_url(url, DEFAULT_GITHUB_HOST).unwrap();
 
         assert_eq!(parsed.repo_url, "https://github.com/owner/repo.git");
         assert_eq!(parsed.git_ref, "main");
@@ -354,4 +597,156 @@ mod tests {
         assert!(!parsed.is_repo_level);
         assert!(!parsed.is_skill_file);
     }
+
+    #[test]
+    fn test_parse_shorthand_repo_level() {
+        let parsed = parse_github_shorthand("owner/repo", DEFAULT_GITHUB_HOST).unwrap();
+        assert_eq!(parsed.repo_url, "https://github.com/owner/repo.git");
+        assert_eq!(parsed.git_ref, "auto");
+        assert!(parsed.path.is_empty());
+        assert!(parsed.is_repo_level);
+    }
+
+    #[test]
+    fn test_parse_shorthand_with_ref_and_path() {
+        let parsed =
+            parse_github_shorthand("owner/repo@v1.2:skills/refactor/", DEFAULT_GITHUB_HOST)
+                .unwrap();
+        assert_eq!(parsed.repo_url, "https://github.com/owner/repo.git");
+        assert_eq!(parsed.git_ref, "v1.2");
+        assert_eq!(parsed.path, "skills/refactor");
+        assert_eq!(parsed.skill_name(), Some("refactor"));
+        assert!(!parsed.is_repo_level);
+    }
+
+    #[test]
+    fn test_parse_shorthand_enterprise_host() {
+        let parsed =
+            parse_github_shorthand("team/skills:SKILL.md", "https://github.example.com/").unwrap();
+        assert_eq!(
+            parsed.repo_url,
+            "https://github.example.com/team/skills.git"
+        );
+        assert!(parsed.is_skill_file);
+        assert_eq!(parsed.skill_path(), "");
+    }
+
+    #[test]
+    fn test_parse_shorthand_rejects_non_shorthand() {
+        assert!(parse_github_shorthand("just-a-name", DEFAULT_GITHUB_HOST).is_none());
+        assert!(parse_github_shorthand("a/b/c", DEFAULT_GITHUB_HOST).is_none());
+        assert!(parse_github_shorthand("owner/repo@", DEFAULT_GITHUB_HOST).is_none());
+        assert!(parse_github_shorthand("https://github.com/o/r", DEFAULT_GITHUB_HOST).is_none());
+    }
+
+    #[test]
+    fn test_ref_with_slashes_resplits_url() {
+        let url = "https://github.com/owner/repo/blob/feature/foo-bar/skills/lint/SKILL.md";
+        let parsed = parse_github_url(url, DEFAULT_GITHUB_HOST).unwrap();
+        assert_eq!(parsed.git_ref, "feature");
+        assert_eq!(
+            parsed.ref_candidates(),
+            vec![
+                "feature",
+                "feature/foo-bar",
+                "feature/foo-bar/skills",
+                "feature/foo-bar/skills/lint",
+                "feature/foo-bar/skills/lint/SKILL.md",
+            ]
+        );
+
+        let resplit = parsed.with_ref("feature/foo-bar").unwrap();
+        assert_eq!(resplit.git_ref, "feature/foo-bar");
+        assert_eq!(resplit.path, "skills/lint/SKILL.md");
+        assert!(resplit.is_skill_file);
+        assert_eq!(resplit.skill_name(), Some("lint"));
+
+        // tree/<ref> naming only a slashed branch is repo-level
+        let tree = parse_github_url(
+            "https://github.com/owner/repo/tree/release/v2",
+            DEFAULT_GITHUB_HOST,
+        )
+        .unwrap();
+        let resplit = tree.with_ref("release/v2").unwrap();
+        assert!(resplit.is_repo_level);
+        assert_eq!(resplit.path, "");
+
+        assert!(parsed.with_ref("feat").is_none());
+        assert!(parsed.with_ref("other/branch").is_none());
+    }
+
+    #[test]
+    fn test_parse_gitlab_tree_url() {
+        let url = "https://gitlab.com/owner/repo/-/tree/main/skills/foo";
+        let parsed = parse_repo_url(url, DEFAULT_GITHUB_HOST, None).unwrap();
+        assert_eq!(parsed.repo_url, "https://gitlab.com/owner/repo.git");
+        assert_eq!(parsed.git_ref, "main");
+        assert_eq!(parsed.path, "skills/foo");
+        assert!(!parsed.is_repo_level);
+        assert_eq!(parsed.skill_name(), Some("foo"));
+
+        let parsed =
+            parse_gitlab_url("https://gitlab.com/team/ai/skills/-/blob/v2/lint/SKILL.md").unwrap();
+        assert_eq!(parsed.repo_url, "https://gitlab.com/team/ai/skills.git");
+        assert_eq!(parsed.git_ref, "v2");
+        assert!(parsed.is_skill_file);
+        assert_eq!(parsed.skill_path(), "lint");
+    }
+
+    #[test]
+    fn test_parse_gitlab_repo_level_urls() {
+        let parsed = parse_gitlab_url("https://gitlab.com/team/sub/skills.git").unwrap();
+        assert_eq!(parsed.repo_url, "https://gitlab.com/team/sub/skills.git");
+        assert_eq!(parsed.git_ref, "auto");
+        assert!(parsed.is_repo_level);
+
+        let parsed = parse_gitlab_url("https://gitlab.com/team/skills/-/tree/release").unwrap();
+        assert_eq!(parsed.git_ref, "release");
+        assert!(parsed.is_repo_level);
+
+        assert!(parsed.with_ref("release").unwrap().is_repo_level);
+        assert!(parse_gitlab_url("https://gitlab.com/team").is_err());
+        assert!(parse_gitlab_url("https://gitlab.com/team/skills/-/issues/4").is_err());
+        assert!(parse_gitlab_url("https://gitlab.com/team/skills/-/blob/main").is_err());
+    }
+
+    #[test]
+    fn test_parse_repo_url_self_hosted_gitlab() {
+        let url = "https://git.example.com:8443/team/skills/-/tree/main/review";
+        assert!(parse_repo_url(url, DEFAULT_GITHUB_HOST, None).is_err());
+        // --host names GitHub Enterprise, not GitLab
+        assert!(parse_repo_url(url, "git.example.com", None).is_err());
+
+        let parsed =
+            parse_repo_url(url, DEFAULT_GITHUB_HOST, Some("https://git.example.com/")).unwrap();
+        assert_eq!(
+            parsed.repo_url,
+            "https://git.example.com:8443/team/skills.git"
+        );
+        assert_eq!(parsed.path, "review");
+
+        // GitHub URLs are unaffected by --gitlab-host
+        let parsed = parse_repo_url(
+            "https://github.com/o/r/tree/main/x",
+            DEFAULT_GITHUB_HOST,
+            Some("git.example.com"),
+        )
+        .unwrap();
+        assert_eq!(parsed.repo_url, "https://github.com/o/r.git");
+    }
+
+    #[test]
+    fn test_parse_repo_url_github_enterprise() {
+        let url = "https://ghe.corp/o/r/tree/main/x";
+        assert!(parse_repo_url(url, DEFAULT_GITHUB_HOST, None).is_err());
+
+        let parsed = parse_repo_url(url, "ghe.corp", None).unwrap();
+        assert_eq!(parsed.repo_url, "https://ghe.corp/o/r.git");
+        assert_eq!(parsed.git_ref, "main");
+        assert_eq!(parsed.path, "x");
+
+        // github.com stays GitHub whatever --host names
+        let parsed = parse_repo_url("https://github.com/o/r", "ghe.corp", None).unwrap();
+        assert_eq!(parsed.repo_url, "https://github.com/o/r.git");
+    }
 }
@@ -0,0 +1,100 @@
+//! Content search across installed assets (`aps grep`).
+
+use crate::error::{ApsError, Result};
+use regex::{Regex, RegexBuilder};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A matching line, numbered from 1
+pub struct LineMatch {
+    pub number: usize,
+    pub text: String,
+}
+
+/// The matching lines of one file
+pub struct FileMatches {
+    pub path: PathBuf,
+    pub lines: Vec<LineMatch>,
+}
+
+/// Compile a search pattern, as a regular expression unless `fixed` is set
+pub fn build_pattern(pattern: &str, ignore_case: bool, fixed: bool) -> Result<Regex> {
+    let source = if fixed {
+        regex::escape(pattern)
+    } else {
+        pattern.to_string()
+    };
+    RegexBuilder::new(&source)
+        .case_insensitive(ignore_case)
+        .build()
+        .map_err(|e| ApsError::InvalidPattern {
+            pattern: pattern.to_string(),
+            message: e.to_string(),
+        })
+}
+
+/// Search every text file under `root` (or `root` itself when it is a
+/// file), following symlinked installs. Files are returned in path order;
+/// binary and unreadable files are skipped.
+pub fn search(root: &Path, pattern: &Regex) -> Vec<FileMatches> {
+    WalkDir::new(root)
+        .follow_links(true)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let content = std::fs::read_to_string(e.path()).ok()?;
+            let lines: Vec<LineMatch> = content
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| pattern.is_match(line))
+                .map(|(i, line)| LineMatch {
+                    number: i + 1,
+                    text: line.to_string(),
+                })
+                .collect();
+            (!lines.is_empty()).then(|| FileMatches {
+                path: e.path().to_path_buf(),
+                lines,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_search_finds_matching_lines_in_order() {
+        let temp = tempdir().unwrap();
+        fs::create_dir_all(temp.path().join("b")).unwrap();
+        fs::write(temp.path().join("a.md"), "Run tests\nthen lint\n").unwrap();
+        fs::write(temp.path().join("b/SKILL.md"), "# Skill\nrun TESTS first\n").unwrap();
+        fs::write(temp.path().join("logo.png"), [0xff, 0xfe, 0x00]).unwrap();
+
+        let pattern = build_pattern("run tests", true, false).unwrap();
+        let found = search(temp.path(), &pattern);
+        let summary: Vec<(String, usize)> = found
+            .iter()
+            .flat_map(|f| {
+                let name = f.path.strip_prefix(temp.path()).unwrap();
+                let name = name.to_string_lossy().replace('\\', "/");
+                f.lines.iter().map(move |l| (name.clone(), l.number))
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("a.md".to_string(), 1), ("b/SKILL.md".to_string(), 2)]
+        );
+
+        // Fixed strings are not regular expressions
+        let literal = build_pattern("tests?", false, true).unwrap();
+        assert!(search(temp.path(), &literal).is_empty());
+        assert!(build_pattern("(", false, false).is_err());
+    }
+}
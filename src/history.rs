@@ -0,0 +1,289 @@
+//! Asset changelog from the lockfile's git history (`aps changelog`).
+//!
+//! Each commit that touched the lockfile is compared with the one before it,
+//! so the changelog lists when entries were added, upgraded to a new
+//! upstream commit, updated in place, moved to another source, or removed.
+
+use crate::error::{ApsError, Result};
+use crate::output::short_id;
+use crate::sources::{file_at_commit, log_commits};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Default file `aps changelog --write` writes, relative to the manifest
+pub const CHANGELOG_FILE: &str = "AGENT_ASSETS_CHANGELOG.md";
+
+/// What happened to an entry between two lockfile commits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetChange {
+    /// The entry first appeared, at an upstream commit for git sources
+    Added { id: String, commit: Option<String> },
+    /// The entry moved from one upstream commit to another
+    Upgraded {
+        id: String,
+        from: String,
+        to: String,
+    },
+    /// The entry now installs from a different source
+    SourceChanged {
+        id: String,
+        from: String,
+        to: String,
+    },
+    /// The installed content changed without a new upstream commit
+    Updated { id: String },
+    /// The entry was dropped from the lockfile
+    Removed { id: String },
+}
+
+/// A lockfile commit and the entry changes it made
+#[derive(Debug)]
+pub struct Release {
+    pub commit: String,
+    /// Committer date (RFC 3339)
+    pub date: String,
+    pub author: String,
+    pub subject: String,
+    pub changes: Vec<AssetChange>,
+}
+
+/// The fields of a past lockfile the changelog compares. Parsed leniently
+/// so lockfiles written by older versions still read.
+#[derive(Debug, Default, Deserialize)]
+struct Snapshot {
+    #[serde(default)]
+    entries: BTreeMap<String, SnapshotEntry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SnapshotEntry {
+    #[serde(default)]
+    source: serde_yaml::Value,
+    #[serde(default)]
+    commit: Option<String>,
+    #[serde(default)]
+    checksum: Option<String>,
+}
+
+impl SnapshotEntry {
+    /// Sources are a string, or a list of paths for composite entries
+    fn source(&self) -> String {
+        match &self.source {
+            serde_yaml::Value::String(source) => source.clone(),
+            serde_yaml::Value::Sequence(sources) => sources
+                .iter()
+                .filter_map(|s| s.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+            _ => String::new(),
+        }
+    }
+}
+
+/// Changes between two lockfile snapshots, by entry ID
+fn diff(old: &Snapshot, new: &Snapshot) -> Vec<AssetChange> {
+    let mut changes = Vec::new();
+    for (id, entry) in &new.entries {
+        let id = id.clone();
+        let Some(previous) = old.entries.get(&id) else {
+            changes.push(AssetChange::Added {
+                id,
+                commit: entry.commit.clone(),
+            });
+            continue;
+        };
+        if previous.source() != entry.source() {
+            changes.push(AssetChange::SourceChanged {
+                id,
+                from: previous.source(),
+                to: entry.source(),
+            });
+        } else if let (Some(from), Some(to)) = (&previous.commit, &entry.commit) {
+            if from != to {
+                changes.push(AssetChange::Upgraded {
+                    id,
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+        } else if previous.checksum != entry.checksum {
+            changes.push(AssetChange::Updated { id });
+        }
+    }
+    for id in old.entries.keys() {
+        if !new.entries.contains_key(id) {
+            changes.push(AssetChange::Removed { id: id.clone() });
+        }
+    }
+    changes
+}
+
+/// Entry changes recorded in the git history of `lockfile_path`, newest
+/// first. Commits that changed no entry are left out.
+pub fn build(lockfile_path: &Path) -> Result<Vec<Release>> {
+    let no_history = || ApsError::NoLockfileHistory {
+        path: lockfile_path.to_path_buf(),
+    };
+    let dir = lockfile_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let file = lockfile_path
+        .file_name()
+        .ok_or_else(no_history)?
+        .to_string_lossy()
+        .to_string();
+
+    let mut commits =
+        log_commits(dir, "HEAD", None, None, Some(&file)).map_err(|_| no_history())?;
+    if commits.is_empty() {
+        return Err(no_history());
+    }
+    commits.reverse();
+
+    let mut releases = Vec::new();
+    let mut previous = Snapshot::default();
+    for commit in commits {
+        // A revision that cannot be read changes nothing
+        let Some(snapshot) = file_at_commit(dir, &commit.sha, &file)
+            .and_then(|content| serde_yaml::from_str::<Snapshot>(&content).ok())
+        else {
+            continue;
+        };
+        let changes = diff(&previous, &snapshot);
+        previous = snapshot;
+        if changes.is_empty() {
+            continue;
+        }
+        releases.push(Release {
+            commit: commit.sha,
+            date: commit.date,
+            author: commit.author,
+            subject: commit.subject,
+            changes,
+        });
+    }
+    releases.reverse();
+    Ok(releases)
+}
+
+/// Render releases as a Markdown changelog
+pub fn render_markdown(releases: &[Release]) -> String {
+    let mut out = String::from("# Agent Assets Changelog\n");
+    if releases.is_empty() {
+        out.push_str("\nNo entry changes recorded yet.\n");
+    }
+    for release in releases {
+        let day = release.date.get(..10).unwrap_or(&release.date);
+        out.push_str(&format!("\n## {} - {}\n\n", day, release.subject));
+        out.push_str(&format!(
+            "Commit `{}` by {}.\n\n",
+            short_id(&release.commit),
+            release.author
+        ));
+        for change in &release.changes {
+            let line = match change {
+                AssetChange::Added {
+                    id,
+                    commit: Some(commit),
+                } => format!("Added `{}` at {}", id, short_id(commit)),
+                AssetChange::Added { id, commit: None } => format!("Added `{}`", id),
+                AssetChange::Upgraded { id, from, to } => {
+                    format!("Upgraded `{}`: {} → {}", id, short_id(from), short_id(to))
+                }
+                AssetChange::SourceChanged { id, from, to } => {
+                    format!("Changed the source of `{}`: {} → {}", id, from, to)
+                }
+                AssetChange::Updated { id } => format!("Updated `{}`", id),
+                AssetChange::Removed { id } => format!("Removed `{}`", id),
+            };
+            out.push_str(&format!("- {}\n", line));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lockfile_snapshots() {
+        let snapshot = |yaml: &str| serde_yaml::from_str::<Snapshot>(yaml).unwrap();
+        let old = snapshot(
+            r#"
+version: 1
+entries:
+  docs:
+    source: https://github.com/org/docs.git
+    dest: ./AGENTS.md
+    commit: aaaaaaaaaaaa
+    checksum: sha256:1
+  rules:
+    source: ./rules
+    dest: ./.cursor/rules
+    checksum: sha256:2
+  old:
+    source: ./old
+    dest: ./old
+    checksum: sha256:3
+  moved:
+    source: ./here
+    dest: ./moved
+    checksum: sha256:4
+"#,
+        );
+        let new = snapshot(
+            r#"
+version: 1
+entries:
+  docs:
+    source: https://github.com/org/docs.git
+    dest: ./AGENTS.md
+    commit: bbbbbbbbbbbb
+    checksum: sha256:5
+  rules:
+    source: ./rules
+    dest: ./.cursor/rules
+    checksum: sha256:6
+  moved:
+    source: ./there
+    dest: ./moved
+    checksum: sha256:4
+  skill:
+    source: https://github.com/org/skills.git
+    dest: ./.claude/skills/skill
+    commit: cccccccccccc
+    checksum: sha256:7
+"#,
+        );
+
+        assert_eq!(
+            diff(&old, &new),
+            vec![
+                AssetChange::Upgraded {
+                    id: "docs".to_string(),
+                    from: "aaaaaaaaaaaa".to_string(),
+                    to: "bbbbbbbbbbbb".to_string(),
+                },
+                AssetChange::SourceChanged {
+                    id: "moved".to_string(),
+                    from: "./here".to_string(),
+                    to: "./there".to_string(),
+                },
+                AssetChange::Updated {
+                    id: "rules".to_string()
+                },
+                AssetChange::Added {
+                    id: "skill".to_string(),
+                    commit: Some("cccccccccccc".to_string()),
+                },
+                AssetChange::Removed {
+                    id: "old".to_string()
+                },
+            ]
+        );
+        assert!(diff(&new, &new).is_empty());
+    }
+}
@@ -1,8 +1,81 @@
 use crate::error::{ApsError, Result};
+use serde_json::Value as JsonValue;
 use serde_yaml::Value;
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
+/// Outcome of merging a source hooks.json into the project's copy
+#[derive(Debug, Default, PartialEq)]
+pub struct HooksMergeReport {
+    /// Dotted paths of the values the source contributes
+    pub merged_keys: Vec<String>,
+    /// Dotted paths where the project's value differed from the source
+    pub conflicts: Vec<String>,
+}
+
+/// Merge a source hooks config into the project's config.
+///
+/// Objects are merged key by key and arrays (the hook lists) are unioned
+/// without duplicates. Where scalar values disagree, `prefer_source` decides
+/// which one is kept; either way the path is reported as a conflict.
+pub fn merge_hooks_config(
+    local: &mut JsonValue,
+    source: &JsonValue,
+    prefer_source: bool,
+) -> HooksMergeReport {
+    let mut report = HooksMergeReport::default();
+    merge_json_value(local, source, "", prefer_source, &mut report);
+    report
+}
+
+fn merge_json_value(
+    local: &mut JsonValue,
+    source: &JsonValue,
+    path: &str,
+    prefer_source: bool,
+    report: &mut HooksMergeReport,
+) {
+    match (local, source) {
+        (JsonValue::Object(local_map), JsonValue::Object(source_map)) => {
+            for (key, source_value) in source_map {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match local_map.get_mut(key) {
+                    Some(local_value) => {
+                        merge_json_value(local_value, source_value, &child, prefer_source, report)
+                    }
+                    None => {
+                        local_map.insert(key.clone(), source_value.clone());
+                        if !source_value.is_object() {
+                            report.merged_keys.push(child);
+                        }
+                    }
+                }
+            }
+        }
+        (JsonValue::Array(local_items), JsonValue::Array(source_items)) => {
+            for item in source_items {
+                if !local_items.contains(item) {
+                    local_items.push(item.clone());
+                }
+            }
+            report.merged_keys.push(path.to_string());
+        }
+        (local_value, source_value) => {
+            if local_value != source_value {
+                report.conflicts.push(path.to_string());
+                if prefer_source {
+                    *local_value = source_value.clone();
+                }
+            }
+            report.merged_keys.push(path.to_string());
+        }
+    }
+}
+
 pub fn validate_cursor_hooks(hooks_dir: &Path, strict: bool) -> Result<Vec<String>> {
     validate_hooks(hooks_dir, strict)
 }
@@ -62,7 +135,128 @@ fn validate_hooks(hooks_dir: &Path, strict: bool) -> Result<Vec<String>> {
     Ok(warnings)
 }
 
-fn hooks_root_dir(hooks_dir: &Path) -> PathBuf {
+/// A hook command from hooks.json
+#[derive(Debug, Clone, PartialEq)]
+pub struct HookPreview {
+    pub event: String,
+    pub command: String,
+    /// Scripts the command runs, relative to the hooks root
+    pub scripts: Vec<PathBuf>,
+}
+
+/// The hooks in a hooks directory's hooks.json, for `aps validate --show-hooks`
+#[derive(Debug, Default, PartialEq)]
+pub struct HooksPreview {
+    pub hooks: Vec<HookPreview>,
+    /// Structural problems and scripts referenced outside the hooks root
+    pub warnings: Vec<String>,
+}
+
+/// List each event and command in a hooks directory's hooks.json, checking
+/// that events map to lists of `{ "command": "..." }` objects and that
+/// scripts stay inside the synced directory. A missing or unreadable
+/// hooks.json yields an empty preview; [`validate_cursor_hooks`] reports it.
+pub fn preview_hooks(hooks_dir: &Path, strict: bool) -> Result<HooksPreview> {
+    let mut preview = HooksPreview::default();
+    let config_path = hooks_root_dir(hooks_dir).join("hooks.json");
+    let Ok(config) = read_hooks_config(&config_path) else {
+        return Ok(preview);
+    };
+    let invalid = |message: String| ApsError::InvalidHooksConfig {
+        path: config_path.clone(),
+        message,
+    };
+
+    if let Some(version) = config.get("version") {
+        if !version.is_u64() {
+            warn_or_error(
+                &mut preview.warnings,
+                strict,
+                invalid("'version' must be a number".to_string()),
+            )?;
+        }
+    }
+    let Some(Value::Mapping(events)) = get_hooks_section(&config) else {
+        return Ok(preview);
+    };
+
+    for (event, hooks) in events {
+        let event = event.as_str().unwrap_or_default().to_string();
+        let Value::Sequence(hooks) = hooks else {
+            let message = format!("'hooks.{}' must be a list of hooks", event);
+            warn_or_error(&mut preview.warnings, strict, invalid(message))?;
+            continue;
+        };
+        for hook in hooks {
+            let Some(command) = hook.get("command").and_then(|c| c.as_str()) else {
+                let message = format!("a hook in '{}' has no 'command' string", event);
+                warn_or_error(&mut preview.warnings, strict, invalid(message))?;
+                continue;
+            };
+
+            let mut scripts = Vec::new();
+            for token in command.split_whitespace() {
+                if let Some(rel_path) = extract_relative_path(token) {
+                    if escapes_root(&rel_path) {
+                        let error = ApsError::HookScriptOutside {
+                            event: event.clone(),
+                            path: trim_token(token).to_string(),
+                        };
+                        warn_or_error(&mut preview.warnings, strict, error)?;
+                    } else {
+                        scripts.push(PathBuf::from(rel_path));
+                    }
+                } else if is_outside_script(token) {
+                    let error = ApsError::HookScriptOutside {
+                        event: event.clone(),
+                        path: trim_token(token).to_string(),
+                    };
+                    warn_or_error(&mut preview.warnings, strict, error)?;
+                }
+            }
+            preview.hooks.push(HookPreview {
+                event: event.clone(),
+                command: command.to_string(),
+                scripts,
+            });
+        }
+    }
+
+    Ok(preview)
+}
+
+/// Whether a relative path climbs out of the directory it is relative to
+fn escapes_root(rel_path: &str) -> bool {
+    let mut depth = 0i32;
+    for part in rel_path.split(['/', '\\']) {
+        match part {
+            ".." => depth -= 1,
+            "" | "." => {}
+            _ => depth += 1,
+        }
+        if depth < 0 {
+            return true;
+        }
+    }
+    false
+}
+
+/// Whether a command argument is a script (a path ending in a file
+/// extension) that lives outside the project: absolute, in the home
+/// directory, behind an environment variable, or above the working directory
+fn is_outside_script(token: &str) -> bool {
+    let token = trim_token(token);
+    let Some(name) = token.rsplit(['/', '\\']).next() else {
+        return false;
+    };
+    if name == token || !name.contains('.') || name.starts_with('.') {
+        return false;
+    }
+    token.starts_with(['/', '~', '$']) || token.get(1..3) == Some(":\\") || escapes_root(token)
+}
+
+/// The directory hooks.json and the paths in its commands are relative to
+pub fn hooks_root_dir(hooks_dir: &Path) -> PathBuf {
     match hooks_dir.file_name().and_then(|name| name.to_str()) {
         Some("hooks") | Some("scripts") => hooks_dir.parent().unwrap_or(hooks_dir).to_path_buf(),
         _ => hooks_dir.to_path_buf(),
@@ -178,3 +372,106 @@ fn warn_or_error(warnings: &mut Vec<String>, strict: bool, error: ApsError) -> R
     warnings.push(error.to_string());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_hooks_config_unions_hook_arrays() {
+        let mut local = json!({
+            "version": 1,
+            "hooks": {
+                "onStart": [{ "command": "local.sh" }, { "command": "shared.sh" }],
+                "onStop": [{ "command": "stop.sh" }]
+            }
+        });
+        let source = json!({
+            "version": 1,
+            "hooks": {
+                "onStart": [{ "command": "shared.sh" }, { "command": "team.sh" }],
+                "onSave": [{ "command": "save.sh" }]
+            }
+        });
+
+        let report = merge_hooks_config(&mut local, &source, true);
+
+        assert_eq!(
+            local,
+            json!({
+                "version": 1,
+                "hooks": {
+                    "onStart": [
+                        { "command": "local.sh" },
+                        { "command": "shared.sh" },
+                        { "command": "team.sh" }
+                    ],
+                    "onStop": [{ "command": "stop.sh" }],
+                    "onSave": [{ "command": "save.sh" }]
+                }
+            })
+        );
+        assert_eq!(
+            report.merged_keys,
+            vec!["version", "hooks.onStart", "hooks.onSave"]
+        );
+        assert!(report.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_merge_hooks_config_reports_conflicts() {
+        let source = json!({ "version": 2 });
+
+        let mut local = json!({ "version": 1 });
+        let report = merge_hooks_config(&mut local, &source, true);
+        assert_eq!(local, json!({ "version": 2 }));
+        assert_eq!(report.conflicts, vec!["version"]);
+
+        let mut local = json!({ "version": 1 });
+        let report = merge_hooks_config(&mut local, &source, false);
+        assert_eq!(local, json!({ "version": 1 }));
+        assert_eq!(report.conflicts, vec!["version"]);
+    }
+
+    #[test]
+    fn test_preview_hooks_lists_commands_and_flags_outside_scripts() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("hooks.json"),
+            r#"{
+  "version": 1,
+  "hooks": {
+    "beforeShellExecution": [
+      { "command": "bash .cursor/hooks/check.sh --strict" },
+      { "command": "python3 ~/tools/audit.py" }
+    ],
+    "stop": [{ "cmd": "oops" }],
+    "afterFileEdit": { "command": "format.sh" }
+  }
+}"#,
+        )
+        .unwrap();
+
+        let preview = preview_hooks(dir.path(), false).unwrap();
+        assert_eq!(
+            preview.hooks[0],
+            HookPreview {
+                event: "beforeShellExecution".to_string(),
+                command: "bash .cursor/hooks/check.sh --strict".to_string(),
+                scripts: vec![PathBuf::from("hooks/check.sh")],
+            }
+        );
+        assert!(preview.hooks[1].scripts.is_empty());
+        assert_eq!(preview.warnings.len(), 3);
+        assert!(preview.warnings[0].contains("~/tools/audit.py"));
+        assert!(preview_hooks(dir.path(), true).is_err());
+
+        assert!(escapes_root("hooks/../../x.sh"));
+        assert!(!escapes_root("hooks/../scripts/x.sh"));
+        assert!(is_outside_script("../shared/run.sh"));
+        assert!(is_outside_script("$HOME/run.sh"));
+        assert!(!is_outside_script("/bin/bash"));
+        assert!(!is_outside_script("./run.sh"));
+    }
+}
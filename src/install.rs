@@ -1,16 +1,35 @@
-use crate::backup::{create_backup, has_conflict};
-use crate::checksum::{compute_source_checksum, compute_string_checksum};
+use crate::approval::{Approvals, Prompt};
+use crate::backup::{create_backup, has_conflict, BackupPolicy};
+use crate::cache::offline_clone;
+use crate::checksum::{compute_bytes_checksum, compute_source_checksum, compute_string_checksum};
+use crate::ci::interactive;
+use crate::cli::RefreshPolicy;
 use crate::compose::{
-    compose_markdown, read_source_file, write_composed_file, ComposeOptions, ComposedSource,
+    check_composition, compose_markdown, read_source_file, write_composed_file, ComposeOptions,
+    ComposedSource,
 };
 use crate::error::{ApsError, Result};
-use crate::hooks::validate_cursor_hooks;
+use crate::hooks::{merge_hooks_config, validate_cursor_hooks, HooksMergeReport};
+use crate::license::detect_license;
 use crate::lockfile::{LockedEntry, Lockfile};
-use crate::manifest::{AssetKind, Entry};
-use crate::sources::{clone_at_commit, get_remote_commit_sha, GitInfo, ResolvedSource};
-use dialoguer::Confirm;
-use std::io::IsTerminal;
-use std::path::{Path, PathBuf};
+use crate::manifest::{
+    detect_case_collisions, AssetKind, Entry, HooksMergeStrategy, Manifest, Source, SymlinkStyle,
+};
+use crate::output::{say, short_id};
+use crate::path_glob::{self, glob_base, is_glob, split_glob};
+use crate::path_guard::{ensure_within, find_escaping_links, safe_join};
+use crate::persona::validate_personas;
+use crate::sources::{
+    cached_url, clone_and_resolve, clone_at_commit, expand_path, fetch_url, get_remote_commit_sha,
+    http_cache_dir, render_generated, tree_oid, GitInfo, ResolvedGitSource, ResolvedSource,
+};
+use crate::staleness::remote_head;
+use crate::transform::{stage, transform_labels, StagedSource};
+use crate::warning::{Warning, WarningCode, WarningSet};
+use dialoguer::{Confirm, Select};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
 use tracing::{debug, info};
 use walkdir::WalkDir;
 
@@ -27,13 +46,28 @@ fn normalize_path(path: &Path) -> PathBuf {
 }
 
 /// Options for the install operation
+#[derive(Clone)]
 pub struct InstallOptions {
     pub dry_run: bool,
-    pub yes: bool,
+    /// Prompts answered by `--yes`
+    pub yes: Approvals,
     pub strict: bool,
     /// When true, fetch latest versions from sources (ignore locked versions)
     /// When false (default), respect locked versions from the lockfile
     pub upgrade: bool,
+    /// When true, install git sources only from the clone cache, without network access
+    pub offline: bool,
+    /// Whether and how conflicting content is backed up before it is
+    /// overwritten, with the entry's overrides applied
+    pub backup: BackupPolicy,
+    /// Warning codes suppressed for every entry
+    pub suppress_warnings: WarningSet,
+    /// Install copies even for sources that would be symlinked (the CI profile)
+    pub force_copy: bool,
+    /// When locked git entries check their remote for a newer commit
+    pub refresh: RefreshPolicy,
+    /// How symlinks refer to their sources, with the entry's override applied
+    pub symlink_style: SymlinkStyle,
 }
 
 /// Handle conflict detection and resolution for a destination path.
@@ -51,13 +85,13 @@ fn handle_conflict(
     info!("Conflict detected at {:?}", dest_path);
 
     if options.dry_run {
-        println!("[dry-run] Would backup and overwrite: {:?}", dest_path);
+        say!("[dry-run] Would backup and overwrite: {:?}", dest_path);
         return Ok(false);
     }
 
-    let should_overwrite = if options.yes {
+    let should_overwrite = if options.yes.approve(Prompt::Overwrite) {
         true
-    } else if std::io::stdin().is_terminal() {
+    } else if interactive() {
         Confirm::new()
             .with_prompt(format!("Overwrite existing content at {:?}?", dest_path))
             .default(false)
@@ -72,10 +106,7 @@ fn handle_conflict(
         return Err(ApsError::Cancelled);
     }
 
-    // Create backup
-    let backup_path = create_backup(manifest_dir, dest_path)?;
-    println!("Created backup at: {:?}", backup_path);
-
+    back_up_conflict(manifest_dir, dest_path, options)?;
     Ok(true)
 }
 
@@ -91,7 +122,7 @@ fn handle_partial_conflict(
     }
 
     if options.dry_run {
-        println!(
+        say!(
             "[dry-run] Would overwrite {} item(s) under {:?}",
             conflict_paths.len(),
             dest_path
@@ -99,9 +130,9 @@ fn handle_partial_conflict(
         return Ok(false);
     }
 
-    let should_overwrite = if options.yes {
+    let should_overwrite = if options.yes.approve(Prompt::Overwrite) {
         true
-    } else if std::io::stdin().is_terminal() {
+    } else if interactive() {
         Confirm::new()
             .with_prompt(format!(
                 "Overwrite {} existing item(s) under {:?}?",
@@ -121,13 +152,254 @@ fn handle_partial_conflict(
     }
 
     for path in conflict_paths {
-        let backup_path = create_backup(manifest_dir, path)?;
-        println!("Created backup at: {:?}", backup_path);
+        back_up_conflict(manifest_dir, path, options)?;
     }
 
     Ok(true)
 }
 
+/// Back up conflicting content before it is overwritten, or say that it is
+/// overwritten without a backup when backups are turned off
+fn back_up_conflict(manifest_dir: &Path, path: &Path, options: &InstallOptions) -> Result<()> {
+    match create_backup(manifest_dir, path, &options.backup)? {
+        Some(backup_path) => say!("Created backup at: {:?}", backup_path),
+        None => say!("Overwriting {:?} without a backup (backup: false)", path),
+    }
+    Ok(())
+}
+
+/// The warning for local changes that were backed up, or overwritten because
+/// backups are turned off
+fn local_changes_warning(
+    manifest_dir: &Path,
+    dest_path: &Path,
+    options: &InstallOptions,
+) -> Result<String> {
+    Ok(
+        match create_backup(manifest_dir, dest_path, &options.backup)? {
+            Some(backup_path) => format!("local changes backed up to {}", backup_path.display()),
+            None => "local changes overwritten without a backup (backup: false)".to_string(),
+        },
+    )
+}
+
+/// What to do with a destination edited since the last sync when its source
+/// has also changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LocalChangesChoice {
+    /// Back up the edited destination, then install the new source
+    BackUp,
+    /// Leave the destination alone and skip the update
+    Keep,
+    /// Install the new source, discarding the local edits
+    Overwrite,
+}
+
+/// Whether a copied destination was edited since the last sync, i.e. its
+/// content no longer matches the checksum recorded in the lockfile.
+///
+/// Only destinations aps copies wholesale are compared: symlinked installs,
+/// kinds merged into a shared directory, `include` subsets, and copied
+/// licenses all leave content at the destination that the source checksum
+/// does not cover. Neither is a destination last installed from an override,
+/// or one whose pin `aps update` has moved since.
+fn dest_locally_modified(entry: &Entry, dest_path: &Path, locked: &LockedEntry) -> bool {
+    if locked.is_symlink
+        || locked.dest_stale
+        || entry.kind.merges_into_dest()
+        || !entry.include.is_empty()
+        || entry.copy_license
+    {
+        return false;
+    }
+    match dest_path.symlink_metadata() {
+        Ok(meta) if !meta.file_type().is_symlink() => {}
+        _ => return false,
+    }
+    compute_source_checksum(dest_path, &entry.checksum_ignore)
+        .map(|checksum| checksum != locked.checksum)
+        .unwrap_or(false)
+}
+
+/// Checksum of the content installed at an entry's destination, for
+/// `aps pin --from-dest` to record in place of the synced checksum. Fails for
+/// destinations whose content can never match the lockfile on its own.
+pub fn installed_checksum(entry: &Entry, base_dir: &Path, locked: &LockedEntry) -> Result<String> {
+    let unsupported = |reason: &str| ApsError::PinUnsupported {
+        id: entry.id.clone(),
+        reason: reason.to_string(),
+    };
+    let dest_path = base_dir.join(&locked.dest);
+    match dest_path.symlink_metadata() {
+        Err(_) => return Err(unsupported("nothing is installed at its destination")),
+        Ok(meta) if locked.is_symlink || meta.file_type().is_symlink() => {
+            return Err(unsupported("its destination is a symlink to the source"));
+        }
+        Ok(_) => {}
+    }
+
+    if entry.is_composite() {
+        let content = std::fs::read(&dest_path)
+            .map_err(|e| ApsError::io(e, format!("Failed to read {:?}", dest_path)))?;
+        return Ok(compute_bytes_checksum(&content));
+    }
+    if entry.kind.merges_into_dest() {
+        return Err(unsupported(
+            "its content is merged into a destination shared with local files",
+        ));
+    }
+    if !entry.include.is_empty() {
+        return Err(unsupported(
+            "it installs only the paths listed in `include:`",
+        ));
+    }
+    if entry.copy_license {
+        return Err(unsupported(
+            "its destination also holds the source's license",
+        ));
+    }
+    compute_source_checksum(&dest_path, &entry.checksum_ignore)
+}
+
+/// On-disk state of an installed destination, as checked by `aps status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DestState {
+    /// Present and, where it can be compared, matching the lockfile
+    Ok,
+    /// Nothing exists at the destination
+    Missing,
+    /// A copied destination no longer matches its locked checksum
+    Modified,
+    /// The destination, or a symlink inside it, points at nothing
+    DanglingSymlink,
+}
+
+impl DestState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DestState::Ok => "ok",
+            DestState::Missing => "missing",
+            DestState::Modified => "modified",
+            DestState::DanglingSymlink => "dangling-symlink",
+        }
+    }
+
+    pub fn is_broken(&self) -> bool {
+        *self != DestState::Ok
+    }
+}
+
+impl std::fmt::Display for DestState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Check a locked entry's destination on disk. Without its manifest `entry`
+/// (it was removed since the last sync) edits cannot be told apart from
+/// intended content, so only missing and dangling destinations are detected.
+pub fn dest_state(entry: Option<&Entry>, base_dir: &Path, locked: &LockedEntry) -> DestState {
+    let dest_path = base_dir.join(&locked.dest);
+    if dest_path.symlink_metadata().is_err() {
+        return DestState::Missing;
+    }
+    if !dangling_symlinks(&dest_path).is_empty() {
+        return DestState::DanglingSymlink;
+    }
+    match entry {
+        Some(entry) if dest_locally_modified(entry, &dest_path, locked) => DestState::Modified,
+        _ => DestState::Ok,
+    }
+}
+
+/// Symlinks at or under `path` whose targets do not exist
+pub fn dangling_symlinks(path: &Path) -> Vec<PathBuf> {
+    WalkDir::new(path)
+        .follow_root_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path_is_symlink() && !e.path().exists())
+        .map(|e| e.into_path())
+        .collect()
+}
+
+/// Print the differences between a locally edited destination and the
+/// incoming source
+fn show_local_changes_diff(dest_path: &Path, source_path: &Path) {
+    let color = if crate::color::enabled() {
+        "--color=always"
+    } else {
+        "--color=never"
+    };
+    // `git diff --no-index` exits 1 when the paths differ, so only report
+    // failures to run it at all
+    let status = Command::new("git")
+        .args(["diff", "--no-index", color, "--"])
+        .arg(dest_path)
+        .arg(source_path)
+        .stdin(Stdio::null())
+        .status();
+    if let Err(e) = status {
+        debug!("Failed to run git diff: {}", e);
+    }
+}
+
+/// Ask how to handle a destination edited since the last sync when the
+/// source has also changed. Backing up is the default for `--yes` and
+/// dry runs; non-interactive runs without `--yes` fail rather than guess.
+fn resolve_local_changes(
+    entry_id: &str,
+    dest_path: &Path,
+    source_path: &Path,
+    options: &InstallOptions,
+) -> Result<LocalChangesChoice> {
+    info!("Local changes detected at {:?}", dest_path);
+
+    if options.dry_run {
+        say!(
+            "[dry-run] {:?} was edited since the last sync; would back it up and overwrite",
+            dest_path
+        );
+        return Ok(LocalChangesChoice::BackUp);
+    }
+
+    if options.yes.approve(Prompt::Overwrite) {
+        return Ok(LocalChangesChoice::BackUp);
+    }
+
+    if !interactive() {
+        return Err(ApsError::LocallyModified {
+            id: entry_id.to_string(),
+            path: dest_path.to_path_buf(),
+        });
+    }
+
+    say!(
+        "{:?} was edited since the last sync and '{}' has upstream changes:",
+        dest_path,
+        entry_id
+    );
+    show_local_changes_diff(dest_path, source_path);
+
+    let choices = [
+        LocalChangesChoice::BackUp,
+        LocalChangesChoice::Keep,
+        LocalChangesChoice::Overwrite,
+    ];
+    let selection = Select::new()
+        .with_prompt("How should the local changes be handled?")
+        .items(&[
+            "Back up local changes and install the update",
+            "Keep local changes and skip the update",
+            "Overwrite local changes",
+        ])
+        .default(0)
+        .interact()
+        .map_err(|_| ApsError::Cancelled)?;
+
+    Ok(choices[selection])
+}
+
 /// Result of an install operation
 pub struct InstallResult {
     pub id: String,
@@ -135,11 +407,15 @@ pub struct InstallResult {
     pub installed: bool,
     pub skipped_no_change: bool,
     pub locked_entry: Option<LockedEntry>,
-    pub warnings: Vec<String>,
+    pub warnings: Vec<Warning>,
     pub dest_path: PathBuf,
     pub was_symlink: bool,
     /// Whether a newer version is available (for git sources in locked mode)
     pub upgrade_available: Option<UpgradeInfo>,
+    /// Sources of a regenerated composite entry whose content changed
+    pub changed_sources: Vec<String>,
+    /// Whether the destination existed before this sync
+    pub dest_existed: bool,
 }
 
 /// Information about an available upgrade
@@ -149,6 +425,46 @@ pub struct UpgradeInfo {
     pub available_commit: String,
 }
 
+/// Fail when entries' destinations differ only in case and the project sits
+/// on a case-insensitive filesystem, where they would overwrite each other.
+/// On case-sensitive filesystems the collision is only a warning, reported by
+/// [`detect_overlapping_destinations`](crate::manifest::detect_overlapping_destinations).
+pub fn check_case_collisions(manifest: &Manifest, base_dir: &Path) -> Result<()> {
+    let collisions = detect_case_collisions(manifest);
+    let Some(collision) = collisions.first() else {
+        return Ok(());
+    };
+    if !is_case_insensitive_fs(base_dir) {
+        return Ok(());
+    }
+    Err(ApsError::CaseInsensitiveCollision {
+        ids: collision.ids.join(", "),
+        dests: collision.dests_display(),
+    })
+}
+
+/// Whether `dir` is on a case-insensitive filesystem, probed by creating a
+/// lowercase file and looking it up in uppercase. Unwritable directories are
+/// treated as case-sensitive.
+fn is_case_insensitive_fs(dir: &Path) -> bool {
+    let probe = match tempfile::Builder::new()
+        .prefix(".aps-case-probe-")
+        .tempfile_in(dir)
+    {
+        Ok(probe) => probe,
+        Err(e) => {
+            debug!("Could not probe case sensitivity of {:?}: {}", dir, e);
+            return false;
+        }
+    };
+    let name = probe
+        .path()
+        .file_name()
+        .map(|n| n.to_string_lossy().to_uppercase())
+        .unwrap_or_default();
+    dir.join(name).exists()
+}
+
 /// Install a single entry
 pub fn install_entry(
     entry: &Entry,
@@ -157,6 +473,10 @@ pub fn install_entry(
     options: &InstallOptions,
 ) -> Result<InstallResult> {
     info!("Processing entry: {}", entry.id);
+    let dest_existed = manifest_dir
+        .join(entry.destination())
+        .symlink_metadata()
+        .is_ok();
 
     // Get the source (required for non-composite entries)
     let source = entry
@@ -166,8 +486,11 @@ pub fn install_entry(
             id: entry.id.clone(),
         })?;
 
+    // Checksums of HTTP sources, recorded to detect upstream changes
+    let mut source_checksums = BTreeMap::new();
+
     // For git sources, handle locked vs upgrade mode
-    let resolved = if let Some((repo, git_ref)) = source.git_info() {
+    let mut resolved = if let Some((repo, git_ref)) = source.git_info() {
         let dest_path = manifest_dir.join(entry.destination());
         let locked_entry = lockfile.entries.get(&entry.id);
 
@@ -181,13 +504,18 @@ pub fn install_entry(
             let locked_ref = locked.resolved_ref.as_deref().unwrap_or("unknown");
 
             // Check if there's a newer version available on the remote
-            let upgrade_available = match get_remote_commit_sha(repo, git_ref) {
-                Ok(Some(remote_sha)) if remote_sha != *locked_commit => {
+            let remote_sha = if options.offline {
+                None
+            } else {
+                remote_head(manifest_dir, repo, git_ref, options.refresh)
+            };
+            let upgrade_available = match remote_sha {
+                Some(remote_sha) if remote_sha != *locked_commit => {
                     debug!(
                         "Upgrade available for {}: {} -> {}",
                         entry.id,
-                        &locked_commit[..8.min(locked_commit.len())],
-                        &remote_sha[..8.min(remote_sha.len())]
+                        short_id(locked_commit),
+                        short_id(&remote_sha)
                     );
                     Some(UpgradeInfo {
                         current_commit: locked_commit.clone(),
@@ -198,11 +526,14 @@ pub fn install_entry(
             };
 
             // If destination exists and commit matches, we're up to date
-            if dest_path.exists() {
+            if dest_path.exists()
+                && !locked.dest_stale
+                && locked.transforms == transform_labels(&entry.transform)
+            {
                 info!(
                     "Entry {} is up to date (using locked commit {})",
                     entry.id,
-                    &locked_commit[..8.min(locked_commit.len())]
+                    short_id(locked_commit)
                 );
                 let was_symlink = locked.is_symlink;
                 return Ok(InstallResult {
@@ -214,6 +545,8 @@ pub fn install_entry(
                     dest_path: dest_path.clone(),
                     was_symlink,
                     upgrade_available,
+                    changed_sources: Vec::new(),
+                    dest_existed,
                 });
             }
 
@@ -221,38 +554,63 @@ pub fn install_entry(
             info!(
                 "Installing {} from locked commit {}",
                 entry.id,
-                &locked_commit[..8.min(locked_commit.len())]
+                short_id(locked_commit)
             );
-            let resolved_git = clone_at_commit(repo, locked_commit, locked_ref)?;
+            let clone_from = if options.offline {
+                offline_clone(manifest_dir, &entry.id, repo, locked_commit)?
+                    .to_string_lossy()
+                    .to_string()
+            } else {
+                repo.to_string()
+            };
+            let sparse_path = source.git_path().map(expand_path);
+            let resolved_git = clone_at_commit(
+                &clone_from,
+                locked_commit,
+                locked_ref,
+                sparse_path.as_deref(),
+            )?;
 
             // Build the path within the cloned repo
             let path = source
                 .git_path()
-                .map(|p| p.to_string())
+                .map(glob_base)
                 .unwrap_or_else(|| ".".to_string());
             let source_path = if path == "." {
                 resolved_git.repo_path.clone()
             } else {
                 resolved_git.repo_path.join(&path)
             };
+            ensure_within(&resolved_git.repo_path, &source_path)?;
 
             let git_info = GitInfo {
                 resolved_ref: resolved_git.resolved_ref.clone(),
                 commit_sha: resolved_git.commit_sha.clone(),
+                commit_date: resolved_git.commit_date.clone(),
+                release: locked.release.clone(),
             };
 
+            let repo_path = resolved_git.repo_path.clone();
             ResolvedSource::git(source_path, repo.to_string(), git_info, resolved_git)
+                .with_root(repo_path)
+        } else if options.offline {
+            return Err(ApsError::OfflineUnavailable {
+                id: entry.id.clone(),
+                reason: "it has no locked commit".to_string(),
+            });
         } else {
             // Upgrade mode or no locked commit: check remote and clone latest
             // Fast-path: skip if remote commit matches lockfile and dest exists
             if dest_path.exists() {
                 debug!("Checking remote commit for {} ({})", repo, git_ref);
+                let transforms_match = locked_entry
+                    .is_some_and(|e| e.transforms == transform_labels(&entry.transform));
                 if let Ok(Some(remote_sha)) = get_remote_commit_sha(repo, git_ref) {
-                    if lockfile.commit_matches(&entry.id, &remote_sha) {
+                    if transforms_match && lockfile.commit_matches(&entry.id, &remote_sha) {
                         info!(
                             "Entry {} is up to date (commit {} unchanged)",
                             entry.id,
-                            &remote_sha[..8.min(remote_sha.len())]
+                            short_id(&remote_sha)
                         );
                         let was_symlink = lockfile
                             .entries
@@ -268,11 +626,13 @@ pub fn install_entry(
                             dest_path: dest_path.clone(),
                             was_symlink,
                             upgrade_available: None,
+                            changed_sources: Vec::new(),
+                            dest_existed,
                         });
                     }
                     debug!(
                         "Remote commit {} differs from lockfile, will clone latest",
-                        &remote_sha[..8.min(remote_sha.len())]
+                        short_id(&remote_sha)
                     );
                 }
             }
@@ -282,21 +642,60 @@ pub fn install_entry(
             adapter.resolve(manifest_dir)?
         }
     } else {
-        // Non-git source (filesystem): use adapter directly
-        let adapter = source.to_adapter();
-        adapter.resolve(manifest_dir)?
+        // Non-git source (filesystem or HTTP)
+        let locked = lockfile.entries.get(&entry.id).filter(|_| !options.upgrade);
+        let (resolved, checksums) =
+            resolve_single_source(entry, source, manifest_dir, locked, options.offline)?;
+        source_checksums = checksums;
+        resolved
     };
+    // A glob path resolves to the directory before its first glob segment
+    let glob = source_subpath(source)
+        .filter(|path| is_glob(path))
+        .map(split_glob);
     debug!("Source path: {:?}", resolved.source_path);
 
     // Verify source exists
     if !resolved.source_path.exists() {
-        return Err(ApsError::SourcePathNotFound {
-            path: resolved.source_path,
-        });
+        return Err(resolved.path_not_found());
+    }
+    let glob_matches = match &glob {
+        Some((_, pattern)) => {
+            let mut matches = path_glob::expand(
+                &entry.id,
+                &resolved.source_path,
+                pattern,
+                entry.kind == AssetKind::AgentSkill,
+            )?;
+            // `include` narrows the matches by name, as it does subdirectories
+            if !entry.include.is_empty() {
+                matches.retain(|m| {
+                    let name = m.file_name().unwrap_or_default().to_string_lossy();
+                    entry.include.iter().any(|prefix| name.starts_with(prefix))
+                });
+            }
+            Some(matches)
+        }
+        None => None,
+    };
+
+    if options.force_copy && resolved.use_symlink {
+        debug!("Installing {} as a copy instead of a symlink", entry.id);
+        resolved.use_symlink = false;
     }
 
-    // Compute checksum
-    let checksum = compute_source_checksum(&resolved.source_path)?;
+    // Install transformed entries from a staged copy; the original location
+    // still bounds symlink checks and license lookup
+    let upstream_path = resolved.source_path.clone();
+    let source_oid = source_tree_oid(&resolved);
+    let staged = stage_transforms(entry, &mut resolved)?;
+
+    // Compute checksum, unless the locked git tree is unchanged
+    let locked = lockfile.entries.get(&entry.id);
+    let checksum = match locked_tree_checksum(entry, source_oid.as_deref(), locked) {
+        Some(checksum) => checksum,
+        None => compute_source_checksum(&resolved.source_path, &entry.checksum_ignore)?,
+    };
     debug!("Source checksum: {}", checksum);
 
     // Resolve destination path
@@ -307,13 +706,28 @@ pub fn install_entry(
     if lockfile.checksum_matches(&entry.id, &checksum) {
         // Even with matching checksum, verify destination exists and symlink targets are correct
         let dest_valid = if let Some(locked_entry) = lockfile.entries.get(&entry.id) {
-            if locked_entry.is_symlink {
+            if locked_entry.is_symlink != resolved.use_symlink {
+                // Switching between a symlink and a copy reinstalls
+                false
+            } else if locked_entry.is_symlink {
                 // For symlinks, verify the symlink exists and points to the correct target
                 match dest_path.symlink_metadata() {
                     Ok(metadata) if metadata.file_type().is_symlink() => {
                         // Check if symlink target matches current source path
                         match std::fs::read_link(&dest_path) {
+                            Ok(current_target)
+                                if current_target.is_relative()
+                                    != (options.symlink_style == SymlinkStyle::Relative) =>
+                            {
+                                debug!("Symlink style changed for {:?}", dest_path);
+                                false
+                            }
                             Ok(current_target) => {
+                                // Relative targets resolve from the link's directory
+                                let current_target = match dest_path.parent() {
+                                    Some(parent) => parent.join(current_target),
+                                    None => current_target,
+                                };
                                 let expected_target = &resolved.source_path;
                                 // Canonicalize both paths for comparison (handle relative vs absolute)
                                 let current_canonical = current_target
@@ -362,6 +776,8 @@ pub fn install_entry(
                 dest_path: dest_path.clone(),
                 was_symlink,
                 upgrade_available: None,
+                changed_sources: Vec::new(),
+                dest_existed,
             });
         } else {
             debug!(
@@ -371,6 +787,26 @@ pub fn install_entry(
         }
     }
 
+    // A destination edited since the last sync is a real conflict when the
+    // source changed too: installing would silently discard the edits
+    let suppressed = options.suppress_warnings.with(&entry.suppress_warnings);
+    let mut warnings = Vec::new();
+    let locally_modified = lockfile.entries.get(&entry.id).is_some_and(|locked| {
+        locked.checksum != checksum && dest_locally_modified(entry, &dest_path, locked)
+    });
+    if locally_modified {
+        match resolve_local_changes(&entry.id, &dest_path, &resolved.source_path, options)? {
+            LocalChangesChoice::Keep => {
+                return Ok(kept_local_changes(entry, dest_path, suppressed));
+            }
+            LocalChangesChoice::BackUp if !options.dry_run => {
+                let message = local_changes_warning(manifest_dir, &dest_path, options)?;
+                warnings.extend(suppressed.coded(WarningCode::LocalChanges, vec![message]));
+            }
+            LocalChangesChoice::BackUp | LocalChangesChoice::Overwrite => {}
+        }
+    }
+
     // Check for conflicts
     // For directory assets (CursorRules, CursorSkillsRoot) using symlinks, we use
     // file-level symlinks which can coexist with other files in the directory.
@@ -381,20 +817,23 @@ pub fn install_entry(
         AssetKind::CursorRules
         | AssetKind::CursorHooks
         | AssetKind::CursorSkillsRoot
-        | AssetKind::AgentSkill => {
+        | AssetKind::AgentSkill
+        | AssetKind::Persona => {
             // For directory assets with symlinks, we add files to the directory
             // without backing up existing content from other sources
             !resolved.use_symlink
         }
     };
 
-    if should_check_conflict {
-        if matches!(entry.kind, AssetKind::CursorHooks) {
-            let mut conflicts = collect_hook_conflicts(&resolved.source_path, &dest_path)?;
+    if should_check_conflict && !locally_modified {
+        if entry.kind.merges_into_dest() {
+            let mut conflicts = collect_merge_conflicts(&resolved.source_path, &dest_path)?;
             if let Some((source_config, dest_config)) =
                 hooks_config_paths(&entry.kind, &resolved.source_path, &dest_path)?
             {
-                if source_config.exists()
+                // Merged configs are combined rather than overwritten
+                if entry.hooks_merge == HooksMergeStrategy::Replace
+                    && source_config.exists()
                     && dest_config.exists()
                     && !dest_config
                         .symlink_metadata()
@@ -428,21 +867,68 @@ pub fn install_entry(
         }
     }
 
-    // Validate skills if this is a skills root
-    let mut warnings = Vec::new();
+    // Validate skills if this is a skills root. Suppressed problems are not
+    // escalated to errors by --strict.
+    let strict = |code| options.strict && !suppressed.contains(code);
     if entry.kind == AssetKind::CursorSkillsRoot {
-        warnings.extend(validate_skills_root(&resolved.source_path, options.strict)?);
+        let code = WarningCode::MissingSkillMd;
+        let found = validate_skills_root(&resolved.source_path, strict(code))?;
+        warnings.extend(suppressed.coded(code, found));
     }
     if entry.kind == AssetKind::CursorHooks {
-        warnings.extend(validate_cursor_hooks(
-            &resolved.source_path,
-            options.strict,
-        )?);
+        let code = WarningCode::InvalidHooks;
+        let found = validate_cursor_hooks(&resolved.source_path, strict(code))?;
+        warnings.extend(suppressed.coded(code, found));
     }
+    if entry.kind == AssetKind::Persona {
+        let code = WarningCode::InvalidPersona;
+        let found = validate_personas(&resolved.source_path, strict(code))?;
+        warnings.extend(suppressed.coded(code, found));
+    }
+
+    // Copies must not dereference symlinks that lead out of the source
+    let escaping = if resolved.use_symlink {
+        Vec::new()
+    } else {
+        find_escaping_links(&upstream_path)
+    };
+    for link in &escaping {
+        if strict(WarningCode::EscapingSymlink) {
+            return Err(link.to_error());
+        }
+        info!(
+            "Security: {:?} links to {:?} outside the source; it will not be copied",
+            link.link, link.target
+        );
+        let relative = link.link.strip_prefix(&upstream_path).unwrap_or(&link.link);
+        warnings.extend(suppressed.coded(
+            WarningCode::EscapingSymlink,
+            vec![format!(
+                "Skipped {}: symlink points outside the source",
+                relative.display()
+            )],
+        ));
+    }
+    let skipped: Vec<PathBuf> = escaping.into_iter().map(|l| l.link).collect();
+
     for warning in &warnings {
-        println!("Warning: {}", warning);
+        say!("Warning: {}", warning);
     }
 
+    // Glob matches, or the `include` subset, installed one by one
+    let items = match &glob_matches {
+        Some(matches) => Some(
+            matches
+                .iter()
+                .map(|m| resolved.source_path.join(m))
+                .collect(),
+        ),
+        None if !entry.include.is_empty() => {
+            Some(filter_by_prefix(&resolved.source_path, &entry.include)?)
+        }
+        None => None,
+    };
+
     // Perform the install
     let symlinked_items = if options.dry_run {
         Vec::new()
@@ -452,26 +938,79 @@ pub fn install_entry(
             &resolved.source_path,
             &dest_path,
             resolved.use_symlink,
-            &entry.include,
+            options.symlink_style,
+            items.as_deref(),
+            CopyOptions::for_entry(entry).skipping(&skipped),
         )?
     };
 
+    let mut merge_report = HooksMergeReport::default();
     if !options.dry_run && matches!(entry.kind, AssetKind::CursorHooks) {
-        sync_hooks_config(
+        merge_report = sync_hooks_config(
             &entry.kind,
             &resolved.source_path,
             &dest_path,
             resolved.use_symlink,
+            options.symlink_style,
+            entry.hooks_merge,
         )?;
         if !resolved.use_symlink {
-            make_shell_scripts_executable(&dest_path)?;
+            make_scripts_executable(&dest_path)?;
+        }
+    }
+    let prefer = if entry.hooks_merge == HooksMergeStrategy::PreferLocal {
+        "project's"
+    } else {
+        "source"
+    };
+    let conflicts = merge_report
+        .conflicts
+        .iter()
+        .map(|key| {
+            format!(
+                "hooks.json '{}' differs from the project's copy; kept the {} value",
+                key, prefer
+            )
+        })
+        .collect();
+    for warning in suppressed.coded(WarningCode::HooksMergeConflict, conflicts) {
+        say!("Warning: {}", warning);
+        warnings.push(warning);
+    }
+
+    let license = detect_license(&upstream_path, resolved.root.as_deref());
+    if entry.copy_license && !options.dry_run {
+        match &license {
+            Some(license) => copy_license_file(&license.path, &dest_path)?,
+            None => {
+                let message = "copy_license is set but no upstream license file was found";
+                for warning in suppressed.coded(WarningCode::MissingLicense, vec![message.into()]) {
+                    say!("Warning: {}", warning);
+                    warnings.push(warning);
+                }
+            }
         }
     }
 
     // Create locked entry from resolved source
     // Store relative path in lockfile for portability across machines
     let relative_dest = entry.destination();
-    let locked_entry = resolved.to_locked_entry(&relative_dest, checksum, symlinked_items);
+    let mut locked_entry = resolved.to_locked_entry(&relative_dest, checksum, symlinked_items);
+    locked_entry.tree_oid = source_oid;
+    locked_entry.source_checksums = source_checksums;
+    if resolved.use_symlink && options.symlink_style == SymlinkStyle::Relative {
+        let target = relative_link_target(&resolved.source_path, &dest_path);
+        locked_entry.target_path = Some(target.to_string_lossy().to_string());
+    }
+    locked_entry.merged_keys = merge_report.merged_keys;
+    if let (Some((base, _)), Some(matches)) = (&glob, &glob_matches) {
+        locked_entry.expanded_paths = matches.iter().map(|m| expanded_path(base, m)).collect();
+    }
+    record_transforms(&mut locked_entry, entry, staged);
+    if let Some(license) = license {
+        locked_entry.license = Some(license.spdx);
+        locked_entry.license_file = Some(license.relative_path);
+    }
 
     Ok(InstallResult {
         id: entry.id.clone(),
@@ -482,12 +1021,174 @@ pub fn install_entry(
         dest_path,
         was_symlink: resolved.use_symlink,
         upgrade_available: None,
+        changed_sources: Vec::new(),
+        dest_existed,
     })
 }
 
+/// Git object ID of a git source's path at its resolved commit
+fn source_tree_oid(resolved: &ResolvedSource) -> Option<String> {
+    let git_info = resolved.git_info.as_ref()?;
+    let root = resolved.root.as_ref()?;
+    let path = resolved.source_path.strip_prefix(root).ok()?;
+    tree_oid(root, &git_info.commit_sha, path)
+}
+
+/// The locked checksum of an unchanged, untransformed entry whose git tree is
+/// the one already locked, so its files need not be hashed again
+fn locked_tree_checksum(
+    entry: &Entry,
+    source_oid: Option<&str>,
+    locked: Option<&LockedEntry>,
+) -> Option<String> {
+    let locked = locked?;
+    let unchanged = source_oid.is_some()
+        && entry.transform.is_empty()
+        && locked.tree_oid.as_deref() == source_oid
+        && locked.entry_checksum == Some(entry.checksum());
+    if unchanged {
+        debug!(
+            "Git tree for {} is unchanged; reusing its checksum",
+            entry.id
+        );
+    }
+    unchanged.then(|| locked.checksum.clone())
+}
+
+/// Point `resolved` at a staged copy of its source with the entry's transforms
+/// applied, installed as a copy. Returns the staged copy, which must outlive
+/// the install, and the checksum of the untransformed source.
+fn stage_transforms(
+    entry: &Entry,
+    resolved: &mut ResolvedSource,
+) -> Result<Option<(StagedSource, String)>> {
+    if entry.transform.is_empty() {
+        return Ok(None);
+    }
+    let upstream_checksum = compute_source_checksum(&resolved.source_path, &entry.checksum_ignore)?;
+    let staged = stage(&resolved.source_path, &entry.transform)?;
+    debug!("Staged transformed source at {:?}", staged.path);
+    resolved.source_path = staged.path.clone();
+    resolved.use_symlink = false;
+    Ok(Some((staged, upstream_checksum)))
+}
+
+/// Record the transforms applied to a staged source in its locked entry
+fn record_transforms(
+    locked: &mut LockedEntry,
+    entry: &Entry,
+    staged: Option<(StagedSource, String)>,
+) {
+    if let Some((_, upstream_checksum)) = staged {
+        locked.transforms = transform_labels(&entry.transform);
+        locked.upstream_checksum = Some(upstream_checksum);
+    }
+}
+
+/// Result for an entry whose local edits were kept over an upstream update.
+/// Nothing is locked, so the next sync asks again.
+fn kept_local_changes(entry: &Entry, dest_path: PathBuf, suppressed: WarningSet) -> InstallResult {
+    let message = "kept local changes; upstream update not applied".to_string();
+    let warnings = suppressed.coded(WarningCode::LocalChanges, vec![message]);
+    for warning in &warnings {
+        say!("Warning: {}", warning);
+    }
+    InstallResult {
+        id: entry.id.clone(),
+        installed: false,
+        skipped_no_change: true,
+        locked_entry: None,
+        warnings,
+        dest_path,
+        was_symlink: false,
+        upgrade_available: None,
+        changed_sources: Vec::new(),
+        dest_existed: true,
+    }
+}
+
+/// Copy an upstream license next to an installed asset: inside a directory
+/// destination, or as `<dest>.LICENSE` beside a single-file one
+fn copy_license_file(license_path: &Path, dest_path: &Path) -> Result<()> {
+    let target = if dest_path.is_dir() {
+        dest_path.join(license_path.file_name().unwrap_or_default())
+    } else {
+        let mut name = dest_path.as_os_str().to_os_string();
+        name.push(".LICENSE");
+        PathBuf::from(name)
+    };
+    // A license inside the source directory may already be installed as a symlink
+    if target.symlink_metadata().is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(license_path, &target)
+        .map_err(|e| ApsError::io(e, format!("Failed to copy license file to {:?}", target)))?;
+    debug!("Copied license {:?} to {:?}", license_path, target);
+    Ok(())
+}
+
+/// Point each `also_install_for` mirror at the installed destination with a
+/// relative symlink, copying instead where symlinks are unavailable.
+/// Returns the mirror paths for the lockfile.
+pub fn sync_mirrors(
+    entry: &Entry,
+    manifest_dir: &Path,
+    options: &InstallOptions,
+) -> Result<Vec<String>> {
+    let dest = entry.destination();
+    let dest_path = manifest_dir.join(&dest);
+    let mut mirrors = Vec::new();
+
+    for mirror in entry.mirror_destinations()? {
+        let mirror_path = manifest_dir.join(&mirror);
+        mirrors.push(mirror.to_string_lossy().to_string());
+
+        // Climb from the mirror's directory to the project root, then down to dest
+        let depth = mirror.components().count().saturating_sub(1);
+        let mut target: PathBuf = std::iter::repeat_n(Path::new(".."), depth).collect();
+        target.extend(dest.components().filter(|c| *c != Component::CurDir));
+
+        if std::fs::read_link(&mirror_path).is_ok_and(|current| current == target) {
+            continue;
+        }
+        if options.dry_run {
+            say!(
+                "[dry-run] Would mirror {} to {}",
+                dest.display(),
+                mirror.display()
+            );
+            continue;
+        }
+        let is_symlink = mirror_path
+            .symlink_metadata()
+            .map(|m| m.file_type().is_symlink())
+            .unwrap_or(false);
+        if !is_symlink {
+            handle_conflict(&mirror_path, manifest_dir, options)?;
+        }
+
+        // The mirror target is already relative to the mirror's directory
+        if let Err(e) = create_symlink(&target, &mirror_path, SymlinkStyle::Absolute) {
+            debug!(
+                "Symlinking mirror {:?} failed ({}), copying",
+                mirror_path, e
+            );
+            if dest_path.is_dir() {
+                copy_directory(&dest_path, &mirror_path, CopyOptions::for_entry(entry))?;
+            } else {
+                copy_file(&dest_path, &mirror_path, CopyOptions::for_entry(entry))?;
+            }
+        }
+        info!("Mirrored {:?} to {:?}", dest, mirror);
+    }
+
+    Ok(mirrors)
+}
+
 /// Install a composite entry (merge multiple sources into one file)
 pub fn install_composite_entry(
     entry: &Entry,
+    manifest: &Manifest,
     manifest_dir: &Path,
     lockfile: &Lockfile,
     options: &InstallOptions,
@@ -500,35 +1201,26 @@ pub fn install_composite_entry(
         });
     }
 
-    // Resolve all sources and collect their content
-    let mut composed_sources: Vec<ComposedSource> = Vec::new();
-    let mut all_checksums: Vec<String> = Vec::new();
-
-    for source in &entry.sources {
-        let adapter = source.to_adapter();
-        let resolved = adapter.resolve(manifest_dir)?;
-
-        if !resolved.source_path.exists() {
-            return Err(ApsError::SourcePathNotFound {
-                path: resolved.source_path,
-            });
-        }
-
-        // Read the source file
-        let composed_source = read_source_file(&resolved.source_path)?;
-        composed_sources.push(composed_source);
-
-        // Compute and collect checksum for this source
-        let source_checksum = compute_source_checksum(&resolved.source_path)?;
-        all_checksums.push(source_checksum);
+    // Git sources stay at their locked commits, and HTTP sources at their
+    // locked content, unless upgrading
+    let locked = lockfile.entries.get(&entry.id).filter(|_| !options.upgrade);
+    let Composition {
+        content: composed_content,
+        source_checksums,
+        source_commits,
+        problems,
+    } = compose_entry_sources(entry, manifest, manifest_dir, locked, options.offline)?;
+
+    // Oversized or badly structured output is reported on every sync
+    let suppressed = options.suppress_warnings.with(&entry.suppress_warnings);
+    let code = WarningCode::ComposeLimitExceeded;
+    if options.strict && !problems.is_empty() && !suppressed.contains(code) {
+        return Err(ApsError::ComposeLimitExceeded {
+            id: entry.id.clone(),
+            problems: problems.join("; "),
+        });
     }
-
-    // Compose all sources into one markdown string
-    let compose_options = ComposeOptions {
-        add_separators: false,
-        include_source_info: false,
-    };
-    let composed_content = compose_markdown(&composed_sources, &compose_options)?;
+    let mut warnings = suppressed.coded(code, problems);
 
     // Compute checksum of the final composed content
     let checksum = compute_string_checksum(&composed_content);
@@ -537,6 +1229,7 @@ pub fn install_composite_entry(
     // Resolve destination path
     let dest_path = manifest_dir.join(entry.destination());
     debug!("Destination path: {:?}", dest_path);
+    let dest_existed = dest_path.symlink_metadata().is_ok();
 
     // Check if content is unchanged
     if lockfile.checksum_matches(&entry.id, &checksum) && dest_path.exists() {
@@ -549,22 +1242,53 @@ pub fn install_composite_entry(
             installed: false,
             skipped_no_change: true,
             locked_entry: None,
-            warnings: Vec::new(),
+            warnings,
             dest_path: dest_path.clone(),
             was_symlink: false,
             upgrade_available: None,
+            changed_sources: Vec::new(),
+            dest_existed,
         });
     }
 
-    // Check for conflicts and handle backup if needed
-    handle_conflict(&dest_path, manifest_dir, options)?;
+    // Compare the composed file against the content that was last written
+    let locally_modified = match lockfile.entries.get(&entry.id) {
+        Some(locked) if dest_path.is_file() => std::fs::read(&dest_path)
+            .map(|content| compute_bytes_checksum(&content) != locked.checksum)
+            .unwrap_or(false),
+        _ => false,
+    };
+    if locally_modified {
+        let incoming = tempfile::NamedTempFile::new()
+            .and_then(|mut file| {
+                std::io::Write::write_all(&mut file, composed_content.as_bytes())?;
+                Ok(file)
+            })
+            .map_err(|e| ApsError::io(e, "Failed to stage composed content"))?;
+        match resolve_local_changes(&entry.id, &dest_path, incoming.path(), options)? {
+            LocalChangesChoice::Keep => {
+                return Ok(kept_local_changes(entry, dest_path, suppressed));
+            }
+            LocalChangesChoice::BackUp if !options.dry_run => {
+                let message = local_changes_warning(manifest_dir, &dest_path, options)?;
+                for warning in suppressed.coded(WarningCode::LocalChanges, vec![message]) {
+                    say!("Warning: {}", warning);
+                    warnings.push(warning);
+                }
+            }
+            LocalChangesChoice::BackUp | LocalChangesChoice::Overwrite => {}
+        }
+    } else {
+        // Check for conflicts and handle backup if needed
+        handle_conflict(&dest_path, manifest_dir, options)?;
+    }
 
     // Write the composed file
     if !options.dry_run {
         write_composed_file(&composed_content, &dest_path)?;
         info!("Wrote composed file to {:?}", dest_path);
     } else {
-        println!("[dry-run] Would write composed file to {:?}", dest_path);
+        say!("[dry-run] Would write composed file to {:?}", dest_path);
     }
 
     // Create locked entry with original source paths (preserving shell variables like $HOME)
@@ -572,28 +1296,346 @@ pub fn install_composite_entry(
     let source_paths: Vec<String> = entry.sources.iter().map(|s| s.display_path()).collect();
     let relative_dest = entry.destination();
 
-    let locked_entry =
+    let mut locked_entry =
         LockedEntry::new_composite(source_paths, &relative_dest.to_string_lossy(), checksum);
+    // Name the partials behind the regeneration, where the lockfile knows them
+    let changed_sources: Vec<String> = match lockfile.entries.get(&entry.id) {
+        Some(locked) => source_checksums
+            .iter()
+            .filter(|(source, checksum)| {
+                locked
+                    .source_checksums
+                    .get(*source)
+                    .is_some_and(|old| old != *checksum)
+            })
+            .map(|(source, _)| source.clone())
+            .collect(),
+        None => Vec::new(),
+    };
+    locked_entry.source_checksums = source_checksums;
+    locked_entry.source_commits = source_commits;
 
     Ok(InstallResult {
         id: entry.id.clone(),
         installed: !options.dry_run,
         skipped_no_change: false,
         locked_entry: Some(locked_entry),
-        warnings: Vec::new(),
+        warnings,
         dest_path,
         was_symlink: false,
         upgrade_available: None,
+        changed_sources,
+        dest_existed,
+    })
+}
+
+/// A composite entry's sources composed into one markdown string
+pub struct Composition {
+    pub content: String,
+    /// Checksums of each source's content, keyed by its display path
+    pub source_checksums: BTreeMap<String, String>,
+    /// Commits of git sources, keyed by [`LockedEntry::source_commit_key`]
+    pub source_commits: BTreeMap<String, String>,
+    /// Ways the composed content breaks the manifest's `compose_limits`
+    pub problems: Vec<String>,
+}
+
+/// The body of an HTTP source, revalidated against the server or, offline,
+/// taken from the cache
+fn http_body(
+    entry: &Entry,
+    url: &str,
+    headers: &BTreeMap<String, String>,
+    manifest_dir: &Path,
+    offline: bool,
+) -> Result<PathBuf> {
+    let cache_dir = http_cache_dir(manifest_dir);
+    if offline {
+        cached_url(url, &cache_dir).ok_or_else(|| ApsError::OfflineUnavailable {
+            id: entry.id.clone(),
+            reason: format!("{} is not in the HTTP cache", url),
+        })
+    } else {
+        fetch_url(url, headers, &cache_dir)
+    }
+}
+
+/// Fail when an HTTP source's content no longer matches its checksum in `locked`
+fn verify_http_checksum(url: &str, locked: Option<&LockedEntry>, actual: &str) -> Result<()> {
+    match locked.and_then(|l| l.source_checksums.get(url)) {
+        Some(locked_checksum) if locked_checksum != actual => Err(ApsError::HttpChecksumMismatch {
+            url: url.to_string(),
+            locked: locked_checksum.clone(),
+            actual: actual.to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Resolve a non-composite entry's source. An HTTP body must match its locked
+/// checksum unless `locked` is `None`; its checksum is returned by URL.
+fn resolve_single_source(
+    entry: &Entry,
+    source: &Source,
+    manifest_dir: &Path,
+    locked: Option<&LockedEntry>,
+    offline: bool,
+) -> Result<(ResolvedSource, BTreeMap<String, String>)> {
+    let mut source_checksums = BTreeMap::new();
+    let resolved = match source {
+        Source::Http { url, headers } => {
+            let body = http_body(entry, url, headers, manifest_dir, offline)?;
+            let content = std::fs::read(&body)
+                .map_err(|e| ApsError::io(e, format!("Failed to read {:?}", body)))?;
+            let checksum = compute_bytes_checksum(&content);
+            verify_http_checksum(url, locked, &checksum)?;
+            source_checksums.insert(url.clone(), checksum);
+            ResolvedSource::http(body, url.clone())
+        }
+        _ => source.to_adapter().resolve(manifest_dir)?,
+    };
+    Ok((resolved, source_checksums))
+}
+
+/// Resolve a composite entry's sources and compose them into one markdown string.
+/// Generated sources are rendered from the manifest's installed assets; HTTP
+/// sources come from the fetch cache, which `offline` uses without revalidating.
+/// Git sources sharing a repo and ref are read from one clone, checked out at
+/// the commit in `locked` when there is one and at the ref's tip otherwise.
+/// HTTP sources must still match their checksum in `locked`. Pass `None` to
+/// follow every source to its latest content.
+pub fn compose_entry_sources(
+    entry: &Entry,
+    manifest: &Manifest,
+    manifest_dir: &Path,
+    locked: Option<&LockedEntry>,
+    offline: bool,
+) -> Result<Composition> {
+    let empty = BTreeMap::new();
+    let pinned = locked.map_or(&empty, |l| &l.source_commits);
+    // Resolve all sources and collect their content
+    let mut composed_sources: Vec<ComposedSource> = Vec::new();
+    let mut source_checksums = BTreeMap::new();
+    let mut source_commits = BTreeMap::new();
+    let mut clones: HashMap<String, ResolvedGitSource> = HashMap::new();
+
+    for source in &entry.sources {
+        let composed_source = match source {
+            Source::Generated { generator } => ComposedSource {
+                content: render_generated(*generator, manifest, manifest_dir),
+                label: generator.as_str().to_string(),
+                ..Default::default()
+            },
+            Source::Http { url, headers } => {
+                let body = http_body(entry, url, headers, manifest_dir, offline)?;
+                let composed = read_source_file(&body)?;
+                verify_http_checksum(url, locked, &compute_string_checksum(&composed.content))?;
+                composed
+            }
+            Source::Git {
+                repo,
+                r#ref,
+                shallow,
+                path,
+            } => {
+                let key = LockedEntry::source_commit_key(repo, r#ref);
+                if !clones.contains_key(&key) {
+                    let clone = match pinned.get(&key) {
+                        Some(commit) => {
+                            let clone_from = if offline {
+                                offline_clone(manifest_dir, &entry.id, repo, commit)?
+                                    .to_string_lossy()
+                                    .to_string()
+                            } else {
+                                repo.clone()
+                            };
+                            clone_at_commit(&clone_from, commit, r#ref, None)?
+                        }
+                        None if offline => {
+                            return Err(ApsError::OfflineUnavailable {
+                                id: entry.id.clone(),
+                                reason: format!("{} has no locked commit", repo),
+                            });
+                        }
+                        // Sources of one repository share a clone, so it is never sparse
+                        None => clone_and_resolve(repo, r#ref, *shallow, None)?,
+                    };
+                    source_commits.insert(key.clone(), clone.commit_sha.clone());
+                    clones.insert(key.clone(), clone);
+                }
+                let clone = &clones[&key];
+                let source_path = match path {
+                    Some(path) => clone.repo_path.join(expand_path(path)),
+                    None => clone.repo_path.clone(),
+                };
+                ensure_within(&clone.repo_path, &source_path)?;
+                if !source_path.exists() {
+                    return Err(ApsError::source_path_not_found(
+                        source_path,
+                        Some(&clone.repo_path),
+                        Some(&clone.resolved_ref),
+                    ));
+                }
+                read_source_file(&source_path)?
+            }
+            Source::Filesystem { .. } | Source::Aps { .. } => {
+                let resolved = source.to_adapter().resolve(manifest_dir)?;
+                if !resolved.source_path.exists() {
+                    return Err(resolved.path_not_found());
+                }
+                read_source_file(&resolved.source_path)?
+            }
+        };
+        source_checksums.insert(
+            source.display_path(),
+            compute_string_checksum(&composed_source.content),
+        );
+        composed_sources.push(composed_source);
+    }
+
+    // Compose all sources into one markdown string
+    let compose_options = ComposeOptions {
+        add_separators: false,
+        include_source_info: false,
+    };
+    let content = compose_markdown(&composed_sources, &compose_options)?;
+    let partials: Vec<(String, &str)> = entry
+        .sources
+        .iter()
+        .zip(&composed_sources)
+        .map(|(source, composed)| (source.display_path(), composed.content.as_str()))
+        .collect();
+    let problems = check_composition(&partials, &content, &manifest.compose_limits);
+    Ok(Composition {
+        content,
+        source_checksums,
+        source_commits,
+        problems,
     })
 }
 
-/// Install an asset based on its kind
+/// Result of resolving an entry for the lockfile without installing it
+pub struct LockResult {
+    pub id: String,
+    pub locked_entry: LockedEntry,
+    /// Whether the locked entry differs from the existing lockfile
+    pub changed: bool,
+}
+
+/// Resolve an entry and compute its locked state without touching its destination.
+///
+/// Git sources keep their locked commit unless `upgrade` is set, mirroring `aps sync`.
+pub fn lock_entry(
+    entry: &Entry,
+    manifest: &Manifest,
+    manifest_dir: &Path,
+    lockfile: &Lockfile,
+    upgrade: bool,
+) -> Result<LockResult> {
+    info!("Locking entry: {}", entry.id);
+    let existing = lockfile.entries.get(&entry.id);
+    let relative_dest = entry.destination();
+
+    let mut locked_entry = if entry.is_composite() {
+        let locked = existing.filter(|_| !upgrade);
+        let composition = compose_entry_sources(entry, manifest, manifest_dir, locked, false)?;
+        let checksum = compute_string_checksum(&composition.content);
+        let source_paths: Vec<String> = entry.sources.iter().map(|s| s.display_path()).collect();
+        let mut locked =
+            LockedEntry::new_composite(source_paths, &relative_dest.to_string_lossy(), checksum);
+        locked.source_checksums = composition.source_checksums;
+        locked.source_commits = composition.source_commits;
+        locked
+    } else {
+        let source = entry
+            .source
+            .as_ref()
+            .ok_or_else(|| ApsError::EntryRequiresSource {
+                id: entry.id.clone(),
+            })?;
+
+        let locked_git = existing.filter(|e| source.git_info().is_some() && e.commit.is_some());
+        if let (Some(locked), false) = (locked_git, upgrade) {
+            // Already pinned; nothing to resolve
+            return Ok(LockResult {
+                id: entry.id.clone(),
+                locked_entry: locked.clone(),
+                changed: false,
+            });
+        }
+
+        let locked = existing.filter(|_| !upgrade);
+        let (mut resolved, source_checksums) =
+            resolve_single_source(entry, source, manifest_dir, locked, false)?;
+        if !resolved.source_path.exists() {
+            return Err(resolved.path_not_found());
+        }
+        let source_oid = source_tree_oid(&resolved);
+        let staged = stage_transforms(entry, &mut resolved)?;
+        let checksum = match locked_tree_checksum(entry, source_oid.as_deref(), existing) {
+            Some(checksum) => checksum,
+            None => compute_source_checksum(&resolved.source_path, &entry.checksum_ignore)?,
+        };
+        // Symlinked items are only known once installed; carry over what was recorded
+        let symlinked_items = existing
+            .map(|e| e.symlinked_items.clone())
+            .unwrap_or_default();
+        let mut locked = resolved.to_locked_entry(&relative_dest, checksum, symlinked_items);
+        locked.tree_oid = source_oid;
+        locked.source_checksums = source_checksums;
+        record_transforms(&mut locked, entry, staged);
+        locked
+    };
+
+    // The last sync outcome still describes what is installed
+    if let Some(existing) = existing {
+        locked_entry.last_status = existing.last_status;
+        locked_entry.last_error = existing.last_error.clone();
+    }
+
+    let changed = existing
+        .map(|e| {
+            e.checksum != locked_entry.checksum
+                || e.commit != locked_entry.commit
+                || e.dest != locked_entry.dest
+        })
+        .unwrap_or(true);
+
+    Ok(LockResult {
+        id: entry.id.clone(),
+        locked_entry,
+        changed,
+    })
+}
+
+/// The `path` of a git or filesystem source
+fn source_subpath(source: &Source) -> Option<&str> {
+    match source {
+        Source::Git { path, .. } | Source::Filesystem { path, .. } => path.as_deref(),
+        Source::Http { .. } | Source::Aps { .. } | Source::Generated { .. } => None,
+    }
+}
+
+/// A glob match as recorded in the lockfile: its path in the source
+fn expanded_path(base: &str, matched: &Path) -> String {
+    let matched = matched.to_string_lossy().replace('\\', "/");
+    if base == "." {
+        matched
+    } else {
+        format!("{}/{}", base, matched)
+    }
+}
+
+/// Install an asset based on its kind. With `items`, only those paths are
+/// installed, each under `dest` by its name.
 fn install_asset(
     kind: &AssetKind,
     source: &Path,
     dest: &Path,
     use_symlink: bool,
-    include: &[String],
+    style: SymlinkStyle,
+    items: Option<&[PathBuf]>,
+    copy: CopyOptions<'_>,
 ) -> Result<Vec<String>> {
     // Track symlinked items for lockfile
     let mut symlinked_items = Vec::new();
@@ -610,13 +1652,11 @@ fn install_asset(
         AssetKind::AgentsMd => {
             // Single file
             if use_symlink {
-                create_symlink(source, dest)?;
+                create_symlink(source, dest, style)?;
                 symlinked_items.push(source.to_string_lossy().to_string());
                 debug!("Symlinked file {:?} to {:?}", source, dest);
             } else {
-                std::fs::copy(source, dest).map_err(|e| {
-                    ApsError::io(e, format!("Failed to copy {:?} to {:?}", source, dest))
-                })?;
+                copy_file(source, dest, copy)?;
                 debug!("Copied file {:?} to {:?}", source, dest);
             }
         }
@@ -630,16 +1670,17 @@ fn install_asset(
         AssetKind::CursorRules
         | AssetKind::CursorHooks
         | AssetKind::CursorSkillsRoot
-        | AssetKind::AgentSkill => {
+        | AssetKind::AgentSkill
+        | AssetKind::Persona => {
             if use_symlink {
-                if include.is_empty() {
+                if items.is_none() {
                     // Symlink individual files (not the directory itself)
                     // This allows multiple sources to contribute to the same dest
-                    symlink_directory_files(source, dest, &mut symlinked_items)?;
+                    symlink_directory_files(source, dest, style, &mut symlinked_items)?;
                     debug!("Symlinked directory files from {:?} to {:?}", source, dest);
                 } else {
                     // Filter and symlink individual items
-                    let items = filter_by_prefix(source, include)?;
+                    let items = items.unwrap_or_default();
 
                     // Ensure dest directory exists for individual symlinks
                     if !dest.exists() {
@@ -659,15 +1700,15 @@ fn install_asset(
                             )
                         })?;
                         let item_dest = dest.join(item_name);
-                        create_symlink(&item, &item_dest)?;
+                        create_symlink(item, &item_dest, style)?;
                         symlinked_items.push(item.to_string_lossy().to_string());
                         debug!("Symlinked {:?} to {:?}", item, item_dest);
                     }
                 }
             } else {
                 // Copy behavior
-                if include.is_empty() {
-                    if matches!(kind, AssetKind::CursorHooks) {
+                if items.is_none() {
+                    if kind.merges_into_dest() {
                         if dest.exists() {
                             let meta = dest.symlink_metadata().map_err(|e| {
                                 ApsError::io(e, format!("Failed to read metadata for {:?}", dest))
@@ -681,16 +1722,16 @@ fn install_asset(
                         std::fs::create_dir_all(dest).map_err(|e| {
                             ApsError::io(e, format!("Failed to create directory {:?}", dest))
                         })?;
-                        copy_directory_merge(source, dest)?;
+                        copy_directory_merge(source, dest, copy)?;
                     } else {
-                        copy_directory(source, dest)?;
+                        copy_directory(source, dest, copy)?;
                     }
                 } else {
                     // Filter and copy individual items
-                    let items = filter_by_prefix(source, include)?;
+                    let items = items.unwrap_or_default();
 
                     // Ensure dest exists
-                    if matches!(kind, AssetKind::CursorHooks) {
+                    if kind.merges_into_dest() {
                         if dest.exists() {
                             let meta = dest.symlink_metadata().map_err(|e| {
                                 ApsError::io(e, format!("Failed to read metadata for {:?}", dest))
@@ -718,7 +1759,7 @@ fn install_asset(
                         })?;
                     }
 
-                    for item in items {
+                    for item in items.iter().filter(|i| !copy.is_skipped(i)) {
                         let item_name = item.file_name().ok_or_else(|| {
                             ApsError::io(
                                 std::io::Error::new(
@@ -730,10 +1771,10 @@ fn install_asset(
                         })?;
                         let item_dest = dest.join(item_name);
                         if item.is_dir() {
-                            if matches!(kind, AssetKind::CursorHooks) {
-                                copy_directory_merge(&item, &item_dest)?;
+                            if kind.merges_into_dest() {
+                                copy_directory_merge(item, &item_dest, copy)?;
                             } else {
-                                copy_directory(&item, &item_dest)?;
+                                copy_directory(item, &item_dest, copy)?;
                             }
                         } else {
                             if item_dest.exists() {
@@ -759,9 +1800,7 @@ fn install_asset(
                                     })?;
                                 }
                             }
-                            std::fs::copy(&item, &item_dest).map_err(|e| {
-                                ApsError::io(e, format!("Failed to copy {:?}", item))
-                            })?;
+                            copy_file(item, &item_dest, copy)?;
                         }
                     }
                 }
@@ -776,6 +1815,7 @@ fn install_asset(
 fn symlink_directory_files(
     source: &Path,
     dest: &Path,
+    style: SymlinkStyle,
     symlinked_items: &mut Vec<String>,
 ) -> Result<()> {
     // Create destination directory if it doesn't exist
@@ -794,10 +1834,10 @@ fn symlink_directory_files(
 
         if entry_path.is_dir() {
             // Recurse into subdirectory (create real directory at dest)
-            symlink_directory_files(&entry_path, &dest_path, symlinked_items)?;
+            symlink_directory_files(&entry_path, &dest_path, style, symlinked_items)?;
         } else {
             // Symlink individual file
-            create_symlink(&entry_path, &dest_path)?;
+            create_symlink(&entry_path, &dest_path, style)?;
             symlinked_items.push(entry_path.to_string_lossy().to_string());
             debug!("Symlinked file {:?} to {:?}", entry_path, dest_path);
         }
@@ -830,9 +1870,37 @@ fn filter_by_prefix(source_dir: &Path, prefixes: &[String]) -> Result<Vec<PathBu
     Ok(matches)
 }
 
+/// The path from `dest`'s directory to `source`, for a relative symlink.
+/// Falls back to `source` when the two share no common root (e.g. different
+/// drives) or `dest`'s directory cannot be resolved.
+fn relative_link_target(source: &Path, dest: &Path) -> PathBuf {
+    // Resolve directories only, so a source that is itself a symlink is
+    // linked to rather than followed
+    let resolve = |path: &Path| match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => parent.canonicalize().ok().map(|p| p.join(name)),
+        _ => None,
+    };
+    let (Some(source), Some(dest)) = (resolve(source), resolve(dest)) else {
+        return source.to_path_buf();
+    };
+    let dest_dir = dest.parent().unwrap_or(&dest);
+    let common = source
+        .components()
+        .zip(dest_dir.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 0 {
+        return source;
+    }
+    let mut target: PathBuf =
+        std::iter::repeat_n(Path::new(".."), dest_dir.components().count() - common).collect();
+    target.extend(source.components().skip(common));
+    target
+}
+
 /// Create a symbolic link (platform-specific)
 #[cfg(unix)]
-fn create_symlink(source: &Path, dest: &Path) -> Result<()> {
+fn create_symlink(source: &Path, dest: &Path, style: SymlinkStyle) -> Result<()> {
     // Normalize paths to handle trailing slashes
     let dest = normalize_path(dest);
     let source = normalize_path(source);
@@ -846,6 +1914,11 @@ fn create_symlink(source: &Path, dest: &Path) -> Result<()> {
         }
     }
 
+    let source = match style {
+        SymlinkStyle::Absolute => source,
+        SymlinkStyle::Relative => relative_link_target(&source, &dest),
+    };
+
     // Remove existing destination if present
     if dest.exists() || dest.symlink_metadata().is_ok() {
         if dest.is_dir()
@@ -873,7 +1946,7 @@ fn create_symlink(source: &Path, dest: &Path) -> Result<()> {
 }
 
 #[cfg(windows)]
-fn create_symlink(source: &Path, dest: &Path) -> Result<()> {
+fn create_symlink(source: &Path, dest: &Path, style: SymlinkStyle) -> Result<()> {
     // Normalize paths to handle trailing slashes
     let dest = normalize_path(dest);
     let source = normalize_path(source);
@@ -887,6 +1960,12 @@ fn create_symlink(source: &Path, dest: &Path) -> Result<()> {
         }
     }
 
+    let source_is_dir = source.is_dir();
+    let source = match style {
+        SymlinkStyle::Absolute => source,
+        SymlinkStyle::Relative => relative_link_target(&source, &dest),
+    };
+
     // Remove existing destination if present
     if dest.exists() {
         if dest.is_dir() {
@@ -898,7 +1977,7 @@ fn create_symlink(source: &Path, dest: &Path) -> Result<()> {
         }
     }
 
-    if source.is_dir() {
+    if source_is_dir {
         std::os::windows::fs::symlink_dir(&source, &dest).map_err(|e| {
             ApsError::io(
                 e,
@@ -951,8 +2030,71 @@ fn validate_skills_root(source: &Path, strict: bool) -> Result<Vec<String>> {
     Ok(warnings)
 }
 
+/// How copied files inherit metadata from their source
+#[derive(Debug, Clone, Copy)]
+struct CopyOptions<'a> {
+    /// Keep source file modes; otherwise files get the umask default
+    preserve_permissions: bool,
+    /// Keep source modification times
+    preserve_mtime: bool,
+    /// Source paths (and everything under them) left out of the copy
+    skip: &'a [PathBuf],
+}
+
+impl<'a> CopyOptions<'a> {
+    fn for_entry(entry: &Entry) -> Self {
+        Self {
+            preserve_permissions: entry.preserve_permissions,
+            preserve_mtime: entry.preserve_mtime,
+            skip: &[],
+        }
+    }
+
+    fn skipping(self, skip: &'a [PathBuf]) -> Self {
+        Self { skip, ..self }
+    }
+
+    fn is_skipped(&self, path: &Path) -> bool {
+        self.skip.iter().any(|s| path.starts_with(s))
+    }
+}
+
+/// Copy a single file, applying the entry's permission and mtime policy
+fn copy_file(src: &Path, dst: &Path, copy: CopyOptions<'_>) -> Result<()> {
+    if copy.preserve_permissions {
+        // std::fs::copy carries the source permission bits over
+        std::fs::copy(src, dst)
+            .map_err(|e| ApsError::io(e, format!("Failed to copy {:?} to {:?}", src, dst)))?;
+    } else {
+        // Recreate the file so it is created with the process umask applied
+        if dst.symlink_metadata().is_ok() {
+            std::fs::remove_file(dst)
+                .map_err(|e| ApsError::io(e, format!("Failed to remove file {:?}", dst)))?;
+        }
+        let mut reader = std::fs::File::open(src)
+            .map_err(|e| ApsError::io(e, format!("Failed to open {:?}", src)))?;
+        let mut writer = std::fs::File::create(dst)
+            .map_err(|e| ApsError::io(e, format!("Failed to create {:?}", dst)))?;
+        std::io::copy(&mut reader, &mut writer)
+            .map_err(|e| ApsError::io(e, format!("Failed to copy {:?} to {:?}", src, dst)))?;
+    }
+
+    if copy.preserve_mtime {
+        let modified = std::fs::metadata(src)
+            .and_then(|m| m.modified())
+            .map_err(|e| ApsError::io(e, format!("Failed to read metadata for {:?}", src)))?;
+        std::fs::File::options()
+            .write(true)
+            .open(dst)
+            .and_then(|f| f.set_modified(modified))
+            .map_err(|e| ApsError::io(e, format!("Failed to set modified time for {:?}", dst)))?;
+    }
+
+    Ok(())
+}
+
 /// Copy a directory recursively
-fn copy_directory(src: &Path, dst: &Path) -> Result<()> {
+fn copy_directory(src: &Path, dst: &Path, copy: CopyOptions<'_>) -> Result<()> {
     // Normalize paths to handle trailing slashes
     let src = normalize_path(src);
     let dst = normalize_path(dst);
@@ -981,12 +2123,14 @@ fn copy_directory(src: &Path, dst: &Path) -> Result<()> {
         let entry = entry.map_err(|e| ApsError::io(e, "Failed to read directory entry"))?;
         let src_path = entry.path();
         let dst_path = dst.join(entry.file_name());
+        if copy.is_skipped(&src_path) {
+            continue;
+        }
 
         if src_path.is_dir() {
-            copy_directory(&src_path, &dst_path)?;
+            copy_directory(&src_path, &dst_path, copy)?;
         } else {
-            std::fs::copy(&src_path, &dst_path)
-                .map_err(|e| ApsError::io(e, format!("Failed to copy {:?}", src_path)))?;
+            copy_file(&src_path, &dst_path, copy)?;
         }
     }
 
@@ -998,7 +2142,7 @@ fn copy_directory(src: &Path, dst: &Path) -> Result<()> {
 ///
 /// Overwrites destination entries that conflict with source entries while
 /// preserving other destination content.
-fn copy_directory_merge(src: &Path, dst: &Path) -> Result<()> {
+fn copy_directory_merge(src: &Path, dst: &Path, copy: CopyOptions<'_>) -> Result<()> {
     // Normalize paths to handle trailing slashes
     let src = normalize_path(src);
     let dst = normalize_path(dst);
@@ -1008,7 +2152,11 @@ fn copy_directory_merge(src: &Path, dst: &Path) -> Result<()> {
             .map_err(|e| ApsError::io(e, format!("Failed to create directory {:?}", dst)))?;
     }
 
-    for entry in WalkDir::new(&src).follow_links(true) {
+    for entry in WalkDir::new(&src)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|e| !copy.is_skipped(e.path()))
+    {
         let entry = entry.map_err(|e| {
             ApsError::io(
                 std::io::Error::other(e),
@@ -1025,7 +2173,7 @@ fn copy_directory_merge(src: &Path, dst: &Path) -> Result<()> {
         if rel.as_os_str().is_empty() {
             continue;
         }
-        let dest_path = dst.join(rel);
+        let dest_path = safe_join(&dst, rel)?;
 
         if entry.file_type().is_dir() {
             if dest_path.exists() {
@@ -1063,8 +2211,7 @@ fn copy_directory_merge(src: &Path, dst: &Path) -> Result<()> {
                     })?;
                 }
             }
-            std::fs::copy(path, &dest_path)
-                .map_err(|e| ApsError::io(e, format!("Failed to copy {:?}", path)))?;
+            copy_file(path, &dest_path, copy)?;
         }
     }
 
@@ -1072,8 +2219,10 @@ fn copy_directory_merge(src: &Path, dst: &Path) -> Result<()> {
     Ok(())
 }
 
-/// Make all .sh scripts under a directory executable (recursive).
-fn make_shell_scripts_executable(dir: &Path) -> Result<()> {
+/// Make all scripts under a directory executable (recursive).
+///
+/// Scripts are `.sh` files and extensionless files starting with a shebang.
+fn make_scripts_executable(dir: &Path) -> Result<()> {
     if !dir.exists() {
         return Ok(());
     }
@@ -1092,7 +2241,7 @@ fn make_shell_scripts_executable(dir: &Path) -> Result<()> {
             if !entry.file_type().is_file() {
                 continue;
             }
-            if entry.path().extension().and_then(|ext| ext.to_str()) != Some("sh") {
+            if !is_script(entry.path()) {
                 continue;
             }
 
@@ -1122,6 +2271,22 @@ fn make_shell_scripts_executable(dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Check whether a file is a `.sh` script or an extensionless file with a shebang
+#[cfg(unix)]
+fn is_script(path: &Path) -> bool {
+    use std::io::Read;
+    match path.extension() {
+        Some(ext) => ext == "sh",
+        None => {
+            let mut magic = [0u8; 2];
+            std::fs::File::open(path)
+                .and_then(|mut f| f.read_exact(&mut magic))
+                .map(|_| &magic == b"#!")
+                .unwrap_or(false)
+        }
+    }
+}
+
 fn hooks_config_paths(
     kind: &AssetKind,
     source_hooks_dir: &Path,
@@ -1149,20 +2314,26 @@ fn hooks_config_paths(
     )))
 }
 
+/// Install the hooks.json that sits beside the hooks directory.
+///
+/// Copied configs are merged into the project's existing hooks.json unless the
+/// entry uses the `replace` strategy.
 fn sync_hooks_config(
     kind: &AssetKind,
     source_hooks_dir: &Path,
     dest_hooks_dir: &Path,
     use_symlink: bool,
-) -> Result<()> {
+    style: SymlinkStyle,
+    strategy: HooksMergeStrategy,
+) -> Result<HooksMergeReport> {
     let Some((source_config, dest_config)) =
         hooks_config_paths(kind, source_hooks_dir, dest_hooks_dir)?
     else {
-        return Ok(());
+        return Ok(HooksMergeReport::default());
     };
 
     if !source_config.exists() {
-        return Ok(());
+        return Ok(HooksMergeReport::default());
     }
 
     if let Some(parent) = dest_config.parent() {
@@ -1173,8 +2344,8 @@ fn sync_hooks_config(
     }
 
     if use_symlink {
-        create_symlink(&source_config, &dest_config)?;
-        return Ok(());
+        create_symlink(&source_config, &dest_config, style)?;
+        return Ok(HooksMergeReport::default());
     }
 
     if dest_config.exists() {
@@ -1191,6 +2362,10 @@ fn sync_hooks_config(
         }
     }
 
+    if strategy != HooksMergeStrategy::Replace {
+        return merge_hooks_config_file(&source_config, &dest_config, strategy);
+    }
+
     std::fs::copy(&source_config, &dest_config).map_err(|e| {
         ApsError::io(
             e,
@@ -1198,10 +2373,50 @@ fn sync_hooks_config(
         )
     })?;
 
-    Ok(())
+    Ok(HooksMergeReport::default())
+}
+
+/// Merge the source hooks.json into the project's hooks.json in place.
+fn merge_hooks_config_file(
+    source_config: &Path,
+    dest_config: &Path,
+    strategy: HooksMergeStrategy,
+) -> Result<HooksMergeReport> {
+    let read_json = |path: &Path| -> Result<serde_json::Value> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ApsError::io(e, format!("Failed to read {:?}", path)))?;
+        serde_json::from_str(&content).map_err(|e| ApsError::InvalidHooksConfig {
+            path: path.to_path_buf(),
+            message: format!("{} (set `hooks_merge: replace` to overwrite it)", e),
+        })
+    };
+
+    let source = read_json(source_config)?;
+    let mut merged = if dest_config.is_file() {
+        read_json(dest_config)?
+    } else {
+        serde_json::Value::Object(serde_json::Map::new())
+    };
+    let report = merge_hooks_config(
+        &mut merged,
+        &source,
+        strategy != HooksMergeStrategy::PreferLocal,
+    );
+
+    let mut content =
+        serde_json::to_string_pretty(&merged).map_err(|e| ApsError::InvalidHooksConfig {
+            path: dest_config.to_path_buf(),
+            message: e.to_string(),
+        })?;
+    content.push('\n');
+    std::fs::write(dest_config, content)
+        .map_err(|e| ApsError::io(e, format!("Failed to write {:?}", dest_config)))?;
+
+    debug!("Merged {:?} into {:?}", source_config, dest_config);
+    Ok(report)
 }
 
-fn collect_hook_conflicts(source: &Path, dest: &Path) -> Result<Vec<PathBuf>> {
+fn collect_merge_conflicts(source: &Path, dest: &Path) -> Result<Vec<PathBuf>> {
     let mut conflicts = Vec::new();
 
     for entry in WalkDir::new(source).follow_links(true) {
@@ -0,0 +1,220 @@
+//! Editor integrations applied after sync.
+//!
+//! When `integrations: cursor: true` is set in the manifest, synced rules and
+//! skills directories are registered in `.vscode/settings.json`, which both
+//! Cursor and VS Code read. Locations added by aps are tracked under
+//! `aps.managedLocations` so they can be updated or removed on later syncs
+//! without touching settings the user added by hand.
+
+use crate::error::{ApsError, Result};
+use crate::manifest::{AssetKind, Manifest};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Workspace settings file, relative to the manifest directory
+pub const EDITOR_SETTINGS_PATH: &str = ".vscode/settings.json";
+
+/// Setting listing directories of rule/instruction files
+const INSTRUCTIONS_SETTING: &str = "chat.instructionsFilesLocations";
+
+/// Setting listing directories of agent skills
+const SKILLS_SETTING: &str = "chat.agentSkillsLocations";
+
+/// Setting recording which locations aps manages
+const MANAGED_SETTING: &str = "aps.managedLocations";
+
+/// Register synced directories with the editor, if enabled in the manifest.
+///
+/// Returns the settings path when it was written.
+pub fn apply_editor_integrations(manifest: &Manifest, base_dir: &Path) -> Result<Option<PathBuf>> {
+    if !manifest.integrations.cursor {
+        return Ok(None);
+    }
+
+    let settings_path = base_dir.join(EDITOR_SETTINGS_PATH);
+    let mut settings = read_settings(&settings_path)?;
+    let (instructions, skills) = editor_locations(manifest);
+
+    if !update_settings(&mut settings, &instructions, &skills) {
+        return Ok(None);
+    }
+
+    if let Some(parent) = settings_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| ApsError::io(e, format!("Failed to create directory {:?}", parent)))?;
+    }
+    let mut content = serde_json::to_string_pretty(&Value::Object(settings)).map_err(|e| {
+        ApsError::InvalidInput {
+            message: format!("Failed to serialize editor settings: {}", e),
+        }
+    })?;
+    content.push('\n');
+    std::fs::write(&settings_path, content)
+        .map_err(|e| ApsError::io(e, format!("Failed to write {:?}", settings_path)))?;
+
+    info!("Updated editor settings at {:?}", settings_path);
+    Ok(Some(settings_path))
+}
+
+/// Read existing settings, refusing to rewrite files aps cannot parse
+/// (e.g., settings with comments) rather than discarding their content.
+fn read_settings(path: &Path) -> Result<Map<String, Value>> {
+    if !path.exists() {
+        return Ok(Map::new());
+    }
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ApsError::io(e, format!("Failed to read {:?}", path)))?;
+    if content.trim().is_empty() {
+        return Ok(Map::new());
+    }
+    match serde_json::from_str(&content) {
+        Ok(Value::Object(map)) => Ok(map),
+        Ok(_) => Err(ApsError::InvalidInput {
+            message: format!("{:?} must contain a JSON object", path),
+        }),
+        Err(e) => Err(ApsError::InvalidInput {
+            message: format!(
+                "Cannot update {:?}: {} (remove comments or trailing commas, or disable `integrations.cursor`)",
+                path, e
+            ),
+        }),
+    }
+}
+
+/// Collect (instruction dirs, skill dirs) from the manifest, in manifest order.
+fn editor_locations(manifest: &Manifest) -> (Vec<String>, Vec<String>) {
+    let mut instructions = Vec::new();
+    let mut skills = Vec::new();
+
+    for entry in &manifest.entries {
+        let dest = entry.destination();
+        let location = match entry.kind {
+            AssetKind::CursorRules => Some((&mut instructions, dest)),
+            AssetKind::CursorSkillsRoot => Some((&mut skills, dest)),
+            // Individual skills register the directory that holds them
+            AssetKind::AgentSkill => dest
+                .parent()
+                .map(|p| p.to_path_buf())
+                .map(|parent| (&mut skills, parent)),
+            AssetKind::CursorHooks
+            | AssetKind::AgentsMd
+            | AssetKind::CompositeAgentsMd
+            | AssetKind::Persona => None,
+        };
+
+        if let Some((list, path)) = location {
+            let path = normalize_location(&path);
+            if !path.is_empty() && !list.contains(&path) {
+                list.push(path);
+            }
+        }
+    }
+
+    (instructions, skills)
+}
+
+fn normalize_location(path: &Path) -> String {
+    let s = path.to_string_lossy();
+    let s = s.strip_prefix("./").unwrap_or(&s);
+    s.trim_end_matches('/').to_string()
+}
+
+/// Replace aps-managed locations in `settings`. Returns true if anything changed.
+fn update_settings(
+    settings: &mut Map<String, Value>,
+    instructions: &[String],
+    skills: &[String],
+) -> bool {
+    let before = settings.clone();
+
+    let previously_managed: Vec<String> = settings
+        .get(MANAGED_SETTING)
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    for (key, locations) in [
+        (INSTRUCTIONS_SETTING, instructions),
+        (SKILLS_SETTING, skills),
+    ] {
+        let value = settings
+            .entry(key)
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !value.is_object() {
+            *value = Value::Object(Map::new());
+        }
+        let map = value.as_object_mut().expect("setting is an object");
+        for old in &previously_managed {
+            map.remove(old);
+        }
+        for location in locations {
+            map.insert(location.clone(), Value::Bool(true));
+        }
+        if map.is_empty() {
+            settings.remove(key);
+        }
+    }
+
+    let managed: Vec<Value> = instructions
+        .iter()
+        .chain(skills)
+        .map(|l| Value::String(l.clone()))
+        .collect();
+    if managed.is_empty() {
+        settings.remove(MANAGED_SETTING);
+    } else {
+        settings.insert(MANAGED_SETTING.to_string(), Value::Array(managed));
+    }
+
+    *settings != before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_update_settings_preserves_user_locations() {
+        let mut settings = json!({
+            "editor.tabSize": 2,
+            "chat.instructionsFilesLocations": { "docs/rules": true, ".cursor/old": true },
+            "aps.managedLocations": [".cursor/old"]
+        })
+        .as_object()
+        .unwrap()
+        .clone();
+
+        let changed = update_settings(
+            &mut settings,
+            &[".cursor/rules".to_string()],
+            &[".claude/skills".to_string()],
+        );
+
+        assert!(changed);
+        assert_eq!(
+            Value::Object(settings),
+            json!({
+                "editor.tabSize": 2,
+                "chat.instructionsFilesLocations": { "docs/rules": true, ".cursor/rules": true },
+                "aps.managedLocations": [".cursor/rules", ".claude/skills"],
+                "chat.agentSkillsLocations": { ".claude/skills": true }
+            })
+        );
+    }
+
+    #[test]
+    fn test_update_settings_is_idempotent() {
+        let mut settings = Map::new();
+        let rules = [".cursor/rules".to_string()];
+        assert!(update_settings(&mut settings, &rules, &[]));
+        assert!(!update_settings(&mut settings, &rules, &[]));
+        assert!(!settings.contains_key(SKILLS_SETTING));
+    }
+}
@@ -0,0 +1,183 @@
+//! Upstream license detection.
+//!
+//! Synced assets often come from third-party repositories. The nearest
+//! `LICENSE*`/`LICENCE*`/`COPYING*` file is found by searching from the synced
+//! path up to the source root, and its text is matched against well-known
+//! licenses to guess an SPDX identifier.
+
+use std::path::{Path, PathBuf};
+
+/// Identifier recorded when a license file exists but is not recognized
+pub const UNKNOWN_LICENSE: &str = "unknown";
+
+/// A license file found for a source
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedLicense {
+    /// Path to the license file
+    pub path: PathBuf,
+    /// Path relative to the source root, for display and the lockfile
+    pub relative_path: String,
+    /// SPDX identifier guessed from the text, or `unknown`
+    pub spdx: String,
+}
+
+/// Find the license that applies to `source_path`, searching its directory
+/// and then each parent up to and including `root`
+pub fn detect_license(source_path: &Path, root: Option<&Path>) -> Option<DetectedLicense> {
+    let start = if source_path.is_dir() {
+        source_path
+    } else {
+        source_path.parent()?
+    };
+    let root = root.unwrap_or(start);
+    if !start.starts_with(root) {
+        return None;
+    }
+
+    let mut dir = start;
+    loop {
+        if let Some(path) = license_file_in(dir) {
+            let relative_path = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            let text = std::fs::read_to_string(&path).unwrap_or_default();
+            let spdx = guess_spdx(&text).unwrap_or(UNKNOWN_LICENSE).to_string();
+            return Some(DetectedLicense {
+                path,
+                relative_path,
+                spdx,
+            });
+        }
+        if dir == root {
+            return None;
+        }
+        dir = dir.parent()?;
+    }
+}
+
+/// The license file directly inside `dir`, preferring the shortest name
+/// (`LICENSE` over `LICENSE-APACHE`)
+fn license_file_in(dir: &Path) -> Option<PathBuf> {
+    let mut candidates: Vec<PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .filter(|p| {
+            let name = p
+                .file_name()
+                .map(|n| n.to_string_lossy().to_ascii_uppercase())
+                .unwrap_or_default();
+            ["LICENSE", "LICENCE", "COPYING"]
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+        })
+        .collect();
+    candidates.sort_by_key(|p| (p.as_os_str().len(), p.clone()));
+    candidates.into_iter().next()
+}
+
+/// Guess an SPDX identifier from license text
+pub fn guess_spdx(text: &str) -> Option<&'static str> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let has = |needle: &str| text.contains(needle);
+
+    let spdx = if has("Apache License") && has("Version 2.0") {
+        "Apache-2.0"
+    } else if has("Permission is hereby granted, free of charge") {
+        "MIT"
+    } else if has("GNU LESSER GENERAL PUBLIC LICENSE") {
+        if has("Version 3") {
+            "LGPL-3.0"
+        } else {
+            "LGPL-2.1"
+        }
+    } else if has("GNU AFFERO GENERAL PUBLIC LICENSE") {
+        "AGPL-3.0"
+    } else if has("GNU GENERAL PUBLIC LICENSE") {
+        if has("Version 3") {
+            "GPL-3.0"
+        } else {
+            "GPL-2.0"
+        }
+    } else if has("Mozilla Public License") && has("2.0") {
+        "MPL-2.0"
+    } else if has("Redistribution and use in source and binary forms") {
+        if has("Neither the name") || has("endorse or promote") {
+            "BSD-3-Clause"
+        } else {
+            "BSD-2-Clause"
+        }
+    } else if has("Permission to use, copy, modify, and/or distribute this software") {
+        "ISC"
+    } else if has("This is free and unencumbered software released into the public domain") {
+        "Unlicense"
+    } else if has("CC0 1.0 Universal") {
+        "CC0-1.0"
+    } else if has("Attribution-ShareAlike 4.0") {
+        "CC-BY-SA-4.0"
+    } else if has("Attribution 4.0 International") {
+        "CC-BY-4.0"
+    } else {
+        return None;
+    };
+    Some(spdx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_guess_spdx_common_licenses() {
+        assert_eq!(
+            guess_spdx(
+                "MIT License\n\nPermission is hereby granted, free of\n charge, to any person"
+            ),
+            Some("MIT")
+        );
+        assert_eq!(
+            guess_spdx("Apache License\n Version 2.0, January 2004"),
+            Some("Apache-2.0")
+        );
+        assert_eq!(
+            guess_spdx("Redistribution and use in source and binary forms ... Neither the name"),
+            Some("BSD-3-Clause")
+        );
+        assert_eq!(guess_spdx("All rights reserved."), None);
+    }
+
+    #[test]
+    fn test_detect_license_searches_up_to_root() {
+        let repo = TempDir::new().unwrap();
+        let skill = repo.path().join("skills/pdf");
+        std::fs::create_dir_all(&skill).unwrap();
+        std::fs::write(skill.join("SKILL.md"), "# PDF").unwrap();
+        std::fs::write(
+            repo.path().join("LICENSE"),
+            "Permission is hereby granted, free of charge",
+        )
+        .unwrap();
+        std::fs::write(
+            repo.path().join("LICENSE-APACHE"),
+            "Apache License Version 2.0",
+        )
+        .unwrap();
+
+        let license = detect_license(&skill, Some(repo.path())).unwrap();
+        assert_eq!(license.relative_path, "LICENSE");
+        assert_eq!(license.spdx, "MIT");
+
+        // A license next to the synced path wins over the repository's
+        std::fs::write(skill.join("LICENSE.txt"), "Custom terms").unwrap();
+        let license = detect_license(&skill.join("SKILL.md"), Some(repo.path())).unwrap();
+        assert_eq!(license.relative_path, "skills/pdf/LICENSE.txt");
+        assert_eq!(license.spdx, UNKNOWN_LICENSE);
+
+        // The search never leaves the root
+        assert_eq!(detect_license(&skill, Some(&skill.join("missing"))), None);
+    }
+}
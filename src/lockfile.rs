@@ -1,9 +1,14 @@
 use crate::error::{ApsError, Result};
+use crate::install::DestState;
+use crate::manifest::Manifest;
+use crate::output::{display_dest, porcelain_line, say, short_id};
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::path::{Path, PathBuf};
-use tracing::{debug, info};
+use std::sync::OnceLock;
+use tracing::{debug, info, warn};
 
 /// Default lockfile filename
 pub const LOCKFILE_NAME: &str = "aps.lock.yaml";
@@ -11,6 +16,83 @@ pub const LOCKFILE_NAME: &str = "aps.lock.yaml";
 /// Legacy lockfile filename (for backward compatibility)
 const LEGACY_LOCKFILE_NAME: &str = "aps.manifest.lock";
 
+/// Profile in the manifest's `lockfiles` used automatically under the CI profile
+const CI_LOCKFILE_PROFILE: &str = "ci";
+
+/// Lockfile chosen with `--lockfile` for this process
+static SELECTED: OnceLock<String> = OnceLock::new();
+
+/// Choose the lockfile for this process: a path, or a profile named in the
+/// manifest's `lockfiles`
+pub fn select(lockfile: Option<String>) {
+    if let Some(lockfile) = lockfile {
+        let _ = SELECTED.set(lockfile);
+    }
+}
+
+/// The `--lockfile` value chosen for this process, if any
+pub fn selected() -> Option<&'static str> {
+    SELECTED.get().map(String::as_str)
+}
+
+/// Resolve a `--lockfile` value: a profile from the manifest's `lockfiles`,
+/// relative to `dir`, or otherwise a path
+fn resolve_selected(selected: &str, dir: &Path, manifest: &Manifest) -> Result<PathBuf> {
+    if let Some(path) = manifest.lockfiles.get(selected) {
+        return Ok(dir.join(path));
+    }
+    // A bare word is a profile name; anything else is a path
+    if !selected.contains(['.', '/', std::path::MAIN_SEPARATOR]) {
+        let available: Vec<&str> = manifest.lockfiles.keys().map(String::as_str).collect();
+        return Err(ApsError::UnknownLockfileProfile {
+            name: selected.to_string(),
+            available: if available.is_empty() {
+                "none defined".to_string()
+            } else {
+                available.join(", ")
+            },
+        });
+    }
+    Ok(PathBuf::from(selected))
+}
+
+/// Newest lockfile format version this build understands
+pub const LOCKFILE_VERSION: u32 = 1;
+
+/// Top-level lockfile fields known to this build
+const KNOWN_LOCKFILE_FIELDS: &[&str] = &["version", "aps_version", "manifest_checksum", "entries"];
+
+/// Locked entry fields known to this build
+const KNOWN_ENTRY_FIELDS: &[&str] = &[
+    "source",
+    "dest",
+    "resolved_ref",
+    "release",
+    "commit",
+    "commit_date",
+    "tree_oid",
+    "checksum",
+    "is_symlink",
+    "target_path",
+    "symlinked_items",
+    "last_status",
+    "last_error",
+    "last_updated_at",
+    "merged_keys",
+    "license",
+    "license_file",
+    "mirrors",
+    "expanded_paths",
+    "source_checksums",
+    "source_commits",
+    "transforms",
+    "upstream_checksum",
+    "entry_checksum",
+    "previous_dests",
+    "dest_stale",
+    "manifest",
+];
+
 /// Source types for locked entries - supports both simple strings and composite structures
 #[derive(Debug, Clone, PartialEq)]
 pub enum LockedSource {
@@ -73,6 +155,28 @@ impl Serialize for LockedSource {
     }
 }
 
+impl JsonSchema for LockedSource {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "LockedSource".into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Source description: a git URL or filesystem path, or the sources of a composite entry",
+            "oneOf": [
+                { "type": "string" },
+                {
+                    "type": "object",
+                    "properties": {
+                        "composite": { "type": "array", "items": { "type": "string" } }
+                    },
+                    "required": ["composite"]
+                }
+            ]
+        })
+    }
+}
+
 impl<'de> Deserialize<'de> for LockedSource {
     fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
     where
@@ -152,7 +256,7 @@ impl<'de> Deserialize<'de> for LockedSource {
 }
 
 /// The lockfile structure
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
 pub struct Lockfile {
     /// Version of the lockfile format
     #[serde(default = "default_version")]
@@ -162,17 +266,21 @@ pub struct Lockfile {
     #[serde(default)]
     pub aps_version: String,
 
+    /// Checksum of the manifest file at the last full sync
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest_checksum: Option<String>,
+
     /// Locked entries by ID
     #[serde(default)]
     pub entries: HashMap<String, LockedEntry>,
 }
 
 fn default_version() -> u32 {
-    1
+    LOCKFILE_VERSION
 }
 
 /// A locked entry with installation metadata
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct LockedEntry {
     /// Source description (simple string or composite structure)
     pub source: LockedSource,
@@ -184,10 +292,23 @@ pub struct LockedEntry {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resolved_ref: Option<String>,
 
+    /// Name of the release a `release:` ref resolved to; `resolved_ref` is its tag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release: Option<String>,
+
     /// Git commit SHA (if applicable)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub commit: Option<String>,
 
+    /// Committer date of the locked commit, RFC 3339 (if applicable)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_date: Option<String>,
+
+    /// Git object ID of the source path at `commit`; while it is unchanged,
+    /// `checksum` is reused instead of hashing the files again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tree_oid: Option<String>,
+
     /// Content checksum
     pub checksum: String,
 
@@ -202,6 +323,126 @@ pub struct LockedEntry {
     /// List of symlinked items (for filtered symlinks)
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub symlinked_items: Vec<String>,
+
+    /// Outcome of the most recent sync of this entry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_status: Option<LastSyncStatus>,
+
+    /// Error message from the most recent sync, if it failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+
+    /// When the installed content last changed, RFC 3339
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated_at: Option<String>,
+
+    /// hooks.json keys contributed by this entry when merged into the project's copy
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub merged_keys: Vec<String>,
+
+    /// SPDX identifier guessed from the upstream license file, or `unknown`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+
+    /// Upstream license file, relative to the source root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license_file: Option<String>,
+
+    /// Mirror destinations from `also_install_for`, symlinked to `dest`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
+
+    /// Source paths a glob `path` expanded to, each installed under `dest`
+    /// by its name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expanded_paths: Vec<String>,
+
+    /// Checksums of each composite source's content, keyed by source path or URL
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub source_checksums: BTreeMap<String, String>,
+
+    /// Commits of composite git sources, keyed by `repo#ref`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub source_commits: BTreeMap<String, String>,
+
+    /// Transforms applied when installing, in order; `checksum` covers the result
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transforms: Vec<String>,
+
+    /// Checksum of the source before `transforms` were applied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upstream_checksum: Option<String>,
+
+    /// Checksum of the manifest entry this was installed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry_checksum: Option<String>,
+
+    /// Earlier destinations that were left in place when `dest` changed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub previous_dests: Vec<String>,
+
+    /// The destination holds something other than what this entry locks:
+    /// content from a `--override` source, or from the pin before `aps
+    /// update` moved it. Only a successful install clears this
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dest_stale: bool,
+
+    /// Manifest that installed this entry, relative to the lockfile's
+    /// directory; orphan cleanup from other manifests leaves its paths alone
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<String>,
+}
+
+/// Outcome of the most recent sync of a locked entry
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LastSyncStatus {
+    /// Installed as a symlink
+    Synced,
+    /// Installed as a copy
+    Copied,
+    /// Installed, but with warnings
+    Warning,
+    /// Installation failed
+    Failed,
+    /// Installation was cancelled by a sync timeout
+    TimedOut,
+    /// Installed from a `--override` source; the rest of the entry still
+    /// describes the locked source, which the next sync reinstalls
+    Overridden,
+    /// Pin moved by `aps update`; the destination still holds the previous
+    /// content until the next sync installs the new pin
+    Pending,
+}
+
+impl LastSyncStatus {
+    /// Stable status word for `--porcelain` output, matching the lockfile spelling
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LastSyncStatus::Synced => "synced",
+            LastSyncStatus::Copied => "copied",
+            LastSyncStatus::Warning => "warning",
+            LastSyncStatus::Failed => "failed",
+            LastSyncStatus::TimedOut => "timed_out",
+            LastSyncStatus::Overridden => "overridden",
+            LastSyncStatus::Pending => "pending",
+        }
+    }
+}
+
+impl fmt::Display for LastSyncStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            LastSyncStatus::Synced => "synced",
+            LastSyncStatus::Copied => "copied",
+            LastSyncStatus::Warning => "warning",
+            LastSyncStatus::Failed => "failed",
+            LastSyncStatus::TimedOut => "timed out",
+            LastSyncStatus::Overridden => "overridden",
+            LastSyncStatus::Pending => "pending",
+        };
+        write!(f, "{}", s)
+    }
 }
 
 impl LockedEntry {
@@ -223,6 +464,25 @@ impl LockedEntry {
             is_symlink,
             target_path,
             symlinked_items,
+            last_status: None,
+            last_error: None,
+            last_updated_at: None,
+            merged_keys: Vec::new(),
+            commit_date: None,
+            release: None,
+            tree_oid: None,
+            license: None,
+            license_file: None,
+            mirrors: Vec::new(),
+            expanded_paths: Vec::new(),
+            source_checksums: BTreeMap::new(),
+            source_commits: BTreeMap::new(),
+            transforms: Vec::new(),
+            upstream_checksum: None,
+            entry_checksum: None,
+            previous_dests: Vec::new(),
+            dest_stale: false,
+            manifest: None,
         }
     }
 
@@ -244,6 +504,25 @@ impl LockedEntry {
             is_symlink: false,
             target_path: None,
             symlinked_items: Vec::new(),
+            last_status: None,
+            last_error: None,
+            last_updated_at: None,
+            merged_keys: Vec::new(),
+            commit_date: None,
+            release: None,
+            tree_oid: None,
+            license: None,
+            license_file: None,
+            mirrors: Vec::new(),
+            expanded_paths: Vec::new(),
+            source_checksums: BTreeMap::new(),
+            source_commits: BTreeMap::new(),
+            transforms: Vec::new(),
+            upstream_checksum: None,
+            entry_checksum: None,
+            previous_dests: Vec::new(),
+            dest_stale: false,
+            manifest: None,
         }
     }
 
@@ -258,8 +537,32 @@ impl LockedEntry {
             is_symlink: false,
             target_path: None,
             symlinked_items: Vec::new(),
+            last_status: None,
+            last_error: None,
+            last_updated_at: None,
+            merged_keys: Vec::new(),
+            commit_date: None,
+            release: None,
+            tree_oid: None,
+            license: None,
+            license_file: None,
+            mirrors: Vec::new(),
+            expanded_paths: Vec::new(),
+            source_checksums: BTreeMap::new(),
+            source_commits: BTreeMap::new(),
+            transforms: Vec::new(),
+            upstream_checksum: None,
+            entry_checksum: None,
+            previous_dests: Vec::new(),
+            dest_stale: false,
+            manifest: None,
         }
     }
+
+    /// The key a composite git source's commit is recorded under
+    pub fn source_commit_key(repo: &str, git_ref: &str) -> String {
+        format!("{}#{}", repo, git_ref)
+    }
 }
 
 impl Lockfile {
@@ -268,31 +571,61 @@ impl Lockfile {
         Self {
             version: default_version(),
             aps_version: env!("CARGO_PKG_VERSION").to_string(),
+            manifest_checksum: None,
             entries: HashMap::new(),
         }
     }
 
-    /// Get the lockfile path relative to the manifest
-    pub fn path_for_manifest(manifest_path: &Path) -> PathBuf {
-        manifest_path
-            .parent()
-            .map(|p| p.join(LOCKFILE_NAME))
-            .unwrap_or_else(|| PathBuf::from(LOCKFILE_NAME))
+    /// Get the lockfile path for a manifest: the one chosen with `--lockfile`,
+    /// or the default in the manifest's directory
+    pub fn path_for_manifest(manifest_path: &Path, manifest: &Manifest) -> Result<PathBuf> {
+        let dir = manifest_path.parent().unwrap_or(Path::new(""));
+        match SELECTED.get() {
+            Some(selected) => resolve_selected(selected, dir, manifest),
+            None => Ok(Self::path_in(dir, manifest)),
+        }
+    }
+
+    /// Path of the lockfile for one of the manifest's `targets`. A lockfile
+    /// chosen by path keeps its file name inside each target.
+    pub fn path_for_target(target_dir: &Path, manifest: &Manifest) -> Result<PathBuf> {
+        match SELECTED.get() {
+            Some(selected) => {
+                let path = resolve_selected(selected, target_dir, manifest)?;
+                match (manifest.lockfiles.contains_key(selected), path.file_name()) {
+                    (false, Some(name)) => Ok(target_dir.join(name)),
+                    _ => Ok(path),
+                }
+            }
+            None => Ok(Self::path_in(target_dir, manifest)),
+        }
+    }
+
+    /// Path of the lockfile in a project directory, ignoring `--lockfile`:
+    /// the manifest's `ci` lockfile under the CI profile, or `aps.lock.yaml`
+    pub fn path_in(dir: &Path, manifest: &Manifest) -> PathBuf {
+        match manifest.lockfiles.get(CI_LOCKFILE_PROFILE) {
+            Some(path) if crate::ci::active() => dir.join(path),
+            _ => dir.join(LOCKFILE_NAME),
+        }
     }
 
     /// Load a lockfile from disk
     ///
     /// Supports backward compatibility with legacy filename (aps.manifest.lock)
     pub fn load(path: &Path) -> Result<Self> {
+        Self::load_with_options(path, false)
+    }
+
+    /// Load a lockfile from disk, optionally forcing a best-effort read of a
+    /// lockfile written by a newer, incompatible version of aps.
+    pub fn load_with_options(path: &Path, force: bool) -> Result<Self> {
         // Try loading from the provided path first (new filename)
         if path.exists() {
             let content = std::fs::read_to_string(path)
                 .map_err(|e| ApsError::io(e, format!("Failed to read lockfile at {:?}", path)))?;
 
-            let lockfile: Lockfile =
-                serde_yaml::from_str(&content).map_err(|e| ApsError::LockfileReadError {
-                    message: e.to_string(),
-                })?;
+            let lockfile = Self::parse(&content, force)?;
 
             debug!("Loaded lockfile with {} entries", lockfile.entries.len());
             return Ok(lockfile);
@@ -314,10 +647,7 @@ impl Lockfile {
                 ApsError::io(e, format!("Failed to read lockfile at {:?}", legacy_path))
             })?;
 
-            let lockfile: Lockfile =
-                serde_yaml::from_str(&content).map_err(|e| ApsError::LockfileReadError {
-                    message: e.to_string(),
-                })?;
+            let lockfile = Self::parse(&content, force)?;
 
             debug!(
                 "Loaded legacy lockfile with {} entries",
@@ -329,6 +659,99 @@ impl Lockfile {
         Err(ApsError::LockfileNotFound)
     }
 
+    /// Load a lockfile like [`Self::load_with_options`], but salvage the
+    /// readable entries of a malformed one (truncated, hand-edited, or left
+    /// with merge conflict markers) instead of failing
+    pub fn load_recovering(path: &Path, force: bool) -> Result<(Self, Option<Recovery>)> {
+        match Self::load_with_options(path, force) {
+            Err(ApsError::LockfileReadError { message }) if path.exists() => {
+                let content = std::fs::read_to_string(path).map_err(|e| {
+                    ApsError::io(e, format!("Failed to read lockfile at {:?}", path))
+                })?;
+                let (lockfile, unreadable) = salvage(&content);
+                debug!(
+                    "Recovered {} entries from malformed lockfile",
+                    lockfile.entries.len()
+                );
+                Ok((
+                    lockfile,
+                    Some(Recovery {
+                        error: message,
+                        unreadable,
+                    }),
+                ))
+            }
+            result => result.map(|lockfile| (lockfile, None)),
+        }
+    }
+
+    /// Parse lockfile content, checking format compatibility first.
+    ///
+    /// A lockfile with a newer format version is rejected unless `force` is
+    /// set, in which case it is read best-effort and any fields this build
+    /// does not know about are reported, since they will be dropped on save.
+    fn parse(content: &str, force: bool) -> Result<Self> {
+        let raw: serde_yaml::Value =
+            serde_yaml::from_str(content).map_err(|e| ApsError::LockfileReadError {
+                message: e.to_string(),
+            })?;
+
+        let version = raw
+            .get("version")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(LOCKFILE_VERSION as u64);
+        let written_by = raw
+            .get("aps_version")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let current = env!("CARGO_PKG_VERSION");
+
+        if version > LOCKFILE_VERSION as u64 {
+            if !force {
+                return Err(ApsError::LockfileVersionUnsupported {
+                    version,
+                    supported: LOCKFILE_VERSION,
+                    aps_version: if written_by.is_empty() {
+                        "unknown".to_string()
+                    } else {
+                        written_by
+                    },
+                });
+            }
+            warn!(
+                "Lockfile format v{} is newer than supported v{}; reading best-effort (--force)",
+                version, LOCKFILE_VERSION
+            );
+        } else if is_newer_version(&written_by, current) {
+            warn!(
+                "Lockfile was written by aps {} (this is aps {}); consider upgrading aps",
+                written_by, current
+            );
+        }
+
+        let unknown = unknown_fields(&raw);
+        if !unknown.is_empty() {
+            warn!(
+                "These lockfile fields are not understood by aps {} and will be dropped on save: {}",
+                current,
+                unknown.join(", ")
+            );
+        }
+
+        let mut lockfile: Lockfile =
+            serde_yaml::from_value(raw).map_err(|e| ApsError::LockfileReadError {
+                message: e.to_string(),
+            })?;
+
+        // A forced read is re-saved in the format this build understands
+        if lockfile.version > LOCKFILE_VERSION {
+            lockfile.version = LOCKFILE_VERSION;
+        }
+
+        Ok(lockfile)
+    }
+
     /// Save the lockfile to disk
     ///
     /// Automatically migrates from legacy filename if it exists.
@@ -370,15 +793,70 @@ impl Lockfile {
         Ok(())
     }
 
-    /// Update or insert an entry
-    pub fn upsert(&mut self, id: String, entry: LockedEntry) {
+    /// Update or insert an entry, keeping when it was last updated if its
+    /// content is unchanged
+    pub fn upsert(&mut self, id: String, mut entry: LockedEntry) {
+        if entry.last_updated_at.is_none() {
+            if let Some(previous) = self.entries.get(&id) {
+                if previous.checksum == entry.checksum {
+                    entry.last_updated_at = previous.last_updated_at.clone();
+                }
+            }
+        }
         self.entries.insert(id, entry);
     }
 
+    /// Move a locked entry to a new ID, optionally updating its destination.
+    /// Returns false if no entry exists under `old_id`.
+    pub fn rename_entry(&mut self, old_id: &str, new_id: &str, new_dest: Option<&str>) -> bool {
+        match self.entries.remove(old_id) {
+            Some(mut entry) => {
+                if let Some(dest) = new_dest {
+                    entry.dest = dest.to_string();
+                }
+                self.entries.insert(new_id.to_string(), entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Record the outcome of syncing an entry. Returns false if the entry is not locked.
+    pub fn record_outcome(
+        &mut self,
+        id: &str,
+        status: LastSyncStatus,
+        error: Option<String>,
+    ) -> bool {
+        match self.entries.get_mut(id) {
+            Some(entry) => {
+                entry.last_status = Some(status);
+                entry.last_error = error;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether an entry should be retried: its last sync failed or timed out,
+    /// or it was never locked.
+    pub fn needs_retry(&self, id: &str) -> bool {
+        self.entries
+            .get(id)
+            .map(|e| {
+                matches!(
+                    e.last_status,
+                    Some(LastSyncStatus::Failed | LastSyncStatus::TimedOut)
+                )
+            })
+            .unwrap_or(true)
+    }
+
     /// Check if a checksum matches the locked entry
     pub fn checksum_matches(&self, id: &str, checksum: &str) -> bool {
         self.entries
             .get(id)
+            .filter(|e| !e.dest_stale)
             .map(|e| e.checksum == checksum)
             .unwrap_or(false)
     }
@@ -387,11 +865,49 @@ impl Lockfile {
     pub fn commit_matches(&self, id: &str, commit_sha: &str) -> bool {
         self.entries
             .get(id)
+            .filter(|e| !e.dest_stale)
             .and_then(|e| e.commit.as_ref())
             .map(|c| c == commit_sha)
             .unwrap_or(false)
     }
 
+    /// How the manifest differs from the one last synced, or `None` if it is
+    /// unchanged or the lockfile predates manifest checksums
+    pub fn manifest_changes(
+        &self,
+        manifest: &Manifest,
+        manifest_checksum: &str,
+    ) -> Option<ManifestChanges> {
+        let recorded = self.manifest_checksum.as_deref()?;
+        if recorded == manifest_checksum {
+            return None;
+        }
+
+        let mut changes = ManifestChanges::default();
+        for entry in &manifest.entries {
+            match self.entries.get(&entry.id) {
+                None => changes.added.push(entry.id.clone()),
+                Some(locked) => {
+                    if locked
+                        .entry_checksum
+                        .as_ref()
+                        .is_some_and(|c| *c != entry.checksum())
+                    {
+                        changes.modified.push(entry.id.clone());
+                    }
+                }
+            }
+        }
+        changes.removed = self
+            .entries
+            .keys()
+            .filter(|id| !manifest.entries.iter().any(|e| &e.id == *id))
+            .cloned()
+            .collect();
+        changes.removed.sort();
+        Some(changes)
+    }
+
     /// Retain only entries with IDs in the given set, removing stale entries.
     /// Returns the list of IDs that were removed.
     pub fn retain_entries(&mut self, ids_to_keep: &[&str]) -> Vec<String> {
@@ -412,55 +928,272 @@ impl Lockfile {
     }
 }
 
-/// Display status information from the lockfile
-pub fn display_status(lockfile: &Lockfile) {
+/// Compare two dotted version strings, returning true if `a` is newer than `b`.
+/// Non-numeric components (e.g. pre-release suffixes) are ignored.
+fn is_newer_version(a: &str, b: &str) -> bool {
+    let parse = |v: &str| -> Vec<u64> {
+        v.split('.')
+            .map(|part| {
+                part.chars()
+                    .take_while(|c| c.is_ascii_digit())
+                    .collect::<String>()
+                    .parse()
+                    .unwrap_or(0)
+            })
+            .collect()
+    };
+    if a.is_empty() {
+        return false;
+    }
+    parse(a) > parse(b)
+}
+
+/// List lockfile fields (top-level and per-entry) unknown to this build.
+fn unknown_fields(raw: &serde_yaml::Value) -> Vec<String> {
+    let mut unknown = Vec::new();
+    let Some(map) = raw.as_mapping() else {
+        return unknown;
+    };
+
+    for key in map.keys().filter_map(|k| k.as_str()) {
+        if !KNOWN_LOCKFILE_FIELDS.contains(&key) {
+            unknown.push(key.to_string());
+        }
+    }
+
+    if let Some(entries) = raw.get("entries").and_then(|e| e.as_mapping()) {
+        for (id, entry) in entries {
+            let (Some(id), Some(fields)) = (id.as_str(), entry.as_mapping()) else {
+                continue;
+            };
+            for key in fields.keys().filter_map(|k| k.as_str()) {
+                if !KNOWN_ENTRY_FIELDS.contains(&key) {
+                    unknown.push(format!("entries.{}.{}", id, key));
+                }
+            }
+        }
+    }
+
+    unknown.sort();
+    unknown
+}
+
+/// What was lost when a malformed lockfile was salvaged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recovery {
+    /// Why the lockfile could not be read as a whole
+    pub error: String,
+    /// IDs of entries that were present but could not be read
+    pub unreadable: Vec<String>,
+}
+
+/// Recover what is readable from a malformed lockfile: every entry that
+/// deserializes on its own, plus the IDs of those that do not. When the YAML
+/// itself is broken, entries are split on their indentation, and any entry
+/// touched by a merge conflict is treated as unreadable.
+fn salvage(content: &str) -> (Lockfile, Vec<String>) {
+    let mut lockfile = Lockfile::new();
+    let mut unreadable = Vec::new();
+
+    if let Ok(raw) = serde_yaml::from_str::<serde_yaml::Value>(content) {
+        if let Some(entries) = raw.get("entries").and_then(|e| e.as_mapping()) {
+            for (id, value) in entries {
+                let Some(id) = id.as_str() else { continue };
+                match serde_yaml::from_value::<LockedEntry>(value.clone()) {
+                    Ok(entry) => lockfile.upsert(id.to_string(), entry),
+                    Err(_) => unreadable.push(id.to_string()),
+                }
+            }
+        }
+        unreadable.sort();
+        return (lockfile, unreadable);
+    }
+
+    // (id, lines, touched by a conflict)
+    let mut blocks: Vec<(String, Vec<&str>, bool)> = Vec::new();
+    let mut in_entries = false;
+    let mut in_conflict = false;
+    for line in content.lines() {
+        let is_marker = ["<<<<<<<", "=======", ">>>>>>>", "|||||||"]
+            .iter()
+            .any(|marker| line.starts_with(marker));
+        if is_marker {
+            in_conflict = !line.starts_with(">>>>>>>");
+            continue;
+        }
+        if line.trim_end() == "entries:" {
+            in_entries = true;
+            continue;
+        }
+        if !in_entries || line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with(' ') {
+            // Another top-level field ends the entries
+            in_entries = false;
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        if indent == 2 {
+            let id = line
+                .trim()
+                .trim_end_matches(':')
+                .trim_matches(|c| c == '\'' || c == '"');
+            blocks.push((id.to_string(), Vec::new(), in_conflict));
+        } else if let Some(block) = blocks.last_mut() {
+            block.1.push(&line[4.min(indent)..]);
+            block.2 |= in_conflict;
+        }
+    }
+
+    for (id, lines, conflicted) in blocks {
+        let entry = if conflicted {
+            None
+        } else {
+            serde_yaml::from_str::<LockedEntry>(&lines.join("\n")).ok()
+        };
+        match entry {
+            Some(entry) if !lockfile.entries.contains_key(&id) => lockfile.upsert(id, entry),
+            _ => unreadable.push(id),
+        }
+    }
+    for id in &unreadable {
+        lockfile.entries.remove(id);
+    }
+    unreadable.sort();
+    unreadable.dedup();
+    (lockfile, unreadable)
+}
+
+/// Display lockfile entries as `--porcelain` lines, sorted by ID. A broken
+/// destination's on-disk state replaces the entry's last sync status.
+pub fn display_status_porcelain(lockfile: &Lockfile, states: &HashMap<String, DestState>) {
+    let mut ids: Vec<&String> = lockfile.entries.keys().collect();
+    ids.sort();
+    for id in ids {
+        let entry = &lockfile.entries[id];
+        let status = match entry.last_status {
+            _ if states.get(id).is_some_and(DestState::is_broken) => states[id].as_str(),
+            Some(status) => status.as_str(),
+            None if entry.is_symlink => LastSyncStatus::Synced.as_str(),
+            None => LastSyncStatus::Copied.as_str(),
+        };
+        porcelain_line(id, status, &display_dest(&entry.dest));
+    }
+}
+
+/// Display lockfile entries, annotated with manifest `description`/`owner`
+/// Entry IDs that changed in the manifest since the last full sync
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ManifestChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+}
+
+/// Tell the user the manifest was edited after the last sync
+pub fn display_manifest_changes(changes: &ManifestChanges) {
+    say!("\nManifest changed since last sync:");
+    for id in &changes.added {
+        say!("  + {} (added)", id);
+    }
+    for id in &changes.removed {
+        say!("  - {} (removed)", id);
+    }
+    for id in &changes.modified {
+        say!("  ~ {} (modified)", id);
+    }
+    if changes.added.is_empty() && changes.removed.is_empty() && changes.modified.is_empty() {
+        say!("  (no entry changes)");
+    }
+    say!("Run `aps sync` to apply the changes.");
+}
+
+pub fn display_status(
+    lockfile: &Lockfile,
+    manifest: &Manifest,
+    states: &HashMap<String, DestState>,
+) {
     if !lockfile.aps_version.is_empty() {
-        println!("APS version:  {}", lockfile.aps_version);
+        say!("APS version:  {}", lockfile.aps_version);
     }
 
     if lockfile.entries.is_empty() {
-        println!("No entries in lockfile.");
+        say!("No entries in lockfile.");
         return;
     }
 
-    println!("Synced entries:");
-    println!("{}", "-".repeat(80));
+    say!("Synced entries:");
+    say!("{}", "-".repeat(80));
 
     for (id, entry) in &lockfile.entries {
-        println!("ID:           {}", id);
+        say!("ID:           {}", id);
+        if let Some(entry) = manifest.entries.iter().find(|e| &e.id == id) {
+            if let Some(ref description) = entry.description {
+                say!("Description:  {}", description);
+            }
+            if let Some(ref owner) = entry.owner {
+                say!("Owner:        {}", owner);
+            }
+        }
         match &entry.source {
-            LockedSource::Simple(s) => println!("Source:       {}", s),
+            LockedSource::Simple(s) => say!("Source:       {}", s),
             LockedSource::Composite(sources) => {
-                println!("Source:       composite");
+                say!("Source:       composite");
                 for s in sources {
-                    println!("              - {}", s);
+                    say!("              - {}", s);
                 }
             }
         }
-        println!("Destination:  {}", entry.dest);
+        say!("Destination:  {}", entry.dest);
+        if let Some(state) = states.get(id) {
+            say!("On disk:      {}", state);
+        }
         if let Some(ref resolved_ref) = entry.resolved_ref {
-            println!("Ref:          {}", resolved_ref);
+            say!("Ref:          {}", resolved_ref);
+        }
+        if let Some(ref release) = entry.release {
+            say!("Release:      {}", release);
         }
         if let Some(ref commit) = entry.commit {
-            println!("Commit:       {}", commit);
+            say!("Commit:       {}", short_id(commit));
         }
         if entry.is_symlink {
-            println!("Type:         symlink");
+            say!("Type:         symlink");
             if let Some(ref target) = entry.target_path {
-                println!("Target:       {}", target);
+                say!("Target:       {}", target);
             }
             if !entry.symlinked_items.is_empty() {
-                println!("Items:        {} symlinked", entry.symlinked_items.len());
+                say!("Items:        {} symlinked", entry.symlinked_items.len());
             }
         }
-        println!("Checksum:     {}", entry.checksum);
-        println!("{}", "-".repeat(80));
+        say!("Checksum:     {}", short_id(&entry.checksum));
+        for mirror in &entry.mirrors {
+            say!("Mirror:       {}", mirror);
+        }
+        if let Some(ref license) = entry.license {
+            match entry.license_file {
+                Some(ref file) => say!("License:      {} ({})", license, file),
+                None => say!("License:      {}", license),
+            }
+        }
+        if !entry.merged_keys.is_empty() {
+            say!("Merged keys:  {}", entry.merged_keys.join(", "));
+        }
+        if let Some(status) = entry.last_status {
+            say!("Last sync:    {}", status);
+        }
+        if let Some(ref error) = entry.last_error {
+            say!("Last error:   {}", error);
+        }
+        say!("{}", "-".repeat(80));
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::manifest::Entry;
 
     #[test]
     fn test_retain_entries_removes_stale() {
@@ -514,6 +1247,27 @@ mod tests {
         assert!(lockfile.entries.contains_key("entry3"));
     }
 
+    #[test]
+    fn test_rename_entry_moves_and_updates_dest() {
+        let mut lockfile = Lockfile::new();
+        lockfile.upsert(
+            "old".to_string(),
+            LockedEntry::new_filesystem(
+                "source",
+                ".claude/skills/old/",
+                "checksum".to_string(),
+                false,
+                None,
+                vec![],
+            ),
+        );
+
+        assert!(lockfile.rename_entry("old", "new", Some(".claude/skills/new/")));
+        assert!(!lockfile.entries.contains_key("old"));
+        assert_eq!(lockfile.entries["new"].dest, ".claude/skills/new/");
+        assert!(!lockfile.rename_entry("missing", "other", None));
+    }
+
     #[test]
     fn test_retain_entries_empty_keep_list() {
         let mut lockfile = Lockfile::new();
@@ -568,4 +1322,246 @@ mod tests {
         assert!(removed.is_empty());
         assert_eq!(lockfile.entries.len(), 2);
     }
+
+    #[test]
+    fn test_parse_rejects_newer_format_version() {
+        let content = "version: 99\naps_version: 9.0.0\nentries: {}\n";
+        let err = Lockfile::parse(content, false).unwrap_err();
+        assert!(matches!(
+            err,
+            ApsError::LockfileVersionUnsupported { version: 99, .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_force_reads_newer_format_best_effort() {
+        let content = r#"version: 2
+aps_version: 9.0.0
+signatures: {}
+entries:
+  my-skill:
+    source: ./skills
+    dest: ./.claude/skills/my-skill/
+    checksum: sha256:abc
+    provenance: signed
+"#;
+        let lockfile = Lockfile::parse(content, true).unwrap();
+        assert_eq!(lockfile.version, LOCKFILE_VERSION);
+        assert!(lockfile.entries.contains_key("my-skill"));
+
+        let raw: serde_yaml::Value = serde_yaml::from_str(content).unwrap();
+        assert_eq!(
+            unknown_fields(&raw),
+            vec!["entries.my-skill.provenance", "signatures"]
+        );
+    }
+
+    #[test]
+    fn test_is_newer_version() {
+        assert!(is_newer_version("0.2.0", "0.1.12"));
+        assert!(is_newer_version("0.1.13", "0.1.12"));
+        assert!(!is_newer_version("0.1.12", "0.1.12"));
+        assert!(!is_newer_version("0.1.9", "0.1.12"));
+        assert!(!is_newer_version("", "0.1.12"));
+    }
+
+    #[test]
+    fn test_record_outcome_and_needs_retry() {
+        let mut lockfile = Lockfile::new();
+        lockfile.upsert(
+            "entry1".to_string(),
+            LockedEntry::new_filesystem("source1", "dest1", "c1".to_string(), false, None, vec![]),
+        );
+
+        assert!(!lockfile.needs_retry("entry1"));
+        assert!(lockfile.needs_retry("never-synced"));
+
+        assert!(lockfile.record_outcome(
+            "entry1",
+            LastSyncStatus::Failed,
+            Some("boom".to_string())
+        ));
+        assert!(lockfile.needs_retry("entry1"));
+        assert_eq!(
+            lockfile.entries["entry1"].last_error.as_deref(),
+            Some("boom")
+        );
+
+        assert!(lockfile.record_outcome("entry1", LastSyncStatus::Copied, None));
+        assert!(!lockfile.needs_retry("entry1"));
+        assert!(lockfile.entries["entry1"].last_error.is_none());
+
+        assert!(!lockfile.record_outcome("missing", LastSyncStatus::Failed, None));
+    }
+
+    #[test]
+    fn test_manifest_changes_diff_entries() {
+        let yaml = |agents_root: &str| {
+            format!(
+                "entries:\n  - id: agents\n    kind: agents_md\n    source:\n      type: filesystem\n      root: {}\n  - id: rules\n    kind: cursor_rules\n    source:\n      type: filesystem\n      root: ./rules\n",
+                agents_root
+            )
+        };
+        let synced: Manifest = serde_yaml::from_str(&yaml("./docs")).unwrap();
+        let mut lockfile = Lockfile::new();
+        for entry in &synced.entries {
+            let mut locked =
+                LockedEntry::new_filesystem("src", "dest", "c".to_string(), false, None, vec![]);
+            locked.entry_checksum = Some(entry.checksum());
+            lockfile.upsert(entry.id.clone(), locked);
+        }
+        lockfile.upsert(
+            "old".to_string(),
+            LockedEntry::new_filesystem("src", "dest", "c".to_string(), false, None, vec![]),
+        );
+
+        // Lockfiles without a manifest checksum report nothing
+        assert_eq!(lockfile.manifest_changes(&synced, "sha256:new"), None);
+
+        lockfile.manifest_checksum = Some("sha256:old".to_string());
+        assert_eq!(lockfile.manifest_changes(&synced, "sha256:old"), None);
+
+        let mut edited: Manifest = serde_yaml::from_str(&yaml("./guides")).unwrap();
+        edited.entries.push(Entry {
+            id: "new".to_string(),
+            ..edited.entries[1].clone()
+        });
+        assert_eq!(
+            lockfile.manifest_changes(&edited, "sha256:new"),
+            Some(ManifestChanges {
+                added: vec!["new".to_string()],
+                removed: vec!["old".to_string()],
+                modified: vec!["agents".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_resolve_selected_lockfile() {
+        let manifest: Manifest =
+            serde_yaml::from_str("lockfiles:\n  ci: locks/aps.lock.ci.yaml\nentries: []\n")
+                .unwrap();
+        let dir = Path::new("/project");
+
+        assert_eq!(
+            resolve_selected("ci", dir, &manifest).unwrap(),
+            PathBuf::from("/project/locks/aps.lock.ci.yaml")
+        );
+        assert_eq!(
+            resolve_selected("./other.lock.yaml", dir, &manifest).unwrap(),
+            PathBuf::from("./other.lock.yaml")
+        );
+        assert!(matches!(
+            resolve_selected("staging", dir, &manifest),
+            Err(ApsError::UnknownLockfileProfile { .. })
+        ));
+        assert_eq!(
+            Lockfile::path_in(dir, &manifest),
+            PathBuf::from("/project/aps.lock.yaml")
+        );
+    }
+
+    #[test]
+    fn test_salvage_recovers_readable_entries() {
+        let mut lockfile = Lockfile::new();
+        for id in ["alpha", "beta", "gamma"] {
+            lockfile.upsert(
+                id.to_string(),
+                LockedEntry::new_filesystem(
+                    "../shared",
+                    &format!("./{}", id),
+                    "sha256:a".to_string(),
+                    false,
+                    None,
+                    Vec::new(),
+                ),
+            );
+        }
+        let content = serde_yaml::to_string(&lockfile).unwrap();
+
+        // Truncated mid-entry: the last entry misses required fields
+        let cut = content.rfind("checksum:").unwrap();
+        let (salvaged, unreadable) = salvage(&content[..cut]);
+        assert_eq!(salvaged.entries.len(), 2);
+        assert_eq!(unreadable.len(), 1);
+
+        // Merge conflict inside one entry
+        let conflicted = content.replacen(
+            "  beta:\n",
+            "  beta:\n<<<<<<< HEAD\n    dest: ./beta\n=======\n    dest: ./other\n>>>>>>> theirs\n",
+            1,
+        );
+        let (salvaged, unreadable) = salvage(&conflicted);
+        assert_eq!(unreadable, vec!["beta".to_string()]);
+        assert_eq!(salvaged.entries.len(), 2);
+
+        // Valid YAML with one bad entry
+        let edited = content.replacen("checksum: sha256:a", "checksum: {bad: 1}", 1);
+        let (salvaged, unreadable) = salvage(&edited);
+        assert_eq!(salvaged.entries.len(), 2);
+        assert_eq!(unreadable.len(), 1);
+    }
+
+    #[test]
+    fn test_known_fields_cover_every_serialized_field() {
+        // Every field is set, so each one is serialized; a new field fails to
+        // compile here until it is added, and then fails the assertions until
+        // it is listed as known
+        let entry = LockedEntry {
+            source: LockedSource::simple("https://github.com/org/skills.git"),
+            dest: ".claude/skills/review".to_string(),
+            resolved_ref: Some("main".to_string()),
+            release: Some("v1.0.0".to_string()),
+            commit: Some("abc123".to_string()),
+            commit_date: Some("2024-01-01T00:00:00Z".to_string()),
+            tree_oid: Some("def456".to_string()),
+            checksum: "sha256:a".to_string(),
+            is_symlink: true,
+            target_path: Some("../skills/review".to_string()),
+            symlinked_items: vec!["review".to_string()],
+            last_status: Some(LastSyncStatus::Synced),
+            last_error: Some("failed".to_string()),
+            last_updated_at: Some("2024-01-02T00:00:00Z".to_string()),
+            merged_keys: vec!["hooks".to_string()],
+            license: Some("MIT".to_string()),
+            license_file: Some("LICENSE".to_string()),
+            mirrors: vec![".cursor/skills/review".to_string()],
+            expanded_paths: vec!["skills/review".to_string()],
+            source_checksums: BTreeMap::from([("a".to_string(), "sha256:b".to_string())]),
+            source_commits: BTreeMap::from([("a#main".to_string(), "abc123".to_string())]),
+            transforms: vec!["strip_frontmatter".to_string()],
+            upstream_checksum: Some("sha256:c".to_string()),
+            entry_checksum: Some("sha256:d".to_string()),
+            previous_dests: vec![".claude/skills/old".to_string()],
+            dest_stale: true,
+            manifest: Some("../other/aps.yaml".to_string()),
+        };
+        let lockfile = Lockfile {
+            version: LOCKFILE_VERSION,
+            aps_version: "0.1.0".to_string(),
+            manifest_checksum: Some("sha256:e".to_string()),
+            entries: HashMap::from([("review".to_string(), entry)]),
+        };
+
+        let raw = serde_yaml::to_value(&lockfile).unwrap();
+        let top: Vec<&str> = raw
+            .as_mapping()
+            .unwrap()
+            .keys()
+            .filter_map(|k| k.as_str())
+            .collect();
+        assert_eq!(top.len(), KNOWN_LOCKFILE_FIELDS.len());
+        let fields: Vec<&str> = raw["entries"]["review"]
+            .as_mapping()
+            .unwrap()
+            .keys()
+            .filter_map(|k| k.as_str())
+            .collect();
+        assert_eq!(fields.len(), KNOWN_ENTRY_FIELDS.len());
+        assert!(
+            unknown_fields(&raw).is_empty(),
+            "{:?}",
+            unknown_fields(&raw)
+        );
+    }
 }
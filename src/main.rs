@@ -1,45 +1,132 @@
+mod approval;
 mod backup;
+mod bootstrap;
+mod cache;
 mod catalog;
+mod changelog;
 mod checksum;
+mod ci;
 mod cli;
+mod color;
 mod commands;
 mod compose;
+mod consumers;
+mod dest_lock;
 mod discover;
+mod entry_id;
 mod error;
+mod explain;
+mod filter;
 mod github_url;
+mod grep;
+mod history;
 mod hooks;
 mod install;
+mod integrations;
+mod license;
 mod lockfile;
 mod manifest;
+mod manifest_diff;
 mod orphan;
+mod output;
+mod path_glob;
+mod path_guard;
+mod persona;
+mod plan;
+mod post_install;
+mod preflight;
+mod project_index;
+mod rebuild;
+mod review;
+mod sbom;
+mod schema;
+mod serve;
 mod sources;
+mod staleness;
+mod summary;
 mod sync_output;
+mod transform;
+mod user_config;
+mod warning;
 
-use clap::Parser;
-use cli::{CatalogCommands, Cli, Commands};
+use clap::{ArgMatches, CommandFactory, FromArgMatches};
+use cli::{
+    BackupCommands, CacheCommands, CatalogCommands, Cli, Commands, ExportCommands, IndexCommands,
+    ManifestCommands, OrphansCommands,
+};
 use commands::{
-    cmd_add, cmd_catalog_generate, cmd_init, cmd_list, cmd_status, cmd_sync, cmd_validate,
+    cmd_add, cmd_backup_list, cmd_backup_restore, cmd_bootstrap, cmd_cache_export,
+    cmd_cache_import, cmd_catalog_generate, cmd_changelog, cmd_doctor, cmd_export_sbom, cmd_grep,
+    cmd_index_list, cmd_index_search, cmd_init, cmd_list, cmd_lock, cmd_log, cmd_manifest_rebuild,
+    cmd_orphans_clean, cmd_orphans_list, cmd_outdated, cmd_pin, cmd_plan, cmd_remove, cmd_rename,
+    cmd_schema, cmd_serve, cmd_sort, cmd_status, cmd_sync, cmd_update, cmd_validate, cmd_who_uses,
 };
 use miette::Result;
+use std::time::Instant;
 use tracing::Level;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::FmtSubscriber;
 
 fn main() -> Result<()> {
     // Parse CLI arguments
-    let cli = Cli::parse();
+    let started = Instant::now();
+    let matches = Cli::command().get_matches();
+    let command_name = subcommand_path(&matches);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    // The CI profile answers prompts with --yes and prints plain output
+    let ci = ci::init();
+    if ci {
+        cli.command.assume_yes();
+    }
+
+    // Every command reads and writes the lockfile chosen with --lockfile
+    lockfile::select(cli.lockfile.take());
+
+    // Decide on colors before anything is printed
+    color::init(cli.no_color, ci);
+
+    // --porcelain implies --quiet for everything but its own lines
+    let porcelain = match &cli.command {
+        Commands::Sync(args) => args.porcelain,
+        Commands::Status(args) => args.porcelain,
+        Commands::List(args) => args.porcelain,
+        _ => false,
+    };
+    // `aps serve --stdio` owns stdout for protocol messages, so nothing else prints there
+    let serving = matches!(cli.command, Commands::Serve(_));
+    output::init(cli.quiet || porcelain || serving, cli.full_ids);
+
+    // --explain adds remediation steps to errors from sync and validate
+    explain::init(match &cli.command {
+        Commands::Sync(args) => args.explain,
+        Commands::Validate(args) => args.explain,
+        _ => false,
+    });
 
-    // Set up logging based on --verbose flag
+    // Set up logging based on --verbose and --quiet flags
     let log_level = if cli.verbose {
         Level::DEBUG
+    } else if output::quiet() && !serving {
+        Level::ERROR
     } else {
         Level::WARN
     };
 
+    // Logs from `aps serve` go to stderr
+    let writer = if serving {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+
     let subscriber = FmtSubscriber::builder()
         .with_max_level(log_level)
+        .with_writer(writer)
         .with_target(false)
         .with_thread_ids(false)
         .with_thread_names(false)
+        .with_ansi(color::enabled())
         .finish();
 
     tracing::subscriber::set_global_default(subscriber).expect("Failed to set tracing subscriber");
@@ -48,15 +135,67 @@ fn main() -> Result<()> {
     let result = match cli.command {
         Commands::Init(args) => cmd_init(args),
         Commands::Add(args) => cmd_add(args),
+        Commands::Remove(args) => cmd_remove(args),
         Commands::Sync(args) => cmd_sync(args),
+        Commands::Lock(args) => cmd_lock(args),
+        Commands::Outdated(args) => cmd_outdated(args),
+        Commands::Update(args) => cmd_update(args),
+        Commands::Plan(args) => cmd_plan(args),
         Commands::Validate(args) => cmd_validate(args),
+        Commands::Doctor => cmd_doctor(),
         Commands::Status(args) => cmd_status(args),
+        Commands::Log(args) => cmd_log(args),
+        Commands::Changelog(args) => cmd_changelog(args),
         Commands::List(args) => cmd_list(args),
+        Commands::Grep(args) => cmd_grep(args),
+        Commands::WhoUses(args) => cmd_who_uses(args),
         Commands::Catalog(args) => match args.command {
             CatalogCommands::Generate(gen_args) => cmd_catalog_generate(gen_args),
         },
+        Commands::Cache(args) => match args.command {
+            CacheCommands::Export(export_args) => cmd_cache_export(export_args),
+            CacheCommands::Import(import_args) => cmd_cache_import(import_args),
+        },
+        Commands::Index(args) => match args.command {
+            IndexCommands::List(list_args) => cmd_index_list(list_args),
+            IndexCommands::Search(search_args) => cmd_index_search(search_args),
+        },
+        Commands::Backup(args) => match args.command {
+            BackupCommands::List(list_args) => cmd_backup_list(list_args),
+            BackupCommands::Restore(restore_args) => cmd_backup_restore(restore_args),
+        },
+        Commands::Orphans(args) => match args.command {
+            OrphansCommands::List(list_args) => cmd_orphans_list(list_args),
+            OrphansCommands::Clean(clean_args) => cmd_orphans_clean(clean_args),
+        },
+        Commands::Manifest(args) => match args.command {
+            ManifestCommands::Rebuild(rebuild_args) => cmd_manifest_rebuild(rebuild_args),
+        },
+        Commands::Rename(args) => cmd_rename(args),
+        Commands::Pin(args) => cmd_pin(args),
+        Commands::Sort(args) => cmd_sort(args),
+        Commands::Bootstrap(args) => cmd_bootstrap(args),
+        Commands::Export(args) => match args.command {
+            ExportCommands::Sbom(sbom_args) => cmd_export_sbom(sbom_args),
+        },
+        Commands::Schema(args) => cmd_schema(args),
+        Commands::Serve(args) => cmd_serve(args),
     };
 
+    // Leave a machine-readable record for wrappers when APS_SUMMARY_FILE is set
+    summary::write(&command_name, started, &result);
+
     // Convert our error type to miette for nice display
     result.map_err(|e| e.into())
 }
+
+/// The subcommands given, e.g. `orphans clean`
+fn subcommand_path(matches: &ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    names.join(" ")
+}
@@ -1,7 +1,14 @@
+use crate::checksum::{build_ignore_set, compute_string_checksum};
 use crate::error::{ApsError, Result};
-use crate::sources::{FilesystemSource, GitSource, SourceAdapter};
+use crate::sources::{
+    set_github_tarballs, ApsSource, FilesystemSource, GeneratedSource, GitSource, HttpSource,
+    SourceAdapter,
+};
+use crate::transform::Transform;
+use crate::warning::{Warning, WarningCode, WarningSet};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::path::{Path, PathBuf};
 use tracing::{debug, info};
 
@@ -9,23 +16,309 @@ use tracing::{debug, info};
 pub const DEFAULT_MANIFEST_NAME: &str = "aps.yaml";
 
 /// The main manifest structure
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct Manifest {
     /// List of entries to sync
     #[serde(default)]
     pub entries: Vec<Entry>,
+
+    /// Optional editor integrations applied after sync
+    #[serde(default, skip_serializing_if = "Integrations::is_empty")]
+    pub integrations: Integrations,
+
+    /// Warn when a locked git commit is older than this many days and upstream has moved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<u32>,
+
+    /// Default destination overrides, keyed by asset kind or tool (`cursor`, `claude`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dest_roots: BTreeMap<String, String>,
+
+    /// How conflicting content is backed up before it is overwritten
+    #[serde(default, skip_serializing_if = "BackupStrategy::is_default")]
+    pub backup_strategy: BackupStrategy,
+
+    /// Back up conflicting content before overwriting it (set to false to
+    /// overwrite without a backup)
+    #[serde(default = "default_backup", skip_serializing_if = "is_default_backup")]
+    pub backup: bool,
+
+    /// Directory for backups, relative to the manifest (default `.aps-backups`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_dir: Option<String>,
+
+    /// Project directories, relative to the manifest, that every entry is
+    /// installed into instead of the manifest's own directory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+
+    /// Check `git status` before sync overwrites destinations, and warn (or
+    /// with `--strict`, fail) when they have uncommitted changes
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub check_uncommitted: bool,
+
+    /// Warning codes (such as `W002`) to leave out of sync and validate output
+    /// for every entry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppress_warnings: Vec<WarningCode>,
+
+    /// How installs change when aps runs in CI (the `CI` environment variable is set)
+    #[serde(default, skip_serializing_if = "CiSettings::is_default")]
+    pub ci: CiSettings,
+
+    /// Whether symlinks point at their sources by absolute path or by a path
+    /// relative to the symlink's directory
+    #[serde(default, skip_serializing_if = "SymlinkStyle::is_default")]
+    pub symlink_style: SymlinkStyle,
+
+    /// Lockfiles by profile name, relative to the manifest, e.g.
+    /// `ci: aps.lock.ci.yaml`. Pick one with `--lockfile <profile>`; `ci` is
+    /// used automatically under the CI profile
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub lockfiles: BTreeMap<String, String>,
+
+    /// Keep entries ordered by kind, then ID, whenever aps writes the
+    /// manifest (see `aps sort`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sort: bool,
+
+    /// Size and structure limits composite entries are checked against
+    /// after composing
+    #[serde(default, skip_serializing_if = "ComposeLimits::is_default")]
+    pub compose_limits: ComposeLimits,
+
+    /// Download public GitHub repositories as tarballs instead of cloning
+    /// them. Faster, but `export-ignore` and `export-subst` attributes apply,
+    /// so installed files and checksums can differ from a clone's
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub github_tarballs: bool,
 }
 
 impl Default for Manifest {
     fn default() -> Self {
         Self {
             entries: vec![Entry::example()],
+            integrations: Integrations::default(),
+            max_age: None,
+            dest_roots: BTreeMap::new(),
+            backup_strategy: BackupStrategy::default(),
+            backup: true,
+            backup_dir: None,
+            targets: Vec::new(),
+            check_uncommitted: false,
+            suppress_warnings: Vec::new(),
+            ci: CiSettings::default(),
+            symlink_style: SymlinkStyle::default(),
+            lockfiles: BTreeMap::new(),
+            sort: false,
+            compose_limits: ComposeLimits::default(),
+            github_tarballs: false,
         }
     }
 }
 
+impl Manifest {
+    /// Substitute entry sources for one run from `--override <id>=<source>`
+    /// values, returning the overridden entry IDs
+    pub fn apply_overrides(&mut self, specs: &[String]) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for spec in specs {
+            let (id, source) = spec
+                .split_once('=')
+                .ok_or_else(|| ApsError::InvalidOverride {
+                    value: spec.clone(),
+                    reason: "expected `<id>=<source>`".to_string(),
+                })?;
+            let entry = self
+                .entries
+                .iter_mut()
+                .find(|e| e.id == id)
+                .ok_or_else(|| ApsError::EntryNotFound { id: id.to_string() })?;
+            if entry.is_composite() {
+                return Err(ApsError::InvalidOverride {
+                    value: spec.clone(),
+                    reason: format!("'{}' combines several sources", id),
+                });
+            }
+            entry.source = Some(Source::from_override(source, entry.source.as_ref())?);
+            ids.push(id.to_string());
+        }
+        Ok(ids)
+    }
+
+    /// Warning codes suppressed for an entry: the manifest's own list plus
+    /// the entry's, if the ID names one
+    pub fn suppressed_warnings(&self, id: &str) -> WarningSet {
+        let global = WarningSet::of(&self.suppress_warnings);
+        match self.entries.iter().find(|e| e.id == id) {
+            Some(entry) => global.with(&entry.suppress_warnings),
+            None => global,
+        }
+    }
+
+    /// Default destination for a kind, honoring `dest_roots`.
+    ///
+    /// A kind key (e.g. `agent_skill: .ai/skills`) replaces the whole default
+    /// destination; a tool key (e.g. `claude: .ai`) replaces the tool directory.
+    pub fn default_dest(&self, kind: &AssetKind) -> PathBuf {
+        let builtin = kind.default_dest();
+        if let Some(root) = self.dest_roots.get(kind.as_str()) {
+            return PathBuf::from(expand_dest_root(root));
+        }
+        if let Some(root) = kind.tool().and_then(|tool| self.dest_roots.get(tool)) {
+            let mut components = builtin.components();
+            components.next();
+            return PathBuf::from(expand_dest_root(root)).join(components.as_path());
+        }
+        builtin
+    }
+
+    /// Whether any entry installs into other project directories
+    pub fn has_targets(&self) -> bool {
+        !self.targets.is_empty() || self.entries.iter().any(|e| !e.targets.is_empty())
+    }
+
+    /// Split the manifest by target directory, in order of first mention.
+    ///
+    /// An entry goes to its own `targets`, else the manifest's `targets`, else
+    /// the manifest directory (`.`). Relative filesystem roots are made
+    /// absolute so they still resolve against the manifest directory.
+    pub fn fan_out(&self, manifest_dir: &Path) -> Vec<(String, Manifest)> {
+        let mut targets: Vec<(String, Manifest)> = Vec::new();
+        for entry in &self.entries {
+            let entry_targets = if !entry.targets.is_empty() {
+                &entry.targets
+            } else if !self.targets.is_empty() {
+                &self.targets
+            } else {
+                &vec![".".to_string()]
+            };
+            for target in entry_targets {
+                let index = match targets.iter().position(|(t, _)| t == target) {
+                    Some(index) => index,
+                    None => {
+                        targets.push((
+                            target.clone(),
+                            Manifest {
+                                entries: Vec::new(),
+                                targets: Vec::new(),
+                                ..self.clone()
+                            },
+                        ));
+                        targets.len() - 1
+                    }
+                };
+                let mut entry = entry.clone();
+                entry.targets.clear();
+                for source in entry.source.iter_mut().chain(entry.sources.iter_mut()) {
+                    anchor_filesystem_root(source, manifest_dir);
+                }
+                targets[index].1.entries.push(entry);
+            }
+        }
+        targets
+    }
+
+    /// Resolve `dest_roots` into each entry's default destination
+    pub fn apply_dest_roots(&mut self) {
+        if self.dest_roots.is_empty() {
+            return;
+        }
+        let defaults: Vec<PathBuf> = self
+            .entries
+            .iter()
+            .map(|e| self.default_dest(&e.kind))
+            .collect();
+        for (entry, default_dest) in self.entries.iter_mut().zip(defaults) {
+            entry.default_dest_override = Some(default_dest);
+        }
+    }
+}
+
+fn expand_dest_root(root: &str) -> String {
+    shellexpand::full(root)
+        .map(|s| s.into_owned())
+        .unwrap_or_else(|_| root.to_string())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Make a relative filesystem root absolute, anchored at the manifest directory
+fn anchor_filesystem_root(source: &mut Source, manifest_dir: &Path) {
+    if let Source::Filesystem { root, .. } = source {
+        if Path::new(root.as_str()).is_relative() && !root.starts_with(['$', '~']) {
+            *root = manifest_dir
+                .join(root.as_str())
+                .to_string_lossy()
+                .to_string();
+        }
+    }
+}
+
+/// Editor integrations that register synced directories with editors
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct Integrations {
+    /// Register synced rules and skills directories in `.vscode/settings.json`
+    /// (read by both Cursor and VS Code)
+    #[serde(default)]
+    pub cursor: bool,
+}
+
+impl Integrations {
+    /// Check if no integrations are enabled
+    pub fn is_empty(&self) -> bool {
+        !self.cursor
+    }
+}
+
+/// Settings for the CI profile
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default, PartialEq, Eq)]
+pub struct CiSettings {
+    /// Keep symlinked sources as symlinks in CI instead of installing copies
+    #[serde(default)]
+    pub symlink: bool,
+}
+
+impl CiSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Limits for composed files such as AGENTS.md. Exceeding one is a warning
+/// (`W012`), or an error with `--strict`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct ComposeLimits {
+    /// Largest composed file, in bytes
+    pub max_bytes: Option<usize>,
+    /// Most lines in a composed file
+    pub max_lines: Option<usize>,
+    /// Deepest heading level allowed, e.g. 3 for `###`
+    pub max_heading_depth: Option<usize>,
+    /// Warn when two partials contain a section with the same title
+    pub duplicate_sections: bool,
+}
+
+impl Default for ComposeLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: Some(32 * 1024),
+            max_lines: None,
+            max_heading_depth: None,
+            duplicate_sections: true,
+        }
+    }
+}
+
+impl ComposeLimits {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
 /// A single entry in the manifest
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct Entry {
     /// Unique identifier for this entry
     pub id: String,
@@ -33,6 +326,14 @@ pub struct Entry {
     /// The kind of asset
     pub kind: AssetKind,
 
+    /// Why this entry exists, shown in list, status, and the catalog
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Who maintains this entry (a person, team, or contact)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+
     /// The source to sync from (for single-source entries)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source: Option<Source>,
@@ -48,14 +349,147 @@ pub struct Entry {
     /// Optional list of prefixes to filter which files/folders to sync
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub include: Vec<String>,
+
+    /// Globs for volatile files that are installed but left out of change detection
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checksum_ignore: Vec<String>,
+
+    /// Per-entry override of the manifest-level `max_age` (days)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<u32>,
+
+    /// Keep source file modes when copying (set to false to apply the umask default)
+    #[serde(
+        default = "default_preserve_permissions",
+        skip_serializing_if = "is_default_preserve_permissions"
+    )]
+    pub preserve_permissions: bool,
+
+    /// Keep source modification times when copying
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub preserve_mtime: bool,
+
+    /// Per-entry override of the manifest-level `backup` setting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup: Option<bool>,
+
+    /// Per-entry override of the manifest-level `backup_dir`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup_dir: Option<String>,
+
+    /// Per-entry override of the manifest-level `symlink_style`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symlink_style: Option<SymlinkStyle>,
+
+    /// Copy the upstream license file alongside the installed asset
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub copy_license: bool,
+
+    /// Other tools (`claude`, `cursor`) whose conventional directory should
+    /// also get this asset, as a symlink to the installed destination
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub also_install_for: Vec<String>,
+
+    /// Keep the locked commit through `--upgrade`; only `aps sync --upgrade
+    /// --force --only <id>` moves a frozen entry
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub frozen: bool,
+
+    /// How a synced hooks.json is combined with the project's existing one
+    #[serde(default, skip_serializing_if = "HooksMergeStrategy::is_default")]
+    pub hooks_merge: HooksMergeStrategy,
+
+    /// Project directories to install this entry into, overriding the
+    /// manifest's `targets`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+
+    /// Content transformations applied, in order, to copied files
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "serde_yaml::with::singleton_map_recursive"
+    )]
+    #[schemars(with = "Vec<Transform>")]
+    pub transform: Vec<Transform>,
+
+    /// Warning codes (such as `W001`) to leave out of output for this entry
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppress_warnings: Vec<WarningCode>,
+
+    /// Default destination from the manifest's `dest_roots`, set when loading
+    #[serde(skip)]
+    pub default_dest_override: Option<PathBuf>,
+}
+
+/// Strategy for combining a source hooks.json with the project's copy
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HooksMergeStrategy {
+    /// Union hook arrays; on conflicting values the source wins
+    #[default]
+    Merge,
+    /// Union hook arrays; on conflicting values the local copy wins
+    PreferLocal,
+    /// Overwrite the project's hooks.json with the source
+    Replace,
+}
+
+impl HooksMergeStrategy {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// How symlinked installs refer to their sources
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SymlinkStyle {
+    /// Link to the source path as resolved
+    #[default]
+    Absolute,
+    /// Link by a path relative to the symlink's directory, so the links keep
+    /// working when the project and its sources move together
+    Relative,
+}
+
+impl SymlinkStyle {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Where conflicting content goes before aps overwrites or deletes it
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupStrategy {
+    /// Copy the file or directory to a timestamped path under `.aps-backups/`
+    #[default]
+    Copy,
+    /// Record a restore point whose files are stored once, by content, under
+    /// `.aps-backups/objects/`
+    Store,
+}
+
+impl BackupStrategy {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 impl Entry {
+    /// Checksum of the entry as written in the manifest, to notice edits
+    pub fn checksum(&self) -> String {
+        compute_string_checksum(&serde_yaml::to_string(self).unwrap_or_default())
+    }
+
     /// Create an example entry for the default manifest
     fn example() -> Self {
         Self {
             id: "my-agents".to_string(),
             kind: AssetKind::AgentsMd,
+            description: None,
+            owner: None,
             source: Some(Source::Filesystem {
                 root: "../shared-assets".to_string(),
                 symlink: true,
@@ -64,6 +498,21 @@ impl Entry {
             sources: Vec::new(),
             dest: None,
             include: Vec::new(),
+            checksum_ignore: Vec::new(),
+            max_age: None,
+            preserve_permissions: true,
+            preserve_mtime: false,
+            copy_license: false,
+            also_install_for: Vec::new(),
+            frozen: false,
+            hooks_merge: Default::default(),
+            targets: Vec::new(),
+            transform: Vec::new(),
+            default_dest_override: None,
+            suppress_warnings: Vec::new(),
+            backup: None,
+            backup_dir: None,
+            symlink_style: None,
         }
     }
 
@@ -72,6 +521,54 @@ impl Entry {
         self.kind == AssetKind::CompositeAgentsMd && !self.sources.is_empty()
     }
 
+    /// Whether any composite source is rendered from other installed entries
+    pub fn has_generated_sources(&self) -> bool {
+        self.sources
+            .iter()
+            .any(|s| matches!(s, Source::Generated { .. }))
+    }
+
+    /// Mirror destinations from `also_install_for`: the destination with its
+    /// tool directory swapped for each other tool's (e.g. `.claude/skills/x`
+    /// becomes `.cursor/skills/x`). The entry's own tool is skipped.
+    pub fn mirror_destinations(&self) -> Result<Vec<PathBuf>> {
+        if self.also_install_for.is_empty() {
+            return Ok(Vec::new());
+        }
+        let invalid = |message: String| ApsError::InvalidMirror {
+            id: self.id.clone(),
+            message,
+        };
+
+        let dest = normalize_dest(&self.destination());
+        let mut components = dest.components();
+        let first = components.next().map(|c| c.as_os_str().to_os_string());
+        let rest = components.as_path();
+        let own_tool = MIRROR_TOOLS
+            .iter()
+            .find(|(_, dir)| first.as_deref() == Some(std::ffi::OsStr::new(dir)))
+            .map(|(tool, _)| *tool)
+            .ok_or_else(|| {
+                invalid(format!(
+                    "destination {} is not inside a tool directory",
+                    dest.display()
+                ))
+            })?;
+
+        let mut mirrors = Vec::new();
+        for tool in &self.also_install_for {
+            let (_, dir) = MIRROR_TOOLS
+                .iter()
+                .find(|(name, _)| name == tool)
+                .ok_or_else(|| invalid(format!("unknown tool '{}'", tool)))?;
+            let mirror = Path::new(dir).join(rest);
+            if *tool != own_tool && !mirrors.contains(&mirror) {
+                mirrors.push(mirror);
+            }
+        }
+        Ok(mirrors)
+    }
+
     /// Get the destination path for this entry (with shell variable expansion)
     pub fn destination(&self) -> PathBuf {
         if let Some(ref dest) = self.dest {
@@ -79,14 +576,19 @@ impl Entry {
                 .map(|s| s.into_owned())
                 .unwrap_or_else(|_| dest.clone());
             PathBuf::from(expanded)
+        } else if let Some(ref default_dest) = self.default_dest_override {
+            default_dest.clone()
         } else {
             self.kind.default_dest()
         }
     }
 }
 
+/// Tools `also_install_for` can mirror into, with their conventional directories
+pub const MIRROR_TOOLS: [(&str, &str); 2] = [("claude", ".claude"), ("cursor", ".cursor")];
+
 /// Asset kinds supported by APS
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum AssetKind {
     /// Cursor rules directory
@@ -101,9 +603,44 @@ pub enum AssetKind {
     AgentSkill,
     /// Composite AGENTS.md - merge multiple markdown files into one
     CompositeAgentsMd,
+    /// Directory of persona markdown files, merged into the destination
+    Persona,
 }
 
 impl AssetKind {
+    /// Tool whose directory holds this kind by default (the `dest_roots` tool key)
+    pub fn tool(&self) -> Option<&'static str> {
+        match self {
+            AssetKind::CursorRules | AssetKind::CursorHooks | AssetKind::CursorSkillsRoot => {
+                Some("cursor")
+            }
+            AssetKind::AgentSkill => Some("claude"),
+            AssetKind::AgentsMd | AssetKind::CompositeAgentsMd | AssetKind::Persona => None,
+        }
+    }
+
+    /// Whether copies are merged into an existing destination directory
+    /// rather than replacing it, so project-local files are kept
+    pub fn merges_into_dest(&self) -> bool {
+        matches!(self, AssetKind::CursorHooks | AssetKind::Persona)
+    }
+
+    /// Whether entries of this kind install a single file
+    pub fn installs_single_file(&self) -> bool {
+        matches!(self, AssetKind::AgentsMd | AssetKind::CompositeAgentsMd)
+    }
+
+    /// Every asset kind
+    pub const ALL: [AssetKind; 7] = [
+        AssetKind::CursorRules,
+        AssetKind::CursorHooks,
+        AssetKind::CursorSkillsRoot,
+        AssetKind::AgentsMd,
+        AssetKind::AgentSkill,
+        AssetKind::CompositeAgentsMd,
+        AssetKind::Persona,
+    ];
+
     /// Get the default destination for this asset kind
     pub fn default_dest(&self) -> PathBuf {
         match self {
@@ -113,11 +650,24 @@ impl AssetKind {
             AssetKind::AgentsMd => PathBuf::from("AGENTS.md"),
             AssetKind::AgentSkill => PathBuf::from(".claude/skills"),
             AssetKind::CompositeAgentsMd => PathBuf::from("AGENTS.md"),
+            AssetKind::Persona => PathBuf::from(".ai/personas"),
         }
     }
 
-    /// Check if this is a valid kind string (for future use)
-    #[allow(dead_code)]
+    /// The manifest name of this kind
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssetKind::CursorRules => "cursor_rules",
+            AssetKind::CursorHooks => "cursor_hooks",
+            AssetKind::CursorSkillsRoot => "cursor_skills_root",
+            AssetKind::AgentsMd => "agents_md",
+            AssetKind::AgentSkill => "agent_skill",
+            AssetKind::CompositeAgentsMd => "composite_agents_md",
+            AssetKind::Persona => "persona",
+        }
+    }
+
+    /// Parse a kind from its manifest name
     pub fn from_str(s: &str) -> Result<Self> {
         match s {
             "cursor_rules" => Ok(AssetKind::CursorRules),
@@ -126,6 +676,7 @@ impl AssetKind {
             "agents_md" => Ok(AssetKind::AgentsMd),
             "agent_skill" => Ok(AssetKind::AgentSkill),
             "composite_agents_md" => Ok(AssetKind::CompositeAgentsMd),
+            "persona" => Ok(AssetKind::Persona),
             _ => Err(ApsError::InvalidAssetKind {
                 kind: s.to_string(),
             }),
@@ -134,7 +685,7 @@ impl AssetKind {
 }
 
 /// Source types for syncing assets
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum Source {
     /// Git repository source
@@ -142,13 +693,15 @@ pub enum Source {
         /// Repository URL (SSH or HTTPS)
         #[serde(alias = "url")]
         repo: String,
-        /// Git ref (branch, tag, commit) - "auto" tries main then master
+        /// Git ref (branch, tag, commit) - "auto" follows the remote's default
+        /// branch, and "release:latest" or "release:^1" the newest matching release
         #[serde(default = "default_ref")]
         r#ref: String,
         /// Whether to use shallow clone
         #[serde(default = "default_shallow")]
         shallow: bool,
-        /// Optional path within the repository
+        /// Optional path within the repository; may end in a glob
+        /// (`skills/*`) to install each match by name
         #[serde(default)]
         path: Option<String>,
     },
@@ -159,10 +712,55 @@ pub enum Source {
         /// Whether to create symlinks instead of copying files (default: true)
         #[serde(default = "default_symlink")]
         symlink: bool,
-        /// Optional path within the root directory
+        /// Optional path within the root directory; may end in a glob
         #[serde(default)]
         path: Option<String>,
     },
+    /// Raw file fetched over HTTP(S)
+    Http {
+        /// URL of the file
+        url: String,
+        /// Extra request headers, such as `Authorization`. Values may refer
+        /// to environment variables (`Bearer ${TOKEN}`) so secrets stay out
+        /// of the manifest.
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        headers: BTreeMap<String, String>,
+    },
+    /// An entry of another APS-managed project, resolved through that
+    /// project's manifest
+    Aps {
+        /// Project directory (relative to this manifest) or git repository URL
+        project: String,
+        /// ID of the entry in the project's manifest
+        entry: String,
+        /// Git ref when `project` is a repository - "auto" follows the remote's default branch
+        #[serde(default = "default_ref")]
+        r#ref: String,
+        /// Manifest file within the project (default: aps.yaml)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        manifest: Option<String>,
+    },
+    /// Content rendered by aps when a composite entry is composed
+    Generated {
+        /// What to render
+        generator: Generator,
+    },
+}
+
+/// Renderers for `type: generated` sources
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Generator {
+    /// A list of the installed skills with their descriptions
+    SkillsIndex,
+}
+
+impl Generator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Generator::SkillsIndex => "skills_index",
+        }
+    }
 }
 
 fn default_ref() -> String {
@@ -177,6 +775,22 @@ fn default_symlink() -> bool {
     true
 }
 
+fn default_preserve_permissions() -> bool {
+    true
+}
+
+fn default_backup() -> bool {
+    true
+}
+
+fn is_default_backup(backup: &bool) -> bool {
+    *backup
+}
+
+fn is_default_preserve_permissions(value: &bool) -> bool {
+    *value
+}
+
 impl Source {
     /// Convert this Source to a SourceAdapter implementation
     pub fn to_adapter(&self) -> Box<dyn SourceAdapter> {
@@ -197,6 +811,21 @@ impl Source {
                 symlink,
                 path,
             } => Box::new(FilesystemSource::new(root.clone(), *symlink, path.clone())),
+            Source::Http { url, headers } => {
+                Box::new(HttpSource::new(url.clone(), headers.clone()))
+            }
+            Source::Aps {
+                project,
+                entry,
+                r#ref,
+                manifest,
+            } => Box::new(ApsSource::new(
+                project.clone(),
+                entry.clone(),
+                r#ref.clone(),
+                manifest.clone(),
+            )),
+            Source::Generated { generator } => Box::new(GeneratedSource::new(*generator)),
         }
     }
 
@@ -204,7 +833,10 @@ impl Source {
     pub fn git_info(&self) -> Option<(&str, &str)> {
         match self {
             Source::Git { repo, r#ref, .. } => Some((repo.as_str(), r#ref.as_str())),
-            Source::Filesystem { .. } => None,
+            Source::Filesystem { .. }
+            | Source::Http { .. }
+            | Source::Aps { .. }
+            | Source::Generated { .. } => None,
         }
     }
 
@@ -212,8 +844,66 @@ impl Source {
     pub fn git_path(&self) -> Option<&str> {
         match self {
             Source::Git { path, .. } => path.as_deref(),
-            Source::Filesystem { .. } => None,
+            Source::Filesystem { .. }
+            | Source::Http { .. }
+            | Source::Aps { .. }
+            | Source::Generated { .. } => None,
+        }
+    }
+
+    /// The source a `--override` value stands for: `git+<url>[@<ref>]`, or
+    /// a local directory. It keeps the `path` (and, for directories, the
+    /// `symlink` setting) of the source it replaces, so a fork with the same
+    /// layout can stand in for the original.
+    pub fn from_override(spec: &str, replacing: Option<&Source>) -> Result<Source> {
+        let invalid = |reason: &str| ApsError::InvalidOverride {
+            value: spec.to_string(),
+            reason: reason.to_string(),
+        };
+        let (path, symlink) = match replacing {
+            Some(Source::Git { path, .. }) => (path.clone(), default_symlink()),
+            Some(Source::Filesystem { path, symlink, .. }) => (path.clone(), *symlink),
+            _ => (None, default_symlink()),
+        };
+
+        if let Some(url) = spec.strip_prefix("git+") {
+            // The ref follows the last `@`, unless that `@` belongs to the
+            // user part of the URL (`git@host:...`, `https://user@host/...`)
+            let after_scheme =
+                |s: &str| s.split_once("://").map_or(s, |(_, rest)| rest).to_string();
+            let (repo, git_ref) = match url.rsplit_once('@') {
+                Some((repo, git_ref))
+                    if !git_ref.is_empty()
+                        && !git_ref.contains(':')
+                        && after_scheme(repo).contains(['/', ':']) =>
+                {
+                    (repo, git_ref.to_string())
+                }
+                _ => (url, default_ref()),
+            };
+            if repo.is_empty() {
+                return Err(invalid("expected a repository URL after `git+`"));
+            }
+            return Ok(Source::Git {
+                repo: repo.to_string(),
+                r#ref: git_ref,
+                shallow: default_shallow(),
+                path,
+            });
+        }
+
+        if spec.is_empty() {
+            return Err(invalid("expected `git+<url>[@<ref>]` or a local directory"));
         }
+        let root = std::path::absolute(spec)
+            .map_err(|e| invalid(&e.to_string()))?
+            .to_string_lossy()
+            .to_string();
+        Ok(Source::Filesystem {
+            root,
+            symlink,
+            path,
+        })
     }
 
     /// Get a display-friendly path string that preserves shell variables like $HOME
@@ -234,11 +924,15 @@ impl Source {
                     root.clone()
                 }
             }
+            Source::Http { url, .. } => url.clone(),
+            Source::Aps { project, entry, .. } => format!("aps:{}#{}", project, entry),
+            Source::Generated { generator } => format!("generated:{}", generator.as_str()),
         }
     }
 }
 
-/// Discover and load a manifest
+/// Discover and load a manifest, applying its `github_tarballs` setting to
+/// the process
 pub fn discover_manifest(override_path: Option<&Path>) -> Result<(Manifest, PathBuf)> {
     let manifest_path = if let Some(path) = override_path {
         debug!("Using manifest from --manifest flag: {:?}", path);
@@ -248,7 +942,9 @@ pub fn discover_manifest(override_path: Option<&Path>) -> Result<(Manifest, Path
     };
 
     info!("Loading manifest from {:?}", manifest_path);
-    load_manifest(&manifest_path).map(|m| (m, manifest_path))
+    let manifest = load_manifest(&manifest_path)?;
+    set_github_tarballs(manifest.github_tarballs);
+    Ok((manifest, manifest_path))
 }
 
 /// Walk up from CWD to find a manifest file
@@ -289,19 +985,78 @@ fn find_manifest_walk_up() -> Result<PathBuf> {
 pub fn load_manifest(path: &Path) -> Result<Manifest> {
     let content = std::fs::read_to_string(path)
         .map_err(|e| ApsError::io(e, format!("Failed to read manifest at {:?}", path)))?;
+    parse_manifest(&content)
+}
 
-    let manifest: Manifest =
-        serde_yaml::from_str(&content).map_err(|e| ApsError::ManifestParseError {
+/// Parse manifest content read from a file
+pub fn parse_manifest(content: &str) -> Result<Manifest> {
+    let mut manifest: Manifest =
+        serde_yaml::from_str(content).map_err(|e| ApsError::ManifestParseError {
             message: e.to_string(),
         })?;
+    manifest.apply_dest_roots();
 
     Ok(manifest)
 }
 
+/// Checksum of a manifest file's content, recorded in the lockfile at sync
+pub fn manifest_checksum(path: &Path) -> Result<String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ApsError::io(e, format!("Failed to read manifest at {:?}", path)))?;
+    Ok(compute_string_checksum(&content))
+}
+
+/// Order in which `aps sort` lists entries: by kind, then by ID
+fn entry_order(a: &Entry, b: &Entry) -> std::cmp::Ordering {
+    (a.kind.as_str(), &a.id).cmp(&(b.kind.as_str(), &b.id))
+}
+
+/// Sort entries by kind, then ID. Returns whether the order changed.
+pub fn sort_entries(entries: &mut [Entry]) -> bool {
+    let sorted = entries.is_sorted_by(|a, b| entry_order(a, b).is_le());
+    entries.sort_by(entry_order);
+    !sorted
+}
+
+/// Insert an entry before the first entry that sorts after it, so a sorted
+/// list stays sorted and an unsorted one gains it in a predictable place
+pub fn insert_sorted(entries: &mut Vec<Entry>, entry: Entry) {
+    let index = entries
+        .iter()
+        .position(|e| entry_order(e, &entry).is_gt())
+        .unwrap_or(entries.len());
+    entries.insert(index, entry);
+}
+
+/// Serialize and write a manifest to disk
+pub fn save_manifest(manifest: &Manifest, path: &Path) -> Result<()> {
+    let content = serde_yaml::to_string(manifest).map_err(|e| ApsError::ManifestParseError {
+        message: format!("Failed to serialize manifest: {}", e),
+    })?;
+
+    std::fs::write(path, &content)
+        .map_err(|e| ApsError::io(e, format!("Failed to write manifest to {:?}", path)))?;
+
+    info!("Saved manifest to {:?}", path);
+    Ok(())
+}
+
 /// Validate a manifest for schema correctness
 pub fn validate_manifest(manifest: &Manifest) -> Result<()> {
     let mut seen_ids = HashSet::new();
 
+    for key in manifest.dest_roots.keys() {
+        if AssetKind::from_str(key).is_err() && key != "cursor" && key != "claude" {
+            return Err(ApsError::InvalidDestRoot { key: key.clone() });
+        }
+    }
+
+    if manifest.targets.iter().any(|t| t.trim().is_empty()) {
+        return Err(ApsError::InvalidInput {
+            message: "Manifest `targets` contains an empty path".to_string(),
+        });
+    }
+
     for entry in &manifest.entries {
         // Check for duplicate IDs
         if !seen_ids.insert(&entry.id) {
@@ -310,6 +1065,28 @@ pub fn validate_manifest(manifest: &Manifest) -> Result<()> {
             });
         }
 
+        build_ignore_set(&entry.checksum_ignore)?;
+        entry.mirror_destinations()?;
+
+        for transform in &entry.transform {
+            transform.validate()?;
+        }
+        if !entry.transform.is_empty() && entry.is_composite() {
+            return Err(ApsError::InvalidTransform {
+                transform: entry.transform[0].label(),
+                reason: format!(
+                    "entry '{}' is composite; transforms apply to copied sources",
+                    entry.id
+                ),
+            });
+        }
+
+        if entry.targets.iter().any(|t| t.trim().is_empty()) {
+            return Err(ApsError::InvalidInput {
+                message: format!("Entry '{}' has an empty target", entry.id),
+            });
+        }
+
         // Validate source configuration based on kind
         if entry.kind == AssetKind::CompositeAgentsMd {
             // Composite entries require sources array
@@ -320,10 +1097,25 @@ pub fn validate_manifest(manifest: &Manifest) -> Result<()> {
             }
         } else {
             // Non-composite entries require single source
-            if entry.source.is_none() {
-                return Err(ApsError::EntryRequiresSource {
-                    id: entry.id.clone(),
-                });
+            match &entry.source {
+                None => {
+                    return Err(ApsError::EntryRequiresSource {
+                        id: entry.id.clone(),
+                    })
+                }
+                Some(Source::Generated { generator }) => {
+                    return Err(ApsError::GeneratedSourceNotComposite {
+                        generator: generator.as_str().to_string(),
+                    })
+                }
+                // A fetched body is a single file, so it can't fill a directory
+                Some(Source::Http { url, .. }) if !entry.kind.installs_single_file() => {
+                    return Err(ApsError::HttpSourceNotSingleFile {
+                        url: url.clone(),
+                        kind: entry.kind.as_str().to_string(),
+                    })
+                }
+                Some(_) => {}
             }
         }
     }
@@ -332,6 +1124,135 @@ pub fn validate_manifest(manifest: &Manifest) -> Result<()> {
     Ok(())
 }
 
+/// A manifest setting that aps ignores, such as `include` on an entry that
+/// installs a single file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestLint {
+    /// ID of the entry the setting belongs to
+    pub id: String,
+    /// The setting, e.g. `include` or `source.symlink`
+    pub field: String,
+    /// Why the setting has no effect
+    pub message: String,
+}
+
+impl ManifestLint {
+    fn new(id: &str, field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            id: id.to_string(),
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    /// The error `aps validate --strict` reports for this setting
+    pub fn into_error(self) -> ApsError {
+        ApsError::IneffectiveSetting {
+            id: self.id,
+            field: self.field,
+            message: self.message,
+        }
+    }
+}
+
+impl std::fmt::Display for ManifestLint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Entry '{}': `{}` {}", self.id, self.field, self.message)
+    }
+}
+
+/// Find settings that parse but have no effect. `raw` is the manifest's YAML,
+/// which still holds source keys that deserialization drops (such as
+/// `symlink` on a git source).
+pub fn lint_manifest(manifest: &Manifest, raw: &str) -> Vec<ManifestLint> {
+    let mut lints = Vec::new();
+
+    for entry in &manifest.entries {
+        if !entry.include.is_empty() && entry.kind.installs_single_file() {
+            lints.push(ManifestLint::new(
+                &entry.id,
+                "include",
+                format!(
+                    "has no effect on {} entries, which install a single file",
+                    entry.kind.as_str()
+                ),
+            ));
+        }
+        if entry.is_composite() && entry.source.is_some() {
+            lints.push(ManifestLint::new(
+                &entry.id,
+                "source",
+                "is ignored; composite entries read `sources`",
+            ));
+        }
+        if !entry.is_composite() && !entry.sources.is_empty() {
+            lints.push(ManifestLint::new(
+                &entry.id,
+                "sources",
+                format!(
+                    "is ignored; only composite_agents_md entries compose sources, and {} entries read `source`",
+                    entry.kind.as_str()
+                ),
+            ));
+        }
+    }
+
+    // Keys the typed model silently drops
+    let Ok(value) = serde_yaml::from_str::<serde_yaml::Value>(raw) else {
+        return lints;
+    };
+    let entries = value.get("entries").and_then(|e| e.as_sequence());
+    for entry in entries.into_iter().flatten() {
+        let id = entry.get("id").and_then(|id| id.as_str()).unwrap_or("?");
+        if let Some(source) = entry.get("source") {
+            lint_source_keys(id, "source", source, &mut lints);
+        }
+        let sources = entry.get("sources").and_then(|s| s.as_sequence());
+        for (i, source) in sources.into_iter().flatten().enumerate() {
+            lint_source_keys(id, &format!("sources[{}]", i), source, &mut lints);
+        }
+    }
+
+    lints
+}
+
+/// Report keys that the source's type does not read
+fn lint_source_keys(
+    id: &str,
+    field: &str,
+    source: &serde_yaml::Value,
+    lints: &mut Vec<ManifestLint>,
+) {
+    let Some(map) = source.as_mapping() else {
+        return;
+    };
+    let Some(source_type) = map.get("type").and_then(|t| t.as_str()) else {
+        return;
+    };
+    let known: &[&str] = match source_type {
+        "git" => &["repo", "url", "ref", "shallow", "path"],
+        "filesystem" => &["root", "symlink", "path"],
+        "http" => &["url"],
+        "aps" => &["project", "entry", "ref", "manifest"],
+        "generated" => &["generator"],
+        _ => return,
+    };
+    for key in map.keys().filter_map(|k| k.as_str()) {
+        if key == "type" || known.contains(&key) {
+            continue;
+        }
+        let message = if key == "symlink" && matches!(source_type, "git" | "http") {
+            format!(
+                "has no effect on {} sources, which are always copied",
+                source_type
+            )
+        } else {
+            format!("is not read by {} sources and is ignored", source_type)
+        };
+        lints.push(ManifestLint::new(id, format!("{}.{}", field, key), message));
+    }
+}
+
 /// Normalize a destination path by stripping `./` prefix and trailing slashes
 /// so that `./.claude/skills/foo/` and `.claude/skills/foo` compare equal.
 fn normalize_dest(path: &Path) -> PathBuf {
@@ -342,15 +1263,15 @@ fn normalize_dest(path: &Path) -> PathBuf {
 }
 
 /// Detect entries that write to overlapping destination paths.
-/// Returns a list of human-readable warning strings.
+/// Returns a list of human-readable warning strings, leaving out overlaps
+/// whose entries all suppress `W002`.
 pub fn detect_overlapping_destinations(manifest: &Manifest) -> Vec<String> {
     let mut warnings = Vec::new();
 
     // Build a map of effective destination paths to entry IDs.
     // An entry with `include` filters produces sub-paths like `dest/included_item`.
     // An entry without `include` writes to `dest` directly.
-    let mut dest_to_entries: std::collections::BTreeMap<PathBuf, Vec<&str>> =
-        std::collections::BTreeMap::new();
+    let mut dest_to_entries: BTreeMap<PathBuf, Vec<&str>> = BTreeMap::new();
 
     for entry in &manifest.entries {
         let base_dest = normalize_dest(&entry.destination());
@@ -371,19 +1292,92 @@ pub fn detect_overlapping_destinations(manifest: &Manifest) -> Vec<String> {
         }
     }
 
+    let reported = |ids: &[&str]| {
+        !ids.iter().all(|id| {
+            manifest
+                .suppressed_warnings(id)
+                .contains(WarningCode::OverlappingDestination)
+        })
+    };
+    let overlap =
+        |message: String| Warning::new(WarningCode::OverlappingDestination, message).to_string();
+
     for (dest, ids) in &dest_to_entries {
-        if ids.len() > 1 {
-            warnings.push(format!(
+        if ids.len() > 1 && reported(ids) {
+            warnings.push(overlap(format!(
                 "Entries [{}] write to the same destination '{}'; the last entry wins",
                 ids.join(", "),
                 dest.display()
-            ));
+            )));
         }
     }
 
+    for collision in case_collisions(&dest_to_entries) {
+        let ids: Vec<&str> = collision.ids.iter().map(String::as_str).collect();
+        if !reported(&ids) {
+            continue;
+        }
+        warnings.push(overlap(format!(
+            "Entries [{}] write to destinations that differ only in case ({}); \
+             they overwrite each other on case-insensitive filesystems such as macOS and Windows",
+            collision.ids.join(", "),
+            collision.dests_display()
+        )));
+    }
+
     warnings
 }
 
+/// Destinations that are distinct paths but equal when compared without case
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseCollision {
+    pub ids: Vec<String>,
+    pub dests: Vec<PathBuf>,
+}
+
+impl CaseCollision {
+    /// The colliding destinations, quoted and comma-separated
+    pub fn dests_display(&self) -> String {
+        self.dests
+            .iter()
+            .map(|d| format!("'{}'", d.display()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Detect entries whose destinations differ only in case
+pub fn detect_case_collisions(manifest: &Manifest) -> Vec<CaseCollision> {
+    let mut dest_to_entries: BTreeMap<PathBuf, Vec<&str>> = BTreeMap::new();
+    for entry in &manifest.entries {
+        dest_to_entries
+            .entry(normalize_dest(&entry.destination()))
+            .or_default()
+            .push(&entry.id);
+    }
+    case_collisions(&dest_to_entries)
+}
+
+/// Group destinations that are equal ignoring case, keeping groups of
+/// more than one distinct path
+fn case_collisions(dest_to_entries: &BTreeMap<PathBuf, Vec<&str>>) -> Vec<CaseCollision> {
+    let mut folded: BTreeMap<String, CaseCollision> = BTreeMap::new();
+    for (dest, ids) in dest_to_entries {
+        let collision = folded
+            .entry(dest.to_string_lossy().to_lowercase())
+            .or_insert_with(|| CaseCollision {
+                ids: Vec::new(),
+                dests: Vec::new(),
+            });
+        collision.ids.extend(ids.iter().map(|id| id.to_string()));
+        collision.dests.push(dest.clone());
+    }
+    folded
+        .into_values()
+        .filter(|collision| collision.dests.len() > 1)
+        .collect()
+}
+
 /// Get the manifest directory (for resolving relative paths)
 pub fn manifest_dir(manifest_path: &Path) -> PathBuf {
     manifest_path
@@ -409,11 +1403,173 @@ mod tests {
             sources: Vec::new(),
             dest: None,
             include: Vec::new(),
+            max_age: None,
+            preserve_permissions: true,
+            preserve_mtime: false,
+            hooks_merge: Default::default(),
+            default_dest_override: None,
+            checksum_ignore: Vec::new(),
+            description: None,
+            owner: None,
+            copy_license: false,
+            also_install_for: Vec::new(),
+            frozen: false,
+            targets: Vec::new(),
+            transform: Vec::new(),
+            suppress_warnings: Vec::new(),
+            backup: None,
+            backup_dir: None,
+            symlink_style: None,
         };
 
         assert_eq!(entry.destination(), PathBuf::from("AGENTS.md"));
     }
 
+    #[test]
+    fn test_source_from_override() {
+        let original = Source::Git {
+            repo: "https://github.com/org/skills.git".to_string(),
+            r#ref: "main".to_string(),
+            shallow: true,
+            path: Some("skills/refactor".to_string()),
+        };
+        let git = |spec: &str| match Source::from_override(spec, Some(&original)).unwrap() {
+            Source::Git {
+                repo, r#ref, path, ..
+            } => (repo, r#ref, path),
+            other => panic!("expected a git source, got {:?}", other),
+        };
+
+        let (repo, git_ref, path) = git("git+https://github.com/me/fork.git@feature/x");
+        assert_eq!(repo, "https://github.com/me/fork.git");
+        assert_eq!(git_ref, "feature/x");
+        assert_eq!(path.as_deref(), Some("skills/refactor"));
+        assert_eq!(
+            git("git+git@github.com:me/fork.git"),
+            (
+                "git@github.com:me/fork.git".to_string(),
+                "auto".to_string(),
+                Some("skills/refactor".to_string())
+            )
+        );
+        assert_eq!(git("git+git@github.com:me/fork.git@v2").1, "v2");
+        assert_eq!(git("git+https://user@example.com/fork.git").1, "auto");
+        assert!(Source::from_override("git+", None).is_err());
+
+        match Source::from_override("/tmp/fork", Some(&original)).unwrap() {
+            Source::Filesystem { root, path, .. } => {
+                assert_eq!(root, "/tmp/fork");
+                assert_eq!(path.as_deref(), Some("skills/refactor"));
+            }
+            other => panic!("expected a filesystem source, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sort_entries_by_kind_then_id() {
+        let yaml = r#"
+entries:
+  - id: zeta
+    kind: cursor_rules
+    source: { type: filesystem, root: . }
+  - id: beta
+    kind: agent_skill
+    source: { type: filesystem, root: . }
+  - id: alpha
+    kind: cursor_rules
+    source: { type: filesystem, root: . }
+"#;
+        let manifest: Manifest = serde_yaml::from_str(yaml).unwrap();
+        let ids = |entries: &[Entry]| entries.iter().map(|e| e.id.clone()).collect::<Vec<_>>();
+
+        let mut entries = manifest.entries.clone();
+        assert!(sort_entries(&mut entries));
+        assert_eq!(ids(&entries), ["beta", "alpha", "zeta"]);
+        assert!(!sort_entries(&mut entries));
+
+        let mut added = entries[1].clone();
+        added.id = "mid".to_string();
+        insert_sorted(&mut entries, added);
+        assert_eq!(ids(&entries), ["beta", "alpha", "mid", "zeta"]);
+    }
+
+    #[test]
+    fn test_dest_roots_override_default_dest() {
+        let yaml = r#"
+dest_roots:
+  claude: .ai
+  cursor_rules: rules
+entries:
+  - id: skills
+    kind: agent_skill
+    source:
+      type: filesystem
+      root: .
+  - id: rules
+    kind: cursor_rules
+    source:
+      type: filesystem
+      root: .
+  - id: hooks
+    kind: cursor_hooks
+    source:
+      type: filesystem
+      root: .
+  - id: pinned
+    kind: agent_skill
+    dest: .claude/skills/pinned
+    source:
+      type: filesystem
+      root: .
+"#;
+        let mut manifest: Manifest = serde_yaml::from_str(yaml).unwrap();
+        manifest.apply_dest_roots();
+        validate_manifest(&manifest).unwrap();
+
+        let dests: Vec<PathBuf> = manifest.entries.iter().map(|e| e.destination()).collect();
+        assert_eq!(
+            dests,
+            vec![
+                PathBuf::from(".ai/skills"),
+                PathBuf::from("rules"),
+                PathBuf::from(".cursor/hooks"),
+                PathBuf::from(".claude/skills/pinned"),
+            ]
+        );
+        assert!(!serde_yaml::to_string(&manifest)
+            .unwrap()
+            .contains("default_dest_override"));
+    }
+
+    #[test]
+    fn test_dest_roots_rejects_unknown_key() {
+        let mut manifest = Manifest {
+            entries: Vec::new(),
+            integrations: Default::default(),
+            max_age: None,
+            dest_roots: BTreeMap::new(),
+            backup_strategy: Default::default(),
+            targets: Vec::new(),
+            check_uncommitted: false,
+            suppress_warnings: Vec::new(),
+            ci: Default::default(),
+            backup: true,
+            backup_dir: None,
+            symlink_style: Default::default(),
+            lockfiles: Default::default(),
+            sort: false,
+            compose_limits: ComposeLimits::default(),
+            github_tarballs: false,
+        };
+        manifest
+            .dest_roots
+            .insert("vscode".to_string(), ".ai".to_string());
+        assert!(matches!(
+            validate_manifest(&manifest),
+            Err(ApsError::InvalidDestRoot { .. })
+        ));
+    }
+
     #[test]
     fn test_entry_destination_custom() {
         let entry = Entry {
@@ -427,6 +1583,23 @@ mod tests {
             sources: Vec::new(),
             dest: Some("custom/path/AGENTS.md".to_string()),
             include: Vec::new(),
+            max_age: None,
+            preserve_permissions: true,
+            preserve_mtime: false,
+            hooks_merge: Default::default(),
+            default_dest_override: None,
+            checksum_ignore: Vec::new(),
+            description: None,
+            owner: None,
+            copy_license: false,
+            also_install_for: Vec::new(),
+            frozen: false,
+            targets: Vec::new(),
+            transform: Vec::new(),
+            suppress_warnings: Vec::new(),
+            backup: None,
+            backup_dir: None,
+            symlink_style: None,
         };
 
         assert_eq!(entry.destination(), PathBuf::from("custom/path/AGENTS.md"));
@@ -447,6 +1620,23 @@ mod tests {
             sources: Vec::new(),
             dest: Some("$TEST_DEST_VAR/AGENTS.md".to_string()),
             include: Vec::new(),
+            max_age: None,
+            preserve_permissions: true,
+            preserve_mtime: false,
+            hooks_merge: Default::default(),
+            default_dest_override: None,
+            checksum_ignore: Vec::new(),
+            description: None,
+            owner: None,
+            copy_license: false,
+            also_install_for: Vec::new(),
+            frozen: false,
+            targets: Vec::new(),
+            transform: Vec::new(),
+            suppress_warnings: Vec::new(),
+            backup: None,
+            backup_dir: None,
+            symlink_style: None,
         };
 
         assert_eq!(entry.destination(), PathBuf::from("/custom/dest/AGENTS.md"));
@@ -467,6 +1657,23 @@ mod tests {
             sources: Vec::new(),
             dest: Some("~/agents/AGENTS.md".to_string()),
             include: Vec::new(),
+            max_age: None,
+            preserve_permissions: true,
+            preserve_mtime: false,
+            hooks_merge: Default::default(),
+            default_dest_override: None,
+            checksum_ignore: Vec::new(),
+            description: None,
+            owner: None,
+            copy_license: false,
+            also_install_for: Vec::new(),
+            frozen: false,
+            targets: Vec::new(),
+            transform: Vec::new(),
+            suppress_warnings: Vec::new(),
+            backup: None,
+            backup_dir: None,
+            symlink_style: None,
         };
 
         let result = entry.destination();
@@ -495,6 +1702,23 @@ mod tests {
             ],
             dest: None,
             include: Vec::new(),
+            max_age: None,
+            preserve_permissions: true,
+            preserve_mtime: false,
+            hooks_merge: Default::default(),
+            default_dest_override: None,
+            checksum_ignore: Vec::new(),
+            description: None,
+            owner: None,
+            copy_license: false,
+            also_install_for: Vec::new(),
+            frozen: false,
+            targets: Vec::new(),
+            transform: Vec::new(),
+            suppress_warnings: Vec::new(),
+            backup: None,
+            backup_dir: None,
+            symlink_style: None,
         };
 
         assert!(entry.is_composite());
@@ -531,6 +1755,23 @@ mod tests {
             ],
             dest: Some("./AGENTS.md".to_string()),
             include: Vec::new(),
+            max_age: None,
+            preserve_permissions: true,
+            preserve_mtime: false,
+            hooks_merge: Default::default(),
+            default_dest_override: None,
+            checksum_ignore: Vec::new(),
+            description: None,
+            owner: None,
+            copy_license: false,
+            also_install_for: Vec::new(),
+            frozen: false,
+            targets: Vec::new(),
+            transform: Vec::new(),
+            suppress_warnings: Vec::new(),
+            backup: None,
+            backup_dir: None,
+            symlink_style: None,
         };
 
         assert!(entry.is_composite());
@@ -560,6 +1801,23 @@ mod tests {
                     sources: Vec::new(),
                     dest: Some(".claude/skills/".to_string()),
                     include: vec!["skill-creator".to_string()],
+                    max_age: None,
+                    preserve_permissions: true,
+                    preserve_mtime: false,
+                    hooks_merge: Default::default(),
+                    default_dest_override: None,
+                    checksum_ignore: Vec::new(),
+                    description: None,
+                    owner: None,
+                    copy_license: false,
+                    also_install_for: Vec::new(),
+                    frozen: false,
+                    targets: Vec::new(),
+                    transform: Vec::new(),
+                    suppress_warnings: Vec::new(),
+                    backup: None,
+                    backup_dir: None,
+                    symlink_style: None,
                 },
                 Entry {
                     id: "skill-creator".to_string(),
@@ -573,8 +1831,40 @@ mod tests {
                     sources: Vec::new(),
                     dest: Some(".claude/skills/skill-creator/".to_string()),
                     include: Vec::new(),
+                    max_age: None,
+                    preserve_permissions: true,
+                    preserve_mtime: false,
+                    hooks_merge: Default::default(),
+                    default_dest_override: None,
+                    checksum_ignore: Vec::new(),
+                    description: None,
+                    owner: None,
+                    copy_license: false,
+                    also_install_for: Vec::new(),
+                    frozen: false,
+                    targets: Vec::new(),
+                    transform: Vec::new(),
+                    suppress_warnings: Vec::new(),
+                    backup: None,
+                    backup_dir: None,
+                    symlink_style: None,
                 },
             ],
+            integrations: Integrations::default(),
+            max_age: None,
+            dest_roots: Default::default(),
+            backup_strategy: Default::default(),
+            targets: Vec::new(),
+            check_uncommitted: false,
+            suppress_warnings: Vec::new(),
+            ci: Default::default(),
+            backup: true,
+            backup_dir: None,
+            symlink_style: Default::default(),
+            lockfiles: Default::default(),
+            sort: false,
+            compose_limits: ComposeLimits::default(),
+            github_tarballs: false,
         };
 
         let warnings = detect_overlapping_destinations(&manifest);
@@ -583,6 +1873,147 @@ mod tests {
         assert!(warnings[0].contains("skill-creator"));
     }
 
+    #[test]
+    fn test_detect_destinations_differing_only_in_case() {
+        let mut upper = Entry::example();
+        upper.id = "lint-upper".to_string();
+        upper.dest = Some("./.claude/skills/Lint/".to_string());
+        let mut lower = Entry::example();
+        lower.id = "lint-lower".to_string();
+        lower.dest = Some(".claude/skills/lint".to_string());
+        let mut other = Entry::example();
+        other.id = "other".to_string();
+        other.dest = Some(".claude/skills/other".to_string());
+        let manifest = Manifest {
+            entries: vec![upper, lower, other],
+            ..Manifest::default()
+        };
+
+        let collisions = detect_case_collisions(&manifest);
+        assert_eq!(
+            collisions,
+            vec![CaseCollision {
+                ids: vec!["lint-upper".to_string(), "lint-lower".to_string()],
+                dests: vec![
+                    PathBuf::from(".claude/skills/Lint"),
+                    PathBuf::from(".claude/skills/lint")
+                ],
+            }]
+        );
+
+        let warnings = detect_overlapping_destinations(&manifest);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("differ only in case"));
+    }
+
+    #[test]
+    fn test_lint_flags_ineffective_settings() {
+        let yaml = r#"entries:
+  - id: guide
+    kind: agents_md
+    source:
+      type: git
+      repo: https://github.com/org/guides.git
+      symlink: true
+      path: AGENTS.md
+    sources:
+      - type: filesystem
+        root: .
+    include:
+      - intro
+  - id: rules
+    kind: cursor_rules
+    source:
+      type: filesystem
+      root: .
+      symlink: false
+"#;
+        let manifest: Manifest = serde_yaml::from_str(yaml).unwrap();
+        validate_manifest(&manifest).unwrap();
+        let fields: Vec<String> = lint_manifest(&manifest, yaml)
+            .into_iter()
+            .map(|lint| format!("{}:{}", lint.id, lint.field))
+            .collect();
+        assert_eq!(
+            fields,
+            vec!["guide:include", "guide:sources", "guide:source.symlink"]
+        );
+    }
+
+    #[test]
+    fn test_transform_list_round_trips() {
+        let yaml = r#"entries:
+  - id: rules
+    kind: cursor_rules
+    source:
+      type: filesystem
+      root: .
+    transform:
+      - add_mdc_frontmatter
+      - rename_extension: md->mdc
+"#;
+        let manifest: Manifest = serde_yaml::from_str(yaml).unwrap();
+        validate_manifest(&manifest).unwrap();
+        assert_eq!(
+            manifest.entries[0].transform,
+            vec![
+                Transform::AddMdcFrontmatter,
+                Transform::RenameExtension("md->mdc".to_string())
+            ]
+        );
+        assert!(serde_yaml::to_string(&manifest)
+            .unwrap()
+            .contains("  - add_mdc_frontmatter\n  - rename_extension: md->mdc\n"));
+
+        let invalid: Manifest = serde_yaml::from_str(&yaml.replace("md->mdc", "mdc")).unwrap();
+        assert!(matches!(
+            validate_manifest(&invalid),
+            Err(ApsError::InvalidTransform { .. })
+        ));
+    }
+
+    #[test]
+    fn test_fan_out_groups_entries_by_target() {
+        let shared = Entry::example();
+        let mut only_web = Entry::example();
+        only_web.id = "web-only".to_string();
+        only_web.targets = vec!["apps/web".to_string()];
+        let manifest = Manifest {
+            entries: vec![shared, only_web],
+            targets: vec!["apps/api".to_string(), "apps/web".to_string()],
+            ..Manifest::default()
+        };
+        assert!(manifest.has_targets());
+
+        let split = manifest.fan_out(Path::new("/repo"));
+        let summary: Vec<(&str, Vec<&str>)> = split
+            .iter()
+            .map(|(target, m)| {
+                (
+                    target.as_str(),
+                    m.entries.iter().map(|e| e.id.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("apps/api", vec!["my-agents"]),
+                ("apps/web", vec!["my-agents", "web-only"]),
+            ]
+        );
+
+        let (_, web) = &split[1];
+        assert!(web.targets.is_empty());
+        assert!(web.entries.iter().all(|e| e.targets.is_empty()));
+        match &web.entries[0].source {
+            Some(Source::Filesystem { root, .. }) => {
+                assert_eq!(Path::new(root), Path::new("/repo/../shared-assets"))
+            }
+            other => panic!("unexpected source: {other:?}"),
+        }
+    }
+
     #[test]
     fn test_no_overlap_different_destinations() {
         let manifest = Manifest {
@@ -598,6 +2029,23 @@ mod tests {
                     sources: Vec::new(),
                     dest: Some(".claude/skills/a/".to_string()),
                     include: Vec::new(),
+                    max_age: None,
+                    preserve_permissions: true,
+                    preserve_mtime: false,
+                    hooks_merge: Default::default(),
+                    default_dest_override: None,
+                    checksum_ignore: Vec::new(),
+                    description: None,
+                    owner: None,
+                    copy_license: false,
+                    also_install_for: Vec::new(),
+                    frozen: false,
+                    targets: Vec::new(),
+                    transform: Vec::new(),
+                    suppress_warnings: Vec::new(),
+                    backup: None,
+                    backup_dir: None,
+                    symlink_style: None,
                 },
                 Entry {
                     id: "skill-b".to_string(),
@@ -610,11 +2058,67 @@ mod tests {
                     sources: Vec::new(),
                     dest: Some(".claude/skills/b/".to_string()),
                     include: Vec::new(),
+                    max_age: None,
+                    preserve_permissions: true,
+                    preserve_mtime: false,
+                    hooks_merge: Default::default(),
+                    default_dest_override: None,
+                    checksum_ignore: Vec::new(),
+                    description: None,
+                    owner: None,
+                    copy_license: false,
+                    also_install_for: Vec::new(),
+                    frozen: false,
+                    targets: Vec::new(),
+                    transform: Vec::new(),
+                    suppress_warnings: Vec::new(),
+                    backup: None,
+                    backup_dir: None,
+                    symlink_style: None,
                 },
             ],
+            integrations: Integrations::default(),
+            max_age: None,
+            dest_roots: Default::default(),
+            backup_strategy: Default::default(),
+            targets: Vec::new(),
+            check_uncommitted: false,
+            suppress_warnings: Vec::new(),
+            ci: Default::default(),
+            backup: true,
+            backup_dir: None,
+            symlink_style: Default::default(),
+            lockfiles: Default::default(),
+            sort: false,
+            compose_limits: ComposeLimits::default(),
+            github_tarballs: false,
         };
 
         let warnings = detect_overlapping_destinations(&manifest);
         assert!(warnings.is_empty());
     }
+
+    #[test]
+    fn test_mirror_destinations_swap_tool_directory() {
+        let entry = |dest: &str, tools: &str| -> Entry {
+            serde_yaml::from_str(&format!(
+                "id: review\nkind: agent_skill\ndest: {}\nalso_install_for: [{}]\n",
+                dest, tools
+            ))
+            .unwrap()
+        };
+
+        assert_eq!(
+            entry("./.claude/skills/review/", "cursor, claude")
+                .mirror_destinations()
+                .unwrap(),
+            vec![PathBuf::from(".cursor/skills/review")]
+        );
+        assert!(entry(".claude/skills/review", "windsurf")
+            .mirror_destinations()
+            .is_err());
+        assert!(entry("docs/review", "cursor")
+            .mirror_destinations()
+            .is_err());
+    }
 }
@@ -0,0 +1,170 @@
+//! Manifest edits made by `aps add` and `aps remove`.
+//!
+//! The manifest is read once, edited in memory, and written back only if the
+//! file still holds what was read, so an edit made meanwhile (by hand or by
+//! another aps process) is never overwritten. With `--show-diff` the change
+//! is printed as a line diff of the manifest as read and as edited, both
+//! serialized the same way, before it is written.
+
+use crate::color::style;
+use crate::error::{ApsError, Result};
+use crate::manifest::{parse_manifest, Manifest};
+use crate::output::say;
+use std::path::Path;
+
+/// Unchanged lines shown around each change
+const CONTEXT: usize = 2;
+
+/// One line of a diff
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Line diff of `old` and `new`, from their longest common subsequence
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // common[i][j]: length of the LCS of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+/// Print the changes from `old` to `new`, with a little context, colored
+/// when colors are on
+pub fn print_diff(path: &Path, old: &str, new: &str) {
+    let lines = diff_lines(old, new);
+    let near_change = |index: usize| {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(lines.len());
+        lines[start..end]
+            .iter()
+            .any(|line| !matches!(line, DiffLine::Same(_)))
+    };
+
+    say!("{}", style(format!("--- {}", path.display())).bold());
+    say!("{}", style(format!("+++ {}", path.display())).bold());
+    let mut skipped = false;
+    for (index, line) in lines.iter().enumerate() {
+        if !near_change(index) {
+            skipped = true;
+            continue;
+        }
+        if skipped {
+            say!("{}", style("  ⋮").dim());
+            skipped = false;
+        }
+        match line {
+            DiffLine::Same(text) => say!("  {}", text),
+            DiffLine::Removed(text) => say!("{}", style(format!("- {}", text)).red()),
+            DiffLine::Added(text) => say!("{}", style(format!("+ {}", text)).green()),
+        }
+    }
+    say!();
+}
+
+/// Write `manifest` over `path`, which held `original` when it was read.
+/// Fails without writing if the file has changed since.
+pub fn write_manifest_edit(
+    manifest: &Manifest,
+    path: &Path,
+    original: &str,
+    show_diff: bool,
+) -> Result<()> {
+    let content = serde_yaml::to_string(manifest).map_err(|e| ApsError::ManifestParseError {
+        message: format!("Failed to serialize manifest: {}", e),
+    })?;
+
+    let current = std::fs::read_to_string(path)
+        .map_err(|e| ApsError::io(e, format!("Failed to read manifest at {:?}", path)))?;
+    if current != original {
+        return Err(ApsError::ManifestChangedOnDisk {
+            path: path.to_path_buf(),
+        });
+    }
+
+    if show_diff {
+        // Compare the manifest as read, not the file text, so formatting and
+        // comments that serialization drops do not show up as changes
+        let before = serde_yaml::to_string(&parse_manifest(original)?).map_err(|e| {
+            ApsError::ManifestParseError {
+                message: format!("Failed to serialize manifest: {}", e),
+            }
+        })?;
+        print_diff(path, &before, &content);
+    }
+    std::fs::write(path, &content)
+        .map_err(|e| ApsError::io(e, format!("Failed to write manifest to {:?}", path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines_marks_added_and_removed_lines() {
+        let old = "entries:\n- id: a\n  kind: agent_skill\n- id: c\n";
+        let new = "entries:\n- id: a\n  kind: agent_skill\n- id: b\n- id: c\n";
+        assert_eq!(
+            diff_lines(old, new),
+            vec![
+                DiffLine::Same("entries:"),
+                DiffLine::Same("- id: a"),
+                DiffLine::Same("  kind: agent_skill"),
+                DiffLine::Added("- id: b"),
+                DiffLine::Same("- id: c"),
+            ]
+        );
+        assert_eq!(
+            diff_lines(new, old)
+                .iter()
+                .filter(|line| !matches!(line, DiffLine::Same(_)))
+                .collect::<Vec<_>>(),
+            vec![&DiffLine::Removed("- id: b")]
+        );
+    }
+
+    #[test]
+    fn test_write_manifest_edit_refuses_concurrent_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("aps.yaml");
+        let original = "entries: []\n";
+        std::fs::write(&path, "entries: []\n# edited meanwhile\n").unwrap();
+
+        let manifest: Manifest = serde_yaml::from_str(original).unwrap();
+        let err = write_manifest_edit(&manifest, &path, original, false).unwrap_err();
+        assert!(matches!(err, ApsError::ManifestChangedOnDisk { .. }));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "entries: []\n# edited meanwhile\n"
+        );
+    }
+}
@@ -1,13 +1,17 @@
-use crate::backup::create_backup;
+use crate::approval::Prompt;
+use crate::backup::{create_backup, BackupPolicy};
+use crate::ci::interactive;
+use crate::color::{style, Style};
 use crate::error::{ApsError, Result};
 use crate::install::InstallOptions;
-use crate::lockfile::Lockfile;
-use crate::manifest::Entry;
-use console::{style, Style};
+use crate::lockfile::{Lockfile, LOCKFILE_NAME};
+use crate::manifest::{Entry, DEFAULT_MANIFEST_NAME};
+use crate::output::say;
 use dialoguer::Confirm;
-use std::io::IsTerminal;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use tracing::{debug, info};
+use walkdir::WalkDir;
 
 /// Represents an orphaned path that was left behind when dest changed
 pub struct OrphanedPath {
@@ -25,6 +29,55 @@ pub fn detect_orphaned_paths(
     let mut orphans = Vec::new();
 
     for entry in entries {
+        // Mirrors no longer listed in `also_install_for` are orphans too
+        if let Some(locked_entry) = lockfile.entries.get(&entry.id) {
+            let current = entry.mirror_destinations().unwrap_or_default();
+            let new_dest = manifest_dir.join(entry.destination());
+            for mirror in &locked_entry.mirrors {
+                let old_dest = manifest_dir.join(mirror);
+                // Compared lexically: a mirror symlink resolves to the dest itself
+                if current.iter().any(|m| manifest_dir.join(m) == old_dest)
+                    || old_dest.symlink_metadata().is_err()
+                    || old_dest.starts_with(&new_dest)
+                    || new_dest.starts_with(&old_dest)
+                {
+                    continue;
+                }
+                info!(
+                    "Detected orphaned mirror for entry {}: {:?}",
+                    entry.id, old_dest
+                );
+                orphans.push(OrphanedPath {
+                    entry_id: entry.id.clone(),
+                    old_dest,
+                    new_dest: new_dest.clone(),
+                });
+            }
+        }
+
+        // Earlier destinations left in place by previous dest changes
+        if let Some(locked_entry) = lockfile.entries.get(&entry.id) {
+            let new_dest = manifest_dir.join(entry.destination());
+            for previous in &locked_entry.previous_dests {
+                let old_dest = manifest_dir.join(previous);
+                if old_dest.symlink_metadata().is_err()
+                    || paths_overlap(&old_dest, &new_dest)
+                    || orphans.iter().any(|o| o.old_dest == old_dest)
+                {
+                    continue;
+                }
+                info!(
+                    "Detected earlier destination of entry {}: {:?}",
+                    entry.id, old_dest
+                );
+                orphans.push(OrphanedPath {
+                    entry_id: entry.id.clone(),
+                    old_dest,
+                    new_dest: new_dest.clone(),
+                });
+            }
+        }
+
         // Check if this entry exists in the lockfile
         if let Some(locked_entry) = lockfile.entries.get(&entry.id) {
             // Lockfile stores relative paths, so join with manifest_dir to get absolute path
@@ -41,7 +94,7 @@ pub fn detect_orphaned_paths(
             );
 
             // Check if destinations are different
-            if old_normalized != new_normalized {
+            if old_normalized != new_normalized && !orphans.iter().any(|o| o.old_dest == old_dest) {
                 // Check if old path still exists
                 if old_dest.exists() || old_dest.symlink_metadata().is_ok() {
                     // Check if paths overlap (don't delete new dest!)
@@ -76,13 +129,152 @@ pub fn detect_orphaned_paths(
     orphans
 }
 
+/// Orphaned paths that are still on disk, relative to `manifest_dir`, by
+/// entry ID. These are kept in the lockfile so later syncs still find them.
+pub fn remaining_orphans(
+    orphans: &[OrphanedPath],
+    manifest_dir: &Path,
+) -> BTreeMap<String, Vec<String>> {
+    let mut remaining: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for orphan in orphans {
+        if orphan.old_dest.symlink_metadata().is_ok() {
+            let relative = orphan
+                .old_dest
+                .strip_prefix(manifest_dir)
+                .unwrap_or(&orphan.old_dest);
+            remaining
+                .entry(orphan.entry_id.clone())
+                .or_default()
+                .push(relative.to_string_lossy().to_string());
+        }
+    }
+    remaining
+}
+
+/// A destination recorded by another manifest's lockfile in the workspace
+pub struct ForeignOwner {
+    pub manifest: PathBuf,
+    pub entry_id: String,
+    pub dest: PathBuf,
+}
+
+/// How a lockfile refers to `manifest_path`: relative to the lockfile's
+/// directory, climbing with `..` when the manifest lives above it
+pub fn manifest_ref(manifest_path: &Path, lockfile_path: &Path) -> String {
+    let manifest = normalize_for_comparison(manifest_path);
+    let mut dir = normalize_for_comparison(lockfile_path.parent().unwrap_or(Path::new(".")));
+    let mut ups = PathBuf::new();
+    loop {
+        if let Ok(rest) = manifest.strip_prefix(&dir) {
+            return ups.join(rest).to_string_lossy().replace('\\', "/");
+        }
+        match dir.parent() {
+            Some(parent) => {
+                dir = parent.to_path_buf();
+                ups.push("..");
+            }
+            None => return manifest.to_string_lossy().to_string(),
+        }
+    }
+}
+
+/// Root of the workspace containing `manifest_dir`: the enclosing git work
+/// tree, or the manifest directory itself outside of one
+pub fn workspace_root(manifest_dir: &Path) -> PathBuf {
+    let start = normalize_for_comparison(manifest_dir);
+    start
+        .ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .unwrap_or(&start)
+        .to_path_buf()
+}
+
+/// Destinations installed by other manifests in the workspace, read from
+/// every lockfile under the workspace root except `lockfile_path` and those
+/// whose entries belong to `manifest_path`
+pub fn foreign_owners(manifest_path: &Path, lockfile_path: &Path) -> Vec<ForeignOwner> {
+    let own_manifest = normalize_for_comparison(manifest_path);
+    let own_lockfile = normalize_for_comparison(lockfile_path);
+    let root = workspace_root(manifest_path.parent().unwrap_or(Path::new(".")));
+
+    let mut owners = Vec::new();
+    let lockfiles = WalkDir::new(&root)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git" && e.file_name() != "node_modules")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.file_name() == LOCKFILE_NAME);
+    for found in lockfiles {
+        let path = found.path();
+        if normalize_for_comparison(path) == own_lockfile {
+            continue;
+        }
+        // Read quietly: another manifest's lockfile is only consulted, never saved
+        let parsed = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_yaml::from_str::<Lockfile>(&content).ok());
+        let Some(lockfile) = parsed else {
+            debug!("Skipping unreadable lockfile {:?}", path);
+            continue;
+        };
+        let dir = path.parent().unwrap_or(Path::new("."));
+        for (id, locked) in &lockfile.entries {
+            let manifest = normalize_for_comparison(
+                &dir.join(locked.manifest.as_deref().unwrap_or(DEFAULT_MANIFEST_NAME)),
+            );
+            if manifest == own_manifest {
+                continue;
+            }
+            for dest in std::iter::once(&locked.dest).chain(&locked.mirrors) {
+                owners.push(ForeignOwner {
+                    manifest: manifest.clone(),
+                    entry_id: id.clone(),
+                    dest: dir.join(dest),
+                });
+            }
+        }
+    }
+    owners
+}
+
+/// Drop orphans overlapping a path another manifest owns, reporting each one
+pub fn exclude_foreign_owned(
+    orphans: Vec<OrphanedPath>,
+    owners: &[ForeignOwner],
+    manifest_dir: &Path,
+) -> Vec<OrphanedPath> {
+    orphans
+        .into_iter()
+        .filter(|orphan| {
+            let Some(owner) = owners
+                .iter()
+                .find(|owner| paths_overlap(&orphan.old_dest, &owner.dest))
+            else {
+                return true;
+            };
+            let shown = orphan
+                .old_dest
+                .strip_prefix(manifest_dir)
+                .unwrap_or(&orphan.old_dest);
+            say!(
+                "{} Not deleting {} (from {}): it is owned by entry {} of {}",
+                style("Warning:").yellow(),
+                shown.display(),
+                style(&orphan.entry_id).cyan(),
+                style(&owner.entry_id).cyan(),
+                owner.manifest.display()
+            );
+            false
+        })
+        .collect()
+}
+
 /// Normalize a path for comparison by canonicalizing if possible
 fn normalize_for_comparison(path: &Path) -> PathBuf {
     path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
 }
 
 /// Check if two paths overlap (one is a prefix of the other)
-fn paths_overlap(path1: &Path, path2: &Path) -> bool {
+pub fn paths_overlap(path1: &Path, path2: &Path) -> bool {
     let p1 = normalize_for_comparison(path1);
     let p2 = normalize_for_comparison(path2);
 
@@ -192,33 +384,33 @@ pub fn prompt_and_cleanup_orphans(
     }
 
     // Print orphan list with highlighted diffs
-    println!();
-    println!(
+    say!();
+    say!(
         "Detected {} orphaned path(s) from destination changes:",
         orphans.len()
     );
     for orphan in orphans {
         let (old_formatted, new_formatted) = format_path_diff(&orphan.old_dest, &orphan.new_dest);
-        println!(
+        say!(
             "  {} {}",
             style("─").dim(),
             style(&orphan.entry_id).cyan().bold()
         );
-        println!("      {} {}", style("was:").red(), old_formatted);
-        println!("      {} {}", style("now:").green(), new_formatted);
+        say!("      {} {}", style("was:").red(), old_formatted);
+        say!("      {} {}", style("now:").green(), new_formatted);
     }
-    println!();
+    say!();
 
     // Handle dry-run mode
     if options.dry_run {
-        println!("[dry-run] Would delete {} orphaned path(s)", orphans.len());
+        say!("[dry-run] Would delete {} orphaned path(s)", orphans.len());
         return Ok(0);
     }
 
     // Determine whether to proceed with deletion
-    let should_delete = if options.yes {
+    let should_delete = if options.yes.approve(Prompt::Orphans) {
         true
-    } else if std::io::stdin().is_terminal() {
+    } else if interactive() {
         // Interactive prompt
         Confirm::new()
             .with_prompt(format!("Delete {} orphaned path(s)?", orphans.len()))
@@ -227,8 +419,8 @@ pub fn prompt_and_cleanup_orphans(
             .map_err(|_| ApsError::Cancelled)?
     } else {
         // Non-interactive without --yes flag
-        println!("Warning: Cannot delete orphaned paths without confirmation.");
-        println!("Run with --yes to auto-delete, or run interactively to confirm.");
+        say!("Warning: Cannot delete orphaned paths without confirmation.");
+        say!("Run with --yes (or --yes=orphans) to auto-delete, or run interactively to confirm.");
         return Ok(0);
     };
 
@@ -238,24 +430,25 @@ pub fn prompt_and_cleanup_orphans(
     }
 
     // Delete orphans
-    let mut deleted_count = 0;
+    let mut deleted = Vec::new();
     for orphan in orphans {
-        match delete_orphan(orphan, manifest_dir) {
+        match delete_orphan(orphan, manifest_dir, &options.backup) {
             Ok(()) => {
-                deleted_count += 1;
-                println!("Deleted orphaned path: {:?}", orphan.old_dest);
+                deleted.push(orphan.old_dest.clone());
+                say!("Deleted orphaned path: {:?}", orphan.old_dest);
             }
             Err(e) => {
-                println!("Warning: Failed to delete {:?}: {}", orphan.old_dest, e);
+                say!("Warning: Failed to delete {:?}: {}", orphan.old_dest, e);
             }
         }
     }
+    prune_and_report(&deleted, manifest_dir);
 
-    Ok(deleted_count)
+    Ok(deleted.len())
 }
 
 /// Delete a single orphaned path
-fn delete_orphan(orphan: &OrphanedPath, manifest_dir: &Path) -> Result<()> {
+fn delete_orphan(orphan: &OrphanedPath, manifest_dir: &Path, backup: &BackupPolicy) -> Result<()> {
     let path = &orphan.old_dest;
 
     // Check if it's a symlink
@@ -271,8 +464,7 @@ fn delete_orphan(orphan: &OrphanedPath, manifest_dir: &Path) -> Result<()> {
         debug!("Removed symlink at {:?}", path);
     } else if path.is_file() {
         // Regular file - backup first
-        let backup_path = create_backup(manifest_dir, path)?;
-        println!("  Backed up to: {:?}", backup_path);
+        report_backup(create_backup(manifest_dir, path, backup)?);
 
         std::fs::remove_file(path)
             .map_err(|e| ApsError::io(e, format!("Failed to remove file {:?}", path)))?;
@@ -286,8 +478,7 @@ fn delete_orphan(orphan: &OrphanedPath, manifest_dir: &Path) -> Result<()> {
             debug!("Removed aps-managed directory at {:?}", path);
         } else {
             // Directory with non-symlink content - backup first
-            let backup_path = create_backup(manifest_dir, path)?;
-            println!("  Backed up to: {:?}", backup_path);
+            report_backup(create_backup(manifest_dir, path, backup)?);
 
             std::fs::remove_dir_all(path)
                 .map_err(|e| ApsError::io(e, format!("Failed to remove directory {:?}", path)))?;
@@ -298,6 +489,13 @@ fn delete_orphan(orphan: &OrphanedPath, manifest_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+fn report_backup(backup_path: Option<PathBuf>) {
+    match backup_path {
+        Some(backup_path) => say!("  Backed up to: {:?}", backup_path),
+        None => say!("  Not backed up (backup: false)"),
+    }
+}
+
 /// Check if a directory contains only symlinks (indicating it was created by aps)
 fn is_aps_managed_directory(dir_path: &Path) -> bool {
     match std::fs::read_dir(dir_path) {
@@ -325,12 +523,66 @@ fn is_aps_managed_directory(dir_path: &Path) -> bool {
     }
 }
 
+/// Remove the directories above the deleted `removed` that are left empty,
+/// walking up to `root` but never removing it. Returns the directories
+/// removed, innermost first.
+pub fn prune_empty_parents(removed: &Path, root: &Path) -> Vec<PathBuf> {
+    let mut pruned = Vec::new();
+    let mut dir = removed.parent();
+    while let Some(current) = dir {
+        if current == root || !current.starts_with(root) {
+            break;
+        }
+        // Fails, and stops the walk, unless the directory is empty
+        if std::fs::remove_dir(current).is_err() {
+            break;
+        }
+        debug!("Pruned empty directory {:?}", current);
+        pruned.push(current.to_path_buf());
+        dir = current.parent();
+    }
+    pruned
+}
+
+/// Prune the empty directories above each of `removed` and report them
+pub fn prune_and_report(removed: &[PathBuf], root: &Path) {
+    for path in removed {
+        for dir in prune_empty_parents(path, root) {
+            let relative = dir.strip_prefix(root).unwrap_or(&dir);
+            say!("Pruned empty directory: {:?}", relative);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::fs;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_prune_empty_parents_stops_at_content_and_root() {
+        let root = tempdir().unwrap();
+        let removed = root.path().join(".claude/skills/old/refactor");
+        for dir in [".claude/agents", ".claude/skills/old", ".cursor/rules"] {
+            fs::create_dir_all(root.path().join(dir)).unwrap();
+        }
+
+        // .claude still holds agents/
+        assert_eq!(
+            prune_empty_parents(&removed, root.path()),
+            vec![
+                root.path().join(".claude/skills/old"),
+                root.path().join(".claude/skills"),
+            ]
+        );
+        assert!(root.path().join(".claude/agents").exists());
+
+        let removed = root.path().join(".cursor/rules/style.mdc");
+        assert_eq!(prune_empty_parents(&removed, root.path()).len(), 2);
+        assert!(root.path().exists());
+    }
+
     #[test]
     fn test_paths_overlap_same_path() {
         let path = PathBuf::from("/a/b/c");
@@ -352,6 +604,23 @@ mod tests {
         assert!(!paths_overlap(&path1, &path2));
     }
 
+    #[test]
+    fn test_manifest_ref_is_relative_to_lockfile() {
+        let temp = tempdir().unwrap();
+        let target = temp.path().join("packages/web");
+        fs::create_dir_all(&target).unwrap();
+        let manifest = temp.path().join("aps.yaml");
+
+        assert_eq!(
+            manifest_ref(&manifest, &temp.path().join("aps.lock.yaml")),
+            "aps.yaml"
+        );
+        assert_eq!(
+            manifest_ref(&manifest, &target.join("aps.lock.yaml")),
+            "../../aps.yaml"
+        );
+    }
+
     #[test]
     fn test_is_aps_managed_directory_empty() {
         let temp = tempdir().unwrap();
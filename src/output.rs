@@ -0,0 +1,107 @@
+//! Quiet and porcelain output modes.
+//!
+//! `-q/--quiet` suppresses all non-error output. Commands print through the
+//! [`say!`] and [`say_inline!`] macros below, which check the flag, so it
+//! need not be threaded through. Errors, prompts, and `eprintln!` are
+//! unaffected, as is anything printed with the standard `println!`.
+//!
+//! `--porcelain` implies quiet and prints one `<id>\t<status>\t<dest>` line
+//! per entry through [`porcelain_line`], which bypasses the quiet check. The
+//! format and status words are stable across versions.
+//!
+//! Commit SHAs and checksums are abbreviated in human output through
+//! [`short_id`]; `--full-ids` prints them in full. JSON output is not
+//! affected and always carries full values.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+static FULL_IDS: AtomicBool = AtomicBool::new(false);
+
+/// Hex digits of a commit SHA or checksum shown without `--full-ids`
+const SHORT_ID_LEN: usize = 8;
+
+/// Set the quiet and `--full-ids` preferences for this process
+pub fn init(quiet: bool, full_ids: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+    FULL_IDS.store(full_ids, Ordering::Relaxed);
+}
+
+/// Whether non-error output is suppressed
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// A commit SHA or checksum as shown to people: its first eight hex digits
+/// (after any `sha256:` prefix), or all of it with `--full-ids`
+pub fn short_id(id: &str) -> String {
+    if FULL_IDS.load(Ordering::Relaxed) {
+        return id.to_string();
+    }
+    let (prefix, digest) = match id.split_once(':') {
+        Some((algorithm, digest)) => (&id[..algorithm.len() + 1], digest),
+        None => ("", id),
+    };
+    let end = digest
+        .char_indices()
+        .nth(SHORT_ID_LEN)
+        .map_or(digest.len(), |(i, _)| i);
+    format!("{}{}", prefix, &digest[..end])
+}
+
+/// Print one porcelain line: `<id>\t<status>\t<dest>`
+pub fn porcelain_line(id: &str, status: &str, dest: &str) {
+    println!("{}\t{}\t{}", id, status, dest);
+}
+
+/// A destination as printed in porcelain and list output: `./`-prefixed when relative
+pub fn display_dest(dest: &str) -> String {
+    if dest.starts_with("./") || dest.starts_with('/') {
+        dest.to_string()
+    } else {
+        format!("./{}", dest)
+    }
+}
+
+/// `println!` unless `--quiet` is set
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if !$crate::output::quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+pub(crate) use say;
+
+/// `print!` unless `--quiet` is set
+macro_rules! say_inline {
+    ($($arg:tt)*) => {
+        if !$crate::output::quiet() {
+            print!($($arg)*);
+        }
+    };
+}
+pub(crate) use say_inline;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_dest() {
+        assert_eq!(display_dest(".claude/skills/a"), "./.claude/skills/a");
+        assert_eq!(display_dest("./AGENTS.md"), "./AGENTS.md");
+        assert_eq!(display_dest("/abs/rules"), "/abs/rules");
+    }
+
+    #[test]
+    fn test_short_id() {
+        assert_eq!(
+            short_id("0123456789abcdef0123456789abcdef01234567"),
+            "01234567"
+        );
+        assert_eq!(short_id("sha256:abcdef0123456789"), "sha256:abcdef01");
+        assert_eq!(short_id("abc"), "abc");
+    }
+}
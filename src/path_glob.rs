@@ -0,0 +1,135 @@
+//! Glob patterns in source paths (`path: skills/*`).
+//!
+//! A source `path` with a glob expands when the entry is installed: every
+//! match is installed under the entry's destination by its file name, so one
+//! entry can track all the skills in a folder and picks up new ones when its
+//! source is upgraded. The matches are recorded in the lockfile.
+
+use crate::error::{ApsError, Result};
+use globset::GlobBuilder;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Whether a source path contains glob syntax
+pub fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?', '[', '{'])
+}
+
+/// Split a glob path at its first segment with glob syntax, e.g.
+/// `skills/*` into (`skills`, `*`). The base is `.` when the glob starts
+/// the path.
+pub fn split_glob(path: &str) -> (String, String) {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let first = segments
+        .iter()
+        .position(|segment| is_glob(segment))
+        .unwrap_or(segments.len());
+    let base = segments[..first].join("/");
+    let pattern = segments[first..].join("/");
+    (
+        if base.is_empty() {
+            ".".to_string()
+        } else {
+            base
+        },
+        pattern,
+    )
+}
+
+/// The directory a source path names: the path itself, or for a glob, the
+/// part before its first glob segment
+pub fn glob_base(path: &str) -> String {
+    if is_glob(path) {
+        split_glob(path).0
+    } else {
+        path.to_string()
+    }
+}
+
+/// Paths under `base_dir` matching `pattern`, relative to it and sorted.
+/// `dirs_only` keeps directories (for skills). Fails for an invalid
+/// pattern, no matches, or two matches with the same file name, which would
+/// install over each other.
+pub fn expand(id: &str, base_dir: &Path, pattern: &str, dirs_only: bool) -> Result<Vec<PathBuf>> {
+    let invalid = |message: String| ApsError::InvalidSourceGlob {
+        id: id.to_string(),
+        pattern: pattern.to_string(),
+        message,
+    };
+    let matcher = GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .map_err(|e| invalid(e.kind().to_string()))?
+        .compile_matcher();
+    let depth = pattern.split('/').count();
+
+    let mut matches: Vec<PathBuf> = WalkDir::new(base_dir)
+        .min_depth(depth)
+        .max_depth(depth)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+        .filter(|e| !dirs_only || e.path().is_dir())
+        .filter_map(|e| e.path().strip_prefix(base_dir).ok().map(Path::to_path_buf))
+        .filter(|relative| matcher.is_match(relative))
+        .collect();
+    matches.sort();
+    if matches.is_empty() {
+        return Err(invalid("nothing in the source matches it".to_string()));
+    }
+
+    let mut names: Vec<_> = matches.iter().filter_map(|m| m.file_name()).collect();
+    names.sort();
+    if let Some(pair) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(invalid(format!(
+            "more than one match is named '{}'",
+            pair[0].to_string_lossy()
+        )));
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_split_glob() {
+        assert_eq!(
+            split_glob("skills/*"),
+            ("skills".to_string(), "*".to_string())
+        );
+        assert_eq!(
+            split_glob("packs/*/skills/review-*"),
+            ("packs".to_string(), "*/skills/review-*".to_string())
+        );
+        assert_eq!(split_glob("*.mdc"), (".".to_string(), "*.mdc".to_string()));
+    }
+
+    #[test]
+    fn test_expand_matches_and_rejects_name_clashes() {
+        let temp = tempdir().unwrap();
+        for dir in ["skills/review", "skills/refactor"] {
+            fs::create_dir_all(temp.path().join(dir)).unwrap();
+        }
+        fs::write(temp.path().join("skills/README.md"), "").unwrap();
+
+        assert_eq!(
+            expand("all", &temp.path().join("skills"), "*", true).unwrap(),
+            vec![PathBuf::from("refactor"), PathBuf::from("review")]
+        );
+        assert_eq!(
+            expand("all", &temp.path().join("skills"), "*", false)
+                .unwrap()
+                .len(),
+            3
+        );
+        assert!(expand("none", &temp.path().join("skills"), "lint-*", true).is_err());
+        // skills/review and packs/review would both install as review
+        fs::create_dir_all(temp.path().join("packs/review")).unwrap();
+        assert!(expand("clash", temp.path(), "*/review", true).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+//! Guards against source content that escapes its source or destination.
+//!
+//! Cloned repositories are untrusted input. A symlink in a source can point at
+//! any file on this machine, and a plain copy would dereference it into the
+//! project; a crafted relative path with `..` could write outside the
+//! destination.
+
+use crate::error::{ApsError, Result};
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A symlink inside a source whose target resolves outside that source
+#[derive(Debug, Clone, PartialEq)]
+pub struct EscapingLink {
+    pub link: PathBuf,
+    pub target: PathBuf,
+}
+
+impl EscapingLink {
+    pub fn to_error(&self) -> ApsError {
+        ApsError::UnsafeSourceLink {
+            link: self.link.clone(),
+            target: self.target.clone(),
+        }
+    }
+}
+
+/// Whether `path` resolves (following symlinks) to a location inside `root`.
+/// Paths that cannot be resolved, such as dangling symlinks, are not inside.
+pub fn is_within(root: &Path, path: &Path) -> bool {
+    match (root.canonicalize(), path.canonicalize()) {
+        (Ok(root), Ok(path)) => path.starts_with(root),
+        _ => false,
+    }
+}
+
+/// Fail if an existing `path` (such as a symlinked source `path` in a clone)
+/// resolves outside `root`
+pub fn ensure_within(root: &Path, path: &Path) -> Result<()> {
+    if path.exists() && !is_within(root, path) {
+        return Err(ApsError::UnsafeSourceLink {
+            link: path.to_path_buf(),
+            target: path.canonicalize().unwrap_or_default(),
+        });
+    }
+    Ok(())
+}
+
+/// Find symlinks under `source_root` whose targets resolve outside it
+pub fn find_escaping_links(source_root: &Path) -> Vec<EscapingLink> {
+    let mut links: Vec<EscapingLink> = WalkDir::new(source_root)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path_is_symlink())
+        .filter(|e| !is_within(source_root, e.path()))
+        .map(|e| {
+            let target = e
+                .path()
+                .canonicalize()
+                .or_else(|_| std::fs::read_link(e.path()))
+                .unwrap_or_default();
+            EscapingLink {
+                link: e.path().to_path_buf(),
+                target,
+            }
+        })
+        .collect();
+    links.sort_by(|a, b| a.link.cmp(&b.link));
+    links
+}
+
+/// Join a relative path onto `root`, rejecting absolute paths and `..`
+/// components that would place the result outside `root`
+pub fn safe_join(root: &Path, relative: &Path) -> Result<PathBuf> {
+    let escapes = relative.components().any(|c| {
+        matches!(
+            c,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_)
+        )
+    });
+    if escapes {
+        return Err(ApsError::UnsafePath {
+            path: relative.to_path_buf(),
+            root: root.to_path_buf(),
+        });
+    }
+    Ok(root.join(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_escaping_links_ignores_links_inside_source() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("source");
+        std::fs::create_dir_all(source.join("docs")).unwrap();
+        std::fs::write(source.join("docs/guide.md"), "guide").unwrap();
+        std::fs::write(temp.path().join("secret.txt"), "secret").unwrap();
+
+        std::os::unix::fs::symlink("docs/guide.md", source.join("inside.md")).unwrap();
+        std::os::unix::fs::symlink("../secret.txt", source.join("docs/outside.md")).unwrap();
+        std::os::unix::fs::symlink("/nonexistent/aps", source.join("dangling")).unwrap();
+
+        let links: Vec<PathBuf> = find_escaping_links(&source)
+            .into_iter()
+            .map(|l| l.link)
+            .collect();
+        assert_eq!(
+            links,
+            vec![source.join("dangling"), source.join("docs/outside.md")]
+        );
+    }
+
+    #[test]
+    fn test_safe_join_rejects_parent_and_absolute_components() {
+        let root = Path::new("/project/.claude/skills");
+        assert_eq!(
+            safe_join(root, Path::new("a/b.md")).unwrap(),
+            root.join("a/b.md")
+        );
+        assert!(safe_join(root, Path::new("../../etc/passwd")).is_err());
+        assert!(safe_join(root, Path::new("a/../../b")).is_err());
+        assert!(safe_join(root, Path::new("/etc/passwd")).is_err());
+    }
+}
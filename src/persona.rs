@@ -0,0 +1,137 @@
+//! Persona sets: directories of markdown files that each describe one persona.
+//!
+//! Every persona file carries YAML frontmatter with a `name` and a
+//! `description`, which the catalog lists and validation requires.
+
+use crate::discover::extract_frontmatter_field;
+use crate::error::{ApsError, Result};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Frontmatter fields every persona file must define
+pub const REQUIRED_FIELDS: [&str; 2] = ["name", "description"];
+
+/// Frontmatter read from a persona file
+#[derive(Debug, Default, PartialEq)]
+pub struct PersonaInfo {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+impl PersonaInfo {
+    /// Read the persona frontmatter from a markdown file
+    pub fn read(path: &Path) -> Self {
+        let content = std::fs::read_to_string(path).unwrap_or_default();
+        Self {
+            name: extract_frontmatter_field(&content, "name"),
+            description: extract_frontmatter_field(&content, "description"),
+        }
+    }
+
+    /// Required fields that are missing or empty
+    pub fn missing_fields(&self) -> Vec<&'static str> {
+        REQUIRED_FIELDS
+            .into_iter()
+            .zip([&self.name, &self.description])
+            .filter(|(_, value)| value.is_none())
+            .map(|(field, _)| field)
+            .collect()
+    }
+}
+
+/// Whether a file is a persona definition (markdown, other than a README)
+pub fn is_persona_file(path: &Path) -> bool {
+    let is_markdown = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("markdown"));
+    let is_readme = path
+        .file_stem()
+        .is_some_and(|stem| stem.eq_ignore_ascii_case("readme"));
+    is_markdown && !is_readme
+}
+
+/// Persona files under a directory, sorted for deterministic output
+pub fn find_persona_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| !e.path().components().any(|c| c.as_os_str() == ".git"))
+        .filter(|e| e.file_type().is_file() && is_persona_file(e.path()))
+        .map(|e| e.path().to_path_buf())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Check that a persona directory has personas and each defines the required
+/// frontmatter. Problems are warnings unless `strict` is set.
+pub fn validate_personas(dir: &Path, strict: bool) -> Result<Vec<String>> {
+    let mut warnings = Vec::new();
+
+    let files = find_persona_files(dir);
+    if files.is_empty() {
+        let error = ApsError::NoPersonasFound {
+            path: dir.to_path_buf(),
+        };
+        if strict {
+            return Err(error);
+        }
+        warnings.push(error.to_string());
+    }
+
+    for path in files {
+        let missing = PersonaInfo::read(&path).missing_fields();
+        if missing.is_empty() {
+            continue;
+        }
+        let error = ApsError::InvalidPersona {
+            path,
+            fields: missing.join(", "),
+        };
+        if strict {
+            return Err(error);
+        }
+        warnings.push(error.to_string());
+    }
+
+    Ok(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_validate_personas_reports_missing_frontmatter() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("reviewer.md"),
+            "---\nname: Reviewer\ndescription: Reviews pull requests\n---\n\nBody\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("planner.md"), "---\nname: Planner\n---\n").unwrap();
+        std::fs::write(dir.path().join("README.md"), "# Personas\n").unwrap();
+
+        let warnings = validate_personas(dir.path(), false).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("planner.md"));
+        assert!(warnings[0].contains("description"));
+
+        let err = validate_personas(dir.path(), true).unwrap_err();
+        assert!(matches!(err, ApsError::InvalidPersona { .. }));
+    }
+
+    #[test]
+    fn test_find_persona_files_skips_readme_and_other_files() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("team")).unwrap();
+        std::fs::write(dir.path().join("team/lead.md"), "").unwrap();
+        std::fs::write(dir.path().join("README.md"), "").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let files = find_persona_files(dir.path());
+        assert_eq!(files, vec![dir.path().join("team/lead.md")]);
+    }
+}
@@ -0,0 +1,365 @@
+//! Sync plans: a reviewable record of what `aps sync` would change.
+//!
+//! `aps plan` computes the changes for each entry and can save them as JSON.
+//! `aps sync --plan <file>` recomputes the plan and applies it only if nothing
+//! has drifted since it was written (manifest, lockfile, or planned changes).
+
+use crate::checksum::{compute_source_checksum, compute_string_checksum};
+use crate::color::Style;
+use crate::error::{ApsError, Result};
+use crate::install::compose_entry_sources;
+use crate::lockfile::Lockfile;
+use crate::manifest::{Entry, Manifest};
+use crate::output::{say, short_id};
+use crate::sources::get_remote_commit_sha;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Current plan file format version
+pub const PLAN_VERSION: u32 = 1;
+
+/// A serialized sync plan
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Plan {
+    /// Plan file format version
+    pub version: u32,
+    /// APS version that produced the plan
+    pub aps_version: String,
+    /// Checksum of the manifest the plan was computed from
+    pub manifest_checksum: String,
+    /// Checksum of the lockfile the plan was computed from (absent if none existed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lockfile_checksum: Option<String>,
+    /// Whether git sources are planned at their latest commits
+    #[serde(default)]
+    pub upgrade: bool,
+    /// Planned changes, in manifest order followed by removals
+    pub changes: Vec<PlannedChange>,
+}
+
+/// A single planned change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedChange {
+    pub id: String,
+    pub action: PlanAction,
+    pub reason: String,
+    /// Target git commit, when the change installs a specific commit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+}
+
+/// What a planned change does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanAction {
+    /// Entry has never been synced
+    Install,
+    /// Filesystem or composite source content changed
+    Update,
+    /// Git source has a newer commit (with `--upgrade`)
+    Upgrade,
+    /// Entry is locked but its destination is missing
+    Repair,
+    /// Entry is in the lockfile but no longer in the manifest
+    Remove,
+}
+
+impl std::fmt::Display for PlanAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            PlanAction::Install => "install",
+            PlanAction::Update => "update",
+            PlanAction::Upgrade => "upgrade",
+            PlanAction::Repair => "repair",
+            PlanAction::Remove => "remove",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl Plan {
+    /// Compute the plan for a manifest and its lockfile.
+    ///
+    /// Git sources are only queried when `upgrade` is set; otherwise locked
+    /// commits are assumed, exactly as `aps sync` does. Frozen entries always
+    /// keep their locked commits.
+    pub fn build(
+        manifest: &Manifest,
+        manifest_path: &Path,
+        base_dir: &Path,
+        lockfile: &Lockfile,
+        upgrade: bool,
+    ) -> Result<Self> {
+        let mut changes = Vec::new();
+
+        for entry in &manifest.entries {
+            let upgrade = upgrade && !entry.frozen;
+            if let Some(change) = plan_entry(entry, manifest, base_dir, lockfile, upgrade)? {
+                changes.push(change);
+            }
+        }
+
+        for id in lockfile.entries.keys() {
+            if !manifest.entries.iter().any(|e| &e.id == id) {
+                changes.push(PlannedChange {
+                    id: id.clone(),
+                    action: PlanAction::Remove,
+                    reason: "no longer in manifest".to_string(),
+                    commit: None,
+                });
+            }
+        }
+
+        Ok(Self {
+            version: PLAN_VERSION,
+            aps_version: env!("CARGO_PKG_VERSION").to_string(),
+            manifest_checksum: file_checksum(manifest_path)?.unwrap_or_default(),
+            lockfile_checksum: file_checksum(&Lockfile::path_for_manifest(
+                manifest_path,
+                manifest,
+            )?)?,
+            upgrade,
+            changes,
+        })
+    }
+
+    /// Load a plan file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ApsError::io(e, format!("Failed to read plan at {:?}", path)))?;
+        let plan: Plan = serde_json::from_str(&content).map_err(|e| ApsError::InvalidInput {
+            message: format!("Invalid plan file {:?}: {}", path, e),
+        })?;
+        if plan.version > PLAN_VERSION {
+            return Err(ApsError::InvalidInput {
+                message: format!(
+                    "Plan format v{} is newer than supported v{}; upgrade aps to apply it",
+                    plan.version, PLAN_VERSION
+                ),
+            });
+        }
+        Ok(plan)
+    }
+
+    /// Save the plan as JSON
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut content =
+            serde_json::to_string_pretty(self).map_err(|e| ApsError::InvalidInput {
+                message: format!("Failed to serialize plan: {}", e),
+            })?;
+        content.push('\n');
+        std::fs::write(path, content)
+            .map_err(|e| ApsError::io(e, format!("Failed to write plan to {:?}", path)))
+    }
+
+    /// Describe how `current` differs from this (saved) plan, if at all
+    pub fn drift_from(&self, current: &Plan) -> Option<String> {
+        if self.manifest_checksum != current.manifest_checksum {
+            return Some("the manifest changed".to_string());
+        }
+        if self.lockfile_checksum != current.lockfile_checksum {
+            return Some("the lockfile changed".to_string());
+        }
+        for change in &current.changes {
+            if !self.changes.contains(change) {
+                return Some(format!(
+                    "'{}' would now {} ({})",
+                    change.id, change.action, change.reason
+                ));
+            }
+        }
+        self.changes
+            .iter()
+            .find(|c| !current.changes.contains(c))
+            .map(|c| format!("planned {} of '{}' is no longer needed", c.action, c.id))
+    }
+}
+
+/// Plan a single manifest entry; `None` means it is up to date
+fn plan_entry(
+    entry: &Entry,
+    manifest: &Manifest,
+    base_dir: &Path,
+    lockfile: &Lockfile,
+    upgrade: bool,
+) -> Result<Option<PlannedChange>> {
+    let change = |action: PlanAction, reason: &str, commit: Option<String>| {
+        Some(PlannedChange {
+            id: entry.id.clone(),
+            action,
+            reason: reason.to_string(),
+            commit,
+        })
+    };
+
+    let locked = lockfile.entries.get(&entry.id);
+    let dest_exists = base_dir.join(entry.destination()).exists();
+    let git_info = entry.source.as_ref().and_then(|s| s.git_info());
+
+    let Some(locked) = locked else {
+        let commit = match git_info {
+            Some((repo, git_ref)) if upgrade => get_remote_commit_sha(repo, git_ref)?,
+            _ => None,
+        };
+        return Ok(change(PlanAction::Install, "not in lockfile", commit));
+    };
+
+    if let Some((repo, git_ref)) = git_info {
+        if upgrade {
+            if let Some(remote) = get_remote_commit_sha(repo, git_ref)? {
+                if locked.commit.as_deref() != Some(remote.as_str()) {
+                    let current = locked.commit.as_deref().unwrap_or("unlocked");
+                    let reason = format!("{} → {}", short_id(current), short_id(&remote));
+                    return Ok(change(PlanAction::Upgrade, &reason, Some(remote)));
+                }
+            }
+        }
+        if !dest_exists {
+            return Ok(change(
+                PlanAction::Repair,
+                "destination missing",
+                locked.commit.clone(),
+            ));
+        }
+        return Ok(None);
+    }
+
+    if !dest_exists {
+        return Ok(change(PlanAction::Repair, "destination missing", None));
+    }
+
+    let checksum = if entry.is_composite() {
+        let locked = (!upgrade).then_some(locked);
+        compute_string_checksum(
+            &compose_entry_sources(entry, manifest, base_dir, locked, false)?.content,
+        )
+    } else {
+        let source = entry
+            .source
+            .as_ref()
+            .ok_or_else(|| ApsError::EntryRequiresSource {
+                id: entry.id.clone(),
+            })?;
+        let resolved = source.to_adapter().resolve(base_dir)?;
+        if !resolved.source_path.exists() {
+            return Err(resolved.path_not_found());
+        }
+        compute_source_checksum(&resolved.source_path, &entry.checksum_ignore)?
+    };
+
+    if checksum != locked.checksum {
+        return Ok(change(PlanAction::Update, "source content changed", None));
+    }
+    Ok(None)
+}
+
+fn file_checksum(path: &Path) -> Result<Option<String>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ApsError::io(e, format!("Failed to read {:?}", path)))?;
+    Ok(Some(compute_string_checksum(&content)))
+}
+
+/// Print a plan in a terraform-like layout
+pub fn print_plan(plan: &Plan) {
+    if plan.changes.is_empty() {
+        say!("No changes. Everything is up to date.");
+        return;
+    }
+
+    let dim = Style::new().dim();
+    let max_id_len = plan.changes.iter().map(|c| c.id.len()).max().unwrap_or(0);
+
+    for change in &plan.changes {
+        let (badge, style) = match change.action {
+            PlanAction::Install => ("+", Style::new().green()),
+            PlanAction::Update => ("~", Style::new().yellow()),
+            PlanAction::Upgrade => ("↑", Style::new().color256(208)),
+            PlanAction::Repair => ("!", Style::new().yellow()),
+            PlanAction::Remove => ("-", Style::new().red()),
+        };
+        say!(
+            "  {} {:<width$} {:<8} {}",
+            style.apply_to(badge),
+            change.id,
+            style.apply_to(change.action),
+            dim.apply_to(&change.reason),
+            width = max_id_len,
+        );
+    }
+
+    let count = |action: PlanAction| plan.changes.iter().filter(|c| c.action == action).count();
+    let parts: Vec<String> = [
+        PlanAction::Install,
+        PlanAction::Update,
+        PlanAction::Upgrade,
+        PlanAction::Repair,
+        PlanAction::Remove,
+    ]
+    .into_iter()
+    .filter(|a| count(*a) > 0)
+    .map(|a| format!("{} to {}", count(a), a))
+    .collect();
+    say!("\nPlan: {}", parts.join(", "));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan_with(changes: Vec<PlannedChange>) -> Plan {
+        Plan {
+            version: PLAN_VERSION,
+            aps_version: "test".to_string(),
+            manifest_checksum: "sha256:m".to_string(),
+            lockfile_checksum: None,
+            upgrade: false,
+            changes,
+        }
+    }
+
+    fn install(id: &str) -> PlannedChange {
+        PlannedChange {
+            id: id.to_string(),
+            action: PlanAction::Install,
+            reason: "not in lockfile".to_string(),
+            commit: None,
+        }
+    }
+
+    #[test]
+    fn test_drift_detects_changed_inputs_and_actions() {
+        let saved = plan_with(vec![install("a")]);
+        assert_eq!(saved.drift_from(&plan_with(vec![install("a")])), None);
+
+        let mut edited = plan_with(vec![install("a")]);
+        edited.manifest_checksum = "sha256:other".to_string();
+        assert_eq!(
+            saved.drift_from(&edited),
+            Some("the manifest changed".to_string())
+        );
+
+        let extra = plan_with(vec![install("a"), install("b")]);
+        assert!(saved
+            .drift_from(&extra)
+            .unwrap()
+            .contains("'b' would now install"));
+
+        let done = plan_with(Vec::new());
+        assert!(saved
+            .drift_from(&done)
+            .unwrap()
+            .contains("no longer needed"));
+    }
+
+    #[test]
+    fn test_plan_round_trips_as_json() {
+        let plan = plan_with(vec![install("a")]);
+        let json = serde_json::to_string(&plan).unwrap();
+        assert!(json.contains("\"action\":\"install\""));
+        let parsed: Plan = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.changes, plan.changes);
+    }
+}
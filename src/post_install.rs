@@ -0,0 +1,148 @@
+//! Per-kind actions run after a sync changes entries of that kind.
+//!
+//! Editors do not always notice new rules or skills on their own. Under
+//! `post_install` in the user config, each asset kind can list actions to run
+//! once per sync when any entry of that kind was installed or updated:
+//! touching a marker file, printing instructions, or running a command in the
+//! project directory. They live in the user config rather than the manifest
+//! so a cloned project can never make aps run commands.
+
+use crate::color::style;
+use crate::manifest::AssetKind;
+use crate::output::say;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::process::Command;
+use std::time::SystemTime;
+
+/// One post-install action
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum PostInstallAction {
+    /// Create a file, relative to the project, or update its modification time
+    Touch(String),
+    /// Print a message, such as how to reload the editor
+    Message(String),
+    /// Run a shell command in the project directory
+    Run(String),
+}
+
+/// Run the actions configured for each kind in `changed` (kind, IDs of its
+/// entries that changed), in configuration order. A failing action is
+/// reported and the rest still run; none of them fails the sync.
+pub fn run_post_install(
+    actions: &BTreeMap<String, Vec<PostInstallAction>>,
+    changed: &[(AssetKind, Vec<String>)],
+    base_dir: &Path,
+) {
+    for (kind, ids) in changed {
+        let Some(kind_actions) = actions.get(kind.as_str()) else {
+            continue;
+        };
+        for action in kind_actions {
+            if let Err(message) = run_action(action, kind, ids, base_dir) {
+                say!(
+                    "{} post_install for {}: {}",
+                    style("Warning:").yellow(),
+                    kind.as_str(),
+                    message
+                );
+            }
+        }
+    }
+}
+
+fn run_action(
+    action: &PostInstallAction,
+    kind: &AssetKind,
+    ids: &[String],
+    base_dir: &Path,
+) -> std::result::Result<(), String> {
+    match action {
+        PostInstallAction::Touch(path) => {
+            let path = base_dir.join(path);
+            touch(&path).map_err(|e| format!("failed to touch {}: {}", path.display(), e))?;
+            say!(
+                "{} Touched {}",
+                style("→").cyan(),
+                path.strip_prefix(base_dir).unwrap_or(&path).display()
+            );
+        }
+        PostInstallAction::Message(message) => {
+            say!("{} {}", style("→").cyan(), message);
+        }
+        PostInstallAction::Run(command) => {
+            say!("{} Running {}", style("→").cyan(), style(command).dim());
+            let status = shell(command)
+                .current_dir(base_dir)
+                .env("APS_KIND", kind.as_str())
+                .env("APS_CHANGED_IDS", ids.join(","))
+                .status()
+                .map_err(|e| format!("failed to run `{}`: {}", command, e))?;
+            if !status.success() {
+                return Err(format!("`{}` exited with {}", command, status));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Create `path` (and its directory) or bump its modification time
+fn touch(path: &Path) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+    file.set_modified(SystemTime::now())
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_actions_run_only_for_changed_kinds() {
+        let project = tempdir().unwrap();
+        let actions = BTreeMap::from([
+            (
+                "cursor_rules".to_string(),
+                vec![PostInstallAction::Touch(
+                    ".cursor/rules/.aps-reload".to_string(),
+                )],
+            ),
+            (
+                "agent_skill".to_string(),
+                vec![PostInstallAction::Touch(".claude/.aps-reload".to_string())],
+            ),
+        ]);
+
+        run_post_install(
+            &actions,
+            &[(AssetKind::CursorRules, vec!["rules".to_string()])],
+            project.path(),
+        );
+        assert!(project.path().join(".cursor/rules/.aps-reload").exists());
+        assert!(!project.path().join(".claude/.aps-reload").exists());
+    }
+}
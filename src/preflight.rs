@@ -0,0 +1,174 @@
+//! Destination checks `aps sync` runs before it starts work.
+//!
+//! Every destination (and mirror) the selected entries install to is
+//! resolved up front. A destination that cannot be written, or a filesystem
+//! short on space, is reported together with all the others before any
+//! clone or copy begins, instead of failing the sync halfway through.
+
+use crate::catalog::{asset_size, format_size};
+use crate::error::{ApsError, Result};
+use crate::manifest::{Entry, Source};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Free space a filesystem must keep beyond what local copies need, since
+/// git sources are only sized once cloned
+const MIN_FREE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Check that every destination of `entries` can be written and that each
+/// filesystem they land on has room, reporting all problems at once
+pub fn check_destinations(entries: &[&Entry], base_dir: &Path) -> Result<()> {
+    let mut problems = Vec::new();
+    // Bytes needed per nearest existing directory, for the space check
+    let mut needed: BTreeMap<PathBuf, u64> = BTreeMap::new();
+
+    for entry in entries {
+        let mut dests = vec![entry.destination()];
+        dests.extend(entry.mirror_destinations().unwrap_or_default());
+        for dest in dests {
+            let path = base_dir.join(&dest);
+            match writable_problem(&path) {
+                Ok(probe) => {
+                    *needed.entry(probe).or_default() += local_copy_size(entry);
+                }
+                Err(problem) => {
+                    problems.push(format!("{} ({}): {}", entry.id, dest.display(), problem))
+                }
+            }
+        }
+    }
+
+    for (dir, bytes) in &needed {
+        let Some(available) = available_bytes(dir) else {
+            continue;
+        };
+        let required = bytes + MIN_FREE_BYTES;
+        if available < required {
+            problems.push(format!(
+                "{}: {} free, at least {} needed",
+                dir.display(),
+                format_size(available),
+                format_size(required)
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        return Ok(());
+    }
+    Err(ApsError::DestinationPreflightFailed {
+        count: problems.len(),
+        problems: problems
+            .iter()
+            .map(|p| format!("\n  - {}", p))
+            .collect::<String>(),
+    })
+}
+
+/// Why `path` cannot be written, or the existing directory it will be
+/// created in (or replaced from) when it can
+fn writable_problem(path: &Path) -> std::result::Result<PathBuf, String> {
+    // Replacing an existing destination means writing to it and its parent
+    if let Ok(meta) = path.symlink_metadata() {
+        if meta.is_dir() && !can_create_in(path) {
+            return Err("directory is not writable".to_string());
+        }
+        if meta.is_file() && meta.permissions().readonly() {
+            return Err("file is read-only".to_string());
+        }
+    }
+
+    let mut ancestor = path.parent();
+    while let Some(dir) = ancestor {
+        if dir.as_os_str().is_empty() {
+            break;
+        }
+        match dir.symlink_metadata() {
+            Ok(_) if dir.is_dir() => {
+                return if can_create_in(dir) {
+                    Ok(dir.to_path_buf())
+                } else {
+                    Err(format!("{} is not writable", dir.display()))
+                };
+            }
+            Ok(_) => return Err(format!("{} is not a directory", dir.display())),
+            Err(_) => ancestor = dir.parent(),
+        }
+    }
+    Err("no existing parent directory".to_string())
+}
+
+/// Whether a file can be created in `dir`. Trying it is the only answer
+/// that accounts for ownership, ACLs, and read-only mounts.
+fn can_create_in(dir: &Path) -> bool {
+    tempfile::Builder::new()
+        .prefix(".aps-preflight-")
+        .tempfile_in(dir)
+        .is_ok()
+}
+
+/// Bytes a copied (not symlinked) local source will take at its destination
+fn local_copy_size(entry: &Entry) -> u64 {
+    match &entry.source {
+        Some(Source::Filesystem {
+            root,
+            symlink: false,
+            path,
+        }) => {
+            let root = PathBuf::from(
+                shellexpand::full(root)
+                    .map(|s| s.into_owned())
+                    .unwrap_or_else(|_| root.clone()),
+            );
+            asset_size(&path.as_ref().map_or(root.clone(), |p| root.join(p)))
+        }
+        _ => 0,
+    }
+}
+
+/// Free space on the filesystem holding `dir`, or `None` where `df` is not
+/// available to ask
+fn available_bytes(dir: &Path) -> Option<u64> {
+    let output = Command::new("df").arg("-Pk").arg(dir).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_df_available(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The available column of POSIX `df -Pk` output, in bytes
+fn parse_df_available(output: &str) -> Option<u64> {
+    let line = output.lines().nth(1)?;
+    let kib: u64 = line.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_writable_problem_finds_existing_parent() {
+        let temp = tempdir().unwrap();
+        fs::write(temp.path().join("blocker"), "").unwrap();
+
+        assert_eq!(
+            writable_problem(&temp.path().join("a/b/AGENTS.md")),
+            Ok(temp.path().to_path_buf())
+        );
+        assert!(writable_problem(&temp.path().join("blocker/AGENTS.md"))
+            .unwrap_err()
+            .ends_with("is not a directory"));
+    }
+
+    #[test]
+    fn test_parse_df_available() {
+        let output = "Filesystem     1024-blocks      Used Available Capacity Mounted on\n\
+                      /dev/sda1        102400000  51200000  51200000      50% /\n";
+        assert_eq!(parse_df_available(output), Some(51200000 * 1024));
+        assert_eq!(parse_df_available("Filesystem\n"), None);
+    }
+}
@@ -0,0 +1,270 @@
+//! Machine-wide index of synced projects (`aps index`).
+//!
+//! With `index: true` in the user config, every `aps sync` records the
+//! project it installed into, with the IDs and sources of its entries, in
+//! `$XDG_DATA_HOME/aps/index.yaml` (`~/.local/share/aps/index.yaml` by
+//! default), or the file `APS_INDEX` names. `aps index list` and
+//! `aps index search` read it back to find every project on the machine
+//! using a skill or repository.
+
+use crate::consumers::{source_labels, SourcePattern};
+use crate::error::{ApsError, Result};
+use crate::manifest::{manifest_dir, Manifest};
+use crate::user_config::UserConfig;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+/// Environment variable naming the index file
+const INDEX_ENV: &str = "APS_INDEX";
+
+/// Synced projects, keyed by the directory entries were installed into
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProjectIndex {
+    #[serde(default)]
+    pub projects: BTreeMap<PathBuf, IndexedProject>,
+}
+
+/// A project as of its last sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedProject {
+    pub manifest: PathBuf,
+    /// RFC 3339 time of the last sync
+    pub synced_at: String,
+    #[serde(default)]
+    pub entries: Vec<IndexedEntry>,
+}
+
+/// An entry of an indexed project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedEntry {
+    pub id: String,
+    pub kind: String,
+    /// Sources as the lockfile records them
+    #[serde(default)]
+    pub sources: Vec<String>,
+    /// Normalized repositories and absolute paths the sources are searched by
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+/// Path of the index file, if one applies
+pub fn index_path() -> Option<PathBuf> {
+    if let Ok(path) = std::env::var(INDEX_ENV) {
+        return (!path.is_empty()).then(|| PathBuf::from(path));
+    }
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })?;
+    Some(data_home.join("aps").join("index.yaml"))
+}
+
+impl ProjectIndex {
+    /// Load the index at `path`, treating a missing file as empty
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(ApsError::io(e, format!("Failed to read {:?}", path))),
+        };
+        if content.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_yaml::from_str(&content).map_err(|e| ApsError::ProjectIndexInvalid {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Write the index, replacing the file in one step
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| ApsError::io(e, format!("Failed to create {:?}", dir)))?;
+        }
+        let content = serde_yaml::to_string(self).map_err(|e| ApsError::ProjectIndexInvalid {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+        let temp = path.with_extension("yaml.tmp");
+        std::fs::write(&temp, content)
+            .map_err(|e| ApsError::io(e, format!("Failed to write {:?}", temp)))?;
+        std::fs::rename(&temp, path)
+            .map_err(|e| ApsError::io(e, format!("Failed to write {:?}", path)))
+    }
+
+    /// Entries whose ID is `text` or whose sources match `pattern`, with
+    /// the project each belongs to
+    pub fn search(&self, pattern: &SourcePattern, text: &str) -> Vec<(&Path, &IndexedEntry)> {
+        self.projects
+            .iter()
+            .flat_map(|(project, indexed)| {
+                indexed
+                    .entries
+                    .iter()
+                    .map(move |entry| (project.as_path(), entry))
+            })
+            .filter(|(_, entry)| {
+                entry.id == text || entry.labels.iter().any(|l| pattern.matches_label(l))
+            })
+            .collect()
+    }
+
+    /// Sources used by more than one project, most widely used first, with
+    /// the projects using each
+    pub fn shared_sources(&self) -> Vec<(&str, Vec<&Path>)> {
+        let mut users: BTreeMap<&str, Vec<&Path>> = BTreeMap::new();
+        for (project, indexed) in &self.projects {
+            for entry in &indexed.entries {
+                // The most specific label: the repository path, or the local path
+                let Some(label) = entry.labels.last() else {
+                    continue;
+                };
+                let projects = users.entry(label.as_str()).or_default();
+                if !projects.contains(&project.as_path()) {
+                    projects.push(project);
+                }
+            }
+        }
+        let mut shared: Vec<_> = users
+            .into_iter()
+            .filter(|(_, projects)| projects.len() > 1)
+            .collect();
+        shared.sort_by_key(|(_, projects)| std::cmp::Reverse(projects.len()));
+        shared
+    }
+}
+
+/// Record a sync of `manifest` into `project` when the user config enables
+/// the index. Problems are logged; they never fail the sync.
+pub fn record_sync(project: &Path, manifest_path: &Path, manifest: &Manifest) {
+    if !UserConfig::load().is_ok_and(|config| config.index) {
+        return;
+    }
+    let Some(path) = index_path() else {
+        return;
+    };
+    match record(&path, project, manifest_path, manifest) {
+        Ok(()) => debug!("Recorded {:?} in the project index {:?}", project, path),
+        Err(e) => warn!("Failed to update the project index {:?}: {}", path, e),
+    }
+}
+
+fn record(path: &Path, project: &Path, manifest_path: &Path, manifest: &Manifest) -> Result<()> {
+    // Concurrent syncs take turns updating the index
+    let _lock = lock_index(path)?;
+    let mut index = ProjectIndex::load(path)?;
+    let base_dir = manifest_dir(manifest_path);
+    let entries = manifest
+        .entries
+        .iter()
+        .map(|entry| {
+            let sources: Vec<_> = entry.source.iter().chain(&entry.sources).collect();
+            IndexedEntry {
+                id: entry.id.clone(),
+                kind: entry.kind.as_str().to_string(),
+                sources: sources.iter().map(|s| s.display_path()).collect(),
+                labels: sources
+                    .iter()
+                    .flat_map(|s| source_labels(s, &base_dir))
+                    .collect(),
+            }
+        })
+        .collect();
+    index.projects.insert(
+        canonical(project),
+        IndexedProject {
+            manifest: canonical(manifest_path),
+            synced_at: Utc::now().to_rfc3339(),
+            entries,
+        },
+    );
+    index.save(path)
+}
+
+/// Lock the index for an update, released when the file is dropped
+fn lock_index(path: &Path) -> Result<File> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| ApsError::io(e, format!("Failed to create {:?}", dir)))?;
+    }
+    let lock_path = path.with_extension("lock");
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|e| ApsError::io(e, format!("Failed to open {:?}", lock_path)))?;
+    file.lock()
+        .map_err(|e| ApsError::io(e, format!("Failed to lock {:?}", lock_path)))?;
+    Ok(file)
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, label: &str) -> IndexedEntry {
+        IndexedEntry {
+            id: id.to_string(),
+            kind: "agent_skill".to_string(),
+            sources: vec![label.to_string()],
+            labels: vec![label.to_string()],
+        }
+    }
+
+    fn project(entries: Vec<IndexedEntry>) -> IndexedProject {
+        IndexedProject {
+            manifest: PathBuf::from("aps.yaml"),
+            synced_at: String::new(),
+            entries,
+        }
+    }
+
+    #[test]
+    fn test_search_and_shared_sources() {
+        let mut index = ProjectIndex::default();
+        index.projects.insert(
+            PathBuf::from("/work/api"),
+            project(vec![
+                entry("review", "github.com/org/skills"),
+                entry("notes", "/home/me/notes"),
+            ]),
+        );
+        index.projects.insert(
+            PathBuf::from("/work/web"),
+            project(vec![entry("code-review", "github.com/org/skills")]),
+        );
+
+        let pattern = SourcePattern::parse("https://github.com/org/skills.git").unwrap();
+        let found: Vec<_> = index
+            .search(&pattern, "https://github.com/org/skills.git")
+            .into_iter()
+            .map(|(project, entry)| (project.to_path_buf(), entry.id.clone()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (PathBuf::from("/work/api"), "review".to_string()),
+                (PathBuf::from("/work/web"), "code-review".to_string()),
+            ]
+        );
+
+        let pattern = SourcePattern::parse("notes").unwrap();
+        assert_eq!(index.search(&pattern, "notes").len(), 1);
+
+        let shared = index.shared_sources();
+        assert_eq!(shared.len(), 1);
+        assert_eq!(shared[0].0, "github.com/org/skills");
+        assert_eq!(shared[0].1.len(), 2);
+    }
+}
@@ -0,0 +1,438 @@
+//! Best-effort manifest reconstruction from a lockfile (`aps manifest rebuild`).
+//!
+//! The lockfile records each entry's source and destination but not its kind,
+//! the exact ref it was declared with, or the path within a git repository.
+//! Kinds and refs are inferred and reported as fields needing confirmation;
+//! git paths are found by matching the locked checksum inside a clone of the
+//! locked commit.
+
+use crate::checksum::compute_source_checksum;
+use crate::error::{ApsError, Result};
+use crate::lockfile::{LockedEntry, LockedSource, Lockfile};
+use crate::manifest::{AssetKind, Entry, Generator, Manifest, Source, MIRROR_TOOLS};
+use crate::sources::clone_at_commit;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::debug;
+use walkdir::WalkDir;
+
+/// Reason recorded for git entries until their path is located
+const PATH_NOT_RECORDED: &str = "not recorded in the lockfile; no file or folder in the locked commit matches the locked checksum";
+
+/// A rebuilt field that the lockfile cannot confirm
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unconfirmed {
+    pub id: String,
+    pub field: &'static str,
+    pub reason: String,
+}
+
+/// A manifest rebuilt from a lockfile
+#[derive(Debug)]
+pub struct RebuiltManifest {
+    pub manifest: Manifest,
+    pub unconfirmed: Vec<Unconfirmed>,
+}
+
+impl RebuiltManifest {
+    /// Rebuild a manifest from every lockfile entry, ordered by ID
+    pub fn from_lockfile(lockfile: &Lockfile) -> Self {
+        let mut unconfirmed = Vec::new();
+        let locked: BTreeMap<_, _> = lockfile.entries.iter().collect();
+        let entries = locked
+            .into_iter()
+            .map(|(id, locked)| rebuild_entry(id, locked, &mut unconfirmed))
+            .collect();
+
+        Self {
+            manifest: Manifest {
+                entries,
+                ..Manifest::default()
+            },
+            unconfirmed,
+        }
+    }
+
+    /// Find the path within the repository of each git entry by cloning its
+    /// locked commit and matching the locked checksum. Entries that cannot be
+    /// fetched or matched keep their `path` flag.
+    pub fn locate_git_paths(&mut self, lockfile: &Lockfile) {
+        for entry in &mut self.manifest.entries {
+            let Some(Source::Git { repo, path, .. }) = entry.source.as_mut() else {
+                continue;
+            };
+            let Some(locked) = lockfile.entries.get(&entry.id) else {
+                continue;
+            };
+            let Some(commit) = locked.commit.as_deref() else {
+                continue;
+            };
+            let git_ref = locked.resolved_ref.as_deref().unwrap_or("HEAD");
+            let clone = match clone_at_commit(repo, commit, git_ref, None) {
+                Ok(clone) => clone,
+                Err(e) => {
+                    debug!("Could not fetch {} for {}: {}", repo, entry.id, e);
+                    continue;
+                }
+            };
+            if let Some(found) = find_path_with_checksum(&clone.repo_path, &locked.checksum) {
+                *path = (found != ".").then_some(found);
+                self.unconfirmed
+                    .retain(|u| !(u.id == entry.id && u.field == "path"));
+            }
+        }
+    }
+
+    /// Render the manifest as YAML, headed by a comment listing the fields to confirm
+    pub fn to_yaml(&self, lockfile_name: &str) -> Result<String> {
+        let body =
+            serde_yaml::to_string(&self.manifest).map_err(|e| ApsError::ManifestParseError {
+                message: format!("Failed to serialize manifest: {}", e),
+            })?;
+
+        let mut header = format!(
+            "# Rebuilt from {} by `aps manifest rebuild`.\n",
+            lockfile_name
+        );
+        if !self.unconfirmed.is_empty() {
+            header.push_str("# Confirm these inferred fields, then delete this comment:\n");
+            for u in &self.unconfirmed {
+                header.push_str(&format!("#   - {}.{}: {}\n", u.id, u.field, u.reason));
+            }
+        }
+        Ok(format!("{}{}", header, body))
+    }
+}
+
+fn rebuild_entry(id: &str, locked: &LockedEntry, unconfirmed: &mut Vec<Unconfirmed>) -> Entry {
+    let mut flag = |field: &'static str, reason: String| {
+        unconfirmed.push(Unconfirmed {
+            id: id.to_string(),
+            field,
+            reason,
+        })
+    };
+
+    let (kind, source, sources) = match &locked.source {
+        LockedSource::Composite(paths) => {
+            let sources = paths
+                .iter()
+                .map(|path| {
+                    let (source, guessed) = composite_source(path, locked);
+                    if guessed {
+                        flag("sources", format!("guessed the source type of '{}'", path));
+                    }
+                    source
+                })
+                .collect();
+            (AssetKind::CompositeAgentsMd, None, sources)
+        }
+        LockedSource::Simple(display) => {
+            let source = if locked.commit.is_some() {
+                flag("path", PATH_NOT_RECORDED.to_string());
+                let git_ref = locked
+                    .resolved_ref
+                    .clone()
+                    .unwrap_or_else(|| "auto".to_string());
+                flag(
+                    "ref",
+                    format!(
+                        "set to the locked branch '{}'; the manifest may have used auto or a tag",
+                        git_ref
+                    ),
+                );
+                Source::Git {
+                    repo: display.clone(),
+                    r#ref: git_ref,
+                    shallow: true,
+                    path: None,
+                }
+            } else {
+                Source::Filesystem {
+                    root: display.clone(),
+                    symlink: locked.is_symlink,
+                    path: None,
+                }
+            };
+
+            let (kind, certain) = if locked.merged_keys.is_empty() {
+                infer_kind(&locked.dest)
+            } else {
+                (AssetKind::CursorHooks, true)
+            };
+            if !certain {
+                flag(
+                    "kind",
+                    format!("inferred {} from dest {}", kind.as_str(), locked.dest),
+                );
+            }
+            (kind, Some(source), Vec::new())
+        }
+    };
+
+    let also_install_for = locked
+        .mirrors
+        .iter()
+        .filter_map(|mirror| {
+            MIRROR_TOOLS
+                .iter()
+                .find(|(_, dir)| mirror.trim_start_matches("./").starts_with(dir))
+                .map(|(tool, _)| tool.to_string())
+        })
+        .collect();
+
+    Entry {
+        id: id.to_string(),
+        kind,
+        description: None,
+        owner: None,
+        source,
+        sources,
+        dest: Some(locked.dest.clone()),
+        include: Vec::new(),
+        checksum_ignore: Vec::new(),
+        max_age: None,
+        preserve_permissions: true,
+        preserve_mtime: false,
+        copy_license: false,
+        also_install_for,
+        frozen: false,
+        hooks_merge: Default::default(),
+        default_dest_override: None,
+        targets: Vec::new(),
+        transform: Vec::new(),
+        suppress_warnings: Vec::new(),
+        backup: None,
+        backup_dir: None,
+        symlink_style: None,
+    }
+}
+
+/// Infer an entry's kind from its destination, and whether the match is certain
+fn infer_kind(dest: &str) -> (AssetKind, bool) {
+    let dest = dest.trim_start_matches("./").trim_end_matches('/');
+    let parent = dest.rsplit_once('/').map(|(parent, _)| parent);
+    let name = Path::new(dest)
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+
+    if name.eq_ignore_ascii_case("AGENTS.md") {
+        (AssetKind::AgentsMd, true)
+    } else if Path::new(dest)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+    {
+        (AssetKind::AgentsMd, false)
+    } else if dest.ends_with(".cursor/rules") {
+        (AssetKind::CursorRules, true)
+    } else if dest.ends_with(".cursor/hooks") {
+        (AssetKind::CursorHooks, true)
+    } else if dest.ends_with(".cursor/skills") {
+        (AssetKind::CursorSkillsRoot, true)
+    } else if dest.ends_with("skills") || parent.is_some_and(|p| p.ends_with("skills")) {
+        (AssetKind::AgentSkill, true)
+    } else if dest.ends_with("personas") {
+        (AssetKind::Persona, true)
+    } else {
+        (AssetKind::AgentSkill, false)
+    }
+}
+
+/// Rebuild one composite source from its lockfile label, and whether its type was guessed
+fn composite_source(path: &str, locked: &LockedEntry) -> (Source, bool) {
+    if let Some(generator) = path.strip_prefix("generated:") {
+        if generator == Generator::SkillsIndex.as_str() {
+            return (
+                Source::Generated {
+                    generator: Generator::SkillsIndex,
+                },
+                false,
+            );
+        }
+    }
+    if locked.source_checksums.contains_key(path) {
+        return (
+            Source::Http {
+                url: path.to_string(),
+                headers: BTreeMap::new(),
+            },
+            false,
+        );
+    }
+
+    let (repo, git_path) = split_git_display(path);
+    if git_path.is_some() {
+        let source = Source::Git {
+            repo,
+            r#ref: "auto".to_string(),
+            shallow: true,
+            path: git_path,
+        };
+        return (source, true);
+    }
+    let source = Source::Filesystem {
+        root: path.to_string(),
+        symlink: false,
+        path: None,
+    };
+    (source, true)
+}
+
+/// The shallowest file or folder in a clone whose checksum matches, relative
+/// to the clone (`.` for the whole repository)
+fn find_path_with_checksum(repo_path: &Path, checksum: &str) -> Option<String> {
+    let mut candidates: Vec<_> = WalkDir::new(repo_path)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git")
+        .filter_map(|e| e.ok())
+        .map(|e| e.into_path())
+        .collect();
+    candidates.sort_by_key(|p| p.components().count());
+    candidates.into_iter().find_map(|candidate| {
+        let matches = compute_source_checksum(&candidate, &[])
+            .map(|c| c == checksum)
+            .unwrap_or(false);
+        if !matches {
+            return None;
+        }
+        let relative = candidate.strip_prefix(repo_path).ok()?;
+        Some(if relative.as_os_str().is_empty() {
+            ".".to_string()
+        } else {
+            relative.to_string_lossy().to_string()
+        })
+    })
+}
+
+/// Split a composite git source label (`<repo>:<path>` or `<repo>`) into its parts.
+///
+/// The label joins repo and path with `:`, which also appears in URL schemes,
+/// ports, and scp-style remotes (`git@host:org/repo.git`), so a `:` only
+/// separates a path when the repo part has a path of its own.
+fn split_git_display(display: &str) -> (String, Option<String>) {
+    if let Some((repo, path)) = display.rsplit_once(':') {
+        let is_path = !path.is_empty()
+            && !path.starts_with("//")
+            && !path.starts_with(|c: char| c.is_ascii_digit())
+            && repo.contains('/');
+        if is_path {
+            return (repo.to_string(), Some(path.to_string()));
+        }
+    }
+    (display.to_string(), None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_git_display_handles_schemes_and_scp_remotes() {
+        assert_eq!(
+            split_git_display("https://github.com/org/skills.git:skills/review"),
+            (
+                "https://github.com/org/skills.git".to_string(),
+                Some("skills/review".to_string())
+            )
+        );
+        assert_eq!(
+            split_git_display("https://github.com/org/skills.git"),
+            ("https://github.com/org/skills.git".to_string(), None)
+        );
+        assert_eq!(
+            split_git_display("git@github.com:org/skills.git"),
+            ("git@github.com:org/skills.git".to_string(), None)
+        );
+        assert_eq!(
+            split_git_display("git@github.com:org/skills.git:AGENTS.md"),
+            (
+                "git@github.com:org/skills.git".to_string(),
+                Some("AGENTS.md".to_string())
+            )
+        );
+        assert_eq!(
+            split_git_display("https://git.example.com:8443/org/skills.git"),
+            (
+                "https://git.example.com:8443/org/skills.git".to_string(),
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn test_find_path_with_checksum_matches_files_and_folders() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("skills/review")).unwrap();
+        std::fs::write(dir.path().join("skills/review/SKILL.md"), "# Review").unwrap();
+        std::fs::write(dir.path().join("AGENTS.md"), "# Agents").unwrap();
+
+        let skill = compute_source_checksum(&dir.path().join("skills/review"), &[]).unwrap();
+        assert_eq!(
+            find_path_with_checksum(dir.path(), &skill).as_deref(),
+            Some("skills/review")
+        );
+        let agents = compute_source_checksum(&dir.path().join("AGENTS.md"), &[]).unwrap();
+        assert_eq!(
+            find_path_with_checksum(dir.path(), &agents).as_deref(),
+            Some("AGENTS.md")
+        );
+        assert_eq!(find_path_with_checksum(dir.path(), "sha256:none"), None);
+    }
+
+    #[test]
+    fn test_infer_kind_matches_markdown_names_in_any_case() {
+        assert_eq!(infer_kind("AGENTS.md"), (AssetKind::AgentsMd, true));
+        assert_eq!(infer_kind("./docs/agents.md"), (AssetKind::AgentsMd, true));
+        assert_eq!(infer_kind("docs/NOTES.MD"), (AssetKind::AgentsMd, false));
+        assert_eq!(
+            infer_kind("docs/agents.md.bak"),
+            (AssetKind::AgentSkill, false)
+        );
+    }
+
+    #[test]
+    fn test_rebuild_flags_inferred_kind_and_ref() {
+        let mut lockfile = Lockfile::new();
+        lockfile.entries.insert(
+            "review".to_string(),
+            LockedEntry::new_git(
+                "https://github.com/org/skills.git",
+                ".claude/skills/review",
+                "main".to_string(),
+                "abc123".to_string(),
+                "sha256:1".to_string(),
+            ),
+        );
+        lockfile.entries.insert(
+            "notes".to_string(),
+            LockedEntry::new_filesystem(
+                "../shared/NOTES.md",
+                "docs/NOTES.md",
+                "sha256:2".to_string(),
+                true,
+                None,
+                Vec::new(),
+            ),
+        );
+
+        let rebuilt = RebuiltManifest::from_lockfile(&lockfile);
+        let ids: Vec<_> = rebuilt.manifest.entries.iter().map(|e| &e.id).collect();
+        assert_eq!(ids, ["notes", "review"]);
+
+        let review = &rebuilt.manifest.entries[1];
+        assert_eq!(review.kind, AssetKind::AgentSkill);
+        assert_eq!(review.source.as_ref().and_then(|s| s.git_path()), None);
+
+        let flagged: Vec<_> = rebuilt
+            .unconfirmed
+            .iter()
+            .map(|u| format!("{}.{}", u.id, u.field))
+            .collect();
+        assert_eq!(flagged, ["notes.kind", "review.path", "review.ref"]);
+        assert!(rebuilt
+            .to_yaml("aps.lock.yaml")
+            .unwrap()
+            .contains("#   - review.ref: set to the locked branch 'main'"));
+    }
+}
@@ -0,0 +1,217 @@
+//! Entry-by-entry review before a sync (`aps sync --review`).
+//!
+//! Meant for the first sync of a freshly cloned project: each entry is shown
+//! with its source, destination, and what it would install, and can be
+//! approved, skipped for this sync, or disabled. Disabled entries are kept in
+//! `.aps-cache/review.yaml`, which is local to the machine, and later syncs
+//! leave them out until a review approves them again or `--only` names them.
+
+use crate::catalog::enumerate_entry_assets;
+use crate::ci::interactive;
+use crate::color::style;
+use crate::error::{ApsError, Result};
+use crate::manifest::Entry;
+use crate::output::say;
+use crate::staleness::CACHE_DIR;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// Review state filename within the cache directory
+const REVIEW_FILE: &str = "review.yaml";
+
+/// Assets listed by name in an entry's summary before the rest are counted
+const LISTED_ASSETS: usize = 5;
+
+/// Entries disabled during a review
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReviewState {
+    #[serde(default)]
+    pub disabled: BTreeSet<String>,
+}
+
+/// What to do with a reviewed entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReviewChoice {
+    Approve,
+    Skip,
+    Disable,
+}
+
+impl ReviewState {
+    fn path(base_dir: &Path) -> PathBuf {
+        base_dir.join(CACHE_DIR).join(REVIEW_FILE)
+    }
+
+    /// Load the review state, treating a missing or unreadable file as empty
+    pub fn load(base_dir: &Path) -> Self {
+        std::fs::read_to_string(Self::path(base_dir))
+            .ok()
+            .and_then(|content| serde_yaml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the review state to disk
+    pub fn save(&self, base_dir: &Path) -> Result<()> {
+        let path = Self::path(base_dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| ApsError::io(e, format!("Failed to create {:?}", parent)))?;
+        }
+        let content = serde_yaml::to_string(self).map_err(|e| ApsError::InvalidInput {
+            message: format!("Failed to serialize review state: {}", e),
+        })?;
+        std::fs::write(&path, content)
+            .map_err(|e| ApsError::io(e, format!("Failed to write {:?}", path)))
+    }
+
+    /// Leave out disabled entries, except those `only` names
+    pub fn without_disabled<'a>(&self, entries: Vec<&'a Entry>, only: &[String]) -> Vec<&'a Entry> {
+        let (kept, disabled): (Vec<_>, Vec<_>) = entries
+            .into_iter()
+            .partition(|e| !self.disabled.contains(&e.id) || only.contains(&e.id));
+        if !disabled.is_empty() {
+            let ids: Vec<&str> = disabled.iter().map(|e| e.id.as_str()).collect();
+            say!(
+                "{} {} {}",
+                style("Skipping disabled entries:").yellow(),
+                ids.join(", "),
+                style("(aps sync --review to enable)").dim()
+            );
+        }
+        kept
+    }
+}
+
+/// Ask about each of `entries` in turn and return those approved, recording
+/// disabled and re-approved entries in `state`
+pub fn review_entries<'a>(
+    entries: Vec<&'a Entry>,
+    base_dir: &Path,
+    state: &mut ReviewState,
+) -> Result<Vec<&'a Entry>> {
+    if !interactive() {
+        return Err(ApsError::NotInteractive {
+            command: "aps sync --review".to_string(),
+        });
+    }
+
+    let total = entries.len();
+    let mut approved = Vec::new();
+    for (index, entry) in entries.into_iter().enumerate() {
+        say!();
+        print_entry(entry, base_dir, index + 1, total, state);
+        let choices = [
+            (ReviewChoice::Approve, "Approve and install"),
+            (ReviewChoice::Skip, "Skip this sync"),
+            (ReviewChoice::Disable, "Disable (skip on later syncs too)"),
+        ];
+        let labels: Vec<&str> = choices.iter().map(|(_, label)| *label).collect();
+        let selection = dialoguer::Select::new()
+            .with_prompt(format!("Install {}?", entry.id))
+            .items(&labels)
+            .default(0)
+            .interact_opt()
+            .map_err(|_| ApsError::Cancelled)?
+            .ok_or(ApsError::Cancelled)?;
+        match choices[selection].0 {
+            ReviewChoice::Approve => {
+                state.disabled.remove(&entry.id);
+                approved.push(entry);
+            }
+            ReviewChoice::Skip => {}
+            ReviewChoice::Disable => {
+                state.disabled.insert(entry.id.clone());
+            }
+        }
+    }
+    say!();
+    Ok(approved)
+}
+
+fn print_entry(entry: &Entry, base_dir: &Path, number: usize, total: usize, state: &ReviewState) {
+    let disabled = if state.disabled.contains(&entry.id) {
+        style(" (disabled)").yellow().to_string()
+    } else {
+        String::new()
+    };
+    say!(
+        "{} {}{} {}",
+        style(format!("[{}/{}]", number, total)).dim(),
+        style(&entry.id).cyan().bold(),
+        disabled,
+        style(entry.kind.as_str()).dim()
+    );
+    for source in entry.source.iter().chain(&entry.sources) {
+        match source.git_info() {
+            Some((_, git_ref)) => say!(
+                "  source: {} {}",
+                source.display_path(),
+                style(format!("@ {}", git_ref)).dim()
+            ),
+            None => say!("  source: {}", source.display_path()),
+        }
+    }
+    say!("  dest:   {}", entry.destination().display());
+    for line in content_summary(entry, base_dir) {
+        say!("  {}", line);
+    }
+}
+
+/// What an entry would install: its assets with their descriptions, or why
+/// they could not be listed
+fn content_summary(entry: &Entry, base_dir: &Path) -> Vec<String> {
+    let assets = match enumerate_entry_assets(entry, base_dir, false) {
+        Ok(assets) => assets,
+        Err(e) => {
+            return vec![style(format!("content: not available ({})", e))
+                .dim()
+                .to_string()]
+        }
+    };
+    let mut lines = vec![format!("content: {} asset(s)", assets.len())];
+    for asset in assets.iter().take(LISTED_ASSETS) {
+        match &asset.short_description {
+            Some(description) => lines.push(format!(
+                "  - {} {}",
+                asset.name,
+                style(format!("— {}", description)).dim()
+            )),
+            None => lines.push(format!("  - {}", asset.name)),
+        }
+    }
+    if assets.len() > LISTED_ASSETS {
+        lines.push(format!("  … and {} more", assets.len() - LISTED_ASSETS));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_disabled_entries_round_trip_and_are_left_out() {
+        let dir = tempdir().unwrap();
+        let mut state = ReviewState::load(dir.path());
+        assert!(state.disabled.is_empty());
+        state.disabled.insert("beta".to_string());
+        state.save(dir.path()).unwrap();
+
+        let state = ReviewState::load(dir.path());
+        let entries: Vec<Entry> = ["alpha", "beta"]
+            .iter()
+            .map(|id| serde_yaml::from_str(&format!("id: {}\nkind: agent_skill\n", id)).unwrap())
+            .collect();
+        let refs: Vec<&Entry> = entries.iter().collect();
+        let kept: Vec<&str> = state
+            .without_disabled(refs.clone(), &[])
+            .iter()
+            .map(|e| e.id.as_str())
+            .collect();
+        assert_eq!(kept, vec!["alpha"]);
+        // --only overrides a disable
+        assert_eq!(state.without_disabled(refs, &["beta".to_string()]).len(), 2);
+    }
+}
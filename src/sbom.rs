@@ -0,0 +1,187 @@
+//! Inventory of third-party content for compliance (`aps export sbom`).
+//!
+//! Built from the lockfile alone, so it describes exactly what the last sync
+//! installed: each entry's source, commit, checksum, detected license, and
+//! install path. Rendered as a CycloneDX 1.5 BOM or as plain JSON.
+
+use crate::cli::SbomFormat;
+use crate::lockfile::{LockedSource, Lockfile};
+use serde_json::{json, Value};
+
+/// One installed entry, as recorded in the lockfile
+#[derive(Debug)]
+struct Component<'a> {
+    id: &'a str,
+    sources: Vec<&'a str>,
+    git_ref: Option<&'a str>,
+    commit: Option<&'a str>,
+    checksum: &'a str,
+    license: Option<&'a str>,
+    dest: &'a str,
+    mirrors: &'a [String],
+}
+
+/// Lockfile entries in ID order
+fn components(lockfile: &Lockfile) -> Vec<Component<'_>> {
+    let mut components: Vec<Component> = lockfile
+        .entries
+        .iter()
+        .map(|(id, locked)| Component {
+            id,
+            sources: match &locked.source {
+                LockedSource::Simple(source) => vec![source.as_str()],
+                LockedSource::Composite(sources) => sources.iter().map(String::as_str).collect(),
+            },
+            git_ref: locked.resolved_ref.as_deref(),
+            commit: locked.commit.as_deref(),
+            checksum: &locked.checksum,
+            license: locked.license.as_deref(),
+            dest: &locked.dest,
+            mirrors: &locked.mirrors,
+        })
+        .collect();
+    components.sort_by_key(|c| c.id);
+    components
+}
+
+/// Render the lockfile's entries as an SBOM document
+pub fn render(lockfile: &Lockfile, format: SbomFormat) -> Value {
+    let components = components(lockfile);
+    match format {
+        SbomFormat::Cyclonedx => cyclonedx(&components),
+        SbomFormat::Json => plain_json(&components),
+    }
+}
+
+fn plain_json(components: &[Component]) -> Value {
+    let entries: Vec<Value> = components
+        .iter()
+        .map(|c| {
+            json!({
+                "id": c.id,
+                "source": c.sources.join(", "),
+                "ref": c.git_ref,
+                "commit": c.commit,
+                "checksum": c.checksum,
+                "license": c.license,
+                "dest": c.dest,
+                "mirrors": c.mirrors,
+            })
+        })
+        .collect();
+    json!({
+        "aps_version": env!("CARGO_PKG_VERSION"),
+        "entries": entries,
+    })
+}
+
+fn cyclonedx(components: &[Component]) -> Value {
+    let components: Vec<Value> = components
+        .iter()
+        .map(|c| {
+            let mut component = json!({
+                "type": "file",
+                "bom-ref": c.id,
+                "name": c.id,
+            });
+            if let Some(commit) = c.commit {
+                component["version"] = json!(commit);
+            }
+            // Checksums are recorded as `sha256:<hex>`
+            if let Some(hex) = c.checksum.strip_prefix("sha256:") {
+                component["hashes"] = json!([{ "alg": "SHA-256", "content": hex }]);
+            }
+            if let Some(license) = c.license {
+                component["licenses"] = json!([{ "license": { "id": license } }]);
+            }
+            let reference_type = if c.commit.is_some() { "vcs" } else { "other" };
+            component["externalReferences"] = c
+                .sources
+                .iter()
+                .map(|source| json!({ "type": reference_type, "url": source }))
+                .collect();
+
+            let mut properties = vec![json!({ "name": "aps:dest", "value": c.dest })];
+            if let Some(git_ref) = c.git_ref {
+                properties.push(json!({ "name": "aps:ref", "value": git_ref }));
+            }
+            properties.extend(
+                c.mirrors
+                    .iter()
+                    .map(|mirror| json!({ "name": "aps:mirror", "value": mirror })),
+            );
+            component["properties"] = Value::Array(properties);
+            component
+        })
+        .collect();
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": "aps",
+                    "version": env!("CARGO_PKG_VERSION"),
+                }],
+            },
+        },
+        "components": components,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lockfile() -> Lockfile {
+        serde_yaml::from_str(
+            r#"
+version: 1
+entries:
+  rules:
+    source: /home/me/rules
+    dest: ./.cursor/rules
+    checksum: sha256:bbbb
+  docs:
+    source: https://github.com/org/docs.git
+    dest: ./AGENTS.md
+    resolved_ref: main
+    commit: 0123456789abcdef
+    checksum: sha256:aaaa
+    license: MIT
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_render_cyclonedx() {
+        let bom = render(&lockfile(), SbomFormat::Cyclonedx);
+        assert_eq!(bom["bomFormat"], "CycloneDX");
+        let docs = &bom["components"][0];
+        assert_eq!(docs["name"], "docs");
+        assert_eq!(docs["version"], "0123456789abcdef");
+        assert_eq!(docs["hashes"][0]["content"], "aaaa");
+        assert_eq!(docs["licenses"][0]["license"]["id"], "MIT");
+        assert_eq!(docs["externalReferences"][0]["type"], "vcs");
+        assert_eq!(docs["properties"][1]["value"], "main");
+        let rules = &bom["components"][1];
+        assert_eq!(rules["name"], "rules");
+        assert!(rules.get("version").is_none());
+        assert!(rules.get("licenses").is_none());
+        assert_eq!(rules["externalReferences"][0]["type"], "other");
+    }
+
+    #[test]
+    fn test_render_plain_json() {
+        let inventory = render(&lockfile(), SbomFormat::Json);
+        let docs = &inventory["entries"][0];
+        assert_eq!(docs["source"], "https://github.com/org/docs.git");
+        assert_eq!(docs["commit"], "0123456789abcdef");
+        assert_eq!(docs["dest"], "./AGENTS.md");
+        assert!(inventory["entries"][1]["license"].is_null());
+    }
+}
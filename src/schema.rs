@@ -0,0 +1,38 @@
+//! JSON Schema for the file formats aps reads and writes (`aps schema`).
+//!
+//! Schemas are derived from the serde types, so they track the formats as
+//! fields are added. Point an editor's YAML language server at the manifest
+//! schema to get validation and completion in `aps.yaml`.
+
+use crate::catalog::Catalog;
+use crate::cli::SchemaFormat;
+use crate::lockfile::Lockfile;
+use crate::manifest::Manifest;
+use schemars::{schema_for, Schema};
+
+/// The JSON Schema describing a file format
+pub fn schema_for_format(format: SchemaFormat) -> Schema {
+    match format {
+        SchemaFormat::Manifest => schema_for!(Manifest),
+        SchemaFormat::Lockfile => schema_for!(Lockfile),
+        SchemaFormat::Catalog => schema_for!(Catalog),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_schema_describes_entries() {
+        let schema = serde_json::to_value(schema_for_format(SchemaFormat::Manifest)).unwrap();
+        let entry = &schema["$defs"]["Entry"];
+        assert!(entry["properties"]["kind"].is_object());
+        assert!(entry["properties"].get("default_dest_override").is_none());
+        assert_eq!(
+            entry["required"],
+            serde_json::json!(["id", "kind"]),
+            "only id and kind are required"
+        );
+    }
+}
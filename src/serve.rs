@@ -0,0 +1,479 @@
+//! JSON-RPC server for editor integrations (`aps serve --stdio`).
+//!
+//! Requests and responses are JSON-RPC 2.0 messages, one per line on
+//! stdin/stdout. `status`, `validate`, and `explain` run in-process. `sync`
+//! runs `aps sync --yes` as a child process, so its prompts and progress
+//! output never reach the protocol stream, and reports results from the
+//! lockfile it writes.
+
+use crate::commands::load_or_create_lockfile;
+use crate::error::{ApsError, Result};
+use crate::hooks::validate_cursor_hooks;
+use crate::lockfile::{LockedEntry, Lockfile};
+use crate::manifest::{
+    detect_overlapping_destinations, discover_manifest, lint_manifest, manifest_dir,
+    validate_manifest, AssetKind, Manifest, Source,
+};
+use crate::persona::validate_personas;
+use crate::plan::Plan;
+use crate::warning::{Warning, WarningCode};
+use miette::Diagnostic;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Methods the server implements
+pub const METHODS: [&str; 4] = ["status", "sync", "validate", "explain"];
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Application error: the aps operation itself failed
+const APS_ERROR: i64 = -32000;
+
+/// A JSON-RPC request. Requests without an `id` are notifications and get
+/// no response.
+#[derive(Debug, Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
+/// Parameters shared by all methods; each method ignores the ones it does not use
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Params {
+    /// Manifest to operate on, overriding the server's
+    manifest: Option<PathBuf>,
+    /// Limit `status`, `sync`, and `explain` to these entry IDs
+    ids: Vec<String>,
+    /// Move git sources to their latest commits (`sync`, `explain`)
+    upgrade: bool,
+    /// Treat validation warnings as errors (`validate`)
+    strict: bool,
+}
+
+/// A JSON-RPC error object
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        let mut error = json!({ "code": self.code, "message": self.message });
+        if let Some(data) = &self.data {
+            error["data"] = data.clone();
+        }
+        error
+    }
+}
+
+impl From<ApsError> for RpcError {
+    fn from(err: ApsError) -> Self {
+        let code = err.code().map(|c| c.to_string());
+        Self {
+            code: APS_ERROR,
+            message: err.to_string(),
+            data: Some(json!({ "code": code })),
+        }
+    }
+}
+
+/// Sync state of one entry, as reported by `status` and `sync`
+#[derive(Debug, Serialize)]
+struct EntryStatus<'a> {
+    id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<&'static str>,
+    /// `never`, `synced`, `copied`, `warning`, `failed`, or `timed_out`;
+    /// entries that are locked but no longer in the manifest are `stale`
+    status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    locked: Option<&'a LockedEntry>,
+}
+
+/// A JSON-RPC server bound to a manifest
+pub struct Server {
+    manifest: Option<PathBuf>,
+}
+
+impl Server {
+    /// Create a server; `manifest` is discovered from the working directory when omitted
+    pub fn new(manifest: Option<PathBuf>) -> Self {
+        Self { manifest }
+    }
+
+    /// Answer requests from `input` until it is closed
+    pub fn run(&self, input: impl BufRead, mut output: impl Write) -> Result<()> {
+        for line in input.lines() {
+            let line = line.map_err(|e| ApsError::io(e, "Failed to read request"))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some(response) = self.handle_line(&line) {
+                writeln!(output, "{}", response)
+                    .and_then(|_| output.flush())
+                    .map_err(|e| ApsError::io(e, "Failed to write response"))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle one request line, returning the response unless it was a notification
+    fn handle_line(&self, line: &str) -> Option<Value> {
+        let value: Value = match serde_json::from_str(line) {
+            Ok(value) => value,
+            Err(e) => {
+                return Some(error_response(
+                    Value::Null,
+                    RpcError::new(PARSE_ERROR, e.to_string()),
+                ))
+            }
+        };
+        let request: Request = match serde_json::from_value(value.clone()) {
+            Ok(request) => request,
+            Err(e) => {
+                let id = value.get("id").cloned().unwrap_or(Value::Null);
+                return Some(error_response(
+                    id,
+                    RpcError::new(INVALID_REQUEST, e.to_string()),
+                ));
+            }
+        };
+
+        let result = self.call(&request.method, request.params);
+        let id = request.id?;
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(error) => error_response(id, error),
+        })
+    }
+
+    fn call(&self, method: &str, params: Option<Value>) -> std::result::Result<Value, RpcError> {
+        if !METHODS.contains(&method) {
+            return Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!(
+                    "Unknown method '{}' (expected one of: {})",
+                    method,
+                    METHODS.join(", ")
+                ),
+            ));
+        }
+        let params: Params = match params {
+            Some(Value::Null) | None => Params::default(),
+            Some(value) => serde_json::from_value(value)
+                .map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))?,
+        };
+
+        let override_path = params.manifest.as_deref().or(self.manifest.as_deref());
+        let (manifest, manifest_path) = discover_manifest(override_path)?;
+        for id in &params.ids {
+            if !manifest.entries.iter().any(|e| &e.id == id) {
+                return Err(ApsError::EntryNotFound { id: id.clone() }.into());
+            }
+        }
+
+        let result = match method {
+            "status" => status(&manifest, &manifest_path, &params)?,
+            "sync" => sync(&manifest, &manifest_path, &params)?,
+            "validate" => validate(&manifest, &manifest_path, &params),
+            _ => explain(&manifest, &manifest_path, &params)?,
+        };
+        Ok(result)
+    }
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": error.to_value() })
+}
+
+/// Sync state of the requested entries (all when `ids` is empty)
+fn entry_statuses<'a>(
+    manifest: &'a Manifest,
+    lockfile: &'a Lockfile,
+    ids: &[String],
+) -> Vec<EntryStatus<'a>> {
+    let wanted = |id: &str| ids.is_empty() || ids.iter().any(|i| i == id);
+    let mut statuses: Vec<EntryStatus> = manifest
+        .entries
+        .iter()
+        .filter(|e| wanted(&e.id))
+        .map(|e| {
+            let locked = lockfile.entries.get(&e.id);
+            let status = match locked {
+                Some(l) => l
+                    .last_status
+                    .map(|s| s.to_string().replace(' ', "_"))
+                    .unwrap_or_else(|| "synced".to_string()),
+                None => "never".to_string(),
+            };
+            EntryStatus {
+                id: &e.id,
+                kind: Some(e.kind.as_str()),
+                status,
+                locked,
+            }
+        })
+        .collect();
+
+    if ids.is_empty() {
+        statuses.extend(
+            lockfile
+                .entries
+                .iter()
+                .filter(|(id, _)| !manifest.entries.iter().any(|e| &e.id == *id))
+                .map(|(id, locked)| EntryStatus {
+                    id,
+                    kind: None,
+                    status: "stale".to_string(),
+                    locked: Some(locked),
+                }),
+        );
+    }
+    statuses
+}
+
+fn to_json(value: impl Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+fn status(manifest: &Manifest, manifest_path: &Path, params: &Params) -> Result<Value> {
+    let lockfile = load_or_create_lockfile(
+        &Lockfile::path_for_manifest(manifest_path, manifest)?,
+        false,
+    )?;
+    Ok(json!({
+        "manifest": manifest_path,
+        "entries": to_json(entry_statuses(manifest, &lockfile, &params.ids)),
+    }))
+}
+
+fn sync(manifest: &Manifest, manifest_path: &Path, params: &Params) -> Result<Value> {
+    let exe =
+        std::env::current_exe().map_err(|e| ApsError::io(e, "Failed to locate the aps binary"))?;
+    let mut command = Command::new(exe);
+    command
+        .args(["--no-color", "sync", "--yes", "--keep-going", "--manifest"])
+        .arg(manifest_path);
+    for id in &params.ids {
+        command.args(["--only", id]);
+    }
+    if params.upgrade {
+        command.arg("--upgrade");
+    }
+    if let Some(lockfile) = crate::lockfile::selected() {
+        command.args(["--lockfile", lockfile]);
+    }
+    let output = command
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ApsError::io(e, "Failed to run aps sync"))?;
+
+    let lockfile = load_or_create_lockfile(
+        &Lockfile::path_for_manifest(manifest_path, manifest)?,
+        false,
+    )?;
+    let success = output.status.success();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    Ok(json!({
+        "success": success,
+        "entries": to_json(entry_statuses(manifest, &lockfile, &params.ids)),
+        "output": String::from_utf8_lossy(&output.stdout),
+        "error": (!success).then_some(stderr),
+    }))
+}
+
+fn validate(manifest: &Manifest, manifest_path: &Path, params: &Params) -> Value {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    if let Err(e) = validate_manifest(manifest) {
+        errors.push(RpcError::from(e).to_value());
+        return json!({ "valid": false, "errors": errors, "warnings": warnings });
+    }
+    if let Ok(raw) = std::fs::read_to_string(manifest_path) {
+        for lint in lint_manifest(manifest, &raw) {
+            let code = WarningCode::IneffectiveSetting;
+            if !manifest.suppressed_warnings(&lint.id).contains(code) {
+                warnings.push(Warning::new(code, lint.to_string()).to_string());
+            }
+        }
+    }
+    warnings.extend(detect_overlapping_destinations(manifest));
+
+    let base_dir = manifest_dir(manifest_path);
+    for entry in &manifest.entries {
+        let suppressed = manifest.suppressed_warnings(&entry.id);
+        let mut report = |code, message: String| {
+            for warning in suppressed.coded(code, vec![message]) {
+                warnings.push(format!("{}: {}", entry.id, warning));
+            }
+        };
+        let sources: Vec<_> = if entry.is_composite() {
+            entry
+                .sources
+                .iter()
+                .filter(|s| !matches!(s, Source::Generated { .. }))
+                .collect()
+        } else {
+            entry.source.iter().collect()
+        };
+        if sources.is_empty() {
+            report(
+                WarningCode::SourceUnavailable,
+                format!("Entry '{}' has no source configured", entry.id),
+            );
+        }
+        for source in sources {
+            let resolved = match source.to_adapter().resolve(&base_dir) {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    let message = format!("Source validation failed: {}", e);
+                    report(WarningCode::SourceUnavailable, message);
+                    continue;
+                }
+            };
+            let path = &resolved.source_path;
+            if !path.exists() {
+                let message = format!("Source path not found: {:?}", path);
+                report(WarningCode::SourceUnavailable, message);
+                continue;
+            }
+            let kind_warnings = match entry.kind {
+                AssetKind::CursorHooks => {
+                    validate_cursor_hooks(path, false).map(|w| (WarningCode::InvalidHooks, w))
+                }
+                AssetKind::Persona => {
+                    validate_personas(path, false).map(|w| (WarningCode::InvalidPersona, w))
+                }
+                _ => continue,
+            };
+            match kind_warnings {
+                Ok((code, found)) => found.into_iter().for_each(|w| report(code, w)),
+                Err(e) => errors.push(RpcError::from(e).to_value()),
+            }
+        }
+    }
+
+    if params.strict {
+        errors.extend(
+            warnings
+                .drain(..)
+                .map(|w| json!({ "code": APS_ERROR, "message": w })),
+        );
+    }
+    json!({ "valid": errors.is_empty(), "errors": errors, "warnings": warnings })
+}
+
+fn explain(manifest: &Manifest, manifest_path: &Path, params: &Params) -> Result<Value> {
+    validate_manifest(manifest)?;
+    let base_dir = manifest_dir(manifest_path);
+    let lockfile = load_or_create_lockfile(
+        &Lockfile::path_for_manifest(manifest_path, manifest)?,
+        false,
+    )?;
+    let mut plan = Plan::build(
+        manifest,
+        manifest_path,
+        &base_dir,
+        &lockfile,
+        params.upgrade,
+    )?;
+    if !params.ids.is_empty() {
+        plan.changes.retain(|c| params.ids.contains(&c.id));
+    }
+    Ok(to_json(plan))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn server_for(manifest: &str) -> (TempDir, Server) {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("aps.yaml");
+        std::fs::write(&path, manifest).unwrap();
+        (dir, Server::new(Some(path)))
+    }
+
+    #[test]
+    fn test_protocol_errors() {
+        let (_dir, server) = server_for("entries: []\n");
+
+        let response = server.handle_line("{not json").unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":7,"method":"deploy"}"#)
+            .unwrap();
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":8,"method":"status","params":{"only":["x"]}}"#)
+            .unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":9,"method":"status","params":{"ids":["x"]}}"#)
+            .unwrap();
+        assert_eq!(response["error"]["code"], APS_ERROR);
+        assert_eq!(
+            response["error"]["data"]["code"],
+            "aps::manifest::entry_not_found"
+        );
+
+        // Notifications get no response
+        assert!(server
+            .handle_line(r#"{"jsonrpc":"2.0","method":"status"}"#)
+            .is_none());
+    }
+
+    #[test]
+    fn test_status_and_explain_for_unsynced_entry() {
+        let (dir, server) = server_for(
+            "entries:\n  - id: rules\n    kind: agents_md\n    source:\n      type: filesystem\n      root: .\n      path: SOURCE.md\n    dest: AGENTS.md\n",
+        );
+        std::fs::write(dir.path().join("SOURCE.md"), "# Rules\n").unwrap();
+
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":1,"method":"status"}"#)
+            .unwrap();
+        let entry = &response["result"]["entries"][0];
+        assert_eq!(entry["id"], "rules");
+        assert_eq!(entry["kind"], "agents_md");
+        assert_eq!(entry["status"], "never");
+
+        let response = server
+            .handle_line(
+                r#"{"jsonrpc":"2.0","id":2,"method":"explain","params":{"ids":["rules"]}}"#,
+            )
+            .unwrap();
+        assert_eq!(response["result"]["changes"][0]["action"], "install");
+
+        let response = server
+            .handle_line(r#"{"jsonrpc":"2.0","id":3,"method":"validate"}"#)
+            .unwrap();
+        assert_eq!(response["result"]["valid"], true);
+    }
+}
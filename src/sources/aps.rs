@@ -0,0 +1,196 @@
+//! APS source adapter for entries curated by another APS-managed project.
+//!
+//! The referenced project (a local directory or a git repository) is read
+//! through its own manifest: the named entry's source is resolved relative to
+//! that project, following further `type: aps` references along the way.
+
+use super::{clone_and_resolve, expand_path, GitInfo, ResolvedSource, SourceAdapter};
+use crate::error::{ApsError, Result};
+use crate::manifest::{load_manifest, Source, DEFAULT_MANIFEST_NAME};
+use std::path::Path;
+
+/// How many projects a chain of aps sources may pass through
+const MAX_CHAIN_DEPTH: usize = 8;
+
+/// APS source adapter for an entry in another project's manifest
+#[derive(Debug, Clone)]
+pub struct ApsSource {
+    /// Project directory or git repository URL
+    pub project: String,
+    /// ID of the entry in the project's manifest
+    pub entry: String,
+    /// Git ref when the project is a repository
+    pub git_ref: String,
+    /// Manifest file within the project
+    pub manifest: Option<String>,
+}
+
+impl ApsSource {
+    /// Create a new ApsSource
+    pub fn new(project: String, entry: String, git_ref: String, manifest: Option<String>) -> Self {
+        Self {
+            project,
+            entry,
+            git_ref,
+            manifest,
+        }
+    }
+
+    /// Whether `project` names a git repository rather than a directory
+    fn is_git_project(&self) -> bool {
+        self.project.contains("://")
+            || self.project.starts_with("git@")
+            || self.project.ends_with(".git")
+    }
+
+    /// Resolve the referenced entry, recording each visited project entry in
+    /// `chain` as `(key, display name)` to catch cycles
+    fn resolve_chain(
+        &self,
+        manifest_dir: &Path,
+        chain: &mut Vec<(String, String)>,
+    ) -> Result<ResolvedSource> {
+        let clone = if self.is_git_project() {
+            Some(clone_and_resolve(&self.project, &self.git_ref, true, None)?)
+        } else {
+            None
+        };
+        let project_dir = match &clone {
+            Some(clone) => clone.repo_path.clone(),
+            None => manifest_dir.join(expand_path(&self.project)),
+        };
+        let manifest_path =
+            project_dir.join(self.manifest.as_deref().unwrap_or(DEFAULT_MANIFEST_NAME));
+        if !manifest_path.is_file() {
+            return Err(ApsError::source_path_not_found(
+                manifest_path,
+                Some(&project_dir),
+                clone.as_ref().map(|clone| clone.resolved_ref.as_str()),
+            ));
+        }
+
+        // Local projects are keyed by their real manifest path, so the same
+        // project reached through different relative paths is still a cycle
+        let key = match &clone {
+            Some(_) => self.display_name(),
+            None => format!(
+                "{}#{}",
+                manifest_path
+                    .canonicalize()
+                    .unwrap_or_else(|_| manifest_path.clone())
+                    .display(),
+                self.entry
+            ),
+        };
+        let seen = chain.iter().any(|(k, _)| *k == key);
+        chain.push((key, self.display_name()));
+        if seen {
+            let names: Vec<&str> = chain.iter().map(|(_, name)| name.as_str()).collect();
+            return Err(ApsError::ApsSourceCycle {
+                chain: names.join(" -> "),
+            });
+        }
+        if chain.len() > MAX_CHAIN_DEPTH {
+            return Err(ApsError::InvalidInput {
+                message: format!(
+                    "aps source {} is more than {} projects deep",
+                    chain[0].1, MAX_CHAIN_DEPTH
+                ),
+            });
+        }
+
+        let manifest = load_manifest(&manifest_path)?;
+        let referenced = manifest
+            .entries
+            .iter()
+            .find(|e| e.id == self.entry)
+            .ok_or_else(|| ApsError::ApsSourceEntryNotFound {
+                entry: self.entry.clone(),
+                manifest: manifest_path.clone(),
+            })?;
+        let not_installable = |reason: &str| ApsError::InvalidInput {
+            message: format!(
+                "Entry '{}' in {:?} cannot be referenced: {}",
+                self.entry, manifest_path, reason
+            ),
+        };
+        let source = match &referenced.source {
+            Some(source) if !referenced.is_composite() => source,
+            _ => return Err(not_installable("it has no single source")),
+        };
+
+        let mut resolved = match source {
+            Source::Aps {
+                project,
+                entry,
+                r#ref,
+                manifest,
+            } => ApsSource::new(
+                project.clone(),
+                entry.clone(),
+                r#ref.clone(),
+                manifest.clone(),
+            )
+            .resolve_chain(&project_dir, chain)?,
+            Source::Git { .. } | Source::Filesystem { .. } | Source::Http { .. } => {
+                source.to_adapter().resolve(&project_dir)?
+            }
+            Source::Generated { .. } => {
+                return Err(not_installable("its source only works in composites"))
+            }
+        };
+
+        match clone {
+            Some(clone) => {
+                // Content inside the clone disappears with it, so always copy
+                resolved.use_symlink = false;
+                resolved.original_root = None;
+                resolved.expanded_root = None;
+                if resolved.git_info.is_none() {
+                    resolved.git_info = Some(GitInfo {
+                        resolved_ref: clone.resolved_ref.clone(),
+                        commit_sha: clone.commit_sha.clone(),
+                        commit_date: clone.commit_date.clone(),
+                        release: clone.release.clone(),
+                    });
+                }
+                Ok(resolved.holding(clone))
+            }
+            None => {
+                // Keep symlink targets recorded relative to this manifest
+                if let Some(root) = &resolved.original_root {
+                    if !root.starts_with(['/', '$', '~']) {
+                        let project = self.project.trim_end_matches('/');
+                        resolved.original_root = Some(format!("{}/{}", project, root));
+                    }
+                }
+                Ok(resolved)
+            }
+        }
+    }
+}
+
+impl SourceAdapter for ApsSource {
+    fn source_type(&self) -> &'static str {
+        "aps"
+    }
+
+    fn display_name(&self) -> String {
+        format!("aps:{}#{}", self.project, self.entry)
+    }
+
+    fn path(&self) -> &str {
+        "."
+    }
+
+    fn supports_symlink(&self) -> bool {
+        !self.is_git_project()
+    }
+
+    fn resolve(&self, manifest_dir: &Path) -> Result<ResolvedSource> {
+        let mut chain = Vec::new();
+        let mut resolved = self.resolve_chain(manifest_dir, &mut chain)?;
+        resolved.source_display = self.display_name();
+        Ok(resolved)
+    }
+}
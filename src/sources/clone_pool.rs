@@ -0,0 +1,94 @@
+//! Git clones made ahead of time, several at once, and shared by every source
+//! naming the same repository and ref.
+//!
+//! `aps validate` clones each distinct repository once, up to `--jobs` at a
+//! time, instead of once per source in turn, then checks entries in manifest
+//! order against the clones.
+
+use super::git::{clone_and_resolve, GitSource, ResolvedGitSource};
+use super::ResolvedSource;
+use crate::error::Result;
+use crate::manifest::Source;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Repository, ref, and whether the clone is shallow
+type CloneKey = (String, String, bool);
+
+/// Clones of git sources, by repository and ref
+#[derive(Default)]
+pub struct ClonePool {
+    clones: HashMap<CloneKey, Result<Arc<ResolvedGitSource>>>,
+}
+
+fn clone_key(source: &Source) -> Option<CloneKey> {
+    match source {
+        Source::Git {
+            repo,
+            r#ref,
+            shallow,
+            ..
+        } => Some((repo.clone(), r#ref.clone(), *shallow)),
+        _ => None,
+    }
+}
+
+impl ClonePool {
+    /// Clone each distinct repository and ref among the git `sources`, up to
+    /// `jobs` at once. `on_done` is called with the repository, the ref, and
+    /// the outcome as each clone finishes, in the order they finish.
+    pub fn prefetch<'a>(
+        sources: impl IntoIterator<Item = &'a Source>,
+        jobs: usize,
+        on_done: impl Fn(&str, &str, &Result<Arc<ResolvedGitSource>>) + Sync,
+    ) -> Self {
+        let mut pending: Vec<CloneKey> = sources.into_iter().filter_map(clone_key).collect();
+        pending.sort();
+        pending.dedup();
+
+        let next = AtomicUsize::new(0);
+        let clones = Mutex::new(HashMap::new());
+        std::thread::scope(|scope| {
+            for _ in 0..jobs.min(pending.len()) {
+                scope.spawn(|| {
+                    while let Some(key) = pending.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let (repo, git_ref, shallow) = key;
+                        let clone = clone_and_resolve(repo, git_ref, *shallow, None).map(Arc::new);
+                        on_done(repo, git_ref, &clone);
+                        clones.lock().unwrap().insert(key.clone(), clone);
+                    }
+                });
+            }
+        });
+        Self {
+            clones: clones.into_inner().unwrap(),
+        }
+    }
+
+    /// Resolve `source`, using the pool's clone of its repository if there is
+    /// one. A failed clone's error is returned to the first source asking for
+    /// it; later ones try again.
+    pub fn resolve(&mut self, source: &Source, manifest_dir: &Path) -> Result<ResolvedSource> {
+        let Source::Git {
+            repo,
+            r#ref,
+            shallow,
+            path,
+        } = source
+        else {
+            return source.to_adapter().resolve(manifest_dir);
+        };
+        let key = (repo.clone(), r#ref.clone(), *shallow);
+        match self.clones.get(&key) {
+            Some(Ok(clone)) => GitSource::new(repo.clone(), r#ref.clone(), *shallow, path.clone())
+                .resolve_in(Arc::clone(clone)),
+            Some(Err(_)) => match self.clones.remove(&key) {
+                Some(Err(e)) => Err(e),
+                _ => source.to_adapter().resolve(manifest_dir),
+            },
+            None => source.to_adapter().resolve(manifest_dir),
+        }
+    }
+}
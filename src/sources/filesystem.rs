@@ -2,6 +2,7 @@
 
 use super::{expand_path, ResolvedSource, SourceAdapter};
 use crate::error::Result;
+use crate::path_glob::glob_base;
 use std::path::{Path, PathBuf};
 
 /// Filesystem source adapter for local files and directories
@@ -44,7 +45,7 @@ impl SourceAdapter for FilesystemSource {
     }
 
     fn resolve(&self, manifest_dir: &Path) -> Result<ResolvedSource> {
-        let path = expand_path(self.path());
+        let path = glob_base(&expand_path(self.path()));
         let expanded_root = expand_path(&self.root);
 
         let root_path = if Path::new(&expanded_root).is_absolute() {
@@ -80,6 +81,7 @@ impl SourceAdapter for FilesystemSource {
             self.symlink,
             original_root,
             expanded_root_with_path,
-        ))
+        )
+        .with_root(root_path))
     }
 }
@@ -0,0 +1,152 @@
+//! Generated sources: content aps renders itself when composing a composite
+//! entry, such as an index of the skills installed by the manifest.
+
+use super::{ResolvedSource, SourceAdapter};
+use crate::catalog::extract_agent_skill_description;
+use crate::discover::{find_skill_md, read_skill_name};
+use crate::error::{ApsError, Result};
+use crate::manifest::{AssetKind, Generator, Manifest};
+use std::path::{Path, PathBuf};
+
+/// Heading of the section rendered by the `skills_index` generator
+pub const SKILLS_INDEX_HEADING: &str = "## Installed Skills";
+
+/// Source adapter for `type: generated`. Generated content has no path to
+/// resolve; composite entries render it with [`render_generated`] instead.
+#[derive(Debug, Clone)]
+pub struct GeneratedSource {
+    pub generator: Generator,
+}
+
+impl GeneratedSource {
+    /// Create a new GeneratedSource
+    pub fn new(generator: Generator) -> Self {
+        Self { generator }
+    }
+}
+
+impl SourceAdapter for GeneratedSource {
+    fn source_type(&self) -> &'static str {
+        "generated"
+    }
+
+    fn display_name(&self) -> String {
+        format!("generated:{}", self.generator.as_str())
+    }
+
+    fn path(&self) -> &str {
+        "."
+    }
+
+    fn resolve(&self, _manifest_dir: &Path) -> Result<ResolvedSource> {
+        Err(ApsError::GeneratedSourceNotComposite {
+            generator: self.generator.as_str().to_string(),
+        })
+    }
+
+    fn supports_symlink(&self) -> bool {
+        false
+    }
+}
+
+/// Render a generated source as markdown from the manifest's installed assets
+pub fn render_generated(generator: Generator, manifest: &Manifest, manifest_dir: &Path) -> String {
+    match generator {
+        Generator::SkillsIndex => render_skills_index(manifest, manifest_dir),
+    }
+}
+
+/// List each installed skill with its description, in manifest order
+fn render_skills_index(manifest: &Manifest, manifest_dir: &Path) -> String {
+    let mut lines = Vec::new();
+    for entry in &manifest.entries {
+        if !matches!(
+            entry.kind,
+            AssetKind::AgentSkill | AssetKind::CursorSkillsRoot
+        ) {
+            continue;
+        }
+        let dest = manifest_dir.join(entry.destination());
+        let single = find_skill_md(&dest).is_some();
+        for (folder, skill_md) in installed_skill_folders(&dest) {
+            let name = read_skill_name(&skill_md).unwrap_or_else(|| {
+                folder
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            });
+            // An entry's description describes it only when it is one skill
+            let description = entry
+                .description
+                .clone()
+                .filter(|_| single)
+                .or_else(|| extract_agent_skill_description(&folder));
+            lines.push(match description {
+                Some(description) => format!("- **{}**: {}", name, description),
+                None => format!("- **{}**", name),
+            });
+        }
+    }
+
+    if lines.is_empty() {
+        lines.push("No skills installed.".to_string());
+    }
+    format!("{}\n\n{}\n", SKILLS_INDEX_HEADING, lines.join("\n"))
+}
+
+/// Skill folders at an installed destination, each with its SKILL.md: the
+/// destination itself, or its immediate subfolders
+fn installed_skill_folders(dest: &Path) -> Vec<(PathBuf, PathBuf)> {
+    if let Some(skill_md) = find_skill_md(dest) {
+        return vec![(dest.to_path_buf(), skill_md)];
+    }
+    let mut folders: Vec<(PathBuf, PathBuf)> = std::fs::read_dir(dest)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter_map(|p| find_skill_md(&p).map(|skill_md| (p, skill_md)))
+                .collect()
+        })
+        .unwrap_or_default();
+    folders.sort();
+    folders
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_skills_index_lists_installed_skills() {
+        let dir = TempDir::new().unwrap();
+        let skills = dir.path().join(".claude/skills");
+        for (name, frontmatter) in [
+            ("pdf", "name: pdf\ndescription: Extract text from PDFs"),
+            ("review", "name: code-review"),
+        ] {
+            std::fs::create_dir_all(skills.join(name)).unwrap();
+            // SKILL.md is found in any case
+            let file = if name == "review" {
+                "skill.md"
+            } else {
+                "SKILL.md"
+            };
+            std::fs::write(
+                skills.join(name).join(file),
+                format!("---\n{}\n---\n", frontmatter),
+            )
+            .unwrap();
+        }
+        let manifest: Manifest = serde_yaml::from_str(
+            "entries:\n  - id: skills\n    kind: agent_skill\n    dest: .claude/skills\n  - id: rules\n    kind: agents_md\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            render_generated(Generator::SkillsIndex, &manifest, dir.path()),
+            "## Installed Skills\n\n- **pdf**: Extract text from PDFs\n- **code-review**\n"
+        );
+    }
+}
@@ -1,18 +1,113 @@
 //! Git source adapter for cloning repositories.
 
+use super::git_version::{require_git, supports, GitFeature};
+use super::release::{release_constraint, resolve_release};
+use super::tarball::fetch_github_tarball;
 use super::{expand_path, GitInfo, ResolvedSource, SourceAdapter};
 use crate::error::{ApsError, Result};
+use crate::output::short_id;
+use crate::path_glob::glob_base;
+use crate::path_guard::ensure_within;
+use sha2::{Digest, Sha256};
+use std::cell::Cell;
+use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tempfile::TempDir;
 use tracing::{debug, info};
 
+/// Whether clones should fetch as little as possible, see [`set_low_bandwidth`]
+static LOW_BANDWIDTH: AtomicBool = AtomicBool::new(false);
+
+/// Make every clone in this process shallow and, when the caller names the
+/// path it needs, sparse: only that path and license files are checked out
+pub fn set_low_bandwidth(enabled: bool) {
+    LOW_BANDWIDTH.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether low-bandwidth mode is on
+pub fn low_bandwidth() -> bool {
+    LOW_BANDWIDTH.load(Ordering::Relaxed)
+}
+
+thread_local! {
+    /// Deadline for git commands run on this thread, set by `with_git_deadline`
+    static GIT_DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Run `f` with any git command it starts on this thread killed once
+/// `deadline` passes. A command killed this way fails with a timed-out error.
+pub fn with_git_deadline<T>(deadline: Option<Instant>, f: impl FnOnce() -> T) -> T {
+    let previous = GIT_DEADLINE.with(|d| d.replace(deadline));
+    let result = f();
+    GIT_DEADLINE.with(|d| d.set(previous));
+    result
+}
+
+/// Run a git command to completion, honoring the thread's deadline.
+pub(super) fn run_git(cmd: &mut Command) -> std::io::Result<Output> {
+    let Some(deadline) = GIT_DEADLINE.with(|d| d.get()) else {
+        return cmd.output();
+    };
+
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Drain pipes on separate threads so a chatty command cannot block on a full pipe
+    let drain = |pipe: Option<Box<dyn Read + Send>>| {
+        std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            buf
+        })
+    };
+    let stdout = drain(
+        child
+            .stdout
+            .take()
+            .map(|p| Box::new(p) as Box<dyn Read + Send>),
+    );
+    let stderr = drain(
+        child
+            .stderr
+            .take()
+            .map(|p| Box::new(p) as Box<dyn Read + Send>),
+    );
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Output {
+                status,
+                stdout: stdout.join().unwrap_or_default(),
+                stderr: stderr.join().unwrap_or_default(),
+            });
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "git command timed out",
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
 /// Git source adapter for cloning repositories
 #[derive(Debug, Clone)]
 pub struct GitSource {
     /// Repository URL (SSH or HTTPS)
     pub repo: String,
-    /// Git ref (branch, tag, commit) - "auto" tries main then master
+    /// Git ref (branch, tag, commit) - "auto" follows the remote's default branch
     pub git_ref: String,
     /// Whether to use shallow clone
     pub shallow: bool,
@@ -53,27 +148,39 @@ impl SourceAdapter for GitSource {
         info!("Cloning git repository: {}", self.repo);
 
         // Clone the repository
-        let resolved_git = clone_and_resolve(&self.repo, &self.git_ref, self.shallow)?;
+        let path = expand_path(self.path());
+        let resolved_git = clone_and_resolve(&self.repo, &self.git_ref, self.shallow, Some(&path))?;
+        self.resolve_in(Arc::new(resolved_git))
+    }
+}
 
+impl GitSource {
+    /// Resolve this source's `path` within `clone`, a clone of its
+    /// repository at its ref. `clone` may be shared by several sources.
+    pub fn resolve_in(&self, clone: Arc<ResolvedGitSource>) -> Result<ResolvedSource> {
         // Build the path within the cloned repo
-        let path = expand_path(self.path());
+        let path = glob_base(&expand_path(self.path()));
         let source_path = if path == "." {
-            resolved_git.repo_path.clone()
+            clone.repo_path.clone()
         } else {
-            resolved_git.repo_path.join(&path)
+            clone.repo_path.join(&path)
         };
 
+        // A symlinked `path` must not lead out of the clone
+        ensure_within(&clone.repo_path, &source_path)?;
+
         let git_info = GitInfo {
-            resolved_ref: resolved_git.resolved_ref.clone(),
-            commit_sha: resolved_git.commit_sha.clone(),
+            resolved_ref: clone.resolved_ref.clone(),
+            commit_sha: clone.commit_sha.clone(),
+            commit_date: clone.commit_date.clone(),
+            release: clone.release.clone(),
         };
 
-        Ok(ResolvedSource::git(
-            source_path,
-            self.display_name(),
-            git_info,
-            resolved_git,
-        ))
+        let repo_path = clone.repo_path.clone();
+        Ok(
+            ResolvedSource::git(source_path, self.display_name(), git_info, clone)
+                .with_root(repo_path),
+        )
     }
 }
 
@@ -87,11 +194,159 @@ pub struct ResolvedGitSource {
     pub resolved_ref: String,
     /// Commit SHA at the resolved ref
     pub commit_sha: String,
+    /// Committer date of the commit (RFC 3339), if it could be read
+    pub commit_date: Option<String>,
+    /// Name of the release a `release:` ref resolved to
+    pub release: Option<String>,
+}
+
+/// Branches to try for a ref. `auto` means the remote's default branch (its
+/// HEAD), with main and then master as fallbacks when HEAD cannot be read.
+/// A `release:` ref is the tag of the release it resolves to.
+fn candidate_refs(url: &str, git_ref: &str) -> Vec<String> {
+    if let Some(constraint) = release_constraint(git_ref) {
+        return resolve_release(url, constraint)
+            .map(|release| release.tag)
+            .into_iter()
+            .collect();
+    }
+    if git_ref != "auto" {
+        return vec![git_ref.to_string()];
+    }
+    let mut refs: Vec<String> = remote_head(url)
+        .map(|(branch, _)| branch)
+        .into_iter()
+        .collect();
+    for fallback in ["main", "master"] {
+        if !refs.iter().any(|r| r == fallback) {
+            refs.push(fallback.to_string());
+        }
+    }
+    refs
+}
+
+/// The remote's default branch and its commit, via `git ls-remote --symref`
+fn remote_head(url: &str) -> Option<(String, String)> {
+    let output = run_git(
+        Command::new("git")
+            .args(["ls-remote", "--symref"])
+            .arg(url)
+            .arg("HEAD"),
+    )
+    .ok()?;
+    if !output.status.success() {
+        debug!(
+            "git ls-remote --symref failed for {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return None;
+    }
+    let head = parse_symref_head(&String::from_utf8_lossy(&output.stdout));
+    if let Some((ref branch, _)) = head {
+        debug!("Remote HEAD of {} is '{}'", url, branch);
+    }
+    head
+}
+
+/// Parse `git ls-remote --symref <url> HEAD` output: a
+/// `ref: refs/heads/<branch>` line for HEAD followed by HEAD's commit
+fn parse_symref_head(output: &str) -> Option<(String, String)> {
+    let mut branch = None;
+    let mut sha = None;
+    for line in output.lines() {
+        let (value, name) = line.split_once('\t')?;
+        if name != "HEAD" {
+            continue;
+        }
+        match value.strip_prefix("ref: ") {
+            Some(target) => branch = target.strip_prefix("refs/heads/").map(str::to_string),
+            None => sha = Some(value.to_string()),
+        }
+    }
+    Some((branch?, sha?))
+}
+
+/// Branch and tag names a remote advertises, via `git ls-remote --heads --tags`
+pub fn list_remote_refs(url: &str) -> Result<Vec<String>> {
+    require_git()?;
+    let output = run_git(
+        Command::new("git")
+            .args(["ls-remote", "--heads", "--tags", "--refs"])
+            .arg(url),
+    )
+    .map_err(|e| ApsError::GitError {
+        message: format!("Failed to execute git ls-remote: {}", e),
+    })?;
+    if !output.status.success() {
+        return Err(ApsError::GitError {
+            message: format!(
+                "git ls-remote failed for {}: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+    Ok(parse_ls_remote_refs(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Parse `git ls-remote --refs` output (`<sha>\trefs/heads/<name>`) into ref names
+fn parse_ls_remote_refs(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .filter_map(|(_, name)| {
+            name.strip_prefix("refs/heads/")
+                .or_else(|| name.strip_prefix("refs/tags/"))
+        })
+        .map(str::to_string)
+        .collect()
 }
 
 /// Clone a git repository and resolve the ref using the git CLI.
 /// This inherits the user's existing git configuration (SSH, credentials, etc.)
-pub fn clone_and_resolve(url: &str, git_ref: &str, shallow: bool) -> Result<ResolvedGitSource> {
+///
+/// `path` is the part of the repository the caller reads; in low-bandwidth
+/// mode nothing else is checked out.
+pub fn clone_and_resolve(
+    url: &str,
+    git_ref: &str,
+    shallow: bool,
+    path: Option<&str>,
+) -> Result<ResolvedGitSource> {
+    require_git()?;
+    let shallow = shallow || low_bandwidth();
+    let sparse = sparse_path(path);
+
+    // A release ref clones the tag of the release it resolves to
+    let (git_ref, release) = match release_constraint(git_ref) {
+        Some(constraint) => {
+            let release = resolve_release(url, constraint)?;
+            (release.tag, Some(release.name))
+        }
+        None => (git_ref.to_string(), None),
+    };
+    let git_ref = git_ref.as_str();
+
+    // Public GitHub repositories download faster as a tarball when the
+    // manifest opts in, unless only part of the repository is wanted
+    if shallow && sparse.is_none() {
+        let reference = if git_ref == "auto" {
+            remote_head(url).map(|(branch, _)| branch)
+        } else {
+            Some(git_ref.to_string())
+        };
+        if let Some(resolved) =
+            reference.and_then(|reference| fetch_github_tarball(url, &reference, &reference))
+        {
+            return Ok(ResolvedGitSource {
+                release,
+                ..resolved
+            });
+        }
+    }
     info!("Cloning git repository: {}", url);
 
     // Create temp directory for the clone
@@ -100,23 +355,18 @@ pub fn clone_and_resolve(url: &str, git_ref: &str, shallow: bool) -> Result<Reso
 
     let repo_path = temp_dir.path().to_path_buf();
 
-    // For auto ref, we need to try different branches
-    let refs_to_try = if git_ref == "auto" {
-        vec!["main", "master"]
-    } else {
-        vec![git_ref]
-    };
-
-    let resolved_ref = clone_with_ref_fallback(url, &repo_path, &refs_to_try, shallow)?;
+    let refs_to_try = candidate_refs(url, git_ref);
+    let resolved_ref = clone_with_ref_fallback(url, &repo_path, &refs_to_try, shallow, sparse)?;
 
     // Get the commit SHA
     let commit_sha = get_head_commit(&repo_path)?;
+    let commit_date = get_head_commit_date(&repo_path);
 
     info!(
         "Cloned {} at ref '{}' (commit {})",
         url,
         resolved_ref,
-        &commit_sha[..8.min(commit_sha.len())]
+        short_id(&commit_sha)
     );
 
     Ok(ResolvedGitSource {
@@ -124,11 +374,19 @@ pub fn clone_and_resolve(url: &str, git_ref: &str, shallow: bool) -> Result<Reso
         repo_path,
         resolved_ref,
         commit_sha,
+        commit_date,
+        release,
     })
 }
 
 /// Try to clone with fallback refs using git CLI
-fn clone_with_ref_fallback(url: &str, path: &Path, refs: &[&str], shallow: bool) -> Result<String> {
+fn clone_with_ref_fallback(
+    url: &str,
+    path: &Path,
+    refs: &[String],
+    shallow: bool,
+    sparse: Option<&str>,
+) -> Result<String> {
     let mut last_error = None;
 
     for ref_name in refs {
@@ -146,6 +404,9 @@ fn clone_with_ref_fallback(url: &str, path: &Path, refs: &[&str], shallow: bool)
         if shallow {
             cmd.arg("--depth").arg("1");
         }
+        if sparse.is_some() {
+            cmd.arg("--filter=blob:none").arg("--sparse");
+        }
 
         cmd.arg("--branch").arg(ref_name);
         cmd.arg("--single-branch");
@@ -154,12 +415,15 @@ fn clone_with_ref_fallback(url: &str, path: &Path, refs: &[&str], shallow: bool)
 
         debug!("Running: git clone --branch {} {}", ref_name, url);
 
-        let output = cmd.output().map_err(|e| ApsError::GitError {
+        let output = run_git(&mut cmd).map_err(|e| ApsError::GitError {
             message: format!("Failed to execute git command: {}", e),
         })?;
 
         if output.status.success() {
-            return Ok(ref_name.to_string());
+            if let Some(sparse) = sparse {
+                restrict_checkout(path, sparse)?;
+            }
+            return Ok(ref_name.clone());
         }
 
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -173,25 +437,72 @@ fn clone_with_ref_fallback(url: &str, path: &Path, refs: &[&str], shallow: bool)
         .unwrap_or_default();
 
     Err(ApsError::GitError {
-        message: format!(
-            "Failed to clone with refs {:?}{}",
-            refs.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
-            error_detail
-        ),
+        message: format!("Failed to clone with refs {:?}{}", refs, error_detail),
     })
 }
 
+/// The path to check out sparsely, in low-bandwidth mode and when git
+/// supports it. The whole repository is wanted for `.` or no path.
+fn sparse_path(path: Option<&str>) -> Option<&str> {
+    let path = path?.trim_matches('/');
+    let wanted = !path.is_empty() && path != ".";
+    (wanted && low_bandwidth() && supports(GitFeature::SparseCheckout)).then_some(path)
+}
+
+/// Limit the checkout of the clone at `repo_path` to `path`, plus license
+/// files at any depth so upstream license lookup still finds them
+fn restrict_checkout(repo_path: &Path, path: &str) -> Result<()> {
+    debug!("Checking out only {} in {:?}", path, repo_path);
+    let output = run_git(
+        Command::new("git")
+            .arg("-C")
+            .arg(repo_path)
+            .args(["sparse-checkout", "set", "--no-cone"])
+            .arg(format!("/{}", path))
+            .args(["LICENSE", "LICENCE", "COPYING"].map(any_case_prefix)),
+    )
+    .map_err(|e| ApsError::GitError {
+        message: format!("Failed to execute git sparse-checkout: {}", e),
+    })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ApsError::GitError {
+            message: format!("Failed to check out {}: {}", path, stderr.trim()),
+        });
+    }
+    Ok(())
+}
+
+/// A sparse-checkout pattern matching names that start with `prefix` in any
+/// case, such as `[Ll][Ii]*` for `LI`
+fn any_case_prefix(prefix: &str) -> String {
+    let mut pattern: String = prefix
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphabetic() {
+                format!("[{}{}]", c.to_ascii_uppercase(), c.to_ascii_lowercase())
+            } else {
+                c.to_string()
+            }
+        })
+        .collect();
+    pattern.push('*');
+    pattern
+}
+
 /// Get the HEAD commit SHA using git CLI
 fn get_head_commit(repo_path: &Path) -> Result<String> {
-    let output = Command::new("git")
-        .arg("-C")
-        .arg(repo_path)
-        .arg("rev-parse")
-        .arg("HEAD")
-        .output()
-        .map_err(|e| ApsError::GitError {
-            message: format!("Failed to execute git rev-parse: {}", e),
-        })?;
+    let output = run_git(
+        Command::new("git")
+            .arg("-C")
+            .arg(repo_path)
+            .arg("rev-parse")
+            .arg("HEAD"),
+    )
+    .map_err(|e| ApsError::GitError {
+        message: format!("Failed to execute git rev-parse: {}", e),
+    })?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -204,17 +515,359 @@ fn get_head_commit(repo_path: &Path) -> Result<String> {
     Ok(sha)
 }
 
+/// Get the committer date of HEAD (RFC 3339) using git CLI
+fn get_head_commit_date(repo_path: &Path) -> Option<String> {
+    let output = run_git(Command::new("git").arg("-C").arg(repo_path).args([
+        "show",
+        "-s",
+        "--format=%cI",
+        "HEAD",
+    ]))
+    .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let date = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!date.is_empty()).then_some(date)
+}
+
+/// Object ID of `path` at `commit` (a tree for directories, a blob for files),
+/// via `git rev-parse <commit>:<path>`. Identical content has the same ID.
+pub fn tree_oid(repo_path: &Path, commit: &str, path: &Path) -> Option<String> {
+    let spec = format!("{}:{}", commit, path.to_string_lossy().replace('\\', "/"));
+    let output = run_git(Command::new("git").arg("-C").arg(repo_path).args([
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        &spec,
+    ]))
+    .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let oid = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!oid.is_empty()).then_some(oid)
+}
+
+/// Content of `path` (relative to `dir`) as of `commit` in the repository at
+/// `dir`, or `None` if the file did not exist there
+pub fn file_at_commit(dir: &Path, commit: &str, path: &str) -> Option<String> {
+    let spec = format!("{}:./{}", commit, path.replace('\\', "/"));
+    let output = run_git(Command::new("git").arg("-C").arg(dir).args(["show", &spec])).ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Files under `paths` with uncommitted changes in the git work tree at `dir`,
+/// as `git status` lists them. Untracked files are left out, and nothing is
+/// reported when `dir` is not inside a work tree or git is unavailable.
+pub fn uncommitted_changes(dir: &Path, paths: &[PathBuf]) -> Vec<String> {
+    if paths.is_empty() {
+        return Vec::new();
+    }
+    let output = run_git(
+        Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["status", "--porcelain", "--untracked-files=no", "--"])
+            .args(paths),
+    );
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.get(3..))
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Count the commits between `from` and `to` on a remote ref.
+///
+/// Fetches commit history only (no trees or blobs), so this stays cheap even
+/// for large repositories. Returns `None` if the history cannot be fetched or
+/// `from` is not an ancestor of `to`.
+pub fn count_commits_behind(url: &str, git_ref: &str, from: &str, to: &str) -> Option<u64> {
+    if require_git().is_err() || !supports(GitFeature::TreelessClone) {
+        return None;
+    }
+    let temp_dir = TempDir::new().ok()?;
+    let repo_path = temp_dir.path();
+
+    let cloned = candidate_refs(url, git_ref).iter().any(|ref_name| {
+        debug!("Fetching history of '{}' from {}", ref_name, url);
+        run_git(
+            Command::new("git")
+                .args(["clone", "--bare", "--filter=tree:0", "--single-branch"])
+                .arg("--branch")
+                .arg(ref_name)
+                .arg(url)
+                .arg(repo_path),
+        )
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+    });
+    if !cloned {
+        return None;
+    }
+
+    let output = run_git(
+        Command::new("git")
+            .arg("-C")
+            .arg(repo_path)
+            .args(["rev-list", "--count"])
+            .arg(format!("{}..{}", from, to)),
+    )
+    .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+/// A commit from `git log`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitSummary {
+    pub sha: String,
+    /// Committer date (RFC 3339)
+    pub date: String,
+    pub author: String,
+    pub subject: String,
+}
+
+/// Directory for a repository's cached bare clone within `cache_dir`
+pub fn cached_clone_dir(cache_dir: &Path, url: &str) -> PathBuf {
+    let name = url
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .rsplit(['/', ':'])
+        .next()
+        .filter(|n| !n.is_empty())
+        .unwrap_or("repo");
+    let digest = hex::encode(Sha256::digest(url.as_bytes()));
+    cache_dir.join(format!("{}-{}", name, &digest[..12]))
+}
+
+/// Create or refresh a cached, blobless bare clone of `url` under `cache_dir`.
+///
+/// The clone keeps full commit and tree history (but no file contents), so
+/// path-limited `git log` works offline after the first fetch.
+pub fn update_history_clone(cache_dir: &Path, url: &str) -> Result<PathBuf> {
+    update_bare_clone(cache_dir, url, Some("blob:none"))
+}
+
+/// Create or refresh a complete bare clone of `url` under `cache_dir`, which
+/// can check out any fetched commit without the network
+pub fn update_mirror_clone(cache_dir: &Path, url: &str) -> Result<PathBuf> {
+    update_bare_clone(cache_dir, url, None)
+}
+
+fn update_bare_clone(cache_dir: &Path, url: &str, filter: Option<&str>) -> Result<PathBuf> {
+    require_git()?;
+    let repo_path = cached_clone_dir(cache_dir, url);
+
+    let output = if repo_path.join("HEAD").exists() {
+        debug!("Refreshing cached clone of {} at {:?}", url, repo_path);
+        run_git(
+            Command::new("git")
+                .arg("-C")
+                .arg(&repo_path)
+                .args(["fetch", "--prune", "--tags", "origin"])
+                .arg("+refs/heads/*:refs/heads/*"),
+        )
+    } else {
+        info!("Caching clone of {}", url);
+        std::fs::create_dir_all(cache_dir)
+            .map_err(|e| ApsError::io(e, format!("Failed to create {:?}", cache_dir)))?;
+        let mut cmd = Command::new("git");
+        cmd.args(["clone", "--bare", "--quiet"]);
+        // Without partial clone support, fall back to a full clone
+        if let Some(filter) = filter.filter(|_| supports(GitFeature::PartialClone)) {
+            cmd.arg(format!("--filter={}", filter));
+        }
+        run_git(cmd.arg(url).arg(&repo_path))
+    }
+    .map_err(|e| ApsError::GitError {
+        message: format!("Failed to execute git command: {}", e),
+    })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ApsError::GitError {
+            message: format!("Failed to fetch history of {}: {}", url, stderr.trim()),
+        });
+    }
+    Ok(repo_path)
+}
+
+/// Whether a repository contains a commit object
+pub fn has_commit(repo_path: &Path, sha: &str) -> bool {
+    run_git(
+        Command::new("git")
+            .arg("-C")
+            .arg(repo_path)
+            .args(["cat-file", "-e"])
+            .arg(format!("{}^{{commit}}", sha)),
+    )
+    .map(|o| o.status.success())
+    .unwrap_or(false)
+}
+
+/// Write every branch and tag of a repository to a `git bundle` file
+pub fn create_bundle(repo_path: &Path, bundle_path: &Path) -> Result<()> {
+    require_git()?;
+    let output = run_git(
+        Command::new("git")
+            .arg("-C")
+            .arg(repo_path)
+            .args(["bundle", "create", "--quiet"])
+            .arg(bundle_path)
+            .arg("--all"),
+    )
+    .map_err(|e| ApsError::GitError {
+        message: format!("Failed to execute git bundle: {}", e),
+    })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ApsError::GitError {
+            message: format!("Failed to bundle {:?}: {}", repo_path, stderr.trim()),
+        });
+    }
+    Ok(())
+}
+
+/// Load a `git bundle` of `url` into the cached bare clone at `repo_path`,
+/// creating it if needed
+pub fn import_bundle(bundle_path: &Path, repo_path: &Path, url: &str) -> Result<()> {
+    require_git()?;
+    let output = if repo_path.join("HEAD").exists() {
+        run_git(
+            Command::new("git")
+                .arg("-C")
+                .arg(repo_path)
+                .arg("fetch")
+                .arg(bundle_path)
+                .args(["+refs/heads/*:refs/heads/*", "+refs/tags/*:refs/tags/*"]),
+        )
+    } else {
+        run_git(
+            Command::new("git")
+                .args(["clone", "--bare", "--quiet"])
+                .arg(bundle_path)
+                .arg(repo_path),
+        )
+    }
+    .map_err(|e| ApsError::GitError {
+        message: format!("Failed to execute git command: {}", e),
+    })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ApsError::GitError {
+            message: format!(
+                "Failed to import bundle {:?}: {}",
+                bundle_path,
+                stderr.trim()
+            ),
+        });
+    }
+
+    // Later online refreshes should fetch from the real remote, not the bundle
+    run_git(
+        Command::new("git")
+            .arg("-C")
+            .arg(repo_path)
+            .args(["remote", "set-url", "origin", url]),
+    )
+    .map_err(|e| ApsError::GitError {
+        message: format!("Failed to execute git remote: {}", e),
+    })?;
+    Ok(())
+}
+
+/// Commits reachable from `to` that touch `path` (the whole repository when
+/// `None`), newest first, stopping at `from` and/or at commits before `since`
+pub fn log_commits(
+    repo_path: &Path,
+    to: &str,
+    from: Option<&str>,
+    since: Option<&str>,
+    path: Option<&str>,
+) -> Result<Vec<CommitSummary>> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(repo_path)
+        .args(["log", "--format=%H%x1f%cI%x1f%an%x1f%s"]);
+    if let Some(since) = since {
+        cmd.arg(format!("--since={}", since));
+    }
+    match from {
+        Some(from) => cmd.arg(format!("{}..{}", from, to)),
+        None => cmd.arg(to),
+    };
+    cmd.arg("--");
+    if let Some(path) = path {
+        cmd.arg(path);
+    }
+
+    let output = run_git(&mut cmd).map_err(|e| ApsError::GitError {
+        message: format!("Failed to execute git log: {}", e),
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(ApsError::GitError {
+            message: format!("Failed to read history: {}", stderr.trim()),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\x1f');
+            Some(CommitSummary {
+                sha: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                subject: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect())
+}
+
 /// Clone a git repository at a specific commit SHA.
 /// This is used when respecting locked versions from the lockfile.
+///
+/// As with [`clone_and_resolve`], only `path` is checked out in
+/// low-bandwidth mode.
 pub fn clone_at_commit(
     url: &str,
     commit_sha: &str,
     resolved_ref: &str,
+    path: Option<&str>,
 ) -> Result<ResolvedGitSource> {
+    require_git()?;
+    let sparse = sparse_path(path);
+    if sparse.is_none() {
+        if let Some(resolved) = fetch_github_tarball(url, commit_sha, resolved_ref) {
+            return Ok(ResolvedGitSource {
+                commit_sha: commit_sha.to_string(),
+                ..resolved
+            });
+        }
+    }
     info!(
         "Cloning git repository at locked commit: {} @ {}",
         url,
-        &commit_sha[..8.min(commit_sha.len())]
+        short_id(commit_sha)
     );
 
     // Create temp directory for the clone
@@ -226,14 +879,15 @@ pub fn clone_at_commit(
     // Clone with no checkout first, then fetch the specific commit
     // This approach works even if the commit is not at a branch head
     let mut cmd = Command::new("git");
-    cmd.arg("clone")
-        .arg("--no-checkout")
-        .arg(url)
-        .arg(&repo_path);
+    cmd.arg("clone").arg("--no-checkout");
+    if sparse.is_some() {
+        cmd.arg("--filter=blob:none").arg("--sparse");
+    }
+    cmd.arg(url).arg(&repo_path);
 
     debug!("Running: git clone --no-checkout {}", url);
 
-    let output = cmd.output().map_err(|e| ApsError::GitError {
+    let output = run_git(&mut cmd).map_err(|e| ApsError::GitError {
         message: format!("Failed to execute git command: {}", e),
     })?;
 
@@ -243,24 +897,28 @@ pub fn clone_at_commit(
             message: format!("Failed to clone repository: {}", stderr.trim()),
         });
     }
+    if let Some(sparse) = sparse {
+        restrict_checkout(&repo_path, sparse)?;
+    }
 
     // Checkout the specific commit
-    let checkout_output = Command::new("git")
-        .arg("-C")
-        .arg(&repo_path)
-        .arg("checkout")
-        .arg(commit_sha)
-        .output()
-        .map_err(|e| ApsError::GitError {
-            message: format!("Failed to execute git checkout: {}", e),
-        })?;
+    let checkout_output = run_git(
+        Command::new("git")
+            .arg("-C")
+            .arg(&repo_path)
+            .arg("checkout")
+            .arg(commit_sha),
+    )
+    .map_err(|e| ApsError::GitError {
+        message: format!("Failed to execute git checkout: {}", e),
+    })?;
 
     if !checkout_output.status.success() {
         let stderr = String::from_utf8_lossy(&checkout_output.stderr);
         return Err(ApsError::GitError {
             message: format!(
                 "Failed to checkout commit {}: {}",
-                &commit_sha[..8.min(commit_sha.len())],
+                short_id(commit_sha),
                 stderr.trim()
             ),
         });
@@ -269,40 +927,55 @@ pub fn clone_at_commit(
     info!(
         "Cloned {} at locked commit {} (ref was '{}')",
         url,
-        &commit_sha[..8.min(commit_sha.len())],
+        short_id(commit_sha),
         resolved_ref
     );
 
+    let commit_date = get_head_commit_date(&repo_path);
+
     Ok(ResolvedGitSource {
         _temp_dir: temp_dir,
         repo_path,
         resolved_ref: resolved_ref.to_string(),
         commit_sha: commit_sha.to_string(),
+        commit_date,
+        release: None,
     })
 }
 
 /// Get the commit SHA for a ref from a remote repository without cloning.
 /// Uses `git ls-remote` which is much faster than a full clone.
 pub fn get_remote_commit_sha(url: &str, git_ref: &str) -> Result<Option<String>> {
-    // For "auto" ref, try main then master
-    let refs_to_try = if git_ref == "auto" {
-        vec!["main", "master"]
-    } else {
-        vec![git_ref]
-    };
+    require_git()?;
+
+    if let Some(constraint) = release_constraint(git_ref) {
+        return resolve_release(url, constraint).map(|release| Some(release.commit));
+    }
+
+    // For "auto", the remote HEAD answers in one round trip
+    if git_ref == "auto" {
+        if let Some((branch, sha)) = remote_head(url) {
+            debug!(
+                "Found remote commit {} for default branch '{}'",
+                sha, branch
+            );
+            return Ok(Some(sha));
+        }
+    }
 
-    for ref_name in refs_to_try {
+    for ref_name in candidate_refs(url, git_ref) {
         debug!("Checking remote ref '{}' for {}", ref_name, url);
 
-        let output = Command::new("git")
-            .arg("ls-remote")
-            .arg("--refs")
-            .arg(url)
-            .arg(format!("refs/heads/{}", ref_name))
-            .output()
-            .map_err(|e| ApsError::GitError {
-                message: format!("Failed to execute git ls-remote: {}", e),
-            })?;
+        let output = run_git(
+            Command::new("git")
+                .arg("ls-remote")
+                .arg("--refs")
+                .arg(url)
+                .arg(format!("refs/heads/{}", ref_name)),
+        )
+        .map_err(|e| ApsError::GitError {
+            message: format!("Failed to execute git ls-remote: {}", e),
+        })?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -325,3 +998,35 @@ pub fn get_remote_commit_sha(url: &str, git_ref: &str) -> Result<Option<String>>
     // No matching ref found
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_symref_head() {
+        let output = "ref: refs/heads/develop\tHEAD\n0123abcd\tHEAD\n";
+        assert_eq!(
+            parse_symref_head(output),
+            Some(("develop".to_string(), "0123abcd".to_string()))
+        );
+        // Detached HEAD or an empty repository has no default branch
+        assert_eq!(parse_symref_head("0123abcd\tHEAD\n"), None);
+        assert_eq!(parse_symref_head(""), None);
+    }
+
+    #[test]
+    fn test_any_case_prefix() {
+        assert_eq!(any_case_prefix("Li-2"), "[Ll][Ii]-2*");
+    }
+
+    #[test]
+    fn test_parse_ls_remote_refs() {
+        let output =
+            "0123\trefs/heads/main\n4567\trefs/heads/feature/foo-bar\n89ab\trefs/tags/v1.0\n";
+        assert_eq!(
+            parse_ls_remote_refs(output),
+            vec!["main", "feature/foo-bar", "v1.0"]
+        );
+    }
+}
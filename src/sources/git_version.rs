@@ -0,0 +1,169 @@
+//! Detection of the installed git version and the features it supports.
+//!
+//! The version is read once per run. Commands that cannot work without git
+//! call [`require_git`]; features newer than the minimum are checked with
+//! [`supports`] so callers can fall back instead of failing on an unknown flag.
+
+use super::git::run_git;
+use crate::error::{ApsError, Result};
+use std::fmt;
+use std::io::ErrorKind;
+use std::process::Command;
+use std::sync::OnceLock;
+use tracing::debug;
+
+/// Oldest git aps runs against: `ls-remote --symref` and `--refs` appeared in 2.8
+pub const MIN_GIT_VERSION: GitVersion = GitVersion::new(2, 8, 0);
+
+/// A git release number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GitVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl GitVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse `git --version` output, e.g. `git version 2.39.3 (Apple Git-146)`
+    /// or `git version 2.45.1.windows.1`
+    pub fn parse(output: &str) -> Option<Self> {
+        let version = output.trim().strip_prefix("git version ")?;
+        let mut numbers = version
+            .split(|c: char| !c.is_ascii_digit())
+            .take(3)
+            .map(|n| n.parse::<u32>().ok());
+        let major = numbers.next()??;
+        let minor = numbers.next().flatten().unwrap_or(0);
+        let patch = numbers.next().flatten().unwrap_or(0);
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for GitVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Git features newer than [`MIN_GIT_VERSION`] that aps uses when available
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitFeature {
+    /// `clone --filter=blob:none`, used for the history cache behind `aps log`
+    PartialClone,
+    /// `clone --filter=tree:0`, used to count commits behind upstream
+    TreelessClone,
+    /// `sparse-checkout set --no-cone`, used by low-bandwidth syncs
+    SparseCheckout,
+}
+
+impl GitFeature {
+    pub const ALL: [GitFeature; 3] = [
+        GitFeature::PartialClone,
+        GitFeature::TreelessClone,
+        GitFeature::SparseCheckout,
+    ];
+
+    /// Human-readable name for messages
+    pub fn name(&self) -> &'static str {
+        match self {
+            GitFeature::PartialClone => "partial clone",
+            GitFeature::TreelessClone => "tree-less clone",
+            GitFeature::SparseCheckout => "sparse checkout",
+        }
+    }
+
+    /// First git release with the feature
+    pub fn min_version(&self) -> GitVersion {
+        match self {
+            GitFeature::PartialClone => GitVersion::new(2, 19, 0),
+            GitFeature::TreelessClone => GitVersion::new(2, 20, 0),
+            GitFeature::SparseCheckout => GitVersion::new(2, 35, 0),
+        }
+    }
+
+    /// What aps does instead on older git
+    pub fn fallback(&self) -> &'static str {
+        match self {
+            GitFeature::PartialClone => "`aps log` caches full clones",
+            GitFeature::TreelessClone => "commits-behind counts are skipped",
+            GitFeature::SparseCheckout => "low-bandwidth syncs check out whole repositories",
+        }
+    }
+}
+
+/// The installed git version, detected once per run. `None` if git is not
+/// installed or its version cannot be read.
+///
+/// The probe honors the thread's git deadline; a probe that times out is not
+/// cached, so a later command can still detect the version.
+pub fn git_version() -> Option<GitVersion> {
+    static VERSION: OnceLock<Option<GitVersion>> = OnceLock::new();
+    if let Some(version) = VERSION.get() {
+        return *version;
+    }
+    let version = match run_git(Command::new("git").arg("--version")) {
+        Err(e) if e.kind() == ErrorKind::TimedOut => return None,
+        Err(_) => None,
+        Ok(output) => GitVersion::parse(&String::from_utf8_lossy(&output.stdout)),
+    };
+    debug!("Detected git version {:?}", version);
+    *VERSION.get_or_init(|| version)
+}
+
+/// Ensure git is installed and at least [`MIN_GIT_VERSION`]
+pub fn require_git() -> Result<GitVersion> {
+    let version = git_version().ok_or(ApsError::GitNotFound)?;
+    if version < MIN_GIT_VERSION {
+        return Err(ApsError::GitVersionUnsupported {
+            feature: "aps".to_string(),
+            required: MIN_GIT_VERSION.to_string(),
+            found: version.to_string(),
+        });
+    }
+    Ok(version)
+}
+
+/// Whether the installed git supports `feature`
+pub fn supports(feature: GitFeature) -> bool {
+    let supported = git_version().is_some_and(|v| v >= feature.min_version());
+    if !supported {
+        debug!(
+            "git {} unavailable (needs git >= {})",
+            feature.name(),
+            feature.min_version()
+        );
+    }
+    supported
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_version() {
+        assert_eq!(
+            GitVersion::parse("git version 2.39.3 (Apple Git-146)\n"),
+            Some(GitVersion::new(2, 39, 3))
+        );
+        assert_eq!(
+            GitVersion::parse("git version 2.45.1.windows.1"),
+            Some(GitVersion::new(2, 45, 1))
+        );
+        assert_eq!(
+            GitVersion::parse("git version 2.7"),
+            Some(GitVersion::new(2, 7, 0))
+        );
+        assert_eq!(GitVersion::parse("hub version 2.14.2"), None);
+        assert!(GitVersion::new(2, 7, 4) < MIN_GIT_VERSION);
+        assert!(GitVersion::new(2, 19, 0) >= GitFeature::PartialClone.min_version());
+    }
+}
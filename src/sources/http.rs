@@ -0,0 +1,282 @@
+//! HTTP(S) sources: raw files fetched from a URL, such as a vendor's hosted
+//! guidance document.
+//!
+//! Fetched bodies are cached under `.aps-cache/http/` together with the
+//! response's ETag and Last-Modified date, so repeated syncs send a
+//! conditional request and only download content that changed upstream.
+//! Extra request headers, such as `Authorization`, are expanded from the
+//! environment and handed to curl in a private file rather than on its
+//! command line. Fetching shells out to `curl`.
+
+use super::{ResolvedSource, SourceAdapter};
+use crate::checksum::compute_string_checksum;
+use crate::error::{ApsError, Result};
+use crate::staleness::CACHE_DIR;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tracing::{debug, info};
+
+/// HTTP cache directory within the cache directory
+const HTTP_DIR: &str = "http";
+
+/// Cached response body within a URL's cache slot
+const BODY_FILE: &str = "body";
+
+/// ETag of the cached body within a URL's cache slot
+const ETAG_FILE: &str = "etag";
+
+/// Last-Modified date of the cached body within a URL's cache slot
+const LAST_MODIFIED_FILE: &str = "last-modified";
+
+/// Response headers of the fetch in progress within a URL's cache slot
+const HEADERS_FILE: &str = "headers.part";
+
+/// Upper bound on a single fetch, in seconds
+const FETCH_TIMEOUT_SECS: &str = "60";
+
+/// Source adapter for `type: http`
+#[derive(Debug, Clone)]
+pub struct HttpSource {
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+}
+
+impl HttpSource {
+    /// Create a new HttpSource
+    pub fn new(url: String, headers: BTreeMap<String, String>) -> Self {
+        Self { url, headers }
+    }
+}
+
+impl SourceAdapter for HttpSource {
+    fn source_type(&self) -> &'static str {
+        "http"
+    }
+
+    fn display_name(&self) -> String {
+        self.url.clone()
+    }
+
+    fn path(&self) -> &str {
+        "."
+    }
+
+    fn resolve(&self, manifest_dir: &Path) -> Result<ResolvedSource> {
+        let body = fetch_url(&self.url, &self.headers, &http_cache_dir(manifest_dir))?;
+        Ok(ResolvedSource::http(body, self.url.clone()))
+    }
+
+    fn supports_symlink(&self) -> bool {
+        false
+    }
+}
+
+/// The HTTP cache directory for a project
+pub fn http_cache_dir(base_dir: &Path) -> PathBuf {
+    base_dir.join(CACHE_DIR).join(HTTP_DIR)
+}
+
+/// Cache slot for a URL, named by a hash of the URL
+fn cache_slot(cache_dir: &Path, url: &str) -> PathBuf {
+    let checksum = compute_string_checksum(url);
+    let hash = checksum.trim_start_matches("sha256:");
+    cache_dir.join(&hash[..16])
+}
+
+/// The cached body of a URL, if it was fetched before
+pub fn cached_url(url: &str, cache_dir: &Path) -> Option<PathBuf> {
+    let body = cache_slot(cache_dir, url).join(BODY_FILE);
+    body.is_file().then_some(body)
+}
+
+/// Expand environment variables in request header values, rejecting names
+/// and values that would break the request
+fn expand_headers(url: &str, headers: &BTreeMap<String, String>) -> Result<Vec<String>> {
+    let mut lines = Vec::with_capacity(headers.len());
+    for (name, value) in headers {
+        let value = shellexpand::env(value).map_err(|e| ApsError::HttpHeaderEnv {
+            url: url.to_string(),
+            header: name.clone(),
+            var: e.var_name,
+        })?;
+        let malformed = name.is_empty()
+            || name.contains([':', ' ', '\r', '\n'])
+            || value.contains(['\r', '\n']);
+        if malformed {
+            return Err(ApsError::HttpError {
+                url: url.to_string(),
+                message: format!("malformed request header '{}'", name),
+            });
+        }
+        lines.push(format!("{}: {}", name, value));
+    }
+    Ok(lines)
+}
+
+/// The Last-Modified date of the final response in a curl header dump
+fn last_modified(headers: &str) -> Option<String> {
+    let last_response = headers.rsplit("HTTP/").next().unwrap_or(headers);
+    last_response.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        name.trim()
+            .eq_ignore_ascii_case("last-modified")
+            .then(|| value.trim().to_string())
+    })
+}
+
+/// Fetch a URL into the cache and return the path of its body.
+///
+/// A cached body is revalidated with its ETag, or its Last-Modified date when
+/// the server sent no ETag; a `304 Not Modified` response keeps the cached
+/// copy without downloading it again. `headers` are sent with the request.
+pub fn fetch_url(
+    url: &str,
+    headers: &BTreeMap<String, String>,
+    cache_dir: &Path,
+) -> Result<PathBuf> {
+    let http_error = |message: String| ApsError::HttpError {
+        url: url.to_string(),
+        message,
+    };
+
+    let parsed = url::Url::parse(url).map_err(|e| http_error(e.to_string()))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(http_error(format!(
+            "unsupported scheme '{}', expected http or https",
+            parsed.scheme()
+        )));
+    }
+
+    let header_lines = expand_headers(url, headers)?;
+
+    let slot = cache_slot(cache_dir, url);
+    std::fs::create_dir_all(&slot)
+        .map_err(|e| ApsError::io(e, format!("Failed to create {:?}", slot)))?;
+    let body = slot.join(BODY_FILE);
+    let etag = slot.join(ETAG_FILE);
+    let incoming = slot.join(format!("{}.part", BODY_FILE));
+    let incoming_etag = slot.join(format!("{}.part", ETAG_FILE));
+    let modified = slot.join(LAST_MODIFIED_FILE);
+    let response_headers = slot.join(HEADERS_FILE);
+
+    let mut cmd = Command::new("curl");
+    cmd.args(["--fail", "--silent", "--show-error", "--location"])
+        .args(["--max-time", FETCH_TIMEOUT_SECS])
+        .args(["--write-out", "%{http_code}"])
+        .arg("--output")
+        .arg(&incoming)
+        .arg("--etag-save")
+        .arg(&incoming_etag)
+        .arg("--dump-header")
+        .arg(&response_headers);
+    if body.is_file() && etag.is_file() {
+        cmd.arg("--etag-compare").arg(&etag);
+    } else if body.is_file() {
+        if let Ok(date) = std::fs::read_to_string(&modified) {
+            cmd.arg("--header")
+                .arg(format!("If-Modified-Since: {}", date.trim()));
+        }
+    }
+    // Header values may hold credentials, so they go through a file only
+    // the current user can read instead of curl's command line
+    let header_file = if header_lines.is_empty() {
+        None
+    } else {
+        let mut file = tempfile::NamedTempFile::new()
+            .map_err(|e| ApsError::io(e, "Failed to create request header file"))?;
+        writeln!(file, "{}", header_lines.join("\n"))
+            .map_err(|e| ApsError::io(e, "Failed to write request header file"))?;
+        cmd.arg("--header")
+            .arg(format!("@{}", file.path().display()));
+        Some(file)
+    };
+    let output = cmd
+        .arg("--")
+        .arg(url)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| http_error(format!("failed to run curl: {}", e)))?;
+    drop(header_file);
+    let dumped = std::fs::read_to_string(&response_headers).unwrap_or_default();
+    let _ = std::fs::remove_file(&response_headers);
+
+    let status = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&incoming);
+        let _ = std::fs::remove_file(&incoming_etag);
+        return Err(http_error(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    if status == "304" {
+        debug!("{} not modified, using cached copy", url);
+        let _ = std::fs::remove_file(&incoming);
+        let _ = std::fs::remove_file(&incoming_etag);
+        return Ok(body);
+    }
+
+    std::fs::rename(&incoming, &body)
+        .map_err(|e| ApsError::io(e, format!("Failed to cache {}", url)))?;
+    // Servers that send no ETag leave an empty file; always refetch those
+    let has_etag = std::fs::metadata(&incoming_etag)
+        .map(|m| m.len() > 0)
+        .unwrap_or(false);
+    if has_etag {
+        std::fs::rename(&incoming_etag, &etag)
+            .map_err(|e| ApsError::io(e, format!("Failed to cache {}", url)))?;
+    } else {
+        let _ = std::fs::remove_file(&incoming_etag);
+        let _ = std::fs::remove_file(&etag);
+    }
+    match last_modified(&dumped) {
+        Some(date) => std::fs::write(&modified, date)
+            .map_err(|e| ApsError::io(e, format!("Failed to cache {}", url)))?,
+        None => {
+            let _ = std::fs::remove_file(&modified);
+        }
+    }
+    info!("Fetched {} (HTTP {})", url, status);
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fetch_rejects_non_http_schemes() {
+        let dir = TempDir::new().unwrap();
+        let err = fetch_url("file:///etc/hosts", &BTreeMap::new(), dir.path()).unwrap_err();
+        assert!(err.to_string().contains("file:///etc/hosts"));
+        assert!(cached_url("file:///etc/hosts", dir.path()).is_none());
+    }
+
+    #[test]
+    fn test_last_modified_reads_the_final_response() {
+        let dump = "HTTP/1.1 301 Moved\r\nLast-Modified: Mon, 01 Jan 2024 00:00:00 GMT\r\n\r\n\
+                    HTTP/1.1 200 OK\r\nlast-modified: Tue, 02 Jan 2024 00:00:00 GMT\r\n\r\n";
+        assert_eq!(
+            last_modified(dump).as_deref(),
+            Some("Tue, 02 Jan 2024 00:00:00 GMT")
+        );
+        assert_eq!(last_modified("HTTP/1.1 200 OK\r\n\r\n"), None);
+    }
+
+    #[test]
+    fn test_expand_headers_rejects_unset_variables_and_line_breaks() {
+        let headers = BTreeMap::from([(
+            "Authorization".to_string(),
+            "Bearer ${APS_TEST_UNSET_HTTP_TOKEN}".to_string(),
+        )]);
+        assert!(matches!(
+            expand_headers("https://example.com", &headers),
+            Err(ApsError::HttpHeaderEnv { .. })
+        ));
+        let headers = BTreeMap::from([("X-Team".to_string(), "a\r\nHost: evil".to_string())]);
+        assert!(expand_headers("https://example.com", &headers).is_err());
+    }
+}
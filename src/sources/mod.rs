@@ -1,15 +1,34 @@
 //! Source adapters for syncing assets from different locations.
 //!
 //! This module defines the `SourceAdapter` trait and provides implementations
-//! for different source types (filesystem, git, etc.).
+//! for different source types (filesystem, git, http, etc.).
 
+mod aps;
+mod clone_pool;
 mod filesystem;
+mod generated;
 mod git;
+mod git_version;
+mod http;
+mod release;
+mod tarball;
 
+pub use aps::ApsSource;
+pub use clone_pool::ClonePool;
 pub use filesystem::FilesystemSource;
-pub use git::{clone_and_resolve, clone_at_commit, get_remote_commit_sha, GitSource};
-
-use crate::error::Result;
+pub use generated::{render_generated, GeneratedSource};
+pub use git::{
+    cached_clone_dir, clone_and_resolve, clone_at_commit, count_commits_behind, create_bundle,
+    file_at_commit, get_remote_commit_sha, has_commit, import_bundle, list_remote_refs,
+    log_commits, set_low_bandwidth, tree_oid, uncommitted_changes, update_history_clone,
+    update_mirror_clone, with_git_deadline, CommitSummary, GitSource, ResolvedGitSource,
+};
+pub use git_version::{git_version, require_git, supports, GitFeature, MIN_GIT_VERSION};
+pub use http::{cached_url, fetch_url, http_cache_dir, HttpSource};
+pub use release::{release_constraint, resolve_release};
+pub use tarball::set_github_tarballs;
+
+use crate::error::{ApsError, Result};
 use crate::lockfile::LockedEntry;
 use std::path::{Path, PathBuf};
 
@@ -28,6 +47,8 @@ pub struct ResolvedSource {
     pub original_root: Option<String>,
     /// Expanded root path (for filesystem sources, used for path substitution)
     pub expanded_root: Option<String>,
+    /// Root of the clone or filesystem source, bounding upstream license lookup
+    pub root: Option<PathBuf>,
     /// Holder to keep temp directories alive (for git sources)
     _temp_holder: Option<Box<dyn std::any::Any + Send + Sync>>,
 }
@@ -48,6 +69,7 @@ impl ResolvedSource {
             git_info: None,
             original_root: Some(original_root),
             expanded_root: Some(expanded_root),
+            root: None,
             _temp_holder: None,
         }
     }
@@ -66,10 +88,49 @@ impl ResolvedSource {
             git_info: Some(git_info),
             original_root: None,
             expanded_root: None,
+            root: None,
             _temp_holder: Some(Box::new(temp_holder)),
         }
     }
 
+    /// Create a new ResolvedSource for a body fetched over HTTP
+    pub fn http(source_path: PathBuf, source_display: String) -> Self {
+        Self {
+            source_path,
+            source_display,
+            use_symlink: false,
+            git_info: None,
+            original_root: None,
+            expanded_root: None,
+            root: None,
+            _temp_holder: None,
+        }
+    }
+
+    /// Keep `holder` (such as a clone the content lives in) alive with the source
+    pub fn holding(mut self, holder: impl std::any::Any + Send + Sync + 'static) -> Self {
+        self._temp_holder = Some(Box::new((self._temp_holder.take(), holder)));
+        self
+    }
+
+    /// Set the root of the clone or filesystem source
+    pub fn with_root(mut self, root: PathBuf) -> Self {
+        self.root = Some(root);
+        self
+    }
+
+    /// Error for a `source_path` that does not exist, with help pointing at
+    /// similar paths under `root`
+    pub fn path_not_found(&self) -> ApsError {
+        ApsError::source_path_not_found(
+            self.source_path.clone(),
+            self.root.as_deref(),
+            self.git_info
+                .as_ref()
+                .map(|info| info.resolved_ref.as_str()),
+        )
+    }
+
     /// Create a LockedEntry from this resolved source
     pub fn to_locked_entry(
         &self,
@@ -78,13 +139,16 @@ impl ResolvedSource {
         symlinked_items: Vec<String>,
     ) -> LockedEntry {
         if let Some(ref git_info) = self.git_info {
-            LockedEntry::new_git(
+            let mut locked = LockedEntry::new_git(
                 &self.source_display,
                 &dest_path.to_string_lossy(),
                 git_info.resolved_ref.clone(),
                 git_info.commit_sha.clone(),
                 checksum,
-            )
+            );
+            locked.commit_date = git_info.commit_date.clone();
+            locked.release = git_info.release.clone();
+            locked
         } else {
             // For filesystem sources, preserve shell variables in paths
             let (target_path, transformed_items) = if self.use_symlink {
@@ -128,6 +192,10 @@ pub struct GitInfo {
     pub resolved_ref: String,
     /// Commit SHA at the resolved ref
     pub commit_sha: String,
+    /// Committer date of the commit (RFC 3339), if known
+    pub commit_date: Option<String>,
+    /// Name of the release a `release:` ref resolved to
+    pub release: Option<String>,
 }
 
 /// Trait for source adapters that can resolve and provide content
@@ -145,6 +213,9 @@ pub trait SourceAdapter: Send + Sync {
     ///
     /// For filesystem sources, this expands variables and resolves relative paths.
     /// For git sources, this clones the repository and returns the path.
+    /// For http sources, this fetches the URL into the cache and returns the body.
+    /// For aps sources, this resolves the referenced entry in the other project.
+    /// Generated sources have no path and fail to resolve.
     fn resolve(&self, manifest_dir: &Path) -> Result<ResolvedSource>;
 
     /// Whether this source supports symlinking
@@ -372,6 +443,30 @@ mod tests {
         assert!(!source.supports_symlink());
     }
 
+    // ==================== ApsSource adapter tests ====================
+
+    #[test]
+    fn test_aps_source_display_and_symlink_support() {
+        let local = ApsSource::new(
+            "../shared".to_string(),
+            "review".to_string(),
+            "auto".to_string(),
+            None,
+        );
+        assert_eq!(local.source_type(), "aps");
+        assert_eq!(local.display_name(), "aps:../shared#review");
+        assert!(local.supports_symlink());
+
+        let remote = ApsSource::new(
+            "https://github.com/example/curated.git".to_string(),
+            "review".to_string(),
+            "main".to_string(),
+            None,
+        );
+        // Content from a cloned project is always copied
+        assert!(!remote.supports_symlink());
+    }
+
     // ==================== ResolvedSource tests ====================
 
     #[test]
@@ -451,6 +546,8 @@ mod tests {
         let git_info = GitInfo {
             resolved_ref: "main".to_string(),
             commit_sha: "abc123def456".to_string(),
+            commit_date: Some("2024-01-02T03:04:05+00:00".to_string()),
+            release: None,
         };
 
         let resolved = ResolvedSource::git(
@@ -472,5 +569,9 @@ mod tests {
         assert!(!locked.is_symlink);
         assert_eq!(locked.resolved_ref, Some("main".to_string()));
         assert_eq!(locked.commit, Some("abc123def456".to_string()));
+        assert_eq!(
+            locked.commit_date.as_deref(),
+            Some("2024-01-02T03:04:05+00:00")
+        );
     }
 }
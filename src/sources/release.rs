@@ -0,0 +1,325 @@
+//! Release refs for git sources: `ref: release:latest` or `ref: release:^1`.
+//!
+//! A release ref resolves to the newest published release whose tag matches
+//! the constraint, and the entry locks that tag's commit. GitHub repositories
+//! are asked through the releases API, which skips drafts and prereleases and
+//! supplies release names; other remotes (or an unreachable API) fall back to
+//! the version tags `git ls-remote` lists. `aps sync --upgrade` moves to a
+//! newer matching release instead of a newer branch commit.
+
+use super::git::run_git;
+use super::tarball::{curl, github_repo};
+use crate::error::{ApsError, Result};
+use std::process::Command;
+use tracing::debug;
+
+/// Prefix marking a ref as a release constraint
+pub const RELEASE_PREFIX: &str = "release:";
+
+/// The constraint of a `release:` ref, such as `latest` or `^1`
+pub fn release_constraint(git_ref: &str) -> Option<&str> {
+    git_ref.strip_prefix(RELEASE_PREFIX)
+}
+
+/// A published release and the commit its tag points at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub tag: String,
+    /// Release title, or the tag when the release has none
+    pub name: String,
+    pub commit: String,
+}
+
+/// A `major.minor.patch` version read from a tag like `v1.2.3`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Version(u64, u64, u64);
+
+/// Read a stable version from a tag. Missing parts count as zero; tags with
+/// a prerelease or build suffix are not stable versions.
+fn parse_version(tag: &str) -> Option<Version> {
+    let parts = parse_parts(tag.strip_prefix(['v', 'V']).unwrap_or(tag))?;
+    Some(Version(
+        parts[0],
+        parts.get(1).copied().unwrap_or(0),
+        parts.get(2).copied().unwrap_or(0),
+    ))
+}
+
+/// One to three dot-separated numbers
+fn parse_parts(text: &str) -> Option<Vec<u64>> {
+    let parts: Vec<u64> = text
+        .split('.')
+        .map(|p| p.parse().ok())
+        .collect::<Option<_>>()?;
+    (1..=3).contains(&parts.len()).then_some(parts)
+}
+
+/// Which versions a release ref accepts
+#[derive(Debug, PartialEq, Eq)]
+enum Constraint {
+    /// `latest` or `*`: any stable release
+    Latest,
+    /// `^1.2`: compatible versions, as Cargo reads a caret requirement
+    Caret(Vec<u64>),
+    /// `~1.2`: the same minor version (or major, for `~1`)
+    Tilde(Vec<u64>),
+    /// `1.2`: versions starting with the given parts
+    Exact(Vec<u64>),
+}
+
+impl Constraint {
+    fn parse(text: &str) -> Result<Self> {
+        let invalid = || ApsError::InvalidReleaseRef {
+            value: format!("{}{}", RELEASE_PREFIX, text),
+        };
+        let text = text.trim();
+        if text == "latest" || text == "*" {
+            return Ok(Constraint::Latest);
+        }
+        let (make, rest): (fn(Vec<u64>) -> Self, &str) = if let Some(rest) = text.strip_prefix('^')
+        {
+            (Constraint::Caret, rest)
+        } else if let Some(rest) = text.strip_prefix('~') {
+            (Constraint::Tilde, rest)
+        } else {
+            (Constraint::Exact, text)
+        };
+        let rest = rest.strip_prefix(['v', 'V']).unwrap_or(rest);
+        parse_parts(rest).map(make).ok_or_else(invalid)
+    }
+
+    fn matches(&self, version: Version) -> bool {
+        // How many leading parts must equal the constraint's
+        let (parts, fixed) = match self {
+            Constraint::Latest => return true,
+            Constraint::Exact(parts) => (parts, parts.len()),
+            Constraint::Tilde(parts) => (parts, parts.len().min(2)),
+            // Up to and including the first non-zero part
+            Constraint::Caret(parts) => (
+                parts,
+                parts
+                    .iter()
+                    .position(|p| *p != 0)
+                    .unwrap_or(parts.len() - 1)
+                    + 1,
+            ),
+        };
+        let mut wanted = [0; 3];
+        wanted[..parts.len()].copy_from_slice(parts);
+        let actual = [version.0, version.1, version.2];
+        actual[..fixed] == wanted[..fixed] && actual >= wanted
+    }
+}
+
+/// The newest of `releases` (tag, name; newest first) matching `constraint`.
+/// Unversioned tags can only be picked as the latest release, when no
+/// release has a version.
+fn newest<'a>(
+    releases: &'a [(String, String)],
+    constraint: &Constraint,
+) -> Option<&'a (String, String)> {
+    releases
+        .iter()
+        .filter_map(|release| Some((parse_version(&release.0)?, release)))
+        .filter(|(version, _)| constraint.matches(*version))
+        .max_by_key(|(version, _)| *version)
+        .map(|(_, release)| release)
+        .or_else(|| {
+            (*constraint == Constraint::Latest)
+                .then(|| releases.first())
+                .flatten()
+        })
+}
+
+/// The newest release of `url` matching `constraint` (the part after
+/// `release:`)
+pub fn resolve_release(url: &str, constraint: &str) -> Result<Release> {
+    let parsed = Constraint::parse(constraint)?;
+    let tags = remote_tags(url)?;
+    let commit_of = |tag: &str| {
+        tags.iter()
+            .find(|(name, _)| name == tag)
+            .map(|(_, commit)| commit.clone())
+    };
+
+    // Published releases, newest first, or every tag when they are unknown
+    let releases: Vec<(String, String)> = match github_releases(url) {
+        Some(releases) => releases
+            .into_iter()
+            .filter(|(tag, _)| commit_of(tag).is_some())
+            .collect(),
+        None => tags
+            .iter()
+            .map(|(tag, _)| (tag.clone(), tag.clone()))
+            .collect(),
+    };
+
+    let (tag, name) = newest(&releases, &parsed).ok_or_else(|| ApsError::NoMatchingRelease {
+        repo: url.to_string(),
+        constraint: constraint.to_string(),
+    })?;
+    let commit = commit_of(tag).unwrap_or_default();
+    debug!(
+        "Release {} of {} ('{}') is tag {} at {}",
+        constraint, url, name, tag, commit
+    );
+    Ok(Release {
+        tag: tag.clone(),
+        name: name.clone(),
+        commit,
+    })
+}
+
+/// Tags a remote advertises and the commits they point at
+fn remote_tags(url: &str) -> Result<Vec<(String, String)>> {
+    let output =
+        run_git(Command::new("git").args(["ls-remote", "--tags"]).arg(url)).map_err(|e| {
+            ApsError::GitError {
+                message: format!("Failed to execute git ls-remote: {}", e),
+            }
+        })?;
+    if !output.status.success() {
+        return Err(ApsError::GitError {
+            message: format!(
+                "git ls-remote failed for {}: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+    Ok(parse_ls_remote_tags(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Parse `git ls-remote --tags` output, taking the commit an annotated tag
+/// peels to (`refs/tags/<name>^{}`) over the tag object itself
+fn parse_ls_remote_tags(output: &str) -> Vec<(String, String)> {
+    let mut tags: Vec<(String, String)> = Vec::new();
+    for (sha, name) in output.lines().filter_map(|line| line.split_once('\t')) {
+        let Some(name) = name.strip_prefix("refs/tags/") else {
+            continue;
+        };
+        match name.strip_suffix("^{}") {
+            Some(peeled) => match tags.iter_mut().find(|(tag, _)| tag == peeled) {
+                Some(existing) => existing.1 = sha.to_string(),
+                None => tags.push((peeled.to_string(), sha.to_string())),
+            },
+            None if !tags.iter().any(|(tag, _)| tag == name) => {
+                tags.push((name.to_string(), sha.to_string()))
+            }
+            None => {}
+        }
+    }
+    tags
+}
+
+/// Tags and names of a GitHub repository's published releases, newest
+/// first, or `None` when the repository is not on GitHub or the API cannot
+/// be reached
+fn github_releases(url: &str) -> Option<Vec<(String, String)>> {
+    let (owner, repo) = github_repo(url)?;
+    let api_url = format!(
+        "https://api.github.com/repos/{}/{}/releases?per_page=100",
+        owner, repo
+    );
+    let output = curl()
+        .args(["--header", "Accept: application/vnd.github+json"])
+        .arg("--")
+        .arg(&api_url)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        debug!(
+            "GitHub releases lookup of {} failed: {}",
+            api_url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return None;
+    }
+    parse_releases_response(&output.stdout)
+}
+
+/// Read published (not draft, not prerelease) releases from a releases API
+/// response
+fn parse_releases_response(body: &[u8]) -> Option<Vec<(String, String)>> {
+    let json: serde_json::Value = serde_json::from_slice(body).ok()?;
+    Some(
+        json.as_array()?
+            .iter()
+            .filter(|r| r["draft"] != true && r["prerelease"] != true)
+            .filter_map(|r| {
+                let tag = r["tag_name"].as_str()?;
+                let name = r["name"].as_str().filter(|n| !n.trim().is_empty());
+                Some((tag.to_string(), name.unwrap_or(tag).to_string()))
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn newest_tag(constraint: &str, tags: &[&str]) -> Option<String> {
+        let releases: Vec<(String, String)> = tags
+            .iter()
+            .map(|tag| (tag.to_string(), tag.to_string()))
+            .collect();
+        newest(&releases, &Constraint::parse(constraint).unwrap()).map(|(tag, _)| tag.clone())
+    }
+
+    #[test]
+    fn test_release_constraints() {
+        let tags = [
+            "v0.3.1",
+            "v0.4.0",
+            "v1.0.0",
+            "v1.2.0",
+            "v1.10.2",
+            "v2.0.0-rc.1",
+            "v2.0.0",
+            "nightly",
+        ];
+        assert_eq!(newest_tag("latest", &tags).as_deref(), Some("v2.0.0"));
+        assert_eq!(newest_tag("^1", &tags).as_deref(), Some("v1.10.2"));
+        assert_eq!(newest_tag("^1.2", &tags).as_deref(), Some("v1.10.2"));
+        assert_eq!(newest_tag("~1.2", &tags).as_deref(), Some("v1.2.0"));
+        assert_eq!(newest_tag("^0.3", &tags).as_deref(), Some("v0.3.1"));
+        assert_eq!(newest_tag("1.0", &tags).as_deref(), Some("v1.0.0"));
+        assert_eq!(newest_tag("^3", &tags), None);
+        assert!(Constraint::parse("newest").is_err());
+        assert!(Constraint::parse("^1.x").is_err());
+    }
+
+    #[test]
+    fn test_parse_ls_remote_tags_prefers_peeled_commits() {
+        let output = "aaaa\trefs/tags/v1.0.0\n\
+                      bbbb\trefs/tags/v1.0.0^{}\n\
+                      cccc\trefs/tags/v1.1.0\n";
+        assert_eq!(
+            parse_ls_remote_tags(output),
+            vec![
+                ("v1.0.0".to_string(), "bbbb".to_string()),
+                ("v1.1.0".to_string(), "cccc".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_releases_response_skips_drafts_and_prereleases() {
+        let body = br#"[
+            {"tag_name": "v2.0.0-rc.1", "name": "RC", "draft": false, "prerelease": true},
+            {"tag_name": "v1.1.0", "name": "", "draft": false, "prerelease": false},
+            {"tag_name": "v1.2.0", "name": "Draft", "draft": true, "prerelease": false},
+            {"tag_name": "v1.0.0", "name": "First", "draft": false, "prerelease": false}
+        ]"#;
+        assert_eq!(
+            parse_releases_response(body),
+            Some(vec![
+                ("v1.1.0".to_string(), "v1.1.0".to_string()),
+                ("v1.0.0".to_string(), "First".to_string()),
+            ])
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Fast path for public GitHub repositories: download a commit's tarball from
+//! codeload instead of cloning the repository.
+//!
+//! The fast path is off unless the manifest sets `github_tarballs: true`:
+//! codeload applies `export-ignore` and `export-subst`, so a tarball's files
+//! (and checksum) can differ from a clone's, and without a `.git` directory
+//! there is no tree ID to record.
+//!
+//! The commit is looked up through the GitHub API first, and the tarball is
+//! only used when its top-level folder names that commit. Anything unexpected
+//! (a private repository, API rate limits, a missing `curl`, an archive entry
+//! outside its folder) returns
+//! `None` so the caller clones with git as usual. Set `APS_NO_TARBALL=1` to
+//! always clone.
+
+use super::git::ResolvedGitSource;
+use crate::error::{ApsError, Result};
+use crate::output::short_id;
+use crate::path_guard::safe_join;
+use flate2::read::GzDecoder;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tempfile::TempDir;
+use tracing::{debug, info};
+
+/// Environment variable that turns the tarball fast path off
+const DISABLE_ENV: &str = "APS_NO_TARBALL";
+
+/// Whether the manifest opted in to tarballs, see [`set_github_tarballs`]
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Let public GitHub repositories be downloaded as tarballs in this process
+pub fn set_github_tarballs(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Upper bound on each request, in seconds
+const FETCH_TIMEOUT_SECS: &str = "60";
+
+/// Whether the fast path is off: not opted in, or turned off through the environment
+fn disabled() -> bool {
+    !ENABLED.load(Ordering::Relaxed)
+        || std::env::var_os(DISABLE_ENV).is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Owner and name of a repository at `https://github.com/<owner>/<repo>[.git]`
+pub(super) fn github_repo(url: &str) -> Option<(String, String)> {
+    let parsed = url::Url::parse(url).ok()?;
+    if parsed.scheme() != "https"
+        || parsed.host_str() != Some("github.com")
+        || !parsed.username().is_empty()
+    {
+        return None;
+    }
+    let mut segments = parsed.path_segments()?.filter(|s| !s.is_empty());
+    let owner = segments.next()?;
+    let repo = segments.next()?.trim_end_matches(".git");
+    if repo.is_empty() || segments.next().is_some() {
+        return None;
+    }
+    Some((owner.to_string(), repo.to_string()))
+}
+
+/// Whether a ref can be placed in an API path as is
+fn is_plain_ref(reference: &str) -> bool {
+    !reference.is_empty()
+        && !reference.contains("..")
+        && reference
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '/'))
+}
+
+/// `curl` set up to fail quietly on HTTP errors
+pub(super) fn curl() -> Command {
+    let mut cmd = Command::new("curl");
+    cmd.args(["--fail", "--silent", "--show-error", "--location"])
+        .args(["--max-time", FETCH_TIMEOUT_SECS])
+        .stdin(Stdio::null());
+    cmd
+}
+
+/// Download `reference` (a branch, tag, or commit) of a public GitHub
+/// repository, recording `resolved_ref` as the ref it came from.
+pub(super) fn fetch_github_tarball(
+    url: &str,
+    reference: &str,
+    resolved_ref: &str,
+) -> Option<ResolvedGitSource> {
+    if disabled() || !is_plain_ref(reference) {
+        return None;
+    }
+    let (owner, repo) = github_repo(url)?;
+    let (commit_sha, commit_date) = lookup_commit(&owner, &repo, reference)?;
+
+    let temp_dir = TempDir::new().ok()?;
+    let archive = temp_dir.path().join("source.tar.gz");
+    let tarball_url = format!(
+        "https://codeload.github.com/{}/{}/tar.gz/{}",
+        owner, repo, commit_sha
+    );
+    debug!("Downloading {}", tarball_url);
+    let downloaded = curl()
+        .arg("--output")
+        .arg(&archive)
+        .arg("--")
+        .arg(&tarball_url)
+        .output()
+        .is_ok_and(|o| o.status.success());
+    if !downloaded {
+        debug!("Could not download {}, cloning instead", tarball_url);
+        return None;
+    }
+
+    let extract_dir = temp_dir.path().join("source");
+    std::fs::create_dir(&extract_dir).ok()?;
+    let extracted = extract_tarball(&archive, &extract_dir);
+    let _ = std::fs::remove_file(&archive);
+    if let Err(e) = extracted {
+        debug!("Could not extract {}: {}, cloning instead", tarball_url, e);
+        return None;
+    }
+    let repo_path = commit_folder(&extract_dir, &commit_sha)?;
+
+    info!(
+        "Downloaded {} at ref '{}' (commit {}) as a tarball",
+        url,
+        resolved_ref,
+        short_id(&commit_sha)
+    );
+    Some(ResolvedGitSource {
+        _temp_dir: temp_dir,
+        repo_path,
+        resolved_ref: resolved_ref.to_string(),
+        commit_sha,
+        commit_date,
+        release: None,
+    })
+}
+
+/// Unpack a `.tar.gz` into `extract_dir`, rejecting entries whose paths
+/// would land outside it
+fn extract_tarball(archive: &Path, extract_dir: &Path) -> Result<()> {
+    let file = std::fs::File::open(archive)
+        .map_err(|e| ApsError::io(e, format!("Failed to open {:?}", archive)))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let entries = archive
+        .entries()
+        .map_err(|e| ApsError::io(e, "Failed to read tarball"))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| ApsError::io(e, "Failed to read tarball entry"))?;
+        let path = entry
+            .path()
+            .map_err(|e| ApsError::io(e, "Failed to read tarball entry path"))?
+            .into_owned();
+        let dest = safe_join(extract_dir, &path)?;
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| ApsError::io(e, format!("Failed to create {:?}", parent)))?;
+        }
+        entry
+            .unpack(&dest)
+            .map_err(|e| ApsError::io(e, format!("Failed to extract {:?}", path)))?;
+    }
+    Ok(())
+}
+
+/// The commit SHA and committer date (RFC 3339) of a ref, from the GitHub API
+fn lookup_commit(owner: &str, repo: &str, reference: &str) -> Option<(String, Option<String>)> {
+    let api_url = format!(
+        "https://api.github.com/repos/{}/{}/commits/{}",
+        owner, repo, reference
+    );
+    let output = curl()
+        .args(["--header", "Accept: application/vnd.github+json"])
+        .arg("--")
+        .arg(&api_url)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        debug!(
+            "GitHub API lookup of {} failed: {}",
+            api_url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return None;
+    }
+    parse_commit_response(&output.stdout)
+}
+
+/// Read `sha` and `commit.committer.date` from a commit API response
+fn parse_commit_response(body: &[u8]) -> Option<(String, Option<String>)> {
+    let json: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let sha = json["sha"].as_str()?;
+    if sha.len() != 40 || !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let date = json["commit"]["committer"]["date"]
+        .as_str()
+        .and_then(|d| chrono::DateTime::parse_from_rfc3339(d).ok())
+        .map(|d| d.to_rfc3339());
+    Some((sha.to_string(), date))
+}
+
+/// The single top-level folder of an extracted tarball, which codeload names
+/// `<repo>-<commit sha>`. Any other layout means the download is not the
+/// commit that was asked for.
+fn commit_folder(extract_dir: &Path, commit_sha: &str) -> Option<PathBuf> {
+    let mut entries = std::fs::read_dir(extract_dir).ok()?.flatten();
+    let folder = entries.next()?.path();
+    let name = folder.file_name()?.to_string_lossy().to_string();
+    if entries.next().is_some() || !folder.is_dir() || !name.ends_with(&format!("-{}", commit_sha))
+    {
+        debug!(
+            "Unexpected tarball layout in {:?}, cloning instead",
+            extract_dir
+        );
+        return None;
+    }
+    Some(folder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_repo_urls() {
+        assert_eq!(
+            github_repo("https://github.com/anthropics/skills.git"),
+            Some(("anthropics".to_string(), "skills".to_string()))
+        );
+        assert_eq!(
+            github_repo("https://github.com/anthropics/skills"),
+            Some(("anthropics".to_string(), "skills".to_string()))
+        );
+        assert_eq!(github_repo("git@github.com:anthropics/skills.git"), None);
+        assert_eq!(github_repo("https://gitlab.com/group/skills.git"), None);
+        assert_eq!(
+            github_repo("https://token@github.com/org/private.git"),
+            None
+        );
+
+        assert!(is_plain_ref("feature/foo"));
+        assert!(!is_plain_ref("main?x=1"));
+        assert!(!is_plain_ref("../../users"));
+    }
+
+    #[test]
+    fn test_tarballs_are_opt_in() {
+        let url = "https://github.com/anthropics/skills";
+        assert!(fetch_github_tarball(url, "main", "main").is_none());
+    }
+
+    /// A gzipped tarball holding `files`, with names written as given
+    fn tarball(dir: &Path, files: &[(&str, &str)]) -> PathBuf {
+        let path = dir.join("source.tar.gz");
+        let gz = flate2::write::GzEncoder::new(
+            std::fs::File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(gz);
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append(&header, content.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_extract_tarball_stays_inside_extract_dir() {
+        let dir = TempDir::new().unwrap();
+        let out = dir.path().join("out");
+        std::fs::create_dir(&out).unwrap();
+
+        let archive = tarball(dir.path(), &[("skills-abc/skills/a/SKILL.md", "# A\n")]);
+        extract_tarball(&archive, &out).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(out.join("skills-abc/skills/a/SKILL.md")).unwrap(),
+            "# A\n"
+        );
+
+        let archive = tarball(dir.path(), &[("../escaped.md", "# Escaped\n")]);
+        assert!(extract_tarball(&archive, &out).is_err());
+        assert!(!dir.path().join("escaped.md").exists());
+    }
+
+    #[test]
+    fn test_commit_folder_must_name_the_commit() {
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join(format!("skills-{}", sha))).unwrap();
+        assert!(commit_folder(dir.path(), sha).is_some());
+        assert!(commit_folder(dir.path(), &sha.replace('0', "f")).is_none());
+
+        let body = format!(
+            r#"{{"sha":"{}","commit":{{"committer":{{"date":"2024-05-01T12:00:00Z"}}}}}}"#,
+            sha
+        );
+        assert_eq!(
+            parse_commit_response(body.as_bytes()),
+            Some((
+                sha.to_string(),
+                Some("2024-05-01T12:00:00+00:00".to_string())
+            ))
+        );
+    }
+}
@@ -0,0 +1,673 @@
+//! Stale-pin detection for locked git commits.
+//!
+//! When `max_age` is set (manifest-wide or per entry), a locked commit older
+//! than that many days is reported if upstream has newer commits. Remote heads
+//! and commit counts are cached in `.aps-cache/` so `aps status` can report
+//! stale pins without touching the network; `aps sync` refreshes the cache
+//! according to its `--refresh` policy.
+//!
+//! Separately, each locked entry records when its installed content last
+//! changed; `--stale-after` on `aps sync` and `aps list` highlights entries
+//! that have not been updated for longer than a given duration.
+
+use crate::cli::RefreshPolicy;
+use crate::error::{ApsError, Result};
+use crate::lockfile::{LockedEntry, Lockfile};
+use crate::manifest::Manifest;
+use crate::sources::{count_commits_behind, get_remote_commit_sha, with_git_deadline};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::debug;
+
+/// Cache directory, relative to the manifest directory
+pub const CACHE_DIR: &str = ".aps-cache";
+
+/// Remote ref cache filename within the cache directory
+const REMOTE_REFS_FILE: &str = "remote-refs.yaml";
+
+/// How long `--refresh auto` trusts a cached remote head
+const REFRESH_TTL_MINUTES: i64 = 60;
+
+/// Cached `git ls-remote` results, keyed by `<repo>#<ref>`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RemoteRefCache {
+    #[serde(default)]
+    pub refs: BTreeMap<String, CachedRemoteRef>,
+}
+
+/// Last known head of a remote ref
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedRemoteRef {
+    /// Commit SHA at the remote ref
+    pub commit: String,
+    /// When the remote was last queried (RFC 3339)
+    pub checked_at: String,
+    /// Commits between `behind_from` and `commit`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub behind: Option<u64>,
+    /// Locked commit that `behind` was counted from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub behind_from: Option<String>,
+}
+
+impl RemoteRefCache {
+    fn path(base_dir: &Path) -> PathBuf {
+        base_dir.join(CACHE_DIR).join(REMOTE_REFS_FILE)
+    }
+
+    /// Load the cache, treating a missing or unreadable file as empty
+    pub fn load(base_dir: &Path) -> Self {
+        std::fs::read_to_string(Self::path(base_dir))
+            .ok()
+            .and_then(|content| serde_yaml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save the cache to disk
+    pub fn save(&self, base_dir: &Path) -> Result<()> {
+        let path = Self::path(base_dir);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| ApsError::io(e, format!("Failed to create {:?}", parent)))?;
+        }
+        let content = serde_yaml::to_string(self).map_err(|e| ApsError::InvalidInput {
+            message: format!("Failed to serialize remote ref cache: {}", e),
+        })?;
+        std::fs::write(&path, content)
+            .map_err(|e| ApsError::io(e, format!("Failed to write {:?}", path)))
+    }
+
+    fn key(repo: &str, git_ref: &str) -> String {
+        format!("{}#{}", repo, git_ref)
+    }
+
+    /// Whether `policy` calls for querying the remote behind `key`
+    fn needs_query(&self, key: &str, policy: RefreshPolicy, now: DateTime<Utc>) -> bool {
+        match policy {
+            RefreshPolicy::Always => true,
+            RefreshPolicy::Never => false,
+            RefreshPolicy::Auto => self
+                .refs
+                .get(key)
+                .and_then(|c| DateTime::parse_from_rfc3339(&c.checked_at).ok())
+                .is_none_or(|checked| {
+                    now - checked.with_timezone(&Utc) >= Duration::minutes(REFRESH_TTL_MINUTES)
+                }),
+        }
+    }
+
+    /// The last known head commit of `repo#git_ref`
+    pub fn head(&self, repo: &str, git_ref: &str) -> Option<&str> {
+        self.refs
+            .get(&Self::key(repo, git_ref))
+            .map(|cached| cached.commit.as_str())
+    }
+
+    /// Query the remote head of `repo#git_ref` and record it, keeping the
+    /// cached commit count while the head has not moved
+    fn refresh(&mut self, repo: &str, git_ref: &str, now: DateTime<Utc>) {
+        if let Ok(Some(remote)) = get_remote_commit_sha(repo, git_ref) {
+            self.record(repo, git_ref, remote, now);
+        }
+    }
+
+    /// Query the heads of `remotes` that `policy` calls for, running up to
+    /// `jobs` queries at once, each cancelled once `deadline` passes
+    pub fn refresh_all(
+        &mut self,
+        remotes: &[(&str, &str)],
+        policy: RefreshPolicy,
+        jobs: usize,
+        deadline: Option<Instant>,
+        now: DateTime<Utc>,
+    ) {
+        let mut pending: Vec<(&str, &str)> = remotes
+            .iter()
+            .copied()
+            .filter(|(repo, git_ref)| self.needs_query(&Self::key(repo, git_ref), policy, now))
+            .collect();
+        pending.sort();
+        pending.dedup();
+
+        let next = AtomicUsize::new(0);
+        let heads = Mutex::new(Vec::new());
+        std::thread::scope(|scope| {
+            for _ in 0..jobs.min(pending.len()) {
+                scope.spawn(|| {
+                    with_git_deadline(deadline, || {
+                        while let Some(&(repo, git_ref)) =
+                            pending.get(next.fetch_add(1, Ordering::Relaxed))
+                        {
+                            if let Ok(Some(head)) = get_remote_commit_sha(repo, git_ref) {
+                                heads.lock().unwrap().push((repo, git_ref, head));
+                            }
+                        }
+                    })
+                });
+            }
+        });
+        for (repo, git_ref, head) in heads.into_inner().unwrap() {
+            self.record(repo, git_ref, head, now);
+        }
+    }
+
+    /// Record `remote` as the head of `repo#git_ref`
+    fn record(&mut self, repo: &str, git_ref: &str, remote: String, now: DateTime<Utc>) {
+        let key = Self::key(repo, git_ref);
+        let previous = self.refs.get(&key).filter(|c| c.commit == remote);
+        let cached = CachedRemoteRef {
+            commit: remote,
+            checked_at: now.to_rfc3339(),
+            behind: previous.and_then(|c| c.behind),
+            behind_from: previous.and_then(|c| c.behind_from.clone()),
+        };
+        self.refs.insert(key, cached);
+    }
+}
+
+/// The remote head of `repo#git_ref` under `policy`, read from and recorded
+/// in the cache under `base_dir`. `None` if it is neither reachable nor cached.
+pub fn remote_head(
+    base_dir: &Path,
+    repo: &str,
+    git_ref: &str,
+    policy: RefreshPolicy,
+) -> Option<String> {
+    let mut cache = RemoteRefCache::load(base_dir);
+    let key = RemoteRefCache::key(repo, git_ref);
+    let now = Utc::now();
+    if cache.needs_query(&key, policy, now) {
+        cache.refresh(repo, git_ref, now);
+        if let Err(e) = cache.save(base_dir) {
+            debug!("Failed to save remote ref cache: {}", e);
+        }
+    }
+    cache.refs.get(&key).map(|c| c.commit.clone())
+}
+
+/// Parse a duration such as `45m`, `12h`, `30d` or `2w`, for `--stale-after`
+pub fn parse_age(value: &str) -> std::result::Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (count, unit) = value.split_at(split);
+    let count: i64 = count
+        .parse()
+        .map_err(|_| format!("expected a duration like 30d, got '{}'", value))?;
+    match unit {
+        "m" => Ok(Duration::minutes(count)),
+        "h" => Ok(Duration::hours(count)),
+        "d" => Ok(Duration::days(count)),
+        "w" => Ok(Duration::weeks(count)),
+        _ => Err(format!(
+            "unknown unit in '{}' (use m, h, d or w, e.g. 30d)",
+            value
+        )),
+    }
+}
+
+/// How long ago a locked entry's content last changed, if recorded
+pub fn last_updated_age(locked: &LockedEntry, now: DateTime<Utc>) -> Option<Duration> {
+    let updated = DateTime::parse_from_rfc3339(locked.last_updated_at.as_deref()?).ok()?;
+    Some(now - updated.with_timezone(&Utc))
+}
+
+/// Describe an age in its largest whole unit, e.g. `3d ago`
+pub fn format_age(age: Duration) -> String {
+    if age.num_days() > 0 {
+        format!("{}d ago", age.num_days())
+    } else if age.num_hours() > 0 {
+        format!("{}h ago", age.num_hours())
+    } else if age.num_minutes() > 0 {
+        format!("{}m ago", age.num_minutes())
+    } else {
+        "just now".to_string()
+    }
+}
+
+/// A locked commit that is older than its `max_age` while upstream has moved on
+#[derive(Debug, Clone, PartialEq)]
+pub struct StalePin {
+    pub entry_id: String,
+    pub age_days: i64,
+    pub max_age: u32,
+    /// Number of newer upstream commits, if known
+    pub behind: Option<u64>,
+}
+
+impl StalePin {
+    pub fn message(&self) -> String {
+        let behind = match self.behind {
+            Some(1) => "1 newer commit".to_string(),
+            Some(n) => format!("{} newer commits", n),
+            None => "newer commits".to_string(),
+        };
+        format!(
+            "'{}' is pinned to a commit {} days old (max_age: {}) and upstream has {}",
+            self.entry_id, self.age_days, self.max_age, behind
+        )
+    }
+}
+
+/// Find stale pins among the given entry IDs.
+///
+/// Remotes are queried and the cache updated as `policy` asks; otherwise only
+/// cached remote data is used and entries without cached data are skipped.
+pub fn check_stale_pins(
+    manifest: &Manifest,
+    entry_ids: &[&str],
+    lockfile: &Lockfile,
+    cache: &mut RemoteRefCache,
+    policy: RefreshPolicy,
+    now: DateTime<Utc>,
+) -> Vec<StalePin> {
+    let mut stale = Vec::new();
+
+    for entry in manifest
+        .entries
+        .iter()
+        .filter(|e| entry_ids.contains(&e.id.as_str()))
+    {
+        let Some(max_age) = entry.max_age.or(manifest.max_age) else {
+            continue;
+        };
+        let Some((repo, git_ref)) = entry.source.as_ref().and_then(|s| s.git_info()) else {
+            continue;
+        };
+        let Some(locked) = lockfile.entries.get(&entry.id) else {
+            continue;
+        };
+        let (Some(locked_commit), Some(commit_date)) = (&locked.commit, &locked.commit_date) else {
+            continue;
+        };
+        let Ok(committed_at) = DateTime::parse_from_rfc3339(commit_date) else {
+            debug!("Unparseable commit date for {}: {}", entry.id, commit_date);
+            continue;
+        };
+
+        let age_days = (now - committed_at.with_timezone(&Utc)).num_days();
+        if age_days <= i64::from(max_age) {
+            continue;
+        }
+
+        let Some((_, behind)) = remote_ahead(cache, repo, git_ref, locked_commit, policy, now)
+        else {
+            continue;
+        };
+
+        stale.push(StalePin {
+            entry_id: entry.id.clone(),
+            age_days,
+            max_age,
+            behind,
+        });
+    }
+
+    stale
+}
+
+/// A locked git commit that upstream has moved past
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutdatedEntry {
+    pub entry_id: String,
+    pub locked_commit: String,
+    pub remote_commit: String,
+    /// Number of newer upstream commits, if known
+    pub behind: Option<u64>,
+    /// Age of the locked commit in days, if its date was recorded
+    pub age_days: Option<i64>,
+}
+
+/// Find git entries among the given IDs whose locked commit is behind the
+/// remote head, furthest behind first (by commit count, then by age).
+///
+/// Remotes are queried and the cache updated as `policy` asks; otherwise, and
+/// for remotes that cannot be reached, cached heads are used.
+pub fn check_outdated(
+    manifest: &Manifest,
+    entry_ids: &[&str],
+    lockfile: &Lockfile,
+    cache: &mut RemoteRefCache,
+    policy: RefreshPolicy,
+    now: DateTime<Utc>,
+) -> Vec<OutdatedEntry> {
+    let mut outdated = Vec::new();
+
+    for entry in manifest
+        .entries
+        .iter()
+        .filter(|e| entry_ids.contains(&e.id.as_str()))
+    {
+        let Some((repo, git_ref)) = entry.source.as_ref().and_then(|s| s.git_info()) else {
+            continue;
+        };
+        let Some(locked) = lockfile.entries.get(&entry.id) else {
+            continue;
+        };
+        let Some(locked_commit) = &locked.commit else {
+            continue;
+        };
+        let Some((remote_commit, behind)) =
+            remote_ahead(cache, repo, git_ref, locked_commit, policy, now)
+        else {
+            continue;
+        };
+        let age_days = locked
+            .commit_date
+            .as_deref()
+            .and_then(|d| DateTime::parse_from_rfc3339(d).ok())
+            .map(|d| (now - d.with_timezone(&Utc)).num_days());
+
+        outdated.push(OutdatedEntry {
+            entry_id: entry.id.clone(),
+            locked_commit: locked_commit.clone(),
+            remote_commit,
+            behind,
+            age_days,
+        });
+    }
+
+    outdated.sort_by(|a, b| {
+        b.behind
+            .cmp(&a.behind)
+            .then(b.age_days.cmp(&a.age_days))
+            .then_with(|| a.entry_id.cmp(&b.entry_id))
+    });
+    outdated
+}
+
+/// The remote head of `repo#git_ref` and how many commits it is ahead of
+/// `locked_commit`, or `None` if the head is unknown or is the locked commit.
+/// When `policy` calls for querying the remote, the commit count is also
+/// computed if the cache does not already hold it.
+pub fn remote_ahead(
+    cache: &mut RemoteRefCache,
+    repo: &str,
+    git_ref: &str,
+    locked_commit: &str,
+    policy: RefreshPolicy,
+    now: DateTime<Utc>,
+) -> Option<(String, Option<u64>)> {
+    let key = RemoteRefCache::key(repo, git_ref);
+    let query = cache.needs_query(&key, policy, now);
+    if query {
+        cache.refresh(repo, git_ref, now);
+    }
+
+    let cached = cache.refs.get_mut(&key)?;
+    if cached.commit == locked_commit {
+        return None;
+    }
+
+    let known_behind = cached
+        .behind
+        .filter(|_| cached.behind_from.as_deref() == Some(locked_commit));
+    let behind = match known_behind {
+        Some(n) => Some(n),
+        None if query => {
+            let counted = count_commits_behind(repo, git_ref, locked_commit, &cached.commit);
+            cached.behind = counted;
+            cached.behind_from = counted.map(|_| locked_commit.to_string());
+            counted
+        }
+        None => None,
+    };
+    Some((cached.commit.clone(), behind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lockfile::LockedEntry;
+    use crate::manifest::{AssetKind, ComposeLimits, Entry, Source};
+
+    fn manifest_with_git_entry(max_age: Option<u32>) -> Manifest {
+        Manifest {
+            entries: vec![Entry {
+                id: "skills".to_string(),
+                kind: AssetKind::AgentSkill,
+                source: Some(Source::Git {
+                    repo: "https://example.com/repo.git".to_string(),
+                    r#ref: "main".to_string(),
+                    shallow: true,
+                    path: None,
+                }),
+                sources: Vec::new(),
+                dest: None,
+                include: Vec::new(),
+                max_age: None,
+                preserve_permissions: true,
+                preserve_mtime: false,
+                hooks_merge: Default::default(),
+                default_dest_override: None,
+                checksum_ignore: Vec::new(),
+                description: None,
+                owner: None,
+                copy_license: false,
+                also_install_for: Vec::new(),
+                frozen: false,
+                targets: Vec::new(),
+                transform: Vec::new(),
+                suppress_warnings: Vec::new(),
+                backup: None,
+                backup_dir: None,
+                symlink_style: None,
+            }],
+            integrations: Default::default(),
+            max_age,
+            dest_roots: Default::default(),
+            backup_strategy: Default::default(),
+            targets: Vec::new(),
+            check_uncommitted: false,
+            suppress_warnings: Vec::new(),
+            ci: Default::default(),
+            backup: true,
+            backup_dir: None,
+            symlink_style: Default::default(),
+            lockfiles: Default::default(),
+            sort: false,
+            compose_limits: ComposeLimits::default(),
+            github_tarballs: false,
+        }
+    }
+
+    fn lockfile_with_commit(commit_date: &str) -> Lockfile {
+        let mut locked = LockedEntry::new_git(
+            "https://example.com/repo.git",
+            ".claude/skills",
+            "main".to_string(),
+            "aaaa".to_string(),
+            "sha256:x".to_string(),
+        );
+        locked.commit_date = Some(commit_date.to_string());
+        let mut lockfile = Lockfile::new();
+        lockfile.upsert("skills".to_string(), locked);
+        lockfile
+    }
+
+    fn cache_with_remote(commit: &str, behind: Option<u64>) -> RemoteRefCache {
+        let mut cache = RemoteRefCache::default();
+        cache.refs.insert(
+            "https://example.com/repo.git#main".to_string(),
+            CachedRemoteRef {
+                commit: commit.to_string(),
+                checked_at: "2024-03-01T00:00:00+00:00".to_string(),
+                behind,
+                behind_from: behind.map(|_| "aaaa".to_string()),
+            },
+        );
+        cache
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2024-03-01T00:00:00+00:00")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_refresh_policy_decides_when_to_query() {
+        let cache = cache_with_remote("bbbb", None);
+        let key = "https://example.com/repo.git#main";
+        let later = |minutes| now() + Duration::minutes(minutes);
+
+        assert!(cache.needs_query(key, RefreshPolicy::Always, now()));
+        assert!(!cache.needs_query(key, RefreshPolicy::Never, later(600)));
+        assert!(!cache.needs_query(key, RefreshPolicy::Auto, later(59)));
+        assert!(cache.needs_query(key, RefreshPolicy::Auto, later(60)));
+        assert!(cache.needs_query(
+            "https://example.com/other.git#main",
+            RefreshPolicy::Auto,
+            now()
+        ));
+    }
+
+    #[test]
+    fn test_old_pin_with_newer_upstream_is_stale() {
+        let manifest = manifest_with_git_entry(Some(30));
+        let lockfile = lockfile_with_commit("2024-01-01T00:00:00+00:00");
+        let mut cache = cache_with_remote("bbbb", Some(12));
+
+        let stale = check_stale_pins(
+            &manifest,
+            &["skills"],
+            &lockfile,
+            &mut cache,
+            RefreshPolicy::Never,
+            now(),
+        );
+
+        assert_eq!(
+            stale,
+            vec![StalePin {
+                entry_id: "skills".to_string(),
+                age_days: 60,
+                max_age: 30,
+                behind: Some(12),
+            }]
+        );
+        assert!(stale[0].message().contains("60 days old"));
+        assert!(stale[0].message().contains("12 newer commits"));
+    }
+
+    #[test]
+    fn test_recent_or_current_pins_are_not_stale() {
+        let manifest = manifest_with_git_entry(Some(90));
+        let lockfile = lockfile_with_commit("2024-01-01T00:00:00+00:00");
+        let mut cache = cache_with_remote("bbbb", None);
+        assert!(check_stale_pins(
+            &manifest,
+            &["skills"],
+            &lockfile,
+            &mut cache,
+            RefreshPolicy::Never,
+            now()
+        )
+        .is_empty());
+
+        let manifest = manifest_with_git_entry(Some(30));
+        let mut cache = cache_with_remote("aaaa", None);
+        assert!(check_stale_pins(
+            &manifest,
+            &["skills"],
+            &lockfile,
+            &mut cache,
+            RefreshPolicy::Never,
+            now()
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_outdated_ignores_max_age_and_current_pins() {
+        let manifest = manifest_with_git_entry(None);
+        let lockfile = lockfile_with_commit("2024-01-01T00:00:00+00:00");
+
+        let mut cache = cache_with_remote("bbbb", Some(3));
+        let outdated = check_outdated(
+            &manifest,
+            &["skills"],
+            &lockfile,
+            &mut cache,
+            RefreshPolicy::Never,
+            now(),
+        );
+        assert_eq!(
+            outdated,
+            vec![OutdatedEntry {
+                entry_id: "skills".to_string(),
+                locked_commit: "aaaa".to_string(),
+                remote_commit: "bbbb".to_string(),
+                behind: Some(3),
+                age_days: Some(60),
+            }]
+        );
+
+        let mut cache = cache_with_remote("aaaa", None);
+        assert!(check_outdated(
+            &manifest,
+            &["skills"],
+            &lockfile,
+            &mut cache,
+            RefreshPolicy::Never,
+            now()
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_no_max_age_or_cache_skips_check() {
+        let lockfile = lockfile_with_commit("2024-01-01T00:00:00+00:00");
+
+        let manifest = manifest_with_git_entry(None);
+        let mut cache = cache_with_remote("bbbb", None);
+        assert!(check_stale_pins(
+            &manifest,
+            &["skills"],
+            &lockfile,
+            &mut cache,
+            RefreshPolicy::Never,
+            now()
+        )
+        .is_empty());
+
+        let manifest = manifest_with_git_entry(Some(30));
+        let mut cache = RemoteRefCache::default();
+        assert!(check_stale_pins(
+            &manifest,
+            &["skills"],
+            &lockfile,
+            &mut cache,
+            RefreshPolicy::Never,
+            now()
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_last_updated_age_parses_and_formats() {
+        assert_eq!(parse_age("30d"), Ok(Duration::days(30)));
+        assert_eq!(parse_age("2w"), Ok(Duration::days(14)));
+        assert_eq!(parse_age("45m"), Ok(Duration::minutes(45)));
+        assert!(parse_age("30").is_err());
+        assert!(parse_age("d").is_err());
+
+        let mut locked = lockfile_with_commit("2024-01-01T00:00:00+00:00")
+            .entries
+            .remove("skills")
+            .unwrap();
+        assert_eq!(last_updated_age(&locked, now()), None);
+
+        locked.last_updated_at = Some("2024-02-20T00:00:00+00:00".to_string());
+        let age = last_updated_age(&locked, now()).unwrap();
+        assert_eq!(format_age(age), "10d ago");
+        assert_eq!(format_age(Duration::hours(5)), "5h ago");
+        assert_eq!(format_age(Duration::seconds(20)), "just now");
+    }
+}
@@ -0,0 +1,158 @@
+//! Exit summary file for wrapper tooling (`APS_SUMMARY_FILE`).
+//!
+//! When `APS_SUMMARY_FILE` names a path, every command writes a small JSON
+//! summary there as it exits: the command, whether it succeeded, how long it
+//! ran, counts it reported (such as entries created or failed by a sync), and
+//! per-entry failures. It is written whatever the console output mode, so
+//! make targets and scripts can read results without parsing stdout.
+//!
+//! Commands add to the summary through [`add_count`] and [`add_failure`]
+//! while they run; `main` writes it with [`write`].
+
+use crate::error::ApsError;
+use miette::Diagnostic;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Instant;
+use tracing::warn;
+
+/// Environment variable naming the summary file
+const SUMMARY_ENV: &str = "APS_SUMMARY_FILE";
+
+/// Current summary format version
+const SUMMARY_VERSION: u32 = 1;
+
+static RECORDED: Mutex<Recorded> = Mutex::new(Recorded {
+    counts: BTreeMap::new(),
+    failures: Vec::new(),
+});
+
+/// What commands have added while running
+struct Recorded {
+    counts: BTreeMap<String, usize>,
+    failures: Vec<SummaryFailure>,
+}
+
+/// The summary file's content
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub version: u32,
+    pub aps_version: String,
+    /// Subcommand path, e.g. `sync` or `orphans clean`
+    pub command: String,
+    pub success: bool,
+    pub exit_code: i32,
+    pub duration_ms: u128,
+    pub counts: BTreeMap<String, usize>,
+    pub failures: Vec<SummaryFailure>,
+    /// The error the command exited with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<SummaryError>,
+}
+
+/// An entry that failed while the command carried on
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryFailure {
+    pub id: String,
+    pub message: String,
+}
+
+/// The error a command exited with
+#[derive(Debug, Serialize)]
+pub struct SummaryError {
+    /// Diagnostic code, e.g. `aps::sync::targets_incomplete`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    pub message: String,
+}
+
+/// Add `count` to the count called `name`
+pub fn add_count(name: &str, count: usize) {
+    if let Ok(mut recorded) = RECORDED.lock() {
+        *recorded.counts.entry(name.to_string()).or_default() += count;
+    }
+}
+
+/// Record that entry `id` failed with `message`
+pub fn add_failure(id: &str, message: &str) {
+    if let Ok(mut recorded) = RECORDED.lock() {
+        recorded.failures.push(SummaryFailure {
+            id: id.to_string(),
+            message: message.to_string(),
+        });
+    }
+}
+
+/// Path of the summary file, if one is requested
+fn summary_path() -> Option<PathBuf> {
+    std::env::var_os(SUMMARY_ENV)
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Build the summary of `command`, started at `started`, that ended with
+/// `result`
+fn build(command: &str, started: Instant, result: &Result<(), ApsError>) -> Summary {
+    let (counts, failures) = match RECORDED.lock() {
+        Ok(mut recorded) => (
+            std::mem::take(&mut recorded.counts),
+            std::mem::take(&mut recorded.failures),
+        ),
+        Err(_) => Default::default(),
+    };
+    Summary {
+        version: SUMMARY_VERSION,
+        aps_version: env!("CARGO_PKG_VERSION").to_string(),
+        command: command.to_string(),
+        success: result.is_ok(),
+        exit_code: if result.is_ok() { 0 } else { 1 },
+        duration_ms: started.elapsed().as_millis(),
+        counts,
+        failures,
+        error: result.as_ref().err().map(|e| SummaryError {
+            code: e.code().map(|code| code.to_string()),
+            message: e.to_string(),
+        }),
+    }
+}
+
+/// Write the summary file, if `APS_SUMMARY_FILE` is set. A summary that
+/// cannot be written is logged; it never changes the command's outcome.
+pub fn write(command: &str, started: Instant, result: &Result<(), ApsError>) {
+    let Some(path) = summary_path() else {
+        return;
+    };
+    let summary = build(command, started, result);
+    let written = serde_json::to_string_pretty(&summary)
+        .map_err(std::io::Error::other)
+        .and_then(|json| std::fs::write(&path, json + "\n"));
+    if let Err(e) = written {
+        warn!("Failed to write summary file {:?}: {}", path, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_takes_recorded_counts_and_failures() {
+        add_count("created", 2);
+        add_count("created", 1);
+        add_failure("broken", "source not found");
+
+        let result = Err(ApsError::Cancelled);
+        let summary = build("sync", Instant::now(), &result);
+        assert_eq!(summary.command, "sync");
+        assert!(!summary.success);
+        assert_eq!(summary.exit_code, 1);
+        assert_eq!(summary.counts.get("created"), Some(&3));
+        assert_eq!(summary.failures.len(), 1);
+        assert!(summary.error.unwrap().code.is_some());
+
+        // Recorded values are taken, not copied
+        assert!(build("sync", Instant::now(), &Ok(())).counts.is_empty());
+    }
+}
@@ -1,24 +1,43 @@
-use console::{style, Style};
+use crate::approval::Prompt;
+use crate::color::{style, Style};
+use crate::output::{porcelain_line, say};
+use crate::staleness::StalePin;
 use std::path::Path;
 
 /// Status of a sync operation for display purposes
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SyncStatus {
-    /// Entry was synced (symlinked)
-    Synced,
-    /// Entry was copied (not symlinked)
-    Copied,
+    /// Entry was installed to a destination that did not exist before
+    Created,
+    /// Entry was reinstalled over an existing destination
+    Updated,
     /// Entry is already current (no changes needed)
     Current,
     /// Entry is current but has an upgrade available
     Upgradable,
+    /// Entry is frozen and was kept at its locked commit despite `--upgrade`
+    Frozen,
     /// Entry had warnings during sync
     Warning,
-    /// Entry failed to sync (reserved for future use)
-    #[allow(dead_code)]
+    /// Entry failed to sync (e.g., timed out)
     Error,
 }
 
+impl SyncStatus {
+    /// Stable status word for `--porcelain` output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncStatus::Created => "created",
+            SyncStatus::Updated => "updated",
+            SyncStatus::Current => "current",
+            SyncStatus::Upgradable => "upgradable",
+            SyncStatus::Frozen => "frozen",
+            SyncStatus::Warning => "warning",
+            SyncStatus::Error => "error",
+        }
+    }
+}
+
 /// Display item for sync output
 #[derive(Debug)]
 pub struct SyncDisplayItem {
@@ -26,6 +45,10 @@ pub struct SyncDisplayItem {
     pub dest_path: String,
     pub status: SyncStatus,
     pub message: Option<String>,
+    /// How long ago the entry was last updated, with `--stale-after`
+    pub last_updated: Option<String>,
+    /// Whether the entry was not updated within `--stale-after`
+    pub stale: bool,
 }
 
 impl SyncDisplayItem {
@@ -35,6 +58,8 @@ impl SyncDisplayItem {
             dest_path,
             status,
             message: None,
+            last_updated: None,
+            stale: false,
         }
     }
 
@@ -42,6 +67,12 @@ impl SyncDisplayItem {
         self.message = Some(message);
         self
     }
+
+    pub fn with_last_updated(mut self, age: String, stale: bool) -> Self {
+        self.last_updated = Some(age);
+        self.stale = stale;
+        self
+    }
 }
 
 /// Format a destination path for display, making it relative and concise
@@ -63,6 +94,18 @@ fn format_dest_path(dest_path: &str, manifest_dir: &Path) -> String {
     }
 }
 
+/// Print sync results as `--porcelain` lines
+pub fn print_sync_porcelain(items: &[SyncDisplayItem], manifest_path: &Path) {
+    let manifest_dir = manifest_path.parent().unwrap_or(Path::new("."));
+    for item in items {
+        porcelain_line(
+            &item.id,
+            item.status.as_str(),
+            &format_dest_path(&item.dest_path, manifest_dir),
+        );
+    }
+}
+
 /// Print all sync results in the new styled format
 pub fn print_sync_results(
     items: &[SyncDisplayItem],
@@ -79,27 +122,27 @@ pub fn print_sync_results(
         .unwrap_or_else(|| manifest_path.to_string_lossy().to_string());
 
     if dry_run {
-        println!(
+        say!(
             "{} {} {}",
             style("Syncing from").dim(),
             style(&manifest_display).cyan(),
             style("[dry-run]").yellow().bold()
         );
     } else {
-        println!(
+        say!(
             "{} {}",
             style("Syncing from").dim(),
             style(&manifest_display).cyan()
         );
     }
-    println!();
+    say!();
 
     // Overlap warnings (between header and entry list)
     if !overlap_warnings.is_empty() {
         for warning in overlap_warnings {
-            println!("  {} {}", style("!").yellow(), style(warning).yellow());
+            say!("  {} {}", style("!").yellow(), style(warning).yellow());
         }
-        println!();
+        say!();
     }
 
     // Styles
@@ -108,6 +151,7 @@ pub fn print_sync_results(
     let yellow = Style::new().yellow();
     let orange = Style::new().color256(208); // Orange color for upgradable
     let red = Style::new().red();
+    let cyan = Style::new().cyan();
 
     // Calculate column widths for alignment
     let max_id_len = items.iter().map(|i| i.id.len()).max().unwrap_or(0);
@@ -121,17 +165,18 @@ pub fn print_sync_results(
     for item in items {
         let (badge, badge_style, status_text, status_style): (&str, &Style, &str, &Style) =
             match item.status {
-                SyncStatus::Synced => ("✓", &green, "[synced]", &green),
-                SyncStatus::Copied => ("✓", &green, "[copied]", &green),
+                SyncStatus::Created => ("+", &green, "[created]", &green),
+                SyncStatus::Updated => ("✓", &green, "[updated]", &green),
                 SyncStatus::Current => ("·", &dim, "[current]", &dim),
                 SyncStatus::Upgradable => ("↑", &orange, "[upgrade available]", &orange),
+                SyncStatus::Frozen => ("*", &cyan, "[frozen]", &cyan),
                 SyncStatus::Warning => ("!", &yellow, "[warning]", &yellow),
                 SyncStatus::Error => ("✗", &red, "[error]", &red),
             };
 
         let dest_display = format_dest_path(&item.dest_path, manifest_dir);
 
-        // Format: "  ✓ entry-id         → ./dest/path     [synced]"
+        // Format: "  ✓ entry-id         → ./dest/path     [updated]"
         let id_style = match item.status {
             SyncStatus::Current => Style::new().dim(),
             SyncStatus::Upgradable => Style::new().color256(208),
@@ -140,13 +185,22 @@ pub fn print_sync_results(
             _ => Style::new().white(),
         };
 
-        println!(
-            "  {} {:<width_id$} {} {:<width_dest$} {}",
+        let updated_display = match item.last_updated {
+            Some(ref age) if item.stale => {
+                format!(" {}", yellow.apply_to(format!("updated {} (stale)", age)))
+            }
+            Some(ref age) => format!(" {}", dim.apply_to(format!("updated {}", age))),
+            None => String::new(),
+        };
+
+        say!(
+            "  {} {:<width_id$} {} {:<width_dest$} {}{}",
             badge_style.apply_to(badge),
             id_style.apply_to(&item.id),
             dim.apply_to("→"),
             dim.apply_to(&dest_display),
             status_style.apply_to(status_text),
+            updated_display,
             width_id = max_id_len,
             width_dest = max_dest_len,
         );
@@ -159,23 +213,24 @@ pub fn print_sync_results(
                 SyncStatus::Error => &red,
                 _ => &dim,
             };
-            println!("      {}", msg_style.apply_to(msg));
+            say!("      {}", msg_style.apply_to(msg));
         }
     }
 
-    println!();
+    say!();
 }
 
 /// Print the summary line after sync
-pub fn print_sync_summary(
-    synced_count: usize,
-    copied_count: usize,
-    current_count: usize,
-    upgradable_count: usize,
-    warning_count: usize,
-    orphan_count: usize,
-    dry_run: bool,
-) {
+pub fn print_sync_summary(items: &[SyncDisplayItem], orphan_count: usize, dry_run: bool) {
+    let count = |status: SyncStatus| items.iter().filter(|i| i.status == status).count();
+    let created_count = count(SyncStatus::Created);
+    let updated_count = count(SyncStatus::Updated);
+    let current_count = count(SyncStatus::Current);
+    let upgradable_count = count(SyncStatus::Upgradable);
+    let frozen_count = count(SyncStatus::Frozen);
+    let warning_count = count(SyncStatus::Warning);
+    let failed_count = count(SyncStatus::Error);
+
     let green = Style::new().green();
     let dim = Style::new().dim();
     let orange = Style::new().color256(208);
@@ -183,39 +238,31 @@ pub fn print_sync_summary(
 
     let mut parts = Vec::new();
 
-    // Combine synced and copied into "installed"
-    let installed_count = synced_count + copied_count;
-
-    if dry_run {
-        if installed_count > 0 {
-            parts.push(format!(
-                "{} {}",
-                style(installed_count).green(),
-                style("would sync").dim()
-            ));
-        }
-        if current_count > 0 {
-            parts.push(format!(
-                "{} {}",
-                dim.apply_to(current_count),
-                dim.apply_to("current")
-            ));
-        }
+    let (created_label, updated_label) = if dry_run {
+        ("would create", "would update")
     } else {
-        if installed_count > 0 {
-            parts.push(format!(
-                "{} {}",
-                green.apply_to(installed_count),
-                green.apply_to("synced")
-            ));
-        }
-        if current_count > 0 {
-            parts.push(format!(
-                "{} {}",
-                dim.apply_to(current_count),
-                dim.apply_to("current")
-            ));
-        }
+        ("created", "updated")
+    };
+    if created_count > 0 {
+        parts.push(format!(
+            "{} {}",
+            green.apply_to(created_count),
+            green.apply_to(created_label)
+        ));
+    }
+    if updated_count > 0 {
+        parts.push(format!(
+            "{} {}",
+            green.apply_to(updated_count),
+            green.apply_to(updated_label)
+        ));
+    }
+    if current_count > 0 {
+        parts.push(format!(
+            "{} {}",
+            dim.apply_to(current_count),
+            dim.apply_to("current")
+        ));
     }
 
     if upgradable_count > 0 {
@@ -230,6 +277,14 @@ pub fn print_sync_summary(
         ));
     }
 
+    if frozen_count > 0 {
+        parts.push(format!(
+            "{} {}",
+            style(frozen_count).cyan(),
+            style("frozen").cyan()
+        ));
+    }
+
     if warning_count > 0 {
         parts.push(format!(
             "{} {}",
@@ -242,6 +297,14 @@ pub fn print_sync_summary(
         ));
     }
 
+    if failed_count > 0 {
+        parts.push(format!(
+            "{} {}",
+            style(failed_count).red(),
+            style("failed").red()
+        ));
+    }
+
     if orphan_count > 0 {
         parts.push(format!(
             "{} {}",
@@ -251,12 +314,12 @@ pub fn print_sync_summary(
     }
 
     if !parts.is_empty() {
-        println!("{}", parts.join(", "));
+        say!("{}", parts.join(", "));
     }
 
     // Print upgrade hint if there are upgradable entries
     if upgradable_count > 0 {
-        println!(
+        say!(
             "\n{} {}",
             orange.apply_to("↑"),
             orange.apply_to("Run `aps sync --upgrade` to update to latest versions.")
@@ -264,6 +327,44 @@ pub fn print_sync_summary(
     }
 }
 
+/// Name the prompts `--yes` answered during the sync, so a scoped or
+/// blanket approval is visible after the fact
+pub fn print_auto_approved(approved: &[(Prompt, usize)]) {
+    if approved.is_empty() {
+        return;
+    }
+    let parts: Vec<String> = approved
+        .iter()
+        .map(|(prompt, count)| format!("{} ({})", prompt.as_str(), count))
+        .collect();
+    say!(
+        "{} {}",
+        style("Auto-approved by --yes:").dim(),
+        parts.join(", ")
+    );
+}
+
+/// Print warnings for locked commits older than their `max_age`
+pub fn print_stale_pins(stale: &[StalePin]) {
+    if stale.is_empty() {
+        return;
+    }
+    let orange = Style::new().color256(208);
+    say!();
+    for pin in stale {
+        say!(
+            "{} {}",
+            orange.apply_to("!"),
+            orange.apply_to(pin.message())
+        );
+    }
+    say!(
+        "{} {}",
+        orange.apply_to("↑"),
+        orange.apply_to("Run `aps sync --upgrade` to review newer upstream commits.")
+    );
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,278 @@
+//! Install-time content transformations.
+//!
+//! An entry's `transform` list is applied, in order, to a staged copy of its
+//! source, so upstream content is never modified. The staged copy is installed
+//! like any copied source, and the lockfile records its checksum alongside the
+//! checksum of the untransformed source.
+
+use crate::error::{ApsError, Result};
+use crate::path_guard::is_within;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use walkdir::WalkDir;
+
+/// A content transformation applied to copied files
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Transform {
+    /// Prepend Cursor `.mdc` front matter (`description`, `globs`,
+    /// `alwaysApply`) to Markdown files that have none
+    AddMdcFrontmatter,
+    /// Remove a leading `---` front matter block from Markdown files
+    StripFrontmatter,
+    /// Rename files from one extension to another, written `md->mdc`
+    RenameExtension(String),
+}
+
+impl Transform {
+    /// How the transform is written in the manifest, e.g. `rename_extension: md->mdc`
+    pub fn label(&self) -> String {
+        match self {
+            Transform::AddMdcFrontmatter => "add_mdc_frontmatter".to_string(),
+            Transform::StripFrontmatter => "strip_frontmatter".to_string(),
+            Transform::RenameExtension(spec) => format!("rename_extension: {}", spec),
+        }
+    }
+
+    /// Check the transform's arguments
+    pub fn validate(&self) -> Result<()> {
+        if let Transform::RenameExtension(spec) = self {
+            parse_extension_rename(spec)?;
+        }
+        Ok(())
+    }
+
+    /// Apply the transform to every file under `root`
+    fn apply(&self, root: &Path) -> Result<()> {
+        for path in files_under(root)? {
+            match self {
+                Transform::AddMdcFrontmatter if is_markdown(&path) => {
+                    rewrite(&path, add_mdc_frontmatter)?;
+                }
+                Transform::StripFrontmatter if is_markdown(&path) => {
+                    rewrite(&path, |content, _| strip_frontmatter(content).to_string())?;
+                }
+                Transform::RenameExtension(spec) => {
+                    let (from, to) = parse_extension_rename(spec)?;
+                    if path.extension().is_some_and(|ext| ext == from) {
+                        rename_file(&path, &path.with_extension(to))?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The labels of a transform list, as recorded in the lockfile
+pub fn transform_labels(transforms: &[Transform]) -> Vec<String> {
+    transforms.iter().map(Transform::label).collect()
+}
+
+/// A transformed copy of a source, removed when dropped
+pub struct StagedSource {
+    _dir: TempDir,
+    /// Path to the transformed file or directory
+    pub path: PathBuf,
+}
+
+/// Copy `source` (a file or directory) to a temporary location and apply
+/// `transforms` to the copy in order.
+///
+/// Symlinks that stay inside the source are copied as the content they point
+/// to; symlinks that lead outside it are left out.
+pub fn stage(source: &Path, transforms: &[Transform]) -> Result<StagedSource> {
+    let dir = TempDir::new().map_err(|e| ApsError::io(e, "Failed to create staging directory"))?;
+    let name = source.file_name().unwrap_or_else(|| "source".as_ref());
+    let path = dir.path().join(name);
+
+    let walker = WalkDir::new(source)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|e| !e.path_is_symlink() || is_within(source, e.path()));
+    for entry in walker {
+        let entry = entry.map_err(|e| ApsError::io(e.into(), "Failed to read source"))?;
+        let relative = entry.path().strip_prefix(source).unwrap_or(Path::new(""));
+        let target = path.join(relative);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&target)
+                .map_err(|e| ApsError::io(e, format!("Failed to create {:?}", target)))?;
+        } else {
+            std::fs::copy(entry.path(), &target)
+                .map_err(|e| ApsError::io(e, format!("Failed to stage {:?}", entry.path())))?;
+        }
+    }
+
+    for transform in transforms {
+        transform.apply(&path)?;
+    }
+    Ok(StagedSource { _dir: dir, path })
+}
+
+/// Split `md->mdc` into its source and target extensions
+fn parse_extension_rename(spec: &str) -> Result<(&str, &str)> {
+    let valid = |ext: &str| !ext.is_empty() && ext.chars().all(|c| c.is_ascii_alphanumeric());
+    match spec.split_once("->") {
+        Some((from, to)) if valid(from.trim()) && valid(to.trim()) => Ok((from.trim(), to.trim())),
+        _ => Err(ApsError::InvalidTransform {
+            transform: format!("rename_extension: {}", spec),
+            reason: "expected `<from>-><to>` with extensions like `md->mdc`".to_string(),
+        }),
+    }
+}
+
+/// Regular files under `root` (or `root` itself if it is a file), sorted
+fn files_under(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in WalkDir::new(root) {
+        let entry = entry.map_err(|e| ApsError::io(e.into(), "Failed to read staged source"))?;
+        if entry.file_type().is_file() {
+            files.push(entry.into_path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md") || ext.eq_ignore_ascii_case("mdc"))
+}
+
+/// Replace a file's content with `f(content, path)`
+fn rewrite(path: &Path, f: impl Fn(&str, &Path) -> String) -> Result<()> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ApsError::io(e, format!("Failed to read {:?}", path)))?;
+    let updated = f(&content, path);
+    if updated != content {
+        std::fs::write(path, updated)
+            .map_err(|e| ApsError::io(e, format!("Failed to write {:?}", path)))?;
+    }
+    Ok(())
+}
+
+fn rename_file(from: &Path, to: &Path) -> Result<()> {
+    if to.exists() {
+        return Err(ApsError::InvalidTransform {
+            transform: "rename_extension".to_string(),
+            reason: format!(
+                "renaming {:?} would overwrite {:?}",
+                from.file_name().unwrap_or_default(),
+                to.file_name().unwrap_or_default()
+            ),
+        });
+    }
+    std::fs::rename(from, to).map_err(|e| ApsError::io(e, format!("Failed to rename {:?}", from)))
+}
+
+/// Byte length of a leading `---` front matter block, including its closing
+/// line, or `None` if the content does not start with one
+fn frontmatter_len(content: &str) -> Option<usize> {
+    let first_line_len = content.find('\n')? + 1;
+    if content[..first_line_len].trim_end() != "---" {
+        return None;
+    }
+    let mut offset = first_line_len;
+    for line in content[first_line_len..].split_inclusive('\n') {
+        offset += line.len();
+        if line.trim_end() == "---" {
+            return Some(offset);
+        }
+    }
+    None
+}
+
+fn strip_frontmatter(content: &str) -> &str {
+    match frontmatter_len(content) {
+        Some(len) => {
+            let rest = &content[len..];
+            rest.strip_prefix("\r\n")
+                .or_else(|| rest.strip_prefix('\n'))
+                .unwrap_or(rest)
+        }
+        None => content,
+    }
+}
+
+/// Prepend `.mdc` front matter, describing the file by its first heading or
+/// its file name
+fn add_mdc_frontmatter(content: &str, path: &Path) -> String {
+    if frontmatter_len(content).is_some() {
+        return content.to_string();
+    }
+    let description = content
+        .lines()
+        .find_map(|line| {
+            let heading = line.trim_start_matches('#');
+            (heading.len() < line.len()).then(|| heading.trim())
+        })
+        .filter(|heading| !heading.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+    let description = serde_yaml::to_string(&description).unwrap_or_default();
+    format!(
+        "---\ndescription: {}\nglobs:\nalwaysApply: false\n---\n\n{}",
+        description.trim_end(),
+        content
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frontmatter_transforms() {
+        let plain = "# Use Rust 2021\n\nAlways use the 2021 edition.\n";
+        let added = add_mdc_frontmatter(plain, Path::new("edition.md"));
+        assert_eq!(
+            added,
+            "---\ndescription: Use Rust 2021\nglobs:\nalwaysApply: false\n---\n\n# Use Rust 2021\n\nAlways use the 2021 edition.\n"
+        );
+        // Existing front matter is kept, and stripping restores the original
+        assert_eq!(add_mdc_frontmatter(&added, Path::new("edition.md")), added);
+        assert_eq!(strip_frontmatter(&added), plain);
+        assert_eq!(strip_frontmatter(plain), plain);
+
+        let untitled = add_mdc_frontmatter("No heading: here\n", Path::new("style-guide.md"));
+        assert!(untitled.starts_with("---\ndescription: style-guide\n"));
+    }
+
+    #[test]
+    fn test_stage_applies_transforms_in_order() {
+        let source = TempDir::new().unwrap();
+        std::fs::write(source.path().join("rust.md"), "# Rust\n").unwrap();
+        std::fs::write(source.path().join("notes.txt"), "# Notes\n").unwrap();
+
+        let staged = stage(
+            source.path(),
+            &[
+                Transform::AddMdcFrontmatter,
+                Transform::RenameExtension("md->mdc".to_string()),
+            ],
+        )
+        .unwrap();
+
+        let rule = std::fs::read_to_string(staged.path.join("rust.mdc")).unwrap();
+        assert!(rule.starts_with("---\ndescription: Rust\n"));
+        assert!(!staged.path.join("rust.md").exists());
+        assert_eq!(
+            std::fs::read_to_string(staged.path.join("notes.txt")).unwrap(),
+            "# Notes\n"
+        );
+        // The source itself is untouched
+        assert!(source.path().join("rust.md").exists());
+
+        assert!(Transform::RenameExtension("md".to_string())
+            .validate()
+            .is_err());
+    }
+}
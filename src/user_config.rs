@@ -0,0 +1,88 @@
+//! Per-user settings.
+//!
+//! Preferences that belong to a machine rather than a project, such as how
+//! hard aps may use the network, live in `~/.config/aps/config.yaml` (or
+//! `$XDG_CONFIG_HOME/aps/config.yaml`). `APS_CONFIG` points at another file;
+//! set it to an empty string to ignore user settings. Command-line flags
+//! override these settings.
+
+use crate::error::{ApsError, Result};
+use crate::manifest::AssetKind;
+use crate::post_install::PostInstallAction;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Environment variable naming the user config file
+const CONFIG_ENV: &str = "APS_CONFIG";
+
+/// Settings read from the user config file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserConfig {
+    /// Network operations `aps sync` runs at once
+    #[serde(default)]
+    pub jobs: Option<u16>,
+
+    /// Sync with shallow, sparse clones and skip upgrade checks
+    #[serde(default)]
+    pub low_bandwidth: bool,
+
+    /// Record each synced project in the machine-wide project index
+    #[serde(default)]
+    pub index: bool,
+
+    /// Actions run after a sync changes entries of a kind, keyed by kind
+    #[serde(default, with = "serde_yaml::with::singleton_map_recursive")]
+    pub post_install: BTreeMap<String, Vec<PostInstallAction>>,
+}
+
+impl UserConfig {
+    /// Path of the user config file, if one applies
+    fn path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var(CONFIG_ENV) {
+            return (!path.is_empty()).then(|| PathBuf::from(path));
+        }
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_home.join("aps").join("config.yaml"))
+    }
+
+    /// Load the user config, treating a missing file as empty
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(ApsError::io(e, format!("Failed to read {:?}", path))),
+        };
+        if content.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        let config: Self =
+            serde_yaml::from_str(&content).map_err(|e| ApsError::UserConfigInvalid {
+                path: path.clone(),
+                message: e.to_string(),
+            })?;
+        if config.jobs == Some(0) {
+            return Err(ApsError::UserConfigInvalid {
+                path,
+                message: "`jobs` must be at least 1".to_string(),
+            });
+        }
+        if let Some(kind) = config
+            .post_install
+            .keys()
+            .find(|kind| AssetKind::from_str(kind).is_err())
+        {
+            return Err(ApsError::UserConfigInvalid {
+                path,
+                message: format!("`post_install` lists an unknown asset kind `{}`", kind),
+            });
+        }
+        Ok(config)
+    }
+}
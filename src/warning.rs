@@ -0,0 +1,159 @@
+//! Warning codes.
+//!
+//! Every warning reported by `aps sync` and `aps validate` carries a stable
+//! code such as `W001`, printed after its message. Codes listed in a
+//! manifest's `suppress_warnings`, globally or on an entry, are neither
+//! printed nor counted, and no longer fail `--strict` runs.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The kind of problem a warning reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum WarningCode {
+    /// A skill in a skills root has no `SKILL.md`
+    #[serde(rename = "W001")]
+    MissingSkillMd,
+    /// Entries write to the same destination, or ones that differ only in case
+    #[serde(rename = "W002")]
+    OverlappingDestination,
+    /// A symlink pointing outside the source was not copied
+    #[serde(rename = "W003")]
+    EscapingSymlink,
+    /// A `hooks.json` key differs between the source and the project
+    #[serde(rename = "W004")]
+    HooksMergeConflict,
+    /// A destination had local changes that were kept or backed up
+    #[serde(rename = "W005")]
+    LocalChanges,
+    /// A Cursor hooks directory is incomplete
+    #[serde(rename = "W006")]
+    InvalidHooks,
+    /// A persona directory is empty or a persona misses required fields
+    #[serde(rename = "W007")]
+    InvalidPersona,
+    /// A manifest setting has no effect
+    #[serde(rename = "W008")]
+    IneffectiveSetting,
+    /// A source could not be resolved or its path does not exist
+    #[serde(rename = "W009")]
+    SourceUnavailable,
+    /// `copy_license` is set but the source has no license file
+    #[serde(rename = "W010")]
+    MissingLicense,
+    /// An agent skill's ID differs from the name in its `SKILL.md`
+    #[serde(rename = "W011")]
+    SkillNameMismatch,
+    /// A composed file breaks the manifest's `compose_limits`
+    #[serde(rename = "W012")]
+    ComposeLimitExceeded,
+}
+
+impl WarningCode {
+    /// The code as written in output and manifests, e.g. `W001`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarningCode::MissingSkillMd => "W001",
+            WarningCode::OverlappingDestination => "W002",
+            WarningCode::EscapingSymlink => "W003",
+            WarningCode::HooksMergeConflict => "W004",
+            WarningCode::LocalChanges => "W005",
+            WarningCode::InvalidHooks => "W006",
+            WarningCode::InvalidPersona => "W007",
+            WarningCode::IneffectiveSetting => "W008",
+            WarningCode::SourceUnavailable => "W009",
+            WarningCode::MissingLicense => "W010",
+            WarningCode::SkillNameMismatch => "W011",
+            WarningCode::ComposeLimitExceeded => "W012",
+        }
+    }
+
+    fn bit(self) -> u32 {
+        1 << (self as u32)
+    }
+}
+
+impl fmt::Display for WarningCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A warning and its code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub code: WarningCode,
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(code: WarningCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]", self.message, self.code)
+    }
+}
+
+/// A set of suppressed warning codes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WarningSet(u32);
+
+impl WarningSet {
+    /// The set holding `codes`
+    pub fn of(codes: &[WarningCode]) -> Self {
+        Self::default().with(codes)
+    }
+
+    /// This set with `codes` added, for combining global and entry settings
+    pub fn with(self, codes: &[WarningCode]) -> Self {
+        Self(codes.iter().fold(self.0, |bits, code| bits | code.bit()))
+    }
+
+    pub fn contains(&self, code: WarningCode) -> bool {
+        self.0 & code.bit() != 0
+    }
+
+    /// Wrap each message in `code`, dropping them all if the code is suppressed
+    pub fn coded(&self, code: WarningCode, messages: Vec<String>) -> Vec<Warning> {
+        if self.contains(code) {
+            return Vec::new();
+        }
+        messages
+            .into_iter()
+            .map(|message| Warning::new(code, message))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_parse_and_combine() {
+        let codes: Vec<WarningCode> = serde_yaml::from_str("[W001, W005]").unwrap();
+        assert_eq!(
+            codes,
+            vec![WarningCode::MissingSkillMd, WarningCode::LocalChanges]
+        );
+        assert!(serde_yaml::from_str::<Vec<WarningCode>>("[W999]").is_err());
+
+        let set = WarningSet::of(&[WarningCode::MissingSkillMd]).with(&codes[1..]);
+        assert!(set.contains(WarningCode::LocalChanges));
+        assert!(!set.contains(WarningCode::EscapingSymlink));
+        assert!(set
+            .coded(WarningCode::MissingSkillMd, vec!["gone".to_string()])
+            .is_empty());
+
+        let warning = Warning::new(WarningCode::MissingLicense, "no license");
+        assert_eq!(warning.to_string(), "no license [W010]");
+    }
+}
@@ -3,16 +3,13 @@
 //! These tests exercise the CLI binary as a user would, ensuring
 //! argument parsing, command execution, and output work correctly.
 
+mod common;
+
 use assert_cmd::Command;
 use assert_fs::prelude::*;
+use common::*;
 use predicates::prelude::*;
 
-/// Get a Command for the aps binary
-#[allow(deprecated)]
-fn aps() -> Command {
-    Command::cargo_bin("aps").unwrap()
-}
-
 // ============================================================================
 // Help and Version Tests
 // ============================================================================
@@ -64,7 +61,8 @@ fn init_creates_gitignore_entry() {
     aps().arg("init").current_dir(&temp).assert().success();
 
     temp.child(".gitignore")
-        .assert(predicate::str::contains(".aps-backups/"));
+        .assert(predicate::str::contains(".aps-backups/"))
+        .assert(predicate::str::contains(".aps-cache/"));
 }
 
 #[test]
@@ -80,6 +78,51 @@ fn init_fails_if_manifest_exists() {
         .stderr(predicate::str::contains("already exists"));
 }
 
+#[test]
+fn init_suggests_entries_for_existing_assets() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("AGENTS.md").write_str("# Agents\n").unwrap();
+    temp.child(".cursor/rules/style.mdc")
+        .write_str("# Style\n")
+        .unwrap();
+    temp.child(".claude/skills/refactor/SKILL.md")
+        .write_str("# Refactor\n")
+        .unwrap();
+    // The skill file is matched case-insensitively
+    temp.child(".claude/skills/lint/skill.md")
+        .write_str("# Lint\n")
+        .unwrap();
+
+    ApsCmd::new(&temp)
+        .ok(&["init"])
+        .stdout(predicate::str::contains(
+            "Found existing assets: ./AGENTS.md, ./.cursor/rules, ./.claude/skills/lint, ./.claude/skills/refactor",
+        ));
+    let manifest = std::fs::read_to_string(temp.child("aps.yaml").path()).unwrap();
+    assert!(manifest.contains("\nentries: []\n# entries:\n# - id: agents-md\n"));
+    assert!(manifest.contains("#   kind: cursor_rules\n"));
+    assert!(manifest.contains("#   dest: ./.claude/skills/refactor\n"));
+    assert!(!manifest.contains("my-agents"));
+    ApsCmd::new(&temp).ok(&["validate"]);
+
+    // Uncommented, the suggestions form a valid manifest
+    let uncommented: String = manifest
+        .lines()
+        .filter(|l| {
+            *l != "entries: []"
+                && !l.starts_with("# Suggested")
+                && !l.starts_with("# `source`")
+                && !l.starts_with("# place")
+        })
+        .map(|l| format!("{}\n", l.strip_prefix("# ").unwrap_or(l)))
+        .collect();
+    temp.child("aps.yaml").write_str(&uncommented).unwrap();
+    ApsCmd::new(&temp)
+        .ok(&["list"])
+        .stdout(predicate::str::contains("cursor-rules"))
+        .stdout(predicate::str::contains("refactor"));
+}
+
 #[test]
 fn init_with_custom_path() {
     let temp = assert_fs::TempDir::new().unwrap();
@@ -246,6 +289,81 @@ fn status_works_after_sync() {
     aps().arg("status").current_dir(&temp).assert().success();
 }
 
+#[test]
+fn status_abbreviates_commits_and_checksums_unless_full_ids() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let repo = temp.child("repo");
+    create_git_repo(repo.path(), &[("AGENTS.md", "# Agents\n")]);
+    let head = git(repo.path())
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .unwrap();
+    let head = String::from_utf8(head.stdout).unwrap().trim().to_string();
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .git_entry(
+            "agents",
+            "agents_md",
+            repo.path(),
+            "AGENTS.md",
+            "./AGENTS.md",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+    aps.ok(&["sync", "--yes"]);
+
+    aps.ok(&["status"])
+        .stdout(predicate::str::contains(format!(
+            "Commit:       {}\n",
+            &head[..8]
+        )))
+        .stdout(predicate::str::is_match(r"Checksum:     sha256:[0-9a-f]{8}\n").unwrap());
+    aps.ok(&["status", "--full-ids"])
+        .stdout(predicate::str::contains(format!(
+            "Commit:       {}\n",
+            head
+        )))
+        .stdout(predicate::str::is_match(r"Checksum:     sha256:[0-9a-f]{64}\n").unwrap());
+}
+
+#[test]
+fn status_rejects_newer_lockfile_version_unless_forced() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    temp.child("aps.yaml").write_str("entries: []\n").unwrap();
+    temp.child("aps.lock.yaml")
+        .write_str("version: 99\naps_version: 99.0.0\nentries: {}\n")
+        .unwrap();
+
+    aps()
+        .arg("status")
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+
+    aps()
+        .args(["status", "--force"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("newer than supported"));
+}
+
+#[test]
+fn sync_does_not_overwrite_newer_lockfile() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    temp.child("aps.yaml").write_str("entries: []\n").unwrap();
+    let lockfile = "version: 99\naps_version: 99.0.0\nentries: {}\n";
+    temp.child("aps.lock.yaml").write_str(lockfile).unwrap();
+
+    aps().arg("sync").current_dir(&temp).assert().failure();
+
+    temp.child("aps.lock.yaml").assert(lockfile);
+}
+
 // ============================================================================
 // Catalog Command Tests
 // ============================================================================
@@ -278,6 +396,46 @@ fn catalog_generate_creates_catalog_file() {
         .assert(predicate::path::exists());
 }
 
+#[test]
+fn catalog_generate_with_health_scores_assets() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.path().join("skills");
+    create_skills_dir(&skills);
+    ManifestBuilder::new()
+        .filesystem_entry("skills", "agent_skill", &skills, "skills", ".claude/skills")
+        .write_to(temp.path());
+
+    ApsCmd::new(&temp)
+        .ok(&["catalog", "generate", "--with-health"])
+        .stdout(predicate::str::contains("health score of at least 70"));
+
+    let catalog = std::fs::read_to_string(temp.path().join("aps.catalog.yaml")).unwrap();
+    assert!(catalog.contains("health:"));
+    assert!(catalog.contains("skill_md_valid: false"));
+    assert!(catalog.contains("score: 70"));
+
+    ApsCmd::new(&temp).ok(&["catalog", "generate"]);
+    let catalog = std::fs::read_to_string(temp.path().join("aps.catalog.yaml")).unwrap();
+    assert!(!catalog.contains("health:"));
+}
+
+#[test]
+fn schema_prints_json_schema_for_each_format() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let aps = ApsCmd::new(&temp);
+
+    let output = aps.ok(&["schema", "manifest"]).get_output().stdout.clone();
+    let schema: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(schema["title"], "Manifest");
+    assert!(schema["$defs"]["AssetKind"].is_object());
+
+    aps.ok(&["schema", "lockfile", "--output", "lock.schema.json"]);
+    temp.child("lock.schema.json")
+        .assert(predicate::str::contains("\"LockedEntry\""));
+
+    aps.fails(&["schema", "plan"]);
+}
+
 // ============================================================================
 // Filesystem Source Tests
 // ============================================================================
@@ -383,6 +541,10 @@ fn sync_cursor_hooks_copies_directory_and_sets_exec() {
         .child(".cursor/scripts/nested/inner.sh")
         .write_str("echo inner\n")
         .unwrap();
+    source
+        .child(".cursor/scripts/lint")
+        .write_str("#!/bin/sh\necho lint\n")
+        .unwrap();
     source
         .child(".cursor/hooks.json")
         .write_str(
@@ -442,9 +604,148 @@ fn sync_cursor_hooks_copies_directory_and_sets_exec() {
             .permissions()
             .mode();
         assert_ne!(nested_mode & 0o100, 0);
+        let shebang_mode = std::fs::metadata(project.path().join(".cursor/scripts/lint"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_ne!(shebang_mode & 0o100, 0);
     }
 }
 
+#[test]
+fn sync_cursor_hooks_merges_into_existing_hooks_json() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let source = temp.child("source");
+    source
+        .child(".cursor/scripts/team.sh")
+        .write_str("echo team\n")
+        .unwrap();
+    source
+        .child(".cursor/hooks.json")
+        .write_str(
+            r#"{
+  "version": 2,
+  "hooks": {
+    "onStart": [{ "command": "bash .cursor/scripts/team.sh" }]
+  }
+}"#,
+        )
+        .unwrap();
+
+    let project = temp.child("project");
+    project
+        .child(".cursor/hooks.json")
+        .write_str(
+            r#"{
+  "version": 1,
+  "hooks": {
+    "onStart": [{ "command": "./local-hook" }],
+    "onStop": [{ "command": "./stop-hook" }]
+  }
+}"#,
+        )
+        .unwrap();
+
+    let manifest = format!(
+        r#"entries:
+  - id: cursor-hooks
+    kind: cursor_hooks
+    source:
+      type: filesystem
+      root: {}
+      path: .cursor/scripts
+      symlink: false
+    dest: ./.cursor/scripts
+"#,
+        source.path().display()
+    );
+    project.child("aps.yaml").write_str(&manifest).unwrap();
+
+    aps()
+        .args(["sync", "--yes"])
+        .current_dir(&project)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hooks.json 'version' differs"));
+
+    let merged: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(project.path().join(".cursor/hooks.json")).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(merged["version"], 2);
+    assert_eq!(
+        merged["hooks"]["onStart"],
+        serde_json::json!([
+            { "command": "./local-hook" },
+            { "command": "bash .cursor/scripts/team.sh" }
+        ])
+    );
+    assert_eq!(
+        merged["hooks"]["onStop"],
+        serde_json::json!([{ "command": "./stop-hook" }])
+    );
+
+    project
+        .child("aps.lock.yaml")
+        .assert(predicate::str::contains("- hooks.onStart"));
+}
+
+#[cfg(unix)]
+#[test]
+fn sync_copy_preserves_source_permissions_unless_disabled() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source.child("rules").create_dir_all().unwrap();
+    source
+        .child("rules/check")
+        .write_str("echo check\n")
+        .unwrap();
+    std::fs::set_permissions(
+        source.path().join("rules/check"),
+        std::fs::Permissions::from_mode(0o750),
+    )
+    .unwrap();
+
+    let manifest = format!(
+        r#"entries:
+  - id: preserved
+    kind: cursor_rules
+    source:
+      type: filesystem
+      root: {root}
+      path: rules
+      symlink: false
+    dest: ./preserved
+  - id: umask
+    kind: cursor_rules
+    preserve_permissions: false
+    source:
+      type: filesystem
+      root: {root}
+      path: rules
+      symlink: false
+    dest: ./umask
+"#,
+        root = source.path().display()
+    );
+    temp.child("aps.yaml").write_str(&manifest).unwrap();
+
+    aps().arg("sync").current_dir(&temp).assert().success();
+
+    let mode = |path: &str| {
+        std::fs::metadata(temp.path().join(path))
+            .unwrap()
+            .permissions()
+            .mode()
+            & 0o777
+    };
+    assert_eq!(mode("preserved/check"), 0o750);
+    assert_eq!(mode("umask/check") & 0o111, 0);
+}
+
 #[test]
 fn validate_cursor_hooks_strict_rejects_missing_config() {
     let temp = assert_fs::TempDir::new().unwrap();
@@ -534,6 +835,55 @@ fn validate_cursor_hooks_strict_accepts_valid() {
         .success();
 }
 
+#[test]
+fn validate_show_hooks_previews_commands() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let source = temp.child("source");
+    source
+        .child(".cursor/scripts/hello.sh")
+        .write_str("echo hello\n")
+        .unwrap();
+    source
+        .child(".cursor/hooks.json")
+        .write_str(
+            r#"{
+  "hooks": {
+    "onStart": [
+      { "command": "bash .cursor/scripts/hello.sh" },
+      { "command": "bash ~/bin/notify.sh" }
+    ]
+  }
+}"#,
+        )
+        .unwrap();
+
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "cursor-hooks",
+            "cursor_hooks",
+            source.path(),
+            ".cursor",
+            "./.cursor",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+
+    aps.ok(&["validate", "--show-hooks"])
+        .stdout(predicate::str::contains("onStart"))
+        .stdout(predicate::str::contains("bash .cursor/scripts/hello.sh"))
+        .stdout(predicate::str::contains("→ ./.cursor/scripts/hello.sh"))
+        .stdout(predicate::str::contains(
+            "runs ~/bin/notify.sh, outside the synced hooks directory",
+        ));
+    aps.ok(&["validate"])
+        .stdout(predicate::str::contains("onStart").not());
+    aps.fails(&["validate", "--show-hooks", "--strict"])
+        .stderr(predicate::str::contains("~/bin/notify.sh"));
+}
+
 // ============================================================================
 // Verbose Flag Tests
 // ============================================================================
@@ -627,65 +977,6 @@ fn manifest_rejects_claude_hooks_kind() {
 // Upgrade Flag Tests (Lock-Respecting Behavior)
 // ============================================================================
 
-/// Helper to run a git command in a directory
-fn git(dir: &std::path::Path) -> std::process::Command {
-    let mut cmd = std::process::Command::new("git");
-    cmd.current_dir(dir);
-    cmd
-}
-
-/// Helper to create a local git repo with an initial commit
-fn create_git_repo_with_agents_md(dir: &std::path::Path, content: &str) {
-    // Initialize git repo with main as default branch
-    git(dir)
-        .args(["init", "--initial-branch=main"])
-        .output()
-        .expect("Failed to init git repo");
-
-    // Configure git user for commits
-    git(dir)
-        .args(["config", "user.email", "test@test.com"])
-        .output()
-        .expect("Failed to configure git email");
-    git(dir)
-        .args(["config", "user.name", "Test User"])
-        .output()
-        .expect("Failed to configure git name");
-
-    // Disable GPG signing for test commits
-    git(dir)
-        .args(["config", "commit.gpgsign", "false"])
-        .output()
-        .expect("Failed to disable gpg signing");
-
-    // Create AGENTS.md
-    std::fs::write(dir.join("AGENTS.md"), content).expect("Failed to write AGENTS.md");
-
-    // Add and commit
-    git(dir)
-        .args(["add", "AGENTS.md"])
-        .output()
-        .expect("Failed to git add");
-    git(dir)
-        .args(["commit", "--no-gpg-sign", "-m", "Initial commit"])
-        .output()
-        .expect("Failed to git commit");
-}
-
-/// Helper to update AGENTS.md and create a new commit
-fn update_agents_md_in_repo(dir: &std::path::Path, new_content: &str) {
-    std::fs::write(dir.join("AGENTS.md"), new_content).expect("Failed to write AGENTS.md");
-
-    git(dir)
-        .args(["add", "AGENTS.md"])
-        .output()
-        .expect("Failed to git add");
-    git(dir)
-        .args(["commit", "--no-gpg-sign", "-m", "Update AGENTS.md"])
-        .output()
-        .expect("Failed to git commit");
-}
-
 #[test]
 fn sync_without_upgrade_respects_locked_commit() {
     let temp = assert_fs::TempDir::new().unwrap();
@@ -841,71 +1132,541 @@ fn sync_shows_upgrade_available_status() {
         );
 }
 
-// ============================================================================
-// Composite Agents MD Tests (Live Git Sources)
-// ============================================================================
-
 #[test]
-#[ignore = "requires network access; run with --ignored or set APS_TEST_NETWORK=1"]
-fn sync_composite_agents_md_from_git_sources() {
+fn sync_locks_release_refs_and_upgrades_between_releases() {
     let temp = assert_fs::TempDir::new().unwrap();
+    let source_repo = temp.child("source-repo");
+    let release = |version: &str| {
+        commit_files(
+            source_repo.path(),
+            &[("AGENTS.md", &format!("# Version {}\n", version))],
+            version,
+        );
+        git(source_repo.path())
+            .args(["tag", &format!("v{}", version)])
+            .output()
+            .unwrap();
+    };
+    create_git_repo(source_repo.path(), &[("README.md", "# Skills\n")]);
+    release("1.0.0");
+    release("1.1.0");
+    release("2.0.0");
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .raw_entry(&format!(
+            "  - id: agents\n    kind: agents_md\n    source:\n      type: git\n      repo: {}\n      ref: release:^1\n      path: AGENTS.md\n    dest: ./AGENTS.md\n",
+            source_repo.path().display()
+        ))
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+
+    aps.ok(&["sync", "--yes"]);
+    project.child("AGENTS.md").assert("# Version 1.1.0\n");
+    let lockfile = std::fs::read_to_string(project.child("aps.lock.yaml").path()).unwrap();
+    assert!(lockfile.contains("resolved_ref: v1.1.0"));
+    assert!(lockfile.contains("release: v1.1.0"));
+
+    // A newer matching release is offered, then installed with --upgrade
+    release("1.2.0");
+    aps.ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains("upgrade available"));
+    project.child("AGENTS.md").assert("# Version 1.1.0\n");
+    aps.ok(&["sync", "--upgrade", "--yes"]);
+    project.child("AGENTS.md").assert("# Version 1.2.0\n");
+    aps.ok(&["status"])
+        .stdout(predicate::str::contains("Release:      v1.2.0"));
 
-    // Create manifest with composite_agents_md using real git sources
-    let manifest = r#"entries:
-  - id: composite-test
-    kind: composite_agents_md
-    sources:
-      - type: git
-        repo: https://github.com/westonplatter/agentically.git
-        ref: main
-        path: agents-md-partials/AGENTS.docker.md
-      - type: git
-        repo: https://github.com/westonplatter/agentically.git
-        ref: main
-        path: agents-md-partials/AGENTS.pandas.md
-    dest: ./AGENTS.md
-"#;
-
-    temp.child("aps.yaml").write_str(manifest).unwrap();
-
-    // Sync should succeed
-    aps().arg("sync").current_dir(&temp).assert().success();
-
-    // Verify the composite file was created
-    let agents_md = temp.child("AGENTS.md");
-    agents_md.assert(predicate::path::exists());
-
-    // Verify content from both sources is present
-    agents_md.assert(predicate::str::contains(
-        "auto-generated by aps (https://github.com/westonplatter/aps)",
-    ));
-    // Docker content should be present (check for something unique to that file)
-    agents_md.assert(predicate::str::contains("docker").or(predicate::str::contains("Docker")));
-    // Pandas content should be present
-    agents_md.assert(predicate::str::contains("pandas").or(predicate::str::contains("Pandas")));
-
-    // Verify lockfile was created with proper structure
-    let lockfile = temp.child("aps.lock.yaml");
-    lockfile.assert(predicate::path::exists());
-
-    // Verify the lockfile has composite structure (not a string)
-    lockfile.assert(predicate::str::contains("composite:"));
-    lockfile.assert(predicate::str::contains(
-        "- https://github.com/westonplatter/agentically.git:agents-md-partials/AGENTS.docker.md",
-    ));
-    lockfile.assert(predicate::str::contains(
-        "- https://github.com/westonplatter/agentically.git:agents-md-partials/AGENTS.pandas.md",
-    ));
+    project
+        .child("aps.yaml")
+        .write_str(
+            &std::fs::read_to_string(project.child("aps.yaml").path())
+                .unwrap()
+                .replace("release:^1", "release:^3"),
+        )
+        .unwrap();
+    aps.fails(&["sync", "--upgrade", "--yes"])
+        .stderr(predicate::str::contains("No release of"));
 }
 
 #[test]
-#[ignore = "requires network access; run with --ignored or set APS_TEST_NETWORK=1"]
-fn sync_composite_agents_md_lockfile_is_valid_yaml() {
+fn sync_refresh_policy_controls_remote_checks() {
     let temp = assert_fs::TempDir::new().unwrap();
-
-    let manifest = r#"entries:
-  - id: composite-test
-    kind: composite_agents_md
+    let source_repo = temp.child("source-repo");
+    create_git_repo_with_agents_md(source_repo.path(), "# Version 1\n");
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .git_entry(
+            "agents",
+            "agents_md",
+            source_repo.path(),
+            "AGENTS.md",
+            "./AGENTS.md",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+
+    // The second sync checks the remote and caches its head
+    aps.ok(&["sync"]);
+    aps.ok(&["sync"])
+        .stdout(predicate::str::contains("[current]"));
+    update_agents_md_in_repo(source_repo.path(), "# Version 2\n");
+
+    aps.ok(&["sync", "--refresh", "never"])
+        .stdout(predicate::str::contains("[current]"));
+    aps.ok(&["sync", "--refresh", "auto"])
+        .stdout(predicate::str::contains("[current]"));
+    aps.ok(&["sync", "--refresh", "always"])
+        .stdout(predicate::str::contains("[upgrade available]"));
+}
+
+#[test]
+fn sync_records_git_tree_oids_and_rehashes_changed_trees() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source_repo = temp.child("source-repo");
+    create_git_repo_with_agents_md(source_repo.path(), "# Version 1\n");
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .git_entry(
+            "agents",
+            "agents_md",
+            source_repo.path(),
+            "AGENTS.md",
+            "./AGENTS.md",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+    let lockfile = project.child("aps.lock.yaml");
+    let tree_oid = |content: &str| {
+        content
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("tree_oid: "))
+            .map(str::to_string)
+    };
+
+    aps.ok(&["sync"]);
+    let first = std::fs::read_to_string(lockfile.path()).unwrap();
+    let first_oid = tree_oid(&first).expect("tree_oid recorded");
+
+    // An unchanged tree leaves the lockfile alone
+    aps.ok(&["sync"]);
+    assert_eq!(std::fs::read_to_string(lockfile.path()).unwrap(), first);
+
+    // A changed tree gets a new object ID and checksum
+    update_agents_md_in_repo(source_repo.path(), "# Version 2\n");
+    aps.ok(&["sync", "--upgrade", "--yes"]);
+    let second = std::fs::read_to_string(lockfile.path()).unwrap();
+    assert_ne!(tree_oid(&second).unwrap(), first_oid);
+    project
+        .child("AGENTS.md")
+        .assert(predicate::str::contains("Version 2"));
+}
+
+#[test]
+fn low_bandwidth_sync_from_user_config_skips_upgrade_checks() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source_repo = temp.child("source-repo");
+    create_skills_repo(source_repo.path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .git_entry(
+            "refactor",
+            "agent_skill",
+            source_repo.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .write_to(project.path());
+    let config = temp.child("config.yaml");
+    config.write_str("low_bandwidth: true\n").unwrap();
+    let aps = ApsCmd::new(&project);
+    let low_bandwidth = |args: &[&str]| {
+        aps.cmd(args)
+            .env("APS_CONFIG", config.path())
+            .assert()
+            .success()
+    };
+
+    // Only the entry's path is needed from the sparse clone
+    low_bandwidth(&["sync"]);
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .assert(predicate::str::contains("Refactors code"));
+
+    commit_files(
+        source_repo.path(),
+        &[("skills/refactor/SKILL.md", "# Refactor\n\nVersion 2\n")],
+        "Update refactor",
+    );
+    low_bandwidth(&["sync"]).stdout(predicate::str::contains("upgrade available").not());
+    aps.ok(&["sync", "--jobs", "2"])
+        .stdout(predicate::str::contains("[upgrade available]"));
+    low_bandwidth(&["sync", "--upgrade", "--yes"]);
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .assert(predicate::str::contains("Version 2"));
+
+    config.write_str("jobs: 0\n").unwrap();
+    aps.cmd(&["sync"])
+        .env("APS_CONFIG", config.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("aps::config::invalid"));
+}
+
+#[test]
+fn sync_runs_post_install_actions_for_changed_kinds() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    create_skills_dir(source.path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            source.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .write_to(project.path());
+    let config = temp.child("config.yaml");
+    config
+        .write_str(
+            "post_install:\n  agent_skill:\n    - touch: .claude/.reload\n    - message: Restart the agent\n    - run: echo \"$APS_KIND $APS_CHANGED_IDS\" > ran.txt\n  cursor_rules:\n    - touch: .cursor/.reload\n",
+        )
+        .unwrap();
+    let sync = || {
+        ApsCmd::new(&project)
+            .cmd(&["sync"])
+            .env("APS_CONFIG", config.path())
+            .assert()
+    };
+
+    sync()
+        .success()
+        .stdout(predicate::str::contains("Restart the agent"));
+    project
+        .child(".claude/.reload")
+        .assert(predicate::path::exists());
+    project
+        .child("ran.txt")
+        .assert(predicate::str::contains("agent_skill refactor"));
+    project
+        .child(".cursor/.reload")
+        .assert(predicate::path::missing());
+
+    // Nothing changed, so nothing runs
+    std::fs::remove_file(project.child(".claude/.reload").path()).unwrap();
+    sync()
+        .success()
+        .stdout(predicate::str::contains("Restart the agent").not());
+    project
+        .child(".claude/.reload")
+        .assert(predicate::path::missing());
+
+    config
+        .write_str("post_install:\n  cursor_rule:\n    - message: Reload\n")
+        .unwrap();
+    sync()
+        .failure()
+        .stderr(predicate::str::contains("aps::config::invalid"));
+}
+
+#[test]
+fn sync_expands_glob_paths_into_each_match() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    create_skills_dir(source.path());
+    source
+        .child("skills/NOTES.md")
+        .write_str("# Notes\n")
+        .unwrap();
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "team-skills",
+            "agent_skill",
+            source.path(),
+            "skills/*",
+            ".claude/skills",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+
+    aps.ok(&["sync", "--yes"]);
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .assert(predicate::path::exists());
+    project
+        .child(".claude/skills/test-gen/SKILL.md")
+        .assert(predicate::path::exists());
+    // Skills match directories only
+    project
+        .child(".claude/skills/NOTES.md")
+        .assert(predicate::path::missing());
+    project
+        .child("aps.lock.yaml")
+        .assert(predicate::str::contains(
+            "expanded_paths:\n    - skills/refactor\n    - skills/test-gen\n",
+        ));
+
+    // A new skill in the folder is picked up by the next sync
+    source
+        .child("skills/lint-fix/SKILL.md")
+        .write_str("# Lint Fix\n")
+        .unwrap();
+    aps.ok(&["sync", "--yes"]);
+    project
+        .child(".claude/skills/lint-fix/SKILL.md")
+        .assert(predicate::path::exists());
+    project
+        .child("aps.lock.yaml")
+        .assert(predicate::str::contains("- skills/lint-fix\n"));
+
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "team-skills",
+            "agent_skill",
+            source.path(),
+            "skills/review-*",
+            ".claude/skills",
+        )
+        .write_to(project.path());
+    aps.fails(&["sync", "--yes"])
+        .stderr(predicate::str::contains("aps::source::invalid_glob"));
+}
+
+#[test]
+fn sync_leaves_out_entries_disabled_in_review() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    create_skills_dir(source.path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            source.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .filesystem_entry(
+            "test-gen",
+            "agent_skill",
+            source.path(),
+            "skills/test-gen",
+            ".claude/skills/test-gen",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+
+    // Reviewing needs a terminal
+    aps.fails(&["sync", "--review"])
+        .stderr(predicate::str::contains("aps::cli::not_interactive"));
+
+    project
+        .child(".aps-cache/review.yaml")
+        .write_str("disabled:\n- test-gen\n")
+        .unwrap();
+    aps.ok(&["sync", "--yes"]).stdout(predicate::str::contains(
+        "Skipping disabled entries: test-gen",
+    ));
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .assert(predicate::path::exists());
+    project
+        .child(".claude/skills/test-gen")
+        .assert(predicate::path::missing());
+
+    // Naming a disabled entry installs it anyway
+    aps.ok(&["sync", "--yes", "--only", "test-gen"]);
+    project
+        .child(".claude/skills/test-gen/SKILL.md")
+        .assert(predicate::path::exists());
+}
+
+#[test]
+fn summary_file_records_command_results() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    create_skills_dir(source.path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            source.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .filesystem_entry(
+            "missing",
+            "agent_skill",
+            source.path(),
+            "skills/missing",
+            ".claude/skills/missing",
+        )
+        .write_to(project.path());
+    let summary_file = temp.child("summary.json");
+    let read_summary = || -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(summary_file.path()).unwrap()).unwrap()
+    };
+
+    // Written even when console output is suppressed
+    ApsCmd::new(&project)
+        .cmd(&["--quiet", "sync", "--yes", "--keep-going"])
+        .env("APS_SUMMARY_FILE", summary_file.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::is_empty());
+    let summary = read_summary();
+    assert_eq!(summary["command"], "sync");
+    assert_eq!(summary["success"], false);
+    assert_eq!(summary["exit_code"], 1);
+    assert_eq!(summary["counts"]["created"], 1);
+    assert_eq!(summary["counts"]["error"], 1);
+    assert_eq!(summary["failures"][0]["id"], "missing");
+    assert!(summary["error"]["code"].is_string());
+    assert!(summary["duration_ms"].is_u64());
+
+    ApsCmd::new(&project)
+        .cmd(&["orphans", "list"])
+        .env("APS_SUMMARY_FILE", summary_file.path())
+        .assert()
+        .success();
+    let summary = read_summary();
+    assert_eq!(summary["command"], "orphans list");
+    assert_eq!(summary["success"], true);
+    assert!(summary.get("error").is_none());
+}
+
+#[test]
+fn sync_and_list_show_when_entries_were_last_updated() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source_repo = temp.child("source-repo");
+    create_git_repo_with_agents_md(source_repo.path(), "# Version 1\n");
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .git_entry(
+            "agents",
+            "agents_md",
+            source_repo.path(),
+            "AGENTS.md",
+            "./AGENTS.md",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+    let lockfile = project.child("aps.lock.yaml");
+
+    aps.ok(&["sync"]);
+    let first = std::fs::read_to_string(lockfile.path()).unwrap();
+    assert!(first.contains("last_updated_at:"));
+
+    // An unchanged sync keeps the timestamp
+    aps.ok(&["sync"]);
+    assert_eq!(std::fs::read_to_string(lockfile.path()).unwrap(), first);
+    aps.ok(&["list", "--stale-after", "30d"])
+        .stdout(predicate::str::contains("updated just now"))
+        .stdout(predicate::str::contains("(stale)").not());
+
+    // Entries not updated within --stale-after are highlighted
+    let backdated = first
+        .lines()
+        .map(|line| match line.split_once("last_updated_at:") {
+            Some((indent, _)) => format!("{}last_updated_at: 2020-01-01T00:00:00+00:00", indent),
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    lockfile.write_str(&backdated).unwrap();
+    aps.ok(&["list", "--stale-after", "30d"])
+        .stdout(predicate::str::contains("(stale)"));
+    aps.ok(&["sync", "--stale-after", "30d", "--refresh", "never"])
+        .stdout(predicate::str::contains("d ago (stale)"));
+    aps.fails(&["list", "--stale-after", "30"]);
+
+    // Upgrading to new content records a new timestamp
+    update_agents_md_in_repo(source_repo.path(), "# Version 2\n");
+    aps.ok(&["sync", "--upgrade", "--yes", "--stale-after", "30d"])
+        .stdout(predicate::str::contains("updated just now"));
+    assert!(!std::fs::read_to_string(lockfile.path())
+        .unwrap()
+        .contains("2020-01-01"));
+}
+
+// ============================================================================
+// Composite Agents MD Tests (Live Git Sources)
+// ============================================================================
+
+#[test]
+#[ignore = "requires network access; run with --ignored or set APS_TEST_NETWORK=1"]
+fn sync_composite_agents_md_from_git_sources() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    // Create manifest with composite_agents_md using real git sources
+    let manifest = r#"entries:
+  - id: composite-test
+    kind: composite_agents_md
+    sources:
+      - type: git
+        repo: https://github.com/westonplatter/agentically.git
+        ref: main
+        path: agents-md-partials/AGENTS.docker.md
+      - type: git
+        repo: https://github.com/westonplatter/agentically.git
+        ref: main
+        path: agents-md-partials/AGENTS.pandas.md
+    dest: ./AGENTS.md
+"#;
+
+    temp.child("aps.yaml").write_str(manifest).unwrap();
+
+    // Sync should succeed
+    aps().arg("sync").current_dir(&temp).assert().success();
+
+    // Verify the composite file was created
+    let agents_md = temp.child("AGENTS.md");
+    agents_md.assert(predicate::path::exists());
+
+    // Verify content from both sources is present
+    agents_md.assert(predicate::str::contains(
+        "auto-generated by aps (https://github.com/westonplatter/aps)",
+    ));
+    // Docker content should be present (check for something unique to that file)
+    agents_md.assert(predicate::str::contains("docker").or(predicate::str::contains("Docker")));
+    // Pandas content should be present
+    agents_md.assert(predicate::str::contains("pandas").or(predicate::str::contains("Pandas")));
+
+    // Verify lockfile was created with proper structure
+    let lockfile = temp.child("aps.lock.yaml");
+    lockfile.assert(predicate::path::exists());
+
+    // Verify the lockfile has composite structure (not a string)
+    lockfile.assert(predicate::str::contains("composite:"));
+    lockfile.assert(predicate::str::contains(
+        "- https://github.com/westonplatter/agentically.git:agents-md-partials/AGENTS.docker.md",
+    ));
+    lockfile.assert(predicate::str::contains(
+        "- https://github.com/westonplatter/agentically.git:agents-md-partials/AGENTS.pandas.md",
+    ));
+}
+
+#[test]
+#[ignore = "requires network access; run with --ignored or set APS_TEST_NETWORK=1"]
+fn sync_composite_agents_md_lockfile_is_valid_yaml() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let manifest = r#"entries:
+  - id: composite-test
+    kind: composite_agents_md
     sources:
       - type: git
         repo: https://github.com/westonplatter/agentically.git
@@ -989,6 +1750,107 @@ fn sync_composite_agents_md_respects_locked_version() {
     assert_eq!(first_checksum, second_checksum);
 }
 
+#[test]
+fn sync_composite_git_sources_share_a_locked_clone() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let partials = temp.child("partials");
+    create_git_repo(
+        partials.path(),
+        &[
+            ("docker.md", "# Docker v1\n"),
+            ("python.md", "# Python v1\n"),
+        ],
+    );
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    let source = |path: &str| {
+        format!(
+            "      - type: git\n        repo: {}\n        ref: main\n        path: {}\n",
+            partials.path().display(),
+            path
+        )
+    };
+    ManifestBuilder::new()
+        .raw_entry(&format!(
+            "  - id: agents\n    kind: composite_agents_md\n    sources:\n{}{}    dest: ./AGENTS.md\n",
+            source("docker.md"),
+            source("python.md")
+        ))
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+
+    aps.ok(&["sync", "--yes"]);
+    let lockfile = std::fs::read_to_string(project.child("aps.lock.yaml").path()).unwrap();
+    let key = format!("{}#main", partials.path().display());
+    assert_eq!(lockfile.matches(&key).count(), 1, "{}", lockfile);
+
+    commit_files(
+        partials.path(),
+        &[
+            ("docker.md", "# Docker v2\n"),
+            ("python.md", "# Python v2\n"),
+        ],
+        "Update partials",
+    );
+    aps.ok(&["sync", "--yes"]);
+    let agents_md = project.child("AGENTS.md");
+    agents_md.assert(predicate::str::contains("Docker v1"));
+    agents_md.assert(predicate::str::contains("Python v1"));
+
+    aps.ok(&["sync", "--yes", "--upgrade"]);
+    agents_md.assert(predicate::str::contains("Docker v2"));
+    agents_md.assert(predicate::str::contains("Python v2"));
+    let upgraded = std::fs::read_to_string(project.child("aps.lock.yaml").path()).unwrap();
+    assert_ne!(lockfile, upgraded);
+}
+
+#[test]
+fn sync_composite_names_the_partials_that_changed() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let partials = temp.child("partials");
+    partials.child("intro.md").write_str("# Intro\n").unwrap();
+    partials
+        .child("python.md")
+        .write_str("# Python v1\n")
+        .unwrap();
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    let source = |path: &str| {
+        format!(
+            "      - type: filesystem\n        root: {}\n        path: {}\n",
+            partials.path().display(),
+            path
+        )
+    };
+    ManifestBuilder::new()
+        .raw_entry(&format!(
+            "  - id: agents\n    kind: composite_agents_md\n    sources:\n{}{}    dest: ./AGENTS.md\n",
+            source("intro.md"),
+            source("python.md")
+        ))
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+
+    aps.ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains("changed:").not());
+    project
+        .child("aps.lock.yaml")
+        .assert(predicate::str::contains(format!(
+            "{}/intro.md: sha256:",
+            partials.path().display()
+        )));
+
+    partials
+        .child("python.md")
+        .write_str("# Python v2\n")
+        .unwrap();
+    aps.ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains(format!(
+            "changed: {}/python.md\n",
+            partials.path().display()
+        )));
+}
+
 #[test]
 fn lockfile_migration_from_legacy_name() {
     // Test that the legacy lockfile name (aps.manifest.lock) is automatically
@@ -1153,36 +2015,136 @@ fn add_to_existing_manifest() {
 }
 
 #[test]
-fn add_duplicate_id_fails() {
+fn add_show_diff_prints_manifest_changes() {
     let temp = assert_fs::TempDir::new().unwrap();
-
-    // Create existing manifest with an entry
-    let existing_manifest = r#"entries:
-  - id: duplicate-skill
+    temp.child("aps.yaml")
+        .write_str(
+            r#"entries:
+  - id: existing-skill
     kind: agent_skill
     source:
       type: git
       repo: https://github.com/other/repo.git
       ref: main
       path: skills/existing
-    dest: ./.claude/skills/duplicate-skill/
-"#;
-    temp.child("aps.yaml").write_str(existing_manifest).unwrap();
+    dest: ./.claude/skills/existing-skill/
+"#,
+        )
+        .unwrap();
 
-    // Try to add a skill with the same ID (derived from folder name)
     aps()
         .args([
             "add",
-            "https://github.com/owner/repo/blob/main/path/to/duplicate-skill",
+            "https://github.com/owner/repo/blob/main/path/to/new-skill",
             "--no-sync",
+            "--show-diff",
         ])
         .current_dir(&temp)
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("Duplicate"));
-}
+        .success()
+        .stdout(predicate::str::contains("+++ "))
+        .stdout(predicate::str::contains("+ - id: new-skill"))
+        .stdout(predicate::str::contains("+ - id: existing-skill").not());
 
-#[test]
+    aps()
+        .args([
+            "add",
+            "https://github.com/owner/repo/blob/main/path/to/other-skill",
+            "--no-sync",
+        ])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+++ ").not());
+}
+
+#[test]
+fn add_verifies_ref_exists_and_suggests_close_matches() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    create_git_repo(
+        &temp.path().join("remotes/owner/repo.git"),
+        &[("skills/review/SKILL.md", "# Review\n")],
+    );
+    git(&temp.path().join("remotes/owner/repo.git"))
+        .args(["tag", "v1.0.0"])
+        .output()
+        .unwrap();
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    // Serve github.com from the local remotes directory
+    let add = |args: &[&str]| {
+        ApsCmd::new(&project)
+            .cmd(args)
+            .env("GIT_CONFIG_COUNT", "1")
+            .env(
+                "GIT_CONFIG_KEY_0",
+                format!("url.file://{}/remotes/.insteadOf", temp.path().display()),
+            )
+            .env("GIT_CONFIG_VALUE_0", "https://github.com/")
+            .assert()
+    };
+
+    add(&[
+        "add",
+        "https://github.com/owner/repo/tree/mian/skills/review",
+        "--no-sync",
+    ])
+    .failure()
+    .stderr(predicate::str::contains("aps::add::ref_not_found"))
+    .stderr(predicate::str::contains("'main'"));
+    project.child("aps.yaml").assert(predicate::path::missing());
+
+    add(&[
+        "add",
+        "https://github.com/owner/repo/tree/v1.0.0/skills/review",
+        "--no-sync",
+    ])
+    .success();
+    add(&[
+        "add",
+        "https://github.com/owner/repo/tree/mian/skills/review",
+        "--no-sync",
+        "--no-verify",
+        "--id",
+        "unverified",
+    ])
+    .success();
+    project
+        .child("aps.yaml")
+        .assert(predicate::str::contains("ref: mian"));
+}
+
+#[test]
+fn add_duplicate_id_fails() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    // Create existing manifest with an entry
+    let existing_manifest = r#"entries:
+  - id: duplicate-skill
+    kind: agent_skill
+    source:
+      type: git
+      repo: https://github.com/other/repo.git
+      ref: main
+      path: skills/existing
+    dest: ./.claude/skills/duplicate-skill/
+"#;
+    temp.child("aps.yaml").write_str(existing_manifest).unwrap();
+
+    // Try to add a skill with the same ID (derived from folder name)
+    aps()
+        .args([
+            "add",
+            "https://github.com/owner/repo/blob/main/path/to/duplicate-skill",
+            "--no-sync",
+        ])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Duplicate"));
+}
+
+#[test]
 fn add_invalid_github_url_fails() {
     let temp = assert_fs::TempDir::new().unwrap();
 
@@ -1190,7 +2152,7 @@ fn add_invalid_github_url_fails() {
     aps()
         .args([
             "add",
-            "https://gitlab.com/owner/repo/blob/main/path",
+            "https://bitbucket.org/owner/repo/src/main/path",
             "--no-sync",
         ])
         .current_dir(&temp)
@@ -1254,6 +2216,73 @@ fn add_with_different_ref() {
     manifest.assert(predicate::str::contains("ref: v1.2.3"));
 }
 
+#[test]
+fn add_with_ref_override_for_branch_with_slashes() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let aps = ApsCmd::new(&temp);
+
+    aps.ok(&[
+        "add",
+        "https://github.com/owner/repo/blob/feature/foo-bar/skills/lint/SKILL.md",
+        "--ref",
+        "feature/foo-bar",
+        "--no-sync",
+    ]);
+    let manifest = temp.child("aps.yaml");
+    manifest.assert(predicate::str::contains("ref: feature/foo-bar"));
+    manifest.assert(predicate::str::contains("path: skills/lint"));
+    manifest.assert(predicate::str::contains("id: lint"));
+
+    aps.fails(&[
+        "add",
+        "https://github.com/owner/repo/blob/main/skills/lint",
+        "--ref",
+        "release/v2",
+        "--no-sync",
+    ])
+    .stderr(predicate::str::contains("does not match"));
+}
+
+#[test]
+fn add_owner_repo_shorthand() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    aps()
+        .args(["add", "owner/repo@v2:skills/refactor", "--no-sync"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    let manifest = temp.child("aps.yaml");
+    manifest.assert(predicate::str::contains(
+        "repo: https://github.com/owner/repo.git",
+    ));
+    manifest.assert(predicate::str::contains("ref: v2"));
+    manifest.assert(predicate::str::contains("path: skills/refactor"));
+    manifest.assert(predicate::str::contains("id: refactor"));
+}
+
+#[test]
+fn add_owner_repo_shorthand_with_host() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    aps()
+        .args([
+            "add",
+            "team/skills:tools/linter",
+            "--host",
+            "github.example.com",
+            "--no-sync",
+        ])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    temp.child("aps.yaml").assert(predicate::str::contains(
+        "repo: https://github.example.com/team/skills.git",
+    ));
+}
+
 #[test]
 fn add_help_shows_usage() {
     aps()
@@ -1271,65 +2300,6 @@ fn add_help_shows_usage() {
 // Repo-Level Discovery Tests
 // ============================================================================
 
-/// Helper to create a local git repo with multiple skills
-fn create_skills_repo(dir: &std::path::Path) {
-    // Initialize git repo with main as default branch
-    git(dir)
-        .args(["init", "--initial-branch=main"])
-        .output()
-        .expect("Failed to init git repo");
-
-    // Configure git user for commits
-    git(dir)
-        .args(["config", "user.email", "test@test.com"])
-        .output()
-        .expect("Failed to configure git email");
-    git(dir)
-        .args(["config", "user.name", "Test User"])
-        .output()
-        .expect("Failed to configure git name");
-    git(dir)
-        .args(["config", "commit.gpgsign", "false"])
-        .output()
-        .expect("Failed to disable gpg signing");
-
-    // Create skill directories with SKILL.md
-    std::fs::create_dir_all(dir.join("skills/refactor")).unwrap();
-    std::fs::write(
-        dir.join("skills/refactor/SKILL.md"),
-        "# Refactor\n\nRefactors code automatically.\n",
-    )
-    .unwrap();
-
-    std::fs::create_dir_all(dir.join("skills/test-gen")).unwrap();
-    std::fs::write(
-        dir.join("skills/test-gen/SKILL.md"),
-        "# Test Generation\n\nGenerates unit tests.\n",
-    )
-    .unwrap();
-
-    std::fs::create_dir_all(dir.join("skills/lint-fix")).unwrap();
-    std::fs::write(
-        dir.join("skills/lint-fix/SKILL.md"),
-        "# Lint Fix\n\nFixes linting issues.\n",
-    )
-    .unwrap();
-
-    // Create a non-skill directory (no SKILL.md)
-    std::fs::create_dir_all(dir.join("docs")).unwrap();
-    std::fs::write(dir.join("docs/README.md"), "# Documentation\n").unwrap();
-
-    // Add and commit all files
-    git(dir)
-        .args(["add", "."])
-        .output()
-        .expect("Failed to git add");
-    git(dir)
-        .args(["commit", "--no-gpg-sign", "-m", "Add skills"])
-        .output()
-        .expect("Failed to git commit");
-}
-
 #[test]
 fn add_repo_level_url_non_github_fails() {
     let temp = assert_fs::TempDir::new().unwrap();
@@ -1338,13 +2308,104 @@ fn add_repo_level_url_non_github_fails() {
 
     // Non-GitHub repo-level URL should fail
     aps()
-        .args(["add", "https://gitlab.com/owner/repo", "--all", "--no-sync"])
+        .args([
+            "add",
+            "https://bitbucket.org/owner/repo",
+            "--all",
+            "--no-sync",
+        ])
         .current_dir(&project)
         .assert()
         .failure()
         .stderr(predicate::str::contains("github.com"));
 }
 
+#[test]
+fn add_gitlab_urls_discover_and_add_skills() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    create_skills_repo(&temp.path().join("remotes/team/ai/skills.git"));
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    // Serve a self-hosted GitLab from the local remotes directory
+    let add = |args: &[&str]| {
+        ApsCmd::new(&project)
+            .cmd(args)
+            .env("GIT_CONFIG_COUNT", "1")
+            .env(
+                "GIT_CONFIG_KEY_0",
+                format!("url.file://{}/remotes/.insteadOf", temp.path().display()),
+            )
+            .env("GIT_CONFIG_VALUE_0", "https://git.example.com/")
+            .assert()
+    };
+
+    // Without --gitlab-host the instance is not recognized
+    add(&["add", "https://git.example.com/team/ai/skills", "--no-sync"])
+        .failure()
+        .stderr(predicate::str::contains("--gitlab-host"));
+
+    add(&[
+        "add",
+        "https://git.example.com/team/ai/skills/-/tree/main/skills",
+        "--gitlab-host",
+        "git.example.com",
+        "--all",
+        "--no-sync",
+    ])
+    .success()
+    .stdout(predicate::str::contains("Found 3 skill(s)"));
+    let manifest = project.child("aps.yaml");
+    manifest.assert(predicate::str::contains(
+        "repo: https://git.example.com/team/ai/skills.git",
+    ));
+    manifest.assert(predicate::str::contains("path: skills/lint-fix"));
+    manifest.assert(predicate::str::contains("ref: main"));
+
+    std::fs::remove_file(manifest.path()).unwrap();
+    add(&[
+        "add",
+        "https://git.example.com/team/ai/skills/-/blob/main/skills/refactor/SKILL.md",
+        "--gitlab-host",
+        "git.example.com",
+    ])
+    .success();
+    manifest.assert(predicate::str::contains("id: refactor"));
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .assert(predicate::path::exists());
+}
+
+#[test]
+fn add_github_enterprise_url_with_host() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    create_skills_repo(&temp.path().join("remotes/o/r.git"));
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+
+    // --host names a GitHub Enterprise instance, so its URLs parse GitHub-style
+    ApsCmd::new(&project)
+        .cmd(&[
+            "add",
+            "https://ghe.corp/o/r/tree/main/skills/refactor",
+            "--host",
+            "ghe.corp",
+            "--no-sync",
+        ])
+        .env("GIT_CONFIG_COUNT", "1")
+        .env(
+            "GIT_CONFIG_KEY_0",
+            format!("url.file://{}/remotes/.insteadOf", temp.path().display()),
+        )
+        .env("GIT_CONFIG_VALUE_0", "https://ghe.corp/")
+        .assert()
+        .success();
+
+    let manifest = project.child("aps.yaml");
+    manifest.assert(predicate::str::contains("repo: https://ghe.corp/o/r.git"));
+    manifest.assert(predicate::str::contains("ref: main"));
+    manifest.assert(predicate::str::contains("path: skills/refactor"));
+}
+
 #[test]
 fn add_repo_url_with_all_discovers_and_adds_skills() {
     let temp = assert_fs::TempDir::new().unwrap();
@@ -1504,27 +2565,6 @@ fn add_existing_manifest_skips_duplicates_on_discover() {
 // Filesystem Path Discovery Tests
 // ============================================================================
 
-/// Helper to create a local skills directory (no git, just files)
-fn create_skills_dir(dir: &std::path::Path) {
-    std::fs::create_dir_all(dir.join("skills/refactor")).unwrap();
-    std::fs::write(
-        dir.join("skills/refactor/SKILL.md"),
-        "# Refactor\n\nRefactors code automatically.\n",
-    )
-    .unwrap();
-
-    std::fs::create_dir_all(dir.join("skills/test-gen")).unwrap();
-    std::fs::write(
-        dir.join("skills/test-gen/SKILL.md"),
-        "# Test Generation\n\nGenerates unit tests.\n",
-    )
-    .unwrap();
-
-    // Non-skill directory
-    std::fs::create_dir_all(dir.join("docs")).unwrap();
-    std::fs::write(dir.join("docs/README.md"), "# Documentation\n").unwrap();
-}
-
 #[test]
 fn add_local_path_discovers_skills_with_all() {
     let temp = assert_fs::TempDir::new().unwrap();
@@ -1562,6 +2602,78 @@ fn add_local_path_discovers_skills_with_all() {
     manifest.assert(predicate::str::contains("symlink: true"));
 }
 
+#[test]
+fn add_local_path_normalizes_ids_of_nested_skills() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    // Two packs each with a "review" skill, and one with an awkward name
+    let source = temp.child("skills");
+    for dir in [
+        "packs/python/review",
+        "packs/rust/review",
+        "Lint Fixer (beta)",
+    ] {
+        source
+            .child(dir)
+            .child("SKILL.md")
+            .write_str("# Skill\n")
+            .unwrap();
+    }
+
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    let source_path = source.path().display().to_string();
+
+    aps()
+        .args(["add", &source_path, "--all", "--no-sync"])
+        .current_dir(&project)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added 3 entries"));
+
+    let manifest = project.child("aps.yaml");
+    manifest.assert(predicate::str::contains("id: packs-python-review"));
+    manifest.assert(predicate::str::contains("id: packs-rust-review"));
+    manifest.assert(predicate::str::contains("id: Lint-Fixer-beta"));
+    // Same-named skills install into different folders
+    manifest.assert(predicate::str::contains("packs-python-review/"));
+    manifest.assert(predicate::str::contains("packs-rust-review/"));
+
+    // A template builds IDs from the skill's parent folder and name
+    let templated = temp.child("templated");
+    templated.create_dir_all().unwrap();
+    aps()
+        .args([
+            "add",
+            &source_path,
+            "--all",
+            "--no-sync",
+            "--id-template",
+            "{parent}/{name}",
+        ])
+        .current_dir(&templated)
+        .assert()
+        .success();
+    let manifest = templated.child("aps.yaml");
+    manifest.assert(predicate::str::contains("id: python/review"));
+    manifest.assert(predicate::str::contains("id: rust/review"));
+
+    // Unknown placeholders are rejected
+    aps()
+        .args([
+            "add",
+            &source_path,
+            "--all",
+            "--no-sync",
+            "--id-template",
+            "{owner}-{name}",
+        ])
+        .current_dir(&templated)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown placeholder"));
+}
+
 #[test]
 fn add_local_single_skill_with_skill_md() {
     let temp = assert_fs::TempDir::new().unwrap();
@@ -1646,3 +2758,4239 @@ fn add_local_path_syncs_filesystem_skills() {
         .child(".claude/skills/test-gen/SKILL.md")
         .assert(predicate::path::exists());
 }
+
+// ============================================================================
+// Namespaced IDs and Rename Tests
+// ============================================================================
+
+#[test]
+fn add_local_path_with_namespaced_ids() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let source = temp.child("my-skills");
+    source.create_dir_all().unwrap();
+    create_skills_dir(source.path());
+
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+
+    aps()
+        .args([
+            "add",
+            &source.path().display().to_string(),
+            "--all",
+            "--no-sync",
+            "--id-style",
+            "namespaced",
+        ])
+        .current_dir(&project)
+        .assert()
+        .success();
+
+    let manifest = project.child("aps.yaml");
+    manifest.assert(predicate::str::contains("id: my-skills/refactor"));
+    manifest.assert(predicate::str::contains("id: my-skills/test-gen"));
+    // Destination stays named after the skill itself
+    manifest.assert(predicate::str::contains("dest: .claude/skills/refactor/"));
+}
+
+#[test]
+fn add_local_path_namespaces_colliding_ids() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let first = temp.child("team-a");
+    first.create_dir_all().unwrap();
+    create_skills_dir(first.path());
+
+    let second = temp.child("team-b");
+    second.create_dir_all().unwrap();
+    create_skills_dir(second.path());
+
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+
+    for source in [&first, &second] {
+        aps()
+            .args([
+                "add",
+                &source.path().display().to_string(),
+                "--all",
+                "--no-sync",
+            ])
+            .current_dir(&project)
+            .assert()
+            .success();
+    }
+
+    let manifest = project.child("aps.yaml");
+    manifest.assert(predicate::str::contains("id: refactor"));
+    manifest.assert(predicate::str::contains("id: team-b/refactor"));
+}
+
+#[test]
+fn add_single_namespaced_skills_get_distinct_dests() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    for team in ["team-a", "team-b"] {
+        temp.child(format!("{}/refactor/SKILL.md", team))
+            .write_str("# Refactor\n\nRefactors code.\n")
+            .unwrap();
+    }
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+
+    for team in ["team-a", "team-b"] {
+        let path = temp.child(team).child("refactor");
+        aps()
+            .args([
+                "add",
+                &path.path().display().to_string(),
+                "--no-sync",
+                "--id-style",
+                "namespaced",
+            ])
+            .current_dir(&project)
+            .assert()
+            .success();
+    }
+
+    let manifest = project.child("aps.yaml");
+    manifest.assert(predicate::str::contains("id: team-a/refactor"));
+    manifest.assert(predicate::str::contains("dest: .claude/skills/refactor/"));
+    manifest.assert(predicate::str::contains("id: team-b/refactor"));
+    manifest.assert(predicate::str::contains(
+        "dest: .claude/skills/team-b-refactor/",
+    ));
+}
+
+#[test]
+fn rename_updates_manifest_lockfile_and_dest() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let source = temp.child("my-skill");
+    source.create_dir_all().unwrap();
+    source
+        .child("SKILL.md")
+        .write_str("# My Skill\n\nDoes something.\n")
+        .unwrap();
+
+    let manifest = format!(
+        r#"entries:
+  - id: my-skill
+    kind: agent_skill
+    source:
+      type: filesystem
+      root: {}
+      symlink: false
+    dest: .claude/skills/my-skill/
+"#,
+        source.path().display()
+    );
+    temp.child("aps.yaml").write_str(&manifest).unwrap();
+
+    aps().arg("sync").current_dir(&temp).assert().success();
+
+    aps()
+        .args(["rename", "my-skill", "better-skill"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Renamed entry 'my-skill' to 'better-skill'",
+        ));
+
+    temp.child("aps.yaml")
+        .assert(predicate::str::contains("id: better-skill"))
+        .assert(predicate::str::contains(
+            "dest: .claude/skills/better-skill/",
+        ));
+    temp.child("aps.lock.yaml")
+        .assert(predicate::str::contains("better-skill:"))
+        .assert(predicate::str::contains("my-skill:").not());
+    temp.child(".claude/skills/better-skill/SKILL.md")
+        .assert(predicate::path::exists());
+    temp.child(".claude/skills/my-skill")
+        .assert(predicate::path::missing());
+}
+
+#[test]
+fn remove_interactive_requires_a_terminal() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .write_to(temp.path());
+    let aps = ApsCmd::new(&temp);
+    aps.ok(&["sync", "--yes"]);
+
+    aps.fails(&["remove"])
+        .stderr(predicate::str::contains("<ID>"));
+    aps.fails(&["remove", "--interactive", "--yes"])
+        .stderr(predicate::str::contains("aps::cli::not_interactive"));
+    // Without a terminal to confirm in, removal needs --yes
+    aps.fails(&["remove", "refactor"])
+        .stderr(predicate::str::contains("aps::cli::not_interactive"));
+    temp.child("aps.yaml")
+        .assert(predicate::str::contains("id: refactor"));
+    temp.child(".claude/skills/refactor/SKILL.md")
+        .assert(predicate::path::exists());
+}
+
+#[test]
+fn remove_by_id_deletes_entry_lock_and_files() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .filesystem_entry(
+            "test-gen",
+            "agent_skill",
+            skills.path(),
+            "skills/test-gen",
+            ".claude/skills/test-gen",
+        )
+        .write_to(temp.path());
+    let aps = ApsCmd::new(&temp);
+    aps.ok(&["sync", "--yes"]);
+
+    aps.fails(&["remove", "missing"])
+        .stderr(predicate::str::contains("aps::manifest::entry_not_found"));
+
+    aps.ok(&["remove", "refactor", "--yes"])
+        .stdout(predicate::str::contains("Removed 1 entries: refactor"));
+    temp.child("aps.yaml")
+        .assert(predicate::str::contains("id: refactor").not());
+    temp.child("aps.lock.yaml")
+        .assert(predicate::str::contains("refactor").not());
+    temp.child(".claude/skills/refactor")
+        .assert(predicate::path::missing());
+
+    // --keep-files leaves the installed skill in place
+    aps.ok(&["remove", "test-gen", "--keep-files", "--yes"]);
+    temp.child("aps.yaml")
+        .assert(predicate::str::contains("id: test-gen").not());
+    temp.child("aps.lock.yaml")
+        .assert(predicate::str::contains("test-gen").not());
+    temp.child(".claude/skills/test-gen/SKILL.md")
+        .assert(predicate::path::exists());
+}
+
+#[test]
+fn remove_keeps_default_dest_shared_with_other_entries() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source.child("one/a.md").write_str("# A\n").unwrap();
+    source.child("two/b.md").write_str("# B\n").unwrap();
+    temp.child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: one
+    kind: persona
+    source:
+      type: filesystem
+      root: {root}
+      path: one
+  - id: two
+    kind: persona
+    source:
+      type: filesystem
+      root: {root}
+      path: two
+"#,
+            root = source.path().display()
+        ))
+        .unwrap();
+    let aps = ApsCmd::new(&temp);
+    aps.ok(&["sync", "--yes"]);
+    temp.child(".ai/personas/a.md")
+        .assert(predicate::path::exists());
+    temp.child(".ai/personas/b.md")
+        .assert(predicate::path::exists());
+
+    // Both entries install into .ai/personas, so only one's own link goes
+    aps.ok(&["remove", "one", "--yes"]);
+    temp.child(".ai/personas/a.md")
+        .assert(predicate::path::missing());
+    temp.child(".ai/personas/b.md")
+        .assert(predicate::str::contains("# B"));
+}
+
+#[test]
+fn remove_lists_only_own_links_in_a_shared_dest() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source.child("one/a.json").write_str("{}\n").unwrap();
+    source.child("two/b.json").write_str("{}\n").unwrap();
+    let entry = |id: &str| {
+        format!(
+            "  - id: {id}\n    kind: cursor_hooks\n    source:\n      type: filesystem\n      root: {}\n      path: {id}\n    dest: .hooks\n",
+            source.path().display()
+        )
+    };
+    ManifestBuilder::new()
+        .raw_entry(&entry("one"))
+        .raw_entry(&entry("two"))
+        .write_to(temp.path());
+    let aps = ApsCmd::new(&temp);
+    aps.ok(&["sync", "--yes"]);
+
+    aps.ok(&["remove", "one", "--yes"])
+        .stdout(predicate::str::contains(".hooks/a.json"))
+        .stdout(predicate::str::is_match(r"(?m)^\s+\.hooks$").unwrap().not());
+    temp.child(".hooks/a.json")
+        .assert(predicate::path::missing());
+    temp.child(".hooks/b.json")
+        .assert(predicate::path::exists());
+    temp.child("aps.lock.yaml")
+        .assert(predicate::str::contains("two:"));
+}
+
+#[test]
+fn rename_to_existing_id_fails() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let manifest = r#"entries:
+  - id: one
+    kind: agents_md
+    source:
+      type: filesystem
+      root: .
+      path: ONE.md
+  - id: two
+    kind: agents_md
+    source:
+      type: filesystem
+      root: .
+      path: TWO.md
+"#;
+    temp.child("aps.yaml").write_str(manifest).unwrap();
+
+    aps()
+        .args(["rename", "one", "two"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Duplicate entry ID"));
+}
+
+// ============================================================================
+// Skill Name Sync Tests
+// ============================================================================
+
+fn write_mismatched_skill_manifest(temp: &assert_fs::TempDir) {
+    let source = temp.child("source-skill");
+    source.create_dir_all().unwrap();
+    source
+        .child("SKILL.md")
+        .write_str("---\nname: real-name\ndescription: Does something.\n---\n\n# Real\n")
+        .unwrap();
+
+    let manifest = format!(
+        r#"entries:
+  - id: old-name
+    kind: agent_skill
+    source:
+      type: filesystem
+      root: {}
+      symlink: false
+    dest: .claude/skills/old-name/
+"#,
+        source.path().display()
+    );
+    temp.child("aps.yaml").write_str(&manifest).unwrap();
+}
+
+#[test]
+fn validate_clones_each_git_repo_once_and_reports_in_manifest_order() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills-repo");
+    create_skills_repo(skills.path());
+    let other = temp.child("other-repo");
+    create_git_repo_with_agents_md(other.path(), "# Guide\n");
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .git_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .git_entry("guide", "agents_md", other.path(), "AGENTS.md", "AGENTS.md")
+        .git_entry(
+            "lint-fix",
+            "agent_skill",
+            skills.path(),
+            "skills/lint-fix",
+            ".claude/skills/lint-fix",
+        )
+        .write_to(project.path());
+
+    let output = ApsCmd::new(&project)
+        .ok(&["validate", "--jobs", "2"])
+        .stdout(predicate::str::contains(
+            "Cloning git sources (2 at a time):",
+        ))
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    let skills_repo = format!("[done] {} @ main", skills.path().display());
+    assert_eq!(stdout.matches(&skills_repo).count(), 1, "{}", stdout);
+
+    let position = |id: &str| stdout.find(&format!("[OK] {} (", id)).unwrap();
+    assert!(position("refactor") < position("guide"));
+    assert!(position("guide") < position("lint-fix"));
+}
+
+#[test]
+fn validate_reports_skill_name_mismatch() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    write_mismatched_skill_manifest(&temp);
+
+    aps()
+        .arg("validate")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "SKILL.md name 'real-name' does not match entry ID 'old-name' or installed directory 'old-name'",
+        ))
+        .stdout(predicate::str::contains("--fix"));
+
+    temp.child("aps.yaml")
+        .assert(predicate::str::contains("id: old-name"));
+}
+
+#[test]
+fn validate_fix_renames_entry_and_dest_to_skill_name() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    write_mismatched_skill_manifest(&temp);
+
+    aps().arg("sync").current_dir(&temp).assert().success();
+
+    aps()
+        .args(["validate", "--fix"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("old-name → real-name"));
+
+    temp.child("aps.yaml")
+        .assert(predicate::str::contains("id: real-name"))
+        .assert(predicate::str::contains("dest: .claude/skills/real-name/"));
+    temp.child("aps.lock.yaml")
+        .assert(predicate::str::contains("real-name:"))
+        .assert(predicate::str::contains("old-name").not());
+    temp.child(".claude/skills/real-name/SKILL.md")
+        .assert(predicate::path::exists());
+    temp.child(".claude/skills/old-name")
+        .assert(predicate::path::missing());
+}
+
+#[test]
+fn validate_fix_leaves_shared_skills_root_alone_without_dest() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source-skill");
+    source.create_dir_all().unwrap();
+    source
+        .child("Skill.md")
+        .write_str("---\nname: real-name\ndescription: Does something.\n---\n\n# Real\n")
+        .unwrap();
+    temp.child(".claude/skills/other/SKILL.md")
+        .write_str("# Other\n")
+        .unwrap();
+
+    let manifest = format!(
+        r#"entries:
+  - id: old-name
+    kind: agent_skill
+    source:
+      type: filesystem
+      root: {}
+      symlink: false
+"#,
+        source.path().display()
+    );
+    temp.child("aps.yaml").write_str(&manifest).unwrap();
+
+    aps()
+        .args(["validate", "--fix"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("old-name → real-name"));
+
+    temp.child("aps.yaml")
+        .assert(predicate::str::contains("id: real-name"))
+        .assert(predicate::str::contains("dest:").not());
+    temp.child(".claude/skills/other/SKILL.md")
+        .assert(predicate::path::exists());
+    temp.child(".claude/real-name")
+        .assert(predicate::path::missing());
+}
+
+// ============================================================================
+// Bootstrap Tests
+// ============================================================================
+
+#[test]
+fn bootstrap_requires_lockfile() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("aps.yaml").write_str("entries: []\n").unwrap();
+
+    aps()
+        .args(["bootstrap", "--emit-script"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("lockfile"));
+}
+
+#[cfg(unix)]
+#[test]
+fn bootstrap_script_installs_locked_assets() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let source = temp.child("my-skill");
+    source.create_dir_all().unwrap();
+    source
+        .child("SKILL.md")
+        .write_str("# My Skill\n\nDoes something.\n")
+        .unwrap();
+
+    let manifest = format!(
+        r#"entries:
+  - id: my-skill
+    kind: agent_skill
+    source:
+      type: filesystem
+      root: {}
+      symlink: false
+    dest: .claude/skills/my-skill/
+"#,
+        source.path().display()
+    );
+    temp.child("aps.yaml").write_str(&manifest).unwrap();
+    aps().arg("sync").current_dir(&temp).assert().success();
+
+    let output = aps()
+        .args(["bootstrap", "--emit-script"])
+        .current_dir(&temp)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let script = String::from_utf8(output.stdout).unwrap();
+    assert!(script.starts_with("#!/bin/sh"));
+    temp.child("setup-agents.sh").write_str(&script).unwrap();
+
+    // Materialize into a fresh directory that has never run aps
+    let target = assert_fs::TempDir::new().unwrap();
+    Command::new("sh")
+        .arg(temp.child("setup-agents.sh").path())
+        .current_dir(&target)
+        .assert()
+        .success();
+
+    target
+        .child(".claude/skills/my-skill/SKILL.md")
+        .assert(predicate::str::contains("Does something."));
+}
+
+// ============================================================================
+// Last Sync Outcome Tests
+// ============================================================================
+
+#[cfg(unix)]
+#[test]
+fn sync_entry_timeout_cancels_hung_git_and_continues() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    // A `git` that never finishes stands in for a hung clone
+    let bin = temp.child("bin");
+    bin.child("git").write_str("#!/bin/sh\nsleep 30\n").unwrap();
+    std::fs::set_permissions(
+        bin.path().join("git"),
+        std::fs::Permissions::from_mode(0o755),
+    )
+    .unwrap();
+    let path = format!(
+        "{}:{}",
+        bin.path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    let source = temp.child("source");
+    source.child("AGENTS.md").write_str("# Local\n").unwrap();
+    let project = temp.child("project");
+    project
+        .child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: hung
+    kind: agents_md
+    source:
+      type: git
+      repo: https://example.com/hung.git
+      path: AGENTS.md
+    dest: ./HUNG.md
+  - id: local
+    kind: agents_md
+    source:
+      type: filesystem
+      root: {}
+      path: AGENTS.md
+      symlink: false
+"#,
+            source.path().display()
+        ))
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    aps()
+        .args(["sync", "--entry-timeout", "1"])
+        .env("PATH", &path)
+        .current_dir(&project)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Timed out after"))
+        .stdout(predicate::str::contains("1 failed"))
+        .stderr(predicate::str::contains("1 entries failed to sync: hung"));
+    assert!(started.elapsed() < std::time::Duration::from_secs(20));
+
+    project
+        .child("AGENTS.md")
+        .assert(predicate::str::contains("# Local"));
+}
+
+#[test]
+fn sync_keep_going_installs_remaining_entries_after_failure() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source.child("AGENTS.md").write_str("# Good\n").unwrap();
+
+    temp.child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: broken
+    kind: agents_md
+    source:
+      type: filesystem
+      root: {root}
+      path: missing.md
+    dest: ./BROKEN.md
+  - id: good
+    kind: agents_md
+    source:
+      type: filesystem
+      root: {root}
+      path: AGENTS.md
+      symlink: false
+"#,
+            root = source.path().display()
+        ))
+        .unwrap();
+
+    aps()
+        .args(["sync", "--keep-going"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("1 failed"))
+        .stderr(predicate::str::contains("1 entries failed to sync: broken"));
+
+    temp.child("AGENTS.md")
+        .assert(predicate::str::contains("# Good"));
+    temp.child("aps.lock.yaml")
+        .assert(predicate::str::contains("good:"));
+
+    // Without --keep-going the first failure still aborts the sync
+    std::fs::remove_file(temp.path().join("AGENTS.md")).unwrap();
+    aps().arg("sync").current_dir(&temp).assert().failure();
+    temp.child("AGENTS.md").assert(predicate::path::missing());
+}
+
+#[test]
+fn sync_records_outcomes_and_retries_failed_entries() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let alpha = temp.child("sources/alpha");
+    alpha.create_dir_all().unwrap();
+    alpha.child("SKILL.md").write_str("# Alpha\n").unwrap();
+    let beta = temp.child("sources/beta");
+
+    let manifest = format!(
+        r#"entries:
+  - id: alpha
+    kind: agent_skill
+    source:
+      type: filesystem
+      root: {}
+      symlink: false
+    dest: .claude/skills/alpha/
+  - id: beta
+    kind: agent_skill
+    source:
+      type: filesystem
+      root: {}
+      symlink: false
+    dest: .claude/skills/beta/
+"#,
+        alpha.path().display(),
+        beta.path().display()
+    );
+    temp.child("aps.yaml").write_str(&manifest).unwrap();
+
+    // beta's source is missing, so the sync fails after installing alpha
+    aps().arg("sync").current_dir(&temp).assert().failure();
+
+    temp.child("aps.lock.yaml")
+        .assert(predicate::str::contains("alpha:"))
+        .assert(predicate::str::contains("last_status: copied"));
+
+    aps()
+        .arg("status")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Last sync:    copied"));
+
+    beta.create_dir_all().unwrap();
+    beta.child("SKILL.md").write_str("# Beta\n").unwrap();
+
+    aps()
+        .args(["sync", "--retry-failed"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("beta"))
+        .stdout(predicate::str::contains("alpha").not());
+
+    temp.child(".claude/skills/beta/SKILL.md")
+        .assert(predicate::path::exists());
+
+    aps()
+        .args(["sync", "--retry-failed"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No failed entries to retry."));
+}
+
+// ============================================================================
+// Editor Integration Tests
+// ============================================================================
+
+#[test]
+fn sync_registers_skill_dirs_in_editor_settings_when_enabled() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let source = temp.child("my-skill");
+    source.create_dir_all().unwrap();
+    source.child("SKILL.md").write_str("# My Skill\n").unwrap();
+    temp.child(".vscode/settings.json")
+        .write_str("{\n  \"editor.tabSize\": 2\n}\n")
+        .unwrap();
+
+    let manifest = format!(
+        r#"integrations:
+  cursor: true
+entries:
+  - id: my-skill
+    kind: agent_skill
+    source:
+      type: filesystem
+      root: {}
+      symlink: false
+    dest: .claude/skills/my-skill/
+"#,
+        source.path().display()
+    );
+    temp.child("aps.yaml").write_str(&manifest).unwrap();
+
+    aps()
+        .arg("sync")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(".vscode/settings.json"));
+
+    temp.child(".vscode/settings.json")
+        .assert(predicate::str::contains("\"editor.tabSize\": 2"))
+        .assert(predicate::str::contains("\"chat.agentSkillsLocations\""))
+        .assert(predicate::str::contains("\".claude/skills\": true"));
+}
+
+#[test]
+fn sync_leaves_editor_settings_alone_by_default() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let source = temp.child("my-skill");
+    source.create_dir_all().unwrap();
+    source.child("SKILL.md").write_str("# My Skill\n").unwrap();
+
+    let manifest = format!(
+        r#"entries:
+  - id: my-skill
+    kind: agent_skill
+    source:
+      type: filesystem
+      root: {}
+      symlink: false
+"#,
+        source.path().display()
+    );
+    temp.child("aps.yaml").write_str(&manifest).unwrap();
+
+    aps().arg("sync").current_dir(&temp).assert().success();
+
+    temp.child(".vscode").assert(predicate::path::missing());
+}
+
+// ============================================================================
+// Lock Command Tests
+// ============================================================================
+
+#[test]
+fn lock_writes_lockfile_without_installing() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let source = temp.child("my-skill");
+    source.create_dir_all().unwrap();
+    source.child("SKILL.md").write_str("# My Skill\n").unwrap();
+
+    let manifest = format!(
+        r#"entries:
+  - id: my-skill
+    kind: agent_skill
+    source:
+      type: filesystem
+      root: {}
+      symlink: false
+    dest: .claude/skills/my-skill/
+"#,
+        source.path().display()
+    );
+    temp.child("aps.yaml").write_str(&manifest).unwrap();
+
+    aps()
+        .arg("lock")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 updated"));
+
+    temp.child("aps.lock.yaml")
+        .assert(predicate::str::contains("my-skill:"))
+        .assert(predicate::str::contains("checksum: sha256:"));
+    temp.child(".claude").assert(predicate::path::missing());
+
+    // Locking again with unchanged sources reports no updates
+    aps()
+        .arg("lock")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("0 updated"));
+}
+
+#[test]
+fn lock_checksum_matches_sync() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    let source = temp.child("my-skill");
+    source.create_dir_all().unwrap();
+    source.child("SKILL.md").write_str("# My Skill\n").unwrap();
+
+    let manifest = format!(
+        r#"entries:
+  - id: my-skill
+    kind: agent_skill
+    source:
+      type: filesystem
+      root: {}
+      symlink: false
+"#,
+        source.path().display()
+    );
+    temp.child("aps.yaml").write_str(&manifest).unwrap();
+
+    aps().arg("lock").current_dir(&temp).assert().success();
+    let locked = std::fs::read_to_string(temp.child("aps.lock.yaml").path()).unwrap();
+
+    aps().arg("sync").current_dir(&temp).assert().success();
+    let synced = std::fs::read_to_string(temp.child("aps.lock.yaml").path()).unwrap();
+
+    let checksum_line = |s: &str| {
+        s.lines()
+            .find(|l| l.trim_start().starts_with("checksum:"))
+            .map(str::to_string)
+    };
+    assert_eq!(checksum_line(&locked), checksum_line(&synced));
+}
+
+#[test]
+fn plan_is_applied_by_sync_and_rejected_after_drift() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source.child("AGENTS.md").write_str("# Planned\n").unwrap();
+
+    let manifest = format!(
+        r#"entries:
+  - id: agents
+    kind: agents_md
+    source:
+      type: filesystem
+      root: {root}
+      path: AGENTS.md
+      symlink: false
+"#,
+        root = source.path().display()
+    );
+    temp.child("aps.yaml").write_str(&manifest).unwrap();
+
+    aps()
+        .args(["plan", "--out", "plan.json"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("agents"))
+        .stdout(predicate::str::contains("1 to install"));
+    temp.child("plan.json")
+        .assert(predicate::str::contains("\"action\": \"install\""));
+    temp.child("AGENTS.md").assert(predicate::path::missing());
+
+    aps()
+        .args(["sync", "--plan", "plan.json"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    temp.child("AGENTS.md")
+        .assert(predicate::str::contains("# Planned"));
+
+    // The lockfile changed, so the old plan no longer applies
+    aps()
+        .args(["sync", "--plan", "plan.json"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Plan is out of date"));
+
+    // Source edits after planning are caught too
+    aps()
+        .args(["plan", "--out", "plan.json"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No changes"));
+    source.child("AGENTS.md").write_str("# Edited\n").unwrap();
+    aps()
+        .args(["sync", "--plan", "plan.json"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'agents' would now update"));
+    temp.child("AGENTS.md")
+        .assert(predicate::str::contains("# Planned"));
+}
+
+#[test]
+fn no_color_flag_and_env_strip_ansi_styling() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source.child("AGENTS.md").write_str("# Colors\n").unwrap();
+    temp.child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: agents
+    kind: agents_md
+    source:
+      type: filesystem
+      root: {root}
+      path: AGENTS.md
+      symlink: false
+"#,
+            root = source.path().display()
+        ))
+        .unwrap();
+
+    aps()
+        .args(["sync", "--dry-run"])
+        .env("CLICOLOR_FORCE", "1")
+        .env_remove("NO_COLOR")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\u{1b}["));
+
+    aps()
+        .args(["--no-color", "sync", "--dry-run"])
+        .env("CLICOLOR_FORCE", "1")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("agents"))
+        .stdout(predicate::str::contains("\u{1b}[").not());
+
+    aps()
+        .args(["sync", "--dry-run"])
+        .env("CLICOLOR_FORCE", "1")
+        .env("NO_COLOR", "1")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\u{1b}[").not());
+}
+
+#[test]
+fn sync_checksum_ignore_excludes_volatile_files_from_change_detection() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skill = temp.child("source/my-skill");
+    skill.child("SKILL.md").write_str("# Skill\n").unwrap();
+    skill.child("build-id.txt").write_str("1\n").unwrap();
+
+    temp.child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: my-skill
+    kind: agent_skill
+    source:
+      type: filesystem
+      root: {root}
+      path: my-skill
+      symlink: false
+    dest: ./.claude/skills/my-skill/
+    checksum_ignore:
+      - "build-id.txt"
+"#,
+            root = temp.child("source").path().display()
+        ))
+        .unwrap();
+
+    aps().arg("sync").current_dir(&temp).assert().success();
+    temp.child(".claude/skills/my-skill/build-id.txt")
+        .assert(predicate::path::exists());
+
+    skill.child("build-id.txt").write_str("2\n").unwrap();
+    aps()
+        .arg("plan")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No changes"));
+
+    skill.child("SKILL.md").write_str("# Changed\n").unwrap();
+    aps()
+        .arg("plan")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("source content changed"));
+}
+
+#[test]
+fn sync_persona_merges_into_dest_and_catalogs_each_persona() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let personas = temp.child("source/personas");
+    personas
+        .child("reviewer.md")
+        .write_str("---\nname: Reviewer\ndescription: Reviews pull requests\n---\n\nBe thorough.\n")
+        .unwrap();
+    personas
+        .child("planner.md")
+        .write_str("# Planner\n")
+        .unwrap();
+    temp.child(".ai/personas/local.md")
+        .write_str("---\nname: Local\ndescription: Project persona\n---\n")
+        .unwrap();
+
+    temp.child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: team-personas
+    kind: persona
+    source:
+      type: filesystem
+      root: {root}
+      path: personas
+      symlink: false
+"#,
+            root = temp.child("source").path().display()
+        ))
+        .unwrap();
+
+    aps()
+        .arg("validate")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "planner.md is missing frontmatter: name, description",
+        ));
+
+    aps()
+        .args(["sync", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    temp.child(".ai/personas/reviewer.md")
+        .assert(predicate::str::contains("Be thorough."));
+    temp.child(".ai/personas/local.md")
+        .assert(predicate::str::contains("Project persona"));
+
+    aps()
+        .args(["catalog", "generate"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    temp.child("aps.catalog.yaml")
+        .assert(predicate::str::contains("id: team-personas:reviewer.md"))
+        .assert(predicate::str::contains("name: Reviewer"))
+        .assert(predicate::str::contains(
+            "short_description: Reviews pull requests",
+        ))
+        .assert(predicate::str::contains("name: planner.md"));
+}
+
+#[test]
+fn status_and_list_filter_entries_by_expression() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source.child("AGENTS.md").write_str("# Agents\n").unwrap();
+    source.child("rules/a.mdc").write_str("rule\n").unwrap();
+
+    temp.child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: agents
+    kind: agents_md
+    source:
+      type: filesystem
+      root: {root}
+      path: AGENTS.md
+      symlink: false
+  - id: rules
+    kind: cursor_rules
+    source:
+      type: filesystem
+      root: {root}
+      path: rules
+"#,
+            root = source.path().display()
+        ))
+        .unwrap();
+
+    aps()
+        .args(["sync", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+
+    aps()
+        .args(["status", "--filter", "symlink=false"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ID:           agents"))
+        .stdout(predicate::str::contains("ID:           rules").not());
+
+    aps()
+        .args([
+            "list",
+            "--filter",
+            "kind!=agents_md",
+            "--filter",
+            "source~=rules",
+        ])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 of 2 entries match"))
+        .stdout(predicate::str::contains("cursor_rules"))
+        .stdout(predicate::str::contains("agents_md").not());
+
+    aps()
+        .args(["status", "--filter", "team=me"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown field 'team'"));
+}
+
+#[test]
+fn entry_description_and_owner_appear_in_list_status_and_catalog() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source
+        .child("AGENTS.md")
+        .write_str("# Agents\n\nExtracted paragraph.\n")
+        .unwrap();
+
+    temp.child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: agents
+    kind: agents_md
+    description: Shared coding conventions
+    owner: platform-team
+    source:
+      type: filesystem
+      root: {root}
+      path: AGENTS.md
+      symlink: false
+"#,
+            root = source.path().display()
+        ))
+        .unwrap();
+
+    aps()
+        .arg("list")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Shared coding conventions"))
+        .stdout(predicate::str::contains("platform-team"));
+
+    aps()
+        .args(["sync", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    aps()
+        .args(["status", "--filter", "owner=platform-team"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Description:  Shared coding conventions",
+        ))
+        .stdout(predicate::str::contains("Owner:        platform-team"));
+
+    aps()
+        .args(["catalog", "generate"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    temp.child("aps.catalog.yaml")
+        .assert(predicate::str::contains(
+            "short_description: Shared coding conventions",
+        ))
+        .assert(predicate::str::contains("owner: platform-team"))
+        .assert(predicate::str::contains("Extracted paragraph").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn sync_skips_source_symlinks_that_escape_the_source() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("secret.txt").write_str("top secret\n").unwrap();
+    let skill = temp.child("source/my-skill");
+    skill.child("SKILL.md").write_str("# Skill\n").unwrap();
+    std::os::unix::fs::symlink(
+        temp.path().join("secret.txt"),
+        skill.path().join("leak.txt"),
+    )
+    .unwrap();
+    std::os::unix::fs::symlink("SKILL.md", skill.path().join("alias.md")).unwrap();
+
+    temp.child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: my-skill
+    kind: agent_skill
+    source:
+      type: filesystem
+      root: {root}
+      path: my-skill
+      symlink: false
+    dest: ./.claude/skills/my-skill/
+"#,
+            root = temp.child("source").path().display()
+        ))
+        .unwrap();
+
+    aps()
+        .args(["sync", "--strict"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("aps::source::unsafe_link"));
+    temp.child(".claude/skills/my-skill")
+        .assert(predicate::path::missing());
+
+    aps()
+        .arg("sync")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Skipped leak.txt: symlink points outside the source",
+        ));
+    temp.child(".claude/skills/my-skill/SKILL.md")
+        .assert(predicate::path::exists());
+    temp.child(".claude/skills/my-skill/alias.md")
+        .assert(predicate::str::contains("# Skill"));
+    temp.child(".claude/skills/my-skill/leak.txt")
+        .assert(predicate::path::missing());
+}
+
+#[test]
+fn sync_only_kind_limits_entries_and_rejects_unknown_kinds() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source.child("AGENTS.md").write_str("# Agents\n").unwrap();
+    source.child("rules/a.mdc").write_str("rule\n").unwrap();
+
+    temp.child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: agents
+    kind: agents_md
+    source:
+      type: filesystem
+      root: {root}
+      path: AGENTS.md
+      symlink: false
+  - id: rules
+    kind: cursor_rules
+    source:
+      type: filesystem
+      root: {root}
+      path: rules
+      symlink: false
+"#,
+            root = source.path().display()
+        ))
+        .unwrap();
+
+    aps()
+        .args(["sync", "--only-kind", "cursor_rules"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    temp.child(".cursor/rules/a.mdc")
+        .assert(predicate::path::exists());
+    temp.child("AGENTS.md").assert(predicate::path::missing());
+
+    // Combined with --only, both filters must match
+    aps()
+        .args(["sync", "--only-kind", "cursor_rules", "--only", "agents"])
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "No entries match the requested kinds",
+        ));
+    temp.child("aps.lock.yaml")
+        .assert(predicate::str::contains("rules:"));
+
+    aps()
+        .args(["validate", "--only-kind", "skills"])
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid asset kind: skills"));
+}
+
+#[test]
+fn sync_records_upstream_license_and_copies_it_when_asked() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source
+        .child("LICENSE")
+        .write_str("MIT License\n\nPermission is hereby granted, free of charge, to any person\n")
+        .unwrap();
+    source
+        .child("skills/review/SKILL.md")
+        .write_str("# Review\n")
+        .unwrap();
+
+    temp.child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: review
+    kind: agent_skill
+    copy_license: true
+    source:
+      type: filesystem
+      root: {root}
+      path: skills
+      symlink: false
+    dest: ./.claude/skills/
+"#,
+            root = source.path().display()
+        ))
+        .unwrap();
+
+    aps().arg("sync").current_dir(&temp).assert().success();
+    temp.child("aps.lock.yaml")
+        .assert(predicate::str::contains("license: MIT"))
+        .assert(predicate::str::contains("license_file: LICENSE"));
+    temp.child(".claude/skills/LICENSE")
+        .assert(predicate::str::contains("Permission is hereby granted"));
+
+    aps()
+        .arg("list")
+        .current_dir(&temp)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("License: MIT"));
+
+    aps()
+        .args(["catalog", "generate"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    temp.child("aps.catalog.yaml")
+        .assert(predicate::str::contains("license: MIT"));
+}
+
+#[test]
+fn low_bandwidth_sync_checks_out_license_files_in_any_case() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source_repo = temp.child("source-repo");
+    create_git_repo(
+        source_repo.path(),
+        &[
+            ("skills/review/SKILL.md", "# Review\n"),
+            (
+                "License.md",
+                "MIT License\n\nPermission is hereby granted, free of charge, to any person\n",
+            ),
+        ],
+    );
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .git_entry(
+            "review",
+            "agent_skill",
+            source_repo.path(),
+            "skills/review",
+            ".claude/skills/review",
+        )
+        .write_to(project.path());
+    let config = temp.child("config.yaml");
+    config.write_str("low_bandwidth: true\n").unwrap();
+
+    ApsCmd::new(&project)
+        .cmd(&["sync"])
+        .env("APS_CONFIG", config.path())
+        .assert()
+        .success();
+    project
+        .child("aps.lock.yaml")
+        .assert(predicate::str::contains("license: MIT"))
+        .assert(predicate::str::contains("license_file: License.md"));
+}
+
+#[test]
+fn changelog_summarizes_lockfile_history() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let upstream = temp.child("upstream");
+    create_git_repo_with_agents_md(upstream.path(), "# Version 1\n");
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    create_git_repo(project.path(), &[("README.md", "# Project\n")]);
+    let aps = ApsCmd::new(&project);
+
+    let docs = || {
+        ManifestBuilder::new().git_entry(
+            "docs",
+            "agents_md",
+            upstream.path(),
+            "AGENTS.md",
+            "./AGENTS.md",
+        )
+    };
+    docs()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .write_to(project.path());
+    aps.ok(&["sync", "--yes"]);
+    aps.fails(&["changelog"])
+        .stderr(predicate::str::contains("aps::changelog::no_history"));
+    commit_files(project.path(), &[], "Add docs and refactor");
+
+    update_agents_md_in_repo(upstream.path(), "# Version 2\n");
+    aps.ok(&["sync", "--yes", "--upgrade"]);
+    commit_files(project.path(), &[], "Upgrade docs");
+
+    docs().write_to(project.path());
+    aps.ok(&["sync", "--yes"]);
+    commit_files(project.path(), &[], "Drop refactor");
+
+    let output = aps.ok(&["changelog"]).get_output().stdout.clone();
+    let output = String::from_utf8(output).unwrap();
+    let drop = output.find("- Drop refactor").unwrap();
+    let upgrade = output.find("- Upgrade docs").unwrap();
+    let add = output.find("- Add docs and refactor").unwrap();
+    assert!(drop < upgrade && upgrade < add, "{}", output);
+    assert!(output.contains("- Removed `refactor`"), "{}", output);
+    assert!(output.contains("- Upgraded `docs`: "), "{}", output);
+    assert!(output.contains("- Added `docs` at "), "{}", output);
+    assert!(output.contains("- Added `refactor`\n"), "{}", output);
+
+    aps.ok(&["changelog", "--write"])
+        .stdout(predicate::str::contains("Wrote 3 lockfile changes"));
+    project
+        .child("AGENT_ASSETS_CHANGELOG.md")
+        .assert(output.as_str());
+}
+
+#[test]
+fn log_shows_upstream_commits_touching_entry_path_since_lock() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source_repo = temp.child("source-repo");
+    source_repo.create_dir_all().unwrap();
+    create_git_repo_with_agents_md(source_repo.path(), "# Version 1\n");
+
+    let project = temp.child("project");
+    project
+        .child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: test-agents
+    kind: agents_md
+    source:
+      type: git
+      repo: {}
+      ref: main
+      shallow: false
+      path: AGENTS.md
+    dest: ./AGENTS.md
+  - id: local
+    kind: agents_md
+    source:
+      type: filesystem
+      root: {}
+      path: AGENTS.md
+    dest: ./LOCAL.md
+"#,
+            source_repo.path().display(),
+            source_repo.path().display()
+        ))
+        .unwrap();
+    aps().arg("sync").current_dir(&project).assert().success();
+
+    aps()
+        .args(["log", "test-agents"])
+        .current_dir(&project)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No upstream changes."));
+
+    update_agents_md_in_repo(source_repo.path(), "# Version 2\n");
+    std::fs::write(source_repo.path().join("README.md"), "unrelated\n").unwrap();
+    git(source_repo.path())
+        .args(["add", "README.md"])
+        .output()
+        .unwrap();
+    git(source_repo.path())
+        .args(["commit", "--no-gpg-sign", "-m", "Add README"])
+        .output()
+        .unwrap();
+
+    aps()
+        .args(["log", "test-agents"])
+        .current_dir(&project)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Update AGENTS.md"))
+        .stdout(predicate::str::contains("Add README").not())
+        .stdout(predicate::str::contains("1 commit."));
+
+    aps()
+        .args(["log", "test-agents", "--since", "2000-01-01"])
+        .current_dir(&project)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Initial commit"))
+        .stdout(predicate::str::contains("2 commits."));
+
+    aps()
+        .args(["log", "local"])
+        .current_dir(&project)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not git-sourced"));
+}
+
+#[test]
+fn cache_export_import_enables_offline_sync() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source_repo = temp.child("source-repo");
+    source_repo.create_dir_all().unwrap();
+    create_git_repo_with_agents_md(source_repo.path(), "# Cached\n");
+
+    let manifest = format!(
+        r#"entries:
+  - id: test-agents
+    kind: agents_md
+    source:
+      type: git
+      repo: {}
+      ref: main
+      path: AGENTS.md
+    dest: ./AGENTS.md
+"#,
+        source_repo.path().display()
+    );
+    let online = temp.child("online");
+    online.child("aps.yaml").write_str(&manifest).unwrap();
+    aps().arg("sync").current_dir(&online).assert().success();
+
+    let archive = temp.child("aps-cache.tar");
+    aps()
+        .args(["cache", "export"])
+        .arg(archive.path())
+        .current_dir(&online)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 1 repositories"));
+
+    // The air-gapped project has the manifest and lockfile but no network
+    let offline = temp.child("offline");
+    offline.child("aps.yaml").write_str(&manifest).unwrap();
+    std::fs::copy(
+        online.child("aps.lock.yaml").path(),
+        offline.child("aps.lock.yaml").path(),
+    )
+    .unwrap();
+    std::fs::rename(source_repo.path(), temp.path().join("moved-away")).unwrap();
+
+    aps()
+        .args(["sync", "--offline"])
+        .current_dir(&offline)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("aps::cache::offline_unavailable"));
+
+    aps()
+        .args(["cache", "import"])
+        .arg(archive.path())
+        .current_dir(&offline)
+        .assert()
+        .success();
+    aps()
+        .args(["sync", "--offline"])
+        .current_dir(&offline)
+        .assert()
+        .success();
+    offline
+        .child("AGENTS.md")
+        .assert(predicate::str::contains("# Cached"));
+}
+
+#[test]
+fn sync_auto_ref_follows_remote_default_branch() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source_repo = temp.child("source-repo");
+    source_repo.create_dir_all().unwrap();
+    create_git_repo_with_agents_md(source_repo.path(), "# Develop\n");
+    git(source_repo.path())
+        .args(["branch", "-m", "main", "develop"])
+        .output()
+        .unwrap();
+
+    let project = temp.child("project");
+    project
+        .child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: test-agents
+    kind: agents_md
+    source:
+      type: git
+      repo: {}
+      ref: auto
+      path: AGENTS.md
+    dest: ./AGENTS.md
+"#,
+            source_repo.path().display()
+        ))
+        .unwrap();
+
+    aps().arg("sync").current_dir(&project).assert().success();
+    project
+        .child("AGENTS.md")
+        .assert(predicate::str::contains("# Develop"));
+    project
+        .child("aps.lock.yaml")
+        .assert(predicate::str::contains("resolved_ref: develop"));
+}
+
+#[cfg(unix)]
+#[test]
+fn sync_mirrors_skill_into_other_tools_and_cleans_up_dropped_mirrors() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source
+        .child("review/SKILL.md")
+        .write_str("# Review\n")
+        .unwrap();
+
+    let manifest = |also: &str| {
+        format!(
+            r#"entries:
+  - id: review
+    kind: agent_skill
+    also_install_for: [{also}]
+    source:
+      type: filesystem
+      root: {root}
+      path: review
+      symlink: false
+    dest: ./.claude/skills/review/
+"#,
+            root = source.path().display()
+        )
+    };
+    temp.child("aps.yaml")
+        .write_str(&manifest("cursor, claude"))
+        .unwrap();
+
+    aps()
+        .args(["sync", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    let mirror = temp.path().join(".cursor/skills/review");
+    assert_eq!(
+        std::fs::read_link(&mirror).unwrap(),
+        std::path::Path::new("../../.claude/skills/review")
+    );
+    temp.child(".cursor/skills/review/SKILL.md")
+        .assert(predicate::str::contains("# Review"));
+    temp.child("aps.lock.yaml").assert(predicate::str::contains(
+        "mirrors:\n    - .cursor/skills/review",
+    ));
+
+    // Dropping the tool removes its mirror but leaves the installed skill
+    temp.child("aps.yaml").write_str(&manifest("")).unwrap();
+    aps()
+        .args(["sync", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    assert!(mirror.symlink_metadata().is_err());
+    temp.child(".claude/skills/review/SKILL.md")
+        .assert(predicate::path::exists());
+
+    temp.child("aps.yaml")
+        .write_str(&manifest("windsurf"))
+        .unwrap();
+    aps()
+        .arg("validate")
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("aps::manifest::invalid_mirror"));
+}
+
+#[test]
+fn serve_stdio_answers_status_and_sync_requests() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("source/AGENTS.md")
+        .write_str("# Shared rules\n")
+        .unwrap();
+    temp.child("aps.yaml")
+        .write_str(
+            r#"entries:
+  - id: rules
+    kind: agents_md
+    source:
+      type: filesystem
+      root: source
+      path: AGENTS.md
+      symlink: false
+    dest: AGENTS.md
+"#,
+        )
+        .unwrap();
+
+    let requests = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"status"}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"sync","params":{"ids":["rules"]}}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"status"}"#,
+    ];
+    let output = aps()
+        .args(["serve", "--stdio"])
+        .current_dir(&temp)
+        .write_stdin(requests.join("\n") + "\n")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let responses: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[0]["result"]["entries"][0]["status"], "never");
+    assert_eq!(responses[1]["id"], 2);
+    assert_eq!(responses[1]["result"]["success"], true);
+    assert_eq!(responses[1]["result"]["entries"][0]["status"], "copied");
+    assert_eq!(
+        responses[2]["result"]["entries"][0]["locked"]["dest"],
+        "AGENTS.md"
+    );
+    temp.child("AGENTS.md")
+        .assert(predicate::str::contains("# Shared rules"));
+}
+
+#[test]
+fn serve_stdio_keeps_lockfile_warnings_off_stdout() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("source/AGENTS.md")
+        .write_str("# Shared rules\n")
+        .unwrap();
+    ManifestBuilder::new()
+        .raw_entry("  - id: rules\n    kind: agents_md\n    source:\n      type: filesystem\n      root: source\n      path: AGENTS.md\n      symlink: false\n    dest: AGENTS.md\n")
+        .write_to(temp.path());
+    ApsCmd::new(&temp).ok(&["sync", "--yes"]);
+    // A lockfile from a newer aps makes loading it warn
+    let lockfile = temp.child("aps.lock.yaml");
+    let content = std::fs::read_to_string(lockfile.path()).unwrap();
+    let content = content
+        .lines()
+        .map(|line| {
+            if line.starts_with("aps_version:") {
+                "aps_version: 99.0.0"
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    lockfile.write_str(&content).unwrap();
+
+    let output = aps()
+        .args(["serve", "--stdio"])
+        .current_dir(&temp)
+        .write_stdin("{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"status\"}\n")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1, "unexpected stdout: {}", stdout);
+    let response: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(response["result"]["entries"][0]["status"], "copied");
+}
+
+#[test]
+fn sync_upgrade_skips_frozen_entries_unless_forced_by_id() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source_repo = temp.child("source-repo");
+    source_repo.create_dir_all().unwrap();
+    create_git_repo_with_agents_md(source_repo.path(), "# Reviewed\n");
+
+    let project = temp.child("project");
+    project
+        .child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: compliance
+    kind: agents_md
+    frozen: true
+    source:
+      type: git
+      repo: {}
+      ref: main
+      path: AGENTS.md
+    dest: ./AGENTS.md
+"#,
+            source_repo.path().display()
+        ))
+        .unwrap();
+
+    aps().arg("sync").current_dir(&project).assert().success();
+    update_agents_md_in_repo(source_repo.path(), "# Unreviewed\n");
+
+    aps()
+        .args(["sync", "--upgrade"])
+        .current_dir(&project)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[frozen]"));
+    project
+        .child("AGENTS.md")
+        .assert(predicate::str::contains("# Reviewed"));
+
+    // --force alone is not enough; the entry must be named
+    aps()
+        .args(["sync", "--upgrade", "--force"])
+        .current_dir(&project)
+        .assert()
+        .success();
+    project
+        .child("AGENTS.md")
+        .assert(predicate::str::contains("# Reviewed"));
+
+    aps()
+        .args([
+            "sync",
+            "--upgrade",
+            "--force",
+            "--only",
+            "compliance",
+            "--yes",
+        ])
+        .current_dir(&project)
+        .assert()
+        .success();
+    project
+        .child("AGENTS.md")
+        .assert(predicate::str::contains("# Unreviewed"));
+}
+
+#[test]
+fn sync_composite_renders_index_of_installed_skills() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("partials/intro.md")
+        .write_str("# Project Agents\n")
+        .unwrap();
+    temp.child("skills/review/SKILL.md")
+        .write_str("---\nname: review\ndescription: Reviews pull requests\n---\n")
+        .unwrap();
+
+    // The composite comes first but is composed after the skills install
+    temp.child("aps.yaml")
+        .write_str(
+            r#"entries:
+  - id: agents
+    kind: composite_agents_md
+    sources:
+      - type: filesystem
+        root: partials
+        path: intro.md
+      - type: generated
+        generator: skills_index
+    dest: ./AGENTS.md
+  - id: skills
+    kind: agent_skill
+    source:
+      type: filesystem
+      root: skills
+      symlink: false
+    dest: ./.claude/skills/
+"#,
+        )
+        .unwrap();
+
+    aps().arg("sync").current_dir(&temp).assert().success();
+    temp.child("AGENTS.md")
+        .assert(predicate::str::contains("# Project Agents"))
+        .assert(predicate::str::contains(
+            "- **review**: Reviews pull requests",
+        ));
+
+    // Adding a skill updates the index on the next sync
+    temp.child("skills/pdf/SKILL.md")
+        .write_str("---\nname: pdf\ndescription: Reads PDFs\n---\n")
+        .unwrap();
+    aps()
+        .args(["sync", "--yes"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    temp.child("AGENTS.md")
+        .assert(predicate::str::contains("- **pdf**: Reads PDFs"));
+}
+
+#[test]
+fn sync_detects_local_edits_before_overwriting_copied_dest() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source
+        .child("AGENTS.md")
+        .write_str("# Upstream v1\n")
+        .unwrap();
+
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "agents",
+            "agents_md",
+            source.path(),
+            "AGENTS.md",
+            "./AGENTS.md",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+
+    aps.ok(&["sync"]);
+
+    project
+        .child("AGENTS.md")
+        .write_str("# Upstream v1\n\nLocal tweak\n")
+        .unwrap();
+    source
+        .child("AGENTS.md")
+        .write_str("# Upstream v2\n")
+        .unwrap();
+
+    // Without --yes a non-interactive sync refuses to guess
+    aps.fails(&["sync"])
+        .stderr(predicate::str::contains("aps::install::locally_modified"));
+    project
+        .child("AGENTS.md")
+        .assert(predicate::str::contains("Local tweak"));
+
+    aps.ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains("local changes backed up"));
+    project
+        .child("AGENTS.md")
+        .assert(predicate::str::contains("# Upstream v2"));
+
+    let backups: Vec<_> = std::fs::read_dir(project.child(".aps-backups").path())
+        .unwrap()
+        .map(|e| std::fs::read_to_string(e.unwrap().path()).unwrap())
+        .collect();
+    assert_eq!(backups.len(), 1);
+    assert!(backups[0].contains("Local tweak"));
+}
+
+#[test]
+fn validate_warns_on_destinations_differing_only_in_case() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/Review",
+        )
+        .filesystem_entry(
+            "test-gen",
+            "agent_skill",
+            skills.path(),
+            "skills/test-gen",
+            ".claude/skills/review",
+        )
+        .write_to(temp.path());
+
+    // The test filesystem is case-sensitive, so this is a warning, not an error
+    ApsCmd::new(&temp)
+        .ok(&["validate"])
+        .stdout(predicate::str::contains("differ only in case"));
+}
+
+#[test]
+fn backup_store_strategy_records_restore_points() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+
+    let project = temp.child("project");
+    project
+        .child(".claude/skills/notes.md")
+        .write_str("# Hand-written notes\n")
+        .unwrap();
+    let manifest = ManifestBuilder::new()
+        .filesystem_entry(
+            "skills",
+            "agent_skill",
+            skills.path(),
+            "skills",
+            ".claude/skills",
+        )
+        .build();
+    project
+        .child("aps.yaml")
+        .write_str(&format!("backup_strategy: store\n{}", manifest))
+        .unwrap();
+    let aps = ApsCmd::new(&project);
+
+    aps.ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains(".aps-backups/points/"));
+    project
+        .child(".aps-backups/objects")
+        .assert(predicate::path::is_dir());
+
+    let output = aps.ok(&["backup", "list"]).get_output().stdout.clone();
+    let listing = String::from_utf8(output).unwrap();
+    let id = listing.split_whitespace().next().unwrap().to_string();
+    assert!(listing.contains(".claude/skills"));
+
+    aps.fails(&["backup", "restore", &id])
+        .stderr(predicate::str::contains("aps::backup::target_exists"));
+
+    aps.ok(&["backup", "restore", &id, "--to", "restored"]);
+    project
+        .child("restored/notes.md")
+        .assert("# Hand-written notes\n");
+
+    aps.fails(&["backup", "restore", "missing"])
+        .stderr(predicate::str::contains("aps::backup::not_found"));
+}
+
+#[test]
+fn sync_composite_fetches_http_sources_with_etag_cache() {
+    let (url, log) = serve_http("# Vendor guidance\n");
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("partials/intro.md")
+        .write_str("# Project rules\n")
+        .unwrap();
+    temp.child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: agents
+    kind: composite_agents_md
+    sources:
+      - type: filesystem
+        root: ./partials
+        path: intro.md
+      - type: http
+        url: {}
+    dest: ./AGENTS.md
+"#,
+            url
+        ))
+        .unwrap();
+
+    aps().arg("sync").current_dir(&temp).assert().success();
+    temp.child("AGENTS.md")
+        .assert(predicate::str::contains("# Project rules"))
+        .assert(predicate::str::contains("# Vendor guidance"));
+
+    let lockfile = std::fs::read_to_string(temp.child("aps.lock.yaml").path()).unwrap();
+    assert!(lockfile.contains(&url));
+    assert!(lockfile.contains("source_checksums:"));
+
+    // The cached copy is revalidated, not downloaded again
+    aps().arg("sync").current_dir(&temp).assert().success();
+    assert_eq!(*log.lock().unwrap(), vec![200, 304]);
+
+    aps()
+        .args(["sync", "--offline"])
+        .current_dir(&temp)
+        .assert()
+        .success();
+    assert_eq!(log.lock().unwrap().len(), 2);
+
+    // A fetched file can't fill a directory
+    temp.child("aps.yaml")
+        .write_str(&format!(
+            "entries:\n  - id: vendor\n    kind: agent_skill\n    source:\n      type: http\n      url: {}\n",
+            url
+        ))
+        .unwrap();
+    aps()
+        .arg("validate")
+        .current_dir(&temp)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("aps::manifest::http_source"));
+}
+
+#[test]
+fn sync_http_source_sends_headers_revalidates_by_date_and_locks_content() {
+    use std::sync::{Arc, Mutex};
+
+    // Served without an ETag, so revalidation relies on Last-Modified
+    let content = Arc::new(Mutex::new((
+        "# Guidance v1\n",
+        "Mon, 01 Jan 2024 00:00:00 GMT",
+    )));
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (served, current) = (log.clone(), content.clone());
+    let url = serve_http_with(move |headers| {
+        let (body, date) = *current.lock().unwrap();
+        let status = if !headers.iter().any(|h| h == "authorization: bearer s3cret") {
+            401
+        } else if headers.contains(&format!("if-modified-since: {}", date.to_ascii_lowercase())) {
+            304
+        } else {
+            200
+        };
+        served.lock().unwrap().push(status);
+        match status {
+            200 => format!(
+                "HTTP/1.1 200 OK\r\nLast-Modified: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                date,
+                body.len(),
+                body
+            ),
+            304 => "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string(),
+            _ => "HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                .to_string(),
+        }
+    });
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("aps.yaml")
+        .write_str(&format!(
+            r#"entries:
+  - id: agents
+    kind: composite_agents_md
+    sources:
+      - type: http
+        url: {}
+        headers:
+          Authorization: Bearer ${{APS_TEST_HTTP_TOKEN}}
+    dest: ./AGENTS.md
+"#,
+            url
+        ))
+        .unwrap();
+    let aps = ApsCmd::new(&temp);
+
+    aps.fails(&["sync"])
+        .stderr(predicate::str::contains("aps::source::http_header_env"));
+    assert!(log.lock().unwrap().is_empty());
+
+    let sync = |args: &[&str]| aps.cmd(args).env("APS_TEST_HTTP_TOKEN", "s3cret").assert();
+    sync(&["sync"]).success();
+    temp.child("AGENTS.md")
+        .assert(predicate::str::contains("# Guidance v1"));
+    // The token is never written to the lockfile
+    temp.child("aps.lock.yaml")
+        .assert(predicate::str::contains("s3cret").not());
+
+    sync(&["sync"]).success();
+    assert_eq!(*log.lock().unwrap(), vec![200, 304]);
+
+    // Changed content must be accepted with --upgrade
+    *content.lock().unwrap() = ("# Guidance v2\n", "Tue, 02 Jan 2024 00:00:00 GMT");
+    sync(&["sync"]).failure().stderr(predicate::str::contains(
+        "aps::source::http_checksum_mismatch",
+    ));
+    temp.child("AGENTS.md")
+        .assert(predicate::str::contains("# Guidance v1"));
+
+    sync(&["sync", "--upgrade", "--yes"]).success();
+    temp.child("AGENTS.md")
+        .assert(predicate::str::contains("# Guidance v2"));
+    sync(&["sync"]).success();
+}
+
+#[test]
+fn sync_standalone_http_source_installs_and_locks_checksum() {
+    use std::sync::{Arc, Mutex};
+
+    let content = Arc::new(Mutex::new("# Vendor v1\n"));
+    let current = content.clone();
+    let url = serve_http_with(move |_| {
+        let body = *current.lock().unwrap();
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    });
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("aps.yaml")
+        .write_str(&format!(
+            "entries:\n  - id: vendor\n    kind: agents_md\n    source:\n      type: http\n      url: {}\n    dest: ./AGENTS.md\n",
+            url
+        ))
+        .unwrap();
+    let aps = ApsCmd::new(&temp);
+
+    aps.ok(&["sync"]);
+    temp.child("AGENTS.md").assert("# Vendor v1\n");
+    let lockfile = std::fs::read_to_string(temp.child("aps.lock.yaml").path()).unwrap();
+    assert!(lockfile.contains("source_checksums:"), "{}", lockfile);
+    assert!(lockfile.contains(&url), "{}", lockfile);
+
+    *content.lock().unwrap() = "# Vendor v2\n";
+    aps.fails(&["sync"]).stderr(predicate::str::contains(
+        "aps::source::http_checksum_mismatch",
+    ));
+    temp.child("AGENTS.md").assert("# Vendor v1\n");
+
+    aps.ok(&["sync", "--upgrade", "--yes"]);
+    temp.child("AGENTS.md").assert("# Vendor v2\n");
+}
+
+#[test]
+fn manifest_rebuild_recovers_entries_from_lockfile() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source_repo = temp.child("source-repo");
+    create_git_repo(
+        source_repo.path(),
+        &[
+            ("skills/review/SKILL.md", "# Review\n"),
+            ("AGENTS.md", "# Agents\n"),
+        ],
+    );
+
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .git_entry(
+            "review",
+            "agent_skill",
+            source_repo.path(),
+            "skills/review",
+            ".claude/skills/review",
+        )
+        .git_entry(
+            "agents",
+            "agents_md",
+            source_repo.path(),
+            "AGENTS.md",
+            "AGENTS.md",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+    aps.ok(&["sync"]);
+    let locked = std::fs::read_to_string(project.child("aps.lock.yaml").path()).unwrap();
+
+    std::fs::remove_file(project.child("aps.yaml").path()).unwrap();
+    aps.ok(&["manifest", "rebuild", "--dry-run"])
+        .stdout(predicate::str::contains("#   - review.ref:"));
+    project.child("aps.yaml").assert(predicate::path::missing());
+
+    aps.fails(&["manifest", "rebuild"])
+        .stderr(predicate::str::contains("aps::install::requires_yes"));
+    aps.ok(&["manifest", "rebuild", "--yes"])
+        .stdout(predicate::str::contains("Rebuilt 2 entries"));
+    project
+        .child("aps.yaml")
+        .assert(predicate::str::contains("kind: agent_skill"))
+        .assert(predicate::str::contains("path: skills/review"));
+
+    // The rebuilt manifest reproduces the locked state (entry order and the
+    // checksum of the manifest text aside)
+    aps.ok(&["sync"]);
+    let relocked = std::fs::read_to_string(project.child("aps.lock.yaml").path()).unwrap();
+    let parse = |text: &str| {
+        let mut value = serde_yaml::from_str::<serde_yaml::Value>(text).unwrap();
+        value.as_mapping_mut().unwrap().remove("manifest_checksum");
+        value
+    };
+    assert_eq!(parse(&relocked), parse(&locked));
+
+    aps.fails(&["manifest", "rebuild", "--yes"])
+        .stderr(predicate::str::contains("aps::init::already_exists"));
+}
+
+#[test]
+fn sync_fans_out_into_each_target_with_its_own_lockfile() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    create_skills_dir(temp.child("shared").path());
+    temp.child("apps/api").create_dir_all().unwrap();
+    temp.child("apps/web").create_dir_all().unwrap();
+
+    let entries = ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            std::path::Path::new("shared"),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .raw_entry(
+            "  - id: test-gen\n    kind: agent_skill\n    source:\n      type: filesystem\n      root: shared\n      path: skills/test-gen\n      symlink: false\n    dest: .claude/skills/test-gen\n    targets:\n      - apps/web\n",
+        )
+        .build();
+    temp.child("aps.yaml")
+        .write_str(&format!("targets:\n  - apps/api\n  - apps/web\n{entries}"))
+        .unwrap();
+
+    ApsCmd::new(&temp)
+        .ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains("apps/api"))
+        .stdout(predicate::str::contains("apps/web"));
+
+    temp.child("apps/api/.claude/skills/refactor/SKILL.md")
+        .assert(predicate::path::exists());
+    temp.child("apps/api/.claude/skills/test-gen")
+        .assert(predicate::path::missing());
+    temp.child("apps/web/.claude/skills/refactor/SKILL.md")
+        .assert(predicate::path::exists());
+    temp.child("apps/web/.claude/skills/test-gen/SKILL.md")
+        .assert(predicate::path::exists());
+    temp.child("apps/api/aps.lock.yaml")
+        .assert(predicate::str::contains("refactor"))
+        .assert(predicate::str::contains("test-gen").not());
+    temp.child("apps/web/aps.lock.yaml")
+        .assert(predicate::str::contains("test-gen"));
+    temp.child("aps.lock.yaml")
+        .assert(predicate::path::missing());
+
+    // A missing target fails on its own; the others still sync
+    std::fs::remove_dir_all(temp.child("apps/api").path()).unwrap();
+    ApsCmd::new(&temp)
+        .fails(&["sync", "--yes"])
+        .stderr(predicate::str::contains("1 sync targets failed: apps/api"));
+}
+
+#[test]
+fn sync_takes_destination_locks_reported_by_doctor() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .write_to(project.path());
+    let locks = temp.child("locks");
+    let aps = ApsCmd::new(&project);
+
+    aps.cmd(&["sync", "--yes"])
+        .env("APS_LOCK_DIR", locks.path())
+        .assert()
+        .success();
+    let lock_files = std::fs::read_dir(locks.path()).unwrap().count();
+    assert!(lock_files > 0);
+    let recorded = std::fs::read_dir(locks.path())
+        .unwrap()
+        .map(|f| std::fs::read_to_string(f.unwrap().path()).unwrap())
+        .collect::<String>();
+    assert!(recorded.contains(".claude/skills/refactor\n"));
+
+    aps.cmd(&["doctor"])
+        .env("APS_LOCK_DIR", locks.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "✓ destination locks in {} ({} lock file(s))",
+            locks.path().display(),
+            lock_files
+        )))
+        .stdout(predicate::str::contains("being written").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn doctor_reports_git_version_and_rejects_old_git() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    let fake_git = |version: &str| {
+        let bin = temp.child(format!("git-{version}"));
+        bin.child("git")
+            .write_str(&format!("#!/bin/sh\necho 'git version {version}'\n"))
+            .unwrap();
+        std::fs::set_permissions(
+            bin.path().join("git"),
+            std::fs::Permissions::from_mode(0o755),
+        )
+        .unwrap();
+        bin.path().to_path_buf()
+    };
+
+    ApsCmd::new(&temp)
+        .ok(&["doctor"])
+        .stdout(predicate::str::contains("✓ git "));
+
+    // Older than a feature's release: the feature is reported with its fallback
+    let git_2_19 = fake_git("2.19.1");
+    ApsCmd::new(&temp)
+        .cmd(&["doctor"])
+        .env("PATH", &git_2_19)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("✓ partial clone"))
+        .stdout(predicate::str::contains(
+            "tree-less clone needs git >= 2.20.0",
+        ));
+
+    // Older than the minimum: doctor and git-backed commands fail with a targeted error
+    let git_2_7 = fake_git("2.7.4");
+    ApsCmd::new(&temp)
+        .cmd(&["doctor"])
+        .env("PATH", &git_2_7)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "aps needs git >= 2.8.0, found 2.7.4",
+        ));
+
+    ManifestBuilder::new()
+        .git_entry(
+            "skills",
+            "agent_skill",
+            &temp.path().join("repo"),
+            "skills",
+            ".claude/skills",
+        )
+        .write_to(temp.path());
+    ApsCmd::new(&temp)
+        .cmd(&["sync", "--yes"])
+        .env("PATH", &git_2_7)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("needs git >= 2.8.0"));
+}
+
+#[test]
+fn quiet_and_porcelain_output_for_scripts() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .filesystem_entry(
+            "test-gen",
+            "agent_skill",
+            skills.path(),
+            "skills/test-gen",
+            ".claude/skills/test-gen",
+        )
+        .write_to(temp.path());
+
+    ApsCmd::new(&temp).ok(&["list", "--porcelain"]).stdout(
+        "refactor\tpending\t./.claude/skills/refactor\n\
+         test-gen\tpending\t./.claude/skills/test-gen\n",
+    );
+
+    ApsCmd::new(&temp)
+        .ok(&["sync", "--yes", "--only", "refactor", "--porcelain"])
+        .stdout("refactor\tcreated\t./.claude/skills/refactor\n");
+
+    ApsCmd::new(&temp)
+        .ok(&["sync", "--yes", "-q"])
+        .stdout(predicate::str::is_empty());
+
+    ApsCmd::new(&temp).ok(&["status", "--porcelain"]).stdout(
+        "refactor\tcopied\t./.claude/skills/refactor\n\
+         test-gen\tcopied\t./.claude/skills/test-gen\n",
+    );
+
+    // Errors are still reported
+    ApsCmd::new(&temp)
+        .fails(&["sync", "--quiet", "--only", "missing"])
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("Entry not found: missing"));
+}
+
+#[test]
+fn sync_applies_transforms_to_copied_rules() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source
+        .child("rules/iterators.md")
+        .write_str("# Prefer iterators\n\nUse iterator adapters.\n")
+        .unwrap();
+    let manifest = |transforms: &str| {
+        format!(
+            "entries:\n  - id: rules\n    kind: cursor_rules\n    source:\n      type: filesystem\n      root: {}\n      path: rules\n    dest: .cursor/rules\n    transform:\n{}",
+            source.path().display(),
+            transforms
+        )
+    };
+    temp.child("aps.yaml")
+        .write_str(&manifest(
+            "      - add_mdc_frontmatter\n      - rename_extension: md->mdc\n",
+        ))
+        .unwrap();
+
+    ApsCmd::new(&temp).ok(&["sync", "--yes"]);
+    temp.child(".cursor/rules/iterators.mdc")
+        .assert(predicate::str::starts_with(
+            "---\ndescription: Prefer iterators\nglobs:\nalwaysApply: false\n---\n\n# Prefer iterators\n",
+        ));
+    temp.child(".cursor/rules/iterators.md")
+        .assert(predicate::path::missing());
+    // Filesystem sources are copied, and the upstream file is untouched
+    assert!(!temp
+        .child(".cursor/rules/iterators.mdc")
+        .path()
+        .is_symlink());
+    source
+        .child("rules/iterators.md")
+        .assert("# Prefer iterators\n\nUse iterator adapters.\n");
+    temp.child("aps.lock.yaml")
+        .assert(predicate::str::contains("- add_mdc_frontmatter"))
+        .assert(predicate::str::contains("upstream_checksum: sha256:"));
+
+    ApsCmd::new(&temp)
+        .ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains("[current]"));
+
+    // Changing the pipeline reinstalls with the new transforms
+    temp.child("aps.yaml")
+        .write_str(&manifest("      - rename_extension: md->mdc\n"))
+        .unwrap();
+    ApsCmd::new(&temp)
+        .ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains("[updated]"));
+    temp.child(".cursor/rules/iterators.mdc")
+        .assert("# Prefer iterators\n\nUse iterator adapters.\n");
+}
+
+#[test]
+fn sync_installs_entries_referenced_from_other_projects() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    // curated -> team -> app, each project reusing the entry of the one before
+    create_skills_dir(temp.child("curated").path());
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            std::path::Path::new("."),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .write_to(temp.child("curated").path());
+    let aps_entry = |project: &str| {
+        format!(
+            "  - id: refactor\n    kind: agent_skill\n    source:\n      type: aps\n      project: {}\n      entry: refactor\n    dest: .claude/skills/refactor\n",
+            project
+        )
+    };
+    temp.child("team").create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .raw_entry(&aps_entry("../curated"))
+        .write_to(temp.child("team").path());
+    temp.child("app").create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .raw_entry(&aps_entry("../team"))
+        .write_to(temp.child("app").path());
+
+    ApsCmd::new(temp.child("app").path()).ok(&["sync", "--yes"]);
+    temp.child("app/.claude/skills/refactor/SKILL.md")
+        .assert("# Refactor\n\nRefactors code automatically.\n");
+    temp.child("app/aps.lock.yaml")
+        .assert(predicate::str::contains("source: aps:../team#refactor"));
+
+    // Pointing the curated entry back at the team project forms a cycle
+    ManifestBuilder::new()
+        .raw_entry(&aps_entry("../team"))
+        .write_to(temp.child("curated").path());
+    ApsCmd::new(temp.child("app").path())
+        .fails(&["sync", "--yes"])
+        .stderr(predicate::str::contains("aps sources form a cycle"));
+
+    ManifestBuilder::new()
+        .raw_entry(&aps_entry("../team").replace("entry: refactor", "entry: missing"))
+        .write_to(temp.child("app").path());
+    ApsCmd::new(temp.child("app").path())
+        .fails(&["sync", "--yes"])
+        .stderr(predicate::str::contains("Entry 'missing' not found"));
+}
+
+#[test]
+fn sync_installs_entry_referenced_from_git_project() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let curated = temp.child("curated");
+    create_skills_repo(curated.path());
+    let manifest = ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            std::path::Path::new("."),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .build();
+    commit_files(curated.path(), &[("aps.yaml", &manifest)], "Add manifest");
+
+    temp.child("app").create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .raw_entry(&format!(
+            "  - id: refactor\n    kind: agent_skill\n    source:\n      type: aps\n      project: file://{}\n      ref: main\n      entry: refactor\n    dest: .claude/skills/refactor\n",
+            curated.path().display()
+        ))
+        .write_to(temp.child("app").path());
+
+    ApsCmd::new(temp.child("app").path()).ok(&["sync", "--yes"]);
+    temp.child("app/.claude/skills/refactor/SKILL.md")
+        .assert(predicate::str::contains("# Refactor"));
+    assert!(!temp
+        .child("app/.claude/skills/refactor")
+        .path()
+        .is_symlink());
+    temp.child("app/aps.lock.yaml")
+        .assert(predicate::str::contains("commit:"));
+}
+
+#[test]
+fn validate_flags_settings_that_have_no_effect() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("guides/AGENTS.md")
+        .write_str("# Guide\n")
+        .unwrap();
+    ManifestBuilder::new()
+        .raw_entry(&format!(
+            "  - id: guide\n    kind: agents_md\n    source:\n      type: filesystem\n      root: {}\n      path: AGENTS.md\n      shallow: true\n    include:\n      - intro\n    dest: AGENTS.md\n",
+            temp.child("guides").path().display()
+        ))
+        .write_to(temp.path());
+
+    ApsCmd::new(&temp)
+        .ok(&["validate"])
+        .stdout(predicate::str::contains(
+            "Entry 'guide': `include` has no effect on agents_md entries",
+        ))
+        .stdout(predicate::str::contains(
+            "`source.shallow` is not read by filesystem sources",
+        ))
+        .stdout(predicate::str::contains("valid with 2 warning(s)"));
+
+    ApsCmd::new(&temp)
+        .fails(&["validate", "--strict"])
+        .stderr(predicate::str::contains(
+            "aps::manifest::ineffective_setting",
+        ));
+}
+
+#[test]
+fn add_detects_asset_kind_from_local_source() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("shared/rules/style.mdc")
+        .write_str("# Style\n")
+        .unwrap();
+    temp.child("shared/rules/testing.mdc")
+        .write_str("# Testing\n")
+        .unwrap();
+    temp.child("shared/guide/AGENTS.md")
+        .write_str("# Guide\n")
+        .unwrap();
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+
+    ApsCmd::new(&project)
+        .ok(&["add", "../shared/rules", "--no-sync"])
+        .stdout(predicate::str::contains(
+            "Detected cursor_rules (found .mdc files)",
+        ));
+    ApsCmd::new(&project)
+        .ok(&["add", "../shared/guide/AGENTS.md", "--no-sync"])
+        .stdout(predicate::str::contains("Detected agents_md"));
+    // A single rule file installs into the rules folder through `include`
+    ApsCmd::new(&project).ok(&[
+        "add",
+        "../shared/rules/style.mdc",
+        "--id",
+        "style",
+        "--no-sync",
+    ]);
+    // --kind skips detection
+    ApsCmd::new(&project)
+        .ok(&[
+            "add",
+            "../shared/rules",
+            "--kind",
+            "cursor-rules",
+            "--id",
+            "explicit",
+            "--no-sync",
+        ])
+        .stdout(predicate::str::contains("Detected").not());
+
+    project
+        .child("aps.yaml")
+        .assert(predicate::str::contains(
+            "- id: rules\n  kind: cursor_rules\n  source:\n    type: filesystem\n    root: ../shared/rules\n",
+        ))
+        .assert(predicate::str::contains(
+            "- id: guide\n  kind: agents_md\n  source:\n    type: filesystem\n    root: ../shared/guide/AGENTS.md\n",
+        ))
+        .assert(predicate::str::contains("  dest: AGENTS.md\n"))
+        .assert(predicate::str::contains(
+            "  dest: .cursor/rules\n  include:\n  - style.mdc\n",
+        ));
+
+    ApsCmd::new(&project).ok(&["sync", "--only", "guide", "--only", "style", "--yes"]);
+    project.child("AGENTS.md").assert("# Guide\n");
+    project.child(".cursor/rules/style.mdc").assert("# Style\n");
+    project
+        .child(".cursor/rules/testing.mdc")
+        .assert(predicate::path::missing());
+}
+
+#[test]
+fn add_from_file_adds_every_line_and_syncs_once() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    create_skills_dir(temp.child("shared").path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    project
+        .child("urls.txt")
+        .write_str("# team skills\n../shared/skills/refactor\n\n../missing\n../shared\n")
+        .unwrap();
+
+    ApsCmd::new(&project)
+        .fails(&["add", "--from-file", "urls.txt"])
+        .stdout(predicate::str::contains(
+            "line 2: ../shared/skills/refactor -> refactor",
+        ))
+        .stdout(predicate::str::contains("line 4: ../missing"))
+        // Discovery skips the skill an earlier line already added
+        .stdout(predicate::str::contains("line 5: ../shared -> test-gen"))
+        .stdout(predicate::str::contains(
+            "Added 2 entries: refactor, test-gen",
+        ))
+        .stderr(predicate::str::contains("aps::add::incomplete"))
+        .stderr(predicate::str::contains("1 lines could not be added: 4"));
+
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .assert(predicate::path::exists());
+    project
+        .child(".claude/skills/test-gen/SKILL.md")
+        .assert(predicate::path::exists());
+
+    // Lines can also come from stdin
+    temp.child("more/docs/SKILL.md")
+        .write_str("# Docs\n")
+        .unwrap();
+    ApsCmd::new(&project)
+        .cmd(&["add", "--from-file", "-", "--no-sync"])
+        .write_stdin("../more/docs\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added 1 entries: docs"));
+    project
+        .child("aps.yaml")
+        .assert(predicate::str::contains("- id: docs\n"));
+}
+
+#[cfg(unix)]
+#[test]
+fn relative_symlinks_survive_moving_the_project() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let workspace = temp.child("workspace");
+    let shared = workspace.child("shared");
+    create_skills_dir(shared.path());
+    let project = workspace.child("project");
+    project.create_dir_all().unwrap();
+    let write_manifest = |style: &str| {
+        let entry = |id: &str, kind: &str, path: &str, dest: &str| {
+            format!(
+                "  - id: {}\n    kind: {}\n    source:\n      type: filesystem\n      root: ../shared\n      path: {}\n    dest: {}\n",
+                id, kind, path, dest
+            )
+        };
+        let manifest = ManifestBuilder::new()
+            .raw_entry(&entry("docs", "agents_md", "docs/README.md", "./AGENTS.md"))
+            .raw_entry(&entry(
+                "refactor",
+                "agent_skill",
+                "skills/refactor",
+                ".claude/skills/refactor",
+            ))
+            .build();
+        std::fs::write(
+            project.path().join("aps.yaml"),
+            format!("symlink_style: {}\n{}", style, manifest),
+        )
+        .unwrap();
+    };
+    write_manifest("relative");
+    ApsCmd::new(&project).ok(&["sync", "--yes"]);
+
+    assert_eq!(
+        std::fs::read_link(project.path().join("AGENTS.md")).unwrap(),
+        std::path::Path::new("../shared/docs/README.md")
+    );
+    assert_eq!(
+        std::fs::read_link(project.path().join(".claude/skills/refactor/SKILL.md")).unwrap(),
+        std::path::Path::new("../../../../shared/skills/refactor/SKILL.md")
+    );
+    project
+        .child("aps.lock.yaml")
+        .assert(predicate::str::contains(
+            "target_path: ../shared/docs/README.md",
+        ));
+
+    let moved = temp.child("moved");
+    std::fs::rename(workspace.path(), moved.path()).unwrap();
+    let project = moved.child("project");
+    project
+        .child("AGENTS.md")
+        .assert(predicate::str::contains("# Documentation"));
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .assert(predicate::str::contains("# Refactor"));
+    ApsCmd::new(&project)
+        .ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains("docs").and(predicate::str::contains("[current]")));
+
+    // Switching back to absolute links relinks the destination
+    std::fs::write(
+        project.path().join("aps.yaml"),
+        std::fs::read_to_string(project.path().join("aps.yaml"))
+            .unwrap()
+            .replace("symlink_style: relative", "symlink_style: absolute"),
+    )
+    .unwrap();
+    ApsCmd::new(&project).ok(&["sync", "--yes"]);
+    assert!(std::fs::read_link(project.path().join("AGENTS.md"))
+        .unwrap()
+        .is_absolute());
+}
+
+#[cfg(unix)]
+#[test]
+fn status_checks_destinations_and_repairs_broken_ones() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .filesystem_entry(
+            "test-gen",
+            "agent_skill",
+            skills.path(),
+            "skills/test-gen",
+            ".claude/skills/test-gen",
+        )
+        .raw_entry(&format!(
+            "  - id: docs\n    kind: agents_md\n    source:\n      type: filesystem\n      root: {}\n      path: docs/README.md\n    dest: ./AGENTS.md\n",
+            skills.path().display()
+        ))
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+    aps.ok(&["sync", "--yes"]);
+    aps.ok(&["status"])
+        .stdout(predicate::str::contains("On disk:      ok"))
+        .stdout(predicate::str::contains("--repair").not());
+
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .write_str("# Refactor\n\nLocal notes.\n")
+        .unwrap();
+    std::fs::remove_dir_all(project.child(".claude/skills/test-gen").path()).unwrap();
+    std::fs::remove_file(project.child("AGENTS.md").path()).unwrap();
+    std::os::unix::fs::symlink(
+        temp.path().join("gone.md"),
+        project.child("AGENTS.md").path(),
+    )
+    .unwrap();
+
+    aps.ok(&["status"])
+        .stdout(predicate::str::contains("On disk:      modified"))
+        .stdout(predicate::str::contains("On disk:      missing"))
+        .stdout(predicate::str::contains("On disk:      dangling-symlink"))
+        .stdout(predicate::str::contains(
+            "3 entries have a broken destination. Run `aps status --repair` to reinstall them.",
+        ));
+    aps.ok(&["status", "--porcelain"]).stdout(
+        "docs\tdangling-symlink\t./AGENTS.md\n\
+         refactor\tmodified\t./.claude/skills/refactor\n\
+         test-gen\tmissing\t./.claude/skills/test-gen\n",
+    );
+
+    aps.ok(&["status", "--repair"])
+        .stdout(predicate::str::contains(
+            "Backed up .claude/skills/refactor to",
+        ))
+        .stdout(predicate::str::contains("Repairing test-gen (missing)"));
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .assert("# Refactor\n\nRefactors code automatically.\n");
+    project
+        .child(".claude/skills/test-gen/SKILL.md")
+        .assert(predicate::path::exists());
+    project.child("AGENTS.md").assert("# Documentation\n");
+    aps.ok(&["status", "--repair"])
+        .stdout(predicate::str::contains("All destinations are intact."));
+}
+
+#[test]
+fn pin_from_dest_accepts_local_edits() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .raw_entry(&format!(
+            "  - id: linked\n    kind: agent_skill\n    source:\n      type: filesystem\n      root: {}\n      path: skills/test-gen\n    dest: ./.claude/skills/test-gen\n",
+            skills.path().display()
+        ))
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+    aps.ok(&["sync", "--yes"]);
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .write_str("# Refactor\n\nHotfixed locally.\n")
+        .unwrap();
+    aps.ok(&["status", "--porcelain"])
+        .stdout(predicate::str::contains("refactor\tmodified"));
+
+    aps.fails(&["pin", "linked", "--from-dest"])
+        .stderr(predicate::str::contains("aps::pin::unsupported"))
+        .stderr(predicate::str::contains("symlink"));
+    aps.fails(&["pin", "missing", "--from-dest"])
+        .stderr(predicate::str::contains("Entry not found: missing"));
+
+    aps.ok(&["pin", "refactor", "--from-dest", "--backup"])
+        .stdout(predicate::str::contains(
+            "Stored installed content of refactor",
+        ))
+        .stdout(predicate::str::contains(
+            "Pinned refactor to its installed content",
+        ));
+    aps.ok(&["status", "--porcelain"])
+        .stdout(predicate::str::contains("refactor\tcopied"));
+    aps.ok(&["backup", "list"])
+        .stdout(predicate::str::contains(".claude/skills/refactor"));
+    aps.ok(&["pin", "refactor", "--from-dest"])
+        .stdout(predicate::str::contains("already matches"));
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .assert("# Refactor\n\nHotfixed locally.\n");
+}
+
+#[test]
+fn sync_override_installs_another_source_for_one_run() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let fork = temp.child("fork");
+    create_skills_dir(fork.path());
+    fork.child("skills/refactor/SKILL.md")
+        .write_str("# Refactor\n\nForked.\n")
+        .unwrap();
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+    aps.ok(&["sync", "--yes"]);
+    let manifest = std::fs::read_to_string(project.child("aps.yaml").path()).unwrap();
+    let lockfile = std::fs::read_to_string(project.child("aps.lock.yaml").path()).unwrap();
+
+    let override_arg = format!("refactor={}", fork.path().display());
+    aps.ok(&["sync", "--yes", "--override", &override_arg])
+        .stdout(predicate::str::contains(
+            "Overriding sources for this run: refactor",
+        ))
+        .stdout(predicate::str::contains("overridden"));
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .assert("# Refactor\n\nForked.\n");
+    project.child("aps.yaml").assert(manifest.as_str());
+    project
+        .child("aps.lock.yaml")
+        .assert(predicate::str::contains("last_status: overridden"))
+        .assert(predicate::str::contains(fork.path().to_str().unwrap()).not());
+    aps.ok(&["status", "--porcelain"])
+        .stdout(predicate::str::contains("refactor\toverridden"));
+
+    // A failed sync leaves the overridden content marked for reinstall
+    aps.fails(&["sync"])
+        .stderr(predicate::str::contains("aps::install::requires_yes"));
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .assert("# Refactor\n\nForked.\n");
+
+    // The next plain sync goes back to the locked source
+    aps.ok(&["sync", "--yes"]);
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .assert("# Refactor\n\nRefactors code automatically.\n");
+    project.child("aps.lock.yaml").assert(lockfile.as_str());
+
+    aps.fails(&["sync", "--override", "missing=/tmp"])
+        .stderr(predicate::str::contains("Entry not found: missing"));
+    aps.fails(&["sync", "--override", "refactor"])
+        .stderr(predicate::str::contains("aps::sync::invalid_override"));
+}
+
+#[test]
+fn status_reports_manifest_edits_since_last_sync() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let refactor = |dest: &str| {
+        ManifestBuilder::new().filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            dest,
+        )
+    };
+    refactor(".claude/skills/refactor").write_to(temp.path());
+
+    ApsCmd::new(&temp).ok(&["sync", "--yes"]);
+    temp.child("aps.lock.yaml")
+        .assert(predicate::str::contains("manifest_checksum: sha256:"))
+        .assert(predicate::str::contains("entry_checksum: sha256:"));
+    ApsCmd::new(&temp)
+        .ok(&["status"])
+        .stdout(predicate::str::contains("Manifest changed").not());
+
+    refactor(".claude/skills/refactor-code")
+        .filesystem_entry(
+            "test-gen",
+            "agent_skill",
+            skills.path(),
+            "skills/test-gen",
+            ".claude/skills/test-gen",
+        )
+        .write_to(temp.path());
+    ApsCmd::new(&temp)
+        .ok(&["status"])
+        .stdout(predicate::str::contains(
+            "Manifest changed since last sync:\n  + test-gen (added)\n  ~ refactor (modified)\n",
+        ));
+
+    ApsCmd::new(&temp).ok(&["sync", "--yes"]);
+    ApsCmd::new(&temp)
+        .ok(&["status"])
+        .stdout(predicate::str::contains("Manifest changed").not());
+}
+
+#[test]
+fn sync_checks_for_uncommitted_changes_when_enabled() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    let manifest = ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .build();
+    create_git_repo(
+        project.path(),
+        &[(
+            "aps.yaml",
+            &format!("check_uncommitted: true\n{}", manifest),
+        )],
+    );
+    let aps = ApsCmd::new(&project);
+    aps.ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains("uncommitted").not());
+    commit_files(project.path(), &[], "Install skills");
+
+    project
+        .child(".claude/skills/refactor/SKILL.md")
+        .write_str("# Refactor\n\nLocal notes.\n")
+        .unwrap();
+    aps.ok(&["sync", "--yes"]).stdout(predicate::str::contains(
+        "sync may overwrite uncommitted changes in:\n  .claude/skills/refactor/SKILL.md",
+    ));
+    aps.fails(&["sync", "--yes", "--strict"])
+        .stderr(predicate::str::contains(
+            "aps::install::uncommitted_changes",
+        ));
+    aps.ok(&["sync", "--yes", "--strict", "--force"]);
+}
+
+#[test]
+fn orphans_are_remembered_until_cleaned() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    let write_manifest = |path: &str, dest: &str| {
+        ManifestBuilder::new()
+            .filesystem_entry("skill", "agent_skill", skills.path(), path, dest)
+            .write_to(project.path());
+    };
+    let aps = ApsCmd::new(&project);
+
+    write_manifest("skills/refactor", ".claude/skills/old");
+    aps.ok(&["sync", "--yes"]);
+
+    // Moving the dest while switching sources, without confirming the cleanup
+    write_manifest("skills/test-gen", ".claude/skills/new");
+    aps.ok(&["sync"])
+        .stdout(predicate::str::contains("Cannot delete orphaned paths"));
+    project
+        .child("aps.lock.yaml")
+        .assert(predicate::str::contains(
+            "previous_dests:\n    - .claude/skills/old",
+        ));
+
+    // The leftover is still found once the lockfile points at the new dest
+    aps.ok(&["orphans", "list"])
+        .stdout(predicate::str::contains(
+            "skill  ./.claude/skills/old (now ./.claude/skills/new)",
+        ));
+    aps.ok(&["sync"])
+        .stdout(predicate::str::contains("Detected 1 orphaned path(s)"));
+
+    aps.ok(&["orphans", "clean", "--dry-run"]);
+    project
+        .child(".claude/skills/old")
+        .assert(predicate::path::exists());
+    aps.ok(&["orphans", "clean", "--yes"]);
+    project
+        .child(".claude/skills/old")
+        .assert(predicate::path::missing());
+    project
+        .child("aps.lock.yaml")
+        .assert(predicate::str::contains("previous_dests").not());
+    aps.ok(&["orphans", "list"])
+        .stdout(predicate::str::contains("No orphaned paths."));
+}
+
+#[test]
+fn orphans_clean_prunes_empty_parent_directories() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    project
+        .child(".agents/README.md")
+        .write_str("# Mine\n")
+        .unwrap();
+    let write_manifest = |dest: &str| {
+        ManifestBuilder::new()
+            .filesystem_entry(
+                "skill",
+                "agent_skill",
+                skills.path(),
+                "skills/refactor",
+                dest,
+            )
+            .write_to(project.path());
+    };
+    let aps = ApsCmd::new(&project);
+
+    write_manifest(".agents/skills/team/refactor");
+    aps.ok(&["sync", "--yes"]);
+    write_manifest(".claude/skills/refactor");
+    aps.ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains(
+            "Pruned empty directory: \".agents/skills/team\"",
+        ))
+        .stdout(predicate::str::contains(
+            "Pruned empty directory: \".agents/skills\"",
+        ));
+    project
+        .child(".agents/skills")
+        .assert(predicate::path::missing());
+    // Directories with other content stay
+    project
+        .child(".agents/README.md")
+        .assert(predicate::path::exists());
+}
+
+#[test]
+fn scoped_yes_only_answers_named_prompts() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    project
+        .child(".claude/skills/old/SKILL.md")
+        .write_str("# Local\n")
+        .unwrap();
+    let write_manifest = |dest: &str| {
+        ManifestBuilder::new()
+            .filesystem_entry(
+                "skill",
+                "agent_skill",
+                skills.path(),
+                "skills/refactor",
+                dest,
+            )
+            .write_to(project.path());
+    };
+    let aps = ApsCmd::new(&project);
+
+    write_manifest(".claude/skills/old");
+    aps.fails(&["sync", "--yes=orphans"])
+        .stderr(predicate::str::contains("aps::install::requires_yes"));
+    aps.ok(&["sync", "--yes=overwrite"])
+        .stdout(predicate::str::contains(
+            "Auto-approved by --yes: overwrite (1)",
+        ));
+
+    write_manifest(".claude/skills/new");
+    aps.ok(&["sync", "--yes=overwrite"])
+        .stdout(predicate::str::contains("Cannot delete orphaned paths"))
+        .stdout(predicate::str::contains("Auto-approved").not());
+    project
+        .child(".claude/skills/old")
+        .assert(predicate::path::exists());
+    aps.ok(&["sync", "--yes=orphans"])
+        .stdout(predicate::str::contains(
+            "Auto-approved by --yes: orphans (1)",
+        ));
+    project
+        .child(".claude/skills/old")
+        .assert(predicate::path::missing());
+
+    aps.fails(&["sync", "--yes=everything"])
+        .stderr(predicate::str::contains("unknown prompt 'everything'"));
+}
+
+#[test]
+fn sort_orders_entries_by_kind_then_id() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "test-gen",
+            "agent_skill",
+            skills.path(),
+            "skills/test-gen",
+            ".claude/skills/test-gen",
+        )
+        .filesystem_entry(
+            "docs",
+            "agents_md",
+            skills.path(),
+            "docs/README.md",
+            "AGENTS.md",
+        )
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+    let ids = || {
+        std::fs::read_to_string(project.child("aps.yaml").path())
+            .unwrap()
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("- id: ").map(str::to_string))
+            .collect::<Vec<_>>()
+    };
+
+    aps.fails(&["sort", "--check"])
+        .stderr(predicate::str::contains("aps::manifest::unsorted"));
+    assert_eq!(ids(), ["test-gen", "docs", "refactor"]);
+    aps.ok(&["sort"])
+        .stdout(predicate::str::contains("Sorted 3 entries"));
+    assert_eq!(ids(), ["refactor", "test-gen", "docs"]);
+    aps.ok(&["sort", "--check"])
+        .stdout(predicate::str::contains("already sorted"));
+}
+
+#[test]
+fn init_sort_keeps_added_entries_sorted() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    let aps = ApsCmd::new(&project);
+
+    aps.ok(&["init", "--sort"]);
+    project
+        .child("aps.yaml")
+        .assert(predicate::str::contains("sort: true"));
+    for skill in ["test-gen", "refactor"] {
+        let path = skills.child("skills").child(skill);
+        aps.ok(&["add", path.path().to_str().unwrap(), "--no-sync"]);
+    }
+    let manifest = std::fs::read_to_string(project.child("aps.yaml").path()).unwrap();
+    let refactor = manifest.find("id: refactor").unwrap();
+    let test_gen = manifest.find("id: test-gen").unwrap();
+    assert!(refactor < test_gen);
+}
+
+#[test]
+fn grep_searches_installed_content_by_entry() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    skills
+        .child("skills/refactor/SKILL.md")
+        .write_str("---\nname: refactor\n---\n# Refactor\nAlways run the tests first\n")
+        .unwrap();
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .filesystem_entry(
+            "docs",
+            "agent_skill",
+            skills.path(),
+            "docs",
+            ".claude/skills/docs",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+    aps.ok(&["sync", "--yes"]);
+
+    aps.ok(&["grep", "-i", "RUN THE TESTS"])
+        .stdout(predicate::str::contains("refactor\n"))
+        .stdout(predicate::str::contains(
+            "./.claude/skills/refactor/SKILL.md",
+        ))
+        .stdout(predicate::str::contains("5: Always run the tests first"))
+        .stdout(predicate::str::contains("docs").not());
+    aps.ok(&["grep", "--filter", "id=docs", "tests"])
+        .stdout(predicate::str::contains("No matches."));
+    aps.fails(&["grep", "("])
+        .stderr(predicate::str::contains("aps::grep::invalid_pattern"));
+}
+
+#[test]
+fn who_uses_lists_entries_across_workspace_manifests() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    git(temp.path()).arg("init").output().unwrap();
+    let shared = temp.child("shared");
+    create_skills_dir(shared.path());
+    let web = temp.child("packages/web");
+    web.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .raw_entry(
+            "  - id: review\n    kind: agent_skill\n    source:\n      type: git\n      repo: git@github.com:org/skills.git\n      path: skills/review\n",
+        )
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            shared.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .write_to(web.path());
+    let api = temp.child("packages/api");
+    api.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .raw_entry(
+            "  - id: review\n    kind: agent_skill\n    source:\n      type: git\n      repo: https://github.com/org/skills\n",
+        )
+        .write_to(api.path());
+    let aps = ApsCmd::new(&web);
+
+    aps.ok(&["who-uses", "https://github.com/org/skills.git"])
+        .stdout(predicate::str::contains("packages/api/aps.yaml"))
+        .stdout(predicate::str::contains("packages/web/aps.yaml"))
+        .stdout(predicate::str::contains("2 entries in 2 of 2 manifest(s)"))
+        .stdout(predicate::str::contains("refactor").not());
+    aps.ok(&["who-uses", "--porcelain", "../../shared/skills"])
+        .stdout(predicate::str::contains("aps.yaml\trefactor\t"))
+        .stdout(predicate::str::contains("review").not());
+    aps.ok(&["who-uses", "github.com/org/skills", "."])
+        .stdout(predicate::str::contains("1 entry in 1 of 1 manifest(s)"));
+    aps.ok(&["who-uses", "gitlab.com/org"])
+        .stdout(predicate::str::contains("No entries use"));
+    aps.fails(&["who-uses", "github.com/org/[skills"])
+        .stderr(predicate::str::contains("aps::who_uses::invalid_pattern"));
+}
+
+#[test]
+fn index_records_synced_projects_when_enabled() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let shared = temp.child("shared");
+    create_skills_dir(shared.path());
+    let config = temp.child("config.yaml");
+    config.write_str("index: true\n").unwrap();
+    let index = temp.child("data/index.yaml");
+    for name in ["api", "web"] {
+        let project = temp.child(name);
+        project.create_dir_all().unwrap();
+        ManifestBuilder::new()
+            .filesystem_entry(
+                &format!("{}-refactor", name),
+                "agent_skill",
+                shared.path(),
+                "skills/refactor",
+                ".claude/skills/refactor",
+            )
+            .write_to(project.path());
+    }
+    let run = |dir: &str, args: &[&str]| {
+        ApsCmd::new(temp.child(dir))
+            .cmd(args)
+            .env("APS_CONFIG", config.path())
+            .env("APS_INDEX", index.path())
+            .assert()
+            .success()
+    };
+
+    // Syncs only record projects once the user config opts in
+    ApsCmd::new(temp.child("api"))
+        .cmd(&["sync"])
+        .env("APS_INDEX", index.path())
+        .assert()
+        .success();
+    index.assert(predicate::path::missing());
+
+    run("api", &["sync"]);
+    run("web", &["sync"]);
+    run("api", &["index", "search", "../shared/skills/refactor"])
+        .stdout(predicate::str::contains("api-refactor"))
+        .stdout(predicate::str::contains("web-refactor"))
+        .stdout(predicate::str::contains("2 entries in 2 of 2 project(s)"));
+    run("api", &["index", "search", "--porcelain", "web-refactor"])
+        .stdout(predicate::str::contains("\tweb-refactor\t"))
+        .stdout(predicate::str::contains("api-refactor").not());
+    run("api", &["index", "list"])
+        .stdout(predicate::str::contains("Shared across projects:"))
+        .stdout(predicate::str::contains("2 projects"));
+    run("api", &["index", "search", "github.com/org/other"])
+        .stdout(predicate::str::contains("No indexed projects use"));
+}
+
+#[test]
+fn explain_suggests_similar_source_paths() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactr",
+            ".claude/skills/refactor",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+
+    aps.fails(&["sync"])
+        .stderr(predicate::str::contains("aps::source::path_not_found"))
+        .stderr(predicate::str::contains("--explain"));
+    aps.fails(&["sync", "--explain"])
+        .stderr(predicate::str::contains("skills/refactor"));
+    aps.ok(&["validate", "--explain"])
+        .stdout(predicate::str::contains(
+            "Similar paths in the source: skills/refactor",
+        ));
+}
+
+#[test]
+fn orphan_cleanup_leaves_paths_owned_by_other_manifests() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    let member = project.child("packages/web");
+    member.create_dir_all().unwrap();
+    let write_root = |dest: &str| {
+        ManifestBuilder::new()
+            .filesystem_entry(
+                "shared",
+                "agent_skill",
+                skills.path(),
+                "skills/refactor",
+                dest,
+            )
+            .write_to(project.path());
+    };
+
+    write_root("packages/web/.claude/skills/refactor");
+    ApsCmd::new(&project).ok(&["sync", "--yes"]);
+
+    // The member manifest takes over the path the root manifest installed to
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .write_to(member.path());
+    ApsCmd::new(&member).ok(&["sync", "--yes"]);
+    member
+        .child("aps.lock.yaml")
+        .assert(predicate::str::contains("manifest: aps.yaml"));
+
+    // Moving the root entry must not delete the member's install
+    write_root(".claude/skills/refactor");
+    ApsCmd::new(&project)
+        .ok(&["orphans", "list"])
+        .stdout(predicate::str::contains("it is owned by entry refactor of"))
+        .stdout(predicate::str::contains("No orphaned paths."));
+    ApsCmd::new(&project).ok(&["sync", "--yes"]);
+    member
+        .child(".claude/skills/refactor/SKILL.md")
+        .assert(predicate::path::exists());
+}
+
+#[test]
+fn suppressed_warning_codes_are_not_reported() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("skills/draft/notes.md")
+        .write_str("# Draft\n")
+        .unwrap();
+    let entry = |suppress: &str| {
+        format!(
+            "  - id: skills\n    kind: cursor_skills_root\n    source:\n      type: filesystem\n      root: {}\n      symlink: false\n    dest: .cursor/skills\n{}",
+            temp.child("skills").path().display(),
+            suppress
+        )
+    };
+
+    ManifestBuilder::new()
+        .raw_entry(&entry(""))
+        .write_to(temp.path());
+    ApsCmd::new(&temp)
+        .ok(&["validate"])
+        .stdout(predicate::str::contains(
+            "Skill 'draft' in entry 'skills' is missing SKILL.md [W001]",
+        ));
+    ApsCmd::new(&temp)
+        .ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains("missing SKILL.md [W001]"));
+
+    ManifestBuilder::new()
+        .raw_entry(&entry("    suppress_warnings: [W001]\n"))
+        .write_to(temp.path());
+    ApsCmd::new(&temp)
+        .ok(&["validate", "--strict"])
+        .stdout(predicate::str::contains("W001").not())
+        .stdout(predicate::str::contains("validated successfully"));
+    ApsCmd::new(&temp)
+        .ok(&["sync", "--yes", "--strict"])
+        .stdout(predicate::str::contains("W001").not());
+}
+
+#[test]
+fn list_outdated_shows_entries_behind_upstream() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let rules = temp.child("rules");
+    let guide = temp.child("guide");
+    create_git_repo(rules.path(), &[("rules/style.mdc", "# Style\n")]);
+    create_git_repo_with_agents_md(guide.path(), "# Guide\n");
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .git_entry(
+            "rules",
+            "cursor_rules",
+            rules.path(),
+            "rules",
+            ".cursor/rules",
+        )
+        .git_entry("guide", "agents_md", guide.path(), "AGENTS.md", "AGENTS.md")
+        .write_to(project.path());
+    ApsCmd::new(&project).ok(&["sync", "--yes"]);
+
+    ApsCmd::new(&project)
+        .ok(&["list", "--outdated"])
+        .stdout(predicate::str::contains("All git entries are up to date"));
+
+    commit_files(rules.path(), &[("rules/a.mdc", "# A\n")], "Add a");
+    commit_files(rules.path(), &[("rules/b.mdc", "# B\n")], "Add b");
+    update_agents_md_in_repo(guide.path(), "# Guide v2\n");
+
+    let output = ApsCmd::new(&project)
+        .ok(&["list", "--outdated"])
+        .stdout(predicate::str::contains("rules 2 commits behind"))
+        .stdout(predicate::str::contains("guide 1 commit behind"))
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    assert!(stdout.find("rules").unwrap() < stdout.find("guide").unwrap());
+
+    // The remote heads are cached for offline use
+    ApsCmd::new(&project)
+        .ok(&["list", "--outdated", "--offline", "--porcelain"])
+        .stdout(predicate::str::contains("rules\toutdated\t"))
+        .stdout(predicate::str::contains("guide\toutdated\t./AGENTS.md"));
+}
+
+#[test]
+fn outdated_and_update_move_only_the_named_pins() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let rules = temp.child("rules");
+    let guide = temp.child("guide");
+    create_git_repo(rules.path(), &[("rules/style.mdc", "# Style\n")]);
+    create_git_repo_with_agents_md(guide.path(), "# Guide\n");
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .git_entry(
+            "rules",
+            "cursor_rules",
+            rules.path(),
+            "rules",
+            ".cursor/rules",
+        )
+        .git_entry("guide", "agents_md", guide.path(), "AGENTS.md", "AGENTS.md")
+        .raw_entry(&format!(
+            "  - id: combined\n    kind: composite_agents_md\n    sources:\n      - type: git\n        repo: {}\n        ref: main\n        path: AGENTS.md\n    dest: COMBINED.md\n",
+            guide.path().display()
+        ))
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+    aps.ok(&["sync", "--yes"]);
+
+    aps.ok(&["outdated"])
+        .stdout(predicate::str::contains("AVAILABLE"))
+        .stdout(predicate::str::contains("Checking 3 git entries"))
+        .stdout(predicate::str::contains("combined (guide)"))
+        .stdout(predicate::str::contains("up to date"))
+        .stdout(predicate::str::contains("All git entries are up to date"));
+
+    commit_files(rules.path(), &[("rules/a.mdc", "# A\n")], "Add a");
+    update_agents_md_in_repo(guide.path(), "# Guide v2\n");
+    aps.ok(&["outdated"])
+        .stdout(predicate::str::contains("1 commit behind"))
+        .stdout(predicate::str::contains("3 entries are behind upstream"));
+
+    let lockfile = project.child("aps.lock.yaml");
+    let before = std::fs::read_to_string(lockfile.path()).unwrap();
+    aps.ok(&["update", "rules", "--dry-run"])
+        .stdout(predicate::str::contains("Would update 1 pin"));
+    assert_eq!(std::fs::read_to_string(lockfile.path()).unwrap(), before);
+
+    aps.fails(&["update", "missing"])
+        .stderr(predicate::str::contains("aps::manifest::entry_not_found"));
+
+    // Only the named pin moves, and nothing is installed
+    aps.ok(&["update", "rules"])
+        .stdout(predicate::str::contains("Updated 1 pin"));
+    aps.ok(&["outdated", "--offline"])
+        .stdout(predicate::str::contains("2 entries are behind upstream"));
+    project
+        .child(".cursor/rules/a.mdc")
+        .assert(predicate::path::missing());
+    project.child("AGENTS.md").assert("# Guide\n");
+
+    aps.ok(&["sync", "--yes"]);
+    project
+        .child(".cursor/rules/a.mdc")
+        .assert(predicate::path::exists());
+    project.child("AGENTS.md").assert("# Guide\n");
+
+    // A composite's git sources move with it
+    aps.ok(&["update", "combined"])
+        .stdout(predicate::str::contains("Updated 1 pin"));
+    aps.ok(&["outdated", "--offline"])
+        .stdout(predicate::str::contains("1 entry is behind upstream"));
+    aps.ok(&["sync", "--yes"]);
+    project
+        .child("COMBINED.md")
+        .assert(predicate::str::contains("# Guide v2"));
+    project.child("AGENTS.md").assert("# Guide\n");
+}
+
+#[test]
+fn update_pin_survives_a_failed_sync() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let guide = temp.child("guide");
+    create_git_repo_with_agents_md(guide.path(), "# Guide\n");
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .git_entry("guide", "agents_md", guide.path(), "AGENTS.md", "AGENTS.md")
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+    aps.ok(&["sync", "--yes"]);
+
+    update_agents_md_in_repo(guide.path(), "# Guide v2\n");
+    aps.ok(&["update", "guide"]);
+    // Overwriting needs --yes without a terminal
+    aps.fails(&["sync"])
+        .stderr(predicate::str::contains("aps::install::requires_yes"));
+    project.child("AGENTS.md").assert("# Guide\n");
+    aps.ok(&["outdated", "--offline"])
+        .stdout(predicate::str::contains("sync pending"));
+
+    aps.ok(&["sync", "--yes"]);
+    project.child("AGENTS.md").assert("# Guide v2\n");
+    project
+        .child("aps.lock.yaml")
+        .assert(predicate::str::contains("dest_stale").not());
+}
+
+#[test]
+fn ci_profile_copies_symlinked_sources_without_prompting() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("shared/AGENTS.md")
+        .write_str("# Shared\n")
+        .unwrap();
+    let project = temp.child("project");
+    project.child("AGENTS.md").write_str("# Local\n").unwrap();
+    let manifest = ManifestBuilder::new().raw_entry(&format!(
+        "  - id: guide\n    kind: agents_md\n    source:\n      type: filesystem\n      root: {}\n      path: AGENTS.md\n      symlink: true\n    dest: AGENTS.md\n",
+        temp.child("shared").path().display()
+    ));
+    manifest.write_to(project.path());
+    let dest = project.child("AGENTS.md");
+
+    // Without the profile, overwriting local content needs --yes
+    ApsCmd::new(&project)
+        .fails(&["sync"])
+        .stderr(predicate::str::contains("aps::install::requires_yes"));
+
+    ApsCmd::new(&project)
+        .cmd(&["sync"])
+        .env("CI", "true")
+        .assert()
+        .success();
+    assert!(!dest.path().is_symlink());
+    dest.assert("# Shared\n");
+
+    // The manifest can keep symlinks, and APS_CI_PROFILE=0 turns the profile off
+    std::fs::write(
+        project.child("aps.yaml").path(),
+        format!("ci:\n  symlink: true\n{}", manifest.build()),
+    )
+    .unwrap();
+    ApsCmd::new(&project)
+        .cmd(&["sync"])
+        .env("CI", "true")
+        .assert()
+        .success();
+    assert!(dest.path().is_symlink());
+
+    std::fs::write(project.child("aps.yaml").path(), manifest.build()).unwrap();
+    ApsCmd::new(&project)
+        .cmd(&["sync", "--yes"])
+        .env("CI", "true")
+        .env("APS_CI_PROFILE", "0")
+        .assert()
+        .success();
+    assert!(dest.path().is_symlink());
+}
+
+#[test]
+fn backups_can_move_or_be_turned_off_per_entry() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let shared = temp.child("shared");
+    shared.child("AGENTS.md").write_str("# Shared\n").unwrap();
+    shared.child("CLAUDE.md").write_str("# Claude\n").unwrap();
+
+    let project = temp.child("project");
+    project.child("AGENTS.md").write_str("# Local\n").unwrap();
+    project.child("CLAUDE.md").write_str("# Local\n").unwrap();
+    let manifest = ManifestBuilder::new()
+        .filesystem_entry("agents", "agents_md", shared.path(), "AGENTS.md", "AGENTS.md")
+        .raw_entry(&format!(
+            "  - id: claude\n    kind: agents_md\n    backup: false\n    source:\n      type: filesystem\n      root: {}\n      path: CLAUDE.md\n      symlink: false\n    dest: CLAUDE.md\n",
+            shared.path().display()
+        ))
+        .build();
+    project
+        .child("aps.yaml")
+        .write_str(&format!("backup_dir: .backups\n{}", manifest))
+        .unwrap();
+
+    ApsCmd::new(&project)
+        .ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains(
+            "CLAUDE.md\" without a backup (backup: false)",
+        ));
+
+    project.child("CLAUDE.md").assert("# Claude\n");
+    project
+        .child(".aps-backups")
+        .assert(predicate::path::missing());
+    let backups: Vec<_> = std::fs::read_dir(project.child(".backups").path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(backups.len(), 1);
+    assert!(backups[0].starts_with("AGENTS.md-"));
+}
+
+#[test]
+fn lockfile_profiles_keep_separate_pins() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source_repo = temp.child("source-repo");
+    create_git_repo_with_agents_md(source_repo.path(), "# Version 1\n");
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    let entries = ManifestBuilder::new()
+        .git_entry(
+            "agents",
+            "agents_md",
+            source_repo.path(),
+            "AGENTS.md",
+            "./AGENTS.md",
+        )
+        .build();
+    project
+        .child("aps.yaml")
+        .write_str(&format!("lockfiles:\n  ci: aps.lock.ci.yaml\n{}", entries))
+        .unwrap();
+    let aps = ApsCmd::new(&project);
+
+    aps.ok(&["sync", "--lockfile", "ci"]);
+    project
+        .child("aps.lock.ci.yaml")
+        .assert(predicate::str::contains("agents:"));
+    project
+        .child("aps.lock.yaml")
+        .assert(predicate::path::missing());
+    aps.ok(&["status", "--lockfile", "ci"]);
+    aps.fails(&["status"]);
+
+    // The default lockfile pins a newer commit; the ci one keeps its own
+    update_agents_md_in_repo(source_repo.path(), "# Version 2\n");
+    aps.ok(&["sync", "--yes"]);
+    let commit = |name: &str| {
+        std::fs::read_to_string(project.child(name).path())
+            .unwrap()
+            .lines()
+            .find(|l| l.trim_start().starts_with("commit:"))
+            .map(str::to_string)
+    };
+    assert_ne!(commit("aps.lock.yaml"), commit("aps.lock.ci.yaml"));
+
+    // The CI profile picks the ci lockfile on its own
+    aps.cmd(&["sync"])
+        .env("APS_CI_PROFILE", "1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[upgrade available]"));
+
+    // Paths work directly; unknown profiles are rejected
+    aps.ok(&["lock", "--lockfile", "./aps.lock.local.yaml"]);
+    project
+        .child("aps.lock.local.yaml")
+        .assert(predicate::str::contains("agents:"));
+    aps.fails(&["sync", "--lockfile", "staging"])
+        .stderr(predicate::str::contains(
+            "Unknown lockfile profile 'staging'",
+        ));
+}
+
+#[test]
+fn sync_recovers_a_malformed_lockfile() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source.child("AGENTS.md").write_str("# Agents\n").unwrap();
+    source
+        .child("rules/style.mdc")
+        .write_str("# Style\n")
+        .unwrap();
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "alpha",
+            "agents_md",
+            source.path(),
+            "AGENTS.md",
+            "./AGENTS.md",
+        )
+        .filesystem_entry(
+            "beta",
+            "cursor_rules",
+            source.path(),
+            "rules",
+            "./.cursor/rules",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+    aps.ok(&["sync"]);
+
+    // A merge conflict left in the lockfile inside one entry
+    let lockfile = project.child("aps.lock.yaml");
+    let content = std::fs::read_to_string(lockfile.path()).unwrap();
+    lockfile
+        .write_str(&content.replacen(
+            "  beta:\n",
+            "  beta:\n<<<<<<< HEAD\n    dest: ./.cursor/rules\n=======\n    dest: ./elsewhere\n>>>>>>> main\n",
+            1,
+        ))
+        .unwrap();
+    aps.fails(&["status"])
+        .stderr(predicate::str::contains("aps sync"));
+
+    aps.ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains("is malformed"))
+        .stdout(predicate::str::contains("recovered 1 readable entries"))
+        .stdout(predicate::str::contains(
+            "lost their pins and will be resolved fresh: beta",
+        ));
+    let backups: Vec<_> = std::fs::read_dir(project.child(".aps-backups").path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    assert!(backups
+        .iter()
+        .any(|name| name.starts_with("aps.lock.yaml-")));
+    lockfile.assert(predicate::str::contains("<<<<<<<").not());
+    aps.ok(&["status"]);
+}
+
+#[test]
+fn sync_reports_unwritable_destinations_before_installing() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    // Files where the destinations need directories
+    project.child("blocked").write_str("").unwrap();
+    project.child(".cursor").write_str("").unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .filesystem_entry(
+            "docs",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            "blocked/refactor",
+        )
+        .filesystem_entry(
+            "rules",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".cursor/skills/refactor",
+        )
+        .write_to(project.path());
+
+    ApsCmd::new(&project)
+        .fails(&["sync", "--yes"])
+        .stderr(predicate::str::contains("cannot write 2 destination(s)"))
+        .stderr(predicate::str::contains("docs (blocked/refactor)"))
+        .stderr(predicate::str::contains("rules (.cursor/skills/refactor)"));
+    // Nothing was installed, not even the entry that could have been
+    project
+        .child(".claude/skills/refactor")
+        .assert(predicate::path::missing());
+    project
+        .child("aps.lock.yaml")
+        .assert(predicate::path::missing());
+}
+
+#[test]
+fn sync_reports_created_and_updated_destinations() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    let manifest = || {
+        ManifestBuilder::new().filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+    };
+    manifest().write_to(project.path());
+    let aps = ApsCmd::new(&project);
+    aps.ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains("[created]"))
+        .stdout(predicate::str::contains("1 created"));
+
+    manifest()
+        .filesystem_entry(
+            "test-gen",
+            "agent_skill",
+            skills.path(),
+            "skills/test-gen",
+            ".claude/skills/test-gen",
+        )
+        .write_to(project.path());
+    skills
+        .child("skills/refactor/SKILL.md")
+        .write_str("# Refactor\n\nChanged.\n")
+        .unwrap();
+    aps.ok(&["sync", "--yes", "--dry-run"])
+        .stdout(predicate::str::contains("1 would create, 1 would update"));
+    aps.ok(&["sync", "--yes", "--porcelain"]).stdout(
+        "refactor\tupdated\t./.claude/skills/refactor\n\
+         test-gen\tcreated\t./.claude/skills/test-gen\n",
+    );
+    // The lockfile still records how each entry was installed
+    project
+        .child("aps.lock.yaml")
+        .assert(predicate::str::contains("last_status: copied"));
+}
+
+#[test]
+fn add_loose_offers_skills_without_skill_md() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let prompts = temp.child("prompts");
+    prompts
+        .child("review/review.md")
+        .write_str("---\ndescription: Reviews diffs\n---\n# Review\n")
+        .unwrap();
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    let prompts_arg = prompts.path().to_str().unwrap();
+    let aps = ApsCmd::new(&project);
+
+    aps.fails(&["add", prompts_arg, "--all", "--no-sync"])
+        .stderr(predicate::str::contains("No skills found"));
+    // Heuristic matches need an explicit kind
+    aps.fails(&["add", prompts_arg, "--loose", "--all", "--no-sync"])
+        .stderr(predicate::str::contains("--kind"));
+
+    aps.ok(&[
+        "add",
+        prompts_arg,
+        "--loose",
+        "--kind",
+        "agent-skill",
+        "--all",
+        "--no-sync",
+    ])
+    .stdout(predicate::str::contains(
+        "1 found by --loose heuristics, without a SKILL.md",
+    ));
+    project
+        .child("aps.yaml")
+        .assert(predicate::str::contains("id: review"))
+        .assert(predicate::str::contains("path: review"));
+}
+
+#[test]
+fn export_sbom_lists_locked_entries() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let skills = temp.child("skills");
+    create_skills_dir(skills.path());
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    ManifestBuilder::new()
+        .filesystem_entry(
+            "refactor",
+            "agent_skill",
+            skills.path(),
+            "skills/refactor",
+            ".claude/skills/refactor",
+        )
+        .write_to(project.path());
+    let aps = ApsCmd::new(&project);
+    aps.fails(&["export", "sbom"])
+        .stderr(predicate::str::contains("No lockfile found"));
+    aps.ok(&["sync", "--yes"]);
+
+    let output = aps.ok(&["export", "sbom"]).get_output().stdout.clone();
+    let bom: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(bom["bomFormat"], "CycloneDX");
+    assert_eq!(bom["components"][0]["name"], "refactor");
+    assert_eq!(bom["components"][0]["hashes"][0]["alg"], "SHA-256");
+    assert_eq!(
+        bom["components"][0]["properties"][0]["value"],
+        ".claude/skills/refactor"
+    );
+
+    aps.ok(&["export", "sbom", "--format", "json", "-o", "sbom.json"])
+        .stdout(predicate::str::contains("Wrote SBOM of 1 entries"));
+    let inventory: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(project.child("sbom.json").path()).unwrap())
+            .unwrap();
+    assert_eq!(inventory["entries"][0]["id"], "refactor");
+    assert!(inventory["entries"][0]["checksum"]
+        .as_str()
+        .unwrap()
+        .starts_with("sha256:"));
+}
+
+#[test]
+fn composite_output_is_checked_against_compose_limits() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let partials = temp.child("partials");
+    partials.child("intro.md").write_str("# Setup\n").unwrap();
+    partials
+        .child("python.md")
+        .write_str("# Python\n\n## Setup\n\nUse uv.\n")
+        .unwrap();
+    let project = temp.child("project");
+    project.create_dir_all().unwrap();
+    let source = |path: &str| {
+        format!(
+            "      - type: filesystem\n        root: {}\n        path: {}\n",
+            partials.path().display(),
+            path
+        )
+    };
+    let manifest = ManifestBuilder::new()
+        .raw_entry(&format!(
+            "  - id: agents\n    kind: composite_agents_md\n    sources:\n{}{}    dest: ./AGENTS.md\n",
+            source("intro.md"),
+            source("python.md")
+        ))
+        .build();
+    project
+        .child("aps.yaml")
+        .write_str(&format!("{}compose_limits:\n  max_lines: 4\n", manifest))
+        .unwrap();
+    let aps = ApsCmd::new(&project);
+
+    aps.fails(&["sync", "--yes", "--strict"])
+        .stderr(predicate::str::contains("aps::compose::limit_exceeded"));
+    aps.ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains(
+            "over the limit of 4 (compose_limits.max_lines) [W012]",
+        ))
+        .stdout(predicate::str::contains("section 'setup' appears in"));
+    project.child("AGENTS.md").assert(predicate::path::exists());
+
+    // Unchanged output is still reported, by sync and validate
+    aps.ok(&["sync", "--yes"])
+        .stdout(predicate::str::contains("[W012]"));
+    aps.ok(&["validate"]).stdout(predicate::str::contains(
+        "Warning: section 'setup' appears in",
+    ));
+}
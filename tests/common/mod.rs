@@ -0,0 +1,274 @@
+//! Shared fixtures for the APS integration tests.
+//!
+//! Include from a test crate with `mod common;`. Each test crate compiles
+//! this module separately and uses only part of it, hence the `dead_code`
+//! allowance below.
+
+#![allow(dead_code)]
+
+use assert_cmd::assert::Assert;
+use assert_cmd::Command;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Get a Command for the aps binary
+#[allow(deprecated)]
+pub fn aps() -> Command {
+    let mut cmd = Command::cargo_bin("aps").unwrap();
+    // Tests opt into the CI profile explicitly, even when run in CI
+    cmd.env_remove("CI").env_remove("APS_CI_PROFILE");
+    // Ignore the user config of whoever runs the tests
+    cmd.env("APS_CONFIG", "");
+    cmd
+}
+
+/// The aps binary bound to a project directory
+pub struct ApsCmd {
+    dir: PathBuf,
+}
+
+impl ApsCmd {
+    /// Run aps commands in `dir`
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// A command for `aps <args>` in the project directory, for tests that
+    /// need to add environment variables or stdin before running it
+    pub fn cmd(&self, args: &[&str]) -> Command {
+        let mut cmd = aps();
+        cmd.args(args).current_dir(&self.dir);
+        cmd
+    }
+
+    /// Run `aps <args>` in the project directory
+    pub fn run(&self, args: &[&str]) -> Assert {
+        self.cmd(args).assert()
+    }
+
+    /// Run `aps <args>` and assert it succeeds
+    pub fn ok(&self, args: &[&str]) -> Assert {
+        self.run(args).success()
+    }
+
+    /// Run `aps <args>` and assert it fails
+    pub fn fails(&self, args: &[&str]) -> Assert {
+        self.run(args).failure()
+    }
+}
+
+/// Helper to run a git command in a directory
+pub fn git(dir: &Path) -> std::process::Command {
+    let mut cmd = std::process::Command::new("git");
+    cmd.current_dir(dir);
+    cmd
+}
+
+/// Create a local git repo on `main` with `files` (path, content) committed
+pub fn create_git_repo(dir: &Path, files: &[(&str, &str)]) {
+    std::fs::create_dir_all(dir).expect("Failed to create repo directory");
+
+    // Initialize git repo with main as default branch
+    git(dir)
+        .args(["init", "--initial-branch=main"])
+        .output()
+        .expect("Failed to init git repo");
+
+    // Configure git user for commits
+    git(dir)
+        .args(["config", "user.email", "test@test.com"])
+        .output()
+        .expect("Failed to configure git email");
+    git(dir)
+        .args(["config", "user.name", "Test User"])
+        .output()
+        .expect("Failed to configure git name");
+
+    // Disable GPG signing for test commits
+    git(dir)
+        .args(["config", "commit.gpgsign", "false"])
+        .output()
+        .expect("Failed to disable gpg signing");
+
+    commit_files(dir, files, "Initial commit");
+}
+
+/// Write `files` (path, content) into a repo and commit them
+pub fn commit_files(dir: &Path, files: &[(&str, &str)], message: &str) {
+    for (path, content) in files {
+        let path = dir.join(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).expect("Failed to create directory");
+        }
+        std::fs::write(&path, content).expect("Failed to write file");
+    }
+
+    git(dir)
+        .args(["add", "."])
+        .output()
+        .expect("Failed to git add");
+    git(dir)
+        .args(["commit", "--no-gpg-sign", "-m", message])
+        .output()
+        .expect("Failed to git commit");
+}
+
+/// Helper to create a local git repo with an initial commit
+pub fn create_git_repo_with_agents_md(dir: &Path, content: &str) {
+    create_git_repo(dir, &[("AGENTS.md", content)]);
+}
+
+/// Helper to update AGENTS.md and create a new commit
+pub fn update_agents_md_in_repo(dir: &Path, new_content: &str) {
+    commit_files(dir, &[("AGENTS.md", new_content)], "Update AGENTS.md");
+}
+
+/// Skills shared by [`create_skills_repo`] and [`create_skills_dir`]
+const SKILL_FILES: [(&str, &str); 2] = [
+    (
+        "skills/refactor/SKILL.md",
+        "# Refactor\n\nRefactors code automatically.\n",
+    ),
+    (
+        "skills/test-gen/SKILL.md",
+        "# Test Generation\n\nGenerates unit tests.\n",
+    ),
+];
+
+/// Helper to create a local git repo with multiple skills
+pub fn create_skills_repo(dir: &Path) {
+    let mut files = SKILL_FILES.to_vec();
+    files.push((
+        "skills/lint-fix/SKILL.md",
+        "# Lint Fix\n\nFixes linting issues.\n",
+    ));
+    // A non-skill directory (no SKILL.md)
+    files.push(("docs/README.md", "# Documentation\n"));
+    create_git_repo(dir, &files);
+}
+
+/// Helper to create a local skills directory (no git, just files)
+pub fn create_skills_dir(dir: &Path) {
+    for (path, content) in SKILL_FILES
+        .iter()
+        .chain([&("docs/README.md", "# Documentation\n")])
+    {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+}
+
+/// Builds `aps.yaml` content entry by entry
+#[derive(Default)]
+pub struct ManifestBuilder {
+    entries: Vec<String>,
+}
+
+impl ManifestBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry sourced from `path` in a git repo's `main` branch
+    pub fn git_entry(mut self, id: &str, kind: &str, repo: &Path, path: &str, dest: &str) -> Self {
+        self.entries.push(format!(
+            "  - id: {}\n    kind: {}\n    source:\n      type: git\n      repo: {}\n      ref: main\n      path: {}\n    dest: {}\n",
+            id,
+            kind,
+            repo.display(),
+            path,
+            dest
+        ));
+        self
+    }
+
+    /// Add an entry copied from `path` under a local directory
+    pub fn filesystem_entry(
+        mut self,
+        id: &str,
+        kind: &str,
+        root: &Path,
+        path: &str,
+        dest: &str,
+    ) -> Self {
+        self.entries.push(format!(
+            "  - id: {}\n    kind: {}\n    source:\n      type: filesystem\n      root: {}\n      path: {}\n      symlink: false\n    dest: {}\n",
+            id,
+            kind,
+            root.display(),
+            path,
+            dest
+        ));
+        self
+    }
+
+    /// Add an entry written as raw YAML, indented as a list item
+    pub fn raw_entry(mut self, yaml: &str) -> Self {
+        self.entries.push(yaml.to_string());
+        self
+    }
+
+    /// Render the manifest
+    pub fn build(&self) -> String {
+        format!("entries:\n{}", self.entries.concat())
+    }
+
+    /// Write the manifest to `aps.yaml` in `dir`
+    pub fn write_to(&self, dir: &Path) {
+        std::fs::write(dir.join("aps.yaml"), self.build()).expect("Failed to write aps.yaml");
+    }
+}
+
+/// Serve requests for `/AGENTS.vendor.md` on a local port, answering each
+/// with the raw response `respond` builds from the request's header lines
+/// (lowercased). Returns the URL.
+pub fn serve_http_with(respond: impl Fn(&[String]) -> String + Send + 'static) -> String {
+    use std::io::{BufRead, BufReader, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/AGENTS.vendor.md", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut headers = Vec::new();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+                headers.push(line.trim_end().to_ascii_lowercase());
+            }
+            stream.write_all(respond(&headers).as_bytes()).unwrap();
+        }
+    });
+    url
+}
+
+/// Serve `body` over HTTP on a local port with a fixed ETag, answering
+/// conditional requests with `304 Not Modified`. Returns the URL and a log
+/// of the status code sent for each request.
+pub fn serve_http(body: &'static str) -> (String, Arc<Mutex<Vec<u16>>>) {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let served = log.clone();
+    let url = serve_http_with(move |headers| {
+        if headers
+            .iter()
+            .any(|h| h.starts_with("if-none-match: \"v1\""))
+        {
+            served.lock().unwrap().push(304);
+            "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n".to_string()
+        } else {
+            served.lock().unwrap().push(200);
+            format!(
+                "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        }
+    });
+    (url, log)
+}